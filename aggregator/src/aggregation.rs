@@ -2,9 +2,12 @@
 mod circuit;
 /// Config for aggregation circuit
 mod config;
+/// Fixed-width decomposition of the length and index fields
+mod decomposition;
 /// config for RLC circuit
 mod rlc;
 
 pub use circuit::AggregationCircuit;
 pub use config::AggregationConfig;
+pub(crate) use decomposition::{DecompositionChip, FieldWidth};
 pub(crate) use rlc::RlcConfig;
@@ -27,7 +27,7 @@ use snark_verifier::{
 #[cfg(not(feature = "disable_proof_aggregation"))]
 use snark_verifier_sdk::{aggregate, flatten_accumulator};
 use snark_verifier_sdk::{CircuitExt, Snark, SnarkWitness};
-use zkevm_circuits::util::Challenges;
+use zkevm_circuits::util::{digest::DigestByteOrder, Challenges};
 
 use crate::{
     batch::BatchHash,
@@ -259,6 +259,7 @@ impl Circuit<Fr> for AggregationCircuit {
             config
                 .keccak_circuit_config
                 .load_aux_tables(&mut layouter)?;
+            config.rlc_config.load_byte_table(&mut layouter)?;
             end_timer!(timer);
 
             let timer = start_timer!(|| "extract hash");
@@ -289,20 +290,21 @@ impl Circuit<Fr> for AggregationCircuit {
         // digests
         let (batch_pi_hash_digest, chunk_pi_hash_digests, _potential_batch_data_hash_digest) =
             parse_hash_digest_cells(&hash_digest_cells);
+        // the keccak circuit assigns digests word by word, starting from the last word;
+        // reorder them to match the big-endian bytes of the public inputs
+        let batch_pi_hash_digest = DigestByteOrder::KeccakWords.to_be(batch_pi_hash_digest);
+        let chunk_pi_hash_digests = chunk_pi_hash_digests
+            .iter()
+            .map(|digest| DigestByteOrder::KeccakWords.to_be(digest))
+            .collect::<Vec<_>>();
 
         // ==============================================
         // step 3: assert public inputs to the snarks are correct
         // ==============================================
         for (i, chunk) in chunk_pi_hash_digests.iter().enumerate() {
             let hash = self.batch_hash.chunks_with_padding[i].public_input_hash();
-            for j in 0..4 {
-                for k in 0..8 {
-                    log::trace!(
-                        "pi {:02x} {:?}",
-                        hash[j * 8 + k],
-                        chunk[8 * (3 - j) + k].value()
-                    );
-                }
+            for j in 0..DIGEST_LEN {
+                log::trace!("pi {:02x} {:?}", hash[j], chunk[j].value());
             }
         }
 
@@ -321,26 +323,18 @@ impl Circuit<Fr> for AggregationCircuit {
                 }
 
                 for i in 0..MAX_AGG_SNARKS {
-                    for j in 0..4 {
-                        for k in 0..8 {
-                            let mut t1 = Fr::default();
-                            let mut t2 = Fr::default();
-                            chunk_pi_hash_digests[i][j * 8 + k].value().map(|x| t1 = *x);
-                            snark_inputs[i * DIGEST_LEN + (3 - j) * 8 + k]
-                                .value()
-                                .map(|x| t2 = *x);
-                            log::trace!(
-                                "{}-th snark: {:?} {:?}",
-                                i,
-                                chunk_pi_hash_digests[i][j * 8 + k].value(),
-                                snark_inputs[i * DIGEST_LEN + (3 - j) * 8 + k].value()
-                            );
-
-                            region.constrain_equal(
-                                chunk_pi_hash_digests[i][j * 8 + k].cell(),
-                                snark_inputs[i * DIGEST_LEN + (3 - j) * 8 + k].cell(),
-                            )?;
-                        }
+                    for j in 0..DIGEST_LEN {
+                        log::trace!(
+                            "{}-th snark: {:?} {:?}",
+                            i,
+                            chunk_pi_hash_digests[i][j].value(),
+                            snark_inputs[i * DIGEST_LEN + j].value()
+                        );
+
+                        region.constrain_equal(
+                            chunk_pi_hash_digests[i][j].cell(),
+                            snark_inputs[i * DIGEST_LEN + j].cell(),
+                        )?;
                     }
                 }
 
@@ -361,20 +355,14 @@ impl Circuit<Fr> for AggregationCircuit {
         }
 
         // public input hash
-        for i in 0..4 {
-            for j in 0..8 {
-                log::trace!(
-                    "pi (circuit vs real): {:?} {:?}",
-                    batch_pi_hash_digest[i * 8 + j].value(),
-                    self.instances()[0][(3 - i) * 8 + j + ACC_LEN]
-                );
+        for (i, cell) in batch_pi_hash_digest.iter().enumerate() {
+            log::trace!(
+                "pi (circuit vs real): {:?} {:?}",
+                cell.value(),
+                self.instances()[0][i + ACC_LEN]
+            );
 
-                layouter.constrain_instance(
-                    batch_pi_hash_digest[i * 8 + j].cell(),
-                    config.instance,
-                    (3 - i) * 8 + j + ACC_LEN,
-                )?;
-            }
+            layouter.constrain_instance(cell.cell(), config.instance, i + ACC_LEN)?;
         }
 
         log::trace!("number of valid snarks: {:?}", num_valid_snarks.value());
@@ -421,6 +409,7 @@ impl CircuitExt<Fr> for AggregationCircuit {
                 [
                     config.0.rlc_config.selector,
                     config.0.rlc_config.enable_challenge,
+                    config.0.rlc_config.lookup_gate_selector,
                 ]
                 .iter()
                 .cloned(),
@@ -0,0 +1,83 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Region},
+    halo2curves::bn256::Fr,
+    plonk::Error,
+};
+
+use super::RlcConfig;
+
+/// Width of a length or index field of a batch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FieldWidth {
+    U16,
+    U32,
+    U64,
+}
+
+impl FieldWidth {
+    /// Number of bytes of a field of this width
+    pub(crate) fn num_bytes(&self) -> usize {
+        match self {
+            Self::U16 => 2,
+            Self::U32 => 4,
+            Self::U64 => 8,
+        }
+    }
+}
+
+/// Chip constraining the length and index fields of a batch to a fixed
+/// width: a field is decomposed into the little-endian bytes of its width,
+/// which are range checked with lookups into the byte table of the
+/// [`RlcConfig`] and packed back into the field.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DecompositionChip<'a> {
+    rlc_config: &'a RlcConfig,
+}
+
+impl<'a> DecompositionChip<'a> {
+    pub(crate) fn new(rlc_config: &'a RlcConfig) -> Self {
+        Self { rlc_config }
+    }
+
+    /// Constrain `input` to fit in `width` and return its byte cells, in
+    /// little endian.
+    pub(crate) fn range_check(
+        &self,
+        region: &mut Region<Fr>,
+        input: &AssignedCell<Fr, Fr>,
+        width: FieldWidth,
+        offset: &mut usize,
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        let mut input_element = Fr::default();
+        input.value().map(|&x| input_element = x);
+
+        // the bytes of an input which doesn't fit are truncated to the width,
+        // and don't pack back into the input
+        let byte_cells = input_element.to_bytes()[..width.num_bytes()]
+            .iter()
+            .map(|&byte| {
+                self.rlc_config
+                    .load_private(region, &Fr::from(byte as u64), offset)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let acc = self.rlc_config.pack_bytes(region, &byte_cells, offset)?;
+        region.constrain_equal(acc.cell(), input.cell())?;
+
+        Ok(byte_cells)
+    }
+
+    /// Load `value` in a private cell constrained to fit in `width`.
+    pub(crate) fn load_private(
+        &self,
+        region: &mut Region<Fr>,
+        value: u64,
+        width: FieldWidth,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let cell = self
+            .rlc_config
+            .load_private(region, &Fr::from(value), offset)?;
+        self.range_check(region, &cell, width, offset)?;
+        Ok(cell)
+    }
+}
@@ -1,6 +1,6 @@
 use halo2_proofs::{
     halo2curves::bn256::Fr,
-    plonk::{Advice, Column, ConstraintSystem, Fixed, SecondPhase, Selector},
+    plonk::{Advice, Column, ConstraintSystem, Fixed, SecondPhase, Selector, TableColumn},
     poly::Rotation,
 };
 
@@ -19,6 +19,10 @@ pub struct RlcConfig {
     pub(crate) selector: Selector,
     pub(crate) fixed: Column<Fixed>,
     pub(crate) enable_challenge: Selector,
+    /// Enables the range lookup of the phase 2 cell into the byte table
+    pub(crate) lookup_gate_selector: Selector,
+    /// Table of the 256 byte values
+    pub(crate) byte_table: TableColumn,
 }
 
 impl RlcConfig {
@@ -66,6 +70,20 @@ impl RlcConfig {
 
             vec![cs1 + cs2]
         });
+
+        let lookup_gate_selector = meta.complex_selector();
+        let byte_table = meta.lookup_table_column();
+        meta.lookup("byte range check", |meta| {
+            // phase_2_column | lookup_gate_selector
+            // ---------------|---------------------
+            // byte           | q
+            //
+            // constraint: q*byte in byte_table
+            let q = meta.query_selector(lookup_gate_selector);
+            let byte = meta.query_advice(phase_2_column, Rotation::cur());
+            vec![(q * byte, byte_table)]
+        });
+
         Self {
             #[cfg(test)]
             _phase_1_column,
@@ -73,6 +91,8 @@ impl RlcConfig {
             selector,
             fixed,
             enable_challenge,
+            lookup_gate_selector,
+            byte_table,
         }
     }
 }
@@ -1,11 +1,11 @@
 use halo2_proofs::{
-    circuit::{AssignedCell, Cell, Region, RegionIndex, Value},
+    circuit::{AssignedCell, Cell, Layouter, Region, RegionIndex, Value},
     halo2curves::bn256::Fr,
     plonk::Error,
 };
 use zkevm_circuits::util::Challenges;
 
-use crate::{constants::LOG_DEGREE, util::assert_equal};
+use crate::constants::LOG_DEGREE;
 
 use super::RlcConfig;
 
@@ -28,9 +28,39 @@ impl RlcConfig {
             5,
             || Value::known(Fr::from(32)),
         )?;
+        region.assign_fixed(
+            || "const two hundred and fifty six",
+            self.fixed,
+            6,
+            || Value::known(Fr::from(256)),
+        )?;
+        region.assign_fixed(
+            || "const two to the sixteen",
+            self.fixed,
+            7,
+            || Value::known(Fr::from(1 << 16)),
+        )?;
         Ok(())
     }
 
+    /// load the byte table used by the byte range lookups
+    pub(crate) fn load_byte_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "byte table",
+            |mut table| {
+                for byte in 0..256 {
+                    table.assign_cell(
+                        || "byte",
+                        self.byte_table,
+                        byte,
+                        || Value::known(Fr::from(byte as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
     #[inline]
     pub(crate) fn zero_cell(&self, region_index: RegionIndex) -> Cell {
         Cell {
@@ -85,6 +115,24 @@ impl RlcConfig {
         }
     }
 
+    #[inline]
+    pub(crate) fn two_hundred_and_fifty_six_cell(&self, region_index: RegionIndex) -> Cell {
+        Cell {
+            region_index,
+            row_offset: 6,
+            column: self.fixed.into(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn two_to_the_sixteen_cell(&self, region_index: RegionIndex) -> Cell {
+        Cell {
+            region_index,
+            row_offset: 7,
+            column: self.fixed.into(),
+        }
+    }
+
     pub(crate) fn load_private(
         &self,
         region: &mut Region<Fr>,
@@ -141,7 +189,6 @@ impl RlcConfig {
     }
 
     /// Enforce res = a + b
-    #[allow(dead_code)]
     pub(crate) fn add(
         &self,
         region: &mut Region<Fr>,
@@ -337,41 +384,14 @@ impl RlcConfig {
         Ok(())
     }
 
-    // decompose a field element into 254 bits of boolean cells
-    pub(crate) fn decomposition(
+    // pack byte cells, in little endian, into a field element, range checking
+    // each of them with a lookup into the byte table
+    pub(crate) fn pack_bytes(
         &self,
         region: &mut Region<Fr>,
-        input: &AssignedCell<Fr, Fr>,
+        bytes: &[AssignedCell<Fr, Fr>],
         offset: &mut usize,
-    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
-        let mut input_element = Fr::default();
-        input.value().map(|&x| input_element = x);
-
-        let bits = input_element
-            .to_bytes()
-            .iter()
-            .flat_map(byte_to_bits_le)
-            .collect::<Vec<_>>();
-        // sanity check
-        {
-            let mut reconstructed = Fr::zero();
-            bits.iter().rev().for_each(|bit| {
-                reconstructed *= Fr::from(2);
-                reconstructed += Fr::from(*bit as u64);
-            });
-            assert_eq!(reconstructed, input_element);
-        }
-
-        let bit_cells = bits
-            .iter()
-            .take(254) // hard coded for BN curve
-            .map(|&bit| {
-                let cell = self.load_private(region, &Fr::from(bit as u64), offset)?;
-                self.enforce_binary(region, &cell, offset)?;
-                Ok(cell)
-            })
-            .collect::<Result<Vec<_>, Error>>()?;
-
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
         let mut acc = {
             let zero = self.load_private(region, &Fr::from(0), offset)?;
             let zero_cell = self.zero_cell(zero.cell().region_index);
@@ -379,27 +399,24 @@ impl RlcConfig {
             zero
         };
 
-        let two = {
-            let two = self.load_private(region, &Fr::from(2), offset)?;
-            let two_cell = self.two_cell(two.cell().region_index);
-            region.constrain_equal(two_cell, two.cell())?;
-            two
+        let two_hundred_and_fifty_six = {
+            let cell = self.load_private(region, &Fr::from(256), offset)?;
+            let fixed_cell = self.two_hundred_and_fifty_six_cell(cell.cell().region_index);
+            region.constrain_equal(fixed_cell, cell.cell())?;
+            cell
         };
 
-        for bit in bit_cells.iter().rev() {
-            acc = self.mul_add(region, &acc, &two, bit, offset)?;
+        for byte in bytes.iter().rev() {
+            // the byte is copied to the third row of the mul_add gate
+            self.lookup_gate_selector.enable(region, *offset + 2)?;
+            acc = self.mul_add(region, &acc, &two_hundred_and_fifty_six, byte, offset)?;
         }
 
-        // sanity check
-        assert_equal(&acc, input);
-
-        region.constrain_equal(acc.cell(), input.cell())?;
-
-        Ok(bit_cells)
+        Ok(acc)
     }
 
     // return a boolean if a is smaller than b
-    // requires that both a and b are smallish
+    // requires that both a and b fit in 16 bits
     pub(crate) fn is_smaller_than(
         &self,
         region: &mut Region<Fr>,
@@ -407,21 +424,29 @@ impl RlcConfig {
         b: &AssignedCell<Fr, Fr>,
         offset: &mut usize,
     ) -> Result<AssignedCell<Fr, Fr>, Error> {
-        // when a and b are both small (as in our use case)
-        // if a < b, (a-b) will under flow and the highest bit of (a-b) be one
-        // else,  the highest bit of (a-b) be zero
-        let sub = self.sub(region, a, b, offset)?;
-        let bits = self.decomposition(region, &sub, offset)?;
-        Ok(bits[253].clone())
-    }
-}
-#[inline]
-fn byte_to_bits_le(byte: &u8) -> Vec<u8> {
-    let mut res = vec![];
-    let mut t = *byte;
-    for _ in 0..8 {
-        res.push(t & 1);
-        t >>= 1;
+        // when a and b both fit in 16 bits, a + 2^16 - b fits in 17 bits,
+        // and its 17th bit is one iff a >= b
+        let two_to_the_sixteen = {
+            let cell = self.load_private(region, &Fr::from(1 << 16), offset)?;
+            let fixed_cell = self.two_to_the_sixteen_cell(cell.cell().region_index);
+            region.constrain_equal(fixed_cell, cell.cell())?;
+            cell
+        };
+        let shifted = self.add(region, a, &two_to_the_sixteen, offset)?;
+        let diff = self.sub(region, &shifted, b, offset)?;
+
+        let mut diff_element = Fr::default();
+        diff.value().map(|&x| diff_element = x);
+
+        // the 3 bytes of diff, in little endian, with the top one binary
+        let byte_cells = diff_element.to_bytes()[..3]
+            .iter()
+            .map(|&byte| self.load_private(region, &Fr::from(byte as u64), offset))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let acc = self.pack_bytes(region, &byte_cells, offset)?;
+        region.constrain_equal(acc.cell(), diff.cell())?;
+        self.enforce_binary(region, &byte_cells[2], offset)?;
+
+        self.not(region, &byte_cells[2], offset)
     }
-    res
 }
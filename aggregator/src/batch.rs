@@ -14,7 +14,7 @@ use super::chunk::ChunkHash;
 /// - the last (#MAX_AGG_SNARKS-k) chunks are from empty traces
 /// A BatchHash consists of 2 hashes.
 /// - batch_pi_hash   := keccak(chain_id || chunk_0.prev_state_root || chunk_k-1.post_state_root ||
-///   chunk_k-1.withdraw_root || batch_data_hash)
+///   chunk_k-1.withdraw_root || batch_data_hash || chunk_0.prev_withdraw_root)
 /// - batch_data_hash := keccak(chunk_0.data_hash || ... || chunk_k-1.data_hash)
 pub struct BatchHash {
     pub(crate) chain_id: u64,
@@ -66,6 +66,10 @@ impl BatchHash {
                 chunks_with_padding[i].post_state_root,
                 chunks_with_padding[i + 1].prev_state_root,
             );
+            assert_eq!(
+                chunks_with_padding[i].withdraw_root,
+                chunks_with_padding[i + 1].prev_withdraw_root,
+            );
             assert_eq!(
                 chunks_with_padding[i].chain_id,
                 chunks_with_padding[i + 1].chain_id,
@@ -99,7 +103,8 @@ impl BatchHash {
         //      chunk[0].prev_state_root ||
         //      chunk[k-1].post_state_root ||
         //      chunk[k-1].withdraw_root ||
-        //      batch_data_hash )
+        //      batch_data_hash ||
+        //      chunk[0].prev_withdraw_root )
         let preimage = [
             chunks_with_padding[0].chain_id.to_be_bytes().as_ref(),
             chunks_with_padding[0].prev_state_root.as_bytes(),
@@ -110,6 +115,7 @@ impl BatchHash {
                 .withdraw_root
                 .as_bytes(),
             data_hash.as_slice(),
+            chunks_with_padding[0].prev_withdraw_root.as_bytes(),
         ]
         .concat();
         let public_input_hash = keccak256(preimage);
@@ -139,7 +145,8 @@ impl BatchHash {
         //      chunk[0].prev_state_root ||
         //      chunk[k-1].post_state_root ||
         //      chunk[k-1].withdraw_root ||
-        //      batch_data_hash )
+        //      batch_data_hash ||
+        //      chunk[0].prev_withdraw_root )
         let batch_public_input_hash_preimage = [
             self.chain_id.to_be_bytes().as_ref(),
             self.chunks_with_padding[0].prev_state_root.as_bytes(),
@@ -150,6 +157,7 @@ impl BatchHash {
                 .withdraw_root
                 .as_bytes(),
             self.data_hash.as_bytes(),
+            self.chunks_with_padding[0].prev_withdraw_root.as_bytes(),
         ]
         .concat();
         res.push(batch_public_input_hash_preimage);
@@ -159,7 +167,7 @@ impl BatchHash {
         // keccak(
         //        chain id ||
         //        chunk[i].prevStateRoot || chunk[i].postStateRoot || chunk[i].withdrawRoot ||
-        //        chunk[i].datahash)
+        //        chunk[i].datahash || chunk[i].prevWithdrawRoot)
         for chunk in self.chunks_with_padding.iter() {
             let chunk_public_input_hash_preimage = [
                 self.chain_id.to_be_bytes().as_ref(),
@@ -167,6 +175,7 @@ impl BatchHash {
                 chunk.post_state_root.as_bytes(),
                 chunk.withdraw_root.as_bytes(),
                 chunk.data_hash.as_bytes(),
+                chunk.prev_withdraw_root.as_bytes(),
             ]
             .concat();
             res.push(chunk_public_input_hash_preimage)
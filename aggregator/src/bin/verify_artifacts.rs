@@ -0,0 +1,48 @@
+//! Verify a batch proof envelope offline.
+//!
+//! Usage: `verify-artifacts <params> <vk> <envelope.json>`
+//!
+//! - `params`: KZG parameters of the aggregation circuit's degree
+//! - `vk`: verifying key of the aggregation circuit, in `SerdeFormat::RawBytes`
+//! - `envelope.json`: a serialized `BatchProofEnvelope`
+
+use std::{fs::File, io::BufReader, path::Path, process};
+
+use aggregator::{verify_artifacts, AggregationCircuit, BatchProofEnvelope};
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, G1Affine},
+    plonk::VerifyingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+
+fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 4 {
+        eprintln!("usage: {} <params> <vk> <envelope.json>", args[0]);
+        process::exit(2);
+    }
+
+    let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(
+        File::open(&args[1]).unwrap_or_else(|_| panic!("{} does not exist", args[1])),
+    ))
+    .expect("read params");
+    let vk = VerifyingKey::<G1Affine>::read::<_, AggregationCircuit>(
+        &mut BufReader::new(
+            File::open(&args[2]).unwrap_or_else(|_| panic!("{} does not exist", args[2])),
+        ),
+        SerdeFormat::RawBytes,
+    )
+    .expect("read vk");
+    let envelope = BatchProofEnvelope::load(Path::new(&args[3])).expect("read envelope");
+
+    match verify_artifacts(&params, &vk, &envelope) {
+        Ok(()) => println!("proof and public inputs are valid"),
+        Err(e) => {
+            eprintln!("verification failed: {e}");
+            process::exit(1);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 //! This module implements `Chunk` related data types.
 //! A chunk is a list of blocks.
+use bus_mapping::circuit_input_builder::TX_DA_LENGTH_PREFIX_BYTES;
 use eth_types::{ToBigEndian, H256};
 use ethers_core::utils::keccak256;
 use halo2_proofs::halo2curves::bn256::Fr;
@@ -9,11 +10,12 @@ use zkevm_circuits::witness::Block;
 
 #[derive(Default, Debug, Clone, Copy, Deserialize, Serialize)]
 /// A chunk is a set of continuous blocks.
-/// A ChunkHash consists of 4 hashes, representing the changes incurred by this chunk of blocks:
+/// A ChunkHash consists of 5 hashes, representing the changes incurred by this chunk of blocks:
 /// - state root before this chunk
 /// - state root after this chunk
 /// - the withdraw root after this chunk
 /// - the data hash of this chunk
+/// - the withdraw root before this chunk
 /// - if the chunk is padded (en empty but valid chunk that is padded for aggregation)
 pub struct ChunkHash {
     /// Chain identifier
@@ -26,22 +28,24 @@ pub struct ChunkHash {
     pub(crate) withdraw_root: H256,
     /// the data hash of this chunk
     pub(crate) data_hash: H256,
+    /// the withdraw root before this chunk
+    #[serde(default)]
+    pub(crate) prev_withdraw_root: H256,
     /// if the chunk is a padded chunk
     pub(crate) is_padding: bool,
 }
 
 impl ChunkHash {
-    /// Construct by a witness block.
-    pub fn from_witness_block(block: &Block<Fr>, is_padding: bool) -> Self {
+    /// The preimage of the data hash of a witness block:
+    /// the context of each block, followed by the hashes of all txs but the
+    /// system calls.
+    pub(crate) fn data_bytes(block: &Block<Fr>) -> Vec<u8> {
         // <https://github.com/scroll-tech/zkevm-circuits/blob/25dd32aa316ec842ffe79bb8efe9f05f86edc33e/bus-mapping/src/circuit_input_builder.rs#L690>
+        let data_txs = || block.txs.iter().filter(|tx| !tx.tx_type.is_system_call());
 
-        let data_bytes = iter::empty()
+        iter::empty()
             .chain(block.context.ctxs.iter().flat_map(|(b_num, b_ctx)| {
-                let num_txs = block
-                    .txs
-                    .iter()
-                    .filter(|tx| tx.block_number == *b_num)
-                    .count() as u16;
+                let num_txs = data_txs().filter(|tx| tx.block_number == *b_num).count() as u16;
 
                 iter::empty()
                     // Block Values
@@ -52,10 +56,13 @@ impl ChunkHash {
                     .chain(num_txs.to_be_bytes())
             }))
             // Tx Hashes
-            .chain(block.txs.iter().flat_map(|tx| tx.hash.to_fixed_bytes()))
-            .collect::<Vec<u8>>();
+            .chain(data_txs().flat_map(|tx| tx.hash.to_fixed_bytes()))
+            .collect::<Vec<u8>>()
+    }
 
-        let data_hash = H256(keccak256(data_bytes));
+    /// Construct by a witness block.
+    pub fn from_witness_block(block: &Block<Fr>, is_padding: bool) -> Self {
+        let data_hash = H256(keccak256(Self::data_bytes(block)));
 
         let post_state_root = block
             .context
@@ -70,6 +77,7 @@ impl ChunkHash {
             post_state_root,
             withdraw_root: H256(block.withdraw_root.to_be_bytes()),
             data_hash,
+            prev_withdraw_root: H256(block.prev_withdraw_root.to_be_bytes()),
             is_padding,
         }
     }
@@ -85,12 +93,15 @@ impl ChunkHash {
         r.fill_bytes(&mut withdraw_root);
         let mut data_hash = [0u8; 32];
         r.fill_bytes(&mut data_hash);
+        let mut prev_withdraw_root = [0u8; 32];
+        r.fill_bytes(&mut prev_withdraw_root);
         Self {
             chain_id: 0,
             prev_state_root: prev_state_root.into(),
             post_state_root: post_state_root.into(),
             withdraw_root: withdraw_root.into(),
             data_hash: data_hash.into(),
+            prev_withdraw_root: prev_withdraw_root.into(),
             is_padding: false,
         }
     }
@@ -108,19 +119,22 @@ impl ChunkHash {
             post_state_root: previous_chunk.post_state_root,
             withdraw_root: previous_chunk.withdraw_root,
             data_hash: keccak256([]).into(),
+            prev_withdraw_root: previous_chunk.withdraw_root,
             is_padding: true,
         }
     }
 
     /// Public input hash for a given chunk is defined as
-    ///  keccak( chain id || prev state root || post state root || withdraw root || data hash )
+    ///  keccak( chain id || prev state root || post state root || withdraw root || data hash ||
+    ///  prev withdraw root )
     pub fn public_input_hash(&self) -> H256 {
         let preimage = self.extract_hash_preimage();
         keccak256::<&[u8]>(preimage.as_ref()).into()
     }
 
     /// Extract the preimage for the hash
-    ///  chain id || prev state root || post state root || withdraw root || data hash
+    ///  chain id || prev state root || post state root || withdraw root || data hash ||
+    ///  prev withdraw root
     pub fn extract_hash_preimage(&self) -> Vec<u8> {
         [
             self.chain_id.to_be_bytes().as_ref(),
@@ -128,7 +142,87 @@ impl ChunkHash {
             self.post_state_root.as_bytes(),
             self.withdraw_root.as_bytes(),
             self.data_hash.as_bytes(),
+            self.prev_withdraw_root.as_bytes(),
         ]
         .concat()
     }
 }
+
+/// The DA payload of a chunk: the rlped L2 txs of its blocks, each prefixed
+/// by its length as a big-endian u32, in the order their hashes are
+/// committed to by the [`ChunkHash`] data hash. L1 msgs are already on L1
+/// and are left out.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkPayload {
+    bytes: Vec<u8>,
+    num_txs: usize,
+}
+
+impl ChunkPayload {
+    /// The payload bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consume the payload into its bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Number of L2 txs in the payload.
+    pub fn num_txs(&self) -> usize {
+        self.num_txs
+    }
+
+    /// The rlped L2 txs of the payload, in order.
+    pub fn tx_rlps(&self) -> Vec<&[u8]> {
+        let mut tx_rlps = Vec::with_capacity(self.num_txs);
+        let mut rest = self.bytes.as_slice();
+        while !rest.is_empty() {
+            let (len, tail) = rest.split_at(TX_DA_LENGTH_PREFIX_BYTES);
+            let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+            let (tx_rlp, tail) = tail.split_at(len);
+            tx_rlps.push(tx_rlp);
+            rest = tail;
+        }
+        tx_rlps
+    }
+}
+
+/// Reasons for the DA payload of a chunk not to be built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkPayloadError {
+    /// The rlp of the L2 tx `tx_id` does not hash to the tx hash committed to
+    /// by the chunk, so the payload would not match the chunk's data hash.
+    HashMismatch {
+        tx_id: usize,
+        expected: H256,
+        found: H256,
+    },
+    /// The rlp of the L2 tx `tx_id` is too long for its length prefix.
+    RlpTooLong { tx_id: usize, len: usize },
+}
+
+/// Build the DA payload of the chunk proven by the witness `block`.
+pub fn chunk_payload(block: &Block<Fr>) -> Result<ChunkPayload, ChunkPayloadError> {
+    let mut payload = ChunkPayload::default();
+    for tx in block.txs.iter().filter(|tx| !tx.tx_type.is_unsigned()) {
+        let found = H256(keccak256(&tx.rlp_signed));
+        if found != tx.hash {
+            return Err(ChunkPayloadError::HashMismatch {
+                tx_id: tx.id,
+                expected: tx.hash,
+                found,
+            });
+        }
+        let len =
+            u32::try_from(tx.rlp_signed.len()).map_err(|_| ChunkPayloadError::RlpTooLong {
+                tx_id: tx.id,
+                len: tx.rlp_signed.len(),
+            })?;
+        payload.bytes.extend_from_slice(&len.to_be_bytes());
+        payload.bytes.extend_from_slice(&tx.rlp_signed);
+        payload.num_txs += 1;
+    }
+    Ok(payload)
+}
@@ -9,6 +9,9 @@ mod circuit;
 mod circuit_ext;
 /// Config for compression circuit
 mod config;
+/// Configurable stack of compression layers
+mod pipeline;
 
 pub use circuit::CompressionCircuit;
 pub use config::CompressionConfig;
+pub use pipeline::CompressionPipeline;
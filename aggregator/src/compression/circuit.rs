@@ -33,7 +33,7 @@ use snark_verifier_sdk::{aggregate, flatten_accumulator, types::Svk, Snark, Snar
 
 use crate::{core::extract_accumulators_and_proof, param::ConfigParams, ACC_LEN, BITS, LIMBS};
 
-use super::config::CompressionConfig;
+use super::{config::CompressionConfig, pipeline::COMPRESSION_CONFIG_ENV};
 
 /// Input a proof, this compression circuit generates a new proof that may have smaller size.
 ///
@@ -78,7 +78,7 @@ impl Circuit<Fr> for CompressionCircuit {
     fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
         // Too bad that configure function doesn't take additional input
         // it would be nicer to load parameters from API rather than ENV
-        let path = std::env::var("COMPRESSION_CONFIG")
+        let path = std::env::var(COMPRESSION_CONFIG_ENV)
             .unwrap_or_else(|_| "configs/compression_wide.config".to_owned());
         let params: ConfigParams = serde_json::from_reader(
             File::open(path.as_str()).unwrap_or_else(|_| panic!("{path:?} does not exist")),
@@ -0,0 +1,165 @@
+//! A configurable stack of compression circuits.
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use ark_std::{end_timer, start_timer};
+use halo2_proofs::{
+    halo2curves::bn256::Bn256,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::Rng;
+use snark_verifier_sdk::{gen_pk, gen_snark_shplonk, Snark};
+
+use crate::param::ConfigParams;
+
+use super::circuit::CompressionCircuit;
+
+/// Environment variable read by [`CompressionCircuit`] during configuration.
+pub(crate) const COMPRESSION_CONFIG_ENV: &str = "COMPRESSION_CONFIG";
+
+/// A pipeline of compression layers.
+///
+/// Each layer takes the snark of the previous layer (or the input snark for
+/// the first layer) and compresses it with a [`CompressionCircuit`] configured
+/// by the layer's [`ConfigParams`]. Wide layers (small degree, many advice
+/// columns) are fast to prove; thin layers (large degree, few advice columns)
+/// are cheap to verify on L1.
+///
+/// ```ignore
+/// let pipeline = CompressionPipeline::new().add_layer(22).add_layer(25);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompressionPipeline {
+    layers: Vec<ConfigParams>,
+    config_dir: PathBuf,
+}
+
+impl Default for CompressionPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompressionPipeline {
+    /// Build an empty pipeline.
+    /// Layer configs are written into `./configs` by default.
+    pub fn new() -> Self {
+        Self {
+            layers: vec![],
+            config_dir: PathBuf::from("configs"),
+        }
+    }
+
+    /// Append a layer of the given degree. The number of advice columns is
+    /// derived from the degree, see [`ConfigParams::compress_param`].
+    pub fn add_layer(self, degree: u32) -> Self {
+        self.add_layer_with_params(ConfigParams::compress_param(degree))
+    }
+
+    /// Append a layer with fully specified parameters.
+    pub fn add_layer_with_params(mut self, params: ConfigParams) -> Self {
+        self.layers.push(params);
+        self
+    }
+
+    /// Set the directory where the per-layer config files are written.
+    pub fn with_config_dir(mut self, config_dir: impl AsRef<Path>) -> Self {
+        self.config_dir = config_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// The default two-layer pipeline: a wide layer followed by a thin one.
+    pub fn wide_thin() -> Self {
+        Self::new()
+            .add_layer_with_params(ConfigParams::default_compress_wide_param())
+            .add_layer_with_params(ConfigParams::_compress_thin_param())
+    }
+
+    /// Parameters of all layers, in proving order.
+    pub fn layers(&self) -> &[ConfigParams] {
+        &self.layers
+    }
+
+    /// Number of layers.
+    pub fn num_layers(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Largest degree among all layers; the SRS needs at least this degree.
+    pub fn max_degree(&self) -> Option<u32> {
+        self.layers.iter().map(|layer| layer.degree).max()
+    }
+
+    /// Path to the config file of the `index`-th layer.
+    pub fn layer_config_path(&self, index: usize) -> PathBuf {
+        self.config_dir
+            .join(format!("compression_layer_{index}.config"))
+    }
+
+    /// Write the config of the `index`-th layer to disk and point
+    /// `COMPRESSION_CONFIG` to it, so that the next [`CompressionCircuit`]
+    /// configuration picks it up.
+    pub fn activate_layer(&self, index: usize) {
+        let path = self.layer_config_path(index);
+        fs::create_dir_all(&self.config_dir)
+            .unwrap_or_else(|_| panic!("cannot create {:?}", self.config_dir));
+        serde_json::to_writer(
+            File::create(&path).unwrap_or_else(|_| panic!("cannot create {path:?}")),
+            &self.layers[index],
+        )
+        .unwrap_or_else(|_| panic!("cannot write {path:?}"));
+        std::env::set_var(COMPRESSION_CONFIG_ENV, path);
+    }
+
+    /// Run all layers on `snark` and return the snark of the last layer.
+    ///
+    /// `has_accumulator` indicates whether the input snark already carries an
+    /// accumulator; the snarks produced by the pipeline always do.
+    pub fn prove(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        snark: Snark,
+        has_accumulator: bool,
+        rng: &mut (impl Rng + Send),
+    ) -> Result<Snark, snark_verifier::Error> {
+        let max_degree = self.max_degree().ok_or_else(|| {
+            snark_verifier::Error::AssertionFailure("empty compression pipeline".to_owned())
+        })?;
+        if params.k() < max_degree {
+            return Err(snark_verifier::Error::AssertionFailure(format!(
+                "params of degree {} are too small for compression layer of degree {}",
+                params.k(),
+                max_degree
+            )));
+        }
+
+        let mut snark = snark;
+        let mut has_accumulator = has_accumulator;
+        for (index, layer) in self.layers.iter().enumerate() {
+            let timer = start_timer!(|| format!("compression layer {index}"));
+            self.activate_layer(index);
+
+            let param = {
+                let mut param = params.clone();
+                param.downsize(layer.degree);
+                param
+            };
+            let circuit = CompressionCircuit::new(&param, snark, has_accumulator, &mut *rng)?;
+            let pk = gen_pk(&param, &circuit, None);
+            snark = gen_snark_shplonk(&param, &pk, circuit, rng, None::<String>);
+            has_accumulator = true;
+
+            log::trace!(
+                "compression layer {} (k = {}) proof size: {}",
+                index,
+                layer.degree,
+                snark.proof.len()
+            );
+            end_timer!(timer);
+        }
+        Ok(snark)
+    }
+}
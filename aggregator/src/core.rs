@@ -21,7 +21,7 @@ use snark_verifier_sdk::{
 use zkevm_circuits::{
     keccak_circuit::{keccak_packed_multi::multi_keccak, KeccakCircuitConfig},
     table::LookupTable,
-    util::Challenges,
+    util::{digest::DigestByteOrder, Challenges},
 };
 
 use crate::{
@@ -33,8 +33,8 @@ use crate::{
         assert_conditional_equal, assert_equal, assert_exist, get_indices, keccak_round_capacity,
         parse_hash_digest_cells, parse_hash_preimage_cells,
     },
-    AggregationConfig, RlcConfig, CHUNK_DATA_HASH_INDEX, POST_STATE_ROOT_INDEX,
-    PREV_STATE_ROOT_INDEX, WITHDRAW_ROOT_INDEX,
+    AggregationConfig, DecompositionChip, FieldWidth, RlcConfig, CHUNK_DATA_HASH_INDEX,
+    POST_STATE_ROOT_INDEX, PREV_STATE_ROOT_INDEX, PREV_WITHDRAW_ROOT_INDEX, WITHDRAW_ROOT_INDEX,
 };
 
 /// Subroutine for the witness generations.
@@ -96,10 +96,12 @@ pub(crate) fn extract_accumulators_and_proof(
 // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
 // 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
 // 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
+// 2.4. batch_pi_hash and chunk[0] use a same prev_withdraw_root
 // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not padded
-// 4. chunks are continuous: they are linked via the state roots
+// 4. chunks are continuous: they are linked via the state roots and the withdraw roots
 // 5. batch and all its chunks use a same chain id
-// 6. chunk[i]'s prev_state_root == post_state_root when chunk[i] is padded
+// 6. chunk[i]'s prev_state_root == post_state_root and prev_withdraw_root == withdraw_root
+// when chunk[i] is padded
 // 7. chunk[i]'s data_hash == "" when chunk[i] is padded
 #[allow(clippy::type_complexity)]
 pub(crate) fn assign_batch_hashes(
@@ -120,13 +122,15 @@ pub(crate) fn assign_batch_hashes(
     // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
     // 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
     // 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
-    // 4. chunks are continuous: they are linked via the state roots
+    // 2.4. batch_pi_hash and chunk[0] use a same prev_withdraw_root
+    // 4. chunks are continuous: they are linked via the state roots and the withdraw roots
     // 5. batch and all its chunks use a same chain id
     copy_constraints(layouter, &hash_input_cells)?;
     // 1. batch_data_hash digest is reused for public input hash
     // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not
     // padded
-    // 6. chunk[i]'s prev_state_root == post_state_root when chunk[i] is padded
+    // 6. chunk[i]'s prev_state_root == post_state_root and prev_withdraw_root == withdraw_root
+    // when chunk[i] is padded
     // 7. chunk[i]'s data_hash == "" when chunk[i] is padded
     let num_valid_snarks = conditional_constraints(
         &config.rlc_config,
@@ -279,7 +283,8 @@ pub(crate) fn extract_hash_cells(
 // 2.1. batch_pi_hash and chunk[0] use a same prev_state_root
 // 2.2. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same post_state_root
 // 2.3. batch_pi_hash and chunk[MAX_AGG_SNARKS-1] use a same withdraw_root
-// 4. chunks are continuous: they are linked via the state roots
+// 2.4. batch_pi_hash and chunk[0] use a same prev_withdraw_root
+// 4. chunks are continuous: they are linked via the state roots and the withdraw roots
 // 5. batch and all its chunks use a same chain id
 fn copy_constraints(
     layouter: &mut impl Layouter<Fr>,
@@ -320,7 +325,8 @@ fn copy_constraints(
                 //      chunk[0].prev_state_root ||
                 //      chunk[k-1].post_state_root ||
                 //      chunk[k-1].withdraw_root ||
-                //      batchData_hash )
+                //      batchData_hash ||
+                //      chunk[0].prev_withdraw_root )
                 //
                 // chunk[i].piHash =
                 //   keccak(
@@ -328,11 +334,12 @@ fn copy_constraints(
                 //        chunk[i].prevStateRoot ||
                 //        chunk[i].postStateRoot ||
                 //        chunk[i].withdrawRoot  ||
-                //        chunk[i].datahash)
+                //        chunk[i].datahash ||
+                //        chunk[i].prevWithdrawRoot)
                 //
-                // PREV_STATE_ROOT_INDEX, POST_STATE_ROOT_INDEX, WITHDRAW_ROOT_INDEX
-                // used below are byte positions for
-                // prev_state_root, post_state_root, withdraw_root
+                // PREV_STATE_ROOT_INDEX, POST_STATE_ROOT_INDEX, WITHDRAW_ROOT_INDEX,
+                // PREV_WITHDRAW_ROOT_INDEX used below are byte positions for
+                // prev_state_root, post_state_root, withdraw_root, prev_withdraw_root
                 for i in 0..DIGEST_LEN {
                     // 2.1 chunk[0].prev_state_root
                     // sanity check
@@ -364,9 +371,19 @@ fn copy_constraints(
                         batch_pi_hash_preimage[i + WITHDRAW_ROOT_INDEX].cell(),
                         chunk_pi_hash_preimages[MAX_AGG_SNARKS - 1][i + WITHDRAW_ROOT_INDEX].cell(),
                     )?;
+                    // 2.4 chunk[0].prev_withdraw_root
+                    assert_equal(
+                        &batch_pi_hash_preimage[i + PREV_WITHDRAW_ROOT_INDEX],
+                        &chunk_pi_hash_preimages[0][i + PREV_WITHDRAW_ROOT_INDEX],
+                    );
+                    region.constrain_equal(
+                        batch_pi_hash_preimage[i + PREV_WITHDRAW_ROOT_INDEX].cell(),
+                        chunk_pi_hash_preimages[0][i + PREV_WITHDRAW_ROOT_INDEX].cell(),
+                    )?;
                 }
 
-                // 4  chunks are continuous: they are linked via the state roots
+                // 4  chunks are continuous: they are linked via the state roots and the
+                // withdraw roots
                 for i in 0..MAX_AGG_SNARKS - 1 {
                     for j in 0..DIGEST_LEN {
                         // sanity check
@@ -378,6 +395,15 @@ fn copy_constraints(
                             chunk_pi_hash_preimages[i + 1][PREV_STATE_ROOT_INDEX + j].cell(),
                             chunk_pi_hash_preimages[i][POST_STATE_ROOT_INDEX + j].cell(),
                         )?;
+                        // sanity check
+                        assert_equal(
+                            &chunk_pi_hash_preimages[i + 1][PREV_WITHDRAW_ROOT_INDEX + j],
+                            &chunk_pi_hash_preimages[i][WITHDRAW_ROOT_INDEX + j],
+                        );
+                        region.constrain_equal(
+                            chunk_pi_hash_preimages[i + 1][PREV_WITHDRAW_ROOT_INDEX + j].cell(),
+                            chunk_pi_hash_preimages[i][WITHDRAW_ROOT_INDEX + j].cell(),
+                        )?;
                     }
                 }
 
@@ -404,7 +430,8 @@ fn copy_constraints(
 // This function asserts the following constraints on the hashes
 // 1. batch_data_hash digest is reused for public input hash
 // 3. batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when chunk[i] is not padded
-// 6. chunk[i]'s prev_state_root == post_state_root when chunk[i] is padded
+// 6. chunk[i]'s prev_state_root == post_state_root and prev_withdraw_root == withdraw_root
+// when chunk[i] is padded
 // 7. chunk[i]'s data_hash == "" when chunk[i] is padded
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn conditional_constraints(
@@ -430,13 +457,16 @@ pub(crate) fn conditional_constraints(
 
                 rlc_config.init(&mut region)?;
                 let mut offset = 0;
+                let decomposition_chip = DecompositionChip::new(rlc_config);
 
                 // ====================================================
                 // build the flags to indicate the chunks are empty or not
                 // ====================================================
-                let num_of_valid_snarks_cell = vec![rlc_config.load_private(
+                // range checked, as `is_smaller_than` requires small inputs
+                let num_of_valid_snarks_cell = vec![decomposition_chip.load_private(
                     &mut region,
-                    &Fr::from(num_of_valid_chunks as u64),
+                    num_of_valid_chunks as u64,
+                    FieldWidth::U16,
                     &mut offset,
                 )?];
                 let chunk_is_valid_cells = chunk_is_valid(
@@ -505,6 +535,38 @@ pub(crate) fn conditional_constraints(
                     _chunk_pi_hash_digests,
                     potential_batch_data_hash_digest,
                 ) = parse_hash_digest_cells(hash_output_cells);
+                // the three candidates for batch_data_hash, in big-endian byte order
+                let potential_batch_data_hash_digest = potential_batch_data_hash_digest
+                    .chunks(DIGEST_LEN)
+                    .map(|digest| DigestByteOrder::KeccakWords.to_be(digest))
+                    .collect::<Vec<_>>();
+                // ====================================================
+                // range check the header fields
+                // ====================================================
+                //
+                // the chain id, in big-endian byte order, is a u64; the chain ids
+                // of the chunks are copied from it
+                let chain_id_bytes = batch_pi_hash_preimage[..CHAIN_ID_LEN]
+                    .iter()
+                    .rev()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let chain_id = rlc_config.pack_bytes(&mut region, &chain_id_bytes, &mut offset)?;
+                decomposition_chip.range_check(
+                    &mut region,
+                    &chain_id,
+                    FieldWidth::U64,
+                    &mut offset,
+                )?;
+                // the candidate lengths of the batch_data_hash preimage are u32
+                for len in &hash_input_len_cells[MAX_AGG_SNARKS * 2 + 3..MAX_AGG_SNARKS * 2 + 6] {
+                    decomposition_chip.range_check(
+                        &mut region,
+                        len,
+                        FieldWidth::U32,
+                        &mut offset,
+                    )?;
+                }
                 // ====================================================
                 // start the actual statements
                 // ====================================================
@@ -517,53 +579,52 @@ pub(crate) fn conditional_constraints(
                 //      chunk[0].prev_state_root ||
                 //      chunk[k-1].post_state_root ||
                 //      chunk[k-1].withdraw_root ||
-                //      batch_data_hash )
+                //      batch_data_hash ||
+                //      chunk[0].prev_withdraw_root )
                 //
                 // #valid snarks | offset of data hash | flags
                 // 1,2,3,4       | 0                   | 1, 0, 0
                 // 5,6,7,8       | 32                  | 0, 1, 0
                 // 9,10          | 64                  | 0, 0, 1
-                for i in 0..4 {
-                    for j in 0..8 {
-                        // sanity check
-                        assert_exist(
-                            &batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX],
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j],
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 32],
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 64],
-                        );
-                        // assert
-                        // batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX]
-                        // = flag1 * potential_batch_data_hash_digest[(3 - i) * 8 + j]
-                        // + flag2 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 32]
-                        // + flag3 * potential_batch_data_hash_digest[(3 - i) * 8 + j + 64]
-
-                        let rhs = rlc_config.mul(
-                            &mut region,
-                            &flag1,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j],
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
-                            &mut region,
-                            &flag2,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 32],
-                            &rhs,
-                            &mut offset,
-                        )?;
-                        let rhs = rlc_config.mul_add(
-                            &mut region,
-                            &flag3,
-                            &potential_batch_data_hash_digest[(3 - i) * 8 + j + 64],
-                            &rhs,
-                            &mut offset,
-                        )?;
+                for i in 0..DIGEST_LEN {
+                    // sanity check
+                    assert_exist(
+                        &batch_pi_hash_preimage[i + CHUNK_DATA_HASH_INDEX],
+                        &potential_batch_data_hash_digest[0][i],
+                        &potential_batch_data_hash_digest[1][i],
+                        &potential_batch_data_hash_digest[2][i],
+                    );
+                    // assert
+                    // batch_pi_hash_preimage[i + CHUNK_DATA_HASH_INDEX]
+                    // = flag1 * potential_batch_data_hash_digest[0][i]
+                    // + flag2 * potential_batch_data_hash_digest[1][i]
+                    // + flag3 * potential_batch_data_hash_digest[2][i]
+
+                    let rhs = rlc_config.mul(
+                        &mut region,
+                        &flag1,
+                        &potential_batch_data_hash_digest[0][i],
+                        &mut offset,
+                    )?;
+                    let rhs = rlc_config.mul_add(
+                        &mut region,
+                        &flag2,
+                        &potential_batch_data_hash_digest[1][i],
+                        &rhs,
+                        &mut offset,
+                    )?;
+                    let rhs = rlc_config.mul_add(
+                        &mut region,
+                        &flag3,
+                        &potential_batch_data_hash_digest[2][i],
+                        &rhs,
+                        &mut offset,
+                    )?;
 
-                        region.constrain_equal(
-                            batch_pi_hash_preimage[i * 8 + j + CHUNK_DATA_HASH_INDEX].cell(),
-                            rhs.cell(),
-                        )?;
-                    }
+                    region.constrain_equal(
+                        batch_pi_hash_preimage[i + CHUNK_DATA_HASH_INDEX].cell(),
+                        rhs.cell(),
+                    )?;
                 }
 
                 // 3 batch_data_hash and chunk[i].pi_hash use a same chunk[i].data_hash when
@@ -577,7 +638,8 @@ pub(crate) fn conditional_constraints(
                 //        chunk[i].prevStateRoot ||
                 //        chunk[i].postStateRoot ||
                 //        chunk[i].withdrawRoot  ||
-                //        chunk[i].datahash)
+                //        chunk[i].datahash ||
+                //        chunk[i].prevWithdrawRoot)
                 let challenge_cell =
                     rlc_config.read_challenge(&mut region, challenges, &mut offset)?;
 
@@ -638,23 +700,29 @@ pub(crate) fn conditional_constraints(
                 let t1t2t3 = rlc_config.mul(&mut region, &t1t2, &t3, &mut offset)?;
                 rlc_config.enforce_zero(&mut region, &t1t2t3)?;
 
-                // 6. chunk[i]'s prev_state_root == post_state_root when chunk[i] is padded
+                // 6. chunk[i]'s prev_state_root == post_state_root and prev_withdraw_root == withdraw_root
+                // when chunk[i] is padded
                 for (i, chunk_hash_input) in chunk_pi_hash_preimages.iter().enumerate() {
-                    for j in 0..DIGEST_LEN {
-                        let t1 = &chunk_hash_input[j + PREV_STATE_ROOT_INDEX];
-                        let t2 = &chunk_hash_input[j + POST_STATE_ROOT_INDEX];
-
-                        assert_conditional_equal(t1, t2, &chunk_is_pad[i]);
-                        // assert (t1 - t2) * chunk_is_padding == 0
-                        let t1_sub_t2 = rlc_config.sub(&mut region, t1, t2, &mut offset)?;
-                        let res = rlc_config.mul(
-                            &mut region,
-                            &t1_sub_t2,
-                            &chunk_is_pad[i],
-                            &mut offset,
-                        )?;
-
-                        rlc_config.enforce_zero(&mut region, &res)?;
+                    for (prev_index, post_index) in [
+                        (PREV_STATE_ROOT_INDEX, POST_STATE_ROOT_INDEX),
+                        (PREV_WITHDRAW_ROOT_INDEX, WITHDRAW_ROOT_INDEX),
+                    ] {
+                        for j in 0..DIGEST_LEN {
+                            let t1 = &chunk_hash_input[j + prev_index];
+                            let t2 = &chunk_hash_input[j + post_index];
+
+                            assert_conditional_equal(t1, t2, &chunk_is_pad[i]);
+                            // assert (t1 - t2) * chunk_is_padding == 0
+                            let t1_sub_t2 = rlc_config.sub(&mut region, t1, t2, &mut offset)?;
+                            let res = rlc_config.mul(
+                                &mut region,
+                                &t1_sub_t2,
+                                &chunk_is_pad[i],
+                                &mut offset,
+                            )?;
+
+                            rlc_config.enforce_zero(&mut region, &res)?;
+                        }
                     }
                 }
 
@@ -734,9 +802,24 @@ pub(crate) fn chunk_is_valid(
 ) -> Result<[AssignedCell<Fr, Fr>; MAX_AGG_SNARKS], halo2_proofs::plonk::Error> {
     let mut res = vec![];
 
+    // the chunk indices are counted from the constant zero and one cells
+    let one = {
+        let one = rlc_config.load_private(region, &Fr::one(), offset)?;
+        let one_cell = rlc_config.one_cell(one.cell().region_index);
+        region.constrain_equal(one_cell, one.cell())?;
+        one
+    };
+    let mut index = {
+        let zero = rlc_config.load_private(region, &Fr::zero(), offset)?;
+        let zero_cell = rlc_config.zero_cell(zero.cell().region_index);
+        region.constrain_equal(zero_cell, zero.cell())?;
+        zero
+    };
     for i in 0..MAX_AGG_SNARKS {
-        let value = rlc_config.load_private(region, &Fr::from(i as u64), offset)?;
-        let is_valid = rlc_config.is_smaller_than(region, &value, num_of_valid_chunks, offset)?;
+        if i > 0 {
+            index = rlc_config.add(region, &index, &one, offset)?;
+        }
+        let is_valid = rlc_config.is_smaller_than(region, &index, num_of_valid_chunks, offset)?;
         res.push(is_valid);
     }
     // constrain the chunks are ordered with real ones at the beginning. that is,
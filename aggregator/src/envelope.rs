@@ -0,0 +1,271 @@
+//! Offline sanity checks for batch proofs.
+//!
+//! A [`BatchProofEnvelope`] carries a batch proof together with the chunk
+//! metadata it was generated from. [`verify_artifacts`] re-derives the public
+//! inputs expected by the L1 verifier from the chunks, compares them with the
+//! instances shipped alongside the proof, and verifies the proof natively with
+//! the same (keccak) transcript as the EVM verifier. This is used to triage
+//! verifier reverts on L1 without a node.
+//!
+//! A [`ChunkProofEnvelope`] carries a chunk proof of the super circuit in the
+//! same way.
+
+use std::{fmt, fs::File, io, path::Path};
+
+use eth_types::{ToLittleEndian, H256, U256};
+use ethers_core::utils::keccak256;
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{verify_proof, VerifyingKey},
+    poly::{
+        commitment::ParamsProver,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::TranscriptReadBuffer,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use snark_verifier::{loader::native::NativeLoader, system::halo2::transcript::evm::EvmTranscript};
+
+use crate::{
+    constants::{ACC_LEN, MAX_AGG_SNARKS},
+    BatchHash, ChunkHash,
+};
+
+/// A batch proof, as submitted to L1, together with its witness metadata.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchProofEnvelope {
+    /// Chunks of the batch, padded to `MAX_AGG_SNARKS`.
+    pub chunks_with_padding: Vec<ChunkHash>,
+    /// Public inputs of the aggregation circuit, including the accumulator.
+    pub instances: Vec<U256>,
+    /// Proof bytes, generated with the EVM (keccak) transcript.
+    pub proof: Vec<u8>,
+}
+
+/// A chunk proof of the super circuit, together with the chunk it proves.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChunkProofEnvelope {
+    /// The chunk proven.
+    pub chunk: ChunkHash,
+    /// Degree of the super circuit.
+    pub k: u32,
+    /// Public inputs of the super circuit, by instance column.
+    pub instances: Vec<Vec<U256>>,
+    /// Proof bytes, generated with the Poseidon transcript.
+    pub proof: Vec<u8>,
+}
+
+/// Reasons for a batch proof to be rejected.
+#[derive(Debug)]
+pub enum VerifyArtifactsError {
+    /// The number of chunks differs from `MAX_AGG_SNARKS`.
+    ChunkCount { expected: usize, found: usize },
+    /// The first chunk is a padding chunk.
+    NoRealChunk,
+    /// The chunk at `index` doesn't continue the previous one, or is a
+    /// padding chunk which doesn't repeat it.
+    ChunkMismatch { index: usize },
+    /// The number of instances differs from the circuit's layout.
+    InstanceLength { expected: usize, found: usize },
+    /// The public input at `index` is not a canonical field element.
+    NonCanonicalInstance { index: usize, value: U256 },
+    /// The public input at `index` does not match the one derived from the
+    /// chunks.
+    InstanceMismatch {
+        index: usize,
+        expected: Fr,
+        found: Fr,
+    },
+    /// The proof does not verify against the given verifying key.
+    InvalidProof(halo2_proofs::plonk::Error),
+}
+
+impl fmt::Display for VerifyArtifactsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ChunkCount { expected, found } => {
+                write!(f, "expected {expected} chunks, found {found}")
+            }
+            Self::NoRealChunk => write!(f, "the batch has no real chunk"),
+            Self::ChunkMismatch { index } => {
+                write!(f, "chunk {index} doesn't follow the previous chunk")
+            }
+            Self::InstanceLength { expected, found } => {
+                write!(f, "expected {expected} public inputs, found {found}")
+            }
+            Self::NonCanonicalInstance { index, value } => {
+                write!(f, "public input {index} is not a field element: {value}")
+            }
+            Self::InstanceMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "public input {index} is {found:?}, expected {expected:?} from the chunks"
+            ),
+            Self::InvalidProof(e) => write!(f, "invalid proof: {e:?}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyArtifactsError {}
+
+/// Check the chunks as [`BatchHash::construct`] does, which panics on a
+/// malformed batch.
+fn check_chunks(chunks_with_padding: &[ChunkHash]) -> Result<(), VerifyArtifactsError> {
+    if chunks_with_padding.len() != MAX_AGG_SNARKS {
+        return Err(VerifyArtifactsError::ChunkCount {
+            expected: MAX_AGG_SNARKS,
+            found: chunks_with_padding.len(),
+        });
+    }
+    if chunks_with_padding[0].is_padding {
+        return Err(VerifyArtifactsError::NoRealChunk);
+    }
+    let empty_data_hash: H256 = keccak256([]).into();
+    for (index, pair) in chunks_with_padding.windows(2).enumerate() {
+        let (prev, chunk) = (&pair[0], &pair[1]);
+        let follows = prev.post_state_root == chunk.prev_state_root
+            && prev.withdraw_root == chunk.prev_withdraw_root
+            && prev.chain_id == chunk.chain_id;
+        let is_valid_padding = !chunk.is_padding
+            || (chunk.data_hash == empty_data_hash
+                && chunk.prev_state_root == chunk.post_state_root
+                && prev.withdraw_root == chunk.withdraw_root);
+        if !follows || !is_valid_padding {
+            return Err(VerifyArtifactsError::ChunkMismatch { index: index + 1 });
+        }
+    }
+    Ok(())
+}
+
+fn load_json<T: DeserializeOwned>(path: &Path) -> Result<T, io::Error> {
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+fn dump_json<T: Serialize>(value: &T, path: &Path) -> Result<(), io::Error> {
+    serde_json::to_writer_pretty(File::create(path)?, value)?;
+    Ok(())
+}
+
+impl BatchProofEnvelope {
+    /// Read an envelope from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        load_json(path)
+    }
+
+    /// Write the envelope as JSON.
+    pub fn dump(&self, path: &Path) -> Result<(), io::Error> {
+        dump_json(self, path)
+    }
+
+    /// Public inputs, excluding the accumulator, derived from the chunks.
+    /// Fails if the chunks don't form a batch.
+    pub fn expected_instances(&self) -> Result<Vec<Fr>, VerifyArtifactsError> {
+        check_chunks(&self.chunks_with_padding)?;
+        let batch_hash = BatchHash::construct(&self.chunks_with_padding);
+        let number_of_valid_chunks = Fr::from(batch_hash.number_of_valid_chunks as u64);
+        Ok(batch_hash
+            .instances_exclude_acc::<Fr>()
+            .concat()
+            .into_iter()
+            .chain(std::iter::once(number_of_valid_chunks))
+            .collect())
+    }
+
+    /// Public inputs of the proof as field elements.
+    pub fn instances(&self) -> Result<Vec<Fr>, VerifyArtifactsError> {
+        self.instances
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                Option::from(Fr::from_bytes(&word.to_le_bytes())).ok_or(
+                    VerifyArtifactsError::NonCanonicalInstance {
+                        index,
+                        value: *word,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Check that the shipped public inputs match the chunks.
+    pub fn check_instances(&self) -> Result<(), VerifyArtifactsError> {
+        let expected = self.expected_instances()?;
+        let found = self.instances()?;
+        if found.len() != expected.len() + ACC_LEN {
+            return Err(VerifyArtifactsError::InstanceLength {
+                expected: expected.len() + ACC_LEN,
+                found: found.len(),
+            });
+        }
+        for (index, (expected, found)) in expected.iter().zip(&found[ACC_LEN..]).enumerate() {
+            if expected != found {
+                return Err(VerifyArtifactsError::InstanceMismatch {
+                    index: index + ACC_LEN,
+                    expected: *expected,
+                    found: *found,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ChunkProofEnvelope {
+    /// Envelope of a proof of `chunk` with the public inputs `instances`.
+    pub fn new(chunk: ChunkHash, k: u32, instances: &[Vec<Fr>], proof: Vec<u8>) -> Self {
+        Self {
+            chunk,
+            k,
+            instances: instances
+                .iter()
+                .map(|column| {
+                    column
+                        .iter()
+                        .map(|value| U256::from_little_endian(&value.to_bytes()))
+                        .collect()
+                })
+                .collect(),
+            proof,
+        }
+    }
+
+    /// Read an envelope from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, io::Error> {
+        load_json(path)
+    }
+
+    /// Write the envelope as JSON.
+    pub fn dump(&self, path: &Path) -> Result<(), io::Error> {
+        dump_json(self, path)
+    }
+}
+
+/// Check the public inputs of an envelope against its chunks and verify its
+/// proof with the aggregation circuit's verifying key.
+pub fn verify_artifacts(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    envelope: &BatchProofEnvelope,
+) -> Result<(), VerifyArtifactsError> {
+    envelope.check_instances()?;
+
+    let instances = envelope.instances()?;
+    let instances = [instances.as_slice()];
+    let mut transcript =
+        EvmTranscript::<G1Affine, NativeLoader, _, _>::init(envelope.proof.as_slice());
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params.verifier_params(),
+        vk,
+        SingleStrategy::new(params),
+        &[&instances],
+        &mut transcript,
+    )
+    .map_err(VerifyArtifactsError::InvalidProof)
+}
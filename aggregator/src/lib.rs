@@ -12,18 +12,34 @@ mod compression;
 mod constants;
 /// Core module for circuit assignment
 mod core;
+/// Offline sanity checks for batch proofs
+mod envelope;
 /// Parameters for compression circuit
 mod param;
+/// One-call chunk proof of a tx fetched from a node
+#[cfg(feature = "prove-tx")]
+pub mod prove_tx;
+/// Proving service around a chunk and batch prover
+#[cfg(feature = "prover-service")]
+pub mod service;
+/// Storage for proofs, witnesses and verifying keys
+pub mod storage;
 /// utilities
 mod util;
+/// Verification artifacts for non-EVM verifiers
+mod verifier;
 
 #[cfg(test)]
 mod tests;
 
 pub use aggregation::*;
 pub use batch::BatchHash;
-pub use chunk::ChunkHash;
+pub use chunk::{chunk_payload, ChunkHash, ChunkPayload, ChunkPayloadError};
 pub use compression::*;
 pub use constants::MAX_AGG_SNARKS;
 pub(crate) use constants::*;
+pub use envelope::{
+    verify_artifacts, BatchProofEnvelope, ChunkProofEnvelope, VerifyArtifactsError,
+};
 pub use param::*;
+pub use verifier::*;
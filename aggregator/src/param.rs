@@ -2,6 +2,10 @@ use snark_verifier::loader::halo2::halo2_ecc::fields::fp::FpStrategy;
 
 use crate::{BITS, LIMBS};
 
+/// Total number of advice cells a compression layer needs, i.e. `num_advice * 2^degree`.
+/// Derived from the thin configuration (one advice column at degree 25).
+pub(crate) const COMPRESSION_ADVICE_CELLS: usize = 1 << 25;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 /// Parameters for aggregation circuit and compression circuit configs.
 pub struct ConfigParams {
@@ -42,6 +46,26 @@ impl ConfigParams {
         }
     }
 
+    /// Parameters of a compression layer with the given degree.
+    /// The number of advice columns is chosen so that the layer has
+    /// roughly the same capacity regardless of its degree: a smaller
+    /// degree gives a wider circuit (cheaper prover, more expensive
+    /// verifier) and vice versa.
+    pub fn compress_param(degree: u32) -> Self {
+        assert!(degree > 1, "compression layer degree {degree} is too small");
+        let num_advice = (COMPRESSION_ADVICE_CELLS >> degree.min(25)).max(1);
+        Self {
+            strategy: FpStrategy::Simple,
+            degree,
+            num_advice: vec![num_advice],
+            num_lookup_advice: vec![1],
+            num_fixed: 1,
+            lookup_bits: 20.min(degree as usize - 1),
+            limb_bits: BITS,
+            num_limbs: LIMBS,
+        }
+    }
+
     pub(crate) fn _compress_thin_param() -> Self {
         Self {
             strategy: FpStrategy::Simple,
@@ -0,0 +1,211 @@
+//! Chunk proof of a single tx, fetched from a node.
+//!
+//! [`prove_tx`] chains the witness generation of a tx ([`BuilderClient::gen_inputs_tx`]),
+//! its conversion into a witness block and the proof of the super circuit,
+//! so that a failure reported on a tx can be reproduced with one call. The
+//! inputs are generated as if the tx were the only one of its block.
+//!
+//! The witness is converted with the default randomness: the proof draws the
+//! challenges of the circuit from its transcript, so the mock randomness of
+//! [`ProveTxCircuit`] is left unused.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use bus_mapping::{
+    circuit_input_builder::{BuilderClient, CircuitInputBuilder, CircuitsParams},
+    rpc::GethClient,
+};
+use ethers_providers::Http;
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::rngs::OsRng;
+use snark_verifier_sdk::{gen_pk, gen_snark_shplonk};
+use zkevm_circuits::{super_circuit::SuperCircuit, witness::block_convert};
+
+use crate::{ChunkHash, ChunkProofEnvelope};
+
+/// Max number of txs of the super circuit proven by [`prove_tx`].
+pub const PROVE_TX_MAX_TXS: usize = 1;
+/// Max calldata of the super circuit proven by [`prove_tx`].
+pub const PROVE_TX_MAX_CALLDATA: usize = 131072;
+/// Max number of inner blocks of the super circuit proven by [`prove_tx`].
+pub const PROVE_TX_MAX_INNER_BLOCKS: usize = 1;
+
+/// The super circuit proven by [`prove_tx`]. Its mock randomness is only used
+/// by `MockProver`, hence zero.
+pub type ProveTxCircuit =
+    SuperCircuit<Fr, PROVE_TX_MAX_TXS, PROVE_TX_MAX_CALLDATA, PROVE_TX_MAX_INNER_BLOCKS, 0>;
+
+/// Circuit parameters of [`prove_tx`]: one tx, and the rows of the EVM and
+/// keccak circuits computed from the witness.
+pub fn prove_tx_circuits_params() -> CircuitsParams {
+    CircuitsParams {
+        max_rws: 1_000_000,
+        max_txs: PROVE_TX_MAX_TXS,
+        max_calldata: PROVE_TX_MAX_CALLDATA,
+        max_inner_blocks: PROVE_TX_MAX_INNER_BLOCKS,
+        max_copy_rows: 1_000_000,
+        max_mpt_rows: 1_000_000,
+        max_exp_steps: 100_000,
+        max_bytecode: 500_000,
+        max_rlp_rows: 500_000,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        ..Default::default()
+    }
+}
+
+/// The stages of [`prove_tx`], reported to its progress callback when they
+/// start.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProveTxStage {
+    /// Fetch the tx, its trace and state from the node and generate the
+    /// circuit inputs.
+    GenInputs,
+    /// Convert the circuit inputs into a witness block.
+    Witness,
+    /// Read the params of degree `k`, the degree of the super circuit.
+    LoadParams { k: u32 },
+    /// Generate the proving key.
+    ProvingKey,
+    /// Generate the proof.
+    Prove,
+}
+
+/// Reasons for [`prove_tx`] to fail.
+#[derive(Debug)]
+pub enum ProveTxError {
+    /// The RPC URL couldn't be parsed.
+    InvalidUrl(String),
+    /// The witness generation or conversion failed.
+    Witness(bus_mapping::Error),
+    /// The params couldn't be read.
+    Params(io::Error),
+    /// The params are of a lower degree than the circuit.
+    ParamsDegree { expected: u32, found: u32 },
+}
+
+impl fmt::Display for ProveTxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUrl(e) => write!(f, "invalid RPC URL: {e}"),
+            Self::Witness(e) => write!(f, "witness generation failed: {e}"),
+            Self::Params(e) => write!(f, "couldn't read the params: {e}"),
+            Self::ParamsDegree { expected, found } => write!(
+                f,
+                "params of degree {found} are too small for the circuit of degree {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProveTxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Witness(e) => Some(e),
+            Self::Params(e) => Some(e),
+            Self::InvalidUrl(_) | Self::ParamsDegree { .. } => None,
+        }
+    }
+}
+
+/// Path to the params of degree `k` in `params_dir`.
+pub fn params_path(params_dir: &Path, k: u32) -> PathBuf {
+    params_dir.join(format!("params{k}"))
+}
+
+/// Prove the tx `tx_hash` from the node at `rpc_url` with the params stored
+/// in `params_dir`, see [`params_path`]. `progress` is called at the start of
+/// each stage.
+///
+/// The params of the degree of the circuit are used if present, else those of
+/// the next degrees, downsized. The stages following the generation of the
+/// inputs run on the blocking thread pool of the runtime.
+pub async fn prove_tx(
+    rpc_url: &str,
+    tx_hash: &str,
+    params_dir: impl AsRef<Path>,
+    mut progress: impl FnMut(ProveTxStage) + Send + 'static,
+) -> Result<ChunkProofEnvelope, ProveTxError> {
+    let url = url::Url::parse(rpc_url).map_err(|e| ProveTxError::InvalidUrl(e.to_string()))?;
+    let cli = GethClient::new(Http::new(url));
+
+    progress(ProveTxStage::GenInputs);
+    let builder = BuilderClient::new(cli, prove_tx_circuits_params())
+        .await
+        .map_err(ProveTxError::Witness)?
+        .gen_inputs_tx(tx_hash)
+        .await
+        .map_err(ProveTxError::Witness)?;
+
+    let params_dir = params_dir.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || prove_inputs(&builder, &params_dir, progress))
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+}
+
+/// Convert the inputs of [`prove_tx`] into a witness block and prove it.
+fn prove_inputs(
+    builder: &CircuitInputBuilder,
+    params_dir: &Path,
+    mut progress: impl FnMut(ProveTxStage),
+) -> Result<ChunkProofEnvelope, ProveTxError> {
+    progress(ProveTxStage::Witness);
+    let block =
+        block_convert::<Fr>(&builder.block, &builder.code_db).map_err(ProveTxError::Witness)?;
+    let chunk = ChunkHash::from_witness_block(&block, false);
+    let (k, circuit, instances) =
+        ProveTxCircuit::build_from_witness_block(block).map_err(ProveTxError::Witness)?;
+
+    progress(ProveTxStage::LoadParams { k });
+    let params = load_params(params_dir, k)?;
+
+    progress(ProveTxStage::ProvingKey);
+    let pk = gen_pk(&params, &circuit, None);
+
+    progress(ProveTxStage::Prove);
+    let snark = gen_snark_shplonk(&params, &pk, circuit, &mut OsRng, None::<String>);
+
+    Ok(ChunkProofEnvelope::new(chunk, k, &instances, snark.proof))
+}
+
+/// Read the params of degree `k`, or of the smallest larger degree found in
+/// `params_dir`, downsized to `k`.
+fn load_params(params_dir: &Path, k: u32) -> Result<ParamsKZG<Bn256>, ProveTxError> {
+    let path = (k..=26)
+        .map(|degree| params_path(params_dir, degree))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| params_path(params_dir, k));
+    let file = File::open(&path).map_err(ProveTxError::Params)?;
+    let mut params =
+        ParamsKZG::<Bn256>::read(&mut BufReader::new(file)).map_err(ProveTxError::Params)?;
+    if params.k() < k {
+        return Err(ProveTxError::ParamsDegree {
+            expected: k,
+            found: params.k(),
+        });
+    }
+    params.downsize(k);
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_params_missing() {
+        let dir = std::env::temp_dir().join("load_params_missing");
+        let err = load_params(&dir, 20).unwrap_err();
+        assert!(matches!(err, ProveTxError::Params(_)));
+        assert!(err.to_string().starts_with("couldn't read the params"));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
@@ -0,0 +1,293 @@
+//! Proving service.
+//!
+//! [`ProverService`] wraps a [`Prover`] with a task queue, a [`ProofStorage`]
+//! for the proofs and a JSON-RPC 2.0 request handler, so coordinators only
+//! need to plug in a transport. Proving runs on a single worker thread, one
+//! task at a time, in submission order. The last task id is kept in the
+//! storage, so task ids stay unique when the service is restarted.
+//!
+//! Supported methods:
+//! - `prove_chunk`: `{"traces": [..]}`, returns a task id
+//! - `prove_batch`: `{"chunks": [..], "chunk_tasks": [..]}`, returns a task id
+//! - `get_status`: `{"task_id": ..}`, returns the [`TaskStatus`]
+//! - `get_proof`: `{"task_id": ..}`, returns the proof bytes of a done task
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    storage::{ArtifactKind, ProofStorage, StorageError},
+    ChunkHash,
+};
+
+/// Identifier of a proving task.
+pub type TaskId = u64;
+
+/// Storage key of the last task id.
+const LAST_TASK_ID_KEY: &str = "service/last_task_id";
+
+/// The proving backend driven by the service.
+pub trait Prover: Send + 'static {
+    /// Prove a chunk from its block traces.
+    fn prove_chunk(&mut self, traces: &[Value]) -> Result<Vec<u8>, String>;
+
+    /// Prove a batch from its chunks, padded to `MAX_AGG_SNARKS`, and the
+    /// proofs of its real chunks.
+    fn prove_batch(
+        &mut self,
+        chunks_with_padding: &[ChunkHash],
+        chunk_proofs: &[Vec<u8>],
+    ) -> Result<Vec<u8>, String>;
+}
+
+/// Status of a proving task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "error", rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Waiting for the worker.
+    Queued,
+    /// Being proven.
+    Running,
+    /// The proof is in the artifact store.
+    Done,
+    /// Proving failed with the given error.
+    Failed(String),
+}
+
+#[derive(Debug)]
+enum Task {
+    Chunk {
+        traces: Vec<Value>,
+    },
+    Batch {
+        chunks: Vec<ChunkHash>,
+        chunk_tasks: Vec<TaskId>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct ProveChunkParams {
+    traces: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProveBatchParams {
+    chunks: Vec<ChunkHash>,
+    chunk_tasks: Vec<TaskId>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskParams {
+    task_id: TaskId,
+}
+
+type Statuses = Arc<Mutex<BTreeMap<TaskId, TaskStatus>>>;
+
+/// A task queue in front of a [`Prover`].
+pub struct ProverService {
+    storage: Arc<dyn ProofStorage>,
+    statuses: Statuses,
+    last_task_id: Mutex<TaskId>,
+    sender: Option<Sender<(TaskId, Task)>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ProverService {
+    /// Start the worker thread proving the queued tasks with `prover`.
+    /// Proofs are stored in `storage` under the task id, and the task ids
+    /// continue from the last one recorded there.
+    pub fn spawn(
+        mut prover: impl Prover,
+        storage: impl ProofStorage + 'static,
+    ) -> Result<Self, StorageError> {
+        let last_task_id = match storage.get_raw(LAST_TASK_ID_KEY)? {
+            Some(bytes) => String::from_utf8(bytes)
+                .ok()
+                .and_then(|id| id.parse::<TaskId>().ok())
+                .ok_or_else(|| StorageError::Backend("invalid last task id".to_string()))?,
+            None => 0,
+        };
+        let storage: Arc<dyn ProofStorage> = Arc::new(storage);
+        let statuses = Statuses::default();
+        let (sender, receiver) = mpsc::channel::<(TaskId, Task)>();
+
+        let worker = {
+            let storage = storage.clone();
+            let statuses = statuses.clone();
+            thread::spawn(move || {
+                for (task_id, task) in receiver {
+                    let set_status = |status| statuses.lock().unwrap().insert(task_id, status);
+                    set_status(TaskStatus::Running);
+
+                    let proof = match task {
+                        Task::Chunk { traces } => prover.prove_chunk(&traces),
+                        Task::Batch {
+                            chunks,
+                            chunk_tasks,
+                        } => chunk_tasks
+                            .iter()
+                            .map(|id| {
+                                storage
+                                    .get(ArtifactKind::Proof, &id.to_string())
+                                    .map_err(|e| format!("chunk task {id}: {e}"))
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                            .and_then(|chunk_proofs| prover.prove_batch(&chunks, &chunk_proofs)),
+                    };
+                    let status = match proof.and_then(|proof| {
+                        storage
+                            .put(ArtifactKind::Proof, &task_id.to_string(), &proof)
+                            .map_err(|e| e.to_string())
+                    }) {
+                        Ok(_) => TaskStatus::Done,
+                        Err(e) => {
+                            log::error!("proving task {task_id} failed: {e}");
+                            TaskStatus::Failed(e)
+                        }
+                    };
+                    set_status(status);
+                }
+            })
+        };
+
+        Ok(Self {
+            storage,
+            statuses,
+            last_task_id: Mutex::new(last_task_id),
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+
+    fn submit(&self, task: Task) -> Result<TaskId, StorageError> {
+        let task_id = {
+            let mut last_task_id = self.last_task_id.lock().unwrap();
+            // recorded before the task is queued, so that no id is reused
+            // after a restart
+            self.storage
+                .put_raw(LAST_TASK_ID_KEY, (*last_task_id + 1).to_string().as_bytes())?;
+            *last_task_id += 1;
+            *last_task_id
+        };
+        self.statuses
+            .lock()
+            .unwrap()
+            .insert(task_id, TaskStatus::Queued);
+        self.sender
+            .as_ref()
+            .expect("worker is running")
+            .send((task_id, task))
+            .expect("worker is running");
+        Ok(task_id)
+    }
+
+    /// Queue a chunk proof.
+    pub fn prove_chunk(&self, traces: Vec<Value>) -> Result<TaskId, StorageError> {
+        self.submit(Task::Chunk { traces })
+    }
+
+    /// Queue a batch proof over the proofs of previously submitted chunk
+    /// tasks.
+    pub fn prove_batch(
+        &self,
+        chunks: Vec<ChunkHash>,
+        chunk_tasks: Vec<TaskId>,
+    ) -> Result<TaskId, StorageError> {
+        self.submit(Task::Batch {
+            chunks,
+            chunk_tasks,
+        })
+    }
+
+    /// Status of a task, or `None` if it was never submitted.
+    pub fn get_status(&self, task_id: TaskId) -> Option<TaskStatus> {
+        self.statuses.lock().unwrap().get(&task_id).cloned()
+    }
+
+    /// Proof of a task, once it is done.
+    pub fn get_proof(&self, task_id: TaskId) -> Option<Vec<u8>> {
+        match self.get_status(task_id)? {
+            TaskStatus::Done => self
+                .storage
+                .get(ArtifactKind::Proof, &task_id.to_string())
+                .ok(),
+            _ => None,
+        }
+    }
+
+    /// Handle a JSON-RPC 2.0 request and return the serialized response.
+    pub fn handle_request(&self, request: &str) -> String {
+        let request: Value = match serde_json::from_str(request) {
+            Ok(request) => request,
+            Err(e) => return rpc_error(Value::Null, -32700, &e.to_string()),
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "prove_chunk" => parse_params::<ProveChunkParams>(params).and_then(|params| {
+                self.prove_chunk(params.traces)
+                    .map(|task_id| json!(task_id))
+                    .map_err(storage_error)
+            }),
+            "prove_batch" => parse_params::<ProveBatchParams>(params).and_then(|params| {
+                self.prove_batch(params.chunks, params.chunk_tasks)
+                    .map(|task_id| json!(task_id))
+                    .map_err(storage_error)
+            }),
+            "get_status" => parse_params::<TaskParams>(params).and_then(|params| {
+                self.get_status(params.task_id)
+                    .map(|status| json!(status))
+                    .ok_or((-32001, format!("unknown task {}", params.task_id)))
+            }),
+            "get_proof" => parse_params::<TaskParams>(params).and_then(|params| {
+                self.get_proof(params.task_id)
+                    .map(|proof| json!(proof))
+                    .ok_or((-32002, format!("no proof for task {}", params.task_id)))
+            }),
+            _ => Err((-32601, format!("method not found: {method}"))),
+        };
+
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err((code, message)) => rpc_error(id, code, &message),
+        }
+    }
+}
+
+impl Drop for ProverService {
+    fn drop(&mut self) {
+        // closing the queue lets the worker finish the pending tasks and exit
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|e| (-32602, e.to_string()))
+}
+
+fn storage_error(e: StorageError) -> (i64, String) {
+    (-32003, e.to_string())
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+    .to_string()
+}
@@ -0,0 +1,242 @@
+//! Persistent storage for proving artifacts.
+//!
+//! Every artifact is stored next to the keccak hash of its content, which is
+//! checked again on load. Pipelines can use [`ProofStorage::contains`] to skip
+//! steps whose outputs already exist, and the recorded hashes to audit which
+//! witness a proof was generated from.
+
+use std::{fmt, fs, io, path::PathBuf, str::FromStr};
+
+use eth_types::H256;
+use ethers_core::utils::keccak256;
+
+/// Type of a stored artifact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// Witness, e.g. the block traces of a chunk.
+    Witness,
+    /// Proof bytes.
+    Proof,
+    /// Verifying key.
+    Vk,
+}
+
+impl ArtifactKind {
+    fn prefix(&self) -> &'static str {
+        match self {
+            Self::Witness => "witness",
+            Self::Proof => "proof",
+            Self::Vk => "vk",
+        }
+    }
+}
+
+/// Errors returned by a [`ProofStorage`].
+#[derive(Debug)]
+pub enum StorageError {
+    /// The artifact does not exist.
+    NotFound(String),
+    /// The content of the artifact does not match its recorded hash.
+    Integrity {
+        key: String,
+        expected: H256,
+        found: H256,
+    },
+    /// Local file system error.
+    Io(io::Error),
+    /// Error reported by a remote backend.
+    Backend(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(key) => write!(f, "artifact {key} not found"),
+            Self::Integrity {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "artifact {key} is corrupted: expected hash {expected:?}, found {found:?}"
+            ),
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Backend(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// A key-value store for proving artifacts.
+///
+/// Backends only implement raw access; hashing and verification are shared.
+pub trait ProofStorage: Send + Sync {
+    /// Write `bytes` under `key`, replacing any previous value.
+    fn put_raw(&self, key: &str, bytes: &[u8]) -> Result<(), StorageError>;
+
+    /// Read the bytes under `key`, or `None` if there are none.
+    fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Store an artifact and return its content hash.
+    fn put(&self, kind: ArtifactKind, name: &str, bytes: &[u8]) -> Result<H256, StorageError> {
+        let key = artifact_key(kind, name);
+        let hash = H256(keccak256(bytes));
+        self.put_raw(&key, bytes)?;
+        // the hash is written last so that an interrupted write is detected on
+        // load instead of being mistaken for a complete artifact
+        self.put_raw(&hash_key(&key), format!("{hash:?}").as_bytes())?;
+        Ok(hash)
+    }
+
+    /// Load an artifact, checking its content against the recorded hash.
+    fn get(&self, kind: ArtifactKind, name: &str) -> Result<Vec<u8>, StorageError> {
+        let key = artifact_key(kind, name);
+        let expected = self
+            .hash(kind, name)?
+            .ok_or_else(|| StorageError::NotFound(key.clone()))?;
+        let bytes = self
+            .get_raw(&key)?
+            .ok_or_else(|| StorageError::NotFound(key.clone()))?;
+        let found = H256(keccak256(&bytes));
+        if found != expected {
+            return Err(StorageError::Integrity {
+                key,
+                expected,
+                found,
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Recorded content hash of an artifact.
+    fn hash(&self, kind: ArtifactKind, name: &str) -> Result<Option<H256>, StorageError> {
+        let key = artifact_key(kind, name);
+        self.get_raw(&hash_key(&key))?
+            .map(|hash| {
+                String::from_utf8(hash)
+                    .ok()
+                    .and_then(|hash| H256::from_str(&hash).ok())
+                    .ok_or_else(|| StorageError::Backend(format!("invalid hash for {key}")))
+            })
+            .transpose()
+    }
+
+    /// Whether an artifact has been completely stored.
+    fn contains(&self, kind: ArtifactKind, name: &str) -> Result<bool, StorageError> {
+        Ok(self.hash(kind, name)?.is_some())
+    }
+}
+
+fn artifact_key(kind: ArtifactKind, name: &str) -> String {
+    format!("{}/{name}", kind.prefix())
+}
+
+fn hash_key(key: &str) -> String {
+    format!("{key}.keccak")
+}
+
+/// Artifacts stored as files under a local directory.
+#[derive(Clone, Debug)]
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    /// Open a storage in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, StorageError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+impl ProofStorage for LocalStorage {
+    fn put_raw(&self, key: &str, bytes: &[u8]) -> Result<(), StorageError> {
+        let path = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // write to a temporary file first so readers never see a partial file
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match fs::read(self.dir.join(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Artifacts stored in an S3-compatible bucket.
+#[cfg(feature = "s3")]
+pub struct S3Storage {
+    bucket: s3::Bucket,
+    prefix: String,
+}
+
+#[cfg(feature = "s3")]
+impl S3Storage {
+    /// Connect to `bucket` at `endpoint`, storing artifacts under `prefix`.
+    /// Credentials are read from the environment.
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint: &str,
+        prefix: &str,
+    ) -> Result<Self, StorageError> {
+        let region = s3::Region::Custom {
+            region: region.to_string(),
+            endpoint: endpoint.to_string(),
+        };
+        let credentials =
+            s3::creds::Credentials::default().map_err(|e| StorageError::Backend(e.to_string()))?;
+        let bucket = s3::Bucket::new(bucket, region, credentials)
+            .map_err(|e| StorageError::Backend(e.to_string()))?
+            .with_path_style();
+        Ok(Self {
+            bucket,
+            prefix: prefix.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn path(&self, key: &str) -> String {
+        format!("{}/{key}", self.prefix)
+    }
+}
+
+#[cfg(feature = "s3")]
+impl ProofStorage for S3Storage {
+    fn put_raw(&self, key: &str, bytes: &[u8]) -> Result<(), StorageError> {
+        let response = self
+            .bucket
+            .put_object_blocking(self.path(key), bytes)
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        match response.status_code() {
+            200..=299 => Ok(()),
+            code => Err(StorageError::Backend(format!("put {key}: status {code}"))),
+        }
+    }
+
+    fn get_raw(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let response = self
+            .bucket
+            .get_object_blocking(self.path(key))
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        match response.status_code() {
+            200..=299 => Ok(Some(response.bytes().to_vec())),
+            404 => Ok(None),
+            code => Err(StorageError::Backend(format!("get {key}: status {code}"))),
+        }
+    }
+}
@@ -1,7 +1,13 @@
 mod aggregation;
 mod compression;
+mod envelope;
 mod mock_chunk;
+mod payload;
 mod rlc;
+#[cfg(feature = "prover-service")]
+mod service;
+mod soundness;
+mod storage;
 
 #[macro_export]
 macro_rules! layer_0 {
@@ -75,7 +75,7 @@ fn test_aggregation_circuit_full() {
     log::trace!("finished verification for circuit");
 }
 
-fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
+pub(crate) fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
     // inner circuit: Mock circuit
     let k0 = 8;
 
@@ -87,6 +87,7 @@ fn build_new_aggregation_circuit(num_real_chunks: usize) -> AggregationCircuit {
         .collect_vec();
     for i in 0..num_real_chunks - 1 {
         chunks_without_padding[i + 1].prev_state_root = chunks_without_padding[i].post_state_root;
+        chunks_without_padding[i + 1].prev_withdraw_root = chunks_without_padding[i].withdraw_root;
     }
     let padded_chunk =
         ChunkHash::mock_padded_chunk_hash_for_testing(&chunks_without_padding[num_real_chunks - 1]);
@@ -17,7 +17,7 @@ use snark_verifier_sdk::{
 
 use crate::{
     compression_layer_evm, compression_layer_snark, layer_0, tests::mock_chunk::MockChunkCircuit,
-    CompressionCircuit,
+    CompressionCircuit, CompressionPipeline,
 };
 
 #[ignore = "it takes too much time"]
@@ -98,3 +98,16 @@ fn test_two_layer_proof_compression() {
     std::env::set_var("COMPRESSION_CONFIG", "./configs/compression_thin.config");
     compression_layer_evm!(layer_1_snark, layer_2_params, k2, path, 2);
 }
+
+#[test]
+fn test_compression_pipeline_layers() {
+    let pipeline = CompressionPipeline::new().add_layer(22).add_layer(25);
+    assert_eq!(pipeline.num_layers(), 2);
+    assert_eq!(pipeline.max_degree(), Some(25));
+    assert_eq!(pipeline.layers()[0].num_advice, vec![8]);
+    assert_eq!(pipeline.layers()[1].num_advice, vec![1]);
+    assert!(pipeline
+        .layers()
+        .iter()
+        .all(|layer| (layer.lookup_bits as u32) < layer.degree));
+}
@@ -0,0 +1,128 @@
+use ark_std::test_rng;
+use eth_types::U256;
+
+use halo2_proofs::halo2curves::bn256::Fr;
+
+use crate::{
+    BatchProofEnvelope, ChunkHash, ChunkProofEnvelope, VerifyArtifactsError, ACC_LEN,
+    MAX_AGG_SNARKS,
+};
+
+fn envelope() -> BatchProofEnvelope {
+    let chunk = ChunkHash::mock_random_chunk_hash_for_testing(&mut test_rng());
+    let padding = ChunkHash::mock_padded_chunk_hash_for_testing(&chunk);
+    let chunks_with_padding = [vec![chunk], vec![padding; MAX_AGG_SNARKS - 1]].concat();
+
+    let mut envelope = BatchProofEnvelope {
+        chunks_with_padding,
+        instances: vec![],
+        proof: vec![],
+    };
+    envelope.instances = vec![U256::zero(); ACC_LEN]
+        .into_iter()
+        .chain(
+            envelope
+                .expected_instances()
+                .unwrap()
+                .iter()
+                .map(|fr| U256::from_little_endian(&fr.to_bytes())),
+        )
+        .collect();
+    envelope
+}
+
+#[test]
+fn test_envelope_instances() {
+    let mut envelope = envelope();
+    assert!(envelope.check_instances().is_ok());
+    let found = envelope.instances().unwrap();
+    assert_eq!(found[ACC_LEN..], envelope.expected_instances().unwrap());
+
+    // a word above the modulus isn't a field element
+    let instance = envelope.instances[ACC_LEN];
+    envelope.instances[ACC_LEN] = U256::MAX;
+    assert!(matches!(
+        envelope.instances(),
+        Err(VerifyArtifactsError::NonCanonicalInstance { index, value })
+            if index == ACC_LEN && value == U256::MAX
+    ));
+    assert!(matches!(
+        envelope.check_instances(),
+        Err(VerifyArtifactsError::NonCanonicalInstance { .. })
+    ));
+
+    envelope.instances[ACC_LEN] = instance + 1;
+    assert!(matches!(
+        envelope.check_instances(),
+        Err(VerifyArtifactsError::InstanceMismatch { index, .. }) if index == ACC_LEN
+    ));
+
+    envelope.instances.pop();
+    assert!(matches!(
+        envelope.check_instances(),
+        Err(VerifyArtifactsError::InstanceLength { .. })
+    ));
+}
+
+#[test]
+fn test_envelope_malformed_chunks() {
+    // too few chunks
+    let mut short = envelope();
+    short.chunks_with_padding.pop();
+    let err = short.expected_instances().unwrap_err();
+    assert!(matches!(
+        err,
+        VerifyArtifactsError::ChunkCount { expected, found }
+            if expected == MAX_AGG_SNARKS && found == MAX_AGG_SNARKS - 1
+    ));
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "expected {MAX_AGG_SNARKS} chunks, found {}",
+            MAX_AGG_SNARKS - 1
+        )
+    );
+
+    // only padding chunks
+    let mut no_real_chunk = envelope();
+    no_real_chunk.chunks_with_padding[0] = no_real_chunk.chunks_with_padding[1];
+    assert!(matches!(
+        no_real_chunk.expected_instances(),
+        Err(VerifyArtifactsError::NoRealChunk)
+    ));
+
+    // a padding chunk which doesn't continue the real one
+    let mut gap = envelope();
+    gap.chunks_with_padding[1].prev_state_root = Default::default();
+    assert!(matches!(
+        gap.check_instances(),
+        Err(VerifyArtifactsError::ChunkMismatch { index: 1 })
+    ));
+}
+
+#[test]
+fn test_chunk_proof_envelope_roundtrip() {
+    let envelope = ChunkProofEnvelope::new(
+        ChunkHash::mock_random_chunk_hash_for_testing(&mut test_rng()),
+        20,
+        &[vec![Fr::from(1), Fr::from(2)], vec![]],
+        vec![0xab; 64],
+    );
+    assert_eq!(
+        envelope.instances,
+        [vec![U256::from(1), U256::from(2)], vec![]]
+    );
+
+    let path = std::env::temp_dir().join("chunk_proof_envelope_roundtrip.json");
+    envelope.dump(&path).unwrap();
+    let loaded = ChunkProofEnvelope::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        loaded.chunk.public_input_hash(),
+        envelope.chunk.public_input_hash()
+    );
+    assert_eq!(loaded.k, envelope.k);
+    assert_eq!(loaded.instances, envelope.instances);
+    assert_eq!(loaded.proof, envelope.proof);
+}
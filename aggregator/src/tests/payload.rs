@@ -0,0 +1,163 @@
+use ark_std::test_rng;
+use bus_mapping::circuit_input_builder::TX_DA_LENGTH_PREFIX_BYTES;
+use eth_types::{geth_types::TxType, Signature, H256};
+use ethers_core::{types::TransactionRequest, utils::keccak256};
+use halo2_proofs::halo2curves::bn256::Fr;
+use rand::{Rng, RngCore};
+use std::str::FromStr;
+use zkevm_circuits::{
+    util::tx_root::{tx_hash_root, tx_inclusion_proof, verify_tx_inclusion},
+    witness::{Block, Transaction},
+};
+
+use crate::{chunk_payload, ChunkHash, ChunkPayloadError};
+
+/// A block of `num_txs` random txs, about a quarter of them L1 msgs.
+fn random_block(rng: &mut impl RngCore, num_txs: usize) -> Block<Fr> {
+    let txs = (0..num_txs)
+        .map(|id| {
+            let (tx_type, rlp_signed) = if rng.gen_ratio(1, 4) {
+                let mut rlp = vec![0x7e; rng.gen_range(1..64)];
+                rng.fill_bytes(&mut rlp[1..]);
+                (TxType::L1Msg, rlp)
+            } else {
+                let mut data = vec![0; rng.gen_range(0..300)];
+                rng.fill_bytes(&mut data);
+                let req = TransactionRequest::new()
+                    .nonce(rng.next_u64())
+                    .gas(rng.next_u32())
+                    .gas_price(rng.next_u64())
+                    .value(rng.next_u64())
+                    .data(data);
+                let sig = Signature {
+                    r: rng.next_u64().into(),
+                    s: rng.next_u64().into(),
+                    v: 27,
+                };
+                (TxType::PreEip155, req.rlp_signed(&sig).to_vec())
+            };
+            Transaction {
+                id: id + 1,
+                hash: H256(keccak256(&rlp_signed)),
+                tx_type,
+                rlp_signed,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Block {
+        txs,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_chunk_payload_matches_data_hash() {
+    let mut rng = test_rng();
+    for num_txs in 0..32 {
+        let block = random_block(&mut rng, num_txs);
+        let payload = chunk_payload(&block).unwrap();
+
+        // the tx hashes committed to by the chunk's data hash
+        let data_bytes = ChunkHash::data_bytes(&block);
+        let committed_hashes = data_bytes[data_bytes.len() - 32 * num_txs..]
+            .chunks(32)
+            .zip(block.txs.iter())
+            .filter(|(_, tx)| !tx.tx_type.is_l1_msg())
+            .map(|(hash, _)| H256::from_slice(hash))
+            .collect::<Vec<_>>();
+
+        let tx_rlps = payload.tx_rlps();
+        assert_eq!(payload.num_txs(), committed_hashes.len());
+        assert_eq!(tx_rlps.len(), committed_hashes.len());
+        for (tx_rlp, hash) in tx_rlps.into_iter().zip(committed_hashes) {
+            assert_eq!(H256(keccak256(tx_rlp)), hash);
+        }
+
+        let rollup_data_size: usize = block
+            .txs
+            .iter()
+            .filter(|tx| !tx.tx_type.is_l1_msg())
+            .map(|tx| TX_DA_LENGTH_PREFIX_BYTES + tx.rlp_signed.len())
+            .sum();
+        assert_eq!(payload.as_bytes().len(), rollup_data_size);
+    }
+}
+
+#[test]
+fn test_chunk_tx_hash_root_inclusion() {
+    let mut rng = test_rng();
+    for num_txs in 0..16 {
+        let block = random_block(&mut rng, num_txs);
+        let root = tx_hash_root(&block.txs.iter().map(|tx| tx.hash).collect::<Vec<_>>());
+
+        // the tx hashes committed to by the chunk's data hash
+        let data_bytes = ChunkHash::data_bytes(&block);
+        let committed_hashes = data_bytes[data_bytes.len() - 32 * num_txs..]
+            .chunks(32)
+            .map(H256::from_slice)
+            .collect::<Vec<_>>();
+
+        for (index, hash) in committed_hashes.iter().enumerate() {
+            let proof = tx_inclusion_proof(&committed_hashes, index).unwrap();
+            assert!(verify_tx_inclusion(root, num_txs, index, *hash, &proof));
+        }
+    }
+}
+
+#[test]
+fn test_chunk_payload_of_mainnet_tx() {
+    // the tx is downloaded from https://etherscan.io/getRawTx?tx=0x1c5bd618bdbc575f71bfe0a54f09bca2997bbf6d90d4f371a509b05e2b3124e3
+    let rlp_signed = hex::decode("02f901e901833c3139842b27f14d86012309ce540083055ca8945f65f7b609678448494de4c87521cdf6cef1e93280b8e4fa558b7100000000000000000000000095ad61b0a150d79219dcf64e1e6cc01f0b64c4ce000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a0000000000000000000000000000000000000000000000000000000000000000100000000000000000000000016a217dedfacdf9c23edb84b57154f26a15848e60000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000028cad80bb7cf17e27c4c8f893f7945f65f7b609678448494de4c87521cdf6cef1e932e1a0d2dc2a0881b05440a4908cf506b4871b1f7eaa46ea0c5dfdcda5f52bc17164a4f8599495ad61b0a150d79219dcf64e1e6cc01f0b64c4cef842a0ba03decd934aae936605e9d437c401439ec4cefbad5795e0965100f929fe339ca0b36e2afa1a25492257090107ad99d079032e543c8dd1ffcd44cf14a96d3015ac80a0821193127789b107351f670025dd3b862f5836e5155f627a29741a251e8d28e8a07ea1e82b1bf6f29c5d0f1e4024acdb698086ac40c353704d7d5e301fb916f2e3")
+        .unwrap();
+    let hash = H256::from_str("0x1c5bd618bdbc575f71bfe0a54f09bca2997bbf6d90d4f371a509b05e2b3124e3")
+        .unwrap();
+    let block = Block::<Fr> {
+        txs: vec![
+            // the L1 msg is left out, whatever its hash
+            Transaction {
+                id: 1,
+                tx_type: TxType::L1Msg,
+                rlp_signed: vec![0x7e, 0xc0],
+                ..Default::default()
+            },
+            Transaction {
+                id: 2,
+                hash,
+                tx_type: TxType::Eip1559,
+                rlp_signed: rlp_signed.clone(),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let payload = chunk_payload(&block).unwrap();
+    assert_eq!(payload.num_txs(), 1);
+    // the tx is 0x1ed bytes long
+    assert_eq!(payload.as_bytes()[..4], [0x00, 0x00, 0x01, 0xed]);
+    assert_eq!(payload.as_bytes()[4..], rlp_signed);
+    assert_eq!(payload.tx_rlps(), vec![rlp_signed.as_slice()]);
+}
+
+#[test]
+fn test_chunk_payload_rejects_mismatched_hash() {
+    let mut rng = test_rng();
+    let mut block = random_block(&mut rng, 8);
+    let tx = block
+        .txs
+        .iter_mut()
+        .find(|tx| !tx.tx_type.is_l1_msg())
+        .unwrap();
+    tx.hash = H256::zero();
+    let (tx_id, found) = (tx.id, H256(keccak256(&tx.rlp_signed)));
+    assert_eq!(
+        chunk_payload(&block),
+        Err(ChunkPayloadError::HashMismatch {
+            tx_id,
+            expected: H256::zero(),
+            found
+        })
+    );
+}
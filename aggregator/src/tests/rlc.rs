@@ -1,2 +1,3 @@
+mod decomposition;
 mod dynamic_hashes;
 mod gates;
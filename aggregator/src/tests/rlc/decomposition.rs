@@ -0,0 +1,125 @@
+//! Tests the fixed-width decomposition chip
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::MockProver,
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use zkevm_circuits::util::Challenges;
+
+use crate::aggregation::{DecompositionChip, FieldWidth, RlcConfig};
+
+#[derive(Debug, Clone)]
+struct DecompositionTestCircuit {
+    value: Fr,
+    width: FieldWidth,
+    /// Bytes packed into the value instead of its decomposition, to check the
+    /// range lookup of the bytes
+    forged_bytes: Option<Vec<Fr>>,
+}
+
+impl Circuit<Fr> for DecompositionTestCircuit {
+    type Config = RlcConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let challenges = Challenges::construct(meta);
+        RlcConfig::configure(meta, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_byte_table(&mut layouter)?;
+
+        let mut first_pass = true;
+        layouter.assign_region(
+            || "test decomposition circuit",
+            |mut region| -> Result<(), Error> {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                config.init(&mut region)?;
+
+                let mut offset = 0;
+
+                let value = config.load_private(&mut region, &self.value, &mut offset)?;
+                match &self.forged_bytes {
+                    Some(bytes) => {
+                        let bytes = bytes
+                            .iter()
+                            .map(|byte| config.load_private(&mut region, byte, &mut offset))
+                            .collect::<Result<Vec<_>, Error>>()?;
+                        let packed = config.pack_bytes(&mut region, &bytes, &mut offset)?;
+                        region.constrain_equal(packed.cell(), value.cell())?;
+                    }
+                    None => {
+                        let bytes = DecompositionChip::new(&config).range_check(
+                            &mut region,
+                            &value,
+                            self.width,
+                            &mut offset,
+                        )?;
+                        assert_eq!(bytes.len(), self.width.num_bytes());
+                        for (byte_cell, byte) in bytes.iter().zip(self.value.to_bytes()) {
+                            byte_cell
+                                .value()
+                                .map(|&x| assert_eq!(x, Fr::from(byte as u64)));
+                        }
+                    }
+                }
+
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn is_satisfied(value: Fr, width: FieldWidth, forged_bytes: Option<Vec<Fr>>) -> bool {
+    let circuit = DecompositionTestCircuit {
+        value,
+        width,
+        forged_bytes,
+    };
+    let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+    prover.verify().is_ok()
+}
+
+#[test]
+fn test_decomposition() {
+    for (width, max) in [
+        (FieldWidth::U16, u16::MAX as u64),
+        (FieldWidth::U32, u32::MAX as u64),
+        (FieldWidth::U64, u64::MAX),
+    ] {
+        assert!(is_satisfied(Fr::zero(), width, None));
+        assert!(is_satisfied(Fr::from(0x1234), width, None));
+        assert!(is_satisfied(Fr::from(max), width, None));
+
+        // one above the max of the width
+        assert!(!is_satisfied(Fr::from(max) + Fr::one(), width, None));
+        // a negative value wraps around the field
+        assert!(!is_satisfied(-Fr::one(), width, None));
+    }
+
+    // 0x100 = 0x100 + 256 * 0x00, with a limb out of the byte range
+    assert!(is_satisfied(
+        Fr::from(0x100),
+        FieldWidth::U16,
+        Some(vec![Fr::zero(), Fr::one()])
+    ));
+    assert!(!is_satisfied(
+        Fr::from(0x100),
+        FieldWidth::U16,
+        Some(vec![Fr::from(0x100), Fr::zero()])
+    ));
+}
@@ -2,7 +2,6 @@
 
 use ark_std::test_rng;
 use halo2_proofs::{
-    arithmetic::Field,
     circuit::{Layouter, SimpleFloorPlanner},
     dev::MockProver,
     halo2curves::bn256::Fr,
@@ -43,6 +42,8 @@ impl Circuit<Fr> for ArithTestCircuit {
     ) -> Result<(), Error> {
         let mut rng = test_rng();
 
+        config.load_byte_table(&mut layouter)?;
+
         let mut first_pass = true;
         layouter.assign_region(
             || "test field circuit",
@@ -135,19 +136,22 @@ impl Circuit<Fr> for ArithTestCircuit {
                         config.rlc_with_flag(&mut region, &inputs, &f5, &flag, &mut offset)?;
                     region.constrain_equal(res.cell(), res_rec.cell())?;
                 }
-                // unit test: decomposition
+                // unit test: byte packing
                 {
-                    for _ in 0..10 {
-                        let tmp = Fr::random(&mut rng);
-                        let tmp = config.load_private(&mut region, &tmp, &mut offset)?;
-                        config.decomposition(&mut region, &tmp, &mut offset)?;
-                    }
+                    let bytes = [Fr::from(1), Fr::from(2), Fr::from(255)]
+                        .iter()
+                        .map(|byte| config.load_private(&mut region, byte, &mut offset))
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    let packed = config.pack_bytes(&mut region, &bytes, &mut offset)?;
+                    let expected =
+                        config.load_private(&mut region, &Fr::from(0xff0201), &mut offset)?;
+                    region.constrain_equal(packed.cell(), expected.cell())?;
                 }
                 // unit test: is smaller than
                 {
                     for _ in 0..10 {
-                        let a = Fr::from(rng.next_u64());
-                        let b = Fr::from(rng.next_u64());
+                        let a = Fr::from(rng.next_u64() & 0xffff);
+                        let b = Fr::from(rng.next_u64() & 0xffff);
                         let c = if a < b { Fr::one() } else { Fr::zero() };
                         let a = config.load_private(&mut region, &a, &mut offset)?;
                         let b = config.load_private(&mut region, &b, &mut offset)?;
@@ -157,7 +161,7 @@ impl Circuit<Fr> for ArithTestCircuit {
                     }
 
                     // equality check
-                    let a = Fr::from(rng.next_u64());
+                    let a = Fr::from(rng.next_u64() & 0xffff);
                     let b = a;
                     let c = Fr::zero();
                     let a = config.load_private(&mut region, &a, &mut offset)?;
@@ -270,3 +274,87 @@ fn test_field_ops() {
         assert!(prover.verify().is_err());
     }
 }
+
+#[derive(Default, Debug, Clone, Copy)]
+struct IsSmallerThanTestCircuit {
+    a: Fr,
+    b: Fr,
+    /// The claimed result of `a < b`
+    expected: Fr,
+}
+
+impl Circuit<Fr> for IsSmallerThanTestCircuit {
+    type Config = RlcConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let challenges = Challenges::construct(meta);
+        RlcConfig::configure(meta, challenges)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_byte_table(&mut layouter)?;
+
+        let mut first_pass = true;
+        layouter.assign_region(
+            || "test is smaller than circuit",
+            |mut region| -> Result<(), Error> {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                config.init(&mut region)?;
+
+                let mut offset = 0;
+
+                let a = config.load_private(&mut region, &self.a, &mut offset)?;
+                let b = config.load_private(&mut region, &self.b, &mut offset)?;
+                let expected = config.load_private(&mut region, &self.expected, &mut offset)?;
+                let res = config.is_smaller_than(&mut region, &a, &b, &mut offset)?;
+                region.constrain_equal(res.cell(), expected.cell())?;
+
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_is_smaller_than() {
+    let is_satisfied = |a: u64, b: u64, expected: bool| {
+        let circuit = IsSmallerThanTestCircuit {
+            a: Fr::from(a),
+            b: Fr::from(b),
+            expected: Fr::from(expected as u64),
+        };
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    };
+
+    for (a, b) in [
+        (0, 0),
+        (0, 1),
+        (1, 0),
+        (3, 4),
+        (4, 4),
+        (0xffff, 0),
+        (0, 0xffff),
+    ] {
+        assert!(is_satisfied(a, b, a < b));
+        // the result is fully determined by the inputs
+        assert!(!is_satisfied(a, b, a >= b));
+    }
+
+    // an input beyond 16 bits doesn't fit in the decomposition
+    assert!(!is_satisfied(0x10000, 0, false));
+    assert!(!is_satisfied(0, 0x10001, true));
+}
@@ -0,0 +1,120 @@
+use std::{path::PathBuf, process, thread, time::Duration};
+
+use serde_json::{json, Value};
+
+use crate::{
+    service::{Prover, ProverService, TaskId, TaskStatus},
+    storage::{ArtifactKind, LocalStorage, ProofStorage},
+    ChunkHash,
+};
+
+/// A prover whose chunk proofs are the number of traces, and whose batch
+/// proofs are the concatenation of the chunk proofs.
+struct EchoProver;
+
+impl Prover for EchoProver {
+    fn prove_chunk(&mut self, traces: &[Value]) -> Result<Vec<u8>, String> {
+        if traces.is_empty() {
+            return Err("no traces".to_string());
+        }
+        Ok(vec![traces.len() as u8])
+    }
+
+    fn prove_batch(
+        &mut self,
+        _chunks_with_padding: &[ChunkHash],
+        chunk_proofs: &[Vec<u8>],
+    ) -> Result<Vec<u8>, String> {
+        Ok(chunk_proofs.concat())
+    }
+}
+
+fn service_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("prover-service-{name}-{}", process::id()))
+}
+
+fn new_service(name: &str) -> ProverService {
+    ProverService::spawn(EchoProver, LocalStorage::open(service_dir(name)).unwrap()).unwrap()
+}
+
+fn wait(service: &ProverService, task_id: TaskId) -> TaskStatus {
+    loop {
+        match service.get_status(task_id).unwrap() {
+            TaskStatus::Queued | TaskStatus::Running => thread::sleep(Duration::from_millis(10)),
+            status => return status,
+        }
+    }
+}
+
+fn call(service: &ProverService, method: &str, params: Value) -> Value {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    serde_json::from_str(&service.handle_request(&request.to_string())).unwrap()
+}
+
+#[test]
+fn test_prover_service_tasks() {
+    let service = new_service("tasks");
+
+    let chunk_0 = service.prove_chunk(vec![json!({}), json!({})]).unwrap();
+    let chunk_1 = service.prove_chunk(vec![json!({})]).unwrap();
+    let batch = service
+        .prove_batch(vec![ChunkHash::default(); 2], vec![chunk_0, chunk_1])
+        .unwrap();
+    let failed = service.prove_chunk(vec![]).unwrap();
+
+    assert_eq!(wait(&service, batch), TaskStatus::Done);
+    assert_eq!(service.get_proof(chunk_0), Some(vec![2]));
+    assert_eq!(service.get_proof(batch), Some(vec![2, 1]));
+
+    assert_eq!(
+        wait(&service, failed),
+        TaskStatus::Failed("no traces".to_string())
+    );
+    assert_eq!(service.get_proof(failed), None);
+    assert_eq!(service.get_status(failed + 1), None);
+}
+
+#[test]
+fn test_prover_service_restart() {
+    let service = new_service("restart");
+    let task_id = service.prove_chunk(vec![json!({})]).unwrap();
+    assert_eq!(wait(&service, task_id), TaskStatus::Done);
+    drop(service);
+
+    // the task ids continue after the restart, so the proof of the first task
+    // is not overwritten
+    let service = new_service("restart");
+    let next_task_id = service.prove_chunk(vec![json!({}), json!({})]).unwrap();
+    assert!(next_task_id > task_id);
+    assert_eq!(wait(&service, next_task_id), TaskStatus::Done);
+    drop(service);
+
+    let storage = LocalStorage::open(service_dir("restart")).unwrap();
+    assert_eq!(
+        storage
+            .get(ArtifactKind::Proof, &task_id.to_string())
+            .unwrap(),
+        vec![1]
+    );
+}
+
+#[test]
+fn test_prover_service_json_rpc() {
+    let service = new_service("json-rpc");
+
+    let response = call(&service, "prove_chunk", json!({ "traces": [{}] }));
+    let task_id = response["result"].as_u64().unwrap();
+    wait(&service, task_id);
+
+    let response = call(&service, "get_status", json!({ "task_id": task_id }));
+    assert_eq!(response["result"], json!({ "status": "done" }));
+    let response = call(&service, "get_proof", json!({ "task_id": task_id }));
+    assert_eq!(response["result"], json!([1]));
+
+    let response = call(&service, "get_status", json!({ "task_id": task_id + 1 }));
+    assert_eq!(response["error"]["code"], json!(-32001));
+    let response = call(&service, "prove_batch", json!({ "chunks": [] }));
+    assert_eq!(response["error"]["code"], json!(-32602));
+    let response = call(&service, "unknown", Value::Null);
+    assert_eq!(response["error"]["code"], json!(-32601));
+}
@@ -0,0 +1,204 @@
+//! Negative tests for the aggregation circuit.
+//!
+//! Each [`Mutation`] flips a single bit (or flag) of either the chunk data the
+//! prover works on or the public instance it is checked against, and the
+//! MockProver is expected to reject the result.
+
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use snark_verifier_sdk::CircuitExt;
+
+use crate::{
+    aggregation::AggregationCircuit,
+    constants::{ACC_LEN, DIGEST_LEN, MAX_AGG_SNARKS},
+    util::CHECK_WITNESS,
+};
+
+use super::aggregation::build_new_aggregation_circuit;
+
+const LOG_DEGREE: u32 = 20;
+const NUM_REAL_CHUNKS: usize = 2;
+
+/// A field of a chunk that can be corrupted.
+#[derive(Debug, Clone, Copy)]
+enum ChunkField {
+    PrevStateRoot,
+    PostStateRoot,
+    WithdrawRoot,
+    DataHash,
+    PrevWithdrawRoot,
+}
+
+impl ChunkField {
+    const ALL: [Self; 5] = [
+        Self::PrevStateRoot,
+        Self::PostStateRoot,
+        Self::WithdrawRoot,
+        Self::DataHash,
+        Self::PrevWithdrawRoot,
+    ];
+}
+
+/// A single corruption of the aggregation circuit's witness or instance.
+#[derive(Debug, Clone, Copy)]
+enum Mutation {
+    /// Flip a bit of a chunk field in the witness; the snarks and the
+    /// instance are left untouched.
+    Chunk {
+        chunk: usize,
+        field: ChunkField,
+        byte: usize,
+        bit: u8,
+    },
+    /// Move the boundary between real and padding chunks by one, toggling the
+    /// padding flag of the chunk at the boundary.
+    Padding { extra_valid_chunk: bool },
+    /// Flip a bit of the batch public input hash in the instance.
+    BatchPiHash { byte: usize, bit: u8 },
+    /// Flip a bit of the number of valid chunks in the instance.
+    NumValidChunks { bit: u8 },
+}
+
+impl Mutation {
+    /// One mutation of each kind, touching the first real chunk, the last real
+    /// chunk and the first padding chunk.
+    fn sample() -> Vec<Self> {
+        let chunks = [0, NUM_REAL_CHUNKS - 1, NUM_REAL_CHUNKS];
+        chunks
+            .into_iter()
+            .flat_map(|chunk| {
+                ChunkField::ALL.into_iter().map(move |field| Self::Chunk {
+                    chunk,
+                    field,
+                    byte: DIGEST_LEN - 1,
+                    bit: 0,
+                })
+            })
+            .chain([
+                Self::Padding {
+                    extra_valid_chunk: true,
+                },
+                Self::Padding {
+                    extra_valid_chunk: false,
+                },
+                Self::BatchPiHash { byte: 0, bit: 7 },
+                Self::BatchPiHash {
+                    byte: DIGEST_LEN - 1,
+                    bit: 0,
+                },
+                Self::NumValidChunks { bit: 0 },
+            ])
+            .collect()
+    }
+
+    /// Every single-bit mutation.
+    fn exhaustive() -> Vec<Self> {
+        let bits = || (0..DIGEST_LEN).flat_map(|byte| (0..8).map(move |bit| (byte, bit)));
+        (0..MAX_AGG_SNARKS)
+            .flat_map(|chunk| {
+                ChunkField::ALL.into_iter().flat_map(move |field| {
+                    bits().map(move |(byte, bit)| Self::Chunk {
+                        chunk,
+                        field,
+                        byte,
+                        bit,
+                    })
+                })
+            })
+            .chain([
+                Self::Padding {
+                    extra_valid_chunk: true,
+                },
+                Self::Padding {
+                    extra_valid_chunk: false,
+                },
+            ])
+            .chain(bits().map(|(byte, bit)| Self::BatchPiHash { byte, bit }))
+            .chain((0..8).map(|bit| Self::NumValidChunks { bit }))
+            .collect()
+    }
+
+    fn apply(&self, circuit: &mut AggregationCircuit, instance: &mut [Fr]) {
+        let batch_hash = &mut circuit.batch_hash;
+        match *self {
+            Self::Chunk {
+                chunk,
+                field,
+                byte,
+                bit,
+            } => {
+                let chunk = &mut batch_hash.chunks_with_padding[chunk];
+                let hash = match field {
+                    ChunkField::PrevStateRoot => &mut chunk.prev_state_root,
+                    ChunkField::PostStateRoot => &mut chunk.post_state_root,
+                    ChunkField::WithdrawRoot => &mut chunk.withdraw_root,
+                    ChunkField::DataHash => &mut chunk.data_hash,
+                    ChunkField::PrevWithdrawRoot => &mut chunk.prev_withdraw_root,
+                };
+                hash.0[byte] ^= 1 << bit;
+            }
+            Self::Padding { extra_valid_chunk } => {
+                if extra_valid_chunk {
+                    batch_hash.chunks_with_padding[batch_hash.number_of_valid_chunks].is_padding =
+                        false;
+                    batch_hash.number_of_valid_chunks += 1;
+                } else {
+                    batch_hash.number_of_valid_chunks -= 1;
+                    batch_hash.chunks_with_padding[batch_hash.number_of_valid_chunks].is_padding =
+                        true;
+                }
+            }
+            Self::BatchPiHash { byte, bit } => {
+                let value = batch_hash.public_input_hash.0[byte] ^ (1 << bit);
+                instance[ACC_LEN + byte] = Fr::from(value as u64);
+            }
+            Self::NumValidChunks { bit } => {
+                let value = batch_hash.number_of_valid_chunks ^ (1 << bit);
+                instance[ACC_LEN + DIGEST_LEN] = Fr::from(value as u64);
+            }
+        }
+    }
+}
+
+/// Returns true if the constraints of the mutated circuit are not satisfied.
+/// The witness sanity checks of the prover are turned off, so that the
+/// mutation reaches the constraints, and witness generation must still
+/// succeed: a mutation which only trips the prover says nothing about the
+/// soundness of the circuit.
+fn is_rejected(circuit: &AggregationCircuit, mutation: Mutation) -> bool {
+    let mut circuit = circuit.clone();
+    let mut instance = circuit.instances().remove(0);
+    mutation.apply(&mut circuit, &mut instance);
+
+    CHECK_WITNESS.with(|check| check.set(false));
+    let prover = MockProver::<Fr>::run(LOG_DEGREE, &circuit, vec![instance]);
+    CHECK_WITNESS.with(|check| check.set(true));
+
+    prover
+        .unwrap_or_else(|e| panic!("witness generation failed for {mutation:?}: {e:?}"))
+        .verify_par()
+        .is_err()
+}
+
+fn assert_all_rejected(mutations: Vec<Mutation>) {
+    let circuit = build_new_aggregation_circuit(NUM_REAL_CHUNKS);
+
+    let prover = MockProver::<Fr>::run(LOG_DEGREE, &circuit, circuit.instances()).unwrap();
+    prover.assert_satisfied_par();
+
+    let accepted = mutations
+        .into_iter()
+        .filter(|mutation| !is_rejected(&circuit, *mutation))
+        .collect::<Vec<_>>();
+    assert!(accepted.is_empty(), "accepted mutations: {accepted:?}");
+}
+
+#[test]
+fn test_aggregation_circuit_rejects_corrupted_inputs() {
+    assert_all_rejected(Mutation::sample());
+}
+
+#[ignore = "it takes too much time"]
+#[test]
+fn test_aggregation_circuit_rejects_all_single_bit_corruptions() {
+    assert_all_rejected(Mutation::exhaustive());
+}
@@ -0,0 +1,38 @@
+use std::{fs, process};
+
+use crate::storage::{ArtifactKind, LocalStorage, ProofStorage, StorageError};
+
+#[test]
+fn test_local_storage_integrity() {
+    let dir = std::env::temp_dir().join(format!("proof-storage-{}", process::id()));
+    let storage = LocalStorage::open(&dir).unwrap();
+
+    assert!(!storage.contains(ArtifactKind::Proof, "chunk_0").unwrap());
+    assert!(matches!(
+        storage.get(ArtifactKind::Proof, "chunk_0"),
+        Err(StorageError::NotFound(_))
+    ));
+
+    let hash = storage
+        .put(ArtifactKind::Proof, "chunk_0", &[1, 2, 3])
+        .unwrap();
+    assert!(storage.contains(ArtifactKind::Proof, "chunk_0").unwrap());
+    assert!(!storage.contains(ArtifactKind::Vk, "chunk_0").unwrap());
+    assert_eq!(
+        storage.hash(ArtifactKind::Proof, "chunk_0").unwrap(),
+        Some(hash)
+    );
+    assert_eq!(
+        storage.get(ArtifactKind::Proof, "chunk_0").unwrap(),
+        vec![1, 2, 3]
+    );
+
+    // tamper with the stored proof
+    fs::write(dir.join("proof/chunk_0"), [1, 2, 4]).unwrap();
+    assert!(matches!(
+        storage.get(ArtifactKind::Proof, "chunk_0"),
+        Err(StorageError::Integrity { expected, .. }) if expected == hash
+    ));
+
+    fs::remove_dir_all(dir).unwrap();
+}
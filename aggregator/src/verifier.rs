@@ -0,0 +1,189 @@
+//! Verification artifacts for verifiers other than the Yul EVM verifier.
+//!
+//! A [`VerifierArtifacts`] bundles everything a native verifier (e.g. a Rust
+//! light client) needs to check a proof produced by the compression or the
+//! aggregation circuit:
+//! - the verifying key, serialized with halo2's `SerdeFormat::RawBytes`
+//! - the KZG succinct verifying key (`g[0]`, `g2`, `s_g2`), compressed points
+//! - the layout of the public inputs, including the accumulator limbs
+//! - the transcript and PCS used to generate the proof
+//!
+//! The artifacts are serialized as JSON; byte strings are hex encoded.
+
+use std::{fs::File, path::Path};
+
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::GroupEncoding,
+    },
+    plonk::VerifyingKey,
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use serde::{Deserialize, Serialize};
+use snark_verifier_sdk::CircuitExt;
+
+use crate::{
+    constants::{ACC_LEN, BITS, DIGEST_LEN, LIMBS},
+    AggregationCircuit, CompressionCircuit,
+};
+
+/// Version of the artifact format. Bump it on breaking changes.
+pub const VERIFIER_ARTIFACTS_VERSION: u32 = 1;
+
+/// Transcript used by the prover to derive challenges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptKind {
+    /// Poseidon transcript, used for snarks that are later aggregated.
+    Poseidon,
+    /// Keccak256 transcript, used for proofs verified by the EVM verifier.
+    Keccak256,
+}
+
+/// A named range of public inputs within an instance column.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceField {
+    /// Name of the field, e.g. `accumulator` or `batch_public_input_hash`.
+    pub name: String,
+    /// Index of the instance column.
+    pub column: usize,
+    /// Offset of the first element within the column.
+    pub offset: usize,
+    /// Number of field elements.
+    pub len: usize,
+}
+
+impl InstanceField {
+    fn new(name: &str, offset: usize, len: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            column: 0,
+            offset,
+            len,
+        }
+    }
+}
+
+/// Verification artifacts of a circuit, in a documented serde format.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifierArtifacts {
+    /// Version of this format, see [`VERIFIER_ARTIFACTS_VERSION`].
+    pub version: u32,
+    /// Name of the circuit the artifacts belong to.
+    pub circuit: String,
+    /// Degree of the circuit.
+    pub degree: u32,
+    /// Verifying key, in halo2 `SerdeFormat::RawBytes`.
+    #[serde(with = "hex_bytes")]
+    pub vk: Vec<u8>,
+    /// Compressed `g[0]` of the KZG parameters.
+    #[serde(with = "hex_bytes")]
+    pub svk_g: Vec<u8>,
+    /// Compressed `g2` of the KZG parameters.
+    #[serde(with = "hex_bytes")]
+    pub g2: Vec<u8>,
+    /// Compressed `s_g2` of the KZG parameters.
+    #[serde(with = "hex_bytes")]
+    pub s_g2: Vec<u8>,
+    /// Number of public inputs per instance column.
+    pub num_instance: Vec<usize>,
+    /// Positions `(column, row)` of the accumulator limbs, if any.
+    /// Each of the 4 base field coordinates of the accumulator is
+    /// decomposed into `num_limbs` limbs of `limb_bits` bits.
+    pub accumulator_indices: Option<Vec<(usize, usize)>>,
+    /// Number of limbs per accumulator coordinate.
+    pub num_limbs: usize,
+    /// Number of bits per accumulator limb.
+    pub limb_bits: usize,
+    /// Named ranges of the public inputs.
+    pub instance_layout: Vec<InstanceField>,
+    /// Transcript used to generate the proof.
+    pub transcript: TranscriptKind,
+    /// Polynomial commitment scheme, always `kzg_bdfg21` (SHPLONK).
+    pub pcs: String,
+}
+
+impl VerifierArtifacts {
+    /// Build the artifacts of a circuit from its verifying key.
+    pub fn new<C: CircuitExt<Fr>>(
+        name: &str,
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        circuit: &C,
+        instance_layout: Vec<InstanceField>,
+        transcript: TranscriptKind,
+    ) -> Self {
+        Self {
+            version: VERIFIER_ARTIFACTS_VERSION,
+            circuit: name.to_owned(),
+            degree: params.k(),
+            vk: vk.to_bytes(SerdeFormat::RawBytes),
+            svk_g: params.get_g()[0].to_bytes().as_ref().to_vec(),
+            g2: params.g2().to_bytes().as_ref().to_vec(),
+            s_g2: params.s_g2().to_bytes().as_ref().to_vec(),
+            num_instance: circuit.num_instance(),
+            accumulator_indices: C::accumulator_indices(),
+            num_limbs: LIMBS,
+            limb_bits: BITS,
+            instance_layout,
+            transcript,
+            pcs: "kzg_bdfg21".to_owned(),
+        }
+    }
+
+    /// Artifacts of a compression circuit.
+    pub fn from_compression_circuit(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        circuit: &CompressionCircuit,
+        transcript: TranscriptKind,
+    ) -> Self {
+        let num_instance: usize = circuit.num_instance().iter().sum();
+        let layout = vec![
+            InstanceField::new("accumulator", 0, ACC_LEN),
+            InstanceField::new("inner_instances", ACC_LEN, num_instance - ACC_LEN),
+        ];
+        Self::new("compression", params, vk, circuit, layout, transcript)
+    }
+
+    /// Artifacts of an aggregation circuit.
+    pub fn from_aggregation_circuit(
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+        circuit: &AggregationCircuit,
+        transcript: TranscriptKind,
+    ) -> Self {
+        let layout = vec![
+            InstanceField::new("accumulator", 0, ACC_LEN),
+            InstanceField::new("batch_public_input_hash", ACC_LEN, DIGEST_LEN),
+            InstanceField::new("num_valid_chunks", ACC_LEN + DIGEST_LEN, 1),
+        ];
+        Self::new("aggregation", params, vk, circuit, layout, transcript)
+    }
+
+    /// Write the artifacts as JSON.
+    pub fn dump(&self, path: &Path) -> Result<(), std::io::Error> {
+        serde_json::to_writer_pretty(File::create(path)?, self)?;
+        Ok(())
+    }
+
+    /// Read artifacts from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, std::io::Error> {
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+    }
+}
@@ -2,60 +2,102 @@
 //! types from geth / web3 and outputs the circuit inputs.
 
 mod access;
+mod batch;
 mod block;
+mod bundle;
 mod call;
+mod call_frame;
+mod checkpoint;
 mod execution;
+mod forced_exit;
+mod gas_audit;
 mod input_state_ref;
+mod l1_fee;
+mod layout;
+#[cfg(test)]
+mod opcode_fuzz_tests;
+mod receipt;
+mod row_usage;
+mod signature;
+mod state_diff;
+mod system_call;
+mod trace_source;
 #[cfg(test)]
 mod tracer_tests;
 mod transaction;
+mod transition;
+mod withdraw_trie;
 
 use self::access::gen_state_access_trace;
 pub use self::block::BlockHead;
 use crate::{
-    error::Error,
-    evm::opcodes::{gen_associated_ops, gen_begin_tx_ops, gen_end_tx_ops},
+    error::{CircuitCapacityError, Error, SubCircuit},
+    evm::opcodes::{gen_associated_ops, gen_associated_steps},
     operation::{self, CallContextField, Operation, RWCounter, StartOp, StorageOp, RW},
     rpc::GethClient,
-    state_db::{self, CodeDB, StateDB},
+    state_db::{self, decode_zktrie_proof, verify_proofs, CodeDB, ProofFormat, StateDB},
 };
 pub use access::{Access, AccessSet, AccessValue, CodeSource};
+pub use batch::{BatchBuilder, ChunkInputs};
 pub use block::{Block, BlockContext};
-pub use call::{Call, CallContext, CallKind};
+pub use bundle::BlockBundle;
+pub use call::{BytesView, Call, CallContext, CallKind};
+pub use call_frame::{CallFrameEntry, CallFrameWitness};
+pub use checkpoint::BuilderCheckpoint;
 use core::fmt::Debug;
 use eth_types::{
     self,
-    evm_types::OpcodeId,
+    evm_types::{
+        GasSchedules, OpcodeId, MAX_CODE_SIZE, MAX_EXPANDED_MEMORY_ADDRESS, MAX_INIT_CODE_SIZE,
+    },
     geth_types,
     sign_types::{pk_bytes_le, pk_bytes_swap_endianness, SignData},
-    Address, GethExecStep, GethExecTrace, ToBigEndian, ToWord, Word, H256, U256,
+    Address, GethExecStep, ToBigEndian, ToWord, Word, H256, U256,
 };
 use ethers_core::{
     k256::ecdsa::SigningKey,
-    types::{Bytes, Signature, TransactionRequest},
+    types::{Bytes, NameOrAddress, Signature, TransactionReceipt, TransactionRequest},
 };
 use ethers_providers::JsonRpcClient;
 pub use execution::{
-    CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep, EcAddOp, EcMulOp,
-    EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
-    PrecompileEvent, PrecompileEvents, N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
+    BigModExp, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder, CopyStep, EcAddOp,
+    EcMulOp, EcPairingOp, EcPairingPair, ExecState, ExecStep, ExpEvent, ExpStep, NumberOrHash,
+    PointEvaluationOp, PrecompileEvent, PrecompileEvents, MODEXP_LIMB_BYTES, MODEXP_SIZE_LIMIT,
+    N_BYTES_PER_PAIR, N_PAIRING_PER_OP,
 };
+pub use forced_exit::ForcedExit;
+use futures::{stream, try_join, StreamExt};
+pub use gas_audit::{expected_gas_cost, GasAuditReport, GasAuditor, GasDivergence};
 use hex::decode_to_slice;
+use serde::{Deserialize, Serialize};
 
 use ethers_core::utils::keccak256;
 pub use input_state_ref::CircuitInputStateRef;
 use itertools::Itertools;
+pub use l1_fee::{Bernoulli, Curie, L1FeeConfig, L1FeeHardfork, L1GasPriceOracle};
+pub use layout::ChunkLayout;
 use log::warn;
+pub use row_usage::{keccak_rows, BlockRowUsage, FitsReport, RowUsageEstimate};
+pub use signature::{verify_tx_signatures, verify_unverified_senders};
+pub use state_diff::{AccountDiff, BlockStateDiff, Change};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     iter,
 };
+pub use system_call::{insert_system_calls, ChainSpec, SystemCall, SystemCallTrace};
+pub use trace_source::{StreamedTrace, TraceSource};
 pub use transaction::{
-    Transaction, TransactionContext, TxL1Fee, TX_L1_COMMIT_EXTRA_COST, TX_L1_FEE_PRECISION,
+    Transaction, TransactionContext, TxL1Fee, TX_DA_LENGTH_PREFIX_BYTES, TX_L1_COMMIT_EXTRA_COST,
+    TX_L1_FEE_PRECISION,
+};
+pub use transition::{
+    check_transition, check_tx_transitions, transition_graph_dot, transition_graph_json,
+    TransitionNode,
 };
+pub use withdraw_trie::{sent_message_hash, WithdrawTrie, SENT_MESSAGE_TOPIC};
 
 /// Setup parameters for ECC-related precompile calls.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PrecompileEcParams {
     /// Maximum number of EcAdd ops supported in one block.
     pub ec_add: usize,
@@ -66,7 +108,7 @@ pub struct PrecompileEcParams {
 }
 
 /// Circuit Setup Parameters
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct CircuitsParams {
     /// Maximum number of rw operations in the state circuit (RwTable length /
     /// number of rows). This must be at least the number of rw operations
@@ -81,6 +123,11 @@ pub struct CircuitsParams {
     pub max_rlp_rows: usize,
     /// Max amount of rows that the CopyCircuit can have.
     pub max_copy_rows: usize,
+    /// Pad the CopyCircuit only with the rows that the last copy event can
+    /// query, leaving the rest up to `max_copy_rows` inactive. The fixed
+    /// columns do not depend on it, but the prover and verifier must agree
+    /// on it, so it is a parameter of the circuit rather than of a witness.
+    pub copy_tight_padding: bool,
     /// Maximum number of inner blocks in a batch
     pub max_inner_blocks: usize,
     /// Max number of steps that the ExpCircuit can have. Each step is further
@@ -88,6 +135,9 @@ pub struct CircuitsParams {
     pub max_exp_steps: usize,
     /// Maximum number of bytes supported in the Bytecode Circuit
     pub max_bytecode: usize,
+    /// Maximum size of the code of a contract (EIP-170), which is chain
+    /// specific. With EIP-3860, the maximum size of init code is twice it.
+    pub max_code_size: usize,
     /// Pad evm circuit number of rows.
     /// When 0, the EVM circuit number of rows will be dynamically calculated,
     /// so the same circuit will not be able to proof different witnesses.
@@ -104,6 +154,9 @@ pub struct CircuitsParams {
     pub max_keccak_rows: usize,
     /// Max number of ECC-related ops supported in the ECC circuit.
     pub max_ec_ops: PrecompileEcParams,
+    /// Max amount of rows that the modexp ops of a block can take, see
+    /// [`BigModExp::num_rows`].
+    pub max_modexp_rows: usize,
 }
 
 impl Default for CircuitsParams {
@@ -117,9 +170,11 @@ impl Default for CircuitsParams {
             // TODO: Check whether this value is correct or we should increase/decrease based on
             // this lib tests
             max_copy_rows: 2000,
+            copy_tight_padding: false,
             max_mpt_rows: 1000,
             max_exp_steps: 1000,
             max_bytecode: 512,
+            max_code_size: MAX_CODE_SIZE as usize,
             max_evm_rows: 0,
             max_keccak_rows: 0,
             max_rlp_rows: 1000,
@@ -128,8 +183,68 @@ impl Default for CircuitsParams {
                 ec_mul: 50,
                 ec_pairing: 2,
             },
+            // two modexp calls with operands at the size limit
+            max_modexp_rows: 2 * BigModExp::MAX_ROWS,
+        }
+    }
+}
+
+impl CircuitsParams {
+    /// Parameters sized to the block: the capacities that the builder can
+    /// measure are left at 0 (unlimited) while handling the block, and
+    /// resolved by [`CircuitInputBuilder::finalize_params`]. The EVM circuit
+    /// rows stay dynamic.
+    pub fn auto() -> Self {
+        Self {
+            max_rws: 0,
+            max_txs: 0,
+            max_calldata: 0,
+            max_rlp_rows: 0,
+            max_mpt_rows: 0,
+            max_inner_blocks: 0,
+            max_copy_rows: 0,
+            max_exp_steps: 0,
+            max_bytecode: 0,
+            max_evm_rows: 0,
+            max_keccak_rows: 0,
+            max_ec_ops: PrecompileEcParams {
+                ec_add: 0,
+                ec_mul: 0,
+                ec_pairing: 0,
+            },
+            max_modexp_rows: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Maximum size of init code in a creation tx or a CREATE, CREATE2.
+    pub fn max_init_code_size(&self) -> u64 {
+        if cfg!(feature = "shanghai") {
+            2 * self.max_code_size as u64
+        } else {
+            MAX_INIT_CODE_SIZE
         }
     }
+
+    /// Check that the circuits can be built with these parameters. The code
+    /// size limits are parameters of the EVM circuit, which compares them
+    /// with sizes of memory, so they can't exceed the maximum memory address.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.max_code_size == 0 || self.max_code_size as u64 > MAX_EXPANDED_MEMORY_ADDRESS {
+            return Err(Error::InvalidCircuitsParams(format!(
+                "max_code_size {} is out of the range 1..={}",
+                self.max_code_size, MAX_EXPANDED_MEMORY_ADDRESS
+            )));
+        }
+        if self.max_init_code_size() > MAX_EXPANDED_MEMORY_ADDRESS {
+            return Err(Error::InvalidCircuitsParams(format!(
+                "max init code size {} exceeds the maximum memory address {}",
+                self.max_init_code_size(),
+                MAX_EXPANDED_MEMORY_ADDRESS
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Builder to generate a complete circuit input from data gathered from a geth
@@ -160,6 +275,8 @@ pub struct CircuitInputBuilder {
     pub block: Block,
     /// Block Context
     pub block_ctx: BlockContext,
+    /// Auditor of the gas cost of the steps, if enabled
+    pub gas_auditor: Option<GasAuditor>,
 }
 
 impl<'a> CircuitInputBuilder {
@@ -171,6 +288,7 @@ impl<'a> CircuitInputBuilder {
             code_db,
             block: block.clone(),
             block_ctx: BlockContext::new(),
+            gas_auditor: None,
         }
     }
     /// Create a new CircuitInputBuilder from the given `eth_block` and
@@ -225,7 +343,18 @@ impl<'a> CircuitInputBuilder {
             ),
         );
 
-        Transaction::new(call_id, &self.sdb, &mut self.code_db, eth_tx, is_success)
+        let block_num = eth_tx
+            .block_number
+            .ok_or(Error::EthTypeError(eth_types::Error::IncompleteBlock))?;
+        let l1_fee_hardfork = self.block.l1_fee_config.hardfork(block_num.as_u64());
+        Transaction::new(
+            call_id,
+            &self.sdb,
+            &mut self.code_db,
+            eth_tx,
+            is_success,
+            l1_fee_hardfork,
+        )
     }
 
     /// Iterate over all generated CallContext RwCounterEndOfReversion
@@ -249,6 +378,29 @@ impl<'a> CircuitInputBuilder {
         }
     }
 
+    /// Handle a block where the unsigned txs in `senders` (tx hash to sender)
+    /// are sent by the caller-provided address, e.g. to replay system txs in
+    /// a simulation. These txs are marked with
+    /// [`Transaction::unverified_sender`], which the tx circuit exposes in the
+    /// tx table instead of recovering the sender from the signature.
+    pub fn handle_block_with_unverified_senders(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        senders: &HashMap<H256, Address>,
+    ) -> Result<(), Error> {
+        verify_unverified_senders(&eth_block.transactions, senders)?;
+        let mut eth_block = eth_block.clone();
+        for tx in eth_block.transactions.iter_mut() {
+            if let Some(from) = senders.get(&tx.hash) {
+                log::warn!("tx {:?} is sent by unverified sender {:?}", tx.hash, from);
+                tx.from = *from;
+                self.block_ctx.unverified_senders.insert(tx.hash);
+            }
+        }
+        self.handle_block(&eth_block, geth_traces)
+    }
+
     /// Handle a block by handling each transaction to generate all the
     /// associated operations.
     pub fn handle_block(
@@ -258,6 +410,17 @@ impl<'a> CircuitInputBuilder {
     ) -> Result<(), Error> {
         self.handle_block_inner(eth_block, geth_traces, true, true)
     }
+
+    /// Like [`Self::handle_block`], reading the trace of each tx from a
+    /// [`TraceSource`], e.g. a [`StreamedTrace`] for txs whose traces are too
+    /// large to be held in memory.
+    pub fn handle_block_from_sources<T: TraceSource>(
+        &mut self,
+        eth_block: &EthBlock,
+        traces: &[T],
+    ) -> Result<(), Error> {
+        self.handle_block_txs(eth_block, traces, true, true, &mut |_| {})
+    }
     /// Handle a block by handling each transaction to generate all the
     /// associated operations.
     pub fn handle_block_inner(
@@ -267,15 +430,48 @@ impl<'a> CircuitInputBuilder {
         handle_rwc_reversion: bool,
         check_last_tx: bool,
     ) -> Result<(), Error> {
+        self.handle_block_txs(
+            eth_block,
+            geth_traces,
+            handle_rwc_reversion,
+            check_last_tx,
+            &mut |_| {},
+        )
+    }
+
+    /// Handle the txs of a block from `self.block_ctx.next_tx` on, calling
+    /// `on_tx` after each of them.
+    fn handle_block_txs<T: TraceSource>(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[T],
+        handle_rwc_reversion: bool,
+        check_last_tx: bool,
+        on_tx: &mut dyn FnMut(&Self),
+    ) -> Result<(), Error> {
+        self.block.circuits_params.validate()?;
         // accumulates gas across all txs in the block
         log::info!(
             "handling block {:?}, tx num {}",
             eth_block.number,
             eth_block.transactions.len()
         );
-        for (tx_index, tx) in eth_block.transactions.iter().enumerate() {
+        verify_tx_signatures(&eth_block.transactions, self.block.chain_id)?;
+        self.block
+            .chain_spec
+            .check_system_calls(&eth_block.transactions)?;
+        if self.block.withdraw_trie.is_none() {
+            self.block.withdraw_trie = Some(WithdrawTrie::from_sdb(&self.sdb));
+        }
+        let first_tx = self.block_ctx.next_tx;
+        if first_tx > 0 {
+            log::info!("resuming block {:?} at tx {first_tx}", eth_block.number);
+        }
+        for (tx_index, tx) in eth_block.transactions.iter().enumerate().skip(first_tx) {
+            self.block_ctx.next_tx = tx_index + 1;
             let batch_tx_idx = self.block.txs.len();
-            if self.block.txs.len() >= self.block.circuits_params.max_txs {
+            let max_txs = self.block.circuits_params.max_txs;
+            if max_txs != 0 && self.block.txs.len() >= max_txs {
                 log::warn!(
                     "skip tx outside MAX_TX limit {}, {}th tx(inner idx: {}) {:?}",
                     self.block.circuits_params.max_txs,
@@ -309,7 +505,10 @@ impl<'a> CircuitInputBuilder {
                 self.block_ctx.rwc,
                 self.block_ctx.cumulative_gas_used
             );
+            on_tx(self);
         }
+        self.block_ctx.next_tx = 0;
+        self.end_inner_block(eth_block);
         if handle_rwc_reversion {
             self.set_value_ops_call_context_rwc_eor();
             self.set_end_block()?;
@@ -321,6 +520,25 @@ impl<'a> CircuitInputBuilder {
         Ok(())
     }
 
+    /// Close the block just handled with an EndInnerBlock step appended to the
+    /// last transaction, so that the EVM circuit proves the boundary between
+    /// the inner blocks of a chunk.  Consecutive empty blocks each add their
+    /// own step to the same transaction.
+    fn end_inner_block(&mut self, eth_block: &EthBlock) {
+        let rwc = self.block_ctx.rwc;
+        match self.block.txs.last_mut() {
+            Some(tx) => tx.steps_mut().push(ExecStep {
+                exec_state: ExecState::EndInnerBlock,
+                rwc,
+                ..Default::default()
+            }),
+            None => log::warn!(
+                "block {:?} has no tx before it, no EndInnerBlock step generated",
+                eth_block.number
+            ),
+        }
+    }
+
     fn print_rw_usage(&self) {
         // opcode -> (count, mem_rw_len, stack_rw_len)
         let mut opcode_info_map = BTreeMap::new();
@@ -371,6 +589,14 @@ impl<'a> CircuitInputBuilder {
             self.block.container.tx_access_list_account_storage.len()
         );
         log::debug!("tx_refund num: {}", self.block.container.tx_refund.len());
+        log::debug!(
+            "tx_created_account num: {}",
+            self.block.container.tx_created_account.len()
+        );
+        log::debug!(
+            "tx_destructed_account num: {}",
+            self.block.container.tx_destructed_account.len()
+        );
         log::debug!("account num: {}", self.block.container.account.len());
         log::debug!(
             "call_context num: {}",
@@ -391,10 +617,23 @@ impl<'a> CircuitInputBuilder {
             .sdb
             .get_storage(&MESSAGE_QUEUE, &WITHDRAW_TRIE_ROOT_SLOT)
             .1;
-        let withdraw_root_before = *self
-            .sdb
-            .get_committed_storage(&MESSAGE_QUEUE, &WITHDRAW_TRIE_ROOT_SLOT)
-            .1;
+        // the withdraw root must be the root of the trie the messages sent by
+        // the block are appended to
+        let mut withdraw_trie = self
+            .block
+            .withdraw_trie
+            .clone()
+            .unwrap_or_else(|| WithdrawTrie::from_sdb(&self.sdb));
+        let withdraw_root_before = withdraw_trie.root().to_word();
+        for message_hash in self.sent_message_hashes() {
+            withdraw_trie.append(message_hash);
+        }
+        if withdraw_trie.root().to_word() != withdraw_root {
+            return Err(Error::WithdrawRootMismatch(
+                H256(withdraw_root.to_be_bytes()),
+                withdraw_trie.root(),
+            ));
+        }
 
         let max_rws = self.block.circuits_params.max_rws;
         let mut end_block_not_last = self.block.block_steps.end_block_not_last.clone();
@@ -446,7 +685,12 @@ impl<'a> CircuitInputBuilder {
                     total_rws,
                     max_rws
                 );
-                return Err(Error::InternalError("rws not enough"));
+                return Err(CircuitCapacityError {
+                    circuit: SubCircuit::State,
+                    needed: total_rws + 1,
+                    available: max_rws,
+                }
+                .into());
             };
         }
         push_op(&mut end_block_last, RWCounter(1), RW::READ, StartOp {});
@@ -472,18 +716,25 @@ impl<'a> CircuitInputBuilder {
     fn handle_tx(
         &mut self,
         eth_tx: &eth_types::Transaction,
-        geth_trace: &GethExecTrace,
+        geth_trace: &dyn TraceSource,
         is_last_tx: bool,
     ) -> Result<(), Error> {
-        let mut tx = self.new_tx(eth_tx, !geth_trace.failed)?;
+        // EIP-3860: a creation tx with init code over the limit is invalid
+        if eth_tx.to.is_none()
+            && eth_tx.input.len() as u64 > self.block.circuits_params.max_init_code_size()
+        {
+            return Err(Error::InitCodeSizeExceeded(eth_tx.hash));
+        }
+        let mut tx = self.new_tx(eth_tx, !geth_trace.failed())?;
+        tx.unverified_sender = self.block_ctx.unverified_senders.contains(&eth_tx.hash);
 
         // Sanity check for transaction L1 fee.
         let tx_l1_fee = tx.l1_fee();
-        if tx_l1_fee != geth_trace.l1_fee {
+        if tx_l1_fee != geth_trace.l1_fee() {
             log::error!(
                 "Mismatch tx_l1_fee: calculated = {}, real = {}",
                 tx_l1_fee,
-                geth_trace.l1_fee
+                geth_trace.l1_fee()
             );
         }
 
@@ -502,21 +753,23 @@ impl<'a> CircuitInputBuilder {
                 }
             }
         }
-        // TODO: Move into gen_associated_steps with
-        // - execution_state: BeginTx
-        // - op: None
         // Generate BeginTx step
-        gen_begin_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx), geth_trace)?;
-
-        for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
+        self.gen_virtual_steps(&mut tx, &mut tx_ctx, ExecState::BeginTx, geth_trace)?;
+
+        // The auditor is taken out of the builder while the steps borrow it.
+        let mut gas_auditor = self.gas_auditor.take();
+        let tx_index = eth_tx.transaction_index.unwrap_or_default().as_u64();
+        // Only the window of the current and next steps is needed, so that the
+        // trace can be streamed.
+        let result = geth_trace.try_for_each_step(&mut |index, geth_steps| {
+            let geth_step = &geth_steps[0];
             let tx_gas = tx.gas;
             let mut state_ref = self.state_ref(&mut tx, &mut tx_ctx);
             log::trace!(
-                "handle {}th tx depth {} {}th/{} opcode {:?} pc: {} gas_left: {} gas_used: {} rwc: {} call_id: {} msize: {} args: {}",
+                "handle {}th tx depth {} {}th opcode {:?} pc: {} gas_left: {} gas_used: {} rwc: {} call_id: {} msize: {} args: {}",
                 eth_tx.transaction_index.unwrap_or_default(),
                 geth_step.depth,
                 index,
-                geth_trace.struct_logs.len(),
                 geth_step.op,
                 geth_step.pc.0,
                 geth_step.gas.0,
@@ -525,7 +778,7 @@ impl<'a> CircuitInputBuilder {
                 state_ref.call().map(|c| c.call_id).unwrap_or(0),
                 state_ref.call_ctx()?.memory.len(),
                 if geth_step.op.is_push_with_data() {
-                    format!("{:?}", geth_trace.struct_logs[index + 1].stack.last())
+                    format!("{:?}", geth_steps[1].stack.last())
                 } else if geth_step.op.is_call_without_value() {
                     format!(
                         "{:?} {:40x} {:?} {:?} {:?} {:?}",
@@ -588,21 +841,28 @@ impl<'a> CircuitInputBuilder {
                 state_ref.call(),
                 state_ref.tx.calls()
             );
-            let exec_steps = gen_associated_ops(
-                &geth_step.op,
-                &mut state_ref,
-                &geth_trace.struct_logs[index..],
-            )?;
+            if let Some(gas_auditor) = gas_auditor.as_mut() {
+                gas_auditor.audit_step(&state_ref, tx_index, index, geth_steps)?;
+            }
+            #[cfg(feature = "step-profile")]
+            let start = std::time::Instant::now();
+            #[allow(unused_mut)]
+            let mut exec_steps = gen_associated_ops(&geth_step.op, &mut state_ref, geth_steps)?;
+            #[cfg(feature = "step-profile")]
+            set_steps_elapsed(&mut exec_steps, start.elapsed());
             tx.steps_mut().extend(exec_steps);
-        }
+            Ok(())
+        });
+        self.gas_auditor = gas_auditor;
+        result?;
 
-        // TODO: Move into gen_associated_steps with
-        // - execution_state: EndTx
-        // - op: None
         // Generate EndTx step
         log::trace!("gen_end_tx_ops");
-        let end_tx_step = gen_end_tx_ops(&mut self.state_ref(&mut tx, &mut tx_ctx))?;
-        tx.steps_mut().push(end_tx_step);
+        self.gen_virtual_steps(&mut tx, &mut tx_ctx, ExecState::EndTx, geth_trace)?;
+        check_tx_transitions(tx.steps())?;
+        for call in tx.calls() {
+            self.block.add_bytecode_hash(call.code_hash);
+        }
 
         self.sdb.commit_tx();
         self.block.txs.push(tx);
@@ -610,6 +870,35 @@ impl<'a> CircuitInputBuilder {
 
         Ok(())
     }
+
+    /// Generate the steps of the virtual execution state `exec_state` (BeginTx
+    /// or EndTx) and push them into `tx`.
+    fn gen_virtual_steps(
+        &mut self,
+        tx: &mut Transaction,
+        tx_ctx: &mut TransactionContext,
+        exec_state: ExecState,
+        geth_trace: &dyn TraceSource,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "step-profile")]
+        let (start, first_step) = (std::time::Instant::now(), tx.steps().len());
+        gen_associated_steps(&mut self.state_ref(tx, tx_ctx), exec_state, geth_trace)?;
+        #[cfg(feature = "step-profile")]
+        set_steps_elapsed(&mut tx.steps_mut()[first_step..], start.elapsed());
+        Ok(())
+    }
+}
+
+/// Split the time spent generating `steps` evenly among them.
+#[cfg(feature = "step-profile")]
+fn set_steps_elapsed(steps: &mut [ExecStep], elapsed: std::time::Duration) {
+    if steps.is_empty() {
+        return;
+    }
+    let elapsed = elapsed / steps.len() as u32;
+    for step in steps {
+        step.elapsed = elapsed;
+    }
 }
 
 /// Return all the keccak inputs used during the processing of the current
@@ -628,11 +917,12 @@ pub fn keccak_inputs(block: &Block, code_db: &CodeDB) -> Result<Vec<Vec<u8>>, Er
         block.chain_id,
         block.prev_state_root,
         block.withdraw_root,
+        block.prev_withdraw_root,
         &block.headers,
         block.txs(),
     ));
     // Bytecode Circuit
-    for _bytecode in code_db.0.values() {
+    for _bytecode in code_db.iter() {
         // keccak_inputs.push(bytecode.clone());
     }
     log::debug!(
@@ -720,6 +1010,7 @@ fn keccak_inputs_pi_circuit(
     chain_id: u64,
     prev_state_root: Word,
     withdraw_trie_root: Word,
+    prev_withdraw_trie_root: Word,
     block_headers: &BTreeMap<u64, BlockHead>,
     transactions: &[Transaction],
 ) -> Vec<Vec<u8>> {
@@ -752,6 +1043,7 @@ fn keccak_inputs_pi_circuit(
         .chain(after_state_root.to_fixed_bytes())
         .chain(withdraw_trie_root.to_be_bytes())
         .chain(data_hash.to_fixed_bytes())
+        .chain(prev_withdraw_trie_root.to_be_bytes())
         .collect::<Vec<u8>>();
 
     vec![data_bytes, pi_bytes]
@@ -773,11 +1065,11 @@ pub fn keccak_inputs_tx_circuit(txs: &[geth_types::Transaction]) -> Result<Vec<V
     inputs.extend_from_slice(&hash_datas);
     inputs.push(dummy_hash_data);
 
-    let sign_datas: Vec<SignData> = txs
+    let signed_txs: Vec<&geth_types::Transaction> = txs
         .iter()
         .enumerate()
         .filter(|(i, tx)| {
-            if !tx.tx_type.is_l1_msg() && tx.v == 0 && tx.r.is_zero() && tx.s.is_zero() {
+            if !tx.tx_type.is_unsigned() && tx.v == 0 && tx.r.is_zero() && tx.s.is_zero() {
                 warn!(
                     "tx {} is not signed and is not L1Msg, skipping tx circuit keccak input",
                     i
@@ -787,14 +1079,19 @@ pub fn keccak_inputs_tx_circuit(txs: &[geth_types::Transaction]) -> Result<Vec<V
                 true
             }
         })
-        .map(|(_, tx)| {
-            if tx.tx_type.is_l1_msg() {
-                Ok(SignData::default())
-            } else {
-                tx.sign_data()
-            }
-        })
-        .try_collect()?;
+        .map(|(_, tx)| tx)
+        .collect();
+    let sign_datas = SignData::batch_from_txs(&signed_txs).map_err(|errors| {
+        for (i, err) in &errors {
+            log::error!(
+                "invalid signature of tx {:?}: {:?}",
+                signed_txs[*i].hash,
+                err
+            );
+        }
+        let (_, err) = errors.into_iter().next().expect("errors are not empty");
+        Error::from(err)
+    })?;
     // Keccak inputs from SignVerify Chip
     let sign_verify_inputs = keccak_inputs_sign_verify(&sign_datas);
     inputs.extend_from_slice(&sign_verify_inputs);
@@ -850,8 +1147,24 @@ pub struct BuilderClient<P: JsonRpcClient> {
     cli: GethClient<P>,
     chain_id: u64,
     circuits_params: CircuitsParams,
+    verify_proofs: bool,
+    proof_format: ProofFormat,
+    check_receipts: bool,
+    max_concurrent_fetches: usize,
+    max_concurrent_requests: usize,
+    l1_fee_config: L1FeeConfig,
+    gas_schedules: GasSchedules,
+    chain_spec: ChainSpec,
 }
 
+/// Default number of blocks fetched concurrently by
+/// [`BuilderClient::gen_inputs_multi_blocks`].
+pub const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 4;
+
+/// Default number of `eth_getProof` or `eth_getCode` requests in flight in
+/// [`BuilderClient::get_state`].
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 32;
+
 /// Get State Accesses from TxExecTraces
 pub fn get_state_accesses(
     eth_block: &EthBlock,
@@ -869,12 +1182,76 @@ pub fn get_state_accesses(
     for (tx_index, tx) in eth_block.transactions.iter().enumerate() {
         let geth_trace = &geth_traces[tx_index];
         let tx_access_trace = gen_state_access_trace(eth_block, tx, geth_trace)?;
-        block_access_trace.extend(tx_access_trace);
+        block_access_trace.extend(
+            tx_access_trace
+                .into_iter()
+                .map(|access| access.with_tx_index(tx_index)),
+        );
     }
 
     Ok(block_access_trace)
 }
 
+/// Build a partial StateDB from step 3, after checking the account and
+/// storage proofs against `state_root`, see [`state_db::verify_proofs`].
+pub fn build_verified_state_code_db(
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+    state_root: H256,
+) -> Result<(StateDB, CodeDB), Error> {
+    verify_proofs(state_root, &proofs)?;
+    Ok(build_state_code_db(proofs, codes))
+}
+
+/// Merge the proofs of `new_proofs` into `proofs`: the storage proofs of an
+/// account which already has a proof are added to it, so that the account
+/// fields come from its first proof. The new storage proofs must be against
+/// the storage root of that first proof, else they are rejected.
+fn merge_proofs(
+    proofs: &mut Vec<eth_types::EIP1186ProofResponse>,
+    new_proofs: Vec<eth_types::EIP1186ProofResponse>,
+) -> Result<(), Error> {
+    for new_proof in new_proofs {
+        match proofs
+            .iter_mut()
+            .find(|proof| proof.address == new_proof.address)
+        {
+            Some(proof) => {
+                if let Some(storage_proof) = new_proof.storage_proof.first() {
+                    if proof.storage_hash != new_proof.storage_hash {
+                        return Err(Error::InvalidStorageProof(
+                            new_proof.address,
+                            storage_proof.key,
+                        ));
+                    }
+                }
+                proof.storage_proof.extend(new_proof.storage_proof)
+            }
+            None => proofs.push(new_proof),
+        }
+    }
+    Ok(())
+}
+
+/// Drop the txs of `eth_block` and their traces beyond the `max_txs` of the
+/// circuits, if it is set.
+fn truncate_to_max_txs(
+    circuits_params: CircuitsParams,
+    eth_block: &mut EthBlock,
+    geth_traces: &mut Vec<eth_types::GethExecTrace>,
+) {
+    if circuits_params.max_txs != 0 && eth_block.transactions.len() > circuits_params.max_txs {
+        log::error!(
+            "max_txs too small: {} < {} for block {}",
+            circuits_params.max_txs,
+            eth_block.transactions.len(),
+            eth_block.number.unwrap_or_default()
+        );
+        eth_block.transactions.truncate(circuits_params.max_txs);
+        geth_traces.truncate(circuits_params.max_txs);
+    }
+}
+
 /// Build a partial StateDB from step 3
 pub fn build_state_code_db(
     proofs: Vec<eth_types::EIP1186ProofResponse>,
@@ -899,11 +1276,54 @@ pub fn build_state_code_db(
         )
     }
 
+    let code_db = build_code_db(&sdb, codes);
+    (sdb, code_db)
+}
+
+/// Build a partial StateDB from the proofs of step 3 in `proof_format`, see
+/// [`build_state_code_db`] and [`build_zktrie_state_code_db`].
+pub fn build_state_code_db_of_format(
+    proof_format: ProofFormat,
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+) -> Result<(StateDB, CodeDB), Error> {
+    match proof_format {
+        ProofFormat::Mpt => Ok(build_state_code_db(proofs, codes)),
+        ProofFormat::ZkTrie => build_zktrie_state_code_db(proofs, codes),
+    }
+}
+
+/// Build a partial StateDB from the zkTrie proofs of step 3, with the
+/// accounts and storage slots decoded from the leaves of the proofs, see
+/// [`decode_zktrie_proof`].
+pub fn build_zktrie_state_code_db(
+    proofs: Vec<eth_types::EIP1186ProofResponse>,
+    codes: HashMap<Address, Vec<u8>>,
+) -> Result<(StateDB, CodeDB), Error> {
+    let mut sdb = StateDB::new();
+    for proof in proofs {
+        sdb.set_account(&proof.address, decode_zktrie_proof(&proof)?);
+    }
+    let code_db = build_code_db(&sdb, codes);
+    Ok((sdb, code_db))
+}
+
+fn build_code_db(sdb: &StateDB, codes: HashMap<Address, Vec<u8>>) -> CodeDB {
+    // the code of accounts with a proof isn't hashed here, see
+    // `CodeDB::verify_hashes`, and the code of accounts sharing a code hash is
+    // stored once
     let mut code_db = CodeDB::new();
-    for (_address, code) in codes {
-        code_db.insert(code.clone());
+    for (address, code) in codes {
+        let (found, account) = sdb.get_account(&address);
+        let code_hash = if found {
+            code_db.insert_with_hash(account.code_hash, code);
+            account.code_hash
+        } else {
+            code_db.insert(code)
+        };
+        code_db.add_ref(address, code_hash);
     }
-    (sdb, code_db)
+    code_db
 }
 
 impl<P: JsonRpcClient> BuilderClient<P> {
@@ -918,17 +1338,106 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             cli: client,
             chain_id,
             circuits_params,
+            verify_proofs: false,
+            proof_format: ProofFormat::default(),
+            check_receipts: false,
+            max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_FETCHES,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            l1_fee_config: L1FeeConfig::default(),
+            gas_schedules: GasSchedules::default(),
+            chain_spec: ChainSpec::default(),
         })
     }
 
-    /// Step 1. Query geth for Block, Txs, TxExecTraces, history block hashes
-    /// and previous state root.
+    /// Set the max number of blocks fetched concurrently when generating the
+    /// inputs of multiple blocks.
+    pub fn with_max_concurrent_fetches(mut self, max_concurrent_fetches: usize) -> Self {
+        self.max_concurrent_fetches = max_concurrent_fetches.max(1);
+        self
+    }
+
+    /// Set the max number of `eth_getProof` or `eth_getCode` requests in
+    /// flight when fetching the state of a block.  Each of the blocks fetched
+    /// concurrently has its own requests in flight.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self
+    }
+
+    /// Set the blocks the L1 fee hardforks of the chain activate at, which
+    /// select the formula the L1 fee of each tx is checked with.
+    pub fn with_l1_fee_config(mut self, l1_fee_config: L1FeeConfig) -> Self {
+        self.l1_fee_config = l1_fee_config;
+        self
+    }
+
+    /// Set the gas schedules of the storage opcodes of the chain, which
+    /// select the costs of the SLOAD and SSTORE of each block.
+    pub fn with_gas_schedules(mut self, gas_schedules: GasSchedules) -> Self {
+        self.gas_schedules = gas_schedules;
+        self
+    }
+
+    /// Set the system calls of the chain, which are handled as pseudo-txs
+    /// before and after the txs of each block, see [`SystemCall`], and the
+    /// first block of its Shanghai hardfork.
+    pub fn with_chain_spec(mut self, chain_spec: ChainSpec) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Verify the account and storage proofs returned by the node against
+    /// the state root of the parent block before building the StateDB, so
+    /// that a faulty node can't forge the state read by the block.
+    pub fn with_proof_verification(mut self, enable: bool) -> Self {
+        self.verify_proofs = enable;
+        self
+    }
+
+    /// Set the format of the account and storage proofs fetched from the
+    /// node. With [`ProofFormat::ZkTrie`], the proofs come from
+    /// `scroll_getProof` and the StateDB is decoded from their leaves. The
+    /// default is [`ProofFormat::ZkTrie`] with the `scroll` feature, the
+    /// same format as [`verify_proofs`].
+    pub fn with_proof_format(mut self, proof_format: ProofFormat) -> Self {
+        self.proof_format = proof_format;
+        self
+    }
+
+    /// Fetch the receipts of each block with its traces, and check the gas
+    /// used, status and log count of its txs against the builder, see
+    /// [`CircuitInputBuilder::check_receipts`], so that a tracer inconsistent
+    /// with the client is caught before the circuits are built.
+    pub fn with_receipt_check(mut self, enable: bool) -> Self {
+        self.check_receipts = enable;
+        self
+    }
+
+    /// Step 1. Query geth for Block, Txs, TxExecTraces, history block hashes,
+    /// previous state root and, if the receipt check is enabled, the tx
+    /// receipts.
     pub async fn get_block(
         &self,
         block_num: u64,
-    ) -> Result<(EthBlock, Vec<eth_types::GethExecTrace>, Vec<Word>, Word), Error> {
-        let eth_block = self.cli.get_block_by_number(block_num.into()).await?;
-        let geth_traces = self.cli.trace_block_by_number(block_num.into()).await?;
+    ) -> Result<
+        (
+            EthBlock,
+            Vec<eth_types::GethExecTrace>,
+            Vec<Word>,
+            Word,
+            Vec<TransactionReceipt>,
+        ),
+        Error,
+    > {
+        let mut eth_block = self.cli.get_block_by_number(block_num.into()).await?;
+        let (mut geth_traces, receipts) = try_join!(
+            self.cli.trace_block_by_number(block_num.into()),
+            self.get_receipts(block_num),
+        )?;
+        if self.chain_spec.has_system_calls() {
+            let (pre_block, post_block) = self.get_system_calls(&eth_block, block_num).await?;
+            insert_system_calls(&mut eth_block, &mut geth_traces, pre_block, post_block);
+        }
 
         // fetch up to 256 blocks
         let mut n_blocks = 0; // std::cmp::min(256, block_num as usize);
@@ -962,9 +1471,58 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             geth_traces,
             history_hashes,
             prev_state_root.unwrap_or_default(),
+            receipts,
         ))
     }
 
+    async fn get_receipts(&self, block_num: u64) -> Result<Vec<TransactionReceipt>, Error> {
+        if !self.check_receipts {
+            return Ok(vec![]);
+        }
+        self.cli.get_block_receipts(block_num.into()).await
+    }
+
+    /// Trace the system calls of `block_num` as pseudo-txs on the state
+    /// before the block, see [`ChainSpec::check_traceable`].
+    async fn get_system_calls(
+        &self,
+        eth_block: &EthBlock,
+        block_num: u64,
+    ) -> Result<(Vec<SystemCallTrace>, Vec<SystemCallTrace>), Error> {
+        self.chain_spec.check_traceable()?;
+        let pre_block = self
+            .trace_system_calls(
+                eth_block,
+                &self.chain_spec.pre_block_calls,
+                block_num.saturating_sub(1),
+            )
+            .await?;
+        // there is no post-block call to trace
+        Ok((pre_block, Vec::new()))
+    }
+
+    async fn trace_system_calls(
+        &self,
+        eth_block: &EthBlock,
+        calls: &[SystemCall],
+        state_block_num: u64,
+    ) -> Result<Vec<SystemCallTrace>, Error> {
+        let mut pseudo_txs = Vec::with_capacity(calls.len());
+        for call in calls {
+            let nonce = self
+                .cli
+                .get_proof(call.from, vec![], state_block_num.into())
+                .await?
+                .nonce;
+            let trace = self
+                .cli
+                .trace_call(call.to_request(nonce), state_block_num.into())
+                .await?;
+            pseudo_txs.push((call.to_eth_tx(eth_block, nonce), trace));
+        }
+        Ok(pseudo_txs)
+    }
+
     /// Step 2. Get State Accesses from TxExecTraces
     pub fn get_state_accesses(
         eth_block: &EthBlock,
@@ -974,7 +1532,9 @@ impl<P: JsonRpcClient> BuilderClient<P> {
     }
 
     /// Step 3. Query geth for all accounts, storage keys, and codes from
-    /// Accesses
+    /// Accesses, in the state before `block_num`. The values are only the
+    /// pre-state of `block_num` itself: a later block sees the writes of the
+    /// blocks in between.
     pub async fn get_state(
         &self,
         block_num: u64,
@@ -986,27 +1546,45 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         ),
         Error,
     > {
-        let mut proofs = Vec::new();
-        for (address, key_set) in access_set.state {
-            let mut keys: Vec<Word> = key_set.iter().cloned().collect();
-            keys.sort();
-            let proof = self
-                .cli
-                .get_proof(address, keys, (block_num - 1).into())
-                .await
-                .unwrap();
-            proofs.push(proof);
-        }
-        let mut codes: HashMap<Address, Vec<u8>> = HashMap::new();
-        for address in access_set.code {
-            let code = self
-                .cli
-                .get_code(address, (block_num - 1).into())
-                .await
-                .unwrap();
-            codes.insert(address, code);
+        let accounts = access_set
+            .state
+            .iter()
+            .map(|(address, key_set)| (*address, key_set.iter().cloned().collect()))
+            .collect();
+        let proofs = self
+            .cli
+            .get_proofs(
+                accounts,
+                (block_num - 1).into(),
+                self.proof_format,
+                self.max_concurrent_requests,
+            )
+            .await?;
+        access_set.check_proofs(&proofs)?;
+        let codes = self
+            .cli
+            .get_codes(
+                access_set.code.iter().cloned().collect(),
+                (block_num - 1).into(),
+                self.max_concurrent_requests,
+            )
+            .await?;
+        Ok((proofs, codes.into_iter().collect()))
+    }
+
+    /// Step 3b. Verify the account and storage proofs from step 3 against the
+    /// state root of the block before `block_num`, if enabled.
+    pub async fn verify_proofs(
+        &self,
+        block_num: u64,
+        proofs: &[eth_types::EIP1186ProofResponse],
+    ) -> Result<(), Error> {
+        if !self.verify_proofs {
+            return Ok(());
         }
-        Ok((proofs, codes))
+        let parent_block = self.cli.get_block_by_number((block_num - 1).into()).await?;
+        self.proof_format
+            .verify_proofs(parent_block.state_root, proofs)
     }
 
     /// Step 4. Build a partial StateDB from step 3
@@ -1017,6 +1595,17 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         build_state_code_db(proofs, codes)
     }
 
+    /// Step 4, for the proof format of the client: the StateDB is built
+    /// from the fields of the responses for MPT proofs, and decoded from the
+    /// leaves of zkTrie proofs.
+    pub fn build_state_code_db_of_format(
+        &self,
+        proofs: Vec<eth_types::EIP1186ProofResponse>,
+        codes: HashMap<Address, Vec<u8>>,
+    ) -> Result<(StateDB, CodeDB), Error> {
+        build_state_code_db_of_format(self.proof_format, proofs, codes)
+    }
+
     /// Step 5. For each step in TxExecTraces, gen the associated ops and state
     /// circuit inputs
     pub fn gen_inputs_from_state(
@@ -1031,6 +1620,9 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         let block = BlockHead::new(self.chain_id, history_hashes, eth_block)?;
         let mut builder =
             CircuitInputBuilder::new_from_headers(self.circuits_params, sdb, code_db, &[block]);
+        builder.block.l1_fee_config = self.l1_fee_config;
+        builder.block.gas_schedules = self.gas_schedules.clone();
+        builder.block.chain_spec = self.chain_spec.clone();
 
         builder.handle_block(eth_block, geth_traces)?;
         Ok(builder)
@@ -1050,11 +1642,31 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             code_db,
             Default::default(),
         );
+        builder.block.chain_id = self.chain_id;
+        builder.block.l1_fee_config = self.l1_fee_config;
+        builder.block.gas_schedules = self.gas_schedules.clone();
+        builder.block.chain_spec = self.chain_spec.clone();
+        // the accesses of the blocks after each block, whose code is kept
+        let mut next_access_sets = Vec::with_capacity(blocks_and_traces.len());
+        let mut next_access_set = AccessSet::default();
+        for (eth_block, geth_traces) in blocks_and_traces.iter().rev() {
+            next_access_sets.push(next_access_set.clone());
+            next_access_set.add(get_state_accesses(eth_block, geth_traces)?);
+        }
+        next_access_sets.reverse();
         for (idx, (eth_block, geth_traces)) in blocks_and_traces.iter().enumerate() {
             let is_last = idx == blocks_and_traces.len() - 1;
             let header = BlockHead::new(self.chain_id, Default::default(), eth_block)?;
             builder.block.headers.insert(header.number.as_u64(), header);
             builder.handle_block_inner(eth_block, geth_traces, is_last, is_last)?;
+            if !is_last {
+                let dropped = builder.code_db.retain_reachable(
+                    &builder.sdb,
+                    &next_access_sets[idx],
+                    &builder.block.bytecode_hashes,
+                );
+                log::debug!("dropped {dropped} unreachable codes after block {idx}");
+            }
         }
         Ok(builder)
     }
@@ -1070,23 +1682,14 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         ),
         Error,
     > {
-        let (mut eth_block, mut geth_traces, history_hashes, prev_state_root) =
+        let (mut eth_block, mut geth_traces, history_hashes, prev_state_root, mut receipts) =
             self.get_block(block_num).await?;
         let access_set = Self::get_state_accesses(&eth_block, &geth_traces)?;
         let (proofs, codes) = self.get_state(block_num, access_set.into()).await?;
-        let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
-        if eth_block.transactions.len() > self.circuits_params.max_txs {
-            log::error!(
-                "max_txs too small: {} < {} for block {}",
-                self.circuits_params.max_txs,
-                eth_block.transactions.len(),
-                eth_block.number.unwrap_or_default()
-            );
-            eth_block
-                .transactions
-                .truncate(self.circuits_params.max_txs);
-            geth_traces.truncate(self.circuits_params.max_txs);
-        }
+        self.verify_proofs(block_num, &proofs).await?;
+        let (state_db, code_db) = self.build_state_code_db_of_format(proofs, codes)?;
+        code_db.verify_hashes(&state_db)?;
+        truncate_to_max_txs(self.circuits_params, &mut eth_block, &mut geth_traces);
         let builder = self.gen_inputs_from_state(
             state_db,
             code_db,
@@ -1095,10 +1698,18 @@ impl<P: JsonRpcClient> BuilderClient<P> {
             history_hashes,
             prev_state_root,
         )?;
+        // the receipts of the txs beyond `max_txs` aren't checked
+        let tx_hashes: HashSet<_> = eth_block.transactions.iter().map(|tx| tx.hash).collect();
+        receipts.retain(|receipt| tx_hashes.contains(&receipt.transaction_hash));
+        builder.check_receipts(&receipts)?;
         Ok((builder, eth_block))
     }
 
-    /// Perform all the steps to generate the circuit inputs
+    /// Perform all the steps to generate the circuit inputs of the blocks
+    /// `block_num_begin..block_num_end`. Each account and storage slot is
+    /// fetched in the state before the first block accessing it, which is
+    /// also its value before the whole range since no earlier block of the
+    /// range wrote it.
     pub async fn gen_inputs_multi_blocks(
         &self,
         block_num_begin: u64,
@@ -1106,15 +1717,41 @@ impl<P: JsonRpcClient> BuilderClient<P> {
     ) -> Result<CircuitInputBuilder, Error> {
         let mut blocks_and_traces = Vec::new();
         let mut access_set = AccessSet::default();
-        for block_num in block_num_begin..block_num_end {
-            let (eth_block, geth_traces, _, _) = self.get_block(block_num).await?;
+        let mut proofs = Vec::new();
+        let mut codes = HashMap::new();
+        let mut receipts = Vec::new();
+        // fetch the next blocks while the state accesses of the current one
+        // are collected, keeping the blocks in order
+        let mut blocks = stream::iter(block_num_begin..block_num_end)
+            .map(|block_num| async move { (block_num, self.get_block(block_num).await) })
+            .buffered(self.max_concurrent_fetches);
+        while let Some((block_num, block)) = blocks.next().await {
+            let (eth_block, geth_traces, _, _, block_receipts) = block?;
             let access_list = Self::get_state_accesses(&eth_block, &geth_traces)?;
-            access_set.add(access_list);
+            // only fetch the accounts and storage slots accessed for the first
+            // time in the chunk, in the state before the block
+            let new_access_set = access_set.add_new(access_list);
+            log::debug!(
+                "fetching {} new accounts and {} new slots before block {}",
+                new_access_set.state.len(),
+                new_access_set
+                    .state
+                    .values()
+                    .map(|keys| keys.len())
+                    .sum::<usize>(),
+                block_num
+            );
+            let (new_proofs, new_codes) = self.get_state(block_num, new_access_set).await?;
+            self.verify_proofs(block_num, &new_proofs).await?;
+            merge_proofs(&mut proofs, new_proofs)?;
+            codes.extend(new_codes);
             blocks_and_traces.push((eth_block, geth_traces));
+            receipts.extend(block_receipts);
         }
-        let (proofs, codes) = self.get_state(block_num_begin, access_set).await?;
-        let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
+        let (state_db, code_db) = self.build_state_code_db_of_format(proofs, codes)?;
+        code_db.verify_hashes(&state_db)?;
         let builder = self.gen_inputs_from_state_multi(state_db, code_db, &blocks_and_traces)?;
+        builder.check_receipts(&receipts)?;
         Ok(builder)
     }
 
@@ -1156,10 +1793,11 @@ impl<P: JsonRpcClient> BuilderClient<P> {
 
         let access_set = AccessSet::from(block_access_trace);
 
-        let (proofs, codes) = self
-            .get_state(tx.block_number.unwrap().as_u64(), access_set)
-            .await?;
-        let (state_db, code_db) = Self::build_state_code_db(proofs, codes);
+        let block_num = tx.block_number.unwrap().as_u64();
+        let (proofs, codes) = self.get_state(block_num, access_set).await?;
+        self.verify_proofs(block_num, &proofs).await?;
+        let (state_db, code_db) = self.build_state_code_db_of_format(proofs, codes)?;
+        code_db.verify_hashes(&state_db)?;
         let builder = self.gen_inputs_from_state(
             state_db,
             code_db,
@@ -1170,4 +1808,95 @@ impl<P: JsonRpcClient> BuilderClient<P> {
         )?;
         Ok(builder)
     }
+
+    /// Perform all the steps to generate the circuit inputs of the unsigned
+    /// `tx`, simulated on top of the state after `block_num` in the context of
+    /// that block, as traced by `debug_traceCall`. The tx is not mined, so
+    /// its sender is unverified (see
+    /// [`CircuitInputBuilder::handle_block_with_unverified_senders`]).
+    pub async fn gen_inputs_call(
+        &self,
+        mut tx: TransactionRequest,
+        block_num: u64,
+    ) -> Result<CircuitInputBuilder, Error> {
+        let from = tx.from.unwrap_or_default();
+        let nonce = match tx.nonce {
+            Some(nonce) => nonce,
+            None => {
+                self.cli
+                    .get_proof(from, vec![], block_num.into())
+                    .await?
+                    .nonce
+            }
+        };
+        let to = match &tx.to {
+            None => None,
+            Some(NameOrAddress::Address(to)) => Some(*to),
+            Some(NameOrAddress::Name(_)) => {
+                return Err(Error::InternalError(
+                    "ENS names are not supported as the callee of a simulated tx",
+                ))
+            }
+        };
+        let mut eth_block = self.cli.get_block_by_number(block_num.into()).await?;
+        tx.from = Some(from);
+        tx.nonce = Some(nonce);
+        tx.gas = Some(tx.gas.unwrap_or(eth_block.gas_limit));
+        tx.chain_id = Some(self.chain_id.into());
+        let geth_traces = vec![self.cli.trace_call(tx.clone(), block_num.into()).await?];
+
+        let eth_tx = eth_types::Transaction {
+            hash: tx.sighash(),
+            nonce,
+            block_hash: eth_block.hash,
+            block_number: eth_block.number,
+            transaction_index: Some(0.into()),
+            from,
+            to,
+            value: tx.value.unwrap_or_default(),
+            gas_price: Some(tx.gas_price.unwrap_or_default()),
+            gas: tx.gas.unwrap_or_default(),
+            input: tx.data.clone().unwrap_or_default(),
+            chain_id: Some(self.chain_id.into()),
+            ..Default::default()
+        };
+        eth_block.transactions = vec![eth_tx.clone()];
+
+        let mut block_access_trace = vec![Access::new(
+            None,
+            RW::WRITE,
+            AccessValue::Account {
+                address: eth_block.author.unwrap_or_default(),
+            },
+        )];
+        block_access_trace.extend(gen_state_access_trace(
+            &eth_types::Block::<eth_types::Transaction>::default(),
+            &eth_tx,
+            &geth_traces[0],
+        )?);
+        let access_set = AccessSet::from(block_access_trace);
+
+        // The state is the one after `block_num`, i.e. before the next block.
+        let (proofs, codes) = self.get_state(block_num + 1, access_set).await?;
+        self.verify_proofs(block_num + 1, &proofs).await?;
+        let (state_db, code_db) = self.build_state_code_db_of_format(proofs, codes)?;
+        code_db.verify_hashes(&state_db)?;
+
+        let block = BlockHead::new(self.chain_id, Default::default(), &eth_block)?;
+        let mut builder = CircuitInputBuilder::new_from_headers(
+            self.circuits_params,
+            state_db,
+            code_db,
+            &[block],
+        );
+        builder.block.l1_fee_config = self.l1_fee_config;
+        builder.block.gas_schedules = self.gas_schedules.clone();
+        builder.block.chain_spec = self.chain_spec.clone();
+        builder.handle_block_with_unverified_senders(
+            &eth_block,
+            &geth_traces,
+            &HashMap::from([(eth_tx.hash, from)]),
+        )?;
+        Ok(builder)
+    }
 }
@@ -1,7 +1,11 @@
-use crate::{operation::RW, Error};
-use eth_types::{evm_types::OpcodeId, Address, GethExecStep, GethExecTrace, ToAddress, Word};
-use ethers_core::utils::get_contract_address;
-use std::collections::{hash_map::Entry, HashMap, HashSet};
+use crate::{operation::RW, precompile::is_precompiled, Error};
+use eth_types::{
+    evm_types::OpcodeId, AccessList, Address, EIP1186ProofResponse, GethExecStep, GethExecTrace,
+    ToAddress, ToBigEndian, Word, H256,
+};
+use ethers_core::{types::transaction::eip2930::AccessListItem, utils::get_contract_address};
+use serde::{Deserialize, Serialize};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
 
 use AccessValue::{Account, Code, Storage};
 use RW::{READ, WRITE};
@@ -31,6 +35,7 @@ pub enum AccessValue {
 /// State Access caused by a transaction or an execution step
 #[derive(Debug, PartialEq, Eq)]
 pub struct Access {
+    tx_index: Option<usize>,
     step_index: Option<usize>,
     rw: RW,
     value: AccessValue,
@@ -39,11 +44,24 @@ pub struct Access {
 impl Access {
     pub(crate) fn new(step_index: Option<usize>, rw: RW, value: AccessValue) -> Self {
         Self {
+            tx_index: None,
             step_index,
             rw,
             value,
         }
     }
+
+    /// Attribute the access to the tx at `tx_index` in its block.
+    pub(crate) fn with_tx_index(mut self, tx_index: usize) -> Self {
+        self.tx_index = Some(tx_index);
+        self
+    }
+
+    /// Index in its block of the tx which caused the access, or `None` for
+    /// an access of the block itself or one not attributed to a tx.
+    pub fn tx_index(&self) -> Option<usize> {
+        self.tx_index
+    }
 }
 
 /// Given a trace and assuming that the first step is a *CALL*/CREATE* kind
@@ -56,13 +74,26 @@ fn get_call_result(trace: &[GethExecStep]) -> Option<Word> {
         .and_then(|s| s.stack.nth_last(0).ok())
 }
 
-/// State and Code Access set.
-#[derive(Debug, PartialEq, Eq, Default)]
+/// Accesses attributed to a tx, see [`AccessSet::to_eip2930_access_list`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct TxAccesses {
+    /// Accounts accessed by the tx itself: the sender, and the callee or the
+    /// created contract, which are warm from the start of the tx.
+    warm: BTreeSet<Address>,
+    /// Accounts and storage keys accessed by the steps of the tx.
+    state: BTreeMap<Address, BTreeSet<Word>>,
+}
+
+/// State and Code Access set.  Ordered, so that the proofs and codes fetched
+/// from it (and the witness built from them) don't depend on hashing.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct AccessSet {
     /// Set of accounts
-    pub state: HashMap<Address, HashSet<Word>>,
+    pub state: BTreeMap<Address, BTreeSet<Word>>,
     /// Set of accounts code
-    pub code: HashSet<Address>,
+    pub code: BTreeSet<Address>,
+    /// Accesses attributed to each tx, by the index of the tx in its block.
+    pub(crate) txs: BTreeMap<usize, TxAccesses>,
 }
 
 impl AccessSet {
@@ -70,13 +101,27 @@ impl AccessSet {
         let state = &mut self.state;
         let code = &mut self.code;
         for access in list {
+            if let Some(tx_index) = access.tx_index {
+                let tx = self.txs.entry(tx_index).or_default();
+                match (access.step_index, &access.value) {
+                    (None, Account { address } | Code { address }) => {
+                        tx.warm.insert(*address);
+                    }
+                    (_, Storage { address, key }) => {
+                        tx.state.entry(*address).or_default().insert(*key);
+                    }
+                    (Some(_), Account { address } | Code { address }) => {
+                        tx.state.entry(*address).or_default();
+                    }
+                }
+            }
             match access.value {
                 AccessValue::Account { address } => {
-                    state.entry(address).or_insert_with(HashSet::new);
+                    state.entry(address).or_insert_with(BTreeSet::new);
                 }
                 AccessValue::Storage { address, key } => match state.entry(address) {
                     Entry::Vacant(entry) => {
-                        let mut storage = HashSet::new();
+                        let mut storage = BTreeSet::new();
                         storage.insert(key);
                         entry.insert(storage);
                     }
@@ -85,12 +130,118 @@ impl AccessSet {
                     }
                 },
                 AccessValue::Code { address } => {
-                    state.entry(address).or_insert_with(HashSet::new);
+                    state.entry(address).or_insert_with(BTreeSet::new);
                     code.insert(address);
                 }
             }
         }
     }
+
+    /// Add the accesses of `list` and return those which weren't in the set
+    /// yet: new accounts with all their storage keys, and new storage keys of
+    /// accounts already in the set.  The tx attributions of `list` are added
+    /// to the set only.
+    pub(crate) fn add_new(&mut self, list: Vec<Access>) -> AccessSet {
+        let other = AccessSet::from(list);
+        for (tx_index, accesses) in other.txs {
+            let tx = self.txs.entry(tx_index).or_default();
+            tx.warm.extend(accesses.warm);
+            for (address, keys) in accesses.state {
+                tx.state.entry(address).or_default().extend(keys);
+            }
+        }
+        let mut new = AccessSet::default();
+        for (address, keys) in other.state {
+            match self.state.entry(address) {
+                Entry::Vacant(entry) => {
+                    new.state.insert(address, keys.clone());
+                    entry.insert(keys);
+                }
+                Entry::Occupied(mut entry) => {
+                    let new_keys: BTreeSet<Word> = keys.difference(entry.get()).copied().collect();
+                    if !new_keys.is_empty() {
+                        entry.get_mut().extend(new_keys.iter().copied());
+                        new.state.insert(address, new_keys);
+                    }
+                }
+            }
+        }
+        for address in other.code {
+            if self.code.insert(address) {
+                new.code.insert(address);
+            }
+        }
+        new
+    }
+
+    /// Accesses which are not covered by `proofs`, sorted: accounts without
+    /// an account proof (with key `None`) and storage slots without a storage
+    /// proof.
+    pub fn missing_proofs(&self, proofs: &[EIP1186ProofResponse]) -> Vec<(Address, Option<Word>)> {
+        let proven: BTreeMap<Address, BTreeSet<Word>> = proofs
+            .iter()
+            .map(|proof| {
+                (
+                    proof.address,
+                    proof.storage_proof.iter().map(|p| p.key).collect(),
+                )
+            })
+            .collect();
+        let mut missing = vec![];
+        for (address, keys) in &self.state {
+            match proven.get(address) {
+                None => missing.push((*address, None)),
+                Some(proven_keys) => missing.extend(
+                    keys.difference(proven_keys)
+                        .map(|key| (*address, Some(*key))),
+                ),
+            }
+        }
+        missing.sort();
+        missing
+    }
+
+    /// The EIP-2930 access list of the tx at `tx_idx` in its block: the
+    /// accounts and storage keys accessed by its steps.  The accounts warm
+    /// from the start of the tx (its sender, its callee or created contract,
+    /// and the precompiles) are left out unless their storage is accessed.
+    /// Empty if no access is attributed to the tx, see [`get_state_accesses`].
+    ///
+    /// [`get_state_accesses`]: super::get_state_accesses
+    pub fn to_eip2930_access_list(&self, tx_idx: usize) -> AccessList {
+        let tx = match self.txs.get(&tx_idx) {
+            Some(tx) => tx,
+            None => return AccessList::default(),
+        };
+        AccessList(
+            tx.state
+                .iter()
+                .filter(|(address, keys)| {
+                    !keys.is_empty() || !(tx.warm.contains(address) || is_precompiled(address))
+                })
+                .map(|(address, keys)| AccessListItem {
+                    address: *address,
+                    storage_keys: keys.iter().map(|key| H256(key.to_be_bytes())).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Check that every account and storage slot of the set has a proof in
+    /// `proofs`, so that no missing entry defaults to a zero value.
+    pub fn check_proofs(&self, proofs: &[EIP1186ProofResponse]) -> Result<(), Error> {
+        let missing = self.missing_proofs(proofs);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        for (address, key) in &missing {
+            match key {
+                None => log::error!("missing account proof of {:?}", address),
+                Some(key) => log::error!("missing storage proof of {:?} slot {:?}", address, key),
+            }
+        }
+        Err(Error::MissingProofs(missing))
+    }
 }
 
 impl From<Vec<Access>> for AccessSet {
@@ -102,7 +253,7 @@ impl From<Vec<Access>> for AccessSet {
 }
 
 /// Source of the code in the EVM execution.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum CodeSource {
     /// Code comes from a deployed contract at `Address`.
     Address(Address),
@@ -282,3 +433,142 @@ pub fn gen_state_access_trace<TX>(
     }
     Ok(accs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, StorageProof};
+
+    #[test]
+    fn access_set_missing_proofs() {
+        let a = address!("0x00000000000000000000000000000000000000aa");
+        let b = address!("0x00000000000000000000000000000000000000bb");
+        let access_set = AccessSet::from(vec![
+            Access::new(
+                None,
+                READ,
+                Storage {
+                    address: a,
+                    key: Word::one(),
+                },
+            ),
+            Access::new(
+                None,
+                READ,
+                Storage {
+                    address: a,
+                    key: Word::from(2),
+                },
+            ),
+            Access::new(None, READ, Account { address: b }),
+        ]);
+        let proofs = vec![EIP1186ProofResponse {
+            address: a,
+            storage_proof: vec![StorageProof {
+                key: Word::one(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        assert_eq!(
+            access_set.missing_proofs(&proofs),
+            vec![(a, Some(Word::from(2))), (b, None)]
+        );
+        assert!(matches!(
+            access_set.check_proofs(&proofs),
+            Err(Error::MissingProofs(_))
+        ));
+    }
+
+    #[test]
+    fn access_set_add_new() {
+        let a = address!("0x00000000000000000000000000000000000000aa");
+        let b = address!("0x00000000000000000000000000000000000000bb");
+        let storage = |key: u64| {
+            Access::new(
+                None,
+                READ,
+                Storage {
+                    address: a,
+                    key: Word::from(key),
+                },
+            )
+        };
+        let mut access_set = AccessSet::from(vec![storage(1)]);
+
+        let new = access_set.add_new(vec![
+            storage(1),
+            storage(2),
+            Access::new(None, READ, Code { address: b }),
+        ]);
+        assert_eq!(
+            new.state,
+            BTreeMap::from([(a, BTreeSet::from([Word::from(2)])), (b, BTreeSet::new())])
+        );
+        assert_eq!(new.code, BTreeSet::from([b]));
+        assert_eq!(access_set.state[&a].len(), 2);
+
+        // nothing new the second time
+        let new = access_set.add_new(vec![
+            storage(2),
+            Access::new(None, READ, Code { address: b }),
+        ]);
+        assert_eq!(new, AccessSet::default());
+    }
+
+    #[test]
+    fn access_set_is_ordered() {
+        let a = address!("0x00000000000000000000000000000000000000aa");
+        let b = address!("0x00000000000000000000000000000000000000bb");
+        let access_set = AccessSet::from(vec![
+            Access::new(None, READ, Code { address: b }),
+            Access::new(
+                None,
+                READ,
+                Storage {
+                    address: a,
+                    key: Word::from(2),
+                },
+            ),
+            Access::new(
+                None,
+                READ,
+                Storage {
+                    address: a,
+                    key: Word::one(),
+                },
+            ),
+            Access::new(None, READ, Code { address: a }),
+        ]);
+        assert_eq!(access_set.state.keys().collect::<Vec<_>>(), vec![&a, &b]);
+        assert_eq!(
+            access_set.state[&a].iter().collect::<Vec<_>>(),
+            vec![&Word::one(), &Word::from(2)]
+        );
+        assert_eq!(access_set.code.iter().collect::<Vec<_>>(), vec![&a, &b]);
+    }
+
+    #[test]
+    fn access_set_add_new_anchors_at_first_block() {
+        let a = address!("0x00000000000000000000000000000000000000aa");
+        let slot = |rw: RW| {
+            Access::new(
+                None,
+                rw,
+                Storage {
+                    address: a,
+                    key: Word::one(),
+                },
+            )
+        };
+        let mut access_set = AccessSet::default();
+
+        // block 1 writes the slot: it's fetched before block 1
+        let new = access_set.add_new(vec![slot(WRITE)]);
+        assert_eq!(new.state[&a], BTreeSet::from([Word::one()]));
+        // block 2 reads the value written by block 1, which must not be
+        // fetched again in the state before block 2
+        let new = access_set.add_new(vec![slot(READ)]);
+        assert!(new.state.is_empty());
+    }
+}
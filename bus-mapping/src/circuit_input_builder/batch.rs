@@ -0,0 +1,431 @@
+//! Witness generation of a batch of consecutive blocks, split into chunks
+//! that fit in the capacities of the circuits.
+
+use super::{
+    BlockHead, BlockRowUsage, ChainSpec, CircuitInputBuilder, CircuitsParams, EthBlock, L1FeeConfig,
+};
+use crate::{
+    error::ErrorKind,
+    state_db::{CodeDB, StateDB},
+    Error,
+};
+use eth_types::{evm_types::GasSchedules, GethExecTrace, Word, H256};
+use std::ops::Range;
+
+/// The circuit inputs of a chunk of a batch, with the roots that link it to
+/// the previous and next chunks in the aggregation.
+#[derive(Debug)]
+pub struct ChunkInputs {
+    /// Builder which handled the blocks of the chunk
+    pub builder: CircuitInputBuilder,
+    /// Indexes of the blocks of the chunk in the batch
+    pub blocks: Range<usize>,
+    /// Usage of the blocks of the chunk
+    pub usage: BlockRowUsage,
+    /// State root before the chunk, the post state root of the previous chunk
+    pub prev_state_root: Word,
+    /// State root after the last block of the chunk
+    pub post_state_root: Word,
+    /// Withdraw root before the chunk, the withdraw root of the previous
+    /// chunk
+    pub prev_withdraw_root: Word,
+    /// Withdraw root after the chunk
+    pub withdraw_root: Word,
+}
+
+/// Splits consecutive blocks into chunks and generates the circuit inputs of
+/// each, as every chunk is proven with the same [`CircuitsParams`].
+///
+/// The blocks of a chunk are chosen from the [`BlockRowUsage`] estimate
+/// of [`CircuitInputBuilder::estimate_circuit_rows`]. As the builder must
+/// know the last tx of a chunk before handling it, the chunk is closed ahead
+/// of the witness generation. Its usage is then checked against all the
+/// capacities of [`CircuitsParams::fits`], and the chunk is handled again
+/// without its last block if the estimate turns out to be short. The params
+/// must be fixed: the capacities left at 0 by [`CircuitsParams::auto`] fit no
+/// block.
+#[derive(Debug)]
+pub struct BatchBuilder {
+    chain_id: u64,
+    circuits_params: CircuitsParams,
+    l1_fee_config: L1FeeConfig,
+    gas_schedules: GasSchedules,
+    chain_spec: ChainSpec,
+    sdb: StateDB,
+    code_db: CodeDB,
+    prev_state_root: Word,
+    l1_queue_index: u64,
+    l1_queue_hashes: Vec<H256>,
+}
+
+impl BatchBuilder {
+    /// Create a batch builder from the state before the first block, whose
+    /// root is `prev_state_root`.
+    pub fn new(
+        chain_id: u64,
+        circuits_params: CircuitsParams,
+        sdb: StateDB,
+        code_db: CodeDB,
+        prev_state_root: Word,
+    ) -> Self {
+        Self {
+            chain_id,
+            circuits_params,
+            l1_fee_config: L1FeeConfig::default(),
+            gas_schedules: GasSchedules::default(),
+            chain_spec: ChainSpec::default(),
+            sdb,
+            code_db,
+            prev_state_root,
+            l1_queue_index: 0,
+            l1_queue_hashes: Vec::new(),
+        }
+    }
+
+    /// Set the index in the L1 message queue of the first L1 msg the batch
+    /// may include. The chunks continue the queue from it.
+    pub fn with_l1_queue_index(mut self, l1_queue_index: u64) -> Self {
+        self.l1_queue_index = l1_queue_index;
+        self
+    }
+
+    /// Set the hashes of the entries of the L1 message queue from the index
+    /// of [`Self::with_l1_queue_index`] on, as enqueued on L1. The L1 msgs of
+    /// the chunks must match them.
+    pub fn with_l1_queue_hashes(mut self, l1_queue_hashes: Vec<H256>) -> Self {
+        self.l1_queue_hashes = l1_queue_hashes;
+        self
+    }
+
+    /// Set the L1 fee config of the chunks.
+    pub fn with_l1_fee_config(mut self, l1_fee_config: L1FeeConfig) -> Self {
+        self.l1_fee_config = l1_fee_config;
+        self
+    }
+
+    /// Set the gas schedules of the storage opcodes of the chunks.
+    pub fn with_gas_schedules(mut self, gas_schedules: GasSchedules) -> Self {
+        self.gas_schedules = gas_schedules;
+        self
+    }
+
+    /// Set the system calls and hardforks of the chain of the chunks.
+    pub fn with_chain_spec(mut self, chain_spec: ChainSpec) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    /// Handle `blocks` in order and return their chunks. Fails with
+    /// [`Error::CircuitCapacityExceeded`] if a block doesn't fit alone in a
+    /// chunk.
+    pub fn build(
+        mut self,
+        blocks: &[(EthBlock, Vec<GethExecTrace>)],
+    ) -> Result<Vec<ChunkInputs>, Error> {
+        let mut chunks = Vec::new();
+        let mut begin = 0;
+        while begin < blocks.len() {
+            let mut end = self.next_chunk_end(blocks, begin)?;
+            let (builder, usage) = loop {
+                match self.build_chunk(&blocks[begin..end]) {
+                    Ok(built) => break built,
+                    Err(err) if err.kind() == ErrorKind::CapacityExceeded && end - begin > 1 => {
+                        log::warn!(
+                            "chunk of blocks {begin}..{end} exceeds the capacities, \
+                             retrying without its last block: {err}"
+                        );
+                        end -= 1;
+                    }
+                    Err(err) => return Err(err),
+                }
+            };
+            let chunk = ChunkInputs {
+                blocks: begin..end,
+                usage,
+                prev_state_root: self.prev_state_root,
+                post_state_root: builder.block.end_state_root(),
+                prev_withdraw_root: builder.block.prev_withdraw_root,
+                withdraw_root: builder.block.withdraw_root,
+                builder,
+            };
+            log::info!(
+                "chunk of blocks {:?}: state root {:?} -> {:?}",
+                chunk.blocks,
+                chunk.prev_state_root,
+                chunk.post_state_root
+            );
+            self.sdb = chunk.builder.sdb.clone();
+            self.code_db = chunk.builder.code_db.clone();
+            self.prev_state_root = chunk.post_state_root;
+            let num_l1_msgs = chunk
+                .builder
+                .block
+                .txs
+                .iter()
+                .filter(|tx| tx.tx_type.is_l1_msg())
+                .count();
+            self.l1_queue_index += num_l1_msgs as u64;
+            self.l1_queue_hashes
+                .drain(..num_l1_msgs.min(self.l1_queue_hashes.len()));
+            begin = end;
+            chunks.push(chunk);
+        }
+        Ok(chunks)
+    }
+
+    /// The end of the chunk starting at the block `begin`: the blocks are
+    /// added while their estimated usage fits.
+    fn next_chunk_end(
+        &self,
+        blocks: &[(EthBlock, Vec<GethExecTrace>)],
+        begin: usize,
+    ) -> Result<usize, Error> {
+        let estimator = self.new_builder();
+        let mut usage = BlockRowUsage::default();
+        for (end, (eth_block, geth_traces)) in blocks.iter().enumerate().skip(begin) {
+            let block_usage = estimator
+                .estimate_circuit_rows(eth_block, geth_traces)
+                .usage;
+            let report = self.circuits_params.fits(&(usage + block_usage));
+            if !report.fits() {
+                if end == begin {
+                    return Err(report.exceeded[0].clone().into());
+                }
+                return Ok(end);
+            }
+            usage += block_usage;
+        }
+        Ok(blocks.len())
+    }
+
+    /// Handle `blocks` as a chunk, from the state after the previous chunk,
+    /// and return it with its usage. Fails with
+    /// [`Error::CircuitCapacityExceeded`] if the usage exceeds a capacity.
+    fn build_chunk(
+        &self,
+        blocks: &[(EthBlock, Vec<GethExecTrace>)],
+    ) -> Result<(CircuitInputBuilder, BlockRowUsage), Error> {
+        let mut builder = self.new_builder();
+        for (idx, (eth_block, geth_traces)) in blocks.iter().enumerate() {
+            let is_last = idx == blocks.len() - 1;
+            let header = BlockHead::new(self.chain_id, Default::default(), eth_block)?;
+            builder.block.headers.insert(header.number.as_u64(), header);
+            builder.handle_block_inner(eth_block, geth_traces, is_last, is_last)?;
+        }
+        let usage = BlockRowUsage::from_builder(&builder)?;
+        if let Some(err) = self.circuits_params.fits(&usage).exceeded.first() {
+            return Err((*err).into());
+        }
+        Ok((builder, usage))
+    }
+
+    fn new_builder(&self) -> CircuitInputBuilder {
+        let mut builder = CircuitInputBuilder::new_from_headers(
+            self.circuits_params,
+            self.sdb.clone(),
+            self.code_db.clone(),
+            Default::default(),
+        );
+        builder.block.chain_id = self.chain_id;
+        builder.block.l1_fee_config = self.l1_fee_config;
+        builder.block.gas_schedules = self.gas_schedules.clone();
+        builder.block.chain_spec = self.chain_spec.clone();
+        builder.block.prev_state_root = self.prev_state_root;
+        builder.block.l1_queue_index = self.l1_queue_index;
+        builder.block.l1_queue_hashes = self.l1_queue_hashes.clone();
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::SubCircuit, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+
+    /// Three consecutive blocks of a tx each, split from a block of three
+    /// txs so that their states follow each other.
+    fn consecutive_blocks() -> (BlockData, Vec<(EthBlock, Vec<GethExecTrace>)>) {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                for tx in txs.iter_mut() {
+                    tx.from(accs[1].address).to(accs[0].address);
+                }
+            },
+            |block, _tx| block.number(0x100u64),
+        )
+        .unwrap()
+        .into();
+        let blocks = block
+            .eth_block
+            .transactions
+            .iter()
+            .zip(&block.geth_traces)
+            .enumerate()
+            .map(|(idx, (tx, trace))| {
+                let mut eth_block = block.eth_block.clone();
+                eth_block.number = Some((0x100 + idx as u64).into());
+                eth_block.state_root = H256::from_low_u64_be(idx as u64 + 1);
+                eth_block.transactions = vec![tx.clone()];
+                (eth_block, vec![trace.clone()])
+            })
+            .collect();
+        (BlockData::new_from_geth_data(block), blocks)
+    }
+
+    fn batch_builder(block_data: &BlockData, circuits_params: CircuitsParams) -> BatchBuilder {
+        BatchBuilder::new(
+            block_data.chain_id,
+            circuits_params,
+            block_data.sdb.clone(),
+            block_data.code_db.clone(),
+            Word::from(0xcafe),
+        )
+    }
+
+    #[test]
+    fn batch_in_chunks() {
+        let (block_data, blocks) = consecutive_blocks();
+        let circuits_params = CircuitsParams {
+            max_txs: 2,
+            max_mpt_rows: 10_000,
+            ..block_data.circuits_params
+        };
+        // Shanghai activates in the middle of the batch
+        let chain_spec = ChainSpec {
+            shanghai_block: Some(0x101),
+            ..Default::default()
+        };
+        let chunks = batch_builder(&block_data, circuits_params)
+            .with_chain_spec(chain_spec.clone())
+            .build(&blocks)
+            .unwrap();
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.blocks.clone())
+                .collect::<Vec<_>>(),
+            vec![0..2, 2..3]
+        );
+        assert_eq!(chunks[0].prev_state_root, Word::from(0xcafe));
+        assert_eq!(chunks[0].post_state_root, Word::from(2));
+        assert_eq!(chunks[1].prev_state_root, chunks[0].post_state_root);
+        assert_eq!(chunks[1].post_state_root, Word::from(3));
+        assert_eq!(chunks[1].prev_withdraw_root, chunks[0].withdraw_root);
+        for chunk in &chunks {
+            assert_eq!(chunk.builder.block.txs.len(), chunk.blocks.len());
+            assert_eq!(chunk.builder.block.prev_state_root, chunk.prev_state_root);
+            assert_eq!(chunk.builder.block.chain_spec, chain_spec);
+            assert!(circuits_params.fits(&chunk.usage).fits());
+        }
+
+        // the last chunk starts from the state left by the first one
+        let mut builder = block_data.new_circuit_input_builder();
+        for (eth_block, geth_traces) in &blocks {
+            builder
+                .handle_block_inner(eth_block, geth_traces, false, false)
+                .unwrap();
+        }
+        assert_eq!(
+            chunks[1].builder.sdb.get_account(&MOCK_ACCOUNTS[1]).1.nonce,
+            builder.sdb.get_account(&MOCK_ACCOUNTS[1]).1.nonce
+        );
+    }
+
+    #[test]
+    fn batch_block_too_large() {
+        let (block_data, blocks) = consecutive_blocks();
+        let circuits_params = CircuitsParams {
+            max_rws: 16,
+            ..block_data.circuits_params
+        };
+        let err = batch_builder(&block_data, circuits_params)
+            .build(&blocks)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CapacityExceeded);
+    }
+
+    #[test]
+    fn batch_respects_capacities() {
+        let (block_data, blocks) = consecutive_blocks();
+        let estimator = block_data.new_circuit_input_builder();
+        let usage = blocks
+            .iter()
+            .map(|(eth_block, geth_traces)| {
+                estimator
+                    .estimate_circuit_rows(eth_block, geth_traces)
+                    .usage
+            })
+            .collect::<Vec<_>>();
+        let roomy_params = CircuitsParams {
+            max_rws: 10_000,
+            max_txs: 3,
+            max_inner_blocks: 3,
+            max_rlp_rows: 10_000,
+            max_mpt_rows: 10_000,
+            ..block_data.circuits_params
+        };
+
+        type Capacity = (
+            SubCircuit,
+            fn(&mut CircuitsParams) -> &mut usize,
+            fn(&BlockRowUsage) -> usize,
+        );
+        let capacities: [Capacity; 3] = [
+            (
+                SubCircuit::InnerBlocks,
+                |params| &mut params.max_inner_blocks,
+                |usage| usage.inner_blocks,
+            ),
+            (
+                SubCircuit::Rlp,
+                |params| &mut params.max_rlp_rows,
+                |usage| usage.rlp_rows,
+            ),
+            (
+                SubCircuit::Mpt,
+                |params| &mut params.max_mpt_rows,
+                |usage| usage.mpt_rows,
+            ),
+        ];
+        for (circuit, capacity, used) in capacities {
+            // room for a block per chunk
+            let mut circuits_params = roomy_params;
+            *capacity(&mut circuits_params) = usage.iter().map(used).max().unwrap();
+            let chunks = batch_builder(&block_data, circuits_params)
+                .build(&blocks)
+                .unwrap();
+            assert_eq!(
+                chunks
+                    .iter()
+                    .map(|chunk| chunk.blocks.clone())
+                    .collect::<Vec<_>>(),
+                vec![0..1, 1..2, 2..3],
+                "{circuit:?}"
+            );
+            for chunk in &chunks {
+                assert!(circuits_params.fits(&chunk.usage).fits(), "{circuit:?}");
+            }
+
+            // no room for a block
+            *capacity(&mut circuits_params) = usage.iter().map(used).min().unwrap() - 1;
+            match batch_builder(&block_data, circuits_params).build(&blocks) {
+                Err(Error::CircuitCapacityExceeded(err)) => assert_eq!(err.circuit, circuit),
+                result => panic!("unexpected result for {circuit:?}: {result:?}"),
+            }
+        }
+    }
+}
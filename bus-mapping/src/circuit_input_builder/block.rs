@@ -3,17 +3,23 @@
 use super::{
     execution::{ExecState, PrecompileEvent, PrecompileEvents},
     transaction::Transaction,
-    CircuitsParams, CopyEvent, ExecStep, ExpEvent,
+    ChainSpec, CircuitsParams, CopyEvent, ExecStep, ExpEvent, L1FeeConfig, NumberOrHash,
+    WithdrawTrie,
 };
 use crate::{
-    operation::{OperationContainer, RWCounter},
+    operation::{OpEnum, Operation, OperationContainer, RWCounter, StorageOp, RW},
     Error,
 };
-use eth_types::{Address, Hash, ToWord, Word};
-use std::collections::{BTreeMap, HashMap};
+use eth_types::{
+    evm_types::{block_utils::calculate_blob_base_fee, GasSchedules, OpcodeId},
+    geth_types::get_excess_blob_gas,
+    Address, Hash, ToWord, Word,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 /// Context of a [`Block`] which can mutate in a [`Transaction`].
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockContext {
     /// Used to track the global counter in every operation in the block.
     /// Contains the next available value.
@@ -24,6 +30,12 @@ pub struct BlockContext {
     pub(crate) call_map: HashMap<usize, (usize, usize)>,
     /// Total gas used by previous transactions in this block.
     pub(crate) cumulative_gas_used: u64,
+    /// Index of the next tx to handle in the eth block being handled, so that
+    /// a restored [`BuilderCheckpoint`](super::BuilderCheckpoint) resumes
+    /// mid-block.
+    pub(crate) next_tx: usize,
+    /// Hashes of the txs handled with a caller-provided sender.
+    pub(crate) unverified_senders: HashSet<Hash>,
 }
 
 impl Default for BlockContext {
@@ -39,12 +51,14 @@ impl BlockContext {
             rwc: RWCounter::new(),
             call_map: HashMap::new(),
             cumulative_gas_used: 0,
+            next_tx: 0,
+            unverified_senders: HashSet::new(),
         }
     }
 }
 
 /// Block-wise execution steps that don't belong to any Transaction.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockSteps {
     /// EndBlock step that is repeated after the last transaction and before
     /// reaching the last EVM row.
@@ -69,7 +83,7 @@ impl Default for BlockSteps {
 }
 
 /// Circuit Input related to a block.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockHead {
     /// chain id
     pub chain_id: u64,
@@ -88,6 +102,8 @@ pub struct BlockHead {
     pub difficulty: Word,
     /// base fee
     pub base_fee: Word,
+    /// base fee per blob gas, derived from the excess blob gas of the block
+    pub blob_base_fee: Word,
     /// Original block from geth
     pub eth_block: eth_types::Block<eth_types::Transaction>,
 }
@@ -128,13 +144,14 @@ impl BlockHead {
                 eth_block.difficulty
             },
             base_fee: eth_block.base_fee_per_gas.unwrap_or_default(),
+            blob_base_fee: calculate_blob_base_fee(get_excess_blob_gas(eth_block)),
             eth_block: eth_block.clone(),
         })
     }
 }
 
 /// Circuit Input related to a block.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Block {
     /// The `Block` struct is in fact "Batch" for l2
     /// while "headers" are "Blocks" insides a batch
@@ -145,6 +162,15 @@ pub struct Block {
     pub withdraw_root: Word,
     /// Withdraw roof of the previous block
     pub prev_withdraw_root: Word,
+    /// Index in the L1 message queue of the first L1 msg the block may
+    /// include: the index after the L1 msgs of the previous chunk
+    pub l1_queue_index: u64,
+    /// Hashes of the entries of the L1 message queue from `l1_queue_index`
+    /// on, as enqueued on L1, which the L1 msgs of the block must match
+    pub l1_queue_hashes: Vec<Hash>,
+    /// Withdraw trie before the first handled tx, which the `SentMessage`
+    /// logs of the block are appended to
+    pub withdraw_trie: Option<WithdrawTrie>,
     /// Container of operations done in this block.
     pub container: OperationContainer,
     /// Transactions contained in the block
@@ -152,7 +178,7 @@ pub struct Block {
     /// Copy events in this block.
     pub copy_events: Vec<CopyEvent>,
     /// ..
-    pub code: HashMap<Hash, Vec<u8>>,
+    pub code: BTreeMap<Hash, Vec<u8>>,
     /// Inputs to the SHA3 opcode
     pub sha3_inputs: Vec<Vec<u8>>,
     /// Block-wise steps
@@ -165,6 +191,20 @@ pub struct Block {
     pub chain_id: u64,
     /// IO to/from the precompiled contract calls.
     pub precompile_events: PrecompileEvents,
+    /// Hashes of the code needed by the bytecode circuit: executed code, code
+    /// targeted by EXTCODESIZE/EXTCODECOPY and code deployed by CREATE*.
+    pub bytecode_hashes: BTreeSet<Hash>,
+    /// Blocks the L1 fee hardforks of the chain activate at
+    pub l1_fee_config: L1FeeConfig,
+    /// Costs of the storage opcodes, by the block they apply from
+    pub gas_schedules: GasSchedules,
+    /// Hardforks and system calls of the chain
+    pub chain_spec: ChainSpec,
+    /// Whether the copy events are dropped once counted, see
+    /// [`Self::set_dry_run`].
+    dry_run: bool,
+    /// Rows of the copy circuit used by the copy events dropped in a dry run
+    dry_run_copy_rows: usize,
 }
 
 impl Block {
@@ -185,6 +225,7 @@ impl Block {
                 .iter()
                 .map(|b| (b.number.as_u64(), b.clone()))
                 .collect::<BTreeMap<_, _>>(),
+            chain_id: headers.first().map_or(0, |head| head.chain_id),
             circuits_params,
             ..Default::default()
         }
@@ -239,12 +280,97 @@ impl Block {
     pub fn txs_mut(&mut self) -> &mut Vec<Transaction> {
         &mut self.txs
     }
+
+    /// Return the operations done by the step `step_idx` of the transaction
+    /// `tx_idx`, in the order they were generated, or `None` if there's no
+    /// such step.
+    pub fn ops_for_step(
+        &self,
+        tx_idx: usize,
+        step_idx: usize,
+    ) -> Option<Vec<(RWCounter, RW, OpEnum)>> {
+        let step = self.txs.get(tx_idx)?.steps().get(step_idx)?;
+        Some(self.ops_for_steps([step]))
+    }
+
+    /// Return the operations done by the steps of the call `call_idx` of the
+    /// transaction `tx_idx`, or `None` if there's no such call.
+    pub fn ops_for_call(
+        &self,
+        tx_idx: usize,
+        call_idx: usize,
+    ) -> Option<Vec<(RWCounter, RW, OpEnum)>> {
+        let tx = self.txs.get(tx_idx)?;
+        tx.calls().get(call_idx)?;
+        Some(self.ops_for_steps(tx.steps().iter().filter(|step| step.call_index == call_idx)))
+    }
+
+    /// Return the operations done by all the steps executing `opcode` in the
+    /// block.
+    pub fn ops_for_opcode(&self, opcode: OpcodeId) -> Vec<(RWCounter, RW, OpEnum)> {
+        self.ops_for_steps(
+            self.txs
+                .iter()
+                .flat_map(|tx| tx.steps())
+                .filter(|step| step.exec_state == ExecState::Op(opcode)),
+        )
+    }
+
+    /// Return the storage operations on the account `address`.
+    pub fn storage_ops_for_address(&self, address: Address) -> Vec<&Operation<StorageOp>> {
+        self.container.storage_ops_for_address(address)
+    }
+
+    fn ops_for_steps<'a>(
+        &self,
+        steps: impl IntoIterator<Item = &'a ExecStep>,
+    ) -> Vec<(RWCounter, RW, OpEnum)> {
+        steps
+            .into_iter()
+            .flat_map(|step| &step.bus_mapping_instance)
+            .map(|op_ref| {
+                self.container
+                    .get(op_ref)
+                    .expect("bus mapping instance refers to a missing operation")
+            })
+            .collect()
+    }
 }
 
 impl Block {
+    /// Drop the copy events and the operations only read by the circuits once
+    /// they are counted, for a dry run of the builder which only measures the
+    /// block. The block can't be turned into a witness afterwards.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+        self.container.set_dry_run(dry_run);
+    }
+    /// Rows of the copy circuit used by the copy events of the block,
+    /// including the ones dropped in a dry run.
+    pub fn copy_rows(&self) -> usize {
+        self.dry_run_copy_rows
+            + self
+                .copy_events
+                .iter()
+                .map(|event| event.full_length() as usize * 2)
+                .sum::<usize>()
+    }
     /// Push a copy event to the block.
     pub fn add_copy_event(&mut self, event: CopyEvent) {
-        self.copy_events.push(event);
+        for id in [&event.src_id, &event.dst_id] {
+            if let NumberOrHash::Hash(code_hash) = id {
+                self.bytecode_hashes.insert(*code_hash);
+            }
+        }
+        if self.dry_run {
+            self.dry_run_copy_rows += event.full_length() as usize * 2;
+        } else {
+            self.copy_events.push(event);
+        }
+    }
+    /// Record that the code of `code_hash` is needed by the bytecode circuit.
+    pub fn add_bytecode_hash(&mut self, code_hash: Hash) {
+        self.bytecode_hashes.insert(code_hash);
     }
     /// Push an exponentiation event to the block.
     pub fn add_exp_event(&mut self, event: ExpEvent) {
@@ -0,0 +1,238 @@
+//! Self-contained inputs of the witness generation of a block, saved to a
+//! file so that the witness can be generated again without a node.
+
+use super::{
+    build_state_code_db_of_format, truncate_to_max_txs, BlockHead, BuilderClient, ChainSpec,
+    CircuitInputBuilder, CircuitsParams, L1FeeConfig,
+};
+use crate::{state_db::ProofFormat, Error};
+use eth_types::{evm_types::GasSchedules, Address, EIP1186ProofResponse, GethExecTrace, Word};
+use ethers_core::types::Bytes;
+use ethers_providers::JsonRpcClient;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+/// Everything the witness generation of a block reads from the node: the
+/// block, the traces of its txs, and the proofs and codes of the accounts it
+/// accesses, in the state before the block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockBundle {
+    /// Chain identifier
+    pub chain_id: u64,
+    /// System calls and hardforks of the chain, whose pseudo-txs are already
+    /// in the block and its traces
+    pub chain_spec: ChainSpec,
+    /// Blocks the L1 fee hardforks of the chain activate at
+    pub l1_fee_config: L1FeeConfig,
+    /// Costs of the storage opcodes, by the block they apply from
+    pub gas_schedules: GasSchedules,
+    /// Layout of the state trie of the proofs
+    pub proof_format: ProofFormat,
+    /// Block from geth
+    pub eth_block: eth_types::Block<eth_types::Transaction>,
+    /// Execution traces of the txs of the block
+    pub geth_traces: Vec<GethExecTrace>,
+    /// Most recent block hashes in history, the latest one last
+    pub history_hashes: Vec<Word>,
+    /// Proofs of the accounts and storage slots accessed by the block
+    pub proofs: Vec<EIP1186ProofResponse>,
+    /// Codes accessed by the block, by the address they were fetched from
+    pub codes: Vec<(Address, Bytes)>,
+}
+
+impl BlockBundle {
+    /// Load a bundle stored by [`BlockBundle::store`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::IoError)?;
+        serde_json::from_reader(BufReader::new(file)).map_err(Error::SerdeError)
+    }
+
+    /// Store the bundle as JSON at `path`.
+    pub fn store(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let file = File::create(path).map_err(Error::IoError)?;
+        serde_json::to_writer(BufWriter::new(file), self).map_err(Error::SerdeError)
+    }
+}
+
+impl CircuitInputBuilder {
+    /// Generate the circuit inputs of the block of a bundle stored by
+    /// [`BuilderClient::dump_block_bundle`], as [`BuilderClient::gen_inputs`]
+    /// does from a node.
+    pub fn from_bundle(
+        path: impl AsRef<Path>,
+        circuits_params: CircuitsParams,
+    ) -> Result<Self, Error> {
+        let BlockBundle {
+            chain_id,
+            chain_spec,
+            l1_fee_config,
+            gas_schedules,
+            proof_format,
+            mut eth_block,
+            mut geth_traces,
+            history_hashes,
+            proofs,
+            codes,
+        } = BlockBundle::load(path)?;
+        let codes = codes
+            .into_iter()
+            .map(|(address, code)| (address, code.to_vec()))
+            .collect();
+        let (sdb, code_db) = build_state_code_db_of_format(proof_format, proofs, codes)?;
+        code_db.verify_hashes(&sdb)?;
+        truncate_to_max_txs(circuits_params, &mut eth_block, &mut geth_traces);
+
+        let block = BlockHead::new(chain_id, history_hashes, &eth_block)?;
+        let mut builder = Self::new_from_headers(circuits_params, sdb, code_db, &[block]);
+        builder.block.l1_fee_config = l1_fee_config;
+        builder.block.gas_schedules = gas_schedules;
+        builder.block.chain_spec = chain_spec;
+        builder.handle_block(&eth_block, &geth_traces)?;
+        Ok(builder)
+    }
+}
+
+impl<P: JsonRpcClient> BuilderClient<P> {
+    /// Fetch everything the witness generation of `block_num` reads from the
+    /// node. The account and storage proofs are verified if enabled.
+    pub async fn get_block_bundle(&self, block_num: u64) -> Result<BlockBundle, Error> {
+        let (eth_block, geth_traces, history_hashes, _prev_state_root, _receipts) =
+            self.get_block(block_num).await?;
+        let access_set = Self::get_state_accesses(&eth_block, &geth_traces)?;
+        let (proofs, codes) = self.get_state(block_num, access_set.into()).await?;
+        self.verify_proofs(block_num, &proofs).await?;
+        Ok(BlockBundle {
+            chain_id: self.chain_id,
+            chain_spec: self.chain_spec.clone(),
+            l1_fee_config: self.l1_fee_config,
+            gas_schedules: self.gas_schedules.clone(),
+            proof_format: self.proof_format,
+            eth_block,
+            geth_traces,
+            history_hashes,
+            proofs,
+            codes: codes
+                .into_iter()
+                .map(|(address, code)| (address, code.into()))
+                .collect(),
+        })
+    }
+
+    /// Save the bundle of `block_num` at `path`, for
+    /// [`CircuitInputBuilder::from_bundle`].
+    pub async fn dump_block_bundle(
+        &self,
+        block_num: u64,
+        path: impl AsRef<Path>,
+    ) -> Result<(), Error> {
+        self.get_block_bundle(block_num).await?.store(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::{get_state_accesses, AccessSet},
+        mock::BlockData,
+    };
+    use eth_types::{bytecode, evm_types::GasSchedule, geth_types::GethData, StorageProof};
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    #[test]
+    fn handle_block_from_bundle() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            SLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let block_data = BlockData::new_from_geth_data(block.clone());
+        let mut expected = block_data.new_circuit_input_builder();
+        expected
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // the proofs and codes a node would return for the mock state
+        let access_set: AccessSet = get_state_accesses(&block.eth_block, &block.geth_traces)
+            .unwrap()
+            .into();
+        let mut proofs = Vec::new();
+        let mut codes = Vec::new();
+        for address in access_set.state.keys() {
+            let (_, account) = block_data.sdb.get_account(address);
+            proofs.push(EIP1186ProofResponse {
+                address: *address,
+                balance: account.balance,
+                keccak_code_hash: account.keccak_code_hash,
+                code_hash: account.code_hash,
+                code_size: account.code_size,
+                nonce: account.nonce,
+                storage_proof: account
+                    .storage
+                    .iter()
+                    .map(|(key, value)| StorageProof {
+                        key: *key,
+                        value: *value,
+                        proof: vec![],
+                    })
+                    .collect(),
+                ..Default::default()
+            });
+            if let Some(code) = block_data.code_db.get(&account.code_hash) {
+                codes.push((*address, code.clone().into()));
+            }
+        }
+        let bundle = BlockBundle {
+            chain_id: block.chain_id,
+            chain_spec: ChainSpec {
+                shanghai_block: Some(0xcafe),
+                ..Default::default()
+            },
+            // the hardforks after the block don't change its witness
+            l1_fee_config: L1FeeConfig {
+                curie_block: Some(0xcaff),
+            },
+            gas_schedules: GasSchedules::default().with_schedule(0xcaff, GasSchedule::default()),
+            proof_format: ProofFormat::Mpt,
+            eth_block: block.eth_block.clone(),
+            geth_traces: block.geth_traces.clone(),
+            history_hashes: block.history_hashes.clone(),
+            proofs,
+            codes,
+        };
+
+        let path = std::env::temp_dir().join("handle_block_from_bundle.json");
+        bundle.store(&path).unwrap();
+        assert_eq!(BlockBundle::load(&path).unwrap(), bundle);
+        let builder = CircuitInputBuilder::from_bundle(&path, block_data.circuits_params).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(builder.block.chain_spec, bundle.chain_spec);
+        assert_eq!(builder.block.l1_fee_config, bundle.l1_fee_config);
+        assert_eq!(builder.block.gas_schedules, bundle.gas_schedules);
+        assert_eq!(builder.block_ctx.rwc, expected.block_ctx.rwc);
+        assert_eq!(
+            format!("{:?}", builder.block.txs),
+            format!("{:?}", expected.block.txs)
+        );
+        assert_eq!(
+            format!("{:?}", builder.block.container),
+            format!("{:?}", expected.block.container)
+        );
+    }
+}
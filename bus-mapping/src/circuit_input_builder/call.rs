@@ -1,12 +1,15 @@
+use std::ops::Range;
+
 use super::CodeSource;
 use crate::{exec_trace::OperationRef, Error};
 use eth_types::{
     evm_types::{Memory, OpcodeId},
-    Address, Hash, Word,
+    Address, Bytes, Hash, Word,
 };
+use serde::{Deserialize, Serialize};
 
 /// Type of a *CALL*/CREATE* Function.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CallKind {
     /// CALL
     Call,
@@ -51,7 +54,7 @@ impl TryFrom<OpcodeId> for CallKind {
 }
 
 /// Circuit Input related to an Ethereum Call
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Call {
     /// Unique call identifier within the Block.
     pub call_id: usize,
@@ -129,6 +132,68 @@ impl Call {
     }
 }
 
+/// Read-only bytes taken from a range of a source buffer (the tx input or the
+/// memory of a call). The bytes are reference counted: cloning a view doesn't
+/// copy them, nor does the view of the tx input. A range of the memory of a
+/// call is copied once into its view, since the memory keeps changing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BytesView {
+    bytes: Bytes,
+    offset: u64,
+}
+
+impl BytesView {
+    /// Create a view of `bytes` located at `offset` in its source buffer.
+    pub fn new(bytes: Bytes, offset: u64) -> Self {
+        Self { bytes, offset }
+    }
+
+    /// Copy `source[offset..offset + length]` into a new view. Bytes out of
+    /// `source` are zero.
+    pub fn from_source(source: &[u8], offset: u64, length: u64) -> Self {
+        let mut bytes = vec![0; length as usize];
+        let start = (offset as usize).min(source.len());
+        let end = (offset.saturating_add(length) as usize).min(source.len());
+        bytes[..end - start].copy_from_slice(&source[start..end]);
+        Self::new(bytes.into(), offset)
+    }
+
+    /// Offset of the view in its source buffer.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Range of the view in its source buffer.
+    pub fn range(&self) -> Range<u64> {
+        self.offset..self.offset + self.bytes.len() as u64
+    }
+
+    /// Shared bytes of the view.
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+}
+
+impl std::ops::Deref for BytesView {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for BytesView {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<Vec<u8>> for BytesView {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes.into(), 0)
+    }
+}
+
 /// Context of a [`Call`].
 #[derive(Debug, Default)]
 pub struct CallContext {
@@ -138,13 +203,14 @@ pub struct CallContext {
     /// call. It is incremented when a subcall in this call succeeds by the
     /// number of successful writes in the subcall.
     pub reversible_write_counter: usize,
-    /// Call data (copy of tx input or caller's
+    /// Call data (tx input or caller's
     /// memory[call_data_offset..call_data_offset + call_data_length])
-    pub call_data: Vec<u8>,
+    pub call_data: BytesView,
     /// memory context of current call
     pub memory: Memory,
-    /// return data buffer
-    pub return_data: Vec<u8>,
+    /// return data buffer (last callee's
+    /// memory[return_data_offset..return_data_offset + return_data_length])
+    pub return_data: BytesView,
 }
 
 impl CallContext {
@@ -0,0 +1,182 @@
+//! Witness of a single call frame (experimental).
+//!
+//! A [`CallFrameWitness`] selects the steps executed by one call of a
+//! transaction, together with the operations they perform and the conditions
+//! under which the call was entered. The entry conditions are meant to be
+//! exposed as public inputs, so that a call can be proven without the rest of
+//! its transaction.
+
+use eth_types::{ToWord, Word};
+
+use super::{Call, ExecState, ExecStep, Transaction};
+use crate::{exec_trace::OperationRef, Error};
+
+/// Conditions under which a call frame is entered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrameEntry {
+    /// Address of the caller
+    pub caller_address: eth_types::Address,
+    /// Address where the call is executed
+    pub address: eth_types::Address,
+    /// Hash of the executed code
+    pub code_hash: eth_types::Hash,
+    /// Value sent with the call
+    pub value: Word,
+    /// Call data offset in the caller's memory (or in the tx input)
+    pub call_data_offset: u64,
+    /// Call data length
+    pub call_data_length: u64,
+    /// Whether the call is static
+    pub is_static: bool,
+    /// Depth of the call
+    pub depth: usize,
+    /// Gas available at the first step of the call
+    pub gas: u64,
+    /// Read/Write counter at the first step of the call
+    pub rw_counter: usize,
+}
+
+impl CallFrameEntry {
+    /// Entry conditions as public inputs, in field declaration order.
+    pub fn public_inputs(&self) -> Vec<Word> {
+        vec![
+            self.caller_address.to_word(),
+            self.address.to_word(),
+            self.code_hash.to_word(),
+            self.value,
+            Word::from(self.call_data_offset),
+            Word::from(self.call_data_length),
+            Word::from(self.is_static as u64),
+            Word::from(self.depth),
+            Word::from(self.gas),
+            Word::from(self.rw_counter),
+        ]
+    }
+}
+
+/// Witness of a single call frame of a transaction.
+#[derive(Clone, Debug)]
+pub struct CallFrameWitness {
+    /// Conditions under which the call is entered
+    pub entry: CallFrameEntry,
+    /// The call
+    pub call: Call,
+    /// Steps executed by the call, excluding the steps of its callees
+    pub steps: Vec<ExecStep>,
+    /// Operations performed by the steps, i.e. the state touched by the call
+    pub operations: Vec<OperationRef>,
+}
+
+impl CallFrameWitness {
+    /// Select the call frame at `call_index` in the calls of `tx`.
+    pub fn new(tx: &Transaction, call_index: usize) -> Result<Self, Error> {
+        let call = tx
+            .calls()
+            .get(call_index)
+            .ok_or(Error::InternalError("call index out of range"))?
+            .clone();
+        // BeginTx and EndTx belong to the tx, not to its root call.
+        let steps: Vec<ExecStep> = tx
+            .steps()
+            .iter()
+            .filter(|step| {
+                step.call_index == call_index
+                    && matches!(step.exec_state, ExecState::Op(_) | ExecState::Precompile(_))
+            })
+            .cloned()
+            .collect();
+        let first_step = steps
+            .first()
+            .ok_or(Error::InternalError("call frame has no steps"))?;
+        let entry = CallFrameEntry {
+            caller_address: call.caller_address,
+            address: call.address,
+            code_hash: call.code_hash,
+            value: call.value,
+            call_data_offset: call.call_data_offset,
+            call_data_length: call.call_data_length,
+            is_static: call.is_static,
+            depth: call.depth,
+            gas: first_step.gas_left.0,
+            rw_counter: first_step.rwc.0,
+        };
+        let operations = steps
+            .iter()
+            .flat_map(|step| step.bus_mapping_instance.iter().cloned())
+            .collect();
+
+        Ok(Self {
+            entry,
+            call,
+            steps,
+            operations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{circuit_input_builder::CircuitsParams, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_DEPLOYED_CONTRACT_BYTECODE,
+    };
+
+    #[test]
+    fn call_frame_witness() {
+        let code = bytecode! {
+            PUSH21(*MOCK_DEPLOYED_CONTRACT_BYTECODE)
+            PUSH1(0)
+            MSTORE
+
+            PUSH1 (0x15)
+            PUSH1 (0xB)
+            PUSH1 (0)
+            CREATE
+
+            PUSH1 (0x20)
+            PUSH1 (0x20)
+            PUSH1 (0x20)
+            PUSH1 (0)
+            PUSH1 (0)
+            DUP6
+            PUSH2 (0xFFFF)
+            CALL
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data_with_params(
+            block.clone(),
+            CircuitsParams {
+                max_rws: 512,
+                ..Default::default()
+            },
+        )
+        .new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let tx = &builder.block.txs()[0];
+
+        // calls: root, CREATE, CALL to the created contract
+        let root = CallFrameWitness::new(tx, 0).unwrap();
+        let callee = CallFrameWitness::new(tx, 2).unwrap();
+        assert_eq!(callee.entry.caller_address, root.entry.address);
+        assert_eq!(callee.entry.depth, root.entry.depth + 1);
+        assert_eq!(callee.entry.gas, 0xFFFF);
+        assert!(callee.steps.iter().all(|step| step.call_index == 2));
+        assert_eq!(callee.entry.public_inputs().len(), 10);
+        assert!(!callee.operations.is_empty());
+        assert!(CallFrameWitness::new(tx, tx.calls().len()).is_err());
+    }
+}
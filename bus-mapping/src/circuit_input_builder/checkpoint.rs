@@ -0,0 +1,161 @@
+//! Checkpoints of the [`CircuitInputBuilder`] between the txs of a block.
+
+use super::{Block, BlockContext, CircuitInputBuilder, EthBlock};
+use crate::{
+    state_db::{CodeDB, StateDB},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+
+/// A copy of the whole state of a [`CircuitInputBuilder`], taken between two
+/// txs. Restoring it and handling the same block again resumes the witness
+/// generation at the first tx that was not handled yet, instead of starting
+/// over from the first tx of the block. A checkpoint can be serialized, so that
+/// the witness generation can resume in another process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuilderCheckpoint {
+    sdb: StateDB,
+    code_db: CodeDB,
+    block: Block,
+    block_ctx: BlockContext,
+}
+
+impl BuilderCheckpoint {
+    /// Number of txs handled in the batch when the checkpoint was taken.
+    pub fn num_txs(&self) -> usize {
+        self.block.txs.len()
+    }
+
+    /// Index of the tx of the current eth block to resume at.
+    pub fn next_tx(&self) -> usize {
+        self.block_ctx.next_tx
+    }
+}
+
+impl CircuitInputBuilder {
+    /// Take a checkpoint of the builder.
+    pub fn snapshot(&self) -> BuilderCheckpoint {
+        BuilderCheckpoint {
+            sdb: self.sdb.clone(),
+            code_db: self.code_db.clone(),
+            block: self.block.clone(),
+            block_ctx: self.block_ctx.clone(),
+        }
+    }
+
+    /// Reset the builder to the state of `checkpoint`. The next
+    /// [`Self::handle_block`] of the block being handled when the checkpoint
+    /// was taken continues after its last handled tx.
+    pub fn restore(&mut self, checkpoint: BuilderCheckpoint) {
+        self.sdb = checkpoint.sdb;
+        self.code_db = checkpoint.code_db;
+        self.block = checkpoint.block;
+        self.block_ctx = checkpoint.block_ctx;
+    }
+
+    /// Like [`Self::handle_block`], calling `on_tx` after each handled tx so
+    /// that the caller can take a [`Self::snapshot`].
+    pub fn handle_block_with_checkpoints(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[eth_types::GethExecTrace],
+        mut on_tx: impl FnMut(&Self),
+    ) -> Result<(), Error> {
+        self.handle_block_txs(eth_block, geth_traces, true, true, &mut on_tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, Word};
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    fn gen_block() -> GethData {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        TestContext::<2, 3>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                for (i, tx) in txs.iter_mut().enumerate() {
+                    tx.from(accs[1].address)
+                        .to(accs[0].address)
+                        .nonce(Word::from(i));
+                }
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into()
+    }
+
+    fn new_builder(block: &GethData) -> CircuitInputBuilder {
+        BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder()
+    }
+
+    /// Take a checkpoint after the first tx of `block`.
+    fn first_tx_checkpoint(block: &GethData) -> BuilderCheckpoint {
+        let mut checkpoint = None;
+        new_builder(block)
+            .handle_block_with_checkpoints(&block.eth_block, &block.geth_traces, |builder| {
+                if builder.block.txs.len() == 1 {
+                    checkpoint = Some(builder.snapshot());
+                }
+            })
+            .unwrap();
+        let checkpoint = checkpoint.unwrap();
+        assert_eq!((checkpoint.num_txs(), checkpoint.next_tx()), (1, 1));
+        checkpoint
+    }
+
+    /// Check that handling `block` from `checkpoint` builds the same witness as
+    /// handling it from the start.
+    fn assert_resumes(block: &GethData, checkpoint: BuilderCheckpoint) {
+        let mut expected = new_builder(block);
+        expected
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let mut resumed = new_builder(block);
+        resumed.restore(checkpoint);
+        resumed
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert_eq!(resumed.block_ctx.rwc, expected.block_ctx.rwc);
+        assert_eq!(
+            resumed.block_ctx.cumulative_gas_used,
+            expected.block_ctx.cumulative_gas_used
+        );
+        assert_eq!(
+            format!("{:?}", resumed.block.txs),
+            format!("{:?}", expected.block.txs)
+        );
+        assert_eq!(
+            format!("{:?}", resumed.block.container),
+            format!("{:?}", expected.block.container)
+        );
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let block = gen_block();
+        let checkpoint = first_tx_checkpoint(&block);
+        assert_resumes(&block, checkpoint);
+    }
+
+    #[test]
+    fn resume_from_stored_checkpoint() {
+        let block = gen_block();
+        let stored = serde_json::to_string(&first_tx_checkpoint(&block)).unwrap();
+        let checkpoint: BuilderCheckpoint = serde_json::from_str(&stored).unwrap();
+        assert_eq!((checkpoint.num_txs(), checkpoint.next_tx()), (1, 1));
+        assert_resumes(&block, checkpoint);
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
 use eth_types::{
     evm_types::{memory::MemoryWordRange, Gas, GasCost, MemoryAddress, OpcodeId, ProgramCounter},
     sign_types::SignData,
-    GethExecStep, Word, H256,
+    GethExecStep, ToBigEndian, ToWord, Word, H256,
 };
 use ethers_core::k256::elliptic_curve::subtle::CtOption;
 use gadgets::impl_expr;
@@ -27,10 +27,11 @@ use halo2_proofs::{
     },
     plonk::Expression,
 };
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 /// An execution step of the EVM.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExecStep {
     /// Execution state
     pub exec_state: ExecState,
@@ -68,6 +69,9 @@ pub struct ExecStep {
     pub error: Option<ExecError>,
     /// Optional auxiliary data that is attached to precompile call internal states.
     pub aux_data: Option<PrecompileAuxData>,
+    /// Wall-clock time spent generating this step.
+    #[cfg(feature = "step-profile")]
+    pub elapsed: std::time::Duration,
 }
 
 impl ExecStep {
@@ -96,6 +100,8 @@ impl ExecStep {
             copy_rw_counter_delta: 0,
             error: None,
             aux_data: None,
+            #[cfg(feature = "step-profile")]
+            elapsed: std::time::Duration::ZERO,
         }
     }
 
@@ -132,21 +138,28 @@ impl Default for ExecStep {
             copy_rw_counter_delta: 0,
             error: None,
             aux_data: None,
+            #[cfg(feature = "step-profile")]
+            elapsed: std::time::Duration::ZERO,
         }
     }
 }
 
 /// Execution state
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ExecState {
     /// EVM Opcode ID
-    Op(OpcodeId),
+    Op(#[serde(with = "opcode_byte")] OpcodeId),
     /// Precompile call
     Precompile(PrecompileCalls),
     /// Virtual step Begin Tx
     BeginTx,
+    /// Virtual step deleting an account self destructed in the tx, right
+    /// before End Tx
+    DestructAccount,
     /// Virtual step End Tx
     EndTx,
+    /// Virtual step End Inner Block, closing one block of a multi-block chunk
+    EndInnerBlock,
     /// Virtual step End Block
     EndBlock,
 }
@@ -190,7 +203,7 @@ impl ExecState {
 }
 
 /// Defines the various source/destination types for a copy event.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CopyDataType {
     /// When we need to pad the Copy rows of the circuit up to a certain maximum
     /// with rows that are not "useful".
@@ -216,7 +229,7 @@ impl CopyDataType {
         PrecompileCalls::iter().map(Self::Precompile).collect()
     }
 }
-const NUM_COPY_DATA_TYPES: usize = 15usize;
+const NUM_COPY_DATA_TYPES: usize = 16usize;
 pub struct CopyDataTypeIter {
     idx: usize,
     back_idx: usize,
@@ -240,6 +253,7 @@ impl CopyDataTypeIter {
             12usize => Some(CopyDataType::Precompile(PrecompileCalls::Bn128Mul)),
             13usize => Some(CopyDataType::Precompile(PrecompileCalls::Bn128Pairing)),
             14usize => Some(CopyDataType::Precompile(PrecompileCalls::Blake2F)),
+            15usize => Some(CopyDataType::Precompile(PrecompileCalls::PointEvaluation)),
             _ => None,
         }
     }
@@ -335,7 +349,7 @@ impl_expr!(CopyDataType, u64::from);
 
 /// Defines a single copy step in a copy event. This type is unified over the
 /// source/destination row in the copy table.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CopyStep {
     /// Byte value copied in this step.
     pub value: u8,
@@ -349,7 +363,7 @@ pub struct CopyStep {
 }
 
 /// Defines an enum type that can hold either a number or a hash value.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NumberOrHash {
     /// Variant to indicate a number value.
     Number(usize),
@@ -368,7 +382,7 @@ pub enum NumberOrHash {
 ///
 /// Additionally, when the destination is memory, `bytes_write_prev` holds the memory content
 /// *before* the write.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CopyBytes {
     /// Represents the list of (bytes, is_code, mask) copied during this copy event
     pub bytes: Vec<(u8, bool, bool)>,
@@ -393,12 +407,21 @@ impl CopyBytes {
             bytes_write_prev,
         }
     }
+
+    /// The copied data, excluding masked segments.
+    pub fn copied_bytes(&self) -> Vec<u8> {
+        self.bytes
+            .iter()
+            .filter(|&step| !step.2)
+            .map(|step| step.0)
+            .collect()
+    }
 }
 
 /// Defines a copy event associated with EVM opcodes such as CALLDATACOPY,
 /// CODECOPY, CREATE, etc. More information:
 /// <https://github.com/privacy-scaling-explorations/zkevm-specs/blob/master/specs/copy-proof.md>.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CopyEvent {
     /// Represents the start address at the source of the copy event.
     pub src_addr: u64,
@@ -437,7 +460,6 @@ impl CopyEvent {
     pub fn copy_length(&self) -> u64 {
         self.copy_bytes.bytes.iter().filter(|&step| !step.2).count() as u64
     }
-
     /// Whether the source performs RW lookups in the state circuit.
     pub fn is_source_rw(&self) -> bool {
         self.src_type == CopyDataType::Memory
@@ -448,6 +470,15 @@ impl CopyEvent {
         self.dst_type == CopyDataType::Memory || self.dst_type == CopyDataType::TxLog
     }
 
+    /// Whether the event copies within the memory of a single call, as MCOPY
+    /// does. It then does all its reads before its writes, as the bytes must
+    /// be read before an overlapping destination overwrites them.
+    pub fn is_memory_copy(&self) -> bool {
+        self.src_type == CopyDataType::Memory
+            && self.dst_type == CopyDataType::Memory
+            && self.src_id == self.dst_id
+    }
+
     /// The RW counter of the first RW lookup performed by this copy event.
     pub fn rw_counter_start(&self) -> u64 {
         usize::from(self.rw_counter_start) as u64
@@ -778,7 +809,7 @@ where
 }
 
 /// Intermediary multiplication step, representing `a * b == d (mod 2^256)`
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ExpStep {
     /// First multiplicand.
     pub a: Word,
@@ -799,7 +830,7 @@ impl From<(Word, Word, Word)> for ExpStep {
 }
 
 /// Event representating an exponentiation `a ^ b == d (mod 2^256)`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExpEvent {
     /// Identifier for the exponentiation trace.
     pub identifier: usize,
@@ -830,7 +861,7 @@ impl Default for ExpEvent {
 }
 
 /// I/Os from all precompiled contract calls in a block.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PrecompileEvents {
     /// All events.
     pub events: Vec<PrecompileEvent>,
@@ -893,10 +924,38 @@ impl PrecompileEvents {
             })
             .collect()
     }
+    /// Get all BigModExp events.
+    pub fn get_modexp_events(&self) -> Vec<BigModExp> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::Modexp(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .map(|op| *op.clone())
+            .collect()
+    }
+    /// Get all PointEvaluation events.
+    pub fn get_point_evaluation_events(&self) -> Vec<PointEvaluationOp> {
+        self.events
+            .iter()
+            .filter_map(|e| {
+                if let PrecompileEvent::PointEvaluation(op) = e {
+                    Some(op)
+                } else {
+                    None
+                }
+            })
+            .map(|op| *op.clone())
+            .collect()
+    }
 }
 
 /// I/O from a precompiled contract call.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PrecompileEvent {
     /// Represents the I/O from Ecrecover call.
     Ecrecover(SignData),
@@ -906,6 +965,10 @@ pub enum PrecompileEvent {
     EcMul(EcMulOp),
     /// Represents the I/O from EcPairing call.
     EcPairing(Box<EcPairingOp>),
+    /// Represents the I/O from Modexp call.
+    Modexp(Box<BigModExp>),
+    /// Represents the I/O from PointEvaluation call.
+    PointEvaluation(Box<PointEvaluationOp>),
 }
 
 impl Default for PrecompileEvent {
@@ -914,14 +977,59 @@ impl Default for PrecompileEvent {
     }
 }
 
+impl PrecompileEvent {
+    /// Returns the big-endian byte representation of the event, in the layout
+    /// of the I/O of the precompile call: the input followed by the output.
+    /// EcPairing ops hold the pairs padded for the ECC circuit.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let g1_bytes_be = |point: &G1Affine| {
+            std::iter::empty()
+                .chain(point.x.to_bytes().iter().rev())
+                .chain(point.y.to_bytes().iter().rev())
+                .cloned()
+                .collect::<Vec<u8>>()
+        };
+        match self {
+            Self::Ecrecover(sign_data) => {
+                let (sig_r, sig_s, sig_v) = sign_data.signature;
+                std::iter::empty()
+                    .chain(sign_data.msg_hash.to_bytes().iter().rev().cloned())
+                    .chain(Word::from(sig_v as u64 + 27).to_be_bytes())
+                    .chain(sig_r.to_bytes().iter().rev().cloned())
+                    .chain(sig_s.to_bytes().iter().rev().cloned())
+                    .chain(sign_data.get_addr().to_word().to_be_bytes())
+                    .collect()
+            }
+            Self::EcAdd(op) => [op.p, op.q, op.r].iter().flat_map(g1_bytes_be).collect(),
+            Self::EcMul(op) => std::iter::empty()
+                .chain(g1_bytes_be(&op.p))
+                .chain(op.s.to_bytes().iter().rev().cloned())
+                .chain(g1_bytes_be(&op.r))
+                .collect(),
+            Self::EcPairing(op) => std::iter::empty()
+                .chain(op.to_bytes_be())
+                .chain(op.output.to_be_bytes())
+                .collect(),
+            Self::Modexp(op) => op.to_bytes_be(),
+            Self::PointEvaluation(op) => std::iter::empty()
+                .chain(op.to_bytes_be())
+                .chain(op.output.iter().cloned())
+                .collect(),
+        }
+    }
+}
+
 /// EcAdd operation: P + Q = R
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EcAddOp {
     /// First EC point.
+    #[serde(with = "bn256_serde::g1")]
     pub p: G1Affine,
     /// Second EC point.
+    #[serde(with = "bn256_serde::g1")]
     pub q: G1Affine,
     /// Addition of the first and second EC points.
+    #[serde(with = "bn256_serde::g1")]
     pub r: G1Affine,
 }
 
@@ -983,13 +1091,16 @@ impl EcAddOp {
 }
 
 /// EcMul operation: s.P = R
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EcMulOp {
     /// EC point.
+    #[serde(with = "bn256_serde::g1")]
     pub p: G1Affine,
     /// Scalar.
+    #[serde(with = "bn256_serde::fr")]
     pub s: Fr,
     /// Result for s.P = R.
+    #[serde(with = "bn256_serde::g1")]
     pub r: G1Affine,
 }
 
@@ -1064,11 +1175,13 @@ pub const N_PAIRING_PER_OP: usize = 4;
 pub const N_BYTES_PER_PAIR: usize = 192;
 
 /// Pair of (G1, G2).
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcPairingPair {
     /// G1 point.
+    #[serde(with = "bn256_serde::g1")]
     pub g1_point: G1Affine,
     /// G2 point.
+    #[serde(with = "bn256_serde::g2")]
     pub g2_point: G2Affine,
 }
 
@@ -1136,7 +1249,7 @@ impl EcPairingPair {
 }
 
 /// EcPairing operation
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcPairingOp {
     /// tuples of G1 and G2 points supplied to the ECC circuit.
     pub pairs: [EcPairingPair; N_PAIRING_PER_OP],
@@ -1174,3 +1287,267 @@ impl EcPairingOp {
         false
     }
 }
+
+/// KZG point evaluation operation (EIP-4844), i.e. a proof that the blob committed to by
+/// `commitment` evaluates to `y` at `z`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PointEvaluationOp {
+    /// Versioned hash of the KZG commitment.
+    pub versioned_hash: H256,
+    /// Evaluation point.
+    pub z: Word,
+    /// Claimed evaluation.
+    pub y: Word,
+    /// KZG commitment, a compressed BLS12-381 G1 point (48 bytes).
+    pub commitment: Vec<u8>,
+    /// KZG proof, a compressed BLS12-381 G1 point (48 bytes).
+    pub proof: Vec<u8>,
+    /// Output of the precompile call, empty if the proof was rejected.
+    pub output: Vec<u8>,
+}
+
+impl PointEvaluationOp {
+    /// Creates a new PointEvaluation op given input and output bytes from a precompile call.
+    pub fn new_from_bytes(input: &[u8], output: &[u8]) -> Self {
+        assert_eq!(input.len(), 192);
+        assert!(output.is_empty() || output.len() == 64);
+
+        Self {
+            versioned_hash: H256::from_slice(&input[0x00..0x20]),
+            z: Word::from_big_endian(&input[0x20..0x40]),
+            y: Word::from_big_endian(&input[0x40..0x60]),
+            commitment: input[0x60..0x90].to_vec(),
+            proof: input[0x90..0xc0].to_vec(),
+            output: output.to_vec(),
+        }
+    }
+
+    /// Whether the proof was accepted by the precompile call.
+    pub fn is_valid(&self) -> bool {
+        !self.output.is_empty()
+    }
+
+    /// Returns the big-endian byte representation of the input to the precompile call.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        std::iter::empty()
+            .chain(self.versioned_hash.to_fixed_bytes())
+            .chain(self.z.to_be_bytes())
+            .chain(self.y.to_be_bytes())
+            .chain(self.commitment.iter().cloned())
+            .chain(self.proof.iter().cloned())
+            .collect()
+    }
+}
+
+/// Maximum size in bytes of the base, exponent and modulus of a modexp call supported by the
+/// circuits, i.e. operands of up to 1024 bits.
+pub const MODEXP_SIZE_LIMIT: usize = 128;
+
+/// The number of bytes in each limb a modexp operand is decomposed into.
+pub const MODEXP_LIMB_BYTES: usize = 16;
+
+/// BigModExp operation, i.e. base ^ exponent mod modulus, with operands of up to
+/// [`MODEXP_SIZE_LIMIT`] bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BigModExp {
+    /// Big-endian bytes of the base.
+    pub base: Vec<u8>,
+    /// Big-endian bytes of the exponent.
+    pub exponent: Vec<u8>,
+    /// Big-endian bytes of the modulus.
+    pub modulus: Vec<u8>,
+    /// Big-endian bytes of the result, as long as the modulus.
+    pub output: Vec<u8>,
+}
+
+impl BigModExp {
+    /// Upper bound of the rows taken by a modexp with operands at the size limit.
+    pub const MAX_ROWS: usize = 2 * MODEXP_SIZE_LIMIT * 8 * (MODEXP_SIZE_LIMIT / MODEXP_LIMB_BYTES);
+
+    /// Upper bound of the length of the input read by [`Self::new_from_input`]: the lengths and
+    /// the operands at the size limit.
+    pub const MAX_INPUT_LEN: usize = 3 * 32 + 3 * MODEXP_SIZE_LIMIT;
+
+    /// Creates a new BigModExp op given input and output bytes from a precompile call. The input
+    /// is right-padded with zeroes as in the EVM. Returns `None` if an operand exceeds
+    /// [`MODEXP_SIZE_LIMIT`].
+    pub fn new_from_bytes(input: &[u8], output: &[u8]) -> Option<Self> {
+        let op = Self::new_from_input(input)?;
+        assert_eq!(output.len(), op.modulus.len());
+
+        Some(Self {
+            output: output.to_vec(),
+            ..op
+        })
+    }
+
+    /// Creates a new BigModExp op with an empty output given the input bytes of a precompile
+    /// call, as in [`Self::new_from_bytes`].
+    pub fn new_from_input(input: &[u8]) -> Option<Self> {
+        let mut input = input.iter().cloned().chain(std::iter::repeat(0u8));
+        let mut read = |len: usize| input.by_ref().take(len).collect::<Vec<u8>>();
+
+        let mut lens = [0usize; 3];
+        for len in lens.iter_mut() {
+            let word = Word::from_big_endian(&read(32));
+            if word > Word::from(MODEXP_SIZE_LIMIT) {
+                return None;
+            }
+            *len = word.as_usize();
+        }
+        let [base_len, exp_len, mod_len] = lens;
+
+        Some(Self {
+            base: read(base_len),
+            exponent: read(exp_len),
+            modulus: read(mod_len),
+            output: vec![],
+        })
+    }
+
+    /// Decompose the big-endian `bytes` into little-endian limbs of [`MODEXP_LIMB_BYTES`] bytes,
+    /// lazily from the least significant end.
+    pub fn limbs(bytes: &[u8]) -> impl Iterator<Item = u128> + '_ {
+        bytes.rchunks(MODEXP_LIMB_BYTES).map(|chunk| {
+            chunk
+                .iter()
+                .fold(0u128, |limb, byte| (limb << 8) | *byte as u128)
+        })
+    }
+
+    /// The number of limbs of the widest operand, at least one.
+    pub fn n_limbs(&self) -> usize {
+        [&self.base, &self.exponent, &self.modulus]
+            .iter()
+            .map(|bytes| (bytes.len() + MODEXP_LIMB_BYTES - 1) / MODEXP_LIMB_BYTES)
+            .max()
+            .unwrap()
+            .max(1)
+    }
+
+    /// The number of significant bits of the exponent.
+    pub fn exponent_bits(&self) -> usize {
+        self.exponent
+            .iter()
+            .position(|byte| *byte != 0)
+            .map_or(0, |i| {
+                (self.exponent.len() - i) * 8 - self.exponent[i].leading_zeros() as usize
+            })
+    }
+
+    /// Rows taken by the op: a square-and-multiply takes a squaring and a multiplication per bit
+    /// of the exponent, each taking a row per limb.
+    pub fn num_rows(&self) -> usize {
+        2 * self.exponent_bits().max(1) * self.n_limbs()
+    }
+
+    /// Returns the big-endian byte representation of the I/O of the precompile call: the lengths,
+    /// the operands and the result.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        std::iter::empty()
+            .chain(Word::from(self.base.len()).to_be_bytes())
+            .chain(Word::from(self.exponent.len()).to_be_bytes())
+            .chain(Word::from(self.modulus.len()).to_be_bytes())
+            .chain(self.base.iter().cloned())
+            .chain(self.exponent.iter().cloned())
+            .chain(self.modulus.iter().cloned())
+            .chain(self.output.iter().cloned())
+            .collect()
+    }
+}
+
+/// Serde of an [`OpcodeId`] as its byte, which unlike its name is kept for the
+/// opcodes that are undefined in the enabled hardforks.
+mod opcode_byte {
+    use eth_types::evm_types::OpcodeId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(op: &OpcodeId, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(op.as_u8())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OpcodeId, D::Error> {
+        u8::deserialize(deserializer).map(OpcodeId::from)
+    }
+}
+
+/// Serde of the bn256 scalars and points of the EC precompile ops, as the
+/// little endian bytes of their coordinates.
+mod bn256_serde {
+    use halo2_proofs::halo2curves::bn256::{Fq, Fq2, Fr, G1Affine, G2Affine};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    fn fq<E: Error>(bytes: &[u8; 32]) -> Result<Fq, E> {
+        Option::from(Fq::from_bytes(bytes))
+            .ok_or_else(|| E::custom("invalid bn256 base field element"))
+    }
+
+    fn fq2<E: Error>(c0: &[u8; 32], c1: &[u8; 32]) -> Result<Fq2, E> {
+        Ok(Fq2 {
+            c0: fq(c0)?,
+            c1: fq(c1)?,
+        })
+    }
+
+    pub mod fr {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(scalar: &Fr, serializer: S) -> Result<S::Ok, S::Error> {
+            scalar.to_bytes().serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fr, D::Error> {
+            let bytes = <[u8; 32]>::deserialize(deserializer)?;
+            Option::from(Fr::from_bytes(&bytes))
+                .ok_or_else(|| D::Error::custom("invalid bn256 scalar"))
+        }
+    }
+
+    pub mod g1 {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            point: &G1Affine,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            [point.x.to_bytes(), point.y.to_bytes()].serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<G1Affine, D::Error> {
+            let [x, y] = <[[u8; 32]; 2]>::deserialize(deserializer)?;
+            Ok(G1Affine {
+                x: fq(&x)?,
+                y: fq(&y)?,
+            })
+        }
+    }
+
+    pub mod g2 {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            point: &G2Affine,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            [
+                point.x.c0.to_bytes(),
+                point.x.c1.to_bytes(),
+                point.y.c0.to_bytes(),
+                point.y.c1.to_bytes(),
+            ]
+            .serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<G2Affine, D::Error> {
+            let [x_c0, x_c1, y_c0, y_c1] = <[[u8; 32]; 4]>::deserialize(deserializer)?;
+            Ok(G2Affine {
+                x: fq2(&x_c0, &x_c1)?,
+                y: fq2(&y_c0, &y_c1)?,
+            })
+        }
+    }
+}
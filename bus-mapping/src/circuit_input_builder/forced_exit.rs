@@ -0,0 +1,144 @@
+//! Escape-hatch proving mode, where a single user-supplied tx is proven
+//! against a committed state root without the sequencer, e.g. to force a
+//! withdrawal after the sequencer halts.
+
+use super::{
+    build_state_code_db, get_state_accesses, AccessSet, BlockHead, CircuitInputBuilder,
+    CircuitsParams, EthBlock,
+};
+use crate::{
+    state_db::{verify_proofs, CodeDB, StateDB},
+    Error,
+};
+use eth_types::{Address, EIP1186ProofResponse, GethExecTrace, ToWord, H256};
+use std::{collections::HashMap, slice};
+
+/// Inputs of a forced-exit proof: a tx, its trace and the proofs of every
+/// account and storage slot it accesses in the state committed by
+/// `state_root`. Only Ethereum (MPT) proofs are supported.
+#[derive(Debug, Clone)]
+pub struct ForcedExit {
+    /// Chain id
+    pub chain_id: u64,
+    /// Committed state root the tx is executed on
+    pub state_root: H256,
+    /// Block the tx is executed in, holding the tx as its only tx
+    pub eth_block: EthBlock,
+    /// Execution trace of the tx
+    pub geth_trace: GethExecTrace,
+    /// Proofs of the accounts and storage slots accessed by the tx
+    pub proofs: Vec<EIP1186ProofResponse>,
+    /// Code of the accounts whose code is accessed by the tx
+    pub codes: HashMap<Address, Vec<u8>>,
+}
+
+impl ForcedExit {
+    /// Accounts, storage slots and codes accessed by the tx.
+    pub fn access_set(&self) -> Result<AccessSet, Error> {
+        if self.eth_block.transactions.len() != 1 {
+            return Err(Error::InvalidForcedExit("block must hold exactly one tx"));
+        }
+        Ok(get_state_accesses(&self.eth_block, slice::from_ref(&self.geth_trace))?.into())
+    }
+
+    /// Build the minimal StateDB of the tx, after checking that every access
+    /// has a proof and that the proofs match `state_root`.
+    pub fn build_state_code_db(&self) -> Result<(StateDB, CodeDB), Error> {
+        let access_set = self.access_set()?;
+        access_set.check_proofs(&self.proofs)?;
+        verify_proofs(self.state_root, &self.proofs)?;
+        for address in &access_set.code {
+            if !self.codes.contains_key(address) {
+                return Err(Error::AddressNotFound(*address));
+            }
+        }
+        let (sdb, code_db) = build_state_code_db(self.proofs.clone(), self.codes.clone());
+        code_db.verify_hashes(&sdb)?;
+        Ok((sdb, code_db))
+    }
+
+    /// Generate the circuit inputs of the tx, starting from `state_root`.
+    pub fn gen_inputs(
+        &self,
+        circuits_params: CircuitsParams,
+    ) -> Result<CircuitInputBuilder, Error> {
+        let (sdb, code_db) = self.build_state_code_db()?;
+        let header = BlockHead::new(self.chain_id, Vec::new(), &self.eth_block)?;
+        let mut builder =
+            CircuitInputBuilder::new_from_headers(circuits_params, sdb, code_db, &[header]);
+        builder.block.chain_id = self.chain_id;
+        builder.block.prev_state_root = self.state_root.to_word();
+        builder.handle_block(&self.eth_block, slice::from_ref(&self.geth_trace))?;
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_db::EMPTY_TRIE_ROOT;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::test_ctx::TestContext;
+
+    fn forced_exit(block: GethData) -> ForcedExit {
+        ForcedExit {
+            chain_id: block.chain_id,
+            state_root: *EMPTY_TRIE_ROOT,
+            eth_block: block.eth_block,
+            geth_trace: block.geth_traces[0].clone(),
+            proofs: Vec::new(),
+            codes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn forced_exit_rejects_unproven_state() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+            .unwrap()
+            .into();
+        let mut forced_exit = forced_exit(block);
+
+        // no proof at all
+        assert!(matches!(
+            forced_exit.gen_inputs(CircuitsParams::default()),
+            Err(Error::MissingProofs(_))
+        ));
+
+        // proofs claiming the funded sender in an empty state
+        let sender = forced_exit.eth_block.transactions[0].from;
+        forced_exit.proofs = forced_exit
+            .access_set()
+            .unwrap()
+            .state
+            .keys()
+            .map(|&address| EIP1186ProofResponse {
+                address,
+                balance: if address == sender {
+                    1.into()
+                } else {
+                    0.into()
+                },
+                ..Default::default()
+            })
+            .collect();
+        assert!(matches!(
+            forced_exit.gen_inputs(CircuitsParams::default()),
+            Err(Error::InvalidAccountProof(address)) if address == sender
+        ));
+    }
+
+    #[test]
+    fn forced_exit_rejects_multiple_txs() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP })
+            .unwrap()
+            .into();
+        let mut forced_exit = forced_exit(block);
+        let tx = forced_exit.eth_block.transactions[0].clone();
+        forced_exit.eth_block.transactions.push(tx);
+
+        assert!(matches!(
+            forced_exit.gen_inputs(CircuitsParams::default()),
+            Err(Error::InvalidForcedExit(_))
+        ));
+    }
+}
@@ -0,0 +1,386 @@
+//! Opt-in audit of the gas cost of the steps against the gas model of the
+//! circuits, to find the opcodes where the witness would disagree with geth.
+
+use super::{CircuitInputBuilder, CircuitInputStateRef};
+use crate::Error;
+use eth_types::{
+    evm_types::{
+        gas_utils::{memory_copier_gas_cost, memory_expansion_gas_cost},
+        GasCost, OpcodeId,
+    },
+    GethExecStep, ToAddress, Word,
+};
+use serde::Serialize;
+
+/// A step whose gas cost in the trace differs from the one of the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GasDivergence {
+    /// Index of the tx in the block
+    pub tx_index: u64,
+    /// Index of the step in the trace of the tx
+    pub step_index: usize,
+    /// Program counter
+    pub pc: u64,
+    /// Opcode
+    pub opcode: OpcodeId,
+    /// Call depth
+    pub depth: u16,
+    /// Gas cost from the model
+    pub expected: u64,
+    /// Gas cost from the trace, `geth_step.gas - next_step.gas`
+    pub actual: u64,
+}
+
+/// The outcome of a [`GasAuditor`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GasAuditReport {
+    /// Number of steps compared with the model
+    pub audited_steps: usize,
+    /// Number of steps which can't be compared: the opcode isn't modelled,
+    /// the step fails, or the next step is in another call.
+    pub skipped_steps: usize,
+    /// Steps where the model and the trace disagree
+    pub divergences: Vec<GasDivergence>,
+}
+
+impl GasAuditReport {
+    /// Whether no divergence has been found.
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Recomputes the gas cost of every step handled by the
+/// [`CircuitInputBuilder`] and compares it with
+/// the one of the trace.
+#[derive(Debug, Clone, Default)]
+pub struct GasAuditor {
+    report: GasAuditReport,
+}
+
+impl GasAuditor {
+    /// Create an auditor with an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The report of the steps audited so far.
+    pub fn report(&self) -> &GasAuditReport {
+        &self.report
+    }
+
+    /// Consume the auditor and return its report.
+    pub fn into_report(self) -> GasAuditReport {
+        self.report
+    }
+
+    /// Audit `geth_steps[0]`, with `geth_steps[1]` being the next step. Must
+    /// be called before the operations of the step are generated, as the
+    /// model reads the state before the step.
+    pub(crate) fn audit_step(
+        &mut self,
+        state: &CircuitInputStateRef,
+        tx_index: u64,
+        step_index: usize,
+        geth_steps: &[GethExecStep],
+    ) -> Result<(), Error> {
+        let geth_step = &geth_steps[0];
+        let next_step = match geth_steps.get(1) {
+            Some(next_step) if geth_step.error.is_none() && next_step.depth == geth_step.depth => {
+                next_step
+            }
+            _ => {
+                self.report.skipped_steps += 1;
+                return Ok(());
+            }
+        };
+        let Some(expected) = expected_gas_cost(state, geth_step)? else {
+            self.report.skipped_steps += 1;
+            return Ok(());
+        };
+        self.report.audited_steps += 1;
+        let actual = geth_step.gas.0.saturating_sub(next_step.gas.0);
+        if expected != actual {
+            log::warn!(
+                "gas divergence in tx {} step {}: {:?} at pc {} depth {}, expected {} actual {}",
+                tx_index,
+                step_index,
+                geth_step.op,
+                geth_step.pc.0,
+                geth_step.depth,
+                expected,
+                actual
+            );
+            self.report.divergences.push(GasDivergence {
+                tx_index,
+                step_index,
+                pc: geth_step.pc.0,
+                opcode: geth_step.op,
+                depth: geth_step.depth,
+                expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl CircuitInputBuilder {
+    /// Audit the gas cost of the steps of the txs handled from now on.
+    pub fn enable_gas_audit(&mut self) {
+        self.gas_auditor.get_or_insert_with(GasAuditor::new);
+    }
+
+    /// The report of the gas audit, if enabled.
+    pub fn gas_audit_report(&self) -> Option<&GasAuditReport> {
+        self.gas_auditor.as_ref().map(GasAuditor::report)
+    }
+}
+
+/// The gas cost of `geth_step` in the gas model of the circuits, computed from
+/// the state before the step. `None` for the opcodes whose cost depends on a
+/// call: CALL, CALLCODE, DELEGATECALL, STATICCALL, CREATE and CREATE2. The
+/// storage opcodes are priced with the gas schedule of the block of the tx.
+pub fn expected_gas_cost(
+    state: &CircuitInputStateRef,
+    geth_step: &GethExecStep,
+) -> Result<Option<u64>, Error> {
+    let op = geth_step.op;
+    let stack = &geth_step.stack;
+    let curr_memory_word_size = state.call_ctx()?.memory_word_size();
+    let next_memory_word_size = |offset: Word, length: Word| {
+        if length.is_zero() {
+            curr_memory_word_size
+        } else {
+            curr_memory_word_size.max((offset.low_u64() + length.low_u64() + 31) / 32)
+        }
+    };
+    let memory_expansion = |offset: Word, length: Word| {
+        memory_expansion_gas_cost(curr_memory_word_size, next_memory_word_size(offset, length))
+    };
+    let memory_copier = |offset: Word, length: Word, per_word_copy_gas: GasCost| {
+        memory_copier_gas_cost(
+            curr_memory_word_size,
+            next_memory_word_size(offset, length),
+            length.low_u64(),
+            per_word_copy_gas.0,
+        )
+    };
+    let cold_account_surcharge = |address: Word| {
+        if state
+            .sdb
+            .check_account_in_access_list(&address.to_address())
+        {
+            0
+        } else {
+            GasCost::COLD_ACCOUNT_ACCESS.0 - GasCost::WARM_ACCESS.0
+        }
+    };
+
+    let schedule = state.block.gas_schedules.schedule(state.tx.block_num);
+    let constant = match op {
+        OpcodeId::TLOAD => schedule.tload,
+        OpcodeId::TSTORE => schedule.tstore,
+        _ => op.constant_gas_cost().0,
+    };
+    let dynamic = match op {
+        OpcodeId::MLOAD | OpcodeId::MSTORE => memory_expansion(stack.nth_last(0)?, 32.into()),
+        OpcodeId::MSTORE8 => memory_expansion(stack.nth_last(0)?, 1.into()),
+        OpcodeId::RETURN | OpcodeId::REVERT => {
+            memory_expansion(stack.nth_last(0)?, stack.nth_last(1)?)
+        }
+        OpcodeId::SHA3 => memory_copier(stack.nth_last(0)?, stack.nth_last(1)?, GasCost::COPY_SHA3),
+        OpcodeId::CALLDATACOPY | OpcodeId::CODECOPY | OpcodeId::RETURNDATACOPY => {
+            memory_copier(stack.nth_last(0)?, stack.nth_last(2)?, GasCost::COPY)
+        }
+        OpcodeId::MCOPY => {
+            let offset = stack.nth_last(0)?.max(stack.nth_last(1)?);
+            memory_copier(offset, stack.nth_last(2)?, GasCost::COPY)
+        }
+        OpcodeId::EXTCODECOPY => {
+            cold_account_surcharge(stack.nth_last(0)?)
+                + memory_copier(stack.nth_last(1)?, stack.nth_last(3)?, GasCost::COPY)
+        }
+        OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
+            cold_account_surcharge(stack.nth_last(0)?)
+        }
+        OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+            let topics = (op.as_u8() - OpcodeId::LOG0.as_u8()) as u64;
+            let length = stack.nth_last(1)?;
+            GasCost::LOG.0 * (topics + 1)
+                + 8 * length.low_u64()
+                + memory_expansion(stack.nth_last(0)?, length)
+        }
+        OpcodeId::EXP => {
+            let exponent_bytes = (stack.nth_last(1)?.bits() as u64 + 7) / 8;
+            GasCost::EXP_BYTE_TIMES.0 * exponent_bytes
+        }
+        OpcodeId::SLOAD | OpcodeId::SSTORE => {
+            let address = state.call()?.address;
+            let key = stack.nth_last(0)?;
+            let is_warm = state
+                .sdb
+                .check_account_storage_in_access_list(&(address, key));
+            if op == OpcodeId::SLOAD {
+                schedule.sload(is_warm)
+            } else {
+                let value = stack.nth_last(1)?;
+                let current = *state.sdb.get_storage(&address, &key).1;
+                let original = *state.sdb.get_committed_storage(&address, &key).1;
+                schedule.sstore(value, current, original, is_warm)
+            }
+        }
+        OpcodeId::SELFDESTRUCT => {
+            let beneficiary = stack.nth_last(0)?.to_address();
+            let is_cold = !state.sdb.check_account_in_access_list(&beneficiary);
+            let value = state.sdb.get_balance(&state.call()?.address);
+            let is_new = state.sdb.get_account(&beneficiary).1.is_empty() && !value.is_zero();
+            let cold_cost = if is_cold {
+                GasCost::COLD_ACCOUNT_ACCESS.0
+            } else {
+                0
+            };
+            cold_cost + if is_new { GasCost::NEW_ACCOUNT.0 } else { 0 }
+        }
+        op if op.is_call_or_create() => return Ok(None),
+        _ => 0,
+    };
+    Ok(Some(constant + dynamic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{
+        bytecode,
+        evm_types::{GasSchedule, GasSchedules},
+        geth_types::GethData,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    #[test]
+    fn gas_audit_of_block() {
+        let code = bytecode! {
+            PUSH1(0x02)
+            PUSH1(0x03)
+            EXP
+            PUSH1(0x40)
+            MSTORE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            SHA3
+            PUSH1(0x00)
+            SSTORE
+            PUSH1(0x00)
+            SLOAD
+            PUSH1(0x20)
+            PUSH1(0x00)
+            PUSH1(0x00)
+            CODECOPY
+            ADDRESS
+            BALANCE
+            PUSH1(0x20)
+            PUSH1(0x00)
+            LOG0
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.enable_gas_audit();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let report = builder.gas_audit_report().unwrap();
+        assert!(report.is_clean(), "{:?}", report.divergences);
+        // every step but the last STOP, which has no next step
+        assert_eq!(report.audited_steps, 21);
+        assert_eq!(report.skipped_steps, 1);
+    }
+
+    #[test]
+    fn gas_audit_of_tampered_trace() {
+        let code = bytecode! {
+            PUSH1(0x02)
+            PUSH1(0x03)
+            EXP
+            PUSH1(0x40)
+            MSTORE
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        // one gas moved from EXP to the PUSH1 after it
+        let struct_logs = &mut block.geth_traces[0].struct_logs;
+        assert_eq!(struct_logs[3].op, OpcodeId::PUSH1);
+        struct_logs[3].gas.0 += 1;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.enable_gas_audit();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let report = builder.gas_audit_report().unwrap();
+        let divergences = report
+            .divergences
+            .iter()
+            .map(|d| (d.step_index, d.opcode, d.expected as i64 - d.actual as i64))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            divergences,
+            vec![(2, OpcodeId::EXP, 1), (3, OpcodeId::PUSH1, -1)]
+        );
+    }
+
+    #[test]
+    fn gas_audit_with_schedule() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        // repriced from the block of the tx on
+        let repriced = GasSchedule {
+            sstore_set: GasCost::SSTORE_SET.0 + 1,
+            ..Default::default()
+        };
+        builder.block.gas_schedules = GasSchedules::default()
+            .with_schedule(0xcaff, GasSchedule::default())
+            .with_schedule(0xcafe, repriced);
+        builder.enable_gas_audit();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let report = builder.gas_audit_report().unwrap();
+        assert_eq!(report.divergences.len(), 1);
+        let divergence = &report.divergences[0];
+        assert_eq!(divergence.opcode, OpcodeId::SSTORE);
+        assert_eq!(divergence.expected, divergence.actual + 1);
+    }
+}
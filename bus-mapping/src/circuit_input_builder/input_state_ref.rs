@@ -1,9 +1,9 @@
 //! CircuitInput builder tooling module.
 
 use super::{
-    get_call_memory_offset_length, get_create_init_code, Block, BlockContext, Call, CallContext,
-    CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, PrecompileEvent, Transaction,
-    TransactionContext,
+    get_call_memory_offset_length, get_create_init_code, Block, BlockContext, BytesView, Call,
+    CallContext, CallKind, CodeSource, CopyEvent, ExecState, ExecStep, ExpEvent, PrecompileEvent,
+    Transaction, TransactionContext,
 };
 #[cfg(feature = "scroll")]
 use crate::util::KECCAK_CODE_HASH_ZERO;
@@ -16,8 +16,8 @@ use crate::{
     exec_trace::OperationRef,
     operation::{
         AccountField, AccountOp, CallContextField, CallContextOp, MemoryOp, Op, OpEnum, Operation,
-        StackOp, Target, TxAccessListAccountOp, TxLogField, TxLogOp, TxReceiptField, TxReceiptOp,
-        RW,
+        StackOp, Target, TxAccessListAccountOp, TxCreatedAccountOp, TxDestructedAccountOp,
+        TxLogField, TxLogOp, TxReceiptField, TxReceiptOp, RW,
     },
     precompile::is_precompiled,
     state_db::{CodeDB, StateDB},
@@ -28,7 +28,7 @@ use eth_types::{
     evm_types::{
         gas_utils::memory_expansion_gas_cost,
         memory::{MemoryRange, MemoryWordRange},
-        Gas, GasCost, Memory, MemoryAddress, MemoryRef, OpcodeId, StackAddress, MAX_CODE_SIZE,
+        Gas, GasCost, Memory, MemoryAddress, MemoryRef, OpcodeId, StackAddress,
     },
     Address, Bytecode, GethExecStep, ToAddress, ToBigEndian, ToWord, Word, H256, U256,
 };
@@ -84,6 +84,17 @@ impl<'a> CircuitInputStateRef<'a> {
             .steps()
             .last()
             .expect("steps should have at least one BeginTx step");
+        // The DestructAccount steps keep the state of the step halting the tx.
+        if prev_step.exec_state == ExecState::DestructAccount {
+            return ExecStep {
+                exec_state: ExecState::EndTx,
+                gas_left: prev_step.gas_left,
+                rwc: self.block_ctx.rwc,
+                reversible_write_counter: prev_step.reversible_write_counter,
+                log_id: prev_step.log_id,
+                ..Default::default()
+            };
+        }
         ExecStep {
             exec_state: ExecState::EndTx,
             gas_left: if prev_step.error.is_none() {
@@ -118,6 +129,15 @@ impl<'a> CircuitInputStateRef<'a> {
         }
     }
 
+    /// Create a new DestructAccount step, between the step halting the tx and
+    /// its EndTx step
+    pub fn new_destruct_account_step(&self) -> ExecStep {
+        ExecStep {
+            exec_state: ExecState::DestructAccount,
+            ..self.new_end_tx_step()
+        }
+    }
+
     /// Push an [`Operation`](crate::operation::Operation) into the
     /// [`OperationContainer`](crate::operation::OperationContainer) with the
     /// next [`RWCounter`](crate::operation::RWCounter) and then adds a
@@ -347,9 +367,10 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// First check the validity and consistency of the rw operation against the
     /// account in the StateDB, then if the rw operation is a write, apply
-    /// it to the corresponding account in the StateDB.
+    /// it to the corresponding account in the StateDB, which touches it. A
+    /// read doesn't create a non-existing account.
     fn check_update_sdb_account(&mut self, rw: RW, op: &AccountOp) {
-        let account = self.sdb.get_account_mut(&op.address).1;
+        let account = self.sdb.get_account(&op.address).1;
         // -- sanity check begin --
         // Verify that a READ doesn't change the field value
         if matches!(rw, RW::READ) && op.value_prev != op.value {
@@ -425,6 +446,8 @@ impl<'a> CircuitInputStateRef<'a> {
         // -- sanity check end --
         // Perform the write to the account in the StateDB
         if matches!(rw, RW::WRITE) {
+            self.sdb.touch_account(op.address);
+            let account = self.sdb.get_account_mut(&op.address).1;
             match op.field {
                 AccountField::Nonce => account.nonce = op.value,
                 AccountField::Balance => {
@@ -594,6 +617,61 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok(())
     }
 
+    /// Mark `address` as created in the current transaction. The write is
+    /// reversible, so that the account of a reverted creation isn't.
+    pub fn tx_created_account_write(
+        &mut self,
+        step: &mut ExecStep,
+        address: Address,
+    ) -> Result<(), Error> {
+        let is_created_prev = self.sdb.is_created_in_tx(&address);
+        self.push_op_reversible(
+            step,
+            TxCreatedAccountOp {
+                tx_id: self.tx_ctx.id(),
+                address,
+                is_created: true,
+                is_created_prev,
+            },
+        )
+    }
+
+    /// Read whether `address` was created in the current transaction.
+    pub fn tx_created_account_read(&mut self, step: &mut ExecStep, address: Address) -> bool {
+        let is_created = self.sdb.is_created_in_tx(&address);
+        self.push_op(
+            step,
+            RW::READ,
+            TxCreatedAccountOp {
+                tx_id: self.tx_ctx.id(),
+                address,
+                is_created,
+                is_created_prev: is_created,
+            },
+        );
+        is_created
+    }
+
+    /// Mark `address` as self destructed in the current transaction, to be
+    /// deleted at its end. The write is reversible, so that the account of a
+    /// reverted `SELFDESTRUCT` isn't.
+    pub fn tx_destructed_account_write(
+        &mut self,
+        step: &mut ExecStep,
+        address: Address,
+    ) -> Result<(), Error> {
+        let is_destructed_prev = self.sdb.is_destructed(&address);
+        self.push_op_reversible(
+            step,
+            TxDestructedAccountOp {
+                tx_id: self.tx_ctx.id(),
+                address,
+                is_destructed: true,
+                is_destructed_prev,
+            },
+        )
+    }
+
     /// Push 2 reversible [`AccountOp`] to update `sender` and `receiver`'s
     /// balance by `value`. If `fee` is existing (not None), also need to push 1
     /// non-reversible [`AccountOp`] to update `sender` balance by `fee`.
@@ -612,6 +690,9 @@ impl<'a> CircuitInputStateRef<'a> {
         if !found {
             return Err(Error::AccountNotFound(sender));
         }
+        // the receiver is touched even by a zero value transfer, so that it's
+        // deleted at the end of the tx if it's empty (EIP-161)
+        self.sdb.touch_account(receiver);
         let mut sender_balance_prev = sender_account.balance;
         debug_assert!(
             sender_account.balance >= value + fee.unwrap_or_default(),
@@ -748,7 +829,6 @@ impl<'a> CircuitInputStateRef<'a> {
     /// Fetch and return code for the given code hash from the code DB.
     pub fn code(&self, code_hash: H256) -> Result<Vec<u8>, Error> {
         self.code_db
-            .0
             .get(&code_hash)
             .cloned()
             .ok_or(Error::CodeNotFound(code_hash))
@@ -813,12 +893,13 @@ impl<'a> CircuitInputStateRef<'a> {
         let current_call = self.call_ctx().expect("current call not found");
         let call_data = match call.kind {
             CallKind::Call | CallKind::CallCode | CallKind::DelegateCall | CallKind::StaticCall => {
-                current_call.memory.read_chunk(MemoryRange::new_with_length(
+                BytesView::from_source(
+                    &current_call.memory.0,
                     call.call_data_offset,
                     call.call_data_length,
-                ))
+                )
             }
-            CallKind::Create | CallKind::Create2 => Vec::new(),
+            CallKind::Create | CallKind::Create2 => BytesView::default(),
         };
 
         let call_id = call.call_id;
@@ -881,7 +962,7 @@ impl<'a> CircuitInputStateRef<'a> {
 
     /// Check if address is a precompiled or not.
     pub fn is_precompiled(&self, address: &Address) -> bool {
-        address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+        is_precompiled(address)
     }
 
     /// Parse [`Call`] from a *CALL*/CREATE* step.
@@ -893,7 +974,7 @@ impl<'a> CircuitInputStateRef<'a> {
             .unwrap();
         let kind = CallKind::try_from(step.op)?;
         let caller = self.call()?;
-        let caller_ctx = self.call_ctx()?;
+        let caller_ctx = self.tx_ctx.call_ctx()?;
 
         let (caller_address, address, value) = match kind {
             CallKind::Call => (
@@ -918,8 +999,8 @@ impl<'a> CircuitInputStateRef<'a> {
 
         let (code_source, code_hash) = match kind {
             CallKind::Create | CallKind::Create2 => {
-                let init_code = get_create_init_code(caller_ctx, step)?.to_vec();
-                let code_hash = self.code_db.insert(init_code);
+                let init_code = get_create_init_code(caller_ctx, step)?;
+                let code_hash = self.code_db.insert_slice(init_code);
                 (CodeSource::Memory, code_hash)
             }
             _ => {
@@ -998,6 +1079,14 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TransientStorage, idx) => {
+                let operation = &self.block.container.transient_storage[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TransientStorage(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             OperationRef(Target::TxAccessListAccount, idx) => {
                 let operation = &self.block.container.tx_access_list_account[*idx];
                 if operation.rw().is_write() && operation.reversible() {
@@ -1030,6 +1119,22 @@ impl<'a> CircuitInputStateRef<'a> {
                     None
                 }
             }
+            OperationRef(Target::TxCreatedAccount, idx) => {
+                let operation = &self.block.container.tx_created_account[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TxCreatedAccount(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
+            OperationRef(Target::TxDestructedAccount, idx) => {
+                let operation = &self.block.container.tx_destructed_account[*idx];
+                if operation.rw().is_write() && operation.reversible() {
+                    Some(OpEnum::TxDestructedAccount(operation.op().reverse()))
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -1040,6 +1145,10 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::Storage(op) => {
                 self.sdb.set_storage(&op.address, &op.key, &op.value);
             }
+            OpEnum::TransientStorage(op) => {
+                self.sdb
+                    .set_transient_storage(&op.address, &op.key, &op.value);
+            }
             OpEnum::TxAccessListAccount(op) => {
                 if !op.is_warm_prev && op.is_warm {
                     self.sdb.add_account_to_access_list(op.address);
@@ -1062,6 +1171,22 @@ impl<'a> CircuitInputStateRef<'a> {
             OpEnum::TxRefund(op) => {
                 self.sdb.set_refund(op.value);
             }
+            OpEnum::TxCreatedAccount(op) => {
+                if !op.is_created_prev && op.is_created {
+                    self.sdb.add_created_account(op.address);
+                }
+                if op.is_created_prev && !op.is_created {
+                    self.sdb.remove_created_account(&op.address);
+                }
+            }
+            OpEnum::TxDestructedAccount(op) => {
+                if !op.is_destructed_prev && op.is_destructed {
+                    self.sdb.destruct_account(op.address);
+                }
+                if op.is_destructed_prev && !op.is_destructed {
+                    self.sdb.remove_destructed_account(&op.address);
+                }
+            }
             _ => unreachable!(),
         };
     }
@@ -1128,12 +1253,8 @@ impl<'a> CircuitInputStateRef<'a> {
                             )
                         };
                         // At the moment it conflicts with `call_ctx` and `caller_ctx`.
-                        let caller_ctx = self.caller_ctx_mut()?;
-                        caller_ctx.return_data.resize(length, 0);
-                        if length != 0 {
-                            caller_ctx.return_data[0..length]
-                                .copy_from_slice(&callee_memory.0[offset..offset + length]);
-                        }
+                        self.caller_ctx_mut()?.return_data =
+                            BytesView::from_source(&callee_memory.0, offset as u64, length as u64);
                         (offset, length)
                     }
                     OpcodeId::CALL
@@ -1150,13 +1271,13 @@ impl<'a> CircuitInputStateRef<'a> {
                             (0, caller_ctx.return_data.len())
                         } else {
                             let caller_ctx = self.caller_ctx_mut()?;
-                            caller_ctx.return_data.truncate(0);
+                            caller_ctx.return_data = BytesView::default();
                             (0, 0)
                         }
                     }
                     _ => {
                         let caller_ctx = self.caller_ctx_mut()?;
-                        caller_ctx.return_data.truncate(0);
+                        caller_ctx.return_data = BytesView::default();
                         (0, 0)
                     }
                 };
@@ -1182,6 +1303,7 @@ impl<'a> CircuitInputStateRef<'a> {
             ));
             let keccak_code_hash = H256(keccak256(&code));
             let code_hash = self.code_db.insert(code);
+            self.code_db.add_ref(call.address, code_hash);
             let (found, callee_account) = self.sdb.get_account_mut(&call.address);
             if !found {
                 return Err(Error::AccountNotFound(call.address));
@@ -1214,7 +1336,7 @@ impl<'a> CircuitInputStateRef<'a> {
         if let Ok(caller_ctx) = self.caller_ctx_mut() {
             // EIP-211 CREATE/CREATE2 call successful case should set RETURNDATASIZE = 0
             if call_success_create {
-                caller_ctx.return_data.truncate(0);
+                caller_ctx.return_data = BytesView::default();
             }
         }
 
@@ -1392,6 +1514,10 @@ impl<'a> CircuitInputStateRef<'a> {
         if matches!(step.op, OpcodeId::INVALID(_)) {
             return Ok(Some(ExecError::InvalidOpcode));
         }
+        // `PUSH0` is undefined before Shanghai
+        if step.op == OpcodeId::PUSH0 && !self.block.chain_spec.is_shanghai(self.tx.block_num) {
+            return Ok(Some(ExecError::InvalidOpcode));
+        }
 
         if let Some(error) = &step.error {
             return Ok(Some(get_step_reported_error(&step.op, error)));
@@ -1442,6 +1568,7 @@ impl<'a> CircuitInputStateRef<'a> {
                     OpcodeId::RETURNDATACOPY => Some(ExecError::ReturnDataOutOfBounds),
                     // Break write protection (CALL with value will be handled below)
                     OpcodeId::SSTORE
+                    | OpcodeId::TSTORE
                     | OpcodeId::CREATE
                     | OpcodeId::CREATE2
                     | OpcodeId::SELFDESTRUCT
@@ -1471,7 +1598,7 @@ impl<'a> CircuitInputStateRef<'a> {
                 if call.is_create() {
                     let offset = step.stack.nth_last(0)?;
                     let length = step.stack.nth_last(1)?;
-                    if length > Word::from(MAX_CODE_SIZE) {
+                    if length > Word::from(self.block.circuits_params.max_code_size) {
                         return Ok(Some(ExecError::MaxCodeSizeExceeded));
                     } else if length > Word::zero()
                         && !call_ctx.memory.is_empty()
@@ -1577,8 +1704,8 @@ impl<'a> CircuitInputStateRef<'a> {
                     ),
                     _ => unreachable!(),
                 };
-                let (found, _) = self.sdb.get_account(&address);
-                if found {
+                let (_, callee_account) = self.sdb.get_account(&address);
+                if callee_account.is_create_collision() {
                     log::error!(
                         "create address collision at {:?}, step {:?}, next_step {:?}",
                         address,
@@ -1963,6 +2090,80 @@ impl<'a> CircuitInputStateRef<'a> {
         Ok((read_steps, write_steps, prev_bytes))
     }
 
+    /// Generate the copy steps of MCOPY, from and to the memory of the current
+    /// call. The source bytes are taken from the memory before the copy, so
+    /// the ranges may overlap.
+    ///
+    /// All the source words are read before the destination words are
+    /// written, see [`CopyEvent::is_memory_copy`], so that a destination which
+    /// overlaps the source at a higher address doesn't change the words still
+    /// to be read.
+    pub(crate) fn gen_copy_steps_for_memory_to_memory(
+        &mut self,
+        exec_step: &mut ExecStep,
+        src_addr: impl Into<MemoryAddress>,
+        dst_addr: impl Into<MemoryAddress>,
+        copy_length: impl Into<MemoryAddress>,
+    ) -> Result<(CopyEventSteps, CopyEventSteps, Vec<u8>), Error> {
+        let copy_length = copy_length.into().0;
+        if copy_length == 0 {
+            return Ok((vec![], vec![], vec![]));
+        }
+
+        let src_addr = src_addr.into().0;
+        let call_ctx = self.call_ctx_mut()?;
+        // The source range also expands the memory.
+        call_ctx
+            .memory
+            .extend_for_range(src_addr.into(), copy_length.into());
+        let src_memory = call_ctx.memory.clone();
+        let (src_range, dst_range, write_slot_bytes) = combine_copy_slot_bytes(
+            src_addr,
+            dst_addr.into().0,
+            copy_length,
+            &src_memory.0,
+            &mut call_ctx.memory,
+        );
+        let read_slot_bytes = src_memory.read_chunk(src_range);
+
+        let read_steps = CopyEventStepsBuilder::memory_range(src_range)
+            .source(read_slot_bytes.as_slice())
+            .build();
+        let write_steps = CopyEventStepsBuilder::memory_range(dst_range)
+            .source(write_slot_bytes.as_slice())
+            .build();
+
+        let mut src_chunk_index = src_range.start_slot().0;
+        let call_id = self.call()?.call_id;
+        for read_chunk in read_slot_bytes.chunks(32) {
+            self.push_op(
+                exec_step,
+                RW::READ,
+                MemoryOp::new(
+                    call_id,
+                    src_chunk_index.into(),
+                    Word::from_big_endian(read_chunk),
+                ),
+            );
+            trace!("read chunk: {call_id} {src_chunk_index} {read_chunk:?}");
+            src_chunk_index += 32;
+        }
+
+        let mut dst_chunk_index = dst_range.start_slot().0;
+        let mut prev_bytes: Vec<u8> = vec![];
+        for write_chunk in write_slot_bytes.chunks(32) {
+            self.write_chunk_for_copy_step(
+                exec_step,
+                write_chunk,
+                dst_chunk_index,
+                &mut prev_bytes,
+            )?;
+            dst_chunk_index += 32;
+        }
+
+        Ok((read_steps, write_steps, prev_bytes))
+    }
+
     pub(crate) fn gen_copy_steps_for_log(
         &mut self,
         exec_step: &mut ExecStep,
@@ -1985,7 +2186,9 @@ impl<'a> CircuitInputStateRef<'a> {
         log_slot_bytes.resize(src_range.full_length().0, 0);
 
         let mut chunk_index = src_range.start_slot().0;
-        // memory word writes to destination word
+        // a memory read and a log data write per touched memory word. As for
+        // SHA3, these RWs are checked by the copy circuit, and the EVM circuit
+        // only skips them.
         for chunk in log_slot_bytes.chunks(32) {
             let dest_word = Word::from_big_endian(chunk);
             // read memory
@@ -0,0 +1,190 @@
+//! L1 fee formulas of the Scroll hardforks.
+//!
+//! The L1 fee of a tx is charged from the params stored in the
+//! L1GasPriceOracle predeploy, with a formula which changed at Curie:
+//! - [`Bernoulli`]: `scalar * l1_base_fee * (tx_data_gas_cost + overhead)`
+//! - [`Curie`]: `commit_scalar * l1_base_fee + blob_scalar * l1_blob_base_fee * tx_len`
+//!
+//! both divided by [`TX_L1_FEE_PRECISION`].
+
+use super::{TxL1Fee, TX_L1_FEE_PRECISION};
+use crate::l2_predeployed::l1_gas_price_oracle;
+use eth_types::{evm_types::gas_utils::tx_data_gas_cost, Word};
+use serde::{Deserialize, Serialize};
+
+/// The L1 fee formula of a hardfork, and the params of the L1GasPriceOracle
+/// it reads.
+pub trait L1GasPriceOracle {
+    /// Read the params of the formula, `storage` being the value of a slot of
+    /// the L1GasPriceOracle contract.
+    fn read_params(&self, storage: &dyn Fn(&Word) -> Word) -> TxL1Fee;
+
+    /// L1 fee and remainder of the tx of the signed rlp `rlp_bytes`.
+    fn tx_l1_fee(&self, l1_fee: &TxL1Fee, rlp_bytes: &[u8]) -> (u64, u64);
+}
+
+/// The formula before Curie: the L1 gas of the tx calldata, plus an overhead,
+/// at the scaled L1 base fee.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bernoulli;
+
+impl L1GasPriceOracle for Bernoulli {
+    fn read_params(&self, storage: &dyn Fn(&Word) -> Word) -> TxL1Fee {
+        let [base_fee, fee_overhead, fee_scalar] = [
+            &l1_gas_price_oracle::BASE_FEE_SLOT,
+            &l1_gas_price_oracle::OVERHEAD_SLOT,
+            &l1_gas_price_oracle::SCALAR_SLOT,
+        ]
+        .map(|slot| storage(slot).as_u64());
+
+        TxL1Fee {
+            hardfork: L1FeeHardfork::Bernoulli,
+            base_fee,
+            fee_overhead,
+            fee_scalar,
+            ..Default::default()
+        }
+    }
+
+    fn tx_l1_fee(&self, l1_fee: &TxL1Fee, rlp_bytes: &[u8]) -> (u64, u64) {
+        l1_fee.tx_l1_fee(tx_data_gas_cost(rlp_bytes))
+    }
+}
+
+/// The formula since Curie, when the txs are committed in blobs: a fixed
+/// commit cost at the scaled L1 base fee, plus the tx length at the scaled L1
+/// blob base fee.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Curie;
+
+impl L1GasPriceOracle for Curie {
+    /// The params of [`Bernoulli`] are still read, as they are still stored
+    /// and read by the witness of the L1 fee of a tx.
+    fn read_params(&self, storage: &dyn Fn(&Word) -> Word) -> TxL1Fee {
+        let [blob_base_fee, commit_scalar, blob_scalar] = [
+            &l1_gas_price_oracle::BLOB_BASE_FEE_SLOT,
+            &l1_gas_price_oracle::COMMIT_SCALAR_SLOT,
+            &l1_gas_price_oracle::BLOB_SCALAR_SLOT,
+        ]
+        .map(|slot| storage(slot).as_u64());
+
+        TxL1Fee {
+            hardfork: L1FeeHardfork::Curie,
+            blob_base_fee,
+            commit_scalar,
+            blob_scalar,
+            ..Bernoulli.read_params(storage)
+        }
+    }
+
+    fn tx_l1_fee(&self, l1_fee: &TxL1Fee, rlp_bytes: &[u8]) -> (u64, u64) {
+        // <https://github.com/scroll-tech/go-ethereum/blob/develop/rollup/fees/rollup_fee.go>
+        let commit_fee = Word::from(l1_fee.commit_scalar) * l1_fee.base_fee;
+        let blob_fee =
+            Word::from(l1_fee.blob_scalar) * l1_fee.blob_base_fee * Word::from(rlp_bytes.len());
+        let (quotient, remainder) = (commit_fee + blob_fee).div_mod(TX_L1_FEE_PRECISION.into());
+
+        (quotient.as_u64(), remainder.as_u64())
+    }
+}
+
+/// The hardfork whose L1 fee formula applies to a tx.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L1FeeHardfork {
+    /// Before Curie
+    #[default]
+    Bernoulli,
+    /// Since Curie
+    Curie,
+}
+
+impl L1FeeHardfork {
+    /// The L1 fee formula of the hardfork.
+    pub fn oracle(self) -> &'static dyn L1GasPriceOracle {
+        match self {
+            Self::Bernoulli => &Bernoulli,
+            Self::Curie => &Curie,
+        }
+    }
+}
+
+/// The blocks the L1 fee hardforks of a chain activate at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1FeeConfig {
+    /// First block of Curie, or `None` if it isn't scheduled
+    pub curie_block: Option<u64>,
+}
+
+impl L1FeeConfig {
+    /// The hardfork of the block `block_num`.
+    pub fn hardfork(&self, block_num: u64) -> L1FeeHardfork {
+        match self.curie_block {
+            Some(curie_block) if block_num >= curie_block => L1FeeHardfork::Curie,
+            _ => L1FeeHardfork::Bernoulli,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_input_builder::TX_L1_COMMIT_EXTRA_COST;
+
+    #[test]
+    fn l1_fee_hardforks() {
+        let config = L1FeeConfig {
+            curie_block: Some(100),
+        };
+        assert_eq!(config.hardfork(99), L1FeeHardfork::Bernoulli);
+        assert_eq!(config.hardfork(100), L1FeeHardfork::Curie);
+        assert_eq!(
+            L1FeeConfig::default().hardfork(u64::MAX),
+            L1FeeHardfork::Bernoulli
+        );
+    }
+
+    #[test]
+    fn l1_fee_formulas() {
+        let storage = |slot: &Word| *slot + 10;
+        let rlp_bytes = [0u8, 1, 2, 3];
+
+        let bernoulli = Bernoulli.read_params(&storage);
+        assert_eq!(
+            (
+                bernoulli.base_fee,
+                bernoulli.fee_overhead,
+                bernoulli.fee_scalar
+            ),
+            (11, 12, 13)
+        );
+        // 3 non-zero bytes and 1 zero byte
+        let tx_l1_gas = 3 * 16 + 4 + 12 + TX_L1_COMMIT_EXTRA_COST;
+        assert_eq!(
+            Bernoulli.tx_l1_fee(&bernoulli, &rlp_bytes),
+            (0, 13 * 11 * tx_l1_gas)
+        );
+
+        let curie = L1FeeHardfork::Curie.oracle().read_params(&storage);
+        assert_eq!(curie.hardfork, L1FeeHardfork::Curie);
+        assert_eq!(
+            (
+                curie.base_fee,
+                curie.fee_overhead,
+                curie.fee_scalar,
+                curie.blob_base_fee,
+                curie.commit_scalar,
+                curie.blob_scalar
+            ),
+            (11, 12, 13, 15, 16, 17)
+        );
+        assert_eq!(
+            Curie.tx_l1_fee(&curie, &rlp_bytes),
+            (0, 16 * 11 + 17 * 15 * 4)
+        );
+        let curie = TxL1Fee {
+            commit_scalar: TX_L1_FEE_PRECISION,
+            ..curie
+        };
+        assert_eq!(Curie.tx_l1_fee(&curie, &rlp_bytes), (11, 17 * 15 * 4));
+    }
+}
@@ -0,0 +1,145 @@
+//! Chunk proof layouts, selecting which optional sub-circuits get rows.
+
+use super::{Block, CircuitsParams, PrecompileEcParams, PrecompileEvent};
+use crate::Error;
+
+/// The optional sub-circuits that a chunk proof reserves rows for. A
+/// sub-circuit left out of the layout is zero-sized through the
+/// [`CircuitsParams`], so every layout has its own verifying key and a chunk
+/// must be proven with a layout that contains [`ChunkLayout::for_block`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkLayout {
+    /// Reserve rows for the ECC circuit (ecAdd, ecMul and ecPairing).
+    pub ecc: bool,
+    /// Reserve rows for the Exp circuit.
+    pub exp: bool,
+    /// Reserve rows for the modexp precompile calls.
+    pub modexp: bool,
+}
+
+impl Default for ChunkLayout {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl ChunkLayout {
+    /// All sub-circuits are sized by the circuit params.
+    pub const FULL: Self = Self {
+        ecc: true,
+        exp: true,
+        modexp: true,
+    };
+    /// Layout for chunks without ECC or modexp precompile calls and EXP ops,
+    /// such as the common simple-transfer chunks.
+    pub const SIMPLE: Self = Self {
+        ecc: false,
+        exp: false,
+        modexp: false,
+    };
+
+    /// The smallest layout able to prove `block`.
+    pub fn for_block(block: &Block) -> Self {
+        Self {
+            ecc: block.precompile_events.events.iter().any(|event| {
+                matches!(
+                    event,
+                    PrecompileEvent::EcAdd(_)
+                        | PrecompileEvent::EcMul(_)
+                        | PrecompileEvent::EcPairing(_)
+                )
+            }),
+            exp: !block.exp_events.is_empty(),
+            modexp: block
+                .precompile_events
+                .events
+                .iter()
+                .any(|event| matches!(event, PrecompileEvent::Modexp(_))),
+        }
+    }
+
+    /// Whether every sub-circuit of `other` is also in this layout.
+    pub fn contains(&self, other: &Self) -> bool {
+        (self.ecc || !other.ecc) && (self.exp || !other.exp) && (self.modexp || !other.modexp)
+    }
+
+    /// Zero-size the sub-circuits left out of the layout in `params`.
+    pub fn apply(&self, params: CircuitsParams) -> CircuitsParams {
+        CircuitsParams {
+            max_ec_ops: if self.ecc {
+                params.max_ec_ops
+            } else {
+                PrecompileEcParams {
+                    ec_add: 0,
+                    ec_mul: 0,
+                    ec_pairing: 0,
+                }
+            },
+            max_exp_steps: if self.exp { params.max_exp_steps } else { 0 },
+            max_modexp_rows: if self.modexp {
+                params.max_modexp_rows
+            } else {
+                0
+            },
+            ..params
+        }
+    }
+
+    /// Check that `block` can be proven with this layout.
+    pub fn check(&self, block: &Block) -> Result<(), Error> {
+        let required = Self::for_block(block);
+        if self.contains(&required) {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedChunkLayout(*self, required))
+        }
+    }
+
+    /// Name of the layout, to tell apart the keys of the different layouts.
+    pub fn name(&self) -> &'static str {
+        match (self.ecc, self.exp, self.modexp) {
+            (true, true, true) => "full",
+            (true, true, false) => "no_modexp",
+            (true, false, true) => "no_exp",
+            (true, false, false) => "no_exp_modexp",
+            (false, true, true) => "no_ecc",
+            (false, true, false) => "no_ecc_modexp",
+            (false, false, true) => "no_ecc_exp",
+            (false, false, false) => "simple",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_layout_apply() {
+        let params = ChunkLayout::SIMPLE.apply(CircuitsParams::default());
+        assert_eq!(params.max_exp_steps, 0);
+        assert_eq!(params.max_ec_ops.ec_pairing, 0);
+        assert_eq!(params.max_modexp_rows, 0);
+        assert_eq!(params.max_rws, CircuitsParams::default().max_rws);
+
+        let params = ChunkLayout::FULL.apply(CircuitsParams::default());
+        assert_eq!(
+            params.max_exp_steps,
+            CircuitsParams::default().max_exp_steps
+        );
+
+        assert_eq!(
+            params.max_modexp_rows,
+            CircuitsParams::default().max_modexp_rows
+        );
+
+        assert!(ChunkLayout::FULL.contains(&ChunkLayout::SIMPLE));
+        assert!(!ChunkLayout::SIMPLE.contains(&ChunkLayout::FULL));
+        let modexp_only = ChunkLayout {
+            modexp: true,
+            ..ChunkLayout::SIMPLE
+        };
+        assert!(!ChunkLayout::SIMPLE.contains(&modexp_only));
+        assert_eq!(modexp_only.name(), "no_ecc_exp");
+    }
+}
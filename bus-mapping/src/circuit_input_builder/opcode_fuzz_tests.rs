@@ -0,0 +1,211 @@
+//! Differential fuzzing of the opcode handlers against the external tracer.
+//!
+//! Each case runs a single opcode on a random stack, memory and calldata. The
+//! stack and memory ops of the resulting [`ExecStep`]s are checked against the
+//! states that geth reports before and after the step, which catches handler
+//! bugs that the block-level tests miss.
+
+use super::*;
+use crate::{mock::BlockData, operation::OpEnum};
+use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Bytecode, Bytes, Word};
+use mock::test_ctx::{helpers::*, LoggerConfig, TestContext};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Number of random cases run by the fuzzer.
+const FUZZ_CASES: u64 = 256;
+
+/// Kind of a stack operand of the fuzzed opcode.
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    /// Any word, biased towards edge cases.
+    Word,
+    /// A memory or data offset, small enough to not run out of gas.
+    Offset,
+    /// A memory or data length, small enough to not run out of gas.
+    Length,
+}
+
+/// The fuzzed opcodes with their stack operands, from the top of the stack.
+fn fuzzed_opcodes() -> Vec<(OpcodeId, Vec<Operand>)> {
+    use Operand::*;
+
+    let mut opcodes = vec![
+        (OpcodeId::MLOAD, vec![Offset]),
+        (OpcodeId::MSTORE, vec![Offset, Word]),
+        (OpcodeId::MSTORE8, vec![Offset, Word]),
+        (OpcodeId::SHA3, vec![Offset, Length]),
+        (OpcodeId::CALLDATALOAD, vec![Offset]),
+        (OpcodeId::CALLDATACOPY, vec![Offset, Offset, Length]),
+        (OpcodeId::CODECOPY, vec![Offset, Offset, Length]),
+        #[cfg(feature = "cancun")]
+        (OpcodeId::MCOPY, vec![Offset, Offset, Length]),
+    ];
+    for (opcode, n_inputs) in [
+        (OpcodeId::ADD, 2),
+        (OpcodeId::MUL, 2),
+        (OpcodeId::SUB, 2),
+        (OpcodeId::DIV, 2),
+        (OpcodeId::SDIV, 2),
+        (OpcodeId::MOD, 2),
+        (OpcodeId::SMOD, 2),
+        (OpcodeId::ADDMOD, 3),
+        (OpcodeId::MULMOD, 3),
+        (OpcodeId::EXP, 2),
+        (OpcodeId::SIGNEXTEND, 2),
+        (OpcodeId::LT, 2),
+        (OpcodeId::GT, 2),
+        (OpcodeId::SLT, 2),
+        (OpcodeId::SGT, 2),
+        (OpcodeId::EQ, 2),
+        (OpcodeId::ISZERO, 1),
+        (OpcodeId::AND, 2),
+        (OpcodeId::OR, 2),
+        (OpcodeId::XOR, 2),
+        (OpcodeId::NOT, 1),
+        (OpcodeId::BYTE, 2),
+        (OpcodeId::SHL, 2),
+        (OpcodeId::SHR, 2),
+        (OpcodeId::SAR, 2),
+        (OpcodeId::POP, 1),
+        (OpcodeId::DUP1, 1),
+        (OpcodeId::DUP16, 16),
+        (OpcodeId::SWAP1, 2),
+        (OpcodeId::SWAP16, 17),
+        (OpcodeId::PC, 0),
+        (OpcodeId::MSIZE, 0),
+        (OpcodeId::GAS, 0),
+        (OpcodeId::CALLDATASIZE, 0),
+        (OpcodeId::CODESIZE, 0),
+    ] {
+        opcodes.push((opcode, vec![Word; n_inputs]));
+    }
+    opcodes
+}
+
+fn random_word(rng: &mut StdRng) -> Word {
+    match rng.gen_range(0..7) {
+        0 => Word::zero(),
+        1 => Word::one(),
+        2 => Word::from(rng.gen_range(0..=300u64)),
+        3 => Word::MAX,
+        4 => Word::MAX - rng.gen_range(0..=300u64),
+        5 => Word::one() << 255,
+        _ => Word::from_big_endian(&rng.gen::<[u8; 32]>()),
+    }
+}
+
+fn random_operand(rng: &mut StdRng, operand: Operand) -> Word {
+    match operand {
+        Operand::Word => random_word(rng),
+        Operand::Offset => rng.gen_range(0..512u64).into(),
+        Operand::Length => rng.gen_range(0..256u64).into(),
+    }
+}
+
+/// Bytecode filling the first words of memory at random, then running
+/// `opcode` on random operands.
+fn random_case(rng: &mut StdRng, opcode: OpcodeId, operands: &[Operand]) -> Bytecode {
+    let mut code = Bytecode::default();
+    for i in 0..rng.gen_range(0..4u64) {
+        code.push(32, random_word(rng));
+        code.push(2, Word::from(i * 32));
+        code.write_op(OpcodeId::MSTORE);
+    }
+    for operand in operands.iter().rev() {
+        code.push(32, random_operand(rng, *operand));
+    }
+    code.write_op(opcode);
+    code.append(&bytecode! {
+        STOP
+    });
+    code
+}
+
+/// Check the ops of each step of the single tx of `block` against the geth
+/// trace.
+fn check_steps(block: &GethData, builder: &CircuitInputBuilder, case: &str) {
+    let geth_steps = &block.geth_traces[0].struct_logs;
+    let steps = builder.block.txs[0]
+        .steps()
+        .iter()
+        .filter(|step| matches!(step.exec_state, ExecState::Op(_)))
+        .collect::<Vec<_>>();
+    assert_eq!(steps.len(), geth_steps.len(), "{case}");
+
+    for (i, (step, geth_step)) in steps.iter().zip(geth_steps).enumerate() {
+        let ctx = format!("{case}, step {i} {:?}", geth_step.op);
+        assert_eq!(step.exec_state, ExecState::Op(geth_step.op), "{ctx}");
+        assert_eq!(step.pc, geth_step.pc, "{ctx}");
+        assert_eq!(step.stack_size, geth_step.stack.0.len(), "{ctx}");
+        assert_eq!(step.memory_size, geth_step.memory.len(), "{ctx}");
+        assert_eq!(step.gas_left, geth_step.gas, "{ctx}");
+        assert_eq!(step.gas_cost, geth_step.gas_cost, "{ctx}");
+
+        // reads see the state before the step, and writes the state after it.
+        let next_step = geth_steps.get(i + 1);
+        for op_ref in &step.bus_mapping_instance {
+            let (_, rw, op) = builder.block.container.get(op_ref).unwrap();
+            let geth_step = if rw.is_write() {
+                next_step.expect("writes before the last step")
+            } else {
+                geth_step
+            };
+            match op {
+                OpEnum::Stack(op) => {
+                    let stack = &geth_step.stack.0;
+                    assert_eq!(
+                        Some(&op.value),
+                        1023usize
+                            .checked_sub(op.address.0)
+                            .and_then(|idx| stack.get(idx)),
+                        "{ctx}, {rw:?} {op:?}"
+                    );
+                }
+                OpEnum::Memory(op) => {
+                    assert_eq!(
+                        op.value,
+                        geth_step.memory.read_word(op.address),
+                        "{ctx}, {rw:?} {op:?}"
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn opcode_handlers_differential_fuzz() {
+    let opcodes = fuzzed_opcodes();
+    for seed in 0..FUZZ_CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (opcode, operands) = &opcodes[rng.gen_range(0..opcodes.len())];
+        let code = random_case(&mut rng, *opcode, operands);
+        let calldata = (0..rng.gen_range(0..128))
+            .map(|_| rng.gen())
+            .collect::<Vec<u8>>();
+        let case = format!("seed {seed}, {opcode:?}");
+
+        let block: GethData = TestContext::<2, 1>::new_with_logger_config(
+            None,
+            account_0_code_account_1_no_code(code),
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .input(Bytes::from(calldata));
+            },
+            |block, _tx| block.number(0xcafeu64),
+            LoggerConfig::enable_memory(),
+        )
+        .unwrap()
+        .into();
+        assert!(!block.geth_traces[0].failed, "{case}");
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_or_else(|err| panic!("{case}: {err:?}"));
+        check_steps(&block, &builder, &case);
+    }
+}
@@ -0,0 +1,184 @@
+//! Cross-check of the tx receipts returned by the node against the results of
+//! the txs handled by the builder, to catch a tracer inconsistent with the
+//! client which executed the block before the circuits are built.
+
+use super::{CircuitInputBuilder, ExecState};
+use crate::{
+    error::{ReceiptField, ReceiptMismatch},
+    operation::TxReceiptField,
+    Error,
+};
+use eth_types::{evm_types::MAX_REFUND_QUOTIENT_OF_GAS_USED, H256};
+use ethers_core::types::TransactionReceipt;
+use std::collections::HashMap;
+
+/// Status, log count and gas used of a tx handled by the builder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct TxResult {
+    status: u64,
+    log_count: u64,
+    gas_used: u64,
+}
+
+impl CircuitInputBuilder {
+    /// Check the gas used, status and log count of `receipts` against the
+    /// results of the txs handled by the builder. Every receipt must belong to
+    /// a handled tx, while the handled pseudo-txs of the system calls have no
+    /// receipt.
+    pub fn check_receipts(&self, receipts: &[TransactionReceipt]) -> Result<(), Error> {
+        let results = self.tx_results();
+        for receipt in receipts {
+            let tx_hash = receipt.transaction_hash;
+            let mismatch = |field, value, builder| ReceiptMismatch {
+                tx_hash,
+                field,
+                receipt: value,
+                builder,
+            };
+            let Some(result) = results.get(&receipt.transaction_hash) else {
+                return Err(mismatch(ReceiptField::Present, 1, 0).into());
+            };
+            let checks = [
+                (
+                    ReceiptField::GasUsed,
+                    receipt.gas_used.unwrap_or_default().low_u64(),
+                    result.gas_used,
+                ),
+                (
+                    ReceiptField::Status,
+                    receipt.status.unwrap_or_default().as_u64(),
+                    result.status,
+                ),
+                (
+                    ReceiptField::LogCount,
+                    receipt.logs.len() as u64,
+                    result.log_count,
+                ),
+            ];
+            for (field, value, builder) in checks {
+                if value != builder {
+                    return Err(mismatch(field, value, builder).into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Results of the handled txs by hash, from the receipt operations and
+    /// the gas left at their `EndTx` step.
+    fn tx_results(&self) -> HashMap<H256, TxResult> {
+        // indexed by tx id, which starts at 1
+        let mut results = vec![TxResult::default(); self.block.txs.len() + 1];
+        for op in self.block.container.tx_receipt.iter().map(|op| op.op()) {
+            let Some(result) = results.get_mut(op.tx_id) else {
+                continue;
+            };
+            match op.field {
+                TxReceiptField::PostStateOrStatus => result.status = op.value,
+                TxReceiptField::LogLength => result.log_count = op.value,
+                TxReceiptField::CumulativeGasUsed => {}
+            }
+        }
+        // the refund read at the end of each tx is its last refund operation
+        let mut refunds = vec![0; self.block.txs.len() + 1];
+        for op in self.block.container.tx_refund.iter().map(|op| op.op()) {
+            if let Some(refund) = refunds.get_mut(op.tx_id) {
+                *refund = op.value;
+            }
+        }
+
+        self.block
+            .txs
+            .iter()
+            .zip(results.iter_mut().zip(refunds).skip(1))
+            .map(|(tx, (result, refund))| {
+                if let Some(end_tx) = tx
+                    .steps()
+                    .iter()
+                    .rev()
+                    .find(|step| step.exec_state == ExecState::EndTx)
+                {
+                    let gas_used = tx.gas - end_tx.gas_left.0;
+                    let effective_refund =
+                        refund.min(gas_used / MAX_REFUND_QUOTIENT_OF_GAS_USED as u64);
+                    result.gas_used = gas_used - effective_refund;
+                }
+                (tx.hash, *result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData, U64};
+    use ethers_core::types::Log;
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    #[test]
+    fn receipts_match_builder() {
+        let code = bytecode! {
+            PUSH1(0x20) // size
+            PUSH1(0x00) // offset
+            LOG0
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx_hash = block.eth_block.transactions[0].hash;
+        let receipt = TransactionReceipt {
+            transaction_hash: tx_hash,
+            gas_used: Some(block.geth_traces[0].gas.0.into()),
+            status: Some(U64::from(!block.geth_traces[0].failed as u64)),
+            logs: vec![Log::default()],
+            ..Default::default()
+        };
+        builder.check_receipts(&[receipt.clone()]).unwrap();
+
+        let wrong_gas = TransactionReceipt {
+            gas_used: receipt.gas_used.map(|gas| gas + 1),
+            ..receipt.clone()
+        };
+        assert!(matches!(
+            builder.check_receipts(&[wrong_gas]),
+            Err(Error::ReceiptMismatch(ReceiptMismatch {
+                field: ReceiptField::GasUsed,
+                ..
+            }))
+        ));
+
+        let no_logs = TransactionReceipt {
+            logs: vec![],
+            ..receipt.clone()
+        };
+        assert_eq!(
+            builder.check_receipts(&[no_logs]).unwrap_err().kind(),
+            crate::error::ErrorKind::TraceInconsistency
+        );
+
+        let unknown_tx = TransactionReceipt {
+            transaction_hash: Default::default(),
+            ..receipt
+        };
+        assert!(matches!(
+            builder.check_receipts(&[unknown_tx]),
+            Err(Error::ReceiptMismatch(ReceiptMismatch {
+                field: ReceiptField::Present,
+                ..
+            }))
+        ));
+    }
+}
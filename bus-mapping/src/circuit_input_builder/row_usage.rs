@@ -0,0 +1,1001 @@
+//! Usage of the circuit capacities by a block, to help block building
+//! software decide quickly whether a candidate tx still fits in a block.
+
+use super::{
+    keccak_inputs, BigModExp, Block, CircuitInputBuilder, CircuitsParams, EthBlock,
+    PrecompileEcParams, PrecompileEvent,
+};
+use crate::{
+    error::{CircuitCapacityError, SubCircuit},
+    precompile::PrecompileCalls,
+    Error,
+};
+use eth_types::{
+    evm_types::{memory::MemoryRange, OpcodeId},
+    geth_types::{get_rlp_signed, get_rlp_unsigned, TxType},
+    Address, GethExecStep, GethExecTrace, Hash, ToAddress, Word,
+};
+use ethers_core::utils::rlp::Rlp;
+use std::{
+    collections::BTreeSet,
+    ops::{Add, AddAssign},
+};
+use zkevm_constants::{
+    evm::MAX_STEP_HEIGHT,
+    keccak::{RATE, ROWS_PER_ROUND},
+};
+
+/// Upper bound of the rws of a BeginTx and EndTx step.
+const TX_RWS: usize = 128;
+/// Upper bound of the rws of a step staying in its call, beside its memory
+/// and log data accesses: the 15 of LOG4, beyond the 11 of SSTORE.
+const STEP_RWS: usize = 15;
+/// Upper bound of the rws of a step switching the call context: a call, a
+/// create, or a step ending its call, which restores the caller context.
+const CALL_RWS: usize = 96;
+/// Upper bound of the length of the RLP encoding of a tx, beside its calldata.
+const TX_RLP_LEN: usize = 256;
+/// Upper bound of the account fields and storage slots read or written by a
+/// BeginTx and EndTx step: the nonce and balance of the sender, the balance,
+/// nonce and code fields of the receiver, the balance of the coinbase, and the
+/// 6 slots of the L1 fee params.
+const TX_MPT_UPDATES: usize = 14;
+/// Upper bound of the account fields read or written by a call, a create or
+/// a selfdestruct: the balances of two accounts, and the nonce and code
+/// fields of one.
+const CALL_MPT_UPDATES: usize = 6;
+/// Rows of the MPT circuit taken by an account field or storage slot: the
+/// lookups of the canonical representations of up to three keys, of 32 rows
+/// each.
+const MPT_ROWS_PER_UPDATE: usize = 3 * 32;
+/// Rows that the circuits sized by rows reserve beside the usage, such as
+/// the unused and disabled rows of the Copy circuit.
+const RESERVED_ROWS: usize = 8;
+/// Rows that the Keccak circuit reserves beside the usage: its capacity
+/// leaves out two keccak_f's worth of rows, for the first dummy keccak_f and
+/// the unusable rows.
+const KECCAK_RESERVED_ROWS: usize = 2 * ROWS_PER_ROUND;
+
+/// A capacity resolved from its usage, rounded up to a power of two so that
+/// blocks of similar sizes share their params.
+fn auto_capacity(used: usize) -> usize {
+    used.next_power_of_two()
+}
+
+/// Usage of the capacities of [`CircuitsParams`]. Padding rows of the
+/// circuits are not included.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRowUsage {
+    /// Steps of the EVM circuit
+    pub evm_steps: usize,
+    /// Rw operations, including the Start row
+    pub rws: usize,
+    /// Txs
+    pub txs: usize,
+    /// Bytes of calldata
+    pub calldata: usize,
+    /// Rows of the Copy circuit
+    pub copy_rows: usize,
+    /// Steps of the Exp circuit
+    pub exp_steps: usize,
+    /// Bytes of bytecode
+    pub bytecode: usize,
+    /// Rows of the Keccak circuit
+    pub keccak_rows: usize,
+    /// EcAdd ops
+    pub ec_add: usize,
+    /// EcMul ops
+    pub ec_mul: usize,
+    /// EcPairing ops
+    pub ec_pairing: usize,
+    /// Rows of the modexp ops
+    pub modexp_rows: usize,
+    /// Inner blocks
+    pub inner_blocks: usize,
+    /// Rows of the RLP circuit
+    pub rlp_rows: usize,
+    /// Rows of the MPT circuit
+    pub mpt_rows: usize,
+}
+
+impl Add for BlockRowUsage {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for BlockRowUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.evm_steps += rhs.evm_steps;
+        self.rws += rhs.rws;
+        self.txs += rhs.txs;
+        self.calldata += rhs.calldata;
+        self.copy_rows += rhs.copy_rows;
+        self.exp_steps += rhs.exp_steps;
+        self.bytecode += rhs.bytecode;
+        self.keccak_rows += rhs.keccak_rows;
+        self.ec_add += rhs.ec_add;
+        self.ec_mul += rhs.ec_mul;
+        self.ec_pairing += rhs.ec_pairing;
+        self.modexp_rows += rhs.modexp_rows;
+        self.inner_blocks += rhs.inner_blocks;
+        self.rlp_rows += rhs.rlp_rows;
+        self.mpt_rows += rhs.mpt_rows;
+    }
+}
+
+/// Rows of the Keccak circuit taken by hashing `len` bytes.
+pub fn keccak_rows(len: usize) -> usize {
+    (len / RATE + 1) * ROWS_PER_ROUND
+}
+
+/// Rows of the RLP circuit taken by decoding the RLP encoding `rlp_bytes` of
+/// a tx: a row per byte, an end row per list, and the end row of the tx.
+pub fn rlp_rows(rlp_bytes: &[u8]) -> usize {
+    fn lists(rlp: &Rlp) -> usize {
+        if rlp.is_list() {
+            1 + rlp.iter().map(|item| lists(&item)).sum::<usize>()
+        } else {
+            0
+        }
+    }
+    // the EIP-2718 type of a typed tx precedes its list
+    let list = match rlp_bytes.first() {
+        Some(&tx_type) if tx_type < 0xc0 => &rlp_bytes[1..],
+        _ => rlp_bytes,
+    };
+    rlp_bytes.len() + lists(&Rlp::new(list)) + 1
+}
+
+/// Rows of the RLP circuit taken by a tx of type `tx_type`, whose signed and
+/// unsigned RLP encodings are decoded, the unsigned one only for a signed tx.
+fn tx_rlp_rows(tx_type: TxType, rlp_signed: &[u8], rlp_unsigned: &[u8]) -> usize {
+    let sign_rows = if tx_type.is_unsigned() {
+        0
+    } else {
+        rlp_rows(rlp_unsigned)
+    };
+    sign_rows + rlp_rows(rlp_signed)
+}
+
+impl BlockRowUsage {
+    /// Exact usage of the block handled by `builder`.
+    pub fn from_builder(builder: &CircuitInputBuilder) -> Result<Self, Error> {
+        let block = &builder.block;
+        let mut usage = Self {
+            evm_steps: block.txs.iter().map(|tx| tx.steps().len()).sum(),
+            rws: builder.block_ctx.rwc.0,
+            txs: block.txs.len(),
+            calldata: block.txs.iter().map(|tx| tx.input.len()).sum(),
+            copy_rows: block.copy_rows(),
+            exp_steps: block.exp_events.iter().map(|event| event.steps.len()).sum(),
+            bytecode: block
+                .bytecode_hashes
+                .iter()
+                .filter_map(|hash| builder.code_db.get(hash))
+                .map(|code| code.len())
+                .sum(),
+            keccak_rows: keccak_inputs(block, &builder.code_db)?
+                .iter()
+                .map(|input| keccak_rows(input.len()))
+                .sum(),
+            inner_blocks: block.headers.len(),
+            rlp_rows: block
+                .txs
+                .iter()
+                .map(|tx| tx_rlp_rows(tx.tx_type, &tx.rlp_bytes, &tx.rlp_unsigned_bytes))
+                .sum(),
+            mpt_rows: Self::mpt_updates(block) * MPT_ROWS_PER_UPDATE,
+            ..Default::default()
+        };
+        for event in &block.precompile_events.events {
+            match event {
+                PrecompileEvent::EcAdd(_) => usage.ec_add += 1,
+                PrecompileEvent::EcMul(_) => usage.ec_mul += 1,
+                PrecompileEvent::EcPairing(_) => usage.ec_pairing += 1,
+                PrecompileEvent::Modexp(op) => usage.modexp_rows += op.num_rows(),
+                _ => {}
+            }
+        }
+        Ok(usage)
+    }
+
+    /// The account fields and storage slots read or written by `block`, each
+    /// being an update of the MPT circuit. The storage slots are updated per
+    /// tx.
+    fn mpt_updates(block: &Block) -> usize {
+        let container = &block.container;
+        let accounts = container
+            .account
+            .iter()
+            .map(|op| (op.op().address, op.op().field))
+            .collect::<BTreeSet<_>>();
+        let storage = container
+            .storage
+            .iter()
+            .map(|op| (op.op().tx_id, op.op().address, op.op().key))
+            .collect::<BTreeSet<_>>();
+        accounts.len() + storage.len()
+    }
+
+    /// Upper bound of the usage of a tx, from its trace only, without running
+    /// the builder. Meant for the marginal usage of a candidate tx, added to
+    /// the usage of the block it may be packed in. The bytecode only includes
+    /// the init code and the code deployed by the tx, as the code of the
+    /// called accounts isn't in the trace.
+    pub fn estimate_tx(tx: &eth_types::Transaction, trace: &GethExecTrace) -> Self {
+        let mut usage = Self {
+            // BeginTx and EndTx
+            evm_steps: 2,
+            rws: TX_RWS,
+            txs: 1,
+            calldata: tx.input.len(),
+            // calldata copied into the tx table, or as init code
+            copy_rows: tx.input.len() * 2,
+            keccak_rows: keccak_rows(tx.input.len() + TX_RLP_LEN) * 2,
+            rlp_rows: tx_rlp_rows(
+                TxType::get_tx_type(tx),
+                &get_rlp_signed(tx),
+                &get_rlp_unsigned(tx),
+            ),
+            mpt_rows: TX_MPT_UPDATES * MPT_ROWS_PER_UPDATE,
+            ..Default::default()
+        };
+        if tx.to.is_none() {
+            usage.bytecode += tx.input.len();
+        }
+        // whether the call at each depth is a creation, whose RETURN deploys
+        // code
+        let mut is_create = vec![tx.to.is_none()];
+        for step in &trace.struct_logs {
+            usage += Self::estimate_step(step);
+
+            let depth = step.depth as usize;
+            is_create.truncate(depth);
+            if step.op == OpcodeId::RETURN
+                && step.error.is_none()
+                && is_create.get(depth.wrapping_sub(1)) == Some(&true)
+            {
+                // the deployed code and its code hash
+                let len = arg_length(step, 1);
+                usage.bytecode += len;
+                usage.keccak_rows += keccak_rows(len);
+            }
+            // the call entered by the step, if any, is at the next depth
+            if step.op.is_call_or_create() {
+                is_create.push(step.op.is_create());
+            }
+        }
+        usage
+    }
+
+    fn estimate_step(step: &GethExecStep) -> Self {
+        let ends_call = step.error.is_some()
+            || matches!(
+                step.op,
+                OpcodeId::STOP | OpcodeId::RETURN | OpcodeId::REVERT | OpcodeId::SELFDESTRUCT
+            );
+        let mut usage = Self {
+            evm_steps: 1,
+            rws: if step.op.is_call_or_create() || ends_call {
+                CALL_RWS
+            } else {
+                STEP_RWS
+            },
+            mpt_rows: match step.op {
+                OpcodeId::SLOAD | OpcodeId::SSTORE | OpcodeId::BALANCE | OpcodeId::SELFBALANCE => 1,
+                // the code fields of the account
+                OpcodeId::EXTCODESIZE | OpcodeId::EXTCODECOPY | OpcodeId::EXTCODEHASH => 3,
+                op if op.is_call_or_create() || op == OpcodeId::SELFDESTRUCT => CALL_MPT_UPDATES,
+                _ => 0,
+            } * MPT_ROWS_PER_UPDATE,
+            ..Default::default()
+        };
+        // failed steps copy nothing
+        if step.error.is_some() {
+            return usage;
+        }
+        // the account is deleted by a DestructAccount step at the end of the tx
+        if step.op == OpcodeId::SELFDESTRUCT {
+            usage.evm_steps += 1;
+        }
+        let length = |nth: usize| arg_length(step, nth);
+        let copy_lengths = match step.op {
+            OpcodeId::CALLDATACOPY
+            | OpcodeId::CODECOPY
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::MCOPY => {
+                vec![length(2)]
+            }
+            OpcodeId::EXTCODECOPY => vec![length(3)],
+            OpcodeId::SHA3 => {
+                usage.keccak_rows += keccak_rows(length(1));
+                vec![length(1)]
+            }
+            OpcodeId::LOG0 | OpcodeId::LOG1 | OpcodeId::LOG2 | OpcodeId::LOG3 | OpcodeId::LOG4 => {
+                // each byte of the log data is written to the tx log
+                usage.rws += length(1);
+                vec![length(1)]
+            }
+            OpcodeId::RETURN | OpcodeId::REVERT => vec![length(1)],
+            OpcodeId::CREATE => {
+                usage.bytecode += length(2);
+                usage.keccak_rows += keccak_rows(length(2));
+                vec![length(2)]
+            }
+            OpcodeId::CREATE2 => {
+                usage.bytecode += length(2);
+                usage.keccak_rows += keccak_rows(length(2)) * 2;
+                vec![length(2)]
+            }
+            OpcodeId::CALL | OpcodeId::CALLCODE => {
+                usage.add_precompile_call(step, 3);
+                vec![length(4), length(6)]
+            }
+            OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+                usage.add_precompile_call(step, 2);
+                vec![length(3), length(5)]
+            }
+            OpcodeId::EXP => {
+                let exponent = step.stack.nth_last(1).unwrap_or_default();
+                usage.exp_steps += exponent.bits();
+                vec![]
+            }
+            _ => vec![],
+        };
+        for len in copy_lengths {
+            // a memory word access per 32 bytes, plus the unaligned ends
+            usage.rws += (len / 32 + 2) * 2;
+            usage.copy_rows += len * 2;
+        }
+        usage
+    }
+
+    /// Count the ops of a call to the precompiles, whose input offset and
+    /// length are the stack args at `args_nth` and `args_nth + 1`. The rows
+    /// of a modexp call are read from its input in the memory of the trace.
+    /// If the trace doesn't capture the memory, each modexp call is bounded
+    /// by [`BigModExp::MAX_ROWS`], so a capacity of `n * MAX_ROWS` rows only
+    /// admits `n` calls.
+    fn add_precompile_call(&mut self, step: &GethExecStep, args_nth: usize) {
+        let address = step.stack.nth_last(1).unwrap_or_default().to_address();
+        let input_len = arg_length(step, args_nth + 1);
+        if address == Address::from(PrecompileCalls::Bn128Add) {
+            self.ec_add += 1;
+        } else if address == Address::from(PrecompileCalls::Bn128Mul) {
+            self.ec_mul += 1;
+        } else if address == Address::from(PrecompileCalls::Bn128Pairing) && input_len > 0 {
+            self.ec_pairing += 1;
+        } else if address == Address::from(PrecompileCalls::Modexp) {
+            let op = if step.memory.is_empty() {
+                None
+            } else {
+                // an op of operands beyond the size limit isn't supported
+                let input_len = input_len.min(BigModExp::MAX_INPUT_LEN);
+                BigModExp::new_from_input(&step.memory.read_chunk(MemoryRange::new_with_length(
+                    arg_length(step, args_nth),
+                    input_len,
+                )))
+            };
+            self.modexp_rows += op.map_or(BigModExp::MAX_ROWS, |op| op.num_rows());
+        }
+    }
+}
+
+/// The stack arg at `nth` from the top of `step`, as a length in bytes.
+fn arg_length(step: &GethExecStep, nth: usize) -> usize {
+    step.stack
+        .nth_last(nth)
+        .ok()
+        .filter(|len| *len <= Word::from(u32::MAX))
+        .map_or(0, |len| len.as_usize())
+}
+
+/// The account whose code is run or read by `step`.
+fn code_address(step: &GethExecStep) -> Option<Address> {
+    let address = match step.op {
+        OpcodeId::CALL | OpcodeId::CALLCODE | OpcodeId::DELEGATECALL | OpcodeId::STATICCALL => {
+            step.stack.nth_last(1)
+        }
+        OpcodeId::EXTCODECOPY | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => step.stack.last(),
+        _ => return None,
+    };
+    address.ok().map(|address| address.to_address())
+}
+
+/// Estimate of the usage of a block, see
+/// [`CircuitInputBuilder::estimate_circuit_rows`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RowUsageEstimate {
+    /// Upper bound of the usage of the block.
+    pub usage: BlockRowUsage,
+    /// The usage against the [`CircuitsParams`] of the builder.
+    pub report: FitsReport,
+}
+
+impl RowUsageEstimate {
+    /// Whether the block is estimated to fit in all the capacities.
+    pub fn fits(&self) -> bool {
+        self.report.fits()
+    }
+}
+
+impl CircuitInputBuilder {
+    /// Upper bound of the usage of `eth_block` from its traces only, without
+    /// generating the witness, so that a sequencer can decide the chunk
+    /// boundaries cheaply. The code of the called accounts is looked up in the
+    /// state of the builder, beside the code created by the block.
+    pub fn estimate_circuit_rows(
+        &self,
+        eth_block: &EthBlock,
+        geth_traces: &[GethExecTrace],
+    ) -> RowUsageEstimate {
+        // the EndInnerBlock step
+        let mut usage = BlockRowUsage {
+            evm_steps: 1,
+            inner_blocks: 1,
+            ..Default::default()
+        };
+        let mut code_hashes = BTreeSet::<Hash>::new();
+        for (tx, trace) in eth_block.transactions.iter().zip(geth_traces) {
+            usage += BlockRowUsage::estimate_tx(tx, trace);
+            for address in tx
+                .to
+                .into_iter()
+                .chain(trace.struct_logs.iter().filter_map(code_address))
+            {
+                code_hashes.insert(self.sdb.get_account(&address).1.code_hash);
+            }
+        }
+        usage.bytecode += code_hashes
+            .iter()
+            .filter_map(|hash| self.code_db.get(hash))
+            .map(|code| code.len())
+            .sum();
+        RowUsageEstimate {
+            usage,
+            report: self.block.circuits_params.fits(&usage),
+        }
+    }
+
+    /// Exact usage of `eth_block`, from a walk of its traces like
+    /// [`Self::handle_block`] which only counts the rws and copy rows instead
+    /// of keeping the operations and copy events that the circuits read. This
+    /// answers whether a block fits much faster than handling it, but the
+    /// builder can't generate the witness afterwards. A block exceeding the
+    /// capacities set in the params fails as in [`Self::handle_block`].
+    pub fn handle_block_dry_run(
+        &mut self,
+        eth_block: &EthBlock,
+        geth_traces: &[GethExecTrace],
+    ) -> Result<BlockRowUsage, Error> {
+        self.block.set_dry_run(true);
+        self.handle_block(eth_block, geth_traces)?;
+        BlockRowUsage::from_builder(self)
+    }
+}
+
+impl CircuitInputBuilder {
+    /// The params of the builder, where the capacities left at 0 by
+    /// [`CircuitsParams::auto`] are replaced by the usage of the handled
+    /// block. The rws are the ones that the EndBlock step was built with, so
+    /// they are not rounded up.
+    pub fn resolved_params(&self) -> Result<CircuitsParams, Error> {
+        let usage = BlockRowUsage::from_builder(self)?;
+        let mut params = self.block.circuits_params;
+        let resolve = |capacity: &mut usize, used: usize| {
+            if *capacity == 0 {
+                *capacity = used;
+            }
+        };
+        // the usage and the Start row, see `set_end_block`
+        resolve(&mut params.max_rws, self.block_ctx.rwc.0 + 1);
+        resolve(&mut params.max_txs, auto_capacity(usage.txs.max(1)));
+        resolve(&mut params.max_calldata, auto_capacity(usage.calldata));
+        resolve(
+            &mut params.max_inner_blocks,
+            auto_capacity(self.block.headers.len().max(1)),
+        );
+        resolve(
+            &mut params.max_copy_rows,
+            auto_capacity(usage.copy_rows + RESERVED_ROWS),
+        );
+        resolve(
+            &mut params.max_exp_steps,
+            auto_capacity(usage.exp_steps + RESERVED_ROWS),
+        );
+        // a row per byte and one per bytecode
+        resolve(
+            &mut params.max_bytecode,
+            auto_capacity(usage.bytecode + self.block.bytecode_hashes.len() + RESERVED_ROWS),
+        );
+        resolve(&mut params.max_rlp_rows, auto_capacity(usage.rlp_rows));
+        resolve(&mut params.max_mpt_rows, auto_capacity(usage.mpt_rows));
+        // whole keccak_f's, as the capacity is rounded down to them
+        let keccak_fs =
+            (usage.keccak_rows + KECCAK_RESERVED_ROWS + ROWS_PER_ROUND - 1) / ROWS_PER_ROUND;
+        resolve(
+            &mut params.max_keccak_rows,
+            auto_capacity(keccak_fs) * ROWS_PER_ROUND,
+        );
+        // ops left out stay at 0, so that the chunk layout excludes them
+        let PrecompileEcParams {
+            ec_add,
+            ec_mul,
+            ec_pairing,
+        } = &mut params.max_ec_ops;
+        for (capacity, used) in [
+            (ec_add, usage.ec_add),
+            (ec_mul, usage.ec_mul),
+            (ec_pairing, usage.ec_pairing),
+        ] {
+            if used > 0 {
+                resolve(capacity, auto_capacity(used));
+            }
+        }
+        if usage.modexp_rows > 0 {
+            resolve(
+                &mut params.max_modexp_rows,
+                auto_capacity(usage.modexp_rows),
+            );
+        }
+        Ok(params)
+    }
+
+    /// Replace the params of the builder with [`Self::resolved_params`], once
+    /// the block is handled, and return them.
+    pub fn finalize_params(&mut self) -> Result<CircuitsParams, Error> {
+        let params = self.resolved_params()?;
+        log::info!("finalized circuits params: {params:?}");
+        self.block.circuits_params = params;
+        Ok(params)
+    }
+}
+
+/// Report of [`CircuitsParams::fits`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FitsReport {
+    /// Capacities exceeded by the usage
+    pub exceeded: Vec<CircuitCapacityError>,
+}
+
+impl FitsReport {
+    /// Whether the usage fits in all the capacities.
+    pub fn fits(&self) -> bool {
+        self.exceeded.is_empty()
+    }
+}
+
+impl CircuitsParams {
+    /// Check `usage` against the capacities of the circuits. The EVM rows
+    /// are bounded by steps of the max height. The EVM and Keccak capacities
+    /// are never exceeded when they're computed dynamically (set to 0).
+    pub fn fits(&self, usage: &BlockRowUsage) -> FitsReport {
+        let capacities = [
+            (SubCircuit::State, usage.rws, self.max_rws),
+            (SubCircuit::Tx, usage.txs, self.max_txs),
+            (SubCircuit::TxCalldata, usage.calldata, self.max_calldata),
+            (SubCircuit::Copy, usage.copy_rows, self.max_copy_rows),
+            (SubCircuit::Exp, usage.exp_steps, self.max_exp_steps),
+            (SubCircuit::Bytecode, usage.bytecode, self.max_bytecode),
+            // and the EndBlock step
+            (
+                SubCircuit::Evm,
+                usage.evm_steps * MAX_STEP_HEIGHT + 1,
+                self.max_evm_rows,
+            ),
+            (
+                SubCircuit::Keccak,
+                usage.keccak_rows + KECCAK_RESERVED_ROWS,
+                self.max_keccak_rows,
+            ),
+            (SubCircuit::EcAdd, usage.ec_add, self.max_ec_ops.ec_add),
+            (SubCircuit::EcMul, usage.ec_mul, self.max_ec_ops.ec_mul),
+            (
+                SubCircuit::EcPairing,
+                usage.ec_pairing,
+                self.max_ec_ops.ec_pairing,
+            ),
+            (SubCircuit::ModExp, usage.modexp_rows, self.max_modexp_rows),
+            (
+                SubCircuit::InnerBlocks,
+                usage.inner_blocks,
+                self.max_inner_blocks,
+            ),
+            (SubCircuit::Rlp, usage.rlp_rows, self.max_rlp_rows),
+            (SubCircuit::Mpt, usage.mpt_rows, self.max_mpt_rows),
+        ];
+        FitsReport {
+            exceeded: capacities
+                .into_iter()
+                .filter(|(circuit, needed, available)| {
+                    let is_dynamic =
+                        matches!(circuit, SubCircuit::Evm | SubCircuit::Keccak) && *available == 0;
+                    needed > available && !is_dynamic
+                })
+                .map(|(circuit, needed, available)| CircuitCapacityError {
+                    circuit,
+                    needed,
+                    available,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::ErrorKind, mock::BlockData};
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{eth, test_ctx::TestContext, MOCK_ACCOUNTS};
+
+    #[test]
+    fn fits_report() {
+        let params = CircuitsParams::default();
+        let usage = BlockRowUsage {
+            rws: 100,
+            txs: 1,
+            ..Default::default()
+        };
+        assert!(params.fits(&usage).fits());
+
+        let usage = usage
+            + BlockRowUsage {
+                rws: params.max_rws,
+                txs: 1,
+                ..Default::default()
+            };
+        assert_eq!(
+            params.fits(&usage).exceeded,
+            vec![
+                CircuitCapacityError {
+                    circuit: SubCircuit::State,
+                    needed: 100 + params.max_rws,
+                    available: params.max_rws,
+                },
+                CircuitCapacityError {
+                    circuit: SubCircuit::Tx,
+                    needed: 2,
+                    available: params.max_txs,
+                },
+            ]
+        );
+
+        // steps of the max height, and the EndBlock row
+        let params = CircuitsParams {
+            max_evm_rows: 100,
+            ..Default::default()
+        };
+        let usage = BlockRowUsage {
+            evm_steps: 5,
+            ..Default::default()
+        };
+        assert_eq!(
+            params.fits(&usage).exceeded,
+            vec![CircuitCapacityError {
+                circuit: SubCircuit::Evm,
+                needed: 5 * MAX_STEP_HEIGHT + 1,
+                available: 100,
+            }]
+        );
+    }
+
+    #[test]
+    fn estimate_step_bounds_rws() {
+        let code = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            SSTORE
+            PUSH1(0)
+            SLOAD
+            PUSH1(4)
+            PUSH1(3)
+            PUSH1(2)
+            PUSH1(1)
+            PUSH1(0x20)
+            PUSH1(0)
+            LOG4
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        // the steps of the opcodes, between BeginTx and EndTx
+        let steps = builder.block.txs()[0].steps();
+        let struct_logs = &block.geth_traces[0].struct_logs;
+        assert_eq!(steps.len(), struct_logs.len() + 2);
+        for (step, geth_step) in steps[1..].iter().zip(struct_logs) {
+            assert!(
+                BlockRowUsage::estimate_step(geth_step).rws >= step.bus_mapping_instance.len(),
+                "rws of {:?}",
+                geth_step.op
+            );
+        }
+    }
+
+    #[test]
+    fn estimate_tx_includes_deployed_code() {
+        let code = bytecode! {
+            PUSH1(0x20)
+            PUSH1(0)
+            RETURN
+        };
+        let init_code_len = code.code().len();
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(MOCK_ACCOUNTS[1]).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0].from(accs[0].address).input(code.into());
+            },
+            |block, _| block,
+        )
+        .unwrap()
+        .into();
+        let estimate =
+            BlockRowUsage::estimate_tx(&block.eth_block.transactions[0], &block.geth_traces[0]);
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let usage = BlockRowUsage::from_builder(&builder).unwrap();
+
+        // the init code and the deployed code
+        assert_eq!(estimate.bytecode, init_code_len + 0x20);
+        assert!(estimate.bytecode >= usage.bytecode);
+        assert!(estimate.keccak_rows >= usage.keccak_rows);
+    }
+
+    #[test]
+    fn rws_capacity_error() {
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! {
+            STOP
+        })
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data_with_params(
+            block.clone(),
+            CircuitsParams {
+                max_rws: 4,
+                ..Default::default()
+            },
+        )
+        .new_circuit_input_builder();
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::CapacityExceeded);
+        match err {
+            Error::CircuitCapacityExceeded(err) => {
+                assert_eq!(err.circuit, SubCircuit::State);
+                assert_eq!(err.available, 4);
+                assert!(err.needed > err.available);
+            }
+            err => panic!("unexpected error {err:?}"),
+        }
+    }
+
+    #[test]
+    fn estimate_tx_bounds_usage() {
+        let code = bytecode! {
+            PUSH1(0x40)
+            PUSH1(0)
+            PUSH1(0)
+            CALLDATACOPY
+            PUSH1(0x40)
+            PUSH1(0)
+            SHA3
+            PUSH1(3)
+            PUSH1(2)
+            EXP
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let estimate =
+            BlockRowUsage::estimate_tx(&block.eth_block.transactions[0], &block.geth_traces[0]);
+
+        // the MPT rows bound of a tx exceeds the default capacity
+        let params = CircuitsParams {
+            max_mpt_rows: 2000,
+            ..Default::default()
+        };
+        let mut builder = BlockData::new_from_geth_data_with_params(block.clone(), params)
+            .new_circuit_input_builder();
+        let block_estimate = builder.estimate_circuit_rows(&block.eth_block, &block.geth_traces);
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let usage = BlockRowUsage::from_builder(&builder).unwrap();
+
+        assert_eq!(estimate.txs, usage.txs);
+        assert_eq!(estimate.calldata, usage.calldata);
+        assert_eq!(estimate.rlp_rows, usage.rlp_rows);
+        assert!(estimate.rws >= usage.rws);
+        assert!(estimate.copy_rows >= usage.copy_rows);
+        assert!(estimate.exp_steps >= usage.exp_steps);
+        assert!(estimate.mpt_rows >= usage.mpt_rows);
+
+        assert!(block_estimate.fits());
+        assert!(block_estimate.usage.evm_steps >= usage.evm_steps);
+        assert!(block_estimate.usage.rws >= usage.rws);
+        assert!(block_estimate.usage.bytecode >= usage.bytecode);
+        assert_eq!(block_estimate.usage.inner_blocks, usage.inner_blocks);
+    }
+
+    #[test]
+    fn estimate_modexp_rows() {
+        use mock::test_ctx::{helpers::*, LoggerConfig};
+
+        // 3 ^ 5 mod 7, with operands of a byte
+        let mut code = bytecode! {
+            PUSH1(1)
+            PUSH1(0)
+            MSTORE
+            PUSH1(1)
+            PUSH1(0x20)
+            MSTORE
+            PUSH1(1)
+            PUSH1(0x40)
+            MSTORE
+            PUSH1(3)
+            PUSH1(0x60)
+            MSTORE8
+            PUSH1(5)
+            PUSH1(0x61)
+            MSTORE8
+            PUSH1(7)
+            PUSH1(0x62)
+            MSTORE8
+        };
+        for _ in 0..3 {
+            code.append(&bytecode! {
+                PUSH1(1)
+                PUSH1(0x80)
+                PUSH1(0x63)
+                PUSH1(0)
+                PUSH1(5)
+                PUSH2(0xffff)
+                STATICCALL
+                POP
+            });
+        }
+        code.op_stop();
+        let gen_block = |logger_config| -> GethData {
+            TestContext::<2, 1>::new_with_logger_config(
+                None,
+                account_0_code_account_1_no_code(code.clone()),
+                tx_from_1_to_0,
+                |block, _tx| block,
+                logger_config,
+            )
+            .unwrap()
+            .into()
+        };
+        let params = CircuitsParams {
+            max_rws: 10_000,
+            max_mpt_rows: 10_000,
+            ..Default::default()
+        };
+        assert_eq!(params.max_modexp_rows, 2 * BigModExp::MAX_ROWS);
+
+        // the rows of the calls are read from their input in the memory
+        let block = gen_block(LoggerConfig::enable_memory());
+        let estimate =
+            BlockRowUsage::estimate_tx(&block.eth_block.transactions[0], &block.geth_traces[0]);
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let usage = BlockRowUsage::from_builder(&builder).unwrap();
+        // a squaring and a multiplication per bit of the exponent, of a limb
+        assert_eq!(usage.modexp_rows, 3 * 2 * 3);
+        assert_eq!(estimate.modexp_rows, usage.modexp_rows);
+        assert!(params.fits(&estimate).fits());
+
+        // without the memory, each call is bounded by MAX_ROWS, so the 3
+        // calls don't fit in the capacity of 2 calls
+        let block = gen_block(LoggerConfig::default());
+        let estimate =
+            BlockRowUsage::estimate_tx(&block.eth_block.transactions[0], &block.geth_traces[0]);
+        assert_eq!(estimate.modexp_rows, 3 * BigModExp::MAX_ROWS);
+        assert_eq!(
+            params.fits(&estimate).exceeded,
+            vec![CircuitCapacityError {
+                circuit: SubCircuit::ModExp,
+                needed: 3 * BigModExp::MAX_ROWS,
+                available: 2 * BigModExp::MAX_ROWS,
+            }]
+        );
+    }
+
+    #[test]
+    fn finalize_auto_params() {
+        let code = bytecode! {
+            PUSH1(0x40)
+            PUSH1(0)
+            SHA3
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder =
+            BlockData::new_from_geth_data_with_params(block.clone(), CircuitsParams::auto())
+                .new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let usage = BlockRowUsage::from_builder(&builder).unwrap();
+
+        let params = builder.finalize_params().unwrap();
+        assert!(params.fits(&usage).fits());
+        assert_eq!(params.max_rws, usage.rws + 1);
+        assert_eq!(params.max_txs, 1);
+        for capacity in [
+            params.max_copy_rows,
+            params.max_bytecode,
+            params.max_rlp_rows,
+            params.max_mpt_rows,
+        ] {
+            assert!(capacity.is_power_of_two());
+        }
+        // the keccak_f's used, and the two left out of the capacity
+        assert_eq!(params.max_keccak_rows % ROWS_PER_ROUND, 0);
+        assert!((params.max_keccak_rows / ROWS_PER_ROUND).is_power_of_two());
+        assert!(params.max_keccak_rows / ROWS_PER_ROUND - 2 >= usage.keccak_rows / ROWS_PER_ROUND);
+        assert_eq!(params.max_ec_ops.ec_pairing, 0);
+        assert_eq!(
+            builder.resolved_params().unwrap().max_copy_rows,
+            params.max_copy_rows
+        );
+    }
+
+    #[test]
+    fn dry_run_usage() {
+        let code = bytecode! {
+            PUSH1(0x40)
+            PUSH1(0)
+            PUSH1(0)
+            CALLDATACOPY
+            PUSH1(0x40)
+            PUSH1(0)
+            SHA3
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::simple_ctx_with_bytecode(code)
+            .unwrap()
+            .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let usage = BlockRowUsage::from_builder(&builder).unwrap();
+
+        let mut dry_builder =
+            BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        let dry_usage = dry_builder
+            .handle_block_dry_run(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert_eq!(dry_usage, usage);
+        assert!(usage.copy_rows > 0);
+        let container = &dry_builder.block.container;
+        assert!(container.memory.is_empty() && container.stack.is_empty());
+        assert!(dry_builder.block.copy_events.is_empty());
+    }
+}
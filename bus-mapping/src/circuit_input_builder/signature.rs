@@ -0,0 +1,220 @@
+//! Up-front verification of the signatures of a block's transactions.
+
+use eth_types::{
+    geth_types::{self, TxType},
+    Address, H256,
+};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::Error;
+
+/// Why the `v` of a signed tx doesn't encode its type and `chain_id` as the tx
+/// circuit requires, if it doesn't: `{27, 28}` for pre-EIP-155 txs,
+/// `chain_id * 2 + {35, 36}` for EIP-155 txs, and `{0, 1}` for typed txs,
+/// whose chain id is signed instead.
+fn v_mismatch(tx: &eth_types::Transaction, tx_type: TxType, chain_id: u64) -> Option<String> {
+    let v = tx.v.as_u64();
+    match tx_type {
+        TxType::PreEip155 => {
+            (v != 27 && v != 28).then(|| format!("pre-EIP-155 v {v} not in {{27, 28}}"))
+        }
+        TxType::Eip155 => (v < 35 || (v - 35) / 2 != chain_id)
+            .then(|| format!("EIP-155 v {v} doesn't encode the chain id {chain_id}")),
+        TxType::Eip1559 | TxType::Eip2930 | TxType::Eip4844 => {
+            let tx_chain_id = tx.chain_id.unwrap_or_default().as_u64();
+            if v > 1 {
+                Some(format!("{tx_type:?} v {v} not in {{0, 1}}"))
+            } else if tx_chain_id != chain_id {
+                Some(format!("chain id {tx_chain_id} instead of {chain_id}"))
+            } else {
+                None
+            }
+        }
+        TxType::L1Msg | TxType::SystemCall => None,
+    }
+}
+
+/// Verify the signatures of all `txs` in parallel: the `v` must encode the tx
+/// type and the `chain_id` of the chunk, and the signature must be valid and
+/// recover the sender of the tx. L1 messages and the pseudo-txs of system
+/// calls are exempt, and unsigned txs are skipped with a warning, as in the tx
+/// circuit.
+///
+/// Returns [`Error::InvalidTxSignatures`] listing every rejected tx, so that
+/// an invalid block is reported before any witness is generated.
+pub fn verify_tx_signatures(txs: &[eth_types::Transaction], chain_id: u64) -> Result<(), Error> {
+    let invalid: Vec<(H256, String)> = txs
+        .par_iter()
+        .filter_map(|tx| {
+            let geth_tx = geth_types::Transaction::from(tx);
+            if geth_tx.tx_type.is_unsigned() {
+                return None;
+            }
+            if geth_tx.v == 0 && geth_tx.r.is_zero() && geth_tx.s.is_zero() {
+                log::warn!("tx {:?} is not signed and is not L1Msg", tx.hash);
+                return None;
+            }
+            if let Some(reason) = v_mismatch(tx, geth_tx.tx_type, chain_id) {
+                return Some((tx.hash, reason));
+            }
+            match geth_tx.sign_data() {
+                Err(err) => Some((tx.hash, format!("invalid signature: {err:?}"))),
+                Ok(sign_data) => {
+                    let signer = sign_data.get_addr();
+                    (signer != tx.from).then(|| {
+                        (
+                            tx.hash,
+                            format!("signed by {signer:?} instead of {:?}", tx.from),
+                        )
+                    })
+                }
+            }
+        })
+        .collect();
+
+    if invalid.is_empty() {
+        return Ok(());
+    }
+    for (hash, reason) in &invalid {
+        log::error!("tx {:?} rejected: {}", hash, reason);
+    }
+    Err(Error::InvalidTxSignatures(invalid))
+}
+
+/// Check the txs given a caller-provided sender in `senders` (tx hash to
+/// sender): they must be in `txs`, unsigned, and neither L1 messages nor the
+/// pseudo-txs of system calls.
+///
+/// Returns [`Error::InvalidTxSignatures`] listing every rejected tx.
+pub fn verify_unverified_senders(
+    txs: &[eth_types::Transaction],
+    senders: &HashMap<H256, Address>,
+) -> Result<(), Error> {
+    let mut invalid: Vec<(H256, String)> = senders
+        .keys()
+        .filter_map(|hash| {
+            let reason = match txs.iter().find(|tx| tx.hash == *hash) {
+                None => "not in the block",
+                Some(tx) => {
+                    let geth_tx = geth_types::Transaction::from(tx);
+                    if geth_tx.tx_type.is_l1_msg() {
+                        "L1 message"
+                    } else if geth_tx.tx_type.is_system_call() {
+                        "system call"
+                    } else if geth_tx.v != 0 || !geth_tx.r.is_zero() || !geth_tx.s.is_zero() {
+                        "signed"
+                    } else {
+                        return None;
+                    }
+                }
+            };
+            Some((*hash, format!("{reason} tx given an unverified sender")))
+        })
+        .collect();
+
+    if invalid.is_empty() {
+        return Ok(());
+    }
+    invalid.sort();
+    for (hash, reason) in &invalid {
+        log::error!("tx {:?} rejected: {}", hash, reason);
+    }
+    Err(Error::InvalidTxSignatures(invalid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, U256, U64};
+    use mock::{MockTransaction, MOCK_CHAIN_ID, MOCK_WALLETS};
+
+    #[test]
+    fn tx_signatures() {
+        let signed: eth_types::Transaction = MockTransaction::default()
+            .from(MOCK_WALLETS[0].clone())
+            .to(address!("0x00000000000000000000000000000000000000aa"))
+            .build()
+            .into();
+        assert!(verify_tx_signatures(&[signed.clone()], *MOCK_CHAIN_ID).is_ok());
+
+        let mut forged = signed.clone();
+        forged.from = address!("0x00000000000000000000000000000000000000bb");
+        let mut invalid = signed.clone();
+        invalid.s = U256::MAX;
+        match verify_tx_signatures(&[signed.clone(), forged.clone(), invalid], *MOCK_CHAIN_ID) {
+            Err(Error::InvalidTxSignatures(errors)) => assert_eq!(errors.len(), 2),
+            res => panic!("unexpected result {res:?}"),
+        }
+
+        // L1 messages are not signed
+        let mut l1_msg = forged;
+        l1_msg.transaction_type = Some(U64::from(0x7e));
+        l1_msg.v = U64::zero();
+        l1_msg.r = U256::zero();
+        l1_msg.s = U256::zero();
+        let mut system_call = l1_msg.clone();
+        system_call.transaction_type = Some(U64::from(0x7d));
+        assert!(verify_tx_signatures(&[signed, l1_msg, system_call], *MOCK_CHAIN_ID).is_ok());
+    }
+
+    #[test]
+    fn tx_signature_chain_id() {
+        let signed: eth_types::Transaction = MockTransaction::default()
+            .from(MOCK_WALLETS[0].clone())
+            .to(address!("0x00000000000000000000000000000000000000aa"))
+            .build()
+            .into();
+        // signed for another chain
+        match verify_tx_signatures(&[signed.clone()], *MOCK_CHAIN_ID + 1) {
+            Err(Error::InvalidTxSignatures(errors)) => assert_eq!(errors.len(), 1),
+            res => panic!("unexpected result {res:?}"),
+        }
+
+        let mut pre_eip155 = signed.clone();
+        pre_eip155.v = U64::one();
+        assert_eq!(
+            v_mismatch(&pre_eip155, TxType::PreEip155, *MOCK_CHAIN_ID),
+            Some("pre-EIP-155 v 1 not in {27, 28}".to_string())
+        );
+
+        let mut eip1559 = signed;
+        eip1559.transaction_type = Some(U64::from(2));
+        eip1559.v = U64::one();
+        assert_eq!(v_mismatch(&eip1559, TxType::Eip1559, *MOCK_CHAIN_ID), None);
+        eip1559.chain_id = Some((*MOCK_CHAIN_ID + 1).into());
+        assert!(v_mismatch(&eip1559, TxType::Eip1559, *MOCK_CHAIN_ID).is_some());
+        eip1559.v = U64::from(2 * *MOCK_CHAIN_ID + 35);
+        assert!(v_mismatch(&eip1559, TxType::Eip1559, *MOCK_CHAIN_ID).is_some());
+    }
+
+    #[test]
+    fn unverified_senders() {
+        let signed: eth_types::Transaction = MockTransaction::default()
+            .from(MOCK_WALLETS[0].clone())
+            .to(address!("0x00000000000000000000000000000000000000aa"))
+            .build()
+            .into();
+        let unsigned: eth_types::Transaction = MockTransaction::default()
+            .from(address!("0x00000000000000000000000000000000000000bb"))
+            .to(address!("0x00000000000000000000000000000000000000aa"))
+            .nonce(U256::one())
+            .build()
+            .into();
+        let sender = address!("0x00000000000000000000000000000000000000cc");
+        let txs = [signed.clone(), unsigned.clone()];
+
+        let senders = HashMap::from([(unsigned.hash, sender)]);
+        assert!(verify_unverified_senders(&txs, &senders).is_ok());
+
+        let senders = HashMap::from([
+            (unsigned.hash, sender),
+            (signed.hash, sender),
+            (H256::repeat_byte(1), sender),
+        ]);
+        match verify_unverified_senders(&txs, &senders) {
+            Err(Error::InvalidTxSignatures(errors)) => assert_eq!(errors.len(), 2),
+            res => panic!("unexpected result {res:?}"),
+        }
+    }
+}
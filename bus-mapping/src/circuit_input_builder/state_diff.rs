@@ -0,0 +1,179 @@
+//! State changes of the blocks handled by the [`CircuitInputBuilder`].
+
+use super::CircuitInputBuilder;
+use crate::operation::AccountField;
+use eth_types::{Address, Hash, ToBigEndian, Word};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A value before and after the handled blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    /// Value before the first handled tx
+    pub prev: T,
+    /// Value after the last handled tx
+    pub post: T,
+}
+
+/// The changes of an account. A field is `None` when it is unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AccountDiff {
+    /// Nonce
+    pub nonce: Option<Change<Word>>,
+    /// Balance
+    pub balance: Option<Change<Word>>,
+    /// Poseidon hash of code
+    pub code_hash: Option<Change<Hash>>,
+    /// Keccak hash of code
+    pub keccak_code_hash: Option<Change<Hash>>,
+    /// Size of code
+    pub code_size: Option<Change<Word>>,
+    /// Changed storage slots, by key
+    pub storage: BTreeMap<Word, Change<Word>>,
+}
+
+impl AccountDiff {
+    fn is_empty(&self) -> bool {
+        self.nonce.is_none()
+            && self.balance.is_none()
+            && self.code_hash.is_none()
+            && self.keccak_code_hash.is_none()
+            && self.code_size.is_none()
+            && self.storage.is_empty()
+    }
+}
+
+/// The changes of the state by the handled blocks, by account. Accounts
+/// which are only read, or written back to their previous values, are not
+/// included.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BlockStateDiff {
+    /// Changed accounts, by address
+    pub accounts: BTreeMap<Address, AccountDiff>,
+}
+
+/// The first previous value and the last value of each key, in rw counter
+/// order, keeping the keys whose value has changed.
+fn changes<K: Ord>(ops: impl Iterator<Item = (usize, K, Word, Word)>) -> BTreeMap<K, Change<Word>> {
+    let mut ops = ops.collect::<Vec<_>>();
+    ops.sort_by_key(|(rwc, ..)| *rwc);
+    let mut changes = BTreeMap::new();
+    for (_, key, value, value_prev) in ops {
+        changes
+            .entry(key)
+            .or_insert(Change {
+                prev: value_prev,
+                post: value,
+            })
+            .post = value;
+    }
+    changes.retain(|_, change| change.prev != change.post);
+    changes
+}
+
+impl CircuitInputBuilder {
+    /// The state changes of the blocks handled so far, from the account and
+    /// storage operations of the witness, so that they can be cross-checked
+    /// against the state diff of an execution client.
+    pub fn state_diff(&self) -> BlockStateDiff {
+        let container = &self.block.container;
+        let mut diff = BlockStateDiff::default();
+
+        let account_changes = changes(container.account.iter().map(|op| {
+            let (rwc, op) = (op.rwc().0, op.op());
+            (rwc, (op.address, op.field), op.value, op.value_prev)
+        }));
+        for ((address, field), change) in account_changes {
+            let account = diff.accounts.entry(address).or_default();
+            let hash_change = || Change {
+                prev: Hash::from(change.prev.to_be_bytes()),
+                post: Hash::from(change.post.to_be_bytes()),
+            };
+            match field {
+                AccountField::Nonce => account.nonce = Some(change),
+                AccountField::Balance => account.balance = Some(change),
+                AccountField::CodeHash => account.code_hash = Some(hash_change()),
+                AccountField::KeccakCodeHash => account.keccak_code_hash = Some(hash_change()),
+                AccountField::CodeSize => account.code_size = Some(change),
+            }
+        }
+
+        let storage_changes = changes(container.storage.iter().map(|op| {
+            let (rwc, op) = (op.rwc().0, op.op());
+            (rwc, (op.address, op.key), op.value, op.value_prev)
+        }));
+        for ((address, key), change) in storage_changes {
+            diff.accounts
+                .entry(address)
+                .or_default()
+                .storage
+                .insert(key, change);
+        }
+
+        diff.accounts.retain(|_, account| !account.is_empty());
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    #[test]
+    fn state_diff_of_block() {
+        let code = bytecode! {
+            // changed slot
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            // slot written back to its previous value
+            PUSH1(0x02)
+            PUSH1(0x01)
+            SSTORE
+            PUSH1(0x00)
+            PUSH1(0x01)
+            SSTORE
+            // read slot
+            PUSH1(0x02)
+            SLOAD
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let diff = builder.state_diff();
+        let tx = &block.eth_block.transactions[0];
+        let contract = &diff.accounts[&tx.to.unwrap()];
+        assert_eq!(
+            contract.storage,
+            BTreeMap::from([(
+                Word::zero(),
+                Change {
+                    prev: Word::zero(),
+                    post: Word::one(),
+                }
+            )])
+        );
+        assert_eq!(contract.nonce, None);
+
+        let sender = &diff.accounts[&tx.from];
+        let nonce = sender.nonce.unwrap();
+        assert_eq!(nonce.post, nonce.prev + 1);
+        let balance = sender.balance.unwrap();
+        assert!(balance.post < balance.prev);
+        assert!(sender.storage.is_empty());
+    }
+}
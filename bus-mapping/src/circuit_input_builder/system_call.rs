@@ -0,0 +1,415 @@
+//! Chain-defined system calls made before and after the txs of a block, e.g.
+//! a setter of the L1 block info.
+//!
+//! A system call is not a tx of the block, so the node doesn't return its
+//! trace along with the traces of the block. It is traced with
+//! `debug_traceCall` and handled as a pseudo-tx placed before or after the
+//! txs of the block. The pseudo-tx has the fields of an L1 msg, as it carries
+//! no signature and pays no fee, under its own EIP-2718 type 0x7d: as a
+//! [`TxType::SystemCall`], it doesn't consume the L1 message queue, and isn't
+//! part of the data hash of the chunk.
+
+use super::EthBlock;
+use crate::{l2_predeployed::l2_scroll_messenger, Error};
+use eth_types::{
+    geth_types::{get_rlp_signed, TxType},
+    Address, Bytes, GethExecTrace, Transaction, Word, H256, U64,
+};
+use ethers_core::{types::TransactionRequest, utils::keccak256};
+use serde::{Deserialize, Serialize};
+
+/// The pseudo-tx of a system call and its trace.
+pub type SystemCallTrace = (Transaction, GethExecTrace);
+
+/// A call made by the chain itself in every block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemCall {
+    /// System address the call is made from
+    pub from: Address,
+    /// Called contract
+    pub to: Address,
+    /// Calldata
+    pub input: Bytes,
+    /// Gas limit of the call
+    pub gas: u64,
+}
+
+/// The chain-defined behaviour the witness generation needs to know about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSpec {
+    /// System calls made before the txs of each block, in order
+    pub pre_block_calls: Vec<SystemCall>,
+    /// System calls made after the txs of each block, in order
+    pub post_block_calls: Vec<SystemCall>,
+    /// First block of the Shanghai hardfork, which enables `PUSH0`, or `None`
+    /// if the chain doesn't activate it
+    pub shanghai_block: Option<u64>,
+    /// L2ScrollMessenger, the only contract whose `SentMessage` logs append
+    /// messages to the withdraw trie
+    pub l2_messenger: Address,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        Self {
+            pre_block_calls: Vec::new(),
+            post_block_calls: Vec::new(),
+            shanghai_block: cfg!(feature = "shanghai").then_some(0),
+            l2_messenger: *l2_scroll_messenger::ADDRESS,
+        }
+    }
+}
+
+impl ChainSpec {
+    /// Whether the chain makes any system call.
+    pub fn has_system_calls(&self) -> bool {
+        !self.pre_block_calls.is_empty() || !self.post_block_calls.is_empty()
+    }
+
+    /// Check that the system calls can be traced exactly from the node. Each
+    /// call is traced on the state of a whole block, which doesn't have the
+    /// changes of the previous system calls of the same block: only a single
+    /// pre-block call, traced on the state of the parent block, sees the
+    /// state it is made on. A post-block call would be traced on the state
+    /// after the block, which may already have its own changes.
+    pub fn check_traceable(&self) -> Result<(), Error> {
+        if self.pre_block_calls.len() > 1 {
+            return Err(Error::InvalidSystemCall(format!(
+                "{} pre-block calls can't be traced on the state of each other",
+                self.pre_block_calls.len()
+            )));
+        }
+        if !self.post_block_calls.is_empty() {
+            return Err(Error::InvalidSystemCall(
+                "post-block calls can't be traced on the state they are made on".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The system call made by the tx at `index` of a block of `num_txs` txs:
+    /// the pseudo-txs of the pre-block calls are the first txs of the block,
+    /// and those of the post-block calls the last ones.
+    pub fn system_call_at(&self, index: usize, num_txs: usize) -> Option<&SystemCall> {
+        if index < self.pre_block_calls.len() {
+            return self.pre_block_calls.get(index);
+        }
+        (index + self.post_block_calls.len())
+            .checked_sub(num_txs)
+            .and_then(|index| self.post_block_calls.get(index))
+    }
+
+    /// Check that the txs of a block start and end with the pseudo-txs of
+    /// its system calls, and that no other tx is typed as a system call.
+    pub fn check_system_calls(&self, txs: &[Transaction]) -> Result<(), Error> {
+        let num_calls = self.pre_block_calls.len() + self.post_block_calls.len();
+        if txs.len() < num_calls {
+            return Err(Error::InvalidSystemCall(format!(
+                "{} txs can't hold the pseudo-txs of {num_calls} system calls",
+                txs.len()
+            )));
+        }
+        for (index, tx) in txs.iter().enumerate() {
+            match self.system_call_at(index, txs.len()) {
+                Some(call) if !call.is_made_by(tx) => {
+                    return Err(Error::InvalidSystemCall(format!(
+                        "tx {:?} at index {index} isn't the pseudo-tx of the system call {call:?}",
+                        tx.hash
+                    )));
+                }
+                None if TxType::get_tx_type(tx).is_system_call() => {
+                    return Err(Error::InvalidSystemCall(format!(
+                        "tx {:?} at index {index} is typed as a system call the chain doesn't make",
+                        tx.hash
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the Shanghai hardfork is active at the block `block_number`.
+    pub fn is_shanghai(&self, block_number: u64) -> bool {
+        self.shanghai_block
+            .map_or(false, |shanghai_block| block_number >= shanghai_block)
+    }
+}
+
+impl SystemCall {
+    /// The request traced with `debug_traceCall`, with the nonce of `from`.
+    pub fn to_request(&self, nonce: Word) -> TransactionRequest {
+        TransactionRequest::new()
+            .from(self.from)
+            .to(self.to)
+            .data(self.input.clone())
+            .gas(self.gas)
+            .gas_price(0)
+            .nonce(nonce)
+    }
+
+    /// The pseudo-tx of the call in `eth_block`, with the nonce of `from`.
+    /// Its index is set when it is inserted in the block.
+    pub fn to_eth_tx(&self, eth_block: &EthBlock, nonce: Word) -> Transaction {
+        let mut tx = Transaction {
+            transaction_type: Some(U64::from(0x7d)),
+            nonce,
+            block_hash: eth_block.hash,
+            block_number: eth_block.number,
+            from: self.from,
+            to: Some(self.to),
+            gas_price: Some(Word::zero()),
+            gas: self.gas.into(),
+            input: self.input.clone(),
+            ..Default::default()
+        };
+        debug_assert!(TxType::get_tx_type(&tx).is_system_call());
+        tx.hash = H256(keccak256(get_rlp_signed(&tx)));
+        tx
+    }
+
+    /// Whether `tx` is a pseudo-tx of the call.
+    pub fn is_made_by(&self, tx: &Transaction) -> bool {
+        TxType::get_tx_type(tx).is_system_call()
+            && tx.from == self.from
+            && tx.to == Some(self.to)
+            && tx.input == self.input
+            && tx.gas == self.gas.into()
+            && tx.value.is_zero()
+    }
+}
+
+/// Insert the pseudo-txs of the system calls and their traces around the
+/// txs of `eth_block`, and renumber the indexes of the txs.
+pub fn insert_system_calls(
+    eth_block: &mut EthBlock,
+    geth_traces: &mut Vec<GethExecTrace>,
+    pre_block: Vec<SystemCallTrace>,
+    post_block: Vec<SystemCallTrace>,
+) {
+    let (pre_txs, pre_traces): (Vec<_>, Vec<_>) = pre_block.into_iter().unzip();
+    let (post_txs, post_traces): (Vec<_>, Vec<_>) = post_block.into_iter().unzip();
+
+    eth_block.transactions = pre_txs
+        .into_iter()
+        .chain(std::mem::take(&mut eth_block.transactions))
+        .chain(post_txs)
+        .enumerate()
+        .map(|(index, tx)| Transaction {
+            transaction_index: Some(U64::from(index)),
+            ..tx
+        })
+        .collect();
+    *geth_traces = pre_traces
+        .into_iter()
+        .chain(std::mem::take(geth_traces))
+        .chain(post_traces)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::{
+        eth,
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS, MOCK_WALLETS,
+    };
+
+    /// A block whose first tx is the pseudo-tx of a pre-block system call to
+    /// a contract which the second tx calls too, and the system call.
+    fn block_with_system_call() -> (GethData, SystemCall) {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let mut block: GethData = TestContext::<3, 2>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code);
+                accs[1].address(MOCK_ACCOUNTS[4]);
+                accs[2].address(MOCK_WALLETS[0].address()).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(100_000))
+                    .gas_price(Word::zero());
+                txs[1].from(MOCK_WALLETS[0].clone()).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let system_call = SystemCall {
+            from: MOCK_ACCOUNTS[4],
+            to: MOCK_ACCOUNTS[0],
+            input: Bytes::default(),
+            gas: 100_000,
+        };
+        block.eth_block.transactions[0] = Transaction {
+            transaction_index: Some(U64::zero()),
+            ..system_call.to_eth_tx(&block.eth_block, Word::zero())
+        };
+        (block, system_call)
+    }
+
+    #[test]
+    fn handle_block_with_system_call() {
+        let (block, system_call) = block_with_system_call();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            pre_block_calls: vec![system_call],
+            ..Default::default()
+        };
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let txs = builder.block.txs();
+        assert_eq!(txs[0].tx_type, TxType::SystemCall);
+        assert_eq!(txs[0].rollup_data_size_estimate(), 0);
+        assert!(!txs[1].tx_type.is_unsigned());
+    }
+
+    #[test]
+    fn handle_block_without_system_call() {
+        let (block, system_call) = block_with_system_call();
+
+        // the chain spec makes a call which the pseudo-tx doesn't make
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            pre_block_calls: vec![SystemCall {
+                gas: 50_000,
+                ..system_call.clone()
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::InvalidSystemCall(_))
+        ));
+
+        // the block has fewer txs than the chain spec has system calls
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            pre_block_calls: vec![system_call.clone()],
+            post_block_calls: vec![system_call.clone(), system_call],
+            ..Default::default()
+        };
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::InvalidSystemCall(_))
+        ));
+
+        // the chain makes no system call
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::InvalidSystemCall(_))
+        ));
+    }
+
+    #[test]
+    fn system_calls_at_block_ends() {
+        let call = |gas| SystemCall {
+            from: MOCK_ACCOUNTS[4],
+            to: MOCK_ACCOUNTS[0],
+            input: Bytes::default(),
+            gas,
+        };
+        let chain_spec = ChainSpec {
+            pre_block_calls: vec![call(1)],
+            post_block_calls: vec![call(2), call(3)],
+            ..Default::default()
+        };
+        assert_eq!(
+            (0..5)
+                .map(|index| chain_spec.system_call_at(index, 5).map(|call| call.gas))
+                .collect::<Vec<_>>(),
+            vec![Some(1), None, None, Some(2), Some(3)]
+        );
+
+        // only a single pre-block call is traced exactly
+        assert!(chain_spec.check_traceable().is_err());
+        let chain_spec = ChainSpec {
+            pre_block_calls: vec![call(1)],
+            ..Default::default()
+        };
+        assert!(chain_spec.check_traceable().is_ok());
+        let chain_spec = ChainSpec {
+            pre_block_calls: vec![call(1), call(2)],
+            ..Default::default()
+        };
+        assert!(chain_spec.check_traceable().is_err());
+    }
+
+    #[test]
+    fn insert_pre_block_system_call() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let system_call = SystemCall {
+            from: MOCK_ACCOUNTS[4],
+            to: block.eth_block.transactions[0].to.unwrap(),
+            input: Bytes::default(),
+            gas: 100_000,
+        };
+        let mut eth_block = block.eth_block.clone();
+        let mut geth_traces = block.geth_traces.clone();
+        let pre_block = vec![(
+            system_call.to_eth_tx(&eth_block, Word::zero()),
+            geth_traces[0].clone(),
+        )];
+        insert_system_calls(&mut eth_block, &mut geth_traces, pre_block, vec![]);
+
+        assert_eq!(eth_block.transactions.len(), 2);
+        assert_eq!(geth_traces.len(), 2);
+        assert!(TxType::get_tx_type(&eth_block.transactions[0]).is_system_call());
+        for (index, tx) in eth_block.transactions.iter().enumerate() {
+            assert_eq!(tx.transaction_index, Some(U64::from(index)));
+        }
+        assert_eq!(
+            eth_block.transactions[1].hash,
+            block.eth_block.transactions[0].hash
+        );
+    }
+
+    #[test]
+    fn shanghai_by_block_number() {
+        let chain_spec = ChainSpec {
+            shanghai_block: Some(100),
+            ..Default::default()
+        };
+        assert!(!chain_spec.is_shanghai(99));
+        assert!(chain_spec.is_shanghai(100));
+
+        let chain_spec = ChainSpec {
+            shanghai_block: None,
+            ..Default::default()
+        };
+        assert!(!chain_spec.is_shanghai(u64::MAX));
+        assert_eq!(
+            ChainSpec::default().is_shanghai(0),
+            cfg!(feature = "shanghai")
+        );
+    }
+}
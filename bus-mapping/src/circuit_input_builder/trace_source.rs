@@ -0,0 +1,198 @@
+//! Sources of the execution trace of a tx, so that a trace doesn't need to be
+//! held in memory as a whole [`GethExecTrace`] to be handled.
+
+use crate::Error;
+use eth_types::{evm_types::Gas, GethExecStep, GethExecTrace};
+
+/// The execution trace of a tx, as read by the [`super::CircuitInputBuilder`].
+///
+/// The steps are only ever visited in order, each of them along with the next
+/// one, which is all the lookahead the witness generation needs (e.g. the
+/// value pushed by a push-with-data opcode is read from the stack of the next
+/// step). A source is visited twice per tx: once to collect the call results
+/// and once to generate the steps.
+pub trait TraceSource {
+    /// L1 fee of the tx.
+    fn l1_fee(&self) -> u64;
+
+    /// Gas used by the tx.
+    fn gas(&self) -> Gas;
+
+    /// True when the tx has failed.
+    fn failed(&self) -> bool;
+
+    /// Gas left at the first step, or `None` if the trace has no step.
+    fn first_step_gas(&self) -> Result<Option<Gas>, Error>;
+
+    /// Call `f` with the index of each step and a window which starts at the
+    /// step and holds the next step too, when there is one. Stops at the first
+    /// error.
+    fn try_for_each_step(
+        &self,
+        f: &mut dyn FnMut(usize, &[GethExecStep]) -> Result<(), Error>,
+    ) -> Result<(), Error>;
+}
+
+impl TraceSource for GethExecTrace {
+    fn l1_fee(&self) -> u64 {
+        self.l1_fee
+    }
+
+    fn gas(&self) -> Gas {
+        self.gas
+    }
+
+    fn failed(&self) -> bool {
+        self.failed
+    }
+
+    fn first_step_gas(&self) -> Result<Option<Gas>, Error> {
+        Ok(self.struct_logs.first().map(|step| step.gas))
+    }
+
+    fn try_for_each_step(
+        &self,
+        f: &mut dyn FnMut(usize, &[GethExecStep]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let len = self.struct_logs.len();
+        for index in 0..len {
+            f(index, &self.struct_logs[index..len.min(index + 2)])?;
+        }
+        Ok(())
+    }
+}
+
+/// A [`TraceSource`] which reads the steps from an iterator, e.g. one which
+/// deserializes them one by one from a file, so that at most two steps of the
+/// trace are held in memory at a time.
+///
+/// `steps` is called each time the trace is visited and must return the steps
+/// from the first one.
+#[derive(Debug, Clone)]
+pub struct StreamedTrace<S> {
+    /// L1 fee of the tx.
+    pub l1_fee: u64,
+    /// Gas used by the tx.
+    pub gas: Gas,
+    /// True when the tx has failed.
+    pub failed: bool,
+    steps: S,
+}
+
+impl<S, I> StreamedTrace<S>
+where
+    S: Fn() -> I,
+    I: Iterator<Item = Result<GethExecStep, Error>>,
+{
+    /// Create a new Self.
+    pub fn new(l1_fee: u64, gas: Gas, failed: bool, steps: S) -> Self {
+        Self {
+            l1_fee,
+            gas,
+            failed,
+            steps,
+        }
+    }
+}
+
+impl<S, I> TraceSource for StreamedTrace<S>
+where
+    S: Fn() -> I,
+    I: Iterator<Item = Result<GethExecStep, Error>>,
+{
+    fn l1_fee(&self) -> u64 {
+        self.l1_fee
+    }
+
+    fn gas(&self) -> Gas {
+        self.gas
+    }
+
+    fn failed(&self) -> bool {
+        self.failed
+    }
+
+    fn first_step_gas(&self) -> Result<Option<Gas>, Error> {
+        (self.steps)()
+            .next()
+            .transpose()
+            .map(|step| step.map(|step| step.gas))
+    }
+
+    fn try_for_each_step(
+        &self,
+        f: &mut dyn FnMut(usize, &[GethExecStep]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut steps = (self.steps)();
+        let mut window = Vec::with_capacity(2);
+        window.extend(steps.next().transpose()?);
+        let mut index = 0;
+        while !window.is_empty() {
+            window.extend(steps.next().transpose()?);
+            f(index, &window)?;
+            window.remove(0);
+            index += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::BlockData;
+    use eth_types::{bytecode, geth_types::GethData};
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    #[test]
+    fn handle_streamed_traces() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            PUSH2(0x1234)
+            PUSH1(0x00)
+            MSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let new_builder =
+            || BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+
+        let mut expected = new_builder();
+        expected
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let traces = block
+            .geth_traces
+            .iter()
+            .map(|trace| {
+                StreamedTrace::new(trace.l1_fee, trace.gas, trace.failed, move || {
+                    trace.struct_logs.iter().cloned().map(Ok)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut streamed = new_builder();
+        streamed
+            .handle_block_from_sources(&block.eth_block, &traces)
+            .unwrap();
+
+        assert_eq!(streamed.block_ctx.rwc, expected.block_ctx.rwc);
+        assert_eq!(
+            format!("{:?}", streamed.block.txs),
+            format!("{:?}", expected.block.txs)
+        );
+        assert_eq!(
+            format!("{:?}", streamed.block.container),
+            format!("{:?}", expected.block.container)
+        );
+    }
+}
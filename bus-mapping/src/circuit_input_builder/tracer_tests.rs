@@ -8,22 +8,23 @@ use crate::{
         GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
         GETH_ERR_STACK_UNDERFLOW,
     },
-    operation::RWCounter,
+    operation::{OpEnum, RWCounter},
     state_db::Account,
 };
 use eth_types::{
     address, bytecode,
     evm_types::{stack::Stack, Gas, Memory, OpcodeId},
     geth_types::GethData,
-    word, Bytecode, Hash, ToAddress, ToWord, Word,
+    word, AccessList, Bytecode, Hash, ToAddress, ToWord, Word, H256,
 };
+use ethers_core::types::transaction::eip2930::AccessListItem;
 use lazy_static::lazy_static;
 use mock::{
     test_ctx::{helpers::*, LoggerConfig, TestContext},
-    MOCK_COINBASE,
+    MOCK_ACCOUNTS, MOCK_COINBASE,
 };
 use pretty_assertions::assert_eq;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 // Helper struct that contains a CircuitInputBuilder, a particuar tx and a
 // particular execution step so that we can easily get a
@@ -1020,6 +1021,117 @@ fn tracer_err_max_code_size_exceeded_tx_deploy() {
     );
 }
 
+#[test]
+fn circuits_params_max_code_size() {
+    let params = CircuitsParams::default();
+    assert!(params.validate().is_ok());
+    assert_eq!(params.max_init_code_size(), MAX_INIT_CODE_SIZE);
+
+    // chain limits below and above the one of Ethereum
+    for max_code_size in [0x4000, 0x8000] {
+        let params = CircuitsParams {
+            max_code_size,
+            ..Default::default()
+        };
+        assert!(params.validate().is_ok());
+    }
+
+    for max_code_size in [0, MAX_EXPANDED_MEMORY_ADDRESS as usize + 1] {
+        let params = CircuitsParams {
+            max_code_size,
+            ..Default::default()
+        };
+        assert!(matches!(
+            params.validate(),
+            Err(Error::InvalidCircuitsParams(_))
+        ));
+    }
+}
+
+#[test]
+fn block_ops_queries() {
+    let code = bytecode! {
+        PUSH1(0x2) // value
+        PUSH1(0x1) // key
+        SSTORE
+        STOP
+    };
+    let block: GethData = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(code),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+    let mut builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    let block = &builder.block;
+
+    let (step_idx, step) = block.txs()[0]
+        .steps()
+        .iter()
+        .enumerate()
+        .find(|(_, step)| step.exec_state == ExecState::Op(OpcodeId::SSTORE))
+        .unwrap();
+    let step_ops = block.ops_for_step(0, step_idx).unwrap();
+    assert_eq!(step_ops.len(), step.bus_mapping_instance.len());
+    assert_eq!(
+        step_ops.iter().map(|(rwc, _, _)| rwc.0).collect::<Vec<_>>(),
+        (step.rwc.0..step.rwc.0 + step_ops.len()).collect::<Vec<_>>()
+    );
+    assert_eq!(block.ops_for_opcode(OpcodeId::SSTORE).len(), step_ops.len());
+    assert!(step_ops
+        .iter()
+        .any(|(_, rw, op)| rw.is_write() && matches!(op, OpEnum::Storage(_))));
+
+    let storage_ops = block.storage_ops_for_address(MOCK_ACCOUNTS[0]);
+    assert_eq!(storage_ops.len(), 1);
+    assert_eq!(storage_ops[0].op().key, Word::one());
+    assert_eq!(storage_ops[0].op().value, Word::from(2));
+
+    assert_eq!(
+        block.ops_for_call(0, 0).unwrap().len(),
+        block.txs()[0]
+            .steps()
+            .iter()
+            .map(|step| step.bus_mapping_instance.len())
+            .sum::<usize>()
+    );
+    assert!(block.ops_for_step(1, 0).is_none());
+    assert!(block.ops_for_call(0, 1).is_none());
+}
+
+#[test]
+fn end_inner_block_step() {
+    let block: GethData = TestContext::<2, 1>::new(
+        None,
+        account_0_code_account_1_no_code(bytecode! { STOP }),
+        tx_from_1_to_0,
+        |block, _tx| block.number(0xcafeu64),
+    )
+    .unwrap()
+    .into();
+    let mut builder =
+        crate::mock::BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+
+    let steps = builder.block.txs()[0].steps();
+    let end_inner_block = &steps[steps.len() - 1];
+    assert_eq!(steps[steps.len() - 2].exec_state, ExecState::EndTx);
+    assert_eq!(end_inner_block.exec_state, ExecState::EndInnerBlock);
+    assert!(end_inner_block.bus_mapping_instance.is_empty());
+    assert_eq!(
+        end_inner_block.rwc,
+        builder.block.block_steps.end_block_last.rwc
+    );
+}
+
 #[test]
 fn tracer_create_stop() {
     // code_creator doesn't output anything because it stops.
@@ -1507,7 +1619,7 @@ fn tracer_err_gas_uint_overflow() {
     let mut builder = CircuitInputBuilderTx::new(&block, step);
     assert_eq!(
         builder.state_ref().get_step_err(step, next_step).unwrap(),
-        Some(ExecError::OutOfGas(OogError::StaticMemoryExpansion))
+        Some(ExecError::OutOfGas(OogError::MemoryExpansion))
     );
 }
 
@@ -2038,14 +2150,27 @@ fn test_gen_access_trace() {
     assert_eq!(
         access_set,
         AccessSet {
-            state: HashMap::from_iter([
-                (ADDR_0, HashSet::new()),
-                (*ADDR_A, HashSet::new()),
-                (*ADDR_B, HashSet::from_iter([Word::from(2), Word::from(3)]))
+            state: BTreeMap::from_iter([
+                (ADDR_0, BTreeSet::new()),
+                (*ADDR_A, BTreeSet::new()),
+                (*ADDR_B, BTreeSet::from_iter([Word::from(2), Word::from(3)]))
             ]),
-            code: HashSet::from_iter([*ADDR_A, *ADDR_B]),
+            code: BTreeSet::from_iter([*ADDR_A, *ADDR_B]),
+            ..Default::default()
         }
-    )
+    );
+
+    // the access lists of the txs, from the accesses of the whole block: the
+    // callees are warm, but not their storage
+    let access_set =
+        AccessSet::from(get_state_accesses(&block.eth_block, &block.geth_traces).unwrap());
+    let access_list = AccessList(vec![AccessListItem {
+        address: *ADDR_B,
+        storage_keys: vec![H256::from_low_u64_be(2), H256::from_low_u64_be(3)],
+    }]);
+    assert_eq!(access_set.to_eip2930_access_list(0), access_list);
+    assert_eq!(access_set.to_eip2930_access_list(1), access_list);
+    assert_eq!(access_set.to_eip2930_access_list(2), AccessList::default());
 }
 
 #[test]
@@ -2143,14 +2268,15 @@ fn test_gen_access_trace_call_EOA_no_new_stack_frame() {
     assert_eq!(
         access_set,
         AccessSet {
-            state: HashMap::from_iter([
+            state: BTreeMap::from_iter([
                 (
                     *MOCK_COINBASE,
-                    HashSet::from_iter([Word::from(2u64), Word::from(3u64)])
+                    BTreeSet::from_iter([Word::from(2u64), Word::from(3u64)])
                 ),
-                (*ADDR_B, HashSet::new()),
+                (*ADDR_B, BTreeSet::new()),
             ]),
-            code: HashSet::from_iter([*ADDR_B, *MOCK_COINBASE]),
+            code: BTreeSet::from_iter([*ADDR_B, *MOCK_COINBASE]),
+            ..Default::default()
         }
     );
 }
@@ -2271,12 +2397,38 @@ fn test_gen_access_trace_create_push_call_stack() {
     assert_eq!(
         access_set,
         AccessSet {
-            state: HashMap::from_iter([
-                (*MOCK_COINBASE, HashSet::new()),
-                (*ADDR_A, HashSet::new()),
-                (*ADDR_B, HashSet::new()),
+            state: BTreeMap::from_iter([
+                (*MOCK_COINBASE, BTreeSet::new()),
+                (*ADDR_A, BTreeSet::new()),
+                (*ADDR_B, BTreeSet::new()),
             ]),
-            code: HashSet::from_iter([*MOCK_COINBASE, *ADDR_B]),
+            code: BTreeSet::from_iter([*MOCK_COINBASE, *ADDR_B]),
+            ..Default::default()
         }
     )
 }
+
+#[test]
+fn merge_proofs_storage_hash_mismatch() {
+    let proof = |storage_hash: H256, key: u64| eth_types::EIP1186ProofResponse {
+        address: *ADDR_A,
+        storage_hash,
+        storage_proof: vec![eth_types::StorageProof {
+            key: Word::from(key),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let mut proofs = vec![proof(H256::repeat_byte(1), 1)];
+
+    merge_proofs(&mut proofs, vec![proof(H256::repeat_byte(1), 2)]).unwrap();
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(proofs[0].storage_proof.len(), 2);
+
+    // the storage of the account was written between the two proofs
+    assert!(matches!(
+        merge_proofs(&mut proofs, vec![proof(H256::repeat_byte(2), 3)]),
+        Err(Error::InvalidStorageProof(address, key)) if address == *ADDR_A && key == Word::from(3)
+    ));
+    assert_eq!(proofs[0].storage_proof.len(), 2);
+}
@@ -3,12 +3,16 @@
 use std::collections::BTreeMap;
 
 use eth_types::{
-    evm_types::{gas_utils::tx_data_gas_cost, Memory},
+    evm_types::Memory,
     geth_types,
-    geth_types::{get_rlp_unsigned, TxType},
-    Address, GethExecTrace, Signature, Word, H256,
+    geth_types::{
+        get_blob_versioned_hashes, get_max_fee_per_blob_gas, get_rlp_signed, get_rlp_unsigned,
+        TxType,
+    },
+    Address, Signature, Word, H256,
 };
 use ethers_core::utils::get_contract_address;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     l2_predeployed::l1_gas_price_oracle,
@@ -16,12 +20,17 @@ use crate::{
     Error,
 };
 
-use super::{call::ReversionGroup, Call, CallContext, CallKind, CodeSource, ExecStep};
+use super::{
+    call::ReversionGroup, BytesView, Call, CallContext, CallKind, CodeSource, ExecStep,
+    L1FeeHardfork, TraceSource,
+};
 
 /// Precision of transaction L1 fee
 pub const TX_L1_FEE_PRECISION: u64 = 1_000_000_000;
 /// Extra cost as the bytes of rlped tx commited to L1 (assume to non-zero, overestimated a bit)
 pub const TX_L1_COMMIT_EXTRA_COST: u64 = 64;
+/// Bytes of the length prefix of a rlped tx in the DA payload of a chunk
+pub const TX_DA_LENGTH_PREFIX_BYTES: usize = 4;
 
 #[derive(Debug, Default)]
 /// Context of a [`Transaction`] which can mutate in an [`ExecStep`].
@@ -50,7 +59,7 @@ impl TransactionContext {
     /// Create a new Self.
     pub fn new(
         eth_tx: &eth_types::Transaction,
-        geth_trace: &GethExecTrace,
+        geth_trace: &dyn TraceSource,
         is_last_tx: bool,
     ) -> Result<Self, Error> {
         // Iterate over geth_trace to inspect and collect each call's is_success, which
@@ -58,8 +67,9 @@ impl TransactionContext {
         let call_is_success = {
             let mut call_is_success_map = BTreeMap::new();
             let mut call_indices = Vec::new();
-            for (index, geth_step) in geth_trace.struct_logs.iter().enumerate() {
-                if let Some(geth_next_step) = geth_trace.struct_logs.get(index + 1) {
+            geth_trace.try_for_each_step(&mut |index, geth_steps| {
+                let geth_step = &geth_steps[0];
+                if let Some(geth_next_step) = geth_steps.get(1) {
                     // Dive into call
                     if geth_step.depth + 1 == geth_next_step.depth {
                         call_indices.push(index);
@@ -73,9 +83,10 @@ impl TransactionContext {
                         call_is_success_map.insert(index, is_success);
                     }
                 }
-            }
+                Ok(())
+            })?;
 
-            std::iter::once(!geth_trace.failed)
+            std::iter::once(!geth_trace.failed())
                 .chain(call_is_success_map.into_values())
                 .collect()
         };
@@ -91,9 +102,9 @@ impl TransactionContext {
             call_is_success,
             calls: Vec::new(),
             reversion_groups: Vec::new(),
-            l1_fee: geth_trace.l1_fee,
+            l1_fee: geth_trace.l1_fee(),
         };
-        tx_ctx.push_call_ctx(0, eth_tx.input.to_vec());
+        tx_ctx.push_call_ctx(0, BytesView::new(eth_tx.input.clone(), 0));
 
         Ok(tx_ctx)
     }
@@ -146,7 +157,7 @@ impl TransactionContext {
     }
 
     /// Push a new call context and its index into the call stack.
-    pub(crate) fn push_call_ctx(&mut self, call_idx: usize, call_data: Vec<u8>) {
+    pub(crate) fn push_call_ctx(&mut self, call_idx: usize, call_data: BytesView) {
         if !self.call_is_success[call_idx] {
             self.reversion_groups
                 .push(ReversionGroup::new(vec![(call_idx, 0)], Vec::new()))
@@ -174,7 +185,7 @@ impl TransactionContext {
             reversible_write_counter: 0,
             call_data,
             memory: Memory::default(),
-            return_data: vec![],
+            return_data: BytesView::default(),
         });
     }
 
@@ -190,7 +201,7 @@ impl TransactionContext {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// Result of the parsing of an Ethereum Transaction.
 pub struct Transaction {
     /// ..
@@ -209,6 +220,10 @@ pub struct Transaction {
     pub gas_fee_cap: Word,
     /// Gas tip cap
     pub gas_tip_cap: Word,
+    /// Max fee per blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Word,
+    /// Blob versioned hashes (EIP-4844)
+    pub blob_versioned_hashes: Vec<H256>,
     /// From / Caller Address
     pub from: Address,
     /// To / Callee Address
@@ -229,6 +244,9 @@ pub struct Transaction {
     pub l1_fee: TxL1Fee,
     /// Committed values of L1 fee
     pub l1_fee_committed: TxL1Fee,
+    /// Whether `from` was provided by the caller instead of recovered from
+    /// the signature of an unsigned tx.
+    pub unverified_sender: bool,
     /// Calls made in the transaction
     pub(crate) calls: Vec<Call>,
     /// Execution steps
@@ -238,6 +256,7 @@ pub struct Transaction {
 impl From<&Transaction> for geth_types::Transaction {
     fn from(tx: &Transaction) -> geth_types::Transaction {
         geth_types::Transaction {
+            tx_type: tx.tx_type,
             hash: tx.hash,
             from: tx.from,
             to: tx.to,
@@ -251,6 +270,8 @@ impl From<&Transaction> for geth_types::Transaction {
             s: tx.signature.s,
             gas_fee_cap: tx.gas_fee_cap,
             gas_tip_cap: tx.gas_tip_cap,
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas,
+            blob_versioned_hashes: tx.blob_versioned_hashes.clone(),
             rlp_unsigned_bytes: tx.rlp_unsigned_bytes.clone(),
             rlp_bytes: tx.rlp_bytes.clone(),
             ..Default::default()
@@ -267,6 +288,8 @@ impl Transaction {
             gas_price: Word::zero(),
             gas_fee_cap: Word::zero(),
             gas_tip_cap: Word::zero(),
+            max_fee_per_blob_gas: Word::zero(),
+            blob_versioned_hashes: Vec::new(),
             from: Address::zero(),
             to: Some(Address::zero()), // or use None?
             value: Word::zero(),
@@ -286,6 +309,7 @@ impl Transaction {
             tx_type: Default::default(),
             l1_fee: Default::default(),
             l1_fee_committed: Default::default(),
+            unverified_sender: false,
         }
     }
 
@@ -296,19 +320,20 @@ impl Transaction {
         code_db: &mut CodeDB,
         eth_tx: &eth_types::Transaction,
         is_success: bool,
+        l1_fee_hardfork: L1FeeHardfork,
     ) -> Result<Self, Error> {
         let (found, _) = sdb.get_account(&eth_tx.from);
         if !found {
             return Err(Error::AccountNotFound(eth_tx.from));
         }
+        if eth_tx.nonce >= u64::MAX.into() {
+            return Err(Error::TxNonceOverflow(eth_tx.hash));
+        }
 
         let call = if let Some(address) = eth_tx.to {
-            // Contract Call / Transfer
-            let (found, account) = sdb.get_account(&address);
-            if !found {
-                return Err(Error::AccountNotFound(address));
-            }
-            let code_hash = account.code_hash;
+            // Contract Call / Transfer, to an account which may not exist, e.g.
+            // an empty account deleted by a previous tx (EIP-161)
+            let code_hash = sdb.get_account(&address).1.code_hash;
             Call {
                 call_id,
                 kind: CallKind::Call,
@@ -326,7 +351,7 @@ impl Transaction {
             }
         } else {
             // Contract creation
-            let code_hash = code_db.insert(eth_tx.input.to_vec());
+            let code_hash = code_db.insert_slice(&eth_tx.input);
             let address = get_contract_address(eth_tx.from, eth_tx.nonce);
             Call {
                 call_id,
@@ -357,8 +382,8 @@ impl Transaction {
             }
         );
 
-        let l1_fee = TxL1Fee::get_current_values_from_state_db(sdb);
-        let l1_fee_committed = TxL1Fee::get_committed_values_from_state_db(sdb);
+        let l1_fee = TxL1Fee::get_current_values_from_state_db(sdb, l1_fee_hardfork);
+        let l1_fee_committed = TxL1Fee::get_committed_values_from_state_db(sdb, l1_fee_hardfork);
 
         log::debug!(
             "l1_fee: {:?}, l1_fee_committed: {:?}",
@@ -370,13 +395,15 @@ impl Transaction {
             block_num: eth_tx.block_number.unwrap().as_u64(),
             hash: eth_tx.hash,
             tx_type: TxType::get_tx_type(eth_tx),
-            rlp_bytes: eth_tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(eth_tx),
             rlp_unsigned_bytes: get_rlp_unsigned(eth_tx),
             nonce: eth_tx.nonce.as_u64(),
             gas: eth_tx.gas.as_u64(),
             gas_price: eth_tx.gas_price.unwrap_or_default(),
             gas_fee_cap: eth_tx.max_fee_per_gas.unwrap_or_default(),
             gas_tip_cap: eth_tx.max_priority_fee_per_gas.unwrap_or_default(),
+            max_fee_per_blob_gas: get_max_fee_per_blob_gas(eth_tx),
+            blob_versioned_hashes: get_blob_versioned_hashes(eth_tx),
             from: eth_tx.from,
             to: eth_tx.to,
             value: eth_tx.value,
@@ -391,6 +418,7 @@ impl Transaction {
             },
             l1_fee,
             l1_fee_committed,
+            unverified_sender: false,
         })
     }
 
@@ -439,27 +467,52 @@ impl Transaction {
         self.steps.is_empty()
     }
 
-    /// Calculate L1 fee of this transaction.
+    /// Calculate L1 fee of this transaction, with the formula of the
+    /// hardfork of its block.
     pub fn l1_fee(&self) -> u64 {
-        let tx_data_gas_cost = tx_data_gas_cost(&self.rlp_bytes);
+        self.l1_fee
+            .hardfork
+            .oracle()
+            .tx_l1_fee(&self.l1_fee, &self.rlp_bytes)
+            .0
+    }
 
-        self.l1_fee.tx_l1_fee(tx_data_gas_cost).0
+    /// Bytes this transaction adds to the DA payload of its chunk: the
+    /// length-prefixed rlped tx. L1 msgs are already on L1 and system calls
+    /// are made by the chain itself, so they add nothing. The payload isn't
+    /// compressed, so this is the exact contribution.
+    pub fn rollup_data_size_estimate(&self) -> usize {
+        if self.tx_type.is_unsigned() {
+            0
+        } else {
+            TX_DA_LENGTH_PREFIX_BYTES + self.rlp_bytes.len()
+        }
     }
 }
 
 /// Transaction L1 fee for L1GasPriceOracle contract
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TxL1Fee {
+    /// Hardfork whose formula the fee is charged with
+    pub hardfork: L1FeeHardfork,
     /// L1 base fee
     pub base_fee: u64,
-    /// L1 fee overhead
+    /// L1 fee overhead, charged before Curie
     pub fee_overhead: u64,
-    /// L1 fee scalar
+    /// L1 fee scalar, charged before Curie
     pub fee_scalar: u64,
+    /// L1 blob base fee, since Curie
+    pub blob_base_fee: u64,
+    /// L1 commit scalar, since Curie
+    pub commit_scalar: u64,
+    /// L1 blob scalar, since Curie
+    pub blob_scalar: u64,
 }
 
 impl TxL1Fee {
-    /// Calculate L1 fee and remainder of transaction.
+    /// Calculate L1 fee and remainder of transaction with the formula before
+    /// Curie, see [`L1GasPriceOracle`](super::L1GasPriceOracle) for the
+    /// formula of each hardfork.
     pub fn tx_l1_fee(&self, tx_data_gas_cost: u64) -> (u64, u64) {
         // <https://github.com/scroll-tech/go-ethereum/blob/49192260a177f1b63fc5ea3b872fb904f396260c/rollup/fees/rollup_fee.go#L118>
         let tx_l1_gas = tx_data_gas_cost + self.fee_overhead + TX_L1_COMMIT_EXTRA_COST;
@@ -471,41 +524,34 @@ impl TxL1Fee {
         )
     }
 
-    fn get_current_values_from_state_db(sdb: &StateDB) -> Self {
-        let [base_fee, fee_overhead, fee_scalar] = [
-            &l1_gas_price_oracle::BASE_FEE_SLOT,
-            &l1_gas_price_oracle::OVERHEAD_SLOT,
-            &l1_gas_price_oracle::SCALAR_SLOT,
-        ]
-        .map(|slot| {
-            sdb.get_storage(&l1_gas_price_oracle::ADDRESS, slot)
-                .1
-                .as_u64()
-        });
-
-        Self {
-            base_fee,
-            fee_overhead,
-            fee_scalar,
-        }
+    fn get_current_values_from_state_db(sdb: &StateDB, hardfork: L1FeeHardfork) -> Self {
+        hardfork
+            .oracle()
+            .read_params(&|slot| *sdb.get_storage(&l1_gas_price_oracle::ADDRESS, slot).1)
     }
 
-    fn get_committed_values_from_state_db(sdb: &StateDB) -> Self {
-        let [base_fee, fee_overhead, fee_scalar] = [
-            &l1_gas_price_oracle::BASE_FEE_SLOT,
-            &l1_gas_price_oracle::OVERHEAD_SLOT,
-            &l1_gas_price_oracle::SCALAR_SLOT,
-        ]
-        .map(|slot| {
-            sdb.get_committed_storage(&l1_gas_price_oracle::ADDRESS, slot)
+    fn get_committed_values_from_state_db(sdb: &StateDB, hardfork: L1FeeHardfork) -> Self {
+        hardfork.oracle().read_params(&|slot| {
+            *sdb.get_committed_storage(&l1_gas_price_oracle::ADDRESS, slot)
                 .1
-                .as_u64()
-        });
+        })
+    }
+}
 
-        Self {
-            base_fee,
-            fee_overhead,
-            fee_scalar,
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rollup_data_size_estimate() {
+        let mut tx = Transaction::dummy();
+        tx.rlp_bytes = vec![0xf8; 100];
+        assert_eq!(tx.rollup_data_size_estimate(), 104);
+
+        tx.tx_type = TxType::L1Msg;
+        assert_eq!(tx.rollup_data_size_estimate(), 0);
+
+        tx.tx_type = TxType::SystemCall;
+        assert_eq!(tx.rollup_data_size_estimate(), 0);
     }
 }
@@ -0,0 +1,256 @@
+//! Allowed transitions between consecutive execution steps.
+//!
+//! The EVM circuit constrains which `ExecutionState` may follow another one.
+//! This module encodes the same rules on [`ExecStep`]s so that an invalid
+//! step sequence is caught during witness generation instead of as an
+//! unsatisfied gate, and exports the transition graph for auditing.
+
+use eth_types::evm_types::OpcodeId;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use super::{ExecState, ExecStep};
+use crate::{error::ExecError, Error};
+
+/// Node of the execution step transition graph. Opcodes are grouped by their
+/// effect on the control flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+pub enum TransitionNode {
+    /// Virtual step Begin Tx
+    BeginTx,
+    /// Opcode which neither calls nor halts
+    Op,
+    /// CALL, CALLCODE, DELEGATECALL or STATICCALL without error, which may be
+    /// followed by a precompile step
+    CallOp,
+    /// Halting opcode, or opcode which fails with an error halting the call
+    HaltOp,
+    /// Precompile call
+    Precompile,
+    /// Virtual step deleting an account self destructed in the tx
+    DestructAccount,
+    /// Virtual step End Tx
+    EndTx,
+    /// Virtual step End Inner Block
+    EndInnerBlock,
+    /// Virtual step End Block
+    EndBlock,
+}
+
+impl TransitionNode {
+    /// Returns the node of an execution step.
+    pub fn of(step: &ExecStep) -> Self {
+        match step.exec_state {
+            ExecState::BeginTx => Self::BeginTx,
+            ExecState::DestructAccount => Self::DestructAccount,
+            ExecState::EndTx => Self::EndTx,
+            ExecState::EndInnerBlock => Self::EndInnerBlock,
+            ExecState::EndBlock => Self::EndBlock,
+            ExecState::Precompile(_) => Self::Precompile,
+            ExecState::Op(op) => {
+                if step.error.as_ref().map_or(false, ExecError::halts) || Self::is_halting_op(op) {
+                    Self::HaltOp
+                } else if matches!(
+                    op,
+                    OpcodeId::CALL
+                        | OpcodeId::CALLCODE
+                        | OpcodeId::DELEGATECALL
+                        | OpcodeId::STATICCALL
+                ) {
+                    Self::CallOp
+                } else {
+                    Self::Op
+                }
+            }
+        }
+    }
+
+    fn is_halting_op(op: OpcodeId) -> bool {
+        matches!(
+            op,
+            OpcodeId::STOP
+                | OpcodeId::RETURN
+                | OpcodeId::REVERT
+                | OpcodeId::SELFDESTRUCT
+                | OpcodeId::INVALID(_)
+        )
+    }
+
+    /// Nodes which may follow this node.
+    pub fn successors(&self) -> &'static [TransitionNode] {
+        use TransitionNode::*;
+        match self {
+            // A tx without code (transfer, creation with empty init code or
+            // call to a precompile) goes to EndTx directly.
+            BeginTx => &[Op, CallOp, HaltOp, EndTx],
+            Op => &[Op, CallOp, HaltOp],
+            CallOp => &[Op, CallOp, HaltOp, Precompile],
+            // The caller continues after a precompile call.
+            Precompile => &[Op, CallOp, HaltOp],
+            // Only the root call halting ends the tx, after the deletion of
+            // the accounts self destructed in it.
+            HaltOp => &[Op, CallOp, HaltOp, DestructAccount, EndTx],
+            DestructAccount => &[DestructAccount, EndTx],
+            EndTx => &[BeginTx, EndInnerBlock, EndBlock],
+            EndInnerBlock => &[BeginTx, EndInnerBlock, EndBlock],
+            EndBlock => &[EndBlock],
+        }
+    }
+
+    /// Returns `true` if `next` may follow this node.
+    pub fn can_transit_to(&self, next: &TransitionNode) -> bool {
+        self.successors().contains(next)
+    }
+}
+
+/// Check that `next` may follow `prev`.
+pub fn check_transition(prev: &ExecStep, next: &ExecStep) -> Result<(), Error> {
+    if TransitionNode::of(prev).can_transit_to(&TransitionNode::of(next)) {
+        Ok(())
+    } else {
+        log::error!(
+            "invalid exec state transition {:?} (error {:?}) -> {:?} (error {:?})",
+            prev.exec_state,
+            prev.error,
+            next.exec_state,
+            next.error
+        );
+        Err(Error::InvalidExecStateTransition(
+            prev.exec_state.clone(),
+            next.exec_state.clone(),
+        ))
+    }
+}
+
+/// Check the steps of a transaction: it starts with BeginTx, ends with EndTx
+/// and every step may follow the previous one.
+pub fn check_tx_transitions(steps: &[ExecStep]) -> Result<(), Error> {
+    let (first, last) = match (steps.first(), steps.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(Error::InternalError("tx has no steps")),
+    };
+    if first.exec_state != ExecState::BeginTx {
+        return Err(Error::InternalError("first step of tx is not BeginTx"));
+    }
+    if last.exec_state != ExecState::EndTx {
+        return Err(Error::InternalError("last step of tx is not EndTx"));
+    }
+    steps
+        .iter()
+        .zip(steps.iter().skip(1))
+        .try_for_each(|(prev, next)| check_transition(prev, next))
+}
+
+/// Export the transition graph in graphviz dot format.
+pub fn transition_graph_dot() -> String {
+    let mut dot = String::from("digraph exec_state {\n");
+    for node in TransitionNode::iter() {
+        for next in node.successors() {
+            dot.push_str(&format!("    {node:?} -> {next:?};\n"));
+        }
+    }
+    dot.push('}');
+    dot.push('\n');
+    dot
+}
+
+/// Export the transition graph as a JSON object mapping each node to its
+/// successors.
+pub fn transition_graph_json() -> serde_json::Value {
+    serde_json::Value::Object(
+        TransitionNode::iter()
+            .map(|node| {
+                (
+                    format!("{node:?}"),
+                    node.successors()
+                        .iter()
+                        .map(|next| serde_json::Value::String(format!("{next:?}")))
+                        .collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::precompile::PrecompileCalls;
+
+    fn step(exec_state: ExecState) -> ExecStep {
+        ExecStep {
+            exec_state,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn valid_transitions() {
+        let steps = [
+            step(ExecState::BeginTx),
+            step(ExecState::Op(OpcodeId::PUSH1)),
+            step(ExecState::Op(OpcodeId::STATICCALL)),
+            step(ExecState::Precompile(PrecompileCalls::Identity)),
+            step(ExecState::Op(OpcodeId::POP)),
+            step(ExecState::Op(OpcodeId::STOP)),
+            step(ExecState::EndTx),
+        ];
+        assert!(check_tx_transitions(&steps).is_ok());
+        let steps = [
+            step(ExecState::BeginTx),
+            step(ExecState::Op(OpcodeId::SELFDESTRUCT)),
+            step(ExecState::DestructAccount),
+            step(ExecState::DestructAccount),
+            step(ExecState::EndTx),
+        ];
+        assert!(check_tx_transitions(&steps).is_ok());
+        assert!(check_tx_transitions(&[step(ExecState::BeginTx), step(ExecState::EndTx)]).is_ok());
+    }
+
+    #[test]
+    fn invalid_transitions() {
+        // precompile must return to its caller
+        let steps = [
+            step(ExecState::BeginTx),
+            step(ExecState::Op(OpcodeId::CALL)),
+            step(ExecState::Precompile(PrecompileCalls::Identity)),
+            step(ExecState::EndTx),
+        ];
+        assert!(check_tx_transitions(&steps).is_err());
+        // non-halting opcode can't end the tx
+        let steps = [
+            step(ExecState::BeginTx),
+            step(ExecState::Op(OpcodeId::ADD)),
+            step(ExecState::EndTx),
+        ];
+        assert!(check_tx_transitions(&steps).is_err());
+        // accounts are only deleted once the tx halted
+        let steps = [
+            step(ExecState::BeginTx),
+            step(ExecState::Op(OpcodeId::SELFDESTRUCT)),
+            step(ExecState::DestructAccount),
+            step(ExecState::Op(OpcodeId::ADD)),
+            step(ExecState::Op(OpcodeId::STOP)),
+            step(ExecState::EndTx),
+        ];
+        assert!(check_tx_transitions(&steps).is_err());
+        // precompile step only follows a call
+        assert!(check_transition(
+            &step(ExecState::Op(OpcodeId::ADD)),
+            &step(ExecState::Precompile(PrecompileCalls::Identity))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn graph_export() {
+        let dot = transition_graph_dot();
+        assert!(dot.contains("CallOp -> Precompile;"));
+        assert!(!dot.contains("    Op -> Precompile;"));
+        let json = transition_graph_json();
+        assert_eq!(
+            json["EndTx"],
+            serde_json::json!(["BeginTx", "EndInnerBlock", "EndBlock"])
+        );
+    }
+}
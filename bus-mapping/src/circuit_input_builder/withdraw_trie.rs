@@ -0,0 +1,329 @@
+//! Recomputation of the withdraw trie, the append-only keccak merkle tree of
+//! the messages sent to L1, whose root is stored in the L2MessageQueue.
+
+use super::CircuitInputBuilder;
+use crate::{
+    l2_predeployed::message_queue::{
+        ADDRESS as MESSAGE_QUEUE, BRANCHES_SLOT, NEXT_MESSAGE_INDEX_SLOT, WITHDRAW_TRIE_ROOT_SLOT,
+    },
+    operation::TxLogField,
+    state_db::StateDB,
+};
+use eth_types::{Address, ToAddress, ToBigEndian, Word, H256, U256};
+use ethers_core::utils::keccak256;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, iter};
+use zkevm_constants::predeployed::WITHDRAW_TRIE_HEIGHT;
+
+/// Topic of the `SentMessage(address indexed sender, address indexed target,
+/// uint256 value, uint256 messageNonce, uint256 gasLimit, bytes message)`
+/// event of the L2ScrollMessenger.
+pub static SENT_MESSAGE_TOPIC: Lazy<H256> = Lazy::new(|| {
+    H256(keccak256(
+        "SentMessage(address,address,uint256,uint256,uint256,bytes)",
+    ))
+});
+
+/// Selector of `relayMessage(address,address,uint256,uint256,bytes)`, whose
+/// calldata is the preimage of the hash of a message.
+static RELAY_MESSAGE_SELECTOR: Lazy<[u8; 4]> = Lazy::new(|| {
+    let hash = keccak256("relayMessage(address,address,uint256,uint256,bytes)");
+    [hash[0], hash[1], hash[2], hash[3]]
+});
+
+/// Roots of the empty subtrees, by height.
+static ZERO_HASHES: Lazy<Vec<H256>> = Lazy::new(|| {
+    let mut hashes = vec![H256::zero(); WITHDRAW_TRIE_HEIGHT];
+    for height in 1..WITHDRAW_TRIE_HEIGHT {
+        hashes[height] = hash_pair(&hashes[height - 1], &hashes[height - 1]);
+    }
+    hashes
+});
+
+fn hash_pair(left: &H256, right: &H256) -> H256 {
+    H256(keccak256([left.as_bytes(), right.as_bytes()].concat()))
+}
+
+/// The withdraw trie of the L2MessageQueue. As in the contract, only the
+/// branches needed to append the next message are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawTrie {
+    next_index: u64,
+    branches: Vec<H256>,
+    root: H256,
+}
+
+impl Default for WithdrawTrie {
+    fn default() -> Self {
+        Self {
+            next_index: 0,
+            branches: vec![H256::zero(); WITHDRAW_TRIE_HEIGHT],
+            root: H256::zero(),
+        }
+    }
+}
+
+impl WithdrawTrie {
+    /// Load the trie from the storage of the L2MessageQueue in `sdb`. Slots
+    /// missing from `sdb` are read as zero: the branches which are not in
+    /// `sdb` are not read by the contract when it appends the next messages.
+    pub fn from_sdb(sdb: &StateDB) -> Self {
+        let slot = |key: Word| *sdb.get_storage(&MESSAGE_QUEUE, &key).1;
+        Self {
+            next_index: slot(*NEXT_MESSAGE_INDEX_SLOT).low_u64(),
+            branches: (0..WITHDRAW_TRIE_HEIGHT)
+                .map(|height| H256(slot(*BRANCHES_SLOT + U256::from(height)).to_be_bytes()))
+                .collect(),
+            root: H256(slot(*WITHDRAW_TRIE_ROOT_SLOT).to_be_bytes()),
+        }
+    }
+
+    /// Root of the trie
+    pub fn root(&self) -> H256 {
+        self.root
+    }
+
+    /// Index of the next appended message, the number of messages of the trie
+    pub fn next_index(&self) -> u64 {
+        self.next_index
+    }
+
+    /// Append the hash of a message and return the new root, as
+    /// `AppendOnlyMerkleTree._appendMessageHash` does.
+    pub fn append(&mut self, message_hash: H256) -> H256 {
+        let mut index = self.next_index;
+        let mut hash = message_hash;
+        let mut height = 0;
+        while index != 0 {
+            if index % 2 == 0 {
+                // a left child, whose right sibling is empty
+                self.branches[height] = hash;
+                hash = hash_pair(&hash, &ZERO_HASHES[height]);
+            } else {
+                // a right child, whose left sibling is the stored branch
+                hash = hash_pair(&self.branches[height], &hash);
+            }
+            height += 1;
+            index >>= 1;
+        }
+        self.branches[height] = hash;
+        self.root = hash;
+        self.next_index += 1;
+        hash
+    }
+}
+
+/// Hash of the message of a `SentMessage` log, or `None` if the log isn't a
+/// well-formed `SentMessage`.
+pub fn sent_message_hash(topics: &[H256], data: &[u8]) -> Option<H256> {
+    if topics.len() != 3 || topics[0] != *SENT_MESSAGE_TOPIC {
+        return None;
+    }
+    // data := value || messageNonce || gasLimit || offset of message || .. ||
+    //         message length || message
+    let word = |offset: usize| data.get(offset..offset + 32);
+    let (value, nonce) = (word(0)?, word(32)?);
+    let message_offset = U256::from_big_endian(word(96)?);
+    if message_offset > U256::from(data.len()) {
+        return None;
+    }
+    let message_offset = message_offset.as_usize();
+    let message_len = U256::from_big_endian(word(message_offset)?);
+    if message_len > U256::from(data.len() - message_offset - 32) {
+        return None;
+    }
+    let message_len = message_len.as_usize();
+    let message = &data[message_offset + 32..message_offset + 32 + message_len];
+
+    // relayMessage(sender, target, value, messageNonce, message)
+    let preimage = iter::empty()
+        .chain(RELAY_MESSAGE_SELECTOR.iter().copied())
+        .chain(topics[1].to_fixed_bytes())
+        .chain(topics[2].to_fixed_bytes())
+        .chain(value.iter().copied())
+        .chain(nonce.iter().copied())
+        .chain(U256::from(5 * 32).to_be_bytes())
+        .chain(U256::from(message_len).to_be_bytes())
+        .chain(message.iter().copied())
+        .chain(iter::repeat(0).take((32 - message_len % 32) % 32))
+        .collect::<Vec<u8>>();
+    Some(H256(keccak256(preimage)))
+}
+
+impl CircuitInputBuilder {
+    /// Hashes of the messages sent to L1 by the handled txs, from the
+    /// `SentMessage` logs of the L2ScrollMessenger of the chain spec, in
+    /// order. Only the logs of persistent calls are kept in the operation
+    /// container.
+    pub fn sent_message_hashes(&self) -> Vec<H256> {
+        let mut logs = BTreeMap::<(usize, usize), (Address, Vec<H256>, Vec<u8>)>::new();
+        for op in self.block.container.tx_log.iter().map(|op| op.op()) {
+            let (address, topics, data) = logs.entry((op.tx_id, op.log_id)).or_default();
+            match op.field {
+                TxLogField::Address => *address = op.value.to_address(),
+                TxLogField::Topic => {
+                    if topics.len() <= op.index {
+                        topics.resize(op.index + 1, H256::zero());
+                    }
+                    topics[op.index] = H256(op.value.to_be_bytes());
+                }
+                // data is written by 32 bytes words, at their byte index
+                TxLogField::Data => {
+                    if data.len() < op.index + 32 {
+                        data.resize(op.index + 32, 0);
+                    }
+                    data[op.index..op.index + 32].copy_from_slice(&op.value.to_be_bytes());
+                }
+            }
+        }
+        logs.values()
+            .filter(|(address, ..)| *address == self.block.chain_spec.l2_messenger)
+            .filter_map(|(_, topics, data)| sent_message_hash(topics, data))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::BlockData, Error};
+    use eth_types::{bytecode, geth_types::GethData, Address, ToWord};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+
+    fn leaf(i: u64) -> H256 {
+        H256::from_low_u64_be(i + 1)
+    }
+
+    #[test]
+    fn withdraw_trie_append() {
+        let mut trie = WithdrawTrie::default();
+        assert_eq!(trie.append(leaf(0)), leaf(0));
+        assert_eq!(trie.append(leaf(1)), hash_pair(&leaf(0), &leaf(1)));
+        assert_eq!(
+            trie.append(leaf(2)),
+            hash_pair(
+                &hash_pair(&leaf(0), &leaf(1)),
+                &hash_pair(&leaf(2), &ZERO_HASHES[0])
+            )
+        );
+        assert_eq!(trie.next_index(), 3);
+    }
+
+    #[test]
+    fn withdraw_trie_from_sdb() {
+        let mut trie = WithdrawTrie::default();
+        for i in 0..5 {
+            trie.append(leaf(i));
+        }
+
+        let mut sdb = StateDB::new();
+        let (_, account) = sdb.get_account_mut(&MESSAGE_QUEUE);
+        account
+            .storage
+            .insert(*NEXT_MESSAGE_INDEX_SLOT, Word::from(trie.next_index()));
+        account
+            .storage
+            .insert(*WITHDRAW_TRIE_ROOT_SLOT, trie.root().to_word());
+        for (height, branch) in trie.branches.iter().enumerate() {
+            account
+                .storage
+                .insert(*BRANCHES_SLOT + U256::from(height), branch.to_word());
+        }
+
+        let mut loaded = WithdrawTrie::from_sdb(&sdb);
+        assert_eq!(loaded, trie);
+        assert_eq!(loaded.append(leaf(5)), trie.append(leaf(5)));
+    }
+
+    #[test]
+    fn sent_message_hash_of_log() {
+        let (sender, target) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+        let message = [0xab; 33];
+        let data = [
+            Word::from(7).to_be_bytes(),  // value
+            Word::from(3).to_be_bytes(),  // messageNonce
+            Word::from(10).to_be_bytes(), // gasLimit
+            Word::from(128).to_be_bytes(),
+            Word::from(message.len()).to_be_bytes(),
+        ]
+        .concat();
+        let data = [data, message.to_vec(), vec![0; 31]].concat();
+        let topics = [*SENT_MESSAGE_TOPIC, sender.into(), target.into()];
+
+        let preimage = [
+            RELAY_MESSAGE_SELECTOR.to_vec(),
+            H256::from(sender).as_bytes().to_vec(),
+            H256::from(target).as_bytes().to_vec(),
+            // value, messageNonce
+            data[..64].to_vec(),
+            Word::from(160).to_be_bytes().to_vec(),
+            // message length, padded message
+            data[128..].to_vec(),
+        ]
+        .concat();
+        assert_eq!(
+            sent_message_hash(&topics, &data),
+            Some(H256(keccak256(preimage)))
+        );
+        assert_eq!(sent_message_hash(&topics[..2], &data), None);
+        assert_eq!(sent_message_hash(&topics, &data[..170]), None);
+    }
+
+    /// A block whose tx makes the contract `MOCK_ACCOUNTS[0]` emit a
+    /// `SentMessage` log, without the matching append to the trie.
+    fn block_with_sent_message() -> GethData {
+        let code = bytecode! {
+            PUSH1(0x80)
+            PUSH1(0x60)
+            MSTORE
+            PUSH1(0x22) // target
+            PUSH1(0x11) // sender
+            PUSH32(SENT_MESSAGE_TOPIC.to_word())
+            PUSH1(0xa0) // size
+            PUSH1(0x00) // offset
+            LOG3
+            STOP
+        };
+        TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn withdraw_root_mismatch() {
+        let block = block_with_sent_message();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec.l2_messenger = MOCK_ACCOUNTS[0];
+        let err = builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap_err();
+
+        assert_eq!(builder.sent_message_hashes().len(), 1);
+        let mut trie = WithdrawTrie::default();
+        let computed = trie.append(builder.sent_message_hashes()[0]);
+        assert!(
+            matches!(err, Error::WithdrawRootMismatch(stored, root) if stored == H256::zero() && root == computed)
+        );
+    }
+
+    #[test]
+    fn sent_message_of_other_contract() {
+        // the log isn't emitted by the L2ScrollMessenger, so it sends nothing
+        let block = block_with_sent_message();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        assert_ne!(builder.block.chain_spec.l2_messenger, MOCK_ACCOUNTS[0]);
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        assert!(builder.sent_message_hashes().is_empty());
+    }
+}
@@ -3,11 +3,16 @@
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use eth_types::{evm_types::OpcodeId, Address, GethExecStep, Word, H256};
 use ethers_providers::ProviderError;
+use serde::{Deserialize, Serialize};
 use std::error::Error as StdError;
 
-use crate::geth_errors::{
-    GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
-    GETH_ERR_STACK_UNDERFLOW,
+use crate::{
+    circuit_input_builder::{ChunkLayout, ExecState},
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_OUT_OF_GAS, GETH_ERR_STACK_OVERFLOW,
+        GETH_ERR_STACK_UNDERFLOW,
+    },
+    precompile::PrecompileCalls,
 };
 
 /// Error type for any BusMapping related failure.
@@ -15,6 +20,8 @@ use crate::geth_errors::{
 pub enum Error {
     /// Serde de/serialization error.
     SerdeError(serde_json::error::Error),
+    /// IO error, e.g. reading or writing a block bundle.
+    IoError(std::io::Error),
     /// JSON-RPC related error.
     JSONRpcError(ProviderError),
     /// OpcodeId is not a call type.
@@ -40,6 +47,247 @@ pub enum Error {
     ExecutionError(ExecError),
     /// Internal Code error
     InternalError(&'static str),
+    /// An execution step can't follow the previous one.
+    InvalidExecStateTransition(ExecState, ExecState),
+    /// Code hash stored in the CodeDB, or in the account at the given
+    /// address, doesn't match the hash of the code: (address, stored hash,
+    /// computed hash).
+    CodeHashMismatch(Option<Address>, H256, H256),
+    /// Nonce of the tx has the max value 2^64 - 1 and can't be increased
+    /// (EIP-2681).
+    TxNonceOverflow(H256),
+    /// Init code of the creation tx exceeds the init code size limit of the
+    /// chain (EIP-3860).
+    InitCodeSizeExceeded(H256),
+    /// Account proof of the given address doesn't match the state root.
+    InvalidAccountProof(Address),
+    /// Storage proof of the given address and slot doesn't match the storage
+    /// root of the account.
+    InvalidStorageProof(Address, Word),
+    /// Accounts (with key `None`) and storage slots accessed by the block
+    /// without a proof from the node.
+    MissingProofs(Vec<(Address, Option<Word>)>),
+    /// Txs of the block with an invalid signature, or a signature which
+    /// doesn't recover the sender: (tx hash, reason).
+    InvalidTxSignatures(Vec<(H256, String)>),
+    /// The block needs sub-circuits left out of the chunk layout:
+    /// (layout, required layout).
+    UnsupportedChunkLayout(ChunkLayout, ChunkLayout),
+    /// Inputs of a forced-exit proof are malformed.
+    InvalidForcedExit(&'static str),
+    /// Circuit parameters which the circuits can't be built with.
+    InvalidCircuitsParams(String),
+    /// The block doesn't fit in a capacity of the circuits.
+    CircuitCapacityExceeded(CircuitCapacityError),
+    /// Withdraw root stored in the L2MessageQueue doesn't match the root
+    /// recomputed from the `SentMessage` logs: (stored root, computed root).
+    WithdrawRootMismatch(H256, H256),
+    /// State root of the MPT updates of a state transition doesn't match the
+    /// expected one: (expected root, computed root).
+    StateRootMismatch(H256, H256),
+    /// Receipt of a tx from the node doesn't match the result of the tx in
+    /// the builder.
+    ReceiptMismatch(ReceiptMismatch),
+    /// Opcode which the circuits can't prove yet.
+    UnsupportedOpcode(OpcodeId),
+    /// Successful call to a precompile which the circuits can't prove yet.
+    UnsupportedPrecompile(PrecompileCalls),
+    /// System calls of the chain spec which can't be traced exactly, or a
+    /// block whose txs don't make them where expected.
+    InvalidSystemCall(String),
+}
+
+/// Class of an [`Error`], for callers which react to a failure, e.g. a chunk
+/// proposer which retries with fewer blocks when a capacity is exceeded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The block doesn't fit in a capacity of the circuits.
+    CapacityExceeded,
+    /// The traces, or the state and proofs from the node, are inconsistent.
+    TraceInconsistency,
+    /// The block, a tx or the circuit parameters are invalid or unsupported.
+    InvalidInput,
+    /// Fetching, reading or (de)serializing the inputs failed.
+    Io,
+    /// Bug of the builder.
+    Internal,
+}
+
+impl Error {
+    /// The class of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::CircuitCapacityExceeded(_) => ErrorKind::CapacityExceeded,
+            Error::AccountNotFound(_)
+            | Error::StorageKeyNotFound(..)
+            | Error::AddressNotFound(_)
+            | Error::CodeNotFound(_)
+            | Error::UnexpectedExecStepError(..)
+            | Error::InvalidGethExecTrace(_)
+            | Error::InvalidGethExecStep(..)
+            | Error::ExecutionError(_)
+            | Error::CodeHashMismatch(..)
+            | Error::InvalidAccountProof(_)
+            | Error::InvalidStorageProof(..)
+            | Error::MissingProofs(_)
+            | Error::WithdrawRootMismatch(..)
+            | Error::StateRootMismatch(..)
+            | Error::ReceiptMismatch(_) => ErrorKind::TraceInconsistency,
+            Error::EthTypeError(err) => match err {
+                eth_types::Error::SerdeError(_) | eth_types::Error::TracingError(_) => {
+                    ErrorKind::Io
+                }
+                eth_types::Error::IncompleteBlock | eth_types::Error::Signature(_) => {
+                    ErrorKind::InvalidInput
+                }
+                _ => ErrorKind::TraceInconsistency,
+            },
+            Error::TxNonceOverflow(_)
+            | Error::InitCodeSizeExceeded(_)
+            | Error::InvalidTxSignatures(_)
+            | Error::UnsupportedChunkLayout(..)
+            | Error::InvalidForcedExit(_)
+            | Error::InvalidCircuitsParams(_)
+            | Error::UnsupportedOpcode(_)
+            | Error::UnsupportedPrecompile(_)
+            | Error::InvalidSystemCall(_) => ErrorKind::InvalidInput,
+            Error::SerdeError(_) | Error::IoError(_) | Error::JSONRpcError(_) => ErrorKind::Io,
+            Error::OpcodeIdNotCallType
+            | Error::InternalError(_)
+            | Error::InvalidExecStateTransition(..) => ErrorKind::Internal,
+        }
+    }
+}
+
+/// Sub-circuit capacity of the
+/// [`CircuitsParams`](crate::circuit_input_builder::CircuitsParams).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubCircuit {
+    /// Rw operations of the State circuit, `max_rws`
+    State,
+    /// Txs of the Tx circuit, `max_txs`
+    Tx,
+    /// Calldata bytes of the Tx circuit, `max_calldata`
+    TxCalldata,
+    /// Rows of the Copy circuit, `max_copy_rows`
+    Copy,
+    /// Steps of the Exp circuit, `max_exp_steps`
+    Exp,
+    /// Bytes of the Bytecode circuit, `max_bytecode`
+    Bytecode,
+    /// Rows of the EVM circuit, `max_evm_rows`
+    Evm,
+    /// Rows of the Keccak circuit, `max_keccak_rows`
+    Keccak,
+    /// EcAdd ops of the Ecc circuit, `max_ec_ops.ec_add`
+    EcAdd,
+    /// EcMul ops of the Ecc circuit, `max_ec_ops.ec_mul`
+    EcMul,
+    /// EcPairing ops of the Ecc circuit, `max_ec_ops.ec_pairing`
+    EcPairing,
+    /// Rows of the ModExp circuit, `max_modexp_rows`
+    ModExp,
+    /// Inner blocks of the PI circuit, `max_inner_blocks`
+    InnerBlocks,
+    /// Rows of the RLP circuit, `max_rlp_rows`
+    Rlp,
+    /// Rows of the MPT circuit, `max_mpt_rows`
+    Mpt,
+}
+
+impl SubCircuit {
+    /// Name of the capacity in the `CircuitsParams`.
+    pub fn param_name(&self) -> &'static str {
+        match self {
+            SubCircuit::State => "max_rws",
+            SubCircuit::Tx => "max_txs",
+            SubCircuit::TxCalldata => "max_calldata",
+            SubCircuit::Copy => "max_copy_rows",
+            SubCircuit::Exp => "max_exp_steps",
+            SubCircuit::Bytecode => "max_bytecode",
+            SubCircuit::Evm => "max_evm_rows",
+            SubCircuit::Keccak => "max_keccak_rows",
+            SubCircuit::EcAdd => "max_ec_ops.ec_add",
+            SubCircuit::EcMul => "max_ec_ops.ec_mul",
+            SubCircuit::EcPairing => "max_ec_ops.ec_pairing",
+            SubCircuit::ModExp => "max_modexp_rows",
+            SubCircuit::InnerBlocks => "max_inner_blocks",
+            SubCircuit::Rlp => "max_rlp_rows",
+            SubCircuit::Mpt => "max_mpt_rows",
+        }
+    }
+}
+
+/// A capacity of the circuits is smaller than the usage of the block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitCapacityError {
+    /// The exceeded capacity
+    pub circuit: SubCircuit,
+    /// Usage of the block
+    pub needed: usize,
+    /// Capacity of the circuit
+    pub available: usize,
+}
+
+impl Display for CircuitCapacityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} exceeded: {} needed, {} available",
+            self.circuit.param_name(),
+            self.needed,
+            self.available
+        )
+    }
+}
+
+impl From<CircuitCapacityError> for Error {
+    fn from(err: CircuitCapacityError) -> Self {
+        Error::CircuitCapacityExceeded(err)
+    }
+}
+
+/// Field of a tx receipt checked against the builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReceiptField {
+    /// Gas used by the tx, after the refund
+    GasUsed,
+    /// Status of the tx, 1 on success and 0 on failure
+    Status,
+    /// Number of logs emitted by the tx
+    LogCount,
+    /// Whether the node returned a receipt for the tx, 1 if it did
+    Present,
+}
+
+/// A field of the receipt of a tx from the node differs from the result of
+/// the tx in the builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReceiptMismatch {
+    /// Hash of the tx
+    pub tx_hash: H256,
+    /// The mismatched field
+    pub field: ReceiptField,
+    /// Value in the receipt from the node
+    pub receipt: u64,
+    /// Value computed by the builder
+    pub builder: u64,
+}
+
+impl Display for ReceiptMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{:?} of tx {:?} mismatch: {} in receipt, {} in builder",
+            self.field, self.tx_hash, self.receipt, self.builder
+        )
+    }
+}
+
+impl From<ReceiptMismatch> for Error {
+    fn from(err: ReceiptMismatch) -> Self {
+        Error::ReceiptMismatch(err)
+    }
 }
 
 impl From<eth_types::Error> for Error {
@@ -63,18 +311,15 @@ impl Display for Error {
 impl StdError for Error {}
 
 /// Out of Gas errors by opcode
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OogError {
     /// Out of Gas for opcodes which have non-zero constant gas cost
     Constant,
-    /// Out of Gas for MLOAD, MSTORE, MSTORE8, which have static memory
-    /// expansion gas cost
-    StaticMemoryExpansion,
-    /// Out of Gas for RETURN, REVERT, which have dynamic memory expansion gas
-    /// cost
-    DynamicMemoryExpansion,
+    /// Out of Gas for MLOAD, MSTORE, MSTORE8, RETURN, REVERT, whose dynamic
+    /// gas cost is only the memory expansion
+    MemoryExpansion,
     /// Out of Gas for CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY,
-    /// which copy a specified chunk of memory
+    /// MCOPY, which copy a specified chunk of memory
     MemoryCopy,
     /// Out of Gas for BALANCE, EXTCODESIZE, EXTCODEHASH, which possibly touch
     /// an extra account
@@ -99,7 +344,7 @@ pub enum OogError {
 }
 
 /// Contract address collision errors by opcode/state.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ContractAddressCollisionError {
     /// Contract address collision during CREATE opcode.
     Create,
@@ -108,7 +353,7 @@ pub enum ContractAddressCollisionError {
 }
 
 /// Depth above limit errors by opcode/state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DepthError {
     /// Depth above limit during CALL/CALLCODE/DELEGATECALL/STATICCALL opcode.
     Call,
@@ -119,7 +364,7 @@ pub enum DepthError {
 }
 
 /// Insufficient balance errors by opcode/state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InsufficientBalanceError {
     /// Insufficient balance during CALL/CALLCODE opcode.
     Call,
@@ -130,7 +375,7 @@ pub enum InsufficientBalanceError {
 }
 
 /// Nonce uint overflow errors by opcode/state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NonceUintOverflowError {
     /// Nonce uint overflow during CREATE opcode.
     Create,
@@ -139,7 +384,7 @@ pub enum NonceUintOverflowError {
 }
 
 /// EVM Execution Error
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExecError {
     /// Invalid Opcode
     InvalidOpcode,
@@ -175,19 +420,36 @@ pub enum ExecError {
     NonceUintOverflow(NonceUintOverflowError),
 }
 
+impl ExecError {
+    /// Returns `true` if the error halts the current call. The other errors
+    /// make the call (or creation) fail, and the caller continues.
+    pub fn halts(&self) -> bool {
+        !matches!(
+            self,
+            ExecError::Depth(_)
+                | ExecError::InsufficientBalance(_)
+                | ExecError::ContractAddressCollision(_)
+                | ExecError::NonceUintOverflow(_)
+                | ExecError::PrecompileFailed
+        )
+    }
+}
+
 // TODO: Move to impl block.
 pub(crate) fn get_step_reported_error(op: &OpcodeId, error: &str) -> ExecError {
     if [GETH_ERR_OUT_OF_GAS, GETH_ERR_GAS_UINT_OVERFLOW].contains(&error) {
         // NOTE: We report a GasUintOverflow error as an OutOfGas error
         let oog_err = match op {
-            OpcodeId::MLOAD | OpcodeId::MSTORE | OpcodeId::MSTORE8 => {
-                OogError::StaticMemoryExpansion
-            }
-            OpcodeId::RETURN | OpcodeId::REVERT => OogError::DynamicMemoryExpansion,
+            OpcodeId::MLOAD
+            | OpcodeId::MSTORE
+            | OpcodeId::MSTORE8
+            | OpcodeId::RETURN
+            | OpcodeId::REVERT => OogError::MemoryExpansion,
             OpcodeId::CALLDATACOPY
             | OpcodeId::CODECOPY
             | OpcodeId::EXTCODECOPY
-            | OpcodeId::RETURNDATACOPY => OogError::MemoryCopy,
+            | OpcodeId::RETURNDATACOPY
+            | OpcodeId::MCOPY => OogError::MemoryCopy,
             OpcodeId::BALANCE | OpcodeId::EXTCODESIZE | OpcodeId::EXTCODEHASH => {
                 OogError::AccountAccess
             }
@@ -1,7 +1,8 @@
 //! Definition of each opcode of the EVM.
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecState, ExecStep,
+        L1FeeHardfork, NumberOrHash, TraceSource,
     },
     error::{
         ContractAddressCollisionError, DepthError, ExecError, InsufficientBalanceError,
@@ -10,7 +11,7 @@ use crate::{
     evm::OpcodeId,
     l2_predeployed::l1_gas_price_oracle,
     operation::{
-        AccountField, AccountOp, CallContextField, StorageOp, TxAccessListAccountOp,
+        AccountField, AccountOp, CallContextField, StorageOp, TxDestructedAccountOp,
         TxReceiptField, TxRefundOp, RW,
     },
     state_db::CodeDB,
@@ -19,7 +20,7 @@ use crate::{
 use core::fmt::Debug;
 use eth_types::{
     evm_types::{gas_utils::tx_data_gas_cost, GasCost, MAX_REFUND_QUOTIENT_OF_GAS_USED},
-    evm_unimplemented, Bytecode, GethExecStep, GethExecTrace, ToAddress, ToWord, Word,
+    evm_unimplemented, Bytecode, GethExecStep, ToWord, Word,
 };
 use ethers_core::utils::get_contract_address;
 
@@ -30,6 +31,8 @@ pub use self::sha3::sha3_tests::{gen_sha3_code, MemoryKind};
 
 mod address;
 mod balance;
+mod blobbasefee;
+mod blobhash;
 mod blockhash;
 mod calldatacopy;
 mod calldataload;
@@ -48,6 +51,7 @@ mod extcodehash;
 mod extcodesize;
 mod gasprice;
 mod logs;
+mod mcopy;
 mod mload;
 mod mstore;
 mod number;
@@ -58,21 +62,25 @@ mod return_revert;
 mod returndatacopy;
 mod returndatasize;
 mod selfbalance;
+mod selfdestruct;
 mod sha3;
 mod sload;
 mod sstore;
 mod stackonlyop;
 mod stop;
 mod swap;
+mod tload;
+mod tstore;
 
 mod error_codestore;
-mod error_contract_address_collision;
 mod error_invalid_creation_code;
 mod error_invalid_jump;
+mod error_invalid_opcode;
 mod error_oog_account_access;
 mod error_oog_call;
 mod error_oog_log;
 mod error_oog_memory_copy;
+mod error_oog_memory_expansion;
 mod error_oog_sload_sstore;
 mod error_precompile_failed;
 mod error_return_data_outofbound;
@@ -84,9 +92,11 @@ mod memory_expansion_test;
 pub use callop::tests::PrecompileCallArgs;
 
 use self::sha3::Sha3;
-use crate::precompile::is_precompiled;
+use crate::precompile::{is_precompiled, PRECOMPILE_COUNT};
 use address::Address;
 use balance::Balance;
+use blobbasefee::Blobbasefee;
+use blobhash::Blobhash;
 use blockhash::Blockhash;
 use calldatacopy::Calldatacopy;
 use calldataload::Calldataload;
@@ -101,10 +111,12 @@ use dup::Dup;
 use error_codestore::ErrorCodeStore;
 use error_invalid_creation_code::ErrorCreationCode;
 use error_invalid_jump::InvalidJump;
+use error_invalid_opcode::InvalidOpcode;
 use error_oog_account_access::ErrorOOGAccountAccess;
 use error_oog_call::OOGCall;
 use error_oog_log::ErrorOOGLog;
 use error_oog_memory_copy::OOGMemoryCopy;
+use error_oog_memory_expansion::ErrorOOGMemoryExpansion;
 use error_oog_sload_sstore::OOGSloadSstore;
 use error_precompile_failed::PrecompileFailed;
 use error_return_data_outofbound::ErrorReturnDataOutOfBound;
@@ -115,6 +127,7 @@ use extcodehash::Extcodehash;
 use extcodesize::Extcodesize;
 use gasprice::GasPrice;
 use logs::Log;
+use mcopy::Mcopy;
 use mload::Mload;
 use mstore::Mstore;
 use origin::Origin;
@@ -123,11 +136,14 @@ use return_revert::ReturnRevert;
 use returndatacopy::Returndatacopy;
 use returndatasize::Returndatasize;
 use selfbalance::Selfbalance;
+use selfdestruct::SelfDestruct;
 use sload::Sload;
 use sstore::Sstore;
 use stackonlyop::StackOnlyOpcode;
 use stop::Stop;
 use swap::Swap;
+use tload::Tload;
+use tstore::Tstore;
 
 /// Generic opcode trait which defines the logic of the
 /// [`Operation`](crate::operation::Operation) that should be generated for one
@@ -220,12 +236,17 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CHAINID => StackOnlyOpcode::<0, 1>::gen_associated_ops,
         OpcodeId::SELFBALANCE => Selfbalance::gen_associated_ops,
         OpcodeId::BASEFEE => StackOnlyOpcode::<0, 1>::gen_associated_ops,
+        OpcodeId::BLOBHASH => Blobhash::gen_associated_ops,
+        OpcodeId::BLOBBASEFEE => Blobbasefee::gen_associated_ops,
         OpcodeId::POP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::MLOAD => Mload::gen_associated_ops,
         OpcodeId::MSTORE => Mstore::<false>::gen_associated_ops,
         OpcodeId::MSTORE8 => Mstore::<true>::gen_associated_ops,
+        OpcodeId::MCOPY => Mcopy::gen_associated_ops,
         OpcodeId::SLOAD => Sload::gen_associated_ops,
         OpcodeId::SSTORE => Sstore::gen_associated_ops,
+        OpcodeId::TLOAD => Tload::gen_associated_ops,
+        OpcodeId::TSTORE => Tstore::gen_associated_ops,
         OpcodeId::JUMP => StackOnlyOpcode::<1, 0>::gen_associated_ops,
         OpcodeId::JUMPI => StackOnlyOpcode::<2, 0>::gen_associated_ops,
         OpcodeId::PC => StackOnlyOpcode::<0, 1>::gen_associated_ops,
@@ -275,10 +296,7 @@ fn fn_gen_associated_ops(opcode_id: &OpcodeId) -> FnGenAssociatedOps {
         OpcodeId::CREATE2 => Create::<true>::gen_associated_ops,
         OpcodeId::RETURN | OpcodeId::REVERT => ReturnRevert::gen_associated_ops,
         OpcodeId::INVALID(_) => Stop::gen_associated_ops,
-        OpcodeId::SELFDESTRUCT => {
-            log::debug!("Using dummy gen_selfdestruct_ops for opcode SELFDESTRUCT");
-            DummySelfDestruct::gen_associated_ops
-        }
+        OpcodeId::SELFDESTRUCT => SelfDestruct::gen_associated_ops,
         _ => {
             log::debug!("Using dummy gen_associated_ops for opcode {:?}", opcode_id);
             Dummy::gen_associated_ops
@@ -292,7 +310,7 @@ fn fn_gen_error_state_associated_ops(
 ) -> Option<FnGenAssociatedOps> {
     match error {
         ExecError::InvalidJump => Some(InvalidJump::gen_associated_ops),
-        ExecError::InvalidOpcode => Some(StackOnlyOpcode::<0, 0, true>::gen_associated_ops),
+        ExecError::InvalidOpcode => Some(InvalidOpcode::gen_associated_ops),
         // Depth error could occur in CALL, CALLCODE, DELEGATECALL and STATICCALL.
         ExecError::Depth(DepthError::Call) => match geth_step.op {
             OpcodeId::CALL | OpcodeId::CALLCODE => Some(CallOpcode::<7>::gen_associated_ops),
@@ -308,25 +326,14 @@ fn fn_gen_error_state_associated_ops(
         ExecError::OutOfGas(OogError::Constant) => {
             Some(StackOnlyOpcode::<0, 0, true>::gen_associated_ops)
         }
-        ExecError::OutOfGas(OogError::Create) => match geth_step.op {
-            OpcodeId::CREATE => Some(StackOnlyOpcode::<3, 0, true>::gen_associated_ops),
-            OpcodeId::CREATE2 => Some(StackOnlyOpcode::<4, 0, true>::gen_associated_ops),
-            op => unreachable!("OOG Create cannot occur in {op}"),
-        },
-        ExecError::OutOfGas(OogError::Log) => Some(ErrorOOGLog::gen_associated_ops),
-        ExecError::OutOfGas(OogError::DynamicMemoryExpansion) => {
-            Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops)
-        }
-        ExecError::OutOfGas(OogError::StaticMemoryExpansion) => {
-            Some(StackOnlyOpcode::<1, 0, true>::gen_associated_ops)
+        ExecError::OutOfGas(OogError::Create | OogError::MemoryExpansion | OogError::Sha3) => {
+            Some(ErrorOOGMemoryExpansion::gen_associated_ops)
         }
+        ExecError::OutOfGas(OogError::Log) => Some(ErrorOOGLog::gen_associated_ops),
         ExecError::OutOfGas(OogError::Exp) => {
             Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops)
         }
         ExecError::OutOfGas(OogError::MemoryCopy) => Some(OOGMemoryCopy::gen_associated_ops),
-        ExecError::OutOfGas(OogError::Sha3) => {
-            Some(StackOnlyOpcode::<2, 0, true>::gen_associated_ops)
-        }
         ExecError::OutOfGas(OogError::SloadSstore) => Some(OOGSloadSstore::gen_associated_ops),
         ExecError::OutOfGas(OogError::AccountAccess) => {
             Some(ErrorOOGAccountAccess::gen_associated_ops)
@@ -470,14 +477,43 @@ pub fn gen_associated_ops(
     fn_gen_associated_ops(state, geth_steps)
 }
 
-pub fn gen_begin_tx_ops(
+/// Generate the steps of the virtual execution state `exec_state` of the
+/// current transaction, which don't correspond to any opcode of the trace, and
+/// push them into the transaction.  Only `BeginTx` and `EndTx` are supported.
+pub fn gen_associated_steps(
     state: &mut CircuitInputStateRef,
-    geth_trace: &GethExecTrace,
+    exec_state: ExecState,
+    geth_trace: &dyn TraceSource,
+) -> Result<(), Error> {
+    match exec_state {
+        // The BeginTx step is pushed by gen_begin_tx_ops itself, as a failed
+        // precompile call needs it in the tx to handle the reversion.
+        ExecState::BeginTx => gen_begin_tx_ops(state, geth_trace),
+        // The accounts self destructed in the tx are deleted right before
+        // EndTx, one DestructAccount step each.
+        ExecState::EndTx => {
+            for address in state.sdb.destructed_accounts() {
+                let exec_step = gen_destruct_account_ops(state, address)?;
+                state.tx.steps_mut().push(exec_step);
+            }
+            let exec_step = gen_end_tx_ops(state)?;
+            state.tx.steps_mut().push(exec_step);
+            Ok(())
+        }
+        _ => Err(Error::InternalError(
+            "gen_associated_steps only supports BeginTx and EndTx",
+        )),
+    }
+}
+
+fn gen_begin_tx_ops(
+    state: &mut CircuitInputStateRef,
+    geth_trace: &dyn TraceSource,
 ) -> Result<(), Error> {
     let mut exec_step = state.new_begin_tx_step();
     let call = state.call()?.clone();
 
-    // Add 3 RW read operations for transaction L1 fee.
+    // Add the RW read operations for transaction L1 fee.
     gen_tx_l1_fee_ops(state, &mut exec_step);
 
     for (field, value) in [
@@ -512,7 +548,7 @@ pub fn gen_begin_tx_ops(
     )?;
 
     // Add precompile contract address to access list
-    for address in 1..=9 {
+    for address in 1..=PRECOMPILE_COUNT {
         let address = eth_types::Address::from_low_u64_be(address);
         let is_warm_prev = !state.sdb.add_account_to_access_list(address);
         state.tx_accesslist_account_write(
@@ -689,6 +725,7 @@ pub fn gen_begin_tx_ops(
                     value_prev: 0.into(),
                 },
             )?;
+            state.tx_created_account_write(&mut exec_step, call.address)?;
             for (field, value) in [
                 (CallContextField::Depth, call.depth.into()),
                 (
@@ -755,10 +792,9 @@ pub fn gen_begin_tx_ops(
         }
     }
 
-    let real_gas_cost = if geth_trace.struct_logs.is_empty() {
-        GasCost(geth_trace.gas.0)
-    } else {
-        GasCost(state.tx.gas - geth_trace.struct_logs[0].gas.0)
+    let real_gas_cost = match geth_trace.first_step_gas()? {
+        None => GasCost(geth_trace.gas().0),
+        Some(gas) => GasCost(state.tx.gas - gas.0),
     };
     if is_precompile {
         // FIXME after we implement all precompiles
@@ -788,7 +824,51 @@ pub fn gen_begin_tx_ops(
     Ok(())
 }
 
-pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
+fn gen_destruct_account_ops(
+    state: &mut CircuitInputStateRef,
+    address: eth_types::Address,
+) -> Result<ExecStep, Error> {
+    let mut exec_step = state.new_destruct_account_step();
+    let call = state.tx.calls()[0].clone();
+
+    state.call_context_read(
+        &mut exec_step,
+        call.call_id,
+        CallContextField::TxId,
+        state.tx_ctx.id().into(),
+    );
+    // The flag is cleared, so that the state circuit can check that every
+    // account self destructed in the tx is deleted.
+    state.push_op(
+        &mut exec_step,
+        RW::WRITE,
+        TxDestructedAccountOp {
+            tx_id: state.tx_ctx.id(),
+            address,
+            is_destructed: false,
+            is_destructed_prev: true,
+        },
+    );
+
+    let account = state.sdb.get_account(&address).1.clone();
+    let code_hash = if account.is_empty() {
+        Word::zero()
+    } else {
+        account.code_hash.to_word()
+    };
+    for (field, value_prev) in [
+        (AccountField::CodeHash, code_hash),
+        (AccountField::Nonce, account.nonce),
+        (AccountField::Balance, account.balance),
+    ] {
+        state.account_write(&mut exec_step, address, field, Word::zero(), value_prev)?;
+    }
+    state.code_db.remove_ref(address);
+
+    Ok(exec_step)
+}
+
+fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Error> {
     let mut exec_step = state.new_end_tx_step();
     let call = state.tx.calls()[0].clone();
 
@@ -943,150 +1023,61 @@ pub fn gen_end_tx_ops(state: &mut CircuitInputStateRef) -> Result<ExecStep, Erro
     Ok(exec_step)
 }
 
-// Add 3 RW read operations for transaction L1 fee.
+// Add the RW read operations of the params of the L1 fee of the tx: 3 with
+// the formula before Curie, and 3 more with the formula since Curie.
 fn gen_tx_l1_fee_ops(state: &mut CircuitInputStateRef, exec_step: &mut ExecStep) {
     let tx_id = state.tx_ctx.id();
+    let (l1_fee, l1_fee_committed) = (state.tx.l1_fee, state.tx.l1_fee_committed);
 
-    let base_fee = Word::from(state.tx.l1_fee.base_fee);
-    let fee_overhead = Word::from(state.tx.l1_fee.fee_overhead);
-    let fee_scalar = Word::from(state.tx.l1_fee.fee_scalar);
-
-    let base_fee_committed = Word::from(state.tx.l1_fee_committed.base_fee);
-    let fee_overhead_committed = Word::from(state.tx.l1_fee_committed.fee_overhead);
-    let fee_scalar_committed = Word::from(state.tx.l1_fee_committed.fee_scalar);
-
-    state.push_op(
-        exec_step,
-        RW::READ,
-        StorageOp::new(
-            *l1_gas_price_oracle::ADDRESS,
-            *l1_gas_price_oracle::BASE_FEE_SLOT,
-            base_fee,
-            base_fee,
-            tx_id,
-            base_fee_committed,
+    let mut params = vec![
+        (
+            &l1_gas_price_oracle::BASE_FEE_SLOT,
+            l1_fee.base_fee,
+            l1_fee_committed.base_fee,
         ),
-    );
-    state.push_op(
-        exec_step,
-        RW::READ,
-        StorageOp::new(
-            *l1_gas_price_oracle::ADDRESS,
-            *l1_gas_price_oracle::OVERHEAD_SLOT,
-            fee_overhead,
-            fee_overhead,
-            tx_id,
-            fee_overhead_committed,
+        (
+            &l1_gas_price_oracle::OVERHEAD_SLOT,
+            l1_fee.fee_overhead,
+            l1_fee_committed.fee_overhead,
         ),
-    );
-    state.push_op(
-        exec_step,
-        RW::READ,
-        StorageOp::new(
-            *l1_gas_price_oracle::ADDRESS,
-            *l1_gas_price_oracle::SCALAR_SLOT,
-            fee_scalar,
-            fee_scalar,
-            tx_id,
-            fee_scalar_committed,
+        (
+            &l1_gas_price_oracle::SCALAR_SLOT,
+            l1_fee.fee_scalar,
+            l1_fee_committed.fee_scalar,
         ),
-    );
-}
-
-#[derive(Debug, Copy, Clone)]
-struct DummySelfDestruct;
-
-impl Opcode for DummySelfDestruct {
-    fn gen_associated_ops(
-        state: &mut CircuitInputStateRef,
-        geth_steps: &[GethExecStep],
-    ) -> Result<Vec<ExecStep>, Error> {
-        dummy_gen_selfdestruct_ops(state, geth_steps)
-    }
-}
-fn dummy_gen_selfdestruct_ops(
-    state: &mut CircuitInputStateRef,
-    geth_steps: &[GethExecStep],
-) -> Result<Vec<ExecStep>, Error> {
-    let geth_step = &geth_steps[0];
-    let mut exec_step = state.new_step(geth_step)?;
-    let sender = state.call()?.address;
-    let receiver = geth_step.stack.last()?.to_address();
-
-    let is_warm = state.sdb.check_account_in_access_list(&receiver);
-    state.push_op_reversible(
-        &mut exec_step,
-        TxAccessListAccountOp {
-            tx_id: state.tx_ctx.id(),
-            address: receiver,
-            is_warm: true,
-            is_warm_prev: is_warm,
-        },
-    )?;
-
-    let (found, receiver_account) = state.sdb.get_account(&receiver);
-    if !found {
-        return Err(Error::AccountNotFound(receiver));
-    }
-    let receiver_account = &receiver_account.clone();
-    let (found, sender_account) = state.sdb.get_account(&sender);
-    if !found {
-        return Err(Error::AccountNotFound(sender));
-    }
-    let sender_account = &sender_account.clone();
-    let value = sender_account.balance;
-    log::trace!(
-        "self destruct, sender {:?} receiver {:?} value {:?}",
-        sender,
-        receiver,
-        value
-    );
-    // NOTE: In this dummy implementation we assume that the receiver already
-    // exists.
-
-    state.push_op_reversible(
-        &mut exec_step,
-        AccountOp {
-            address: sender,
-            field: AccountField::Balance,
-            value: Word::zero(),
-            value_prev: value,
-        },
-    )?;
-    state.push_op_reversible(
-        &mut exec_step,
-        AccountOp {
-            address: sender,
-            field: AccountField::Nonce,
-            value: Word::zero(),
-            value_prev: sender_account.nonce,
-        },
-    )?;
-    state.push_op_reversible(
-        &mut exec_step,
-        AccountOp {
-            address: sender,
-            field: AccountField::CodeHash,
-            value: Word::zero(),
-            value_prev: sender_account.code_hash.to_word(),
-        },
-    )?;
-    if receiver != sender {
-        state.push_op_reversible(
-            &mut exec_step,
-            AccountOp {
-                address: receiver,
-                field: AccountField::Balance,
-                value: receiver_account.balance + value,
-                value_prev: receiver_account.balance,
-            },
-        )?;
+    ];
+    if l1_fee.hardfork == L1FeeHardfork::Curie {
+        params.extend([
+            (
+                &l1_gas_price_oracle::BLOB_BASE_FEE_SLOT,
+                l1_fee.blob_base_fee,
+                l1_fee_committed.blob_base_fee,
+            ),
+            (
+                &l1_gas_price_oracle::COMMIT_SCALAR_SLOT,
+                l1_fee.commit_scalar,
+                l1_fee_committed.commit_scalar,
+            ),
+            (
+                &l1_gas_price_oracle::BLOB_SCALAR_SLOT,
+                l1_fee.blob_scalar,
+                l1_fee_committed.blob_scalar,
+            ),
+        ]);
     }
 
-    if state.call()?.is_persistent {
-        state.sdb.destruct_account(sender);
+    for (slot, value, committed_value) in params {
+        state.push_op(
+            exec_step,
+            RW::READ,
+            StorageOp::new(
+                *l1_gas_price_oracle::ADDRESS,
+                **slot,
+                value.into(),
+                value.into(),
+                tx_id,
+                committed_value.into(),
+            ),
+        );
     }
-
-    state.handle_return(&mut exec_step, geth_steps, false)?;
-    Ok(vec![exec_step])
 }
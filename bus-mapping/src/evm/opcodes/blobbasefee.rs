@@ -0,0 +1,98 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::BLOBBASEFEE`](crate::evm::OpcodeId::BLOBBASEFEE) `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Blobbasefee;
+
+impl Opcode for Blobbasefee {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let blob_base_fee = state
+            .block
+            .headers
+            .get(&state.tx.block_num)
+            .unwrap()
+            .blob_base_fee;
+        if blob_base_fee != geth_steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "BLOBBASEFEE: pushed value isn't the blob base fee of the block",
+                Box::new(geth_steps[1].clone()),
+            ));
+        }
+        state.stack_write(
+            &mut exec_step,
+            geth_steps[1].stack.last_filled(),
+            blob_base_fee,
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod blobbasefee_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        evm::OpcodeId,
+        mock::BlockData,
+        operation::{StackOp, RW},
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{block_utils::MIN_BASE_FEE_PER_BLOB_GAS, StackAddress},
+        geth_types::GethData,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn blobbasefee_opcode_impl() {
+        let code = bytecode! {
+            BLOBBASEFEE
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::BLOBBASEFEE))
+            .unwrap();
+
+        let op = &builder.block.container.stack[step.bus_mapping_instance[0].as_usize()];
+
+        // the mock block has no excess blob gas
+        assert_eq!(
+            (op.rw(), op.op()),
+            (
+                RW::WRITE,
+                &StackOp::new(1, StackAddress(1023), MIN_BASE_FEE_PER_BLOB_GAS.into())
+            )
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::BLOBHASH`](crate::evm::OpcodeId::BLOBHASH)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Blobhash;
+
+impl Opcode for Blobhash {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let index = geth_step.stack.last()?;
+        state.stack_read(&mut exec_step, geth_step.stack.last_filled(), index)?;
+
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        );
+
+        // The versioned hash at `index` of the blobs of the tx, zero if the
+        // index is out of range.
+        let blob_hash = usize::try_from(index)
+            .ok()
+            .and_then(|index| state.tx.blob_versioned_hashes.get(index))
+            .map_or_else(Word::zero, ToWord::to_word);
+        if blob_hash != geth_steps[1].stack.last()? {
+            return Err(Error::InvalidGethExecStep(
+                "BLOBHASH: pushed value isn't the versioned hash of the tx",
+                Box::new(geth_steps[1].clone()),
+            ));
+        }
+        state.stack_write(&mut exec_step, geth_steps[1].stack.last_filled(), blob_hash)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod blobhash_tests {
+    use crate::{
+        circuit_input_builder::ExecState,
+        evm::OpcodeId,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, StackOp, RW},
+        Error,
+    };
+    use eth_types::{
+        bytecode,
+        evm_types::{Stack, StackAddress},
+        geth_types::GethData,
+        Word,
+    };
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn blobhash_opcode_impl() {
+        let code = bytecode! {
+            PUSH1(0x00)
+            BLOBHASH
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::BLOBHASH))
+            .unwrap();
+
+        let container = &builder.block.container;
+        let stack_read = &container.stack[step.bus_mapping_instance[0].as_usize()];
+        let call_context = &container.call_context[step.bus_mapping_instance[1].as_usize()];
+        let stack_write = &container.stack[step.bus_mapping_instance[2].as_usize()];
+
+        assert_eq!(
+            (stack_read.rw(), stack_read.op()),
+            (RW::READ, &StackOp::new(1, StackAddress(1023), Word::zero()))
+        );
+        assert_eq!(
+            (call_context.rw(), call_context.op()),
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id: 1,
+                    field: CallContextField::TxId,
+                    value: Word::one(),
+                }
+            )
+        );
+        // the mock tx carries no blobs
+        assert_eq!(
+            (stack_write.rw(), stack_write.op()),
+            (
+                RW::WRITE,
+                &StackOp::new(1, StackAddress(1023), Word::zero())
+            )
+        );
+    }
+
+    #[test]
+    fn blobhash_inconsistent_trace() {
+        let code = bytecode! {
+            PUSH1(0x00)
+            BLOBHASH
+            STOP
+        };
+
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        // the trace pushes a hash although the tx carries no blobs
+        let struct_logs = &mut block.geth_traces[0].struct_logs;
+        let index = struct_logs
+            .iter()
+            .position(|step| step.op == OpcodeId::BLOBHASH)
+            .unwrap();
+        struct_logs[index + 1].stack = Stack(vec![Word::one()]);
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        assert!(matches!(
+            builder.handle_block(&block.eth_block, &block.geth_traces),
+            Err(Error::InvalidGethExecStep(..))
+        ));
+    }
+}
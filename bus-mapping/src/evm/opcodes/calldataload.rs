@@ -68,7 +68,7 @@ impl Opcode for Calldataload {
                 // avoiding overflow.
                 call_data_offset + offset.min(call_data_length),
                 call_data_offset + call_data_length,
-                state.call_ctx()?.call_data.to_vec(),
+                &state.call_ctx()?.call_data,
             );
 
             let calldata: Vec<_> = (0..32)
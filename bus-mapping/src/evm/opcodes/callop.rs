@@ -5,7 +5,7 @@ use crate::{
         NumberOrHash,
     },
     evm::opcodes::precompiles::gen_associated_ops as precompile_associated_ops,
-    operation::{AccountField, CallContextField, TxAccessListAccountOp},
+    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
     precompile::{execute_precompiled, is_precompiled, PrecompileCalls},
     state_db::CodeDB,
     Error,
@@ -15,10 +15,99 @@ use eth_types::{
         gas_utils::{eip150_gas, memory_expansion_gas_cost},
         Gas, GasCost, OpcodeId, GAS_STIPEND_CALL_WITH_VALUE,
     },
-    GethExecStep, ToWord, Word,
+    Address, GethExecStep, ToWord, Word, H256,
 };
 use std::cmp::min;
 
+/// Callee information gathered by [`gen_call_common_ops`].
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct CallCallee {
+    /// Whether the callee account exists.
+    pub exists: bool,
+    /// Whether the callee has no code (or doesn't exist).
+    pub is_empty_code_hash: bool,
+    /// Whether the callee was in the access list before this step.
+    pub is_warm: bool,
+}
+
+/// Generate the operations shared by CALL, CALLCODE, DELEGATECALL and
+/// STATICCALL, both in the successful and the out of gas case:
+/// - read the `n_args` stack arguments and write the call result
+/// - read the callee code hash (0 if the callee doesn't exist)
+/// - add the callee to the access list if `warm_up`, or only read its warm status otherwise
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn gen_call_common_ops(
+    state: &mut CircuitInputStateRef,
+    exec_step: &mut ExecStep,
+    geth_step: &GethExecStep,
+    n_args: usize,
+    callee_address: Address,
+    callee_code_hash: H256,
+    result: Word,
+    warm_up: bool,
+) -> Result<CallCallee, Error> {
+    for i in 0..n_args {
+        state.stack_read(
+            exec_step,
+            geth_step.stack.nth_last_filled(i),
+            geth_step.stack.nth_last(i)?,
+        )?;
+    }
+
+    state.stack_write(
+        exec_step,
+        geth_step.stack.nth_last_filled(n_args - 1),
+        result,
+    )?;
+
+    let exists = !state.sdb.is_dead(&callee_address);
+    let (callee_code_hash_word, is_empty_code_hash) = if exists {
+        (
+            callee_code_hash.to_word(),
+            callee_code_hash == CodeDB::empty_code_hash(),
+        )
+    } else {
+        (Word::zero(), true)
+    };
+    state.account_read(
+        exec_step,
+        callee_address,
+        AccountField::CodeHash,
+        callee_code_hash_word,
+    );
+
+    let tx_id = state.tx_ctx.id();
+    let is_warm = state.sdb.check_account_in_access_list(&callee_address);
+    if warm_up {
+        state.push_op_reversible(
+            exec_step,
+            TxAccessListAccountOp {
+                tx_id,
+                address: callee_address,
+                is_warm: true,
+                is_warm_prev: is_warm,
+            },
+        )?;
+    } else {
+        state.push_op(
+            exec_step,
+            RW::READ,
+            TxAccessListAccountOp {
+                tx_id,
+                address: callee_address,
+                is_warm,
+                is_warm_prev: is_warm,
+            },
+        );
+    }
+
+    Ok(CallCallee {
+        exists,
+        is_empty_code_hash,
+        is_warm,
+    })
+}
+
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the `OpcodeId::CALL`, `OpcodeId::CALLCODE`,
 /// `OpcodeId::DELEGATECALL` and `OpcodeId::STATICCALL`.
@@ -87,47 +176,19 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
             state.call_context_read(&mut exec_step, current_call.call_id, field, value);
         }
 
-        for i in 0..N_ARGS {
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.nth_last_filled(i),
-                geth_step.stack.nth_last(i)?,
-            )?;
-        }
-
-        state.stack_write(
-            &mut exec_step,
-            geth_step.stack.nth_last_filled(N_ARGS - 1),
-            (call.is_success as u64).into(),
-        )?;
-
-        let callee_code_hash = call.code_hash;
-        let callee_exists = !state.sdb.get_account(&callee_address).1.is_empty();
-
-        let (callee_code_hash_word, is_empty_code_hash) = if callee_exists {
-            (
-                callee_code_hash.to_word(),
-                callee_code_hash == CodeDB::empty_code_hash(),
-            )
-        } else {
-            (Word::zero(), true)
-        };
-        state.account_read(
+        let CallCallee {
+            exists: callee_exists,
+            is_empty_code_hash,
+            is_warm,
+        } = gen_call_common_ops(
+            state,
             &mut exec_step,
+            geth_step,
+            N_ARGS,
             callee_address,
-            AccountField::CodeHash,
-            callee_code_hash_word,
-        );
-
-        let is_warm = state.sdb.check_account_in_access_list(&callee_address);
-        state.push_op_reversible(
-            &mut exec_step,
-            TxAccessListAccountOp {
-                tx_id,
-                address: callee_address,
-                is_warm: true,
-                is_warm_prev: is_warm,
-            },
+            call.code_hash,
+            (call.is_success as u64).into(),
+            true,
         )?;
 
         // Switch to callee's call context
@@ -265,6 +326,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                         &[]
                     },
                     callee_gas_left,
+                    call.is_success,
                 );
 
                 // mutate the callee memory by at least the precompile call's result that will be
@@ -276,7 +338,7 @@ impl<const N_ARGS: usize> Opcode for CallOpcode<N_ARGS> {
                 if length > 0 {
                     {
                         let caller_ctx_mut = state.caller_ctx_mut()?;
-                        caller_ctx_mut.return_data = result.clone();
+                        caller_ctx_mut.return_data = result.clone().into();
                         caller_ctx_mut.memory.extend_at_least(ret_offset + length);
                     }
                 }
@@ -997,4 +1059,179 @@ pub mod tests {
             }
         }
     }
+
+    /// The gas passed to the callee of the CALL opcodes, to a contract or to a
+    /// precompile, is checked against geth for random specified gas.
+    #[test]
+    fn call_gas_forwarding_matches_geth() {
+        use crate::{
+            circuit_input_builder::ExecState, mock::BlockData, precompile::PrecompileCalls,
+        };
+        use eth_types::{
+            evm_types::{gas_utils::eip150_gas, GasCost, GAS_STIPEND_CALL_WITH_VALUE},
+            geth_types::GethData,
+        };
+        use mock::{
+            test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+            TestContext,
+        };
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        const CASES: u64 = 64;
+        let call_ops = [
+            OpcodeId::CALL,
+            OpcodeId::CALLCODE,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::STATICCALL,
+        ];
+        let identity = Word::from(PrecompileCalls::Identity as u8);
+
+        for seed in 0..CASES {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let call_op = call_ops[rng.gen_range(0..call_ops.len())];
+            let to_precompile = rng.gen_bool(0.5);
+            let gas_specified = match rng.gen_range(0..3) {
+                0 => Word::from(rng.gen_range(0..2_000_000u64)),
+                1 => Word::from(rng.gen::<u64>()),
+                _ => Word::from_big_endian(&rng.gen::<[u8; 32]>()),
+            };
+            // the precompile accounts don't exist in the mock state, so a value
+            // call to one would pay for a new account too.
+            let has_value_arg = matches!(call_op, OpcodeId::CALL | OpcodeId::CALLCODE);
+            let value = if to_precompile || !has_value_arg {
+                Word::zero()
+            } else {
+                Word::from(rng.gen_range(0..2u64))
+            };
+            let case = format!("seed {seed}, {call_op:?} to precompile {to_precompile}, gas {gas_specified:?}, value {value:?}");
+
+            // The contract calls itself or the identity precompile with one
+            // byte of call data, and the callee stops when it has call data.
+            let mut call = Bytecode::default();
+            call.push(1, Word::zero())
+                .push(1, Word::zero())
+                .push(1, Word::one())
+                .push(1, Word::zero());
+            if has_value_arg {
+                call.push(32, value);
+            }
+            if to_precompile {
+                call.push(1, identity);
+            } else {
+                call.write_op(OpcodeId::ADDRESS);
+            }
+            call.push(32, gas_specified)
+                .write_op(call_op)
+                .write_op(OpcodeId::STOP);
+            let mut code = Bytecode::default();
+            // CALLDATASIZE, PUSH1 and JUMPI take 4 bytes.
+            code.write_op(OpcodeId::CALLDATASIZE)
+                .push(1, Word::from(4 + call.code().len()))
+                .write_op(OpcodeId::JUMPI);
+            code.append(&call);
+            code.write_op(OpcodeId::JUMPDEST).write_op(OpcodeId::STOP);
+
+            let block: GethData = TestContext::<2, 1>::new(
+                None,
+                account_0_code_account_1_no_code(code),
+                tx_from_1_to_0,
+                |block, _tx| block.number(0xcafeu64),
+            )
+            .unwrap()
+            .into();
+            let mut builder =
+                BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+            builder
+                .handle_block(&block.eth_block, &block.geth_traces)
+                .unwrap_or_else(|err| panic!("{case}: {err:?}"));
+
+            let geth_steps = &block.geth_traces[0].struct_logs;
+            let call_index = geth_steps
+                .iter()
+                .position(|step| step.op == call_op)
+                .expect("call step");
+            let (call_step, next_step) = (&geth_steps[call_index], &geth_steps[call_index + 1]);
+            let has_value = !value.is_zero();
+            // the callee is warm and exists, and memory expands by one word.
+            let gas_cost = GasCost::WARM_ACCESS.0
+                + 3
+                + if has_value {
+                    GasCost::CALL_WITH_VALUE.0
+                } else {
+                    0
+                };
+            let callee_gas_left = eip150_gas(call_step.gas.0 - gas_cost, gas_specified);
+
+            if to_precompile {
+                let precompile_step = builder.block.txs[0]
+                    .steps()
+                    .iter()
+                    .find(|step| matches!(step.exec_state, ExecState::Precompile(_)))
+                    .expect("precompile step");
+                assert_eq!(precompile_step.gas_left.0, callee_gas_left, "{case}");
+                // identity costs 15 + 3 per word, and takes all the gas when
+                // it runs out of gas.
+                let identity_gas_used = callee_gas_left.min(18);
+                assert_eq!(
+                    next_step.gas.0,
+                    call_step.gas.0 - gas_cost - identity_gas_used,
+                    "{case}"
+                );
+            } else {
+                assert_eq!(next_step.depth, call_step.depth + 1, "{case}");
+                let stipend = if has_value {
+                    GAS_STIPEND_CALL_WITH_VALUE
+                } else {
+                    0
+                };
+                assert_eq!(next_step.gas.0, callee_gas_left + stipend, "{case}");
+            }
+        }
+    }
+
+    /// A zero value CALL touches the callee without creating it, and the
+    /// touched empty callee is deleted at the end of the tx (EIP-161).
+    #[test]
+    fn zero_value_call_deletes_empty_callee() {
+        use crate::mock::BlockData;
+        use eth_types::{address, bytecode, geth_types::GethData, ToWord};
+        use mock::{
+            test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+            TestContext,
+        };
+
+        let callee = address!("0x00000000000000000000000000000000000000aa");
+        let code = bytecode! {
+            PUSH1(0) // retLength
+            PUSH1(0) // retOffset
+            PUSH1(0) // argsLength
+            PUSH1(0) // argsOffset
+            PUSH1(0) // value
+            PUSH20(callee.to_word())
+            PUSH2(0xffff) // gas
+            CALL
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        // the mock state has an empty account at each accessed address
+        assert!(builder.sdb.get_account(&callee).0);
+        assert!(builder.sdb.is_dead(&callee));
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert!(!builder.sdb.get_account(&callee).0);
+        assert!(!builder
+            .sdb
+            .is_dead(&block.eth_block.transactions[0].to.unwrap()));
+    }
 }
@@ -11,7 +11,7 @@ use crate::{
 };
 use eth_types::{
     bytecode::BytecodeElement,
-    evm_types::{memory::MemoryWordRange, Memory},
+    evm_types::{gas_utils::all_but_one_64th_gas, memory::MemoryWordRange, Memory},
     Bytecode, GethExecStep, ToBigEndian, ToWord, Word, H160, H256,
 };
 use ethers_core::utils::{get_create2_address, keccak256, rlp};
@@ -66,6 +66,7 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
 
         let callee_account = &state.sdb.get_account(&address).1.clone();
         let callee_exists = !callee_account.is_empty();
+        let is_address_collision = callee_account.is_create_collision();
         if !callee_exists && callee.value.is_zero() {
             state.sdb.get_account_mut(&address).1.storage.clear();
         }
@@ -163,14 +164,12 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             state.call_context_write(&mut exec_step, callee.call_id, field, value);
         }
 
-        // if address created before, nonce is not zero
-
-        // TODO: test create onto non-0 eth address?
-        // this could be good place for checking callee_exists = true, since above
-        // operation happens in evm create() method before checking
-        // ErrContractAddressCollision
+        // The creation fails with ErrContractAddressCollision if the account
+        // at the new address has a non-zero nonce or non-empty code. An
+        // existing account with only balance (e.g. pre-funded address of a
+        // deterministic deployment) doesn't collide.
         let code_hash_previous = if callee_exists {
-            if is_precheck_ok {
+            if is_precheck_ok && is_address_collision {
                 // CREATE2 may cause address collision error. And for a tricky
                 // case of CREATE, it could also cause this error. e.g. the `to`
                 // field of transaction is set to the calculated contract
@@ -197,14 +196,23 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
             AccountField::CodeHash,
             code_hash_previous.to_word(),
         );
+        // and read the nonce of an existing account to check for collision
+        if callee_exists {
+            state.account_read(
+                &mut exec_step,
+                address,
+                AccountField::Nonce,
+                callee_account.nonce,
+            );
+        }
 
-        if is_precheck_ok && !callee_exists {
+        if is_precheck_ok && !is_address_collision {
             state.transfer(
                 &mut exec_step,
                 callee.caller_address,
                 callee.address,
-                true,
-                true,
+                callee_exists,
+                !callee_exists,
                 callee.value,
             )?;
             state.push_op_reversible(
@@ -216,11 +224,13 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
                     value_prev: 0.into(),
                 },
             )?;
+            state.tx_created_account_write(&mut exec_step, callee.address)?;
         }
 
         // Per EIP-150, all but one 64th of the caller's gas is sent to the
         // initialization call.
-        let caller_gas_left = (geth_step.gas.0 - geth_step.gas_cost.0) / 64;
+        let gas_available = geth_step.gas.0 - geth_step.gas_cost.0;
+        let caller_gas_left = gas_available - all_but_one_64th_gas(gas_available);
 
         for (field, value) in [
             (
@@ -311,7 +321,7 @@ impl<const IS_CREATE2: bool> Opcode for Create<IS_CREATE2> {
         state.block.sha3_inputs.push(keccak_input);
         state.block.sha3_inputs.push(initialization_code);
 
-        if length == 0 || callee_exists {
+        if length == 0 || is_address_collision {
             for (field, value) in [
                 (CallContextField::LastCalleeId, 0.into()),
                 (CallContextField::LastCalleeReturnDataOffset, 0.into()),
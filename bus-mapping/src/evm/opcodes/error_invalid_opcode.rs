@@ -0,0 +1,108 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    error::ExecError,
+    evm::{Opcode, OpcodeId},
+    operation::CallContextField,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`ExecError::InvalidOpcode`], of an undefined opcode
+/// or of a `PUSH0` before Shanghai.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct InvalidOpcode;
+
+impl Opcode for InvalidOpcode {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = Some(ExecError::InvalidOpcode);
+
+        // `PUSH0` (parsed as `INVALID(0x5f)` without the shanghai feature)
+        // reads the tx id, to look up the block number it is invalid in
+        if geth_step.op.as_u8() == OpcodeId::PUSH0.as_u8() {
+            let call_id = state.call()?.call_id;
+            let tx_id = state.tx_ctx.id();
+            state.call_context_read(
+                &mut exec_step,
+                call_id,
+                CallContextField::TxId,
+                tx_id.into(),
+            );
+        }
+
+        // `IsSuccess` call context operation is added in handle_return
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        circuit_input_builder::ChainSpec,
+        error::ExecError,
+        mock::BlockData,
+        operation::{CallContextField, CallContextOp, RW},
+    };
+    use eth_types::{bytecode, geth_types::GethData, Word};
+    use mock::test_ctx::{helpers::*, TestContext};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn push0_before_shanghai() {
+        let code = bytecode! {
+            PUSH0
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        // the trace of a chain which activates Shanghai in the next block,
+        // which halts at PUSH0, whether the tracer is Shanghai or not
+        let trace = &mut block.geth_traces[0];
+        trace.struct_logs.truncate(1);
+        trace.struct_logs[0].error = Some("invalid opcode: PUSH0".to_string());
+        trace.failed = true;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            shanghai_block: Some(0xcaff),
+            ..Default::default()
+        };
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let step = builder.block.txs()[0]
+            .steps()
+            .iter()
+            .find(|step| step.error.is_some())
+            .unwrap();
+        assert_eq!(step.error, Some(ExecError::InvalidOpcode));
+
+        // the tx id, to look up the block number of the tx
+        let call_context =
+            &builder.block.container.call_context[step.bus_mapping_instance[0].as_usize()];
+        assert_eq!(
+            (call_context.rw(), call_context.op()),
+            (
+                RW::READ,
+                &CallContextOp {
+                    call_id: 1,
+                    field: CallContextField::TxId,
+                    value: Word::one(),
+                }
+            )
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use super::Opcode;
+use super::{callop::gen_call_common_ops, Opcode};
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
-    operation::{AccountField, CallContextField, TxAccessListAccountOp, RW},
+    operation::CallContextField,
     Error,
 };
-use eth_types::{evm_types::OpcodeId, GethExecStep, ToAddress, ToWord, Word};
+use eth_types::{evm_types::OpcodeId, GethExecStep, ToAddress, Word};
 
 /// Placeholder structure used to implement [`Opcode`] trait over it
 /// corresponding to the out of gas errors for [`OpcodeId::CALL`],
@@ -47,48 +47,19 @@ impl Opcode for OOGCall {
             state.call_context_read(&mut exec_step, current_call.call_id, field, value);
         }
 
-        for i in 0..stack_input_num {
-            state.stack_read(
-                &mut exec_step,
-                geth_step.stack.nth_last_filled(i),
-                geth_step.stack.nth_last(i)?,
-            )?;
-        }
-
-        state.stack_write(
-            &mut exec_step,
-            geth_step.stack.nth_last_filled(stack_input_num - 1),
-            // Must fail.
-            (0_u64).into(),
-        )?;
-
         let (_, callee_account) = state.sdb.get_account(&call_address);
-        let callee_exists = !callee_account.is_empty();
         let callee_code_hash = callee_account.code_hash;
-        let callee_code_hash_word = if callee_exists {
-            callee_code_hash.to_word()
-        } else {
-            Word::zero()
-        };
-
-        state.account_read(
+        gen_call_common_ops(
+            state,
             &mut exec_step,
+            geth_step,
+            stack_input_num,
             call_address,
-            AccountField::CodeHash,
-            callee_code_hash_word,
-        );
-
-        let is_warm = state.sdb.check_account_in_access_list(&call_address);
-        state.push_op(
-            &mut exec_step,
-            RW::READ,
-            TxAccessListAccountOp {
-                tx_id,
-                address: call_address,
-                is_warm,
-                is_warm_prev: is_warm,
-            },
-        );
+            callee_code_hash,
+            // Must fail.
+            Word::zero(),
+            false,
+        )?;
 
         state.handle_return(&mut exec_step, geth_steps, true)?;
         Ok(vec![exec_step])
@@ -23,7 +23,8 @@ impl Opcode for OOGMemoryCopy {
             OpcodeId::CALLDATACOPY,
             OpcodeId::CODECOPY,
             OpcodeId::EXTCODECOPY,
-            OpcodeId::RETURNDATACOPY
+            OpcodeId::RETURNDATACOPY,
+            OpcodeId::MCOPY
         ]
         .contains(&geth_step.op));
 
@@ -56,7 +57,7 @@ impl Opcode for OOGMemoryCopy {
             );
         }
 
-        // Each of CALLDATACOPY, CODECOPY and RETURNDATACOPY has 3 stack read values.
+        // Each of CALLDATACOPY, CODECOPY, RETURNDATACOPY and MCOPY has 3 stack read values.
         // But EXTCODECOPY has 4. It has an extra stack pop for external address.
         let stack_read_num = if is_extcodecopy { 4 } else { 3 };
         for i in 0..stack_read_num {
@@ -0,0 +1,50 @@
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    evm::Opcode,
+    Error,
+};
+use eth_types::{evm_types::OpcodeId, GethExecStep};
+
+/// Number of stack inputs an out of gas step of `op` reads: the operands
+/// which its memory expansion (and other dynamic gas) is computed from.
+pub(crate) fn oog_memory_expansion_stack_inputs(op: OpcodeId) -> usize {
+    match op {
+        // the length is 32 for MLOAD and MSTORE, and 1 for MSTORE8
+        OpcodeId::MLOAD | OpcodeId::MSTORE | OpcodeId::MSTORE8 => 1,
+        OpcodeId::RETURN | OpcodeId::REVERT | OpcodeId::SHA3 => 2,
+        OpcodeId::CREATE => 3,
+        OpcodeId::CREATE2 => 4,
+        op => unreachable!("no memory expansion out of gas step for {op}"),
+    }
+}
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the out of gas errors of the opcodes whose dynamic gas
+/// only depends on their stack inputs:
+/// [`OogError::MemoryExpansion`](crate::error::OogError::MemoryExpansion),
+/// [`OogError::Sha3`](crate::error::OogError::Sha3) and
+/// [`OogError::Create`](crate::error::OogError::Create).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ErrorOOGMemoryExpansion;
+
+impl Opcode for ErrorOOGMemoryExpansion {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        exec_step.error = state.get_step_err(geth_step, geth_steps.get(1))?;
+
+        for i in 0..oog_memory_expansion_stack_inputs(geth_step.op) {
+            state.stack_read(
+                &mut exec_step,
+                geth_step.stack.nth_last_filled(i),
+                geth_step.stack.nth_last(i)?,
+            )?;
+        }
+
+        state.handle_return(&mut exec_step, geth_steps, true)?;
+        Ok(vec![exec_step])
+    }
+}
@@ -53,8 +53,9 @@ impl Opcode for Extcodehash {
             },
         )?;
 
+        // the hash of a dead account, i.e. non-existing or empty, is zero
+        let exists = !state.sdb.is_dead(&external_address);
         let account = state.sdb.get_account(&external_address).1;
-        let exists = !account.is_empty();
         let code_hash = if exists {
             account.keccak_code_hash
         } else {
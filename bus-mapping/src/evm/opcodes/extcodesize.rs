@@ -72,6 +72,11 @@ impl Opcode for Extcodesize {
         if exists {
             state.account_read(&mut exec_step, address, AccountField::CodeSize, code_size);
         }
+        // Otherwise the code size is looked up in the bytecode table.
+        #[cfg(not(feature = "scroll"))]
+        if exists {
+            state.block.add_bytecode_hash(code_hash);
+        }
 
         // Write the EXTCODESIZE result to stack.
         debug_assert_eq!(code_size, geth_steps[1].stack.last()?);
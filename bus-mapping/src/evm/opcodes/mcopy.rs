@@ -0,0 +1,202 @@
+use crate::{
+    circuit_input_builder::{
+        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, ExecStep, NumberOrHash,
+    },
+    evm::Opcode,
+    Error,
+};
+use eth_types::GethExecStep;
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::MCOPY`](crate::evm::OpcodeId::MCOPY)
+/// `OpcodeId`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Mcopy;
+
+impl Opcode for Mcopy {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let dst_offset = geth_step.stack.nth_last(0)?;
+        let src_offset = geth_step.stack.nth_last(1)?;
+        let length = geth_step.stack.nth_last(2)?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(0),
+            dst_offset,
+        )?;
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.nth_last_filled(1),
+            src_offset,
+        )?;
+        state.stack_read(&mut exec_step, geth_step.stack.nth_last_filled(2), length)?;
+
+        let copy_event = gen_copy_event(state, geth_step, &mut exec_step)?;
+        state.push_copy(&mut exec_step, copy_event);
+        Ok(vec![exec_step])
+    }
+}
+
+fn gen_copy_event(
+    state: &mut CircuitInputStateRef,
+    geth_step: &GethExecStep,
+    exec_step: &mut ExecStep,
+) -> Result<CopyEvent, Error> {
+    let rw_counter_start = state.block_ctx.rwc;
+
+    let dst_offset = geth_step.stack.nth_last(0)?;
+    let src_offset = geth_step.stack.nth_last(1)?;
+    let length = geth_step.stack.nth_last(2)?.as_u64();
+
+    // Offsets are only meaningful when some bytes are copied.
+    let (dst_addr, src_addr) = if length == 0 {
+        (0, 0)
+    } else {
+        (dst_offset.as_u64(), src_offset.as_u64())
+    };
+
+    let (read_steps, write_steps, prev_bytes) =
+        state.gen_copy_steps_for_memory_to_memory(exec_step, src_addr, dst_addr, length)?;
+
+    let call_id = state.call()?.call_id;
+    Ok(CopyEvent {
+        src_type: CopyDataType::Memory,
+        src_id: NumberOrHash::Number(call_id),
+        src_addr,
+        src_addr_end: src_addr + length,
+        dst_type: CopyDataType::Memory,
+        dst_id: NumberOrHash::Number(call_id),
+        dst_addr,
+        log_id: None,
+        rw_counter_start,
+        copy_bytes: CopyBytes::new(read_steps, Some(write_steps), Some(prev_bytes)),
+    })
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod mcopy_tests {
+    use crate::{
+        circuit_input_builder::{CopyDataType, ExecState, NumberOrHash},
+        mock::BlockData,
+        operation::RW,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn test_ok(dst_offset: u64, src_offset: u64, length: u64) {
+        let word: Vec<u8> = (1..=32).collect();
+        let code = bytecode! {
+            PUSH32(Word::from_big_endian(&word))
+            PUSH1(0x00)
+            MSTORE
+            PUSH32(length)
+            PUSH32(src_offset)
+            PUSH32(dst_offset)
+            MCOPY
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let tx = &builder.block.txs()[0];
+        let call_id = tx.calls()[0].call_id;
+        let step = tx
+            .steps()
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(OpcodeId::MCOPY))
+            .unwrap();
+
+        let copy_events = &builder.block.copy_events;
+        assert_eq!(copy_events.len(), 1);
+        let copy_event = &copy_events[0];
+        assert_eq!(copy_event.src_type, CopyDataType::Memory);
+        assert_eq!(copy_event.dst_type, CopyDataType::Memory);
+        assert_eq!(copy_event.src_id, NumberOrHash::Number(call_id));
+        assert_eq!(copy_event.dst_id, NumberOrHash::Number(call_id));
+
+        // 3 stack reads, then all the memory word reads before the writes
+        let memory_ops = &step.bus_mapping_instance[3..];
+        if length == 0 {
+            assert!(copy_event.copy_bytes.bytes.is_empty());
+            assert!(memory_ops.is_empty());
+            return;
+        }
+        assert_eq!(copy_event.src_addr, src_offset);
+        assert_eq!(copy_event.src_addr_end, src_offset + length);
+        assert_eq!(copy_event.dst_addr, dst_offset);
+
+        // expected memory after the copy, up to the end of the last written word
+        let mut memory = word.clone();
+        let memory_end = (src_offset.max(dst_offset) + length) as usize;
+        memory.resize(((memory_end + 31) / 32 + 1) * 32, 0);
+        let memory_before = memory.clone();
+        let src = memory[src_offset as usize..(src_offset + length) as usize].to_vec();
+        memory[dst_offset as usize..(dst_offset + length) as usize].copy_from_slice(&src);
+
+        assert_eq!(memory_ops.len() % 2, 0);
+        let words = memory_ops.len() / 2;
+        for (i, op_ref) in memory_ops.iter().enumerate() {
+            let op = &builder.block.container.memory[op_ref.as_usize()];
+            assert_eq!(op.op().call_id(), call_id);
+            let address = op.op().address().0;
+            if i < words {
+                assert_eq!(op.rw(), RW::READ);
+                assert_eq!(
+                    op.op().value(),
+                    Word::from_big_endian(&memory_before[address..address + 32])
+                );
+            } else {
+                assert_eq!(op.rw(), RW::WRITE);
+                assert_eq!(
+                    op.op().value(),
+                    Word::from_big_endian(&memory[address..address + 32])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mcopy_non_overlapping() {
+        test_ok(0x40, 0x00, 0x20);
+        test_ok(0x45, 0x03, 0x11);
+    }
+
+    #[test]
+    fn mcopy_overlapping_backward() {
+        test_ok(0x00, 0x08, 0x18);
+    }
+
+    #[test]
+    fn mcopy_overlapping_forward() {
+        test_ok(0x08, 0x00, 0x18);
+        // the second source word is overwritten by the first destination word
+        test_ok(0x20, 0x00, 0x40);
+        test_ok(0x25, 0x03, 0x3d);
+    }
+
+    #[test]
+    fn mcopy_zero_length() {
+        test_ok(0x40, 0x100000, 0x00);
+    }
+}
@@ -1,9 +1,9 @@
 use eth_types::{GethExecStep, ToWord, Word};
 
 use crate::{
-    circuit_input_builder::{Call, CircuitInputStateRef, ExecState, ExecStep},
+    circuit_input_builder::{Call, CircuitInputStateRef, ExecState, ExecStep, PrecompileEvent},
     operation::CallContextField,
-    precompile::PrecompileCalls,
+    precompile::{PrecompileAuxData, PrecompileCalls},
     Error,
 };
 
@@ -11,11 +11,15 @@ mod ec_add;
 mod ec_mul;
 mod ec_pairing;
 mod ecrecover;
+mod modexp;
+mod point_evaluation;
 
 use ec_add::opt_data as opt_data_ec_add;
 use ec_mul::opt_data as opt_data_ec_mul;
 use ec_pairing::opt_data as opt_data_ec_pairing;
 use ecrecover::opt_data as opt_data_ecrecover;
+use modexp::opt_data as opt_data_modexp;
+use point_evaluation::opt_data as opt_data_point_evaluation;
 
 type InOutRetData = (Option<Vec<u8>>, Option<Vec<u8>>, Option<Vec<u8>>);
 
@@ -32,24 +36,34 @@ pub fn gen_associated_ops(
 
     common_call_ctx_reads(state, &mut exec_step, &call);
 
-    let (opt_event, aux_data) = match precompile {
+    let (opt_event, aux_data) = opt_data(precompile, input_bytes, output_bytes);
+
+    if let Some(event) = opt_event {
+        state.push_precompile_event(event);
+    }
+    exec_step.aux_data = aux_data;
+
+    Ok(exec_step)
+}
+
+fn opt_data(
+    precompile: PrecompileCalls,
+    input_bytes: Option<Vec<u8>>,
+    output_bytes: Option<Vec<u8>>,
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    match precompile {
         PrecompileCalls::Ecrecover => opt_data_ecrecover(input_bytes, output_bytes),
         PrecompileCalls::Bn128Add => opt_data_ec_add(input_bytes, output_bytes),
         PrecompileCalls::Bn128Mul => opt_data_ec_mul(input_bytes, output_bytes),
         PrecompileCalls::Bn128Pairing => opt_data_ec_pairing(input_bytes, output_bytes),
+        PrecompileCalls::Modexp => opt_data_modexp(input_bytes, output_bytes),
+        PrecompileCalls::PointEvaluation => opt_data_point_evaluation(input_bytes, output_bytes),
         PrecompileCalls::Identity => (None, None),
         _ => {
             log::warn!("precompile {:?} unsupported in circuits", precompile);
             (None, None)
         }
-    };
-
-    if let Some(event) = opt_event {
-        state.push_precompile_event(event);
     }
-    exec_step.aux_data = aux_data;
-
-    Ok(exec_step)
 }
 
 fn common_call_ctx_reads(state: &mut CircuitInputStateRef, exec_step: &mut ExecStep, call: &Call) {
@@ -83,3 +97,209 @@ fn common_call_ctx_reads(state: &mut CircuitInputStateRef, exec_step: &mut ExecS
         state.call_context_read(exec_step, call.call_id, field, value);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_input_builder::{BigModExp, MODEXP_SIZE_LIMIT};
+    use eth_types::ToBigEndian;
+
+    /// Golden corpus of precompile calls: (precompile, input, output, event).
+    /// The event is the byte layout of the [`PrecompileEvent`] consumed by the
+    /// sub-circuits, or `None` if the call makes no event. The calls are the
+    /// go-ethereum precompile test vectors (EIP-196, EIP-197, EIP-198 and EIP-4844).
+    const CORPUS: [(PrecompileCalls, &str, &str, Option<&str>); 6] = [
+        (
+            PrecompileCalls::Ecrecover,
+            concat!(
+                "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e",
+                "000000000000000000000000000000000000000000000000000000000000001b",
+                "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e",
+                "789d1dd423d25f0772d2748d60f7e4b81bb14d086eba8e8e8efb6dcff8a4ae02",
+            ),
+            "000000000000000000000000ceaccac640adf55b2028469bd36ba501f28b699d",
+            Some(concat!(
+                "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e",
+                "000000000000000000000000000000000000000000000000000000000000001b",
+                "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e",
+                "789d1dd423d25f0772d2748d60f7e4b81bb14d086eba8e8e8efb6dcff8a4ae02",
+                "000000000000000000000000ceaccac640adf55b2028469bd36ba501f28b699d",
+            )),
+        ),
+        (
+            PrecompileCalls::Bn128Add,
+            concat!(
+                "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9",
+                "063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266",
+                "07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed",
+                "06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+            ),
+            concat!(
+                "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703",
+                "301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+            ),
+            Some(concat!(
+                "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9",
+                "063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266",
+                "07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed",
+                "06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+                "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703",
+                "301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+            )),
+        ),
+        (
+            PrecompileCalls::Bn128Mul,
+            concat!(
+                "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7",
+                "21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204",
+                "00000000000000000000000000000000000000000000000011138ce750fa15c2",
+            ),
+            concat!(
+                "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c",
+                "031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc",
+            ),
+            Some(concat!(
+                "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7",
+                "21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204",
+                "00000000000000000000000000000000000000000000000011138ce750fa15c2",
+                "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c",
+                "031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc",
+            )),
+        ),
+        (
+            PrecompileCalls::Bn128Pairing,
+            concat!(
+                "2cf44499d5d27bb186308b7af7af02ac5bc9eeb6a3d147c186b21fb1b76e18da",
+                "2c0f001f52110ccfe69108924926e45f0b0c868df0e7bde1fe16d3242dc715f6",
+                "1fb19bb476f6b9e44e2a32234da8212f61cd63919354bc06aef31e3cfaff3ebc",
+                "22606845ff186793914e03e21df544c34ffe2f2f3504de8a79d9159eca2d98d9",
+                "2bd368e28381e8eccb5fa81fc26cf3f048eea9abfdd85d7ed3ab3698d63e4f90",
+                "2fe02e47887507adf0ff1743cbac6ba291e66f59be6bd763950bb16041a0a85e",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+                "1971ff0471b09fa93caaf13cbf443c1aede09cc4328f5a62aad45f40ec133eb4",
+                "091058a3141822985733cbdddfed0fd8d6c104e9e9eff40bf5abfef9ab163bc7",
+                "2a23af9a5ce2ba2796c1f4e453a370eb0af8c212d9dc9acd8fc02c2e907baea2",
+                "23a8eb0b0996252cb548a4487da97b02422ebc0e834613f954de6c7e0afdc1fc",
+            ),
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            Some(concat!(
+                "2cf44499d5d27bb186308b7af7af02ac5bc9eeb6a3d147c186b21fb1b76e18da",
+                "2c0f001f52110ccfe69108924926e45f0b0c868df0e7bde1fe16d3242dc715f6",
+                "1fb19bb476f6b9e44e2a32234da8212f61cd63919354bc06aef31e3cfaff3ebc",
+                "22606845ff186793914e03e21df544c34ffe2f2f3504de8a79d9159eca2d98d9",
+                "2bd368e28381e8eccb5fa81fc26cf3f048eea9abfdd85d7ed3ab3698d63e4f90",
+                "2fe02e47887507adf0ff1743cbac6ba291e66f59be6bd763950bb16041a0a85e",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+                "1971ff0471b09fa93caaf13cbf443c1aede09cc4328f5a62aad45f40ec133eb4",
+                "091058a3141822985733cbdddfed0fd8d6c104e9e9eff40bf5abfef9ab163bc7",
+                "2a23af9a5ce2ba2796c1f4e453a370eb0af8c212d9dc9acd8fc02c2e907baea2",
+                "23a8eb0b0996252cb548a4487da97b02422ebc0e834613f954de6c7e0afdc1fc",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+                "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+                "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+                "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+                "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+                "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+                "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+            )),
+        ),
+        (
+            PrecompileCalls::Modexp,
+            concat!(
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "03",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            ),
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            Some(concat!(
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "0000000000000000000000000000000000000000000000000000000000000020",
+                "03",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+                "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+            )),
+        ),
+        (
+            PrecompileCalls::PointEvaluation,
+            concat!(
+                "01e798154708fe7789429634053cbf9f99b619f9f084048927333fce637f549b",
+                "564c0a11a0f704f4fc3e8acfe0f8245f0ad1347b378fbf96e206da11a5d36306",
+                "24d25032e67a7e6a4910df5834b8fe70e6bcfeeac0352434196bdf4b2485d5a1",
+                "8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7",
+                "873033e038326e87ed3e1276fd140253fa08e9fc25fb2d9a98527fc22a2c9612fbeafdad446cbc7bcdbdcd780af2c16a",
+            ),
+            concat!(
+                "0000000000000000000000000000000000000000000000000000000000001000",
+                "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+            ),
+            Some(concat!(
+                "01e798154708fe7789429634053cbf9f99b619f9f084048927333fce637f549b",
+                "564c0a11a0f704f4fc3e8acfe0f8245f0ad1347b378fbf96e206da11a5d36306",
+                "24d25032e67a7e6a4910df5834b8fe70e6bcfeeac0352434196bdf4b2485d5a1",
+                "8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7",
+                "873033e038326e87ed3e1276fd140253fa08e9fc25fb2d9a98527fc22a2c9612fbeafdad446cbc7bcdbdcd780af2c16a",
+                "0000000000000000000000000000000000000000000000000000000000001000",
+                "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+            )),
+        ),
+    ];
+
+    #[test]
+    fn precompile_events_golden_corpus() {
+        for (precompile, input, output, event) in CORPUS {
+            let (opt_event, _) = opt_data(
+                precompile,
+                Some(hex::decode(input).unwrap()),
+                Some(hex::decode(output).unwrap()),
+            );
+            assert_eq!(
+                opt_event.map(|event| hex::encode(event.to_bytes_be())),
+                event.map(String::from),
+                "{precompile:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn modexp_operands() {
+        let (_, input, output, _) = CORPUS[4];
+        let modexp =
+            BigModExp::new_from_bytes(&hex::decode(input).unwrap(), &hex::decode(output).unwrap())
+                .unwrap();
+        assert_eq!(modexp.n_limbs(), 2);
+        assert_eq!(modexp.exponent_bits(), 256);
+        assert_eq!(modexp.num_rows(), 2 * 256 * 2);
+
+        let bytes = (1..=17).collect::<Vec<u8>>();
+        assert_eq!(
+            BigModExp::limbs(&bytes).collect::<Vec<_>>(),
+            vec![0x02030405060708090a0b0c0d0e0f1011, 0x01]
+        );
+
+        // operands are right-padded, and bounded by the size limit.
+        let header = |len: usize| {
+            [len, len, len]
+                .iter()
+                .flat_map(|len| Word::from(*len).to_be_bytes())
+                .collect::<Vec<u8>>()
+        };
+        let modexp =
+            BigModExp::new_from_bytes(&header(MODEXP_SIZE_LIMIT), &[0u8; MODEXP_SIZE_LIMIT])
+                .unwrap();
+        assert_eq!(modexp.modulus, vec![0u8; MODEXP_SIZE_LIMIT]);
+        assert!(BigModExp::new_from_bytes(&header(MODEXP_SIZE_LIMIT + 1), &[]).is_none());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{
+    circuit_input_builder::{BigModExp, PrecompileEvent, MODEXP_SIZE_LIMIT},
+    precompile::PrecompileAuxData,
+};
+
+pub(crate) fn opt_data(
+    input_bytes: Option<Vec<u8>>,
+    output_bytes: Option<Vec<u8>>,
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    // a failed call, or a call with an empty modulus, has no result to prove.
+    let Some(output_bytes) = output_bytes else {
+        return (None, None);
+    };
+    let input_bytes = input_bytes.unwrap_or_default();
+
+    match BigModExp::new_from_bytes(&input_bytes, &output_bytes) {
+        Some(modexp) => (Some(PrecompileEvent::Modexp(Box::new(modexp))), None),
+        None => {
+            log::warn!(
+                "modexp operands larger than {} bytes unsupported in circuits",
+                MODEXP_SIZE_LIMIT
+            );
+            (None, None)
+        }
+    }
+}
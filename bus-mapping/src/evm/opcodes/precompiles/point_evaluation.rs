@@ -0,0 +1,25 @@
+use crate::{
+    circuit_input_builder::{PointEvaluationOp, PrecompileEvent},
+    precompile::PrecompileAuxData,
+};
+
+pub(crate) fn opt_data(
+    input_bytes: Option<Vec<u8>>,
+    output_bytes: Option<Vec<u8>>,
+) -> (Option<PrecompileEvent>, Option<PrecompileAuxData>) {
+    // the call fails on any other input length, in which case there is no proof to check.
+    let input_bytes = match input_bytes {
+        Some(bytes) if bytes.len() == 192 => bytes,
+        _ => return (None, None),
+    };
+    let output_bytes = output_bytes.unwrap_or_default();
+
+    let point_evaluation_op = PointEvaluationOp::new_from_bytes(&input_bytes, &output_bytes);
+
+    (
+        Some(PrecompileEvent::PointEvaluation(Box::new(
+            point_evaluation_op,
+        ))),
+        None,
+    )
+}
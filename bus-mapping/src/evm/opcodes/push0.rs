@@ -1,6 +1,7 @@
 use super::Opcode;
 use crate::{
     circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::CallContextField,
     Error,
 };
 use eth_types::{GethExecStep, U256};
@@ -16,6 +17,16 @@ impl Opcode for Push0 {
         let geth_step = &geth_steps[0];
         let mut exec_step = state.new_step(geth_step)?;
 
+        // the tx id looks up the block number, in which Shanghai must be active
+        let call_id = state.call()?.call_id;
+        let tx_id = state.tx_ctx.id();
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            tx_id.into(),
+        );
+
         state.stack_write(
             &mut exec_step,
             geth_steps[1].stack.last_filled(),
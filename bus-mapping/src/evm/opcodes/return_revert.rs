@@ -1,7 +1,7 @@
 use super::Opcode;
 use crate::{
     circuit_input_builder::{
-        CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder,
+        BytesView, CircuitInputStateRef, CopyBytes, CopyDataType, CopyEvent, CopyEventStepsBuilder,
         NumberOrHash,
     },
     evm::opcodes::ExecStep,
@@ -165,8 +165,8 @@ impl Opcode for ReturnRevert {
                 .memory
                 .0
                 .get(offset..offset + length)
-                .unwrap_or_default()
-                .to_vec();
+                .map(|bytes| BytesView::new(bytes.to_vec().into(), offset as u64))
+                .unwrap_or_default();
 
             state.caller_ctx_mut()?.return_data = return_data;
 
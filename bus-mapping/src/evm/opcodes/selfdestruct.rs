@@ -0,0 +1,190 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{AccountField, AccountOp, CallContextField},
+    Error,
+};
+use eth_types::{GethExecStep, ToAddress, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the
+/// [`OpcodeId::SELFDESTRUCT`](crate::evm::OpcodeId::SELFDESTRUCT) `OpcodeId`.
+///
+/// The balance of the current account is sent to the beneficiary. The
+/// account is then marked as destructed, which with EIP-6780 (`cancun`
+/// feature) only happens if it was created in the same tx; otherwise a
+/// SELFDESTRUCT to itself is a no-op. Like in geth, the account keeps its code
+/// for the rest of the tx and is only deleted at its end, by a
+/// `DestructAccount` step. There is no refund since EIP-3529.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct SelfDestruct;
+
+impl Opcode for SelfDestruct {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+        let call = state.call()?.clone();
+
+        let beneficiary_word = geth_step.stack.last()?;
+        let beneficiary = beneficiary_word.to_address();
+        state.stack_read(
+            &mut exec_step,
+            geth_step.stack.last_filled(),
+            beneficiary_word,
+        )?;
+
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::TxId,
+            state.tx_ctx.id().to_word(),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::CalleeAddress,
+            call.address.to_word(),
+        );
+        state.reversion_info_read(&mut exec_step, &call);
+        state.tx_access_list_write(&mut exec_step, beneficiary)?;
+
+        let beneficiary_account = state.sdb.get_account(&beneficiary).1;
+        let beneficiary_exists = !beneficiary_account.is_empty();
+        let beneficiary_code_hash = if beneficiary_exists {
+            beneficiary_account.code_hash.to_word()
+        } else {
+            Word::zero()
+        };
+        state.account_read(
+            &mut exec_step,
+            beneficiary,
+            AccountField::CodeHash,
+            beneficiary_code_hash,
+        );
+
+        let value = state.sdb.get_balance(&call.address);
+        state.account_read(&mut exec_step, call.address, AccountField::Balance, value);
+        if beneficiary != call.address {
+            state.transfer(
+                &mut exec_step,
+                call.address,
+                beneficiary,
+                beneficiary_exists,
+                false,
+                value,
+            )?;
+        }
+
+        let is_destructed = if cfg!(feature = "cancun") {
+            state.tx_created_account_read(&mut exec_step, call.address)
+        } else {
+            true
+        };
+        if is_destructed {
+            state.tx_destructed_account_write(&mut exec_step, call.address)?;
+            // the balance sent to the account itself is burnt
+            if beneficiary == call.address && !value.is_zero() {
+                state.push_op_reversible(
+                    &mut exec_step,
+                    AccountOp {
+                        address: call.address,
+                        field: AccountField::Balance,
+                        value: Word::zero(),
+                        value_prev: value,
+                    },
+                )?;
+            }
+        }
+
+        state.call_context_read(
+            &mut exec_step,
+            call.call_id,
+            CallContextField::IsSuccess,
+            1.into(),
+        );
+        state.handle_return(&mut exec_step, geth_steps, !call.is_root)?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{circuit_input_builder::ExecState, mock::BlockData};
+    use eth_types::{address, bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    // A contract self destructed in a tx keeps its code until the end of the
+    // tx, so a second call runs it again. Before EIP-6780 it's deleted at the
+    // end of the tx; since then it's kept, as it wasn't created in the tx.
+    #[test]
+    fn selfdestruct_deletes_at_end_of_tx() {
+        let contract = address!("0x0000000000000000000000000000000000000020");
+        let beneficiary = address!("0x0000000000000000000000000000000000000030");
+        let caller_code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH20(contract.to_word())
+            GAS
+            CALL
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH20(contract.to_word())
+            GAS
+            CALL
+            STOP
+        };
+        let block: GethData = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(MOCK_ACCOUNTS[1]).code(caller_code);
+                accs[2].address(contract).balance(eth(2)).code(bytecode! {
+                    PUSH20(beneficiary.to_word())
+                    SELFDESTRUCT
+                });
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(100000u64.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let count = |exec_state: ExecState| {
+            steps
+                .iter()
+                .filter(|step| step.exec_state == exec_state)
+                .count()
+        };
+        assert_eq!(count(ExecState::Op(OpcodeId::SELFDESTRUCT)), 2);
+        let (found, account) = builder.sdb.get_account(&contract);
+        if cfg!(feature = "cancun") {
+            assert_eq!(count(ExecState::DestructAccount), 0);
+            assert!(found && account.balance.is_zero() && !account.is_empty());
+        } else {
+            assert_eq!(count(ExecState::DestructAccount), 1);
+            assert!(!found);
+        }
+        assert_eq!(builder.sdb.get_balance(&beneficiary), eth(2));
+    }
+}
@@ -5,10 +5,7 @@ use crate::{
     },
     Error,
 };
-use eth_types::{
-    evm_types::memory::{MemoryRange, MemoryWordRange},
-    GethExecStep, Word, U256,
-};
+use eth_types::{evm_types::memory::MemoryWordRange, GethExecStep, Word, U256};
 use ethers_core::utils::keccak256;
 
 use super::Opcode;
@@ -41,13 +38,21 @@ impl Opcode for Sha3 {
                 .extend_at_least(offset.as_usize() + size.as_usize());
         }
 
-        let sha3_input = state
-            .call_ctx()?
-            .memory
-            .read_chunk(MemoryRange::new_with_length(
-                offset.low_u64(),
-                size.low_u64(),
-            ));
+        // The hashed bytes are the copied bytes of a single copy event, from
+        // the memory words overlapping them.
+        let dst_range = (size.as_usize() != 0)
+            .then(|| MemoryWordRange::align_range(offset.low_u64(), size.low_u64()));
+        let copy_steps = match dst_range {
+            Some(dst_range) => {
+                let mem = state.call_ctx()?.memory.read_chunk(dst_range);
+                CopyEventStepsBuilder::memory_range(dst_range)
+                    .source(mem.as_slice())
+                    .build()
+            }
+            None => vec![],
+        };
+        let copy_bytes = CopyBytes::new(copy_steps, None, None);
+        let sha3_input = copy_bytes.copied_bytes();
 
         // keccak-256 hash of the given data in memory.
         let sha3 = keccak256(&sha3_input);
@@ -58,25 +63,22 @@ impl Opcode for Sha3 {
             sha3.into(),
         )?;
 
-        // Memory read operations
+        // Memory read operations.
+        //
+        // These reads belong to the copy event below: the EVM circuit only
+        // looks up the copy table and skips the copy's `rwc_inc`, while the
+        // copy circuit checks the bytes against these RWs. Memory is only
+        // committed through the RW table, so one read per touched word is the
+        // minimum; a constant number of RWs per op would need a memory
+        // commitment outside of it.
         let rw_counter_start = state.block_ctx.rwc;
-
-        let copy_steps = if size.as_usize() != 0 {
-            let dst_range = MemoryWordRange::align_range(offset.low_u64(), size.low_u64());
-            let mem = state.call_ctx()?.memory.read_chunk(dst_range);
-            // Read step
+        if let Some(dst_range) = dst_range {
             let mut chunk_index = dst_range.start_slot().0;
             for _ in 0..dst_range.word_count() {
                 state.memory_read_word(&mut exec_step, chunk_index.into())?;
                 chunk_index += 32;
             }
-
-            CopyEventStepsBuilder::memory_range(dst_range)
-                .source(mem.as_slice())
-                .build()
-        } else {
-            vec![]
-        };
+        }
 
         state.block.sha3_inputs.push(sha3_input);
         let call_id = state.call()?.call_id;
@@ -95,7 +97,7 @@ impl Opcode for Sha3 {
                 dst_id: NumberOrHash::Number(call_id),
                 log_id: None,
                 rw_counter_start,
-                copy_bytes: CopyBytes::new(copy_steps, None, None),
+                copy_bytes,
             },
         );
 
@@ -298,6 +300,11 @@ pub mod sha3_tests {
                 mask_count += 1;
             }
         }
+        // the hashed bytes are the ones of the copy event
+        assert_eq!(
+            builder.block.sha3_inputs.last(),
+            Some(&copy_events[0].copy_bytes.copied_bytes())
+        );
     }
 
     #[test]
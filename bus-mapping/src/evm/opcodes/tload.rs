@@ -0,0 +1,69 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TransientStorageOp, RW},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::TLOAD`](crate::evm::OpcodeId::TLOAD)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Tload;
+
+impl Opcode for Tload {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let call_id = state.call()?.call_id;
+        let contract_addr = state.call()?.address;
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        );
+
+        state.call_context_read(
+            &mut exec_step,
+            call_id,
+            CallContextField::CalleeAddress,
+            contract_addr.to_word(),
+        );
+
+        let key = geth_step.stack.last()?;
+        let stack_position = geth_step.stack.last_filled();
+        state.stack_read(&mut exec_step, stack_position, key)?;
+
+        // Transient storage read
+        let value = *state.sdb.get_transient_storage(&contract_addr, &key);
+        // The pushed value is only in the trace if the call goes on after the
+        // TLOAD: it may be the last step of the code, with an implicit STOP.
+        if let Some(next_step) = geth_steps
+            .get(1)
+            .filter(|next_step| next_step.depth == geth_step.depth)
+        {
+            if value != next_step.stack.last()? {
+                return Err(Error::InvalidGethExecStep(
+                    "TLOAD: pushed value isn't the value in the transient storage",
+                    Box::new(next_step.clone()),
+                ));
+            }
+        }
+        state.push_op(
+            &mut exec_step,
+            RW::READ,
+            TransientStorageOp::new(contract_addr, key, value, value, state.tx_ctx.id()),
+        );
+
+        state.stack_write(&mut exec_step, stack_position, value)?;
+
+        Ok(vec![exec_step])
+    }
+}
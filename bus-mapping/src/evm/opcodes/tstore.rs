@@ -0,0 +1,216 @@
+use super::Opcode;
+use crate::{
+    circuit_input_builder::{CircuitInputStateRef, ExecStep},
+    operation::{CallContextField, TransientStorageOp},
+    Error,
+};
+use eth_types::{GethExecStep, ToWord, Word};
+
+/// Placeholder structure used to implement [`Opcode`] trait over it
+/// corresponding to the [`OpcodeId::TSTORE`](crate::evm::OpcodeId::TSTORE)
+/// `OpcodeId`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Tstore;
+
+impl Opcode for Tstore {
+    fn gen_associated_ops(
+        state: &mut CircuitInputStateRef,
+        geth_steps: &[GethExecStep],
+    ) -> Result<Vec<ExecStep>, Error> {
+        let geth_step = &geth_steps[0];
+        let mut exec_step = state.new_step(geth_step)?;
+
+        let contract_addr = state.call()?.address;
+
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::TxId,
+            Word::from(state.tx_ctx.id()),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::IsStatic,
+            Word::from(state.call()?.is_static as u8),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::RwCounterEndOfReversion,
+            Word::from(state.call()?.rw_counter_end_of_reversion),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::IsPersistent,
+            Word::from(state.call()?.is_persistent as u8),
+        );
+        state.call_context_read(
+            &mut exec_step,
+            state.call()?.call_id,
+            CallContextField::CalleeAddress,
+            contract_addr.to_word(),
+        );
+
+        let key = geth_step.stack.nth_last(0)?;
+        let key_stack_position = geth_step.stack.nth_last_filled(0);
+        let value = geth_step.stack.nth_last(1)?;
+        let value_stack_position = geth_step.stack.nth_last_filled(1);
+
+        state.stack_read(&mut exec_step, key_stack_position, key)?;
+        state.stack_read(&mut exec_step, value_stack_position, value)?;
+
+        // Transient storage writes are reverted together with the call.
+        let value_prev = *state.sdb.get_transient_storage(&contract_addr, &key);
+        state.push_op_reversible(
+            &mut exec_step,
+            TransientStorageOp::new(contract_addr, key, value, value_prev, state.tx_ctx.id()),
+        )?;
+
+        Ok(vec![exec_step])
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod tstore_tests {
+    use super::*;
+    use crate::{
+        circuit_input_builder::ExecState,
+        mock::BlockData,
+        operation::{OperationContainer, RW},
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, Word};
+    use mock::{
+        test_ctx::{helpers::*, TestContext},
+        MOCK_ACCOUNTS,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn transient_storage_op(
+        container: &OperationContainer,
+        step_op: OpcodeId,
+        steps: &[ExecStep],
+    ) -> (RW, TransientStorageOp) {
+        let step = steps
+            .iter()
+            .find(|step| step.exec_state == ExecState::Op(step_op))
+            .unwrap();
+        let op_ref = step
+            .bus_mapping_instance
+            .iter()
+            .find(|op_ref| op_ref.target() == crate::operation::Target::TransientStorage)
+            .unwrap();
+        let operation = &container.transient_storage[op_ref.as_usize()];
+        (operation.rw(), operation.op().clone())
+    }
+
+    #[test]
+    fn tstore_tload_opcode_impl() {
+        let code = bytecode! {
+            // Write 0x6f to transient storage slot 0
+            PUSH1(0x6fu64)
+            PUSH1(0x00u64)
+            TSTORE
+            // Load transient storage slot 0
+            PUSH1(0x00u64)
+            TLOAD
+            STOP
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        let steps = builder.block.txs()[0].steps();
+        let container = &builder.block.container;
+        assert_eq!(
+            transient_storage_op(container, OpcodeId::TSTORE, steps),
+            (
+                RW::WRITE,
+                TransientStorageOp::new(
+                    MOCK_ACCOUNTS[0],
+                    Word::zero(),
+                    Word::from(0x6fu64),
+                    Word::zero(),
+                    1
+                )
+            )
+        );
+        assert_eq!(
+            transient_storage_op(container, OpcodeId::TLOAD, steps),
+            (
+                RW::READ,
+                TransientStorageOp::new(
+                    MOCK_ACCOUNTS[0],
+                    Word::zero(),
+                    Word::from(0x6fu64),
+                    Word::from(0x6fu64),
+                    1
+                )
+            )
+        );
+
+        // transient storage does not outlive the transaction
+        assert_eq!(
+            *builder
+                .sdb
+                .get_transient_storage(&MOCK_ACCOUNTS[0], &Word::zero()),
+            Word::zero()
+        );
+    }
+
+    #[test]
+    fn tload_last_step() {
+        // TLOAD is the last step of the trace, followed by an implicit STOP
+        let code = bytecode! {
+            PUSH1(0x6fu64)
+            PUSH1(0x00u64)
+            TSTORE
+            PUSH1(0x00u64)
+            TLOAD
+        };
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert_eq!(
+            transient_storage_op(
+                &builder.block.container,
+                OpcodeId::TLOAD,
+                builder.block.txs()[0].steps()
+            ),
+            (
+                RW::READ,
+                TransientStorageOp::new(
+                    MOCK_ACCOUNTS[0],
+                    Word::zero(),
+                    Word::from(0x6fu64),
+                    Word::from(0x6fu64),
+                    1
+                )
+            )
+        );
+    }
+}
@@ -1,3 +1,5 @@
+//! Error messages of the steps of geth execution traces
+
 /// Geth error message for stack overflow
 pub const GETH_ERR_STACK_OVERFLOW: &str = "stack limit reached";
 /// Geth error message for stack underflow
@@ -6,3 +8,5 @@ pub const GETH_ERR_STACK_UNDERFLOW: &str = "stack underflow";
 pub const GETH_ERR_OUT_OF_GAS: &str = "out of gas";
 /// Geth error message for gas uint64 overflow
 pub const GETH_ERR_GAS_UINT_OVERFLOW: &str = "gas uint64 overflow";
+/// Geth error message for a jump to an invalid destination
+pub const GETH_ERR_INVALID_JUMP: &str = "invalid jump destination";
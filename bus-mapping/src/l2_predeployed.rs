@@ -7,28 +7,53 @@ pub mod message_queue {
     use super::*;
     use eth_types::U256;
     use once_cell::sync::Lazy;
-    use std::str::FromStr;
+    use zkevm_constants::predeployed::{
+        L2_MESSAGE_QUEUE, WITHDRAW_TRIE_BRANCHES_SLOT, WITHDRAW_TRIE_NEXT_INDEX_SLOT,
+        WITHDRAW_TRIE_ROOT_SLOT as SLOT,
+    };
 
     /// address of L2MessageQueue predeploy
-    pub static ADDRESS: Lazy<Address> =
-        Lazy::new(|| Address::from_str("0x5300000000000000000000000000000000000000").unwrap());
+    pub static ADDRESS: Lazy<Address> = Lazy::new(|| Address::from(L2_MESSAGE_QUEUE));
     /// the slot of withdraw root in L2MessageQueue
-    pub static WITHDRAW_TRIE_ROOT_SLOT: Lazy<U256> = Lazy::new(U256::zero);
+    pub static WITHDRAW_TRIE_ROOT_SLOT: Lazy<U256> = Lazy::new(|| U256::from(SLOT));
+    /// the slot of the next message index in L2MessageQueue
+    pub static NEXT_MESSAGE_INDEX_SLOT: Lazy<U256> =
+        Lazy::new(|| U256::from(WITHDRAW_TRIE_NEXT_INDEX_SLOT));
+    /// the first slot of the withdraw trie branches in L2MessageQueue
+    pub static BRANCHES_SLOT: Lazy<U256> = Lazy::new(|| U256::from(WITHDRAW_TRIE_BRANCHES_SLOT));
+}
+
+/// Helper for L2ScrollMessenger contract
+pub mod l2_scroll_messenger {
+    use super::*;
+    use once_cell::sync::Lazy;
+    use zkevm_constants::predeployed::L2_SCROLL_MESSENGER;
+
+    /// address of L2ScrollMessenger on Scroll mainnet
+    pub static ADDRESS: Lazy<Address> = Lazy::new(|| Address::from(L2_SCROLL_MESSENGER));
 }
 
 /// Helper for L1GasPriceOracle contract
 pub mod l1_gas_price_oracle {
     use eth_types::{Address, U256};
     use once_cell::sync::Lazy;
-    use std::str::FromStr;
+    use zkevm_constants::predeployed::{
+        L1_BASE_FEE_SLOT, L1_BLOB_BASE_FEE_SLOT, L1_BLOB_SCALAR_SLOT, L1_COMMIT_SCALAR_SLOT,
+        L1_GAS_PRICE_ORACLE, L1_OVERHEAD_SLOT, L1_SCALAR_SLOT,
+    };
 
     /// L1GasPriceOracle predeployed address
-    pub static ADDRESS: Lazy<Address> =
-        Lazy::new(|| Address::from_str("0x5300000000000000000000000000000000000002").unwrap());
+    pub static ADDRESS: Lazy<Address> = Lazy::new(|| Address::from(L1_GAS_PRICE_ORACLE));
     /// L1 base fee slot in L1GasPriceOracle
-    pub static BASE_FEE_SLOT: Lazy<U256> = Lazy::new(|| U256::from(1));
+    pub static BASE_FEE_SLOT: Lazy<U256> = Lazy::new(|| U256::from(L1_BASE_FEE_SLOT));
     /// L1 overhead slot in L1GasPriceOracle
-    pub static OVERHEAD_SLOT: Lazy<U256> = Lazy::new(|| U256::from(2));
+    pub static OVERHEAD_SLOT: Lazy<U256> = Lazy::new(|| U256::from(L1_OVERHEAD_SLOT));
     /// L1 scalar slot in L1GasPriceOracle
-    pub static SCALAR_SLOT: Lazy<U256> = Lazy::new(|| U256::from(3));
+    pub static SCALAR_SLOT: Lazy<U256> = Lazy::new(|| U256::from(L1_SCALAR_SLOT));
+    /// L1 blob base fee slot in L1GasPriceOracle
+    pub static BLOB_BASE_FEE_SLOT: Lazy<U256> = Lazy::new(|| U256::from(L1_BLOB_BASE_FEE_SLOT));
+    /// L1 commit scalar slot in L1GasPriceOracle
+    pub static COMMIT_SCALAR_SLOT: Lazy<U256> = Lazy::new(|| U256::from(L1_COMMIT_SCALAR_SLOT));
+    /// L1 blob scalar slot in L1GasPriceOracle
+    pub static BLOB_SCALAR_SLOT: Lazy<U256> = Lazy::new(|| U256::from(L1_BLOB_SCALAR_SLOT));
 }
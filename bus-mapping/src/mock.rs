@@ -74,6 +74,7 @@ impl BlockData {
                 hex::encode(account.code.to_vec())
             );
             let code_hash = code_db.insert(account.code.to_vec());
+            code_db.add_ref(account.address, code_hash);
             sdb.set_account(
                 &account.address,
                 state_db::Account {
@@ -9,10 +9,11 @@ pub use eth_types::evm_types::{MemoryAddress, StackAddress};
 
 use core::{cmp::Ordering, fmt, fmt::Debug};
 use eth_types::{Address, Word};
+use serde::{Deserialize, Serialize};
 use std::mem::swap;
 
 /// Marker that defines whether an Operation performs a `READ` or a `WRITE`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RW {
     /// Marks op as READ.
     READ,
@@ -35,7 +36,7 @@ impl RW {
 /// Wrapper type over `usize` which represents the global counter. The purpose
 /// of the `RWCounter` is to enforce that each Opcode/Instruction and Operation
 /// is unique and just executed once.
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct RWCounter(pub usize);
 
 impl fmt::Debug for RWCounter {
@@ -82,7 +83,7 @@ impl RWCounter {
 }
 
 /// Enum used to differenciate between EVM Stack, Memory and Storage operations.
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize, Deserialize)]
 pub enum Target {
     /// Start is a padding operation.
     Start,
@@ -106,6 +107,12 @@ pub enum Target {
     TxReceipt,
     /// Means the target of the operation is the TxLog.
     TxLog,
+    /// Means the target of the operation is the TransientStorage.
+    TransientStorage,
+    /// Means the target of the operation is the TxCreatedAccount.
+    TxCreatedAccount,
+    /// Means the target of the operation is the TxDestructedAccount.
+    TxDestructedAccount,
 }
 
 /// Trait used for Operation Kinds.
@@ -122,7 +129,7 @@ pub trait Op: Clone + Eq + Ord {
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the memory implied
 /// by an specific [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
 /// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MemoryOp {
     /// Call ID
     pub call_id: usize,
@@ -217,7 +224,7 @@ impl Ord for MemoryOp {
 /// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the stack implied
 /// by an specific [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
 /// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StackOp {
     /// Call ID
     pub call_id: usize,
@@ -295,7 +302,7 @@ impl Ord for StackOp {
 /// implied by an specific
 /// [`OpcodeId`](eth_types::evm_types::opcode_ids::OpcodeId) of
 /// the [`ExecStep`](crate::circuit_input_builder::ExecStep).
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StorageOp {
     /// Account Address
     pub address: Address,
@@ -392,10 +399,86 @@ impl Ord for StorageOp {
     }
 }
 
+/// Represents a [`READ`](RW::READ)/[`WRITE`](RW::WRITE) into the transient
+/// storage (EIP-1153) implied by a `TLOAD` or `TSTORE` step. Transient storage
+/// is discarded at the end of each transaction.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransientStorageOp {
+    /// Account Address
+    pub address: Address,
+    /// Transient Storage Key
+    pub key: Word,
+    /// Transient Storage Value after the operation
+    pub value: Word,
+    /// Transient Storage Value before the operation
+    pub value_prev: Word,
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+}
+
+impl fmt::Debug for TransientStorageOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransientStorageOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, key: {:?}, val_prev: 0x{:x}, val: 0x{:x}",
+            self.tx_id, self.address, self.key, self.value_prev, self.value
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl TransientStorageOp {
+    /// Create a new instance of a `TransientStorageOp` from it's components.
+    pub const fn new(
+        address: Address,
+        key: Word,
+        value: Word,
+        value_prev: Word,
+        tx_id: usize,
+    ) -> TransientStorageOp {
+        TransientStorageOp {
+            address,
+            key,
+            value,
+            value_prev,
+            tx_id,
+        }
+    }
+
+    /// Returns the [`Target`] (operation type) of this operation.
+    pub const fn target(&self) -> Target {
+        Target::TransientStorage
+    }
+}
+
+impl Op for TransientStorageOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TransientStorage(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.value, &mut rev.value_prev);
+        rev
+    }
+}
+
+impl PartialOrd for TransientStorageOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TransientStorageOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address, &self.key).cmp(&(&other.tx_id, &other.address, &other.key))
+    }
+}
+
 /// Represents a change in the Account AccessList implied by a `BeginTx`,
 /// `EXTCODECOPY`, `EXTCODESIZE`, `EXTCODEHASH` `BALANCE`, `SELFDESTRUCT`,
 /// `*CALL`* or `CREATE*` step.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TxAccessListAccountOp {
     /// Transaction ID: Transaction index in the block starting at 1.
     pub tx_id: usize,
@@ -443,9 +526,112 @@ impl Op for TxAccessListAccountOp {
     }
 }
 
+/// Represents the creation of an account in a transaction, implied by a
+/// `BeginTx` of a creation transaction or a `CREATE*` step, and read by
+/// `SELFDESTRUCT` which only deletes an account created in the same
+/// transaction since EIP-6780.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxCreatedAccountOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Whether the account was created in the transaction.
+    pub is_created: bool,
+    /// Whether the account was created in the transaction before the
+    /// operation.
+    pub is_created_prev: bool,
+}
+
+impl fmt::Debug for TxCreatedAccountOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TxCreatedAccountOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, is_created_prev: {:?}, is_created: {:?}",
+            self.tx_id, self.address, self.is_created_prev, self.is_created
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialOrd for TxCreatedAccountOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxCreatedAccountOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address).cmp(&(&other.tx_id, &other.address))
+    }
+}
+
+impl Op for TxCreatedAccountOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxCreatedAccount(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.is_created, &mut rev.is_created_prev);
+        rev
+    }
+}
+
+/// Represents the self destruction of an account in a transaction, implied by
+/// a `SELFDESTRUCT` step. The account is only deleted at the end of the
+/// transaction, by a `DestructAccount` step which clears the flag.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxDestructedAccountOp {
+    /// Transaction ID: Transaction index in the block starting at 1.
+    pub tx_id: usize,
+    /// Account Address
+    pub address: Address,
+    /// Whether the account is to be deleted at the end of the transaction.
+    pub is_destructed: bool,
+    /// Whether the account was to be deleted at the end of the transaction
+    /// before the operation.
+    pub is_destructed_prev: bool,
+}
+
+impl fmt::Debug for TxDestructedAccountOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TxDestructedAccountOp { ")?;
+        f.write_fmt(format_args!(
+            "tx_id: {:?}, addr: {:?}, is_destructed_prev: {:?}, is_destructed: {:?}",
+            self.tx_id, self.address, self.is_destructed_prev, self.is_destructed
+        ))?;
+        f.write_str(" }")
+    }
+}
+
+impl PartialOrd for TxDestructedAccountOp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TxDestructedAccountOp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.tx_id, &self.address).cmp(&(&other.tx_id, &other.address))
+    }
+}
+
+impl Op for TxDestructedAccountOp {
+    fn into_enum(self) -> OpEnum {
+        OpEnum::TxDestructedAccount(self)
+    }
+
+    fn reverse(&self) -> Self {
+        let mut rev = self.clone();
+        swap(&mut rev.is_destructed, &mut rev.is_destructed_prev);
+        rev
+    }
+}
+
 /// Represents a change in the Storage AccessList implied by an `SSTORE` or
 /// `SLOAD` step of the [`ExecStep`](crate::circuit_input_builder::ExecStep).
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TxAccessListAccountStorageOp {
     /// Transaction ID: Transaction index in the block starting at 1.
     pub tx_id: usize,
@@ -497,7 +683,7 @@ impl Op for TxAccessListAccountStorageOp {
 /// Represents a change in the Transaction Refund AccessList implied by an
 /// `SSTORE`, `STOP`, `RETURN` or `REVERT` step of the
 /// [`ExecStep`](crate::circuit_input_builder::ExecStep).
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TxRefundOp {
     /// Transaction ID: Transaction index in the block starting at 1.
     pub tx_id: usize,
@@ -544,7 +730,7 @@ impl Op for TxRefundOp {
 
 /// Represents a field parameter of the Account that can be accessed via EVM
 /// execution.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AccountField {
     /// Account Nonce
     Nonce,
@@ -561,7 +747,7 @@ pub enum AccountField {
 /// Represents a change in the Account field implied by a `BeginTx`,
 /// `EXTCODECOPY`, `EXTCODESIZE`, `BALANCE`, `SELFDESTRUCT`, `*CALL`*,
 /// `CREATE*`, `STOP`, `RETURN` or `REVERT` step.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccountOp {
     /// Account Address
     pub address: Address,
@@ -627,7 +813,7 @@ impl Op for AccountOp {
 
 /// Represents a field parameter of the CallContext that can be accessed via EVM
 /// execution.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum CallContextField {
     /// RwCounterEndOfReversion
     RwCounterEndOfReversion,
@@ -682,7 +868,7 @@ pub enum CallContextField {
 }
 
 /// Represents an CallContext read/write operation.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CallContextOp {
     /// call_id of CallContext
     pub call_id: usize,
@@ -753,7 +939,7 @@ impl CallContextOp {
 
 /// Represents a field parameter of the TxLog that can be accessed via EVM
 /// execution.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TxLogField {
     /// contract address
     Address,
@@ -766,7 +952,7 @@ pub enum TxLogField {
 }
 
 /// Represents TxLog read/write operation.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TxLogOp {
     /// tx_id of TxLog, starts with 1 in rw table, and it's unique per Tx
     pub tx_id: usize,
@@ -840,7 +1026,7 @@ impl Op for TxLogOp {
 
 /// Represents a field parameter of the TxReceipt that can be accessed via EVM
 /// execution.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TxReceiptField {
     /// flag indicates whether a tx succeed or not
     PostStateOrStatus,
@@ -852,7 +1038,7 @@ pub enum TxReceiptField {
 }
 
 /// Represent a Start padding operation
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct StartOp {}
 
 impl PartialOrd for StartOp {
@@ -878,7 +1064,7 @@ impl Op for StartOp {
 }
 
 /// Represents TxReceipt read/write operation.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TxReceiptOp {
     /// tx_id of TxReceipt
     pub tx_id: usize,
@@ -947,10 +1133,16 @@ pub enum OpEnum {
     TxLog(TxLogOp),
     /// Start
     Start(StartOp),
+    /// TransientStorage
+    TransientStorage(TransientStorageOp),
+    /// TxCreatedAccount
+    TxCreatedAccount(TxCreatedAccountOp),
+    /// TxDestructedAccount
+    TxDestructedAccount(TxDestructedAccountOp),
 }
 
 /// Operation is a Wrapper over a type that implements Op with a RWCounter.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation<T: Op> {
     rwc: RWCounter,
     rw: RW,
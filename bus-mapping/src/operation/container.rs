@@ -1,10 +1,12 @@
 use super::{
     AccountOp, CallContextOp, MemoryOp, Op, OpEnum, Operation, RWCounter, StackOp, StartOp,
-    StorageOp, Target, TxAccessListAccountOp, TxAccessListAccountStorageOp, TxLogOp, TxReceiptOp,
-    TxRefundOp, RW,
+    StorageOp, Target, TransientStorageOp, TxAccessListAccountOp, TxAccessListAccountStorageOp,
+    TxCreatedAccountOp, TxDestructedAccountOp, TxLogOp, TxReceiptOp, TxRefundOp, RW,
 };
 use crate::exec_trace::OperationRef;
+use eth_types::Address;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 /// The `OperationContainer` is meant to store all of the [`Operation`]s that an
 /// [`ExecStep`](crate::circuit_input_builder::ExecStep) performs during its
@@ -20,7 +22,7 @@ use itertools::Itertools;
 /// they have specified.
 /// That serves as a way to get an input with which is easy to work with in
 /// order to construct the State proof.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OperationContainer {
     /// Operations of MemoryOp
     pub memory: Vec<Operation<MemoryOp>>,
@@ -44,6 +46,15 @@ pub struct OperationContainer {
     pub tx_log: Vec<Operation<TxLogOp>>,
     /// Operations of Start
     pub start: Vec<Operation<StartOp>>,
+    /// Operations of TransientStorageOp
+    pub transient_storage: Vec<Operation<TransientStorageOp>>,
+    /// Operations of TxCreatedAccountOp
+    pub tx_created_account: Vec<Operation<TxCreatedAccountOp>>,
+    /// Operations of TxDestructedAccountOp
+    pub tx_destructed_account: Vec<Operation<TxDestructedAccountOp>>,
+    /// Whether the operations only read by the circuits are dropped, see
+    /// [`Self::set_dry_run`].
+    dry_run: bool,
 }
 
 impl Default for OperationContainer {
@@ -68,9 +79,22 @@ impl OperationContainer {
             tx_receipt: Vec::new(),
             tx_log: Vec::new(),
             start: Vec::new(),
+            transient_storage: Vec::new(),
+            tx_created_account: Vec::new(),
+            tx_destructed_account: Vec::new(),
+            dry_run: false,
         }
     }
 
+    /// Drop the memory, stack, call context and start operations on insertion
+    /// instead of storing them, for a dry run of the builder which only counts
+    /// them. The operations read back by the builder, the reversible ones and
+    /// the logs and receipts, are still stored. The references returned for
+    /// the dropped operations point to no operation.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
     /// Inserts an [`Operation`] into the  container returning a lightweight
     /// reference to it in the form of an [`OperationRef`] which points to the
     /// location of the inserted operation inside the corresponding container
@@ -93,6 +117,18 @@ impl OperationContainer {
         reversible: bool,
         op_enum: OpEnum,
     ) -> OperationRef {
+        if self.dry_run {
+            let dropped = match op_enum {
+                OpEnum::Memory(_) => Some(Target::Memory),
+                OpEnum::Stack(_) => Some(Target::Stack),
+                OpEnum::CallContext(_) => Some(Target::CallContext),
+                OpEnum::Start(_) => Some(Target::Start),
+                _ => None,
+            };
+            if let Some(target) = dropped {
+                return OperationRef::from((target, usize::MAX));
+            }
+        }
         match op_enum {
             OpEnum::Memory(op) => {
                 self.memory.push(Operation::new(rwc, rw, op));
@@ -165,9 +201,72 @@ impl OperationContainer {
                 self.start.push(Operation::new(rwc, rw, op));
                 OperationRef::from((Target::Start, self.start.len() - 1))
             }
+            OpEnum::TransientStorage(op) => {
+                self.transient_storage.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((Target::TransientStorage, self.transient_storage.len() - 1))
+            }
+            OpEnum::TxCreatedAccount(op) => {
+                self.tx_created_account.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((Target::TxCreatedAccount, self.tx_created_account.len() - 1))
+            }
+            OpEnum::TxDestructedAccount(op) => {
+                self.tx_destructed_account.push(if reversible {
+                    Operation::new_reversible(rwc, rw, op)
+                } else {
+                    Operation::new(rwc, rw, op)
+                });
+                OperationRef::from((
+                    Target::TxDestructedAccount,
+                    self.tx_destructed_account.len() - 1,
+                ))
+            }
         }
     }
 
+    /// Returns the operation pointed by `op_ref` as an [`OpEnum`], together
+    /// with its [`RWCounter`] and [`RW`], or `None` if `op_ref` is out of
+    /// range.
+    pub fn get(&self, op_ref: &OperationRef) -> Option<(RWCounter, RW, OpEnum)> {
+        fn get_op<T: Op>(ops: &[Operation<T>], idx: usize) -> Option<(RWCounter, RW, OpEnum)> {
+            ops.get(idx)
+                .map(|op| (op.rwc(), op.rw(), op.op().clone().into_enum()))
+        }
+        let idx = op_ref.as_usize();
+        match op_ref.target() {
+            Target::Start => get_op(&self.start, idx),
+            Target::Memory => get_op(&self.memory, idx),
+            Target::Stack => get_op(&self.stack, idx),
+            Target::Storage => get_op(&self.storage, idx),
+            Target::TxAccessListAccount => get_op(&self.tx_access_list_account, idx),
+            Target::TxAccessListAccountStorage => get_op(&self.tx_access_list_account_storage, idx),
+            Target::TxRefund => get_op(&self.tx_refund, idx),
+            Target::Account => get_op(&self.account, idx),
+            Target::CallContext => get_op(&self.call_context, idx),
+            Target::TxReceipt => get_op(&self.tx_receipt, idx),
+            Target::TxLog => get_op(&self.tx_log, idx),
+            Target::TransientStorage => get_op(&self.transient_storage, idx),
+            Target::TxCreatedAccount => get_op(&self.tx_created_account, idx),
+            Target::TxDestructedAccount => get_op(&self.tx_destructed_account, idx),
+        }
+    }
+
+    /// Returns the [`StorageOp`]s on the storage of `address`, in the order
+    /// they were inserted.
+    pub fn storage_ops_for_address(&self, address: Address) -> Vec<&Operation<StorageOp>> {
+        self.storage
+            .iter()
+            .filter(|op| op.op().address == address)
+            .collect()
+    }
+
     /// Returns a sorted vector of all of the [`MemoryOp`]s contained inside of
     /// the container.
     pub fn sorted_memory_word(&self) -> Vec<Operation<MemoryOp>> {
@@ -236,5 +335,19 @@ mod container_test {
         assert_eq!(stack_ref, OperationRef::from((Target::Stack, 0)));
         assert_eq!(memory_ref, OperationRef::from((Target::Memory, 0)));
         assert_eq!(storage_ref, OperationRef::from((Target::Storage, 0)));
+
+        let (rwc, rw, op) = operation_container.get(&memory_ref).unwrap();
+        assert_eq!((rwc, rw), (memory_operation.rwc(), RW::WRITE));
+        assert!(matches!(op, OpEnum::Memory(op) if op == *memory_operation.op()));
+        assert!(operation_container
+            .get(&OperationRef::from((Target::Memory, 1)))
+            .is_none());
+        assert_eq!(
+            operation_container.storage_ops_for_address(Address::zero()),
+            vec![&storage_operation]
+        );
+        assert!(operation_container
+            .storage_ops_for_address(Address::repeat_byte(0xff))
+            .is_empty());
     }
 }
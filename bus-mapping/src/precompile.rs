@@ -2,18 +2,42 @@
 
 use eth_types::{evm_types::GasCost, Address, ToBigEndian, Word};
 use revm_precompile::{Precompile, Precompiles};
+use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 
 use crate::circuit_input_builder::{EcMulOp, EcPairingOp};
 
+/// Number of precompiled contracts, living at addresses `0x01..=PRECOMPILE_COUNT`.
+#[cfg(not(feature = "cancun"))]
+pub const PRECOMPILE_COUNT: u64 = 9;
+/// Number of precompiled contracts, living at addresses `0x01..=PRECOMPILE_COUNT`.
+#[cfg(feature = "cancun")]
+pub const PRECOMPILE_COUNT: u64 = 10;
+
+/// Number of field elements in a blob, returned by the point evaluation precompile.
+pub const FIELD_ELEMENTS_PER_BLOB: u64 = 4096;
+/// Modulus of the BLS12-381 scalar field, returned by the point evaluation precompile.
+pub const BLS_MODULUS: &str = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+
 /// Check if address is a precompiled or not.
 pub fn is_precompiled(address: &Address) -> bool {
-    Precompiles::berlin()
-        .get(address.as_fixed_bytes())
-        .is_some()
+    address.0[0..19] == [0u8; 19] && (1..=PRECOMPILE_COUNT).contains(&(address.0[19] as u64))
 }
 
-pub(crate) fn execute_precompiled(address: &Address, input: &[u8], gas: u64) -> (Vec<u8>, u64) {
+/// Execute the precompiled contract at `address`. `is_success` is the outcome of the call in the
+/// trace and is only used for precompiles that revm can't run, i.e. the KZG point evaluation.
+#[cfg_attr(not(feature = "cancun"), allow(unused_variables))]
+pub(crate) fn execute_precompiled(
+    address: &Address,
+    input: &[u8],
+    gas: u64,
+    is_success: bool,
+) -> (Vec<u8>, u64) {
+    #[cfg(feature = "cancun")]
+    if *address == PrecompileCalls::PointEvaluation.into() {
+        return execute_point_evaluation(input, gas, is_success);
+    }
+
     let Some(Precompile::Standard(precompile_fn)) = Precompiles::berlin()
         .get(address.as_fixed_bytes())  else {
         panic!("calling non-exist precompiled contract address")
@@ -25,8 +49,25 @@ pub(crate) fn execute_precompiled(address: &Address, input: &[u8], gas: u64) ->
     }
 }
 
+/// The point evaluation precompile verifies a KZG proof over BLS12-381, which revm-precompile
+/// 2.0 does not implement. Its output is constant on success, so it is rebuilt from the trace.
+/// The EVM circuit has no KZG sub-circuit to check it against, so only failed calls to 0x0a can
+/// be proven, through `ErrorPrecompileFailed`.
+#[cfg(feature = "cancun")]
+fn execute_point_evaluation(input: &[u8], gas: u64, is_success: bool) -> (Vec<u8>, u64) {
+    let gas_cost = GasCost::PRECOMPILE_POINT_EVALUATION.as_u64();
+    if !is_success || input.len() != 192 || gas < gas_cost {
+        return (vec![], gas);
+    }
+    let output = std::iter::empty()
+        .chain(Word::from(FIELD_ELEMENTS_PER_BLOB).to_be_bytes())
+        .chain(Word::from_str_radix(BLS_MODULUS, 16).unwrap().to_be_bytes())
+        .collect();
+    (output, gas_cost)
+}
+
 /// Addresses of the precompiled contracts.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter, Serialize, Deserialize)]
 pub enum PrecompileCalls {
     /// Elliptic Curve Recovery
     Ecrecover = 0x01,
@@ -46,6 +87,8 @@ pub enum PrecompileCalls {
     Bn128Pairing = 0x08,
     /// Compression function
     Blake2F = 0x09,
+    /// KZG point evaluation
+    PointEvaluation = 0x0a,
 }
 
 impl Default for PrecompileCalls {
@@ -86,7 +129,9 @@ impl From<u8> for PrecompileCalls {
             0x07 => Self::Bn128Mul,
             0x08 => Self::Bn128Pairing,
             0x09 => Self::Blake2F,
-            _ => unreachable!("precompile contracts only from 0x01 to 0x09"),
+            #[cfg(feature = "cancun")]
+            0x0a => Self::PointEvaluation,
+            _ => unreachable!("precompile contracts only from 0x01 to {PRECOMPILE_COUNT:#04x}"),
         }
     }
 }
@@ -104,6 +149,7 @@ impl PrecompileCalls {
             Self::Bn128Mul => GasCost::PRECOMPILE_BN256MUL,
             Self::Bn128Pairing => GasCost::PRECOMPILE_BN256PAIRING,
             Self::Blake2F => GasCost::PRECOMPILE_BLAKE2F,
+            Self::PointEvaluation => GasCost::PRECOMPILE_POINT_EVALUATION,
         }
     }
 
@@ -123,7 +169,7 @@ impl PrecompileCalls {
 }
 
 /// Auxiliary data for Ecrecover
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcrecoverAuxData {
     /// Keccak hash of the message being signed.
     pub msg_hash: Word,
@@ -169,7 +215,7 @@ impl EcrecoverAuxData {
 }
 
 /// Auxiliary data for EcAdd, i.e. P + Q = R
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcAddAuxData {
     /// x co-ordinate of the first point.
     pub p_x: Word,
@@ -202,7 +248,7 @@ impl EcAddAuxData {
 }
 
 /// Auxiliary data for EcMul, i.e. s * P = R
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcMulAuxData {
     /// x co-ordinate of the point.
     pub p_x: Word,
@@ -237,11 +283,11 @@ impl EcMulAuxData {
 }
 
 /// Auxiliary data for EcPairing.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EcPairingAuxData(pub EcPairingOp);
 
 /// Auxiliary data attached to an internal state for precompile verification.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PrecompileAuxData {
     /// Ecrecover.
     Ecrecover(EcrecoverAuxData),
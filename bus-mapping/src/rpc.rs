@@ -1,13 +1,15 @@
 //! Module which contains all the RPC calls that are needed at any point to
 //! query a Geth node in order to get a Block, Tx or Trace info.
 
-use crate::Error;
+use crate::{state_db::ProofFormat, Error};
 use eth_types::{
     Address, Block, Bytes, EIP1186ProofResponse, GethExecTrace, Hash, ResultGethExecTraces,
     Transaction, Word, H256, U64,
 };
 pub use ethers_core::types::BlockNumber;
+use ethers_core::types::{TransactionReceipt, TransactionRequest};
 use ethers_providers::JsonRpcClient;
+use futures::{stream, StreamExt, TryStreamExt};
 use serde::Serialize;
 
 use crate::util::CHECK_MEM_STRICT;
@@ -102,6 +104,18 @@ impl<P: JsonRpcClient> GethClient<P> {
             .await
             .map_err(|e| Error::JSONRpcError(e.into()))
     }
+    /// Calls `eth_getBlockReceipts` via JSON-RPC returning the
+    /// [`TransactionReceipt`]s of the txs of the block, in order.
+    pub async fn get_block_receipts(
+        &self,
+        block_num: BlockNumber,
+    ) -> Result<Vec<TransactionReceipt>, Error> {
+        let num = serialize(&block_num);
+        self.0
+            .request("eth_getBlockReceipts", [num])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))
+    }
     /// ..
     pub async fn get_tx_by_hash(&self, hash: H256) -> Result<Transaction, Error> {
         let hash = serialize(&hash);
@@ -160,6 +174,27 @@ impl<P: JsonRpcClient> GethClient<P> {
         Ok(vec![resp])
     }
 
+    /// Calls `debug_traceCall` via JSON-RPC returning the [`GethExecTrace`]
+    /// of `tx` executed on top of the state after `block_num`, in the context
+    /// of that block, without `tx` being signed nor mined.
+    pub async fn trace_call(
+        &self,
+        tx: TransactionRequest,
+        block_num: BlockNumber,
+    ) -> Result<GethExecTrace, Error> {
+        let tx = serialize(&tx);
+        let num = serialize(&block_num);
+        let cfg = GethLoggerConfig {
+            enable_memory: *CHECK_MEM_STRICT,
+            ..Default::default()
+        };
+        let cfg = serialize(&cfg);
+        self.0
+            .request("debug_traceCall", [tx, num, cfg])
+            .await
+            .map_err(|e| Error::JSONRpcError(e.into()))
+    }
+
     /// Calls `eth_getCode` via JSON-RPC returning a contract code
     pub async fn get_code(
         &self,
@@ -184,16 +219,65 @@ impl<P: JsonRpcClient> GethClient<P> {
         account: Address,
         keys: Vec<Word>,
         block_num: BlockNumber,
+    ) -> Result<EIP1186ProofResponse, Error> {
+        self.get_proof_in_format(ProofFormat::Mpt, account, keys, block_num)
+            .await
+    }
+
+    /// Calls the JSON-RPC method returning proofs in `format`, `eth_getProof`
+    /// or `scroll_getProof`, see [`get_proof`](Self::get_proof).
+    pub async fn get_proof_in_format(
+        &self,
+        format: ProofFormat,
+        account: Address,
+        keys: Vec<Word>,
+        block_num: BlockNumber,
     ) -> Result<EIP1186ProofResponse, Error> {
         let account = serialize(&account);
         let keys = serialize(&keys);
         let num = serialize(&block_num);
         self.0
-            .request("eth_getProof", [account, keys, num])
+            .request(format.rpc_method(), [account, keys, num])
             .await
             .map_err(|e| Error::JSONRpcError(e.into()))
     }
 
+    /// Calls the JSON-RPC method returning proofs in `format` for each
+    /// account and its storage keys in `accounts`, keeping at most
+    /// `max_concurrent` requests in flight.  The proofs are returned in the
+    /// order of `accounts`.
+    pub async fn get_proofs(
+        &self,
+        accounts: Vec<(Address, Vec<Word>)>,
+        block_num: BlockNumber,
+        format: ProofFormat,
+        max_concurrent: usize,
+    ) -> Result<Vec<EIP1186ProofResponse>, Error> {
+        stream::iter(accounts)
+            .map(|(account, keys)| self.get_proof_in_format(format, account, keys, block_num))
+            .buffered(max_concurrent.max(1))
+            .try_collect()
+            .await
+    }
+
+    /// Calls `eth_getCode` for each of `addresses`, keeping at most
+    /// `max_concurrent` requests in flight.  The codes are returned in the
+    /// order of `addresses`.
+    pub async fn get_codes(
+        &self,
+        addresses: Vec<Address>,
+        block_num: BlockNumber,
+        max_concurrent: usize,
+    ) -> Result<Vec<(Address, Vec<u8>)>, Error> {
+        stream::iter(addresses)
+            .map(|address| async move {
+                Ok::<_, Error>((address, self.get_code(address, block_num).await?))
+            })
+            .buffered(max_concurrent.max(1))
+            .try_collect()
+            .await
+    }
+
     /// Calls `miner_stop` via JSON-RPC, which makes the node stop mining
     /// blocks.  Useful for integration tests.
     pub async fn miner_stop(&self) -> Result<(), Error> {
@@ -2,12 +2,21 @@
 //! Ethereum State Trie.
 
 use crate::{
+    circuit_input_builder::AccessSet,
     precompile::is_precompiled,
-    util::{hash_code, KECCAK_CODE_HASH_ZERO},
+    util::{hash_code, hash_code_keccak, KECCAK_CODE_HASH_ZERO},
+    Error,
 };
 use eth_types::{Address, Hash, Word, H256, U256};
 use lazy_static::lazy_static;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
+
+mod proof;
+pub use proof::{
+    decode_zktrie_proof, verify_account_proof, verify_proofs, verify_storage_proofs,
+    verify_zktrie_account_proof, verify_zktrie_storage_proofs, ProofFormat, EMPTY_TRIE_ROOT,
+};
 
 lazy_static! {
     static ref ACCOUNT_ZERO: Account = Account::zero();
@@ -22,34 +31,123 @@ lazy_static! {
 
 const VALUE_ZERO: Word = Word::zero();
 
-/// Memory storage for contract code by code hash.
-#[derive(Debug)]
-pub struct CodeDB(pub HashMap<Hash, Vec<u8>>);
-
-impl Clone for CodeDB {
-    fn clone(&self) -> Self {
-        CodeDB(self.0.clone())
-    }
-}
-
-impl Default for CodeDB {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Memory storage for contract code by code hash, ordered by hash so that
+/// iterating it is deterministic. A code deployed at several accounts is
+/// stored once, with the accounts referencing it, so that the code no longer
+/// referenced can be dropped by [`CodeDB::retain_reachable`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodeDB {
+    codes: BTreeMap<Hash, Vec<u8>>,
+    /// Accounts referencing each code, by code hash
+    refs: BTreeMap<Hash, BTreeSet<Address>>,
+    /// Code hash referenced by each account
+    owners: BTreeMap<Address, Hash>,
+    /// Codes inserted with a hash given by the caller, which is only checked
+    /// by [`CodeDB::verify_hashes`]
+    unverified: BTreeSet<Hash>,
 }
 
 impl CodeDB {
     /// Create a new empty Self.
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self::default()
     }
     /// Insert code indexed by code hash, and return the code hash.
     pub fn insert(&mut self, code: Vec<u8>) -> Hash {
         let hash = Self::hash(&code);
 
-        self.0.insert(hash, code);
+        self.codes.entry(hash).or_insert(code);
+        self.unverified.remove(&hash);
         hash
     }
+    /// Insert code whose hash is already known, e.g. from an account proof,
+    /// without hashing it. Code which is already stored is kept.
+    pub fn insert_with_hash(&mut self, hash: Hash, code: Vec<u8>) {
+        if let Entry::Vacant(entry) = self.codes.entry(hash) {
+            entry.insert(code);
+            self.unverified.insert(hash);
+        }
+    }
+    /// Insert code indexed by code hash, and return the code hash. The code is
+    /// only copied if it's not stored yet.
+    pub fn insert_slice(&mut self, code: &[u8]) -> Hash {
+        let hash = Self::hash(code);
+
+        self.codes.entry(hash).or_insert_with(|| code.to_vec());
+        self.unverified.remove(&hash);
+        hash
+    }
+    /// Record that the account at `address` has the code of hash `hash`,
+    /// instead of the code it referenced before, if any.
+    pub fn add_ref(&mut self, address: Address, hash: Hash) {
+        self.remove_ref(address);
+        self.owners.insert(address, hash);
+        self.refs.entry(hash).or_default().insert(address);
+    }
+    /// Record that the account at `address` no longer has a code, e.g. once
+    /// destructed.
+    pub fn remove_ref(&mut self, address: Address) {
+        let hash = match self.owners.remove(&address) {
+            Some(hash) => hash,
+            None => return,
+        };
+        if let Some(accounts) = self.refs.get_mut(&hash) {
+            accounts.remove(&address);
+            if accounts.is_empty() {
+                self.refs.remove(&hash);
+            }
+        }
+    }
+    /// Number of accounts referencing the code of hash `hash`.
+    pub fn ref_count(&self, hash: &Hash) -> usize {
+        self.refs.get(hash).map_or(0, BTreeSet::len)
+    }
+    /// Code of hash `hash`, if stored.
+    pub fn get(&self, hash: &Hash) -> Option<&Vec<u8>> {
+        self.codes.get(hash)
+    }
+    /// Stored codes, by code hash.
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash, &Vec<u8>)> {
+        self.codes.iter()
+    }
+    /// Number of stored codes.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+    /// Return if no code is stored.
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+    /// Drop the codes which are neither in `keep`, like the
+    /// [`Block::bytecode_hashes`](crate::circuit_input_builder::Block::bytecode_hashes)
+    /// needed by the bytecode circuit, nor the code of an account of
+    /// `access_set` in `sdb`, e.g. between the blocks of a chunk, with the
+    /// accesses of the next blocks. The references are rebuilt from `sdb`, so
+    /// that a destructed or redeployed account no longer keeps its previous
+    /// code. Return the number of dropped codes.
+    pub fn retain_reachable(
+        &mut self,
+        sdb: &StateDB,
+        access_set: &AccessSet,
+        keep: &BTreeSet<Hash>,
+    ) -> usize {
+        self.refs.clear();
+        self.owners.clear();
+        for address in access_set.state.keys().chain(access_set.code.iter()) {
+            let (found, account) = sdb.get_account(address);
+            if found {
+                self.add_ref(*address, account.code_hash);
+            }
+        }
+
+        let refs = &self.refs;
+        let len = self.codes.len();
+        self.codes
+            .retain(|hash, _| refs.contains_key(hash) || keep.contains(hash));
+        let codes = &self.codes;
+        self.unverified.retain(|hash| codes.contains_key(hash));
+        len - self.codes.len()
+    }
     /// Specify code hash for empty code (nil)
     pub fn empty_code_hash() -> Hash {
         *EMPTY_CODE_HASH
@@ -59,11 +157,38 @@ impl CodeDB {
     pub fn hash(code: &[u8]) -> Hash {
         H256(hash_code(code).into())
     }
+
+    /// Check the hash of the codes inserted with a given hash against the
+    /// code DB keys, and the keccak code hash of the accounts in `sdb` whose
+    /// code is stored.
+    pub fn verify_hashes(&self, sdb: &StateDB) -> Result<(), Error> {
+        for hash in self.unverified.iter() {
+            let computed = Self::hash(&self.codes[hash]);
+            if computed != *hash {
+                return Err(Error::CodeHashMismatch(None, *hash, computed));
+            }
+        }
+        for (address, account) in sdb.state.iter() {
+            let code = match self.codes.get(&account.code_hash) {
+                Some(code) => code,
+                None => continue,
+            };
+            let keccak_code_hash = hash_code_keccak(code);
+            if account.keccak_code_hash != keccak_code_hash {
+                return Err(Error::CodeHashMismatch(
+                    Some(*address),
+                    account.keccak_code_hash,
+                    keccak_code_hash,
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Account of the Ethereum State Trie, which contains an in-memory key-value
 /// database that represents the Account Storage Trie.
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct Account {
     /// Nonce
     pub nonce: Word,
@@ -99,10 +224,24 @@ impl Account {
             && self.code_hash.eq(&CodeDB::empty_code_hash())
             && self.code_size.is_zero()
     }
+
+    /// Return if creating a contract at the address of this account fails
+    /// with an address collision, i.e. the account has a non-zero nonce or
+    /// non-empty code (EIP-684).
+    pub fn is_create_collision(&self) -> bool {
+        !self.nonce.is_zero()
+            || !(self.code_hash.is_zero() || self.code_hash.eq(&CodeDB::empty_code_hash()))
+    }
 }
 
 /// In-memory key-value database that represents the Ethereum State Trie.
-#[derive(Debug, Clone, Default)]
+///
+/// An account exists when it's in the state, even if it's empty, which is
+/// distinct from an account absent from the state. An account which is
+/// absent or empty is dead (EIP-161): the State Circuit encodes it as a
+/// non-existing account, i.e. with code_hash=0, and it's deleted at the end
+/// of the transaction which touched it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StateDB {
     state: HashMap<Address, Account>,
 
@@ -114,13 +253,48 @@ pub struct StateDB {
     // The reason why we need this is that EVM needs committed state, namely
     // state before current transaction, to calculate gas cost for some opcodes like sstore.
     // So both dirty storage and committed storage are needed.
+    #[serde(with = "map_entries")]
     dirty_storage: HashMap<(Address, Word), Word>,
-    // Accounts that have been through `SELFDESTRUCT` under the situation that `is_persistent` is
-    // `true`. These accounts will be reset once `commit_tx` is called.
+    // Transient storage (EIP-1153), discarded when current transaction finishes.
+    #[serde(with = "map_entries")]
+    transient_storage: HashMap<(Address, Word), Word>,
+    // Accounts that have been through `SELFDESTRUCT` in the current transaction. They are kept
+    // until its end, like in geth, and deleted once `commit_tx` is called.
     destructed_account: HashSet<Address>,
+    // Accounts created in the current transaction, which can be deleted by `SELFDESTRUCT` since
+    // EIP-6780. These will be cleared once `commit_tx` is called.
+    created_account: HashSet<Address>,
+    // Accounts touched in the current transaction, which are deleted from `state` by `commit_tx`
+    // if they are empty (EIP-161).
+    touched_account: HashSet<Address>,
     refund: u64,
 }
 
+/// Serde of a map as the list of its entries, for the maps keyed by tuples,
+/// which JSON objects can't be keyed by.
+mod map_entries {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::{collections::HashMap, hash::Hash};
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Vec::<(K, V)>::deserialize(deserializer).map(|entries| entries.into_iter().collect())
+    }
+}
+
 impl StateDB {
     /// Create an empty Self
     pub fn new() -> Self {
@@ -141,6 +315,18 @@ impl StateDB {
         }
     }
 
+    /// Check whether the [`Account`] at `addr` is dead, i.e. it doesn't exist
+    /// or it's empty (EIP-161).
+    pub fn is_dead(&self, addr: &Address) -> bool {
+        self.state.get(addr).map_or(true, Account::is_empty)
+    }
+
+    /// Iterate over the [`Account`]s in the state. Storage writes of the
+    /// current transaction which aren't committed yet are not included.
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.state.iter()
+    }
+
     /// Get a mutable reference to the [`Account`] at `addr`.  If the
     /// [`Account`] is not found in the state, a zero one will be inserted
     /// and returned along with false.
@@ -202,6 +388,20 @@ impl StateDB {
         self.dirty_storage.insert((*addr, *key), *value);
     }
 
+    /// Get the transient storage value at `addr` and `key`. Returns zero if it
+    /// wasn't written during the current transaction.
+    pub fn get_transient_storage(&self, addr: &Address, key: &Word) -> &Word {
+        self.transient_storage
+            .get(&(*addr, *key))
+            .unwrap_or(&VALUE_ZERO)
+    }
+
+    /// Set transient storage value at `addr` and `key`.
+    /// Transient storage is cleared in `commit_tx` method.
+    pub fn set_transient_storage(&mut self, addr: &Address, key: &Word, value: &Word) {
+        self.transient_storage.insert((*addr, *key), *value);
+    }
+
     /// Get balance of account with the given address.
     pub fn get_balance(&self, addr: &Address) -> Word {
         let (_, account) = self.get_account(addr);
@@ -259,12 +459,55 @@ impl StateDB {
         debug_assert!(exist);
     }
 
-    /// Set account as self destructed.
+    /// Set account as self destructed, to be deleted at the end of the
+    /// current transaction.
     pub fn destruct_account(&mut self, addr: Address) {
-        self.state.insert(addr, Account::zero());
         self.destructed_account.insert(addr);
     }
 
+    /// Remove `addr` from the accounts self destructed in the current
+    /// transaction.
+    pub fn remove_destructed_account(&mut self, addr: &Address) {
+        let exist = self.destructed_account.remove(addr);
+        debug_assert!(exist);
+    }
+
+    /// Accounts self destructed in the current transaction, in ascending
+    /// order.
+    pub fn destructed_accounts(&self) -> Vec<Address> {
+        let mut addrs: Vec<_> = self.destructed_account.iter().copied().collect();
+        addrs.sort();
+        addrs
+    }
+
+    /// Check whether `addr` has been self destructed in the current
+    /// transaction.
+    pub fn is_destructed(&self, addr: &Address) -> bool {
+        self.destructed_account.contains(addr)
+    }
+
+    /// Set account as created in the current transaction.
+    pub fn add_created_account(&mut self, addr: Address) {
+        self.created_account.insert(addr);
+    }
+
+    /// Remove `addr` from the accounts created in the current transaction.
+    pub fn remove_created_account(&mut self, addr: &Address) {
+        let exist = self.created_account.remove(addr);
+        debug_assert!(exist);
+    }
+
+    /// Check whether `addr` has been created in the current transaction.
+    pub fn is_created_in_tx(&self, addr: &Address) -> bool {
+        self.created_account.contains(addr)
+    }
+
+    /// Set account as touched in the current transaction, so that it's deleted
+    /// by [`StateDB::commit_tx`] if it's empty then.
+    pub fn touch_account(&mut self, addr: Address) {
+        self.touched_account.insert(addr);
+    }
+
     /// Retrieve refund.
     pub fn refund(&self) -> u64 {
         self.refund
@@ -275,7 +518,9 @@ impl StateDB {
         self.refund = value;
     }
 
-    /// Clear access list and refund, and commit dirty storage.
+    /// Clear access list, transient storage, created accounts and refund, delete
+    /// the self destructed accounts and the touched empty accounts (EIP-161),
+    /// and commit dirty storage.
     /// It should be invoked before processing
     /// with new transaction with the same [`StateDB`].
     pub fn commit_tx(&mut self) {
@@ -286,10 +531,16 @@ impl StateDB {
             *ptr = value;
         }
         self.dirty_storage = HashMap::new();
-        for addr in self.destructed_account.clone() {
-            let (_, account) = self.get_account_mut(&addr);
-            *account = ACCOUNT_ZERO.clone();
+        self.transient_storage = HashMap::new();
+        for addr in std::mem::take(&mut self.destructed_account) {
+            self.state.remove(&addr);
+        }
+        for addr in std::mem::take(&mut self.touched_account) {
+            if self.is_dead(&addr) {
+                self.state.remove(&addr);
+            }
         }
+        self.created_account = HashSet::new();
         self.refund = 0;
     }
 }
@@ -353,4 +604,211 @@ mod statedb_tests {
         assert!(found);
         assert_eq!(value, &Word::from(102));
     }
+
+    #[test]
+    fn transient_storage_is_cleared_by_commit_tx() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let mut statedb = StateDB::new();
+        assert_eq!(
+            statedb.get_transient_storage(&addr, &Word::from(1)),
+            &Word::zero()
+        );
+
+        statedb.set_transient_storage(&addr, &Word::from(1), &Word::from(42));
+        assert_eq!(
+            statedb.get_transient_storage(&addr, &Word::from(1)),
+            &Word::from(42)
+        );
+        // transient storage is not visible through persistent storage
+        assert!(!statedb.get_storage(&addr, &Word::from(1)).0);
+
+        statedb.commit_tx();
+        assert_eq!(
+            statedb.get_transient_storage(&addr, &Word::from(1)),
+            &Word::zero()
+        );
+    }
+
+    #[test]
+    fn created_and_destructed_accounts_are_cleared_by_commit_tx() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let mut statedb = StateDB::new();
+        statedb.set_account(
+            &addr,
+            Account {
+                balance: Word::one(),
+                ..Account::zero()
+            },
+        );
+        statedb.add_created_account(addr);
+        statedb.destruct_account(addr);
+        assert!(statedb.is_created_in_tx(&addr));
+        assert!(statedb.is_destructed(&addr));
+        // the account is only deleted at the end of the tx
+        assert!(statedb.get_account(&addr).0);
+        assert_eq!(statedb.destructed_accounts(), vec![addr]);
+
+        statedb.commit_tx();
+        assert!(!statedb.is_created_in_tx(&addr));
+        assert!(!statedb.is_destructed(&addr));
+        assert!(!statedb.get_account(&addr).0);
+
+        // an account created again after its destruction isn't reset
+        let (_, account) = statedb.get_account_mut(&addr);
+        account.nonce = Word::one();
+        statedb.commit_tx();
+        assert_eq!(statedb.get_account(&addr).1.nonce, Word::one());
+    }
+
+    #[test]
+    fn touched_empty_accounts_are_deleted_by_commit_tx() {
+        let addr_a = address!("0x0000000000000000000000000000000000000001");
+        let addr_b = address!("0x0000000000000000000000000000000000000002");
+        let addr_c = address!("0x0000000000000000000000000000000000000003");
+        let mut statedb = StateDB::new();
+        // an existing empty account isn't a non-existing one, but both are dead
+        statedb.set_account(&addr_a, Account::zero());
+        statedb.set_account(
+            &addr_b,
+            Account {
+                balance: Word::one(),
+                ..Account::zero()
+            },
+        );
+        assert!(statedb.get_account(&addr_a).0);
+        assert!(!statedb.get_account(&addr_c).0);
+        assert!(statedb.is_dead(&addr_a));
+        assert!(!statedb.is_dead(&addr_b));
+        assert!(statedb.is_dead(&addr_c));
+
+        // an untouched empty account is kept
+        statedb.commit_tx();
+        assert!(statedb.get_account(&addr_a).0);
+
+        for addr in [addr_a, addr_b, addr_c] {
+            statedb.touch_account(addr);
+        }
+        statedb.commit_tx();
+        assert!(!statedb.get_account(&addr_a).0);
+        assert!(statedb.get_account(&addr_b).0);
+        assert!(!statedb.get_account(&addr_c).0);
+
+        // the touched accounts are cleared by `commit_tx`
+        statedb.set_account(&addr_a, Account::zero());
+        statedb.commit_tx();
+        assert!(statedb.get_account(&addr_a).0);
+    }
+
+    #[test]
+    fn codedb_verify_hashes() {
+        let addr = address!("0x0000000000000000000000000000000000000001");
+        let code = vec![0x60, 0x01, 0x60, 0x02, 0x01];
+        let mut code_db = CodeDB::new();
+        let code_hash = code_db.insert(code.clone());
+        let mut statedb = StateDB::new();
+        statedb.set_account(
+            &addr,
+            Account {
+                code_hash,
+                keccak_code_hash: hash_code_keccak(&code),
+                code_size: code.len().into(),
+                ..Account::zero()
+            },
+        );
+        assert!(code_db.verify_hashes(&statedb).is_ok());
+
+        // Wrong keccak code hash in the account
+        statedb.get_account_mut(&addr).1.keccak_code_hash = *KECCAK_CODE_HASH_ZERO;
+        assert!(matches!(
+            code_db.verify_hashes(&statedb),
+            Err(Error::CodeHashMismatch(Some(a), _, _)) if a == addr
+        ));
+
+        // Code stored under a wrong hash
+        let mut code_db = CodeDB::new();
+        code_db.insert_with_hash(H256::zero(), code);
+        assert!(matches!(
+            code_db.verify_hashes(&StateDB::new()),
+            Err(Error::CodeHashMismatch(None, _, _))
+        ));
+    }
+
+    #[test]
+    fn codedb_dedup_and_retain_reachable() {
+        let (addr_a, addr_b, addr_c) = (
+            address!("0x0000000000000000000000000000000000000001"),
+            address!("0x0000000000000000000000000000000000000002"),
+            address!("0x0000000000000000000000000000000000000003"),
+        );
+        let mut code_db = CodeDB::new();
+        let mut statedb = StateDB::new();
+        let shared = code_db.insert(vec![0x60, 0x01]);
+        assert_eq!(code_db.insert_slice(&[0x60, 0x01]), shared);
+        let other = code_db.insert(vec![0x60, 0x02]);
+        for (addr, code_hash) in [(addr_a, shared), (addr_b, shared), (addr_c, other)] {
+            code_db.add_ref(addr, code_hash);
+            statedb.set_account(
+                &addr,
+                Account {
+                    code_hash,
+                    ..Account::zero()
+                },
+            );
+        }
+        // an init code, referenced by no account
+        let init_code = code_db.insert(vec![0x60, 0x03]);
+        assert_eq!(code_db.len(), 3);
+        assert_eq!(code_db.ref_count(&shared), 2);
+
+        // a redeployed account references its new code only
+        code_db.add_ref(addr_c, shared);
+        assert_eq!(code_db.ref_count(&shared), 3);
+        assert_eq!(code_db.ref_count(&other), 0);
+        code_db.add_ref(addr_c, other);
+        code_db.remove_ref(addr_a);
+        assert_eq!(code_db.ref_count(&shared), 1);
+
+        // the code of the destructed account is dropped, while the init code
+        // needed by the bytecode circuit is kept
+        statedb.destruct_account(addr_c);
+        statedb.commit_tx();
+        let mut access_set = AccessSet::default();
+        access_set.code.insert(addr_b);
+        access_set.code.insert(addr_c);
+        let keep = BTreeSet::from([init_code]);
+        assert_eq!(code_db.retain_reachable(&statedb, &access_set, &keep), 1);
+        assert_eq!(code_db.len(), 2);
+        assert_eq!(code_db.ref_count(&shared), 1);
+        assert_eq!(code_db.get(&other), None);
+
+        // the code of an account no longer accessed is dropped
+        access_set.code.remove(&addr_b);
+        assert_eq!(code_db.retain_reachable(&statedb, &access_set, &keep), 1);
+        assert_eq!(code_db.get(&shared), None);
+        assert!(code_db.get(&init_code).is_some());
+    }
+
+    #[test]
+    fn codedb_lazy_verification() {
+        let code = vec![0x60, 0x01, 0x60, 0x02, 0x01];
+        let mut code_db = CodeDB::new();
+        code_db.insert_with_hash(H256::zero(), code.clone());
+        let code_hash = code_db.insert(code);
+        assert!(code_db.verify_hashes(&StateDB::new()).is_err());
+
+        // the unreferenced code under the wrong hash is dropped
+        let mut statedb = StateDB::new();
+        statedb.set_account(
+            &Address::zero(),
+            Account {
+                code_hash,
+                keccak_code_hash: hash_code_keccak(&code_db.codes[&code_hash]),
+                ..Account::zero()
+            },
+        );
+        let mut access_set = AccessSet::default();
+        access_set.code.insert(Address::zero());
+        code_db.retain_reachable(&statedb, &access_set, &BTreeSet::new());
+        assert!(code_db.verify_hashes(&statedb).is_ok());
+    }
 }
@@ -0,0 +1,359 @@
+//! Verification of EIP-1186 (`eth_getProof`) account and storage proofs
+//! against a state root of the Ethereum Merkle Patricia Trie, or of Scroll's
+//! zkTrie.
+
+use eth_types::{EIP1186ProofResponse, Hash, ToBigEndian, H256, U256};
+use ethers_core::utils::{
+    keccak256,
+    rlp::{DecoderError, Rlp},
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{util::KECCAK_CODE_HASH_ZERO, Error};
+
+mod zktrie;
+pub use zktrie::{decode_zktrie_proof, verify_zktrie_account_proof, verify_zktrie_storage_proofs};
+
+/// Root of the empty trie, i.e. keccak256(rlp(""))
+pub static EMPTY_TRIE_ROOT: Lazy<Hash> = Lazy::new(|| H256(keccak256([0x80])));
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decode a hex-prefix encoded path into its nibbles and leaf flag.
+fn decode_path(path: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let first = *path.first()?;
+    let is_leaf = first & 0x20 != 0;
+    let mut path_nibbles = nibbles(path);
+    // drop the flag nibble, and the padding nibble of an even path
+    path_nibbles.drain(..if first & 0x10 != 0 { 1 } else { 2 });
+    Some((path_nibbles, is_leaf))
+}
+
+/// Walk `proof` from `root` along `key` and return the value stored at `key`,
+/// or `None` if the proof shows that the key is absent. Errors describe why
+/// the proof is rejected.
+fn verify_proof(
+    root: H256,
+    key: &[u8],
+    proof: &[impl AsRef<[u8]>],
+) -> Result<Option<Vec<u8>>, String> {
+    let key = nibbles(key);
+    let mut expected = root.as_bytes().to_vec();
+    let mut pos = 0;
+    if proof.is_empty() && root == *EMPTY_TRIE_ROOT {
+        return Ok(None);
+    }
+    for (index, node) in proof.iter().enumerate() {
+        let invalid_node = |err: DecoderError| format!("invalid node {index}: {err:?}");
+        let node = node.as_ref();
+        // children shorter than 32 bytes are embedded in their parent
+        let referenced = if expected.len() == 32 {
+            keccak256(node).as_slice() == expected.as_slice()
+        } else {
+            node == expected.as_slice()
+        };
+        if !referenced {
+            return Err(format!("node {index} is not referenced by its parent"));
+        }
+        let rlp = Rlp::new(node);
+        let child = match rlp.item_count().map_err(invalid_node)? {
+            17 => {
+                if pos == key.len() {
+                    let value = rlp.at(16).and_then(|v| v.data()).map_err(invalid_node)?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                }
+                let child = rlp.at(key[pos] as usize).map_err(invalid_node)?;
+                pos += 1;
+                child
+            }
+            2 => {
+                let path = rlp.at(0).and_then(|p| p.data()).map_err(invalid_node)?;
+                let (path, is_leaf) = decode_path(path)
+                    .ok_or_else(|| invalid_node(DecoderError::Custom("empty path")))?;
+                if !key[pos..].starts_with(&path) || (is_leaf && key.len() - pos != path.len()) {
+                    return Ok(None);
+                }
+                if is_leaf {
+                    let value = rlp.at(1).and_then(|v| v.data()).map_err(invalid_node)?;
+                    return Ok(Some(value.to_vec()));
+                }
+                pos += path.len();
+                rlp.at(1).map_err(invalid_node)?
+            }
+            _ => return Err(invalid_node(DecoderError::RlpIncorrectListLen)),
+        };
+        expected = if child.is_list() {
+            child.as_raw().to_vec()
+        } else {
+            let hash = child.data().map_err(invalid_node)?;
+            if hash.is_empty() {
+                return Ok(None);
+            }
+            hash.to_vec()
+        };
+    }
+    Err("proof ends before the key".to_string())
+}
+
+/// Check the account proof of an `eth_getProof` response against
+/// `state_root`: the proof must lead to an account with the nonce, balance,
+/// storage root and keccak code hash of the response, or, for an absent
+/// account, the response must describe an empty account.
+pub fn verify_account_proof(state_root: H256, proof: &EIP1186ProofResponse) -> Result<(), Error> {
+    let invalid = |reason: String| {
+        log::error!(
+            "invalid account proof of {:?} for state root {:?}: {}",
+            proof.address,
+            state_root,
+            reason
+        );
+        Error::InvalidAccountProof(proof.address)
+    };
+
+    let key = keccak256(proof.address.as_bytes());
+    let account = verify_proof(state_root, &key, &proof.account_proof).map_err(invalid)?;
+    match account {
+        None => {
+            if !proof.nonce.is_zero() || !proof.balance.is_zero() {
+                return Err(invalid("account is absent from the trie".to_string()));
+            }
+        }
+        Some(account) => {
+            let rlp = Rlp::new(&account);
+            let decode = || -> Result<(U256, U256, H256, H256), DecoderError> {
+                Ok((
+                    rlp.val_at(0)?,
+                    rlp.val_at(1)?,
+                    rlp.val_at(2)?,
+                    rlp.val_at(3)?,
+                ))
+            };
+            let (nonce, balance, storage_hash, keccak_code_hash) =
+                decode().map_err(|err| invalid(format!("invalid account {err:?}")))?;
+            // nodes return a zero code hash for accounts without code
+            let response_code_hash = if proof.keccak_code_hash.is_zero() {
+                *KECCAK_CODE_HASH_ZERO
+            } else {
+                proof.keccak_code_hash
+            };
+            if (nonce, balance, storage_hash, keccak_code_hash)
+                != (
+                    proof.nonce,
+                    proof.balance,
+                    proof.storage_hash,
+                    response_code_hash,
+                )
+            {
+                return Err(invalid(format!(
+                    "account (nonce {nonce}, balance {balance}, storage root {storage_hash:?}, code hash {keccak_code_hash:?}) differs from the response"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check the storage proofs of an `eth_getProof` response against its
+/// storage root: each proof must lead to the value of the response, or, for
+/// an absent slot, the value must be zero. The storage root itself is bound
+/// to the state root by [`verify_account_proof`].
+pub fn verify_storage_proofs(proof: &EIP1186ProofResponse) -> Result<(), Error> {
+    for storage_proof in &proof.storage_proof {
+        let invalid = |reason: String| {
+            log::error!(
+                "invalid storage proof of {:?} slot {:?} for storage root {:?}: {}",
+                proof.address,
+                storage_proof.key,
+                proof.storage_hash,
+                reason
+            );
+            Error::InvalidStorageProof(proof.address, storage_proof.key)
+        };
+
+        let key = keccak256(storage_proof.key.to_be_bytes());
+        let value =
+            match verify_proof(proof.storage_hash, &key, &storage_proof.proof).map_err(invalid)? {
+                None => U256::zero(),
+                Some(value) => Rlp::new(&value)
+                    .as_val()
+                    .map_err(|err| invalid(format!("invalid value {err:?}")))?,
+            };
+        if value != storage_proof.value {
+            return Err(invalid(format!("value {value} differs from the response")));
+        }
+    }
+    Ok(())
+}
+
+/// Layout of the state trie of the account and storage proofs returned by the
+/// node. The default is the layout of the state trie of the chain: the zkTrie
+/// with the `scroll` feature, the MPT otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofFormat {
+    /// Ethereum Merkle Patricia Trie proofs, from `eth_getProof`
+    Mpt,
+    /// Scroll zkTrie proofs, from `scroll_getProof`
+    ZkTrie,
+}
+
+impl Default for ProofFormat {
+    fn default() -> Self {
+        if cfg!(feature = "scroll") {
+            ProofFormat::ZkTrie
+        } else {
+            ProofFormat::Mpt
+        }
+    }
+}
+
+impl ProofFormat {
+    /// JSON-RPC method returning the proofs.
+    pub fn rpc_method(&self) -> &'static str {
+        match self {
+            ProofFormat::Mpt => "eth_getProof",
+            ProofFormat::ZkTrie => "scroll_getProof",
+        }
+    }
+
+    /// Check the account and storage proofs of `proofs` against
+    /// `state_root`.
+    pub fn verify_proofs(
+        &self,
+        state_root: H256,
+        proofs: &[EIP1186ProofResponse],
+    ) -> Result<(), Error> {
+        for proof in proofs {
+            match self {
+                ProofFormat::Mpt => {
+                    verify_account_proof(state_root, proof)?;
+                    verify_storage_proofs(proof)?;
+                }
+                ProofFormat::ZkTrie => {
+                    verify_zktrie_account_proof(state_root, proof)?;
+                    verify_zktrie_storage_proofs(proof)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check the account and storage proofs of `eth_getProof` responses against
+/// `state_root`, in the layout of the state trie of the chain: the zkTrie
+/// with the `scroll` feature, the MPT otherwise.
+pub fn verify_proofs(state_root: H256, proofs: &[EIP1186ProofResponse]) -> Result<(), Error> {
+    ProofFormat::default().verify_proofs(state_root, proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, Address, Bytes, StorageProof};
+    use ethers_core::utils::rlp::RlpStream;
+
+    fn account_rlp(nonce: u64, balance: u64) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&U256::from(nonce));
+        stream.append(&U256::from(balance));
+        stream.append(&*EMPTY_TRIE_ROOT);
+        stream.append(&*KECCAK_CODE_HASH_ZERO);
+        stream.out().to_vec()
+    }
+
+    /// A trie holding a single account, i.e. a root leaf.
+    fn single_account_trie(address: Address, nonce: u64, balance: u64) -> (H256, Bytes) {
+        let mut path = vec![0x20];
+        path.extend_from_slice(&keccak256(address.as_bytes()));
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&path);
+        stream.append(&account_rlp(nonce, balance));
+        let node = stream.out().to_vec();
+        (H256(keccak256(&node)), Bytes::from(node))
+    }
+
+    fn response(address: Address, nonce: u64, balance: u64, node: Bytes) -> EIP1186ProofResponse {
+        EIP1186ProofResponse {
+            address,
+            balance: balance.into(),
+            nonce: nonce.into(),
+            storage_hash: *EMPTY_TRIE_ROOT,
+            keccak_code_hash: *KECCAK_CODE_HASH_ZERO,
+            account_proof: vec![node],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn account_proof() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let (root, node) = single_account_trie(address, 1, 100);
+
+        assert!(verify_account_proof(root, &response(address, 1, 100, node.clone())).is_ok());
+        // wrong balance
+        assert!(matches!(
+            verify_account_proof(root, &response(address, 1, 101, node.clone())),
+            Err(Error::InvalidAccountProof(a)) if a == address
+        ));
+        // wrong state root
+        assert!(
+            verify_account_proof(*EMPTY_TRIE_ROOT, &response(address, 1, 100, node.clone()))
+                .is_err()
+        );
+        // absent account
+        let other = address!("0x00000000000000000000000000000000000000bb");
+        assert!(verify_account_proof(root, &response(other, 0, 0, node.clone())).is_ok());
+        assert!(verify_account_proof(root, &response(other, 0, 1, node)).is_err());
+    }
+
+    #[test]
+    fn storage_proof() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let slot = U256::from(7);
+        // a storage trie holding a single slot, i.e. a root leaf
+        let mut path = vec![0x20];
+        path.extend_from_slice(&keccak256(slot.to_be_bytes()));
+        let mut value = RlpStream::new();
+        value.append(&U256::from(42));
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&path);
+        stream.append(&value.out().to_vec());
+        let node = Bytes::from(stream.out().to_vec());
+
+        let response = |key: U256, value: u64| EIP1186ProofResponse {
+            address,
+            storage_hash: H256(keccak256(&node)),
+            storage_proof: vec![StorageProof {
+                key,
+                value: value.into(),
+                proof: vec![node.clone()],
+            }],
+            ..Default::default()
+        };
+        assert!(verify_storage_proofs(&response(slot, 42)).is_ok());
+        // wrong value
+        assert!(matches!(
+            verify_storage_proofs(&response(slot, 43)),
+            Err(Error::InvalidStorageProof(a, k)) if a == address && k == slot
+        ));
+        // absent slot
+        assert!(verify_storage_proofs(&response(U256::from(8), 0)).is_ok());
+        assert!(verify_storage_proofs(&response(U256::from(8), 1)).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "scroll"))]
+    fn default_proof_format() {
+        assert_eq!(ProofFormat::default(), ProofFormat::Mpt);
+        assert_eq!(ProofFormat::default().rpc_method(), "eth_getProof");
+    }
+
+    #[test]
+    #[cfg(feature = "scroll")]
+    fn default_proof_format() {
+        assert_eq!(ProofFormat::default(), ProofFormat::ZkTrie);
+        assert_eq!(ProofFormat::default().rpc_method(), "scroll_getProof");
+    }
+}
@@ -0,0 +1,558 @@
+//! Verification and decoding of EIP-1186 (`eth_getProof`) account and
+//! storage proofs of Scroll's zkTrie, the binary sparse merkle trie hashed
+//! with poseidon.
+
+use eth_types::{EIP1186ProofResponse, ToBigEndian, H256, U256};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    halo2curves::{bn256::Fr, group::ff::PrimeField},
+};
+use poseidon_circuit::hash::Hashable;
+
+use crate::{
+    state_db::Account,
+    util::{KECCAK_CODE_HASH_ZERO, POSEIDON_CODE_HASH_ZERO},
+    Error,
+};
+use std::collections::HashMap;
+
+const NODE_TYPE_MIDDLE: u8 = 0;
+const NODE_TYPE_LEAF: u8 = 1;
+const NODE_TYPE_EMPTY: u8 = 2;
+
+/// Last item of the proofs returned by the node, which isn't a node.
+const PROOF_MAGIC: &[u8] = b"THIS IS SOME MAGIC BYTES FOR SMT m1rRXgP2xpDI";
+
+/// Field element of a big endian hash, if canonical.
+fn fr_from_hash(hash: &[u8]) -> Option<Fr> {
+    let mut repr: [u8; 32] = hash.try_into().ok()?;
+    repr.reverse();
+    Fr::from_bytes(&repr).into()
+}
+
+/// Hash of a 32 bytes value, which may not fit in a field element: its two
+/// halves are hashed.
+fn hash_byte32(bytes: &[u8; 32]) -> Fr {
+    let high = u128::from_be_bytes(bytes[..16].try_into().unwrap());
+    let low = u128::from_be_bytes(bytes[16..].try_into().unwrap());
+    Fr::hash([Fr::from_u128(high), Fr::from_u128(low)])
+}
+
+/// Hash of a list of field elements, as `HashElems` of zktrie: the first two
+/// elements are hashed, the others are hashed by pairs, and the results are
+/// hashed the same way until one remains.
+fn hash_elems(elems: &[Fr]) -> Fr {
+    match elems {
+        [] => Fr::zero(),
+        [elem] => *elem,
+        [first, second, rest @ ..] => {
+            let base = Fr::hash([*first, *second]);
+            if rest.is_empty() {
+                return base;
+            }
+            let rest = rest.chunks(2).map(|pair| match pair {
+                [left, right] => Fr::hash([*left, *right]),
+                [elem] => *elem,
+                _ => unreachable!(),
+            });
+            hash_elems(&std::iter::once(base).chain(rest).collect::<Vec<_>>())
+        }
+    }
+}
+
+/// Secure key of a 32 bytes key, which gives the path to its leaf.
+fn secure_key(key: &[u8; 32]) -> Fr {
+    hash_byte32(key)
+}
+
+/// A leaf: its key and the 32 bytes words of its value.
+struct Leaf {
+    key: Fr,
+    value: Vec<[u8; 32]>,
+}
+
+impl Leaf {
+    /// Parse a leaf node, after its type byte, and return it with its hash.
+    fn parse(node: &[u8]) -> Option<(Self, Fr)> {
+        let key = fr_from_hash(node.get(..32)?)?;
+        // number of words of the value, and a flag per word which must be
+        // hashed from its halves
+        let mark = u32::from_le_bytes(node.get(32..36)?.try_into().ok()?);
+        let (len, flags) = ((mark & 0xff) as usize, mark >> 8);
+        let value = node
+            .get(36..36 + 32 * len)?
+            .chunks(32)
+            .map(|word| word.try_into().unwrap())
+            .collect::<Vec<[u8; 32]>>();
+        let elems = value
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                if flags & (1 << i) != 0 {
+                    Some(hash_byte32(word))
+                } else {
+                    fr_from_hash(word)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let hash = Fr::hash([Fr::hash([Fr::one(), key]), hash_elems(&elems)]);
+        Some((Self { key, value }, hash))
+    }
+}
+
+/// Walk `proof` from `root` along the path of `key` and return the value
+/// stored at `key`, or `None` if the proof shows that the key is absent.
+/// Without `root`, the first node is trusted and only the following nodes are
+/// checked against their parent. Errors describe why the proof is rejected.
+fn verify_proof(
+    root: Option<H256>,
+    key: Fr,
+    proof: &[impl AsRef<[u8]>],
+) -> Result<Option<Vec<[u8; 32]>>, String> {
+    let path = key.to_repr();
+    let mut expected = root
+        .map(|root| fr_from_hash(root.as_bytes()).ok_or("root is not a field element"))
+        .transpose()?;
+    let unreferenced = |depth| format!("node {depth} is not referenced by its parent");
+    let nodes = proof
+        .iter()
+        .map(AsRef::as_ref)
+        .filter(|node| *node != PROOF_MAGIC);
+    for (depth, node) in nodes.enumerate() {
+        let invalid_node = || format!("invalid node {depth}");
+        let (node_type, node) = node.split_first().ok_or_else(invalid_node)?;
+        match *node_type {
+            NODE_TYPE_MIDDLE => {
+                let left = node.get(..32).and_then(fr_from_hash);
+                let right = node.get(32..64).and_then(fr_from_hash);
+                let (left, right) = left.zip(right).ok_or_else(invalid_node)?;
+                if matches!(expected, Some(hash) if hash != Fr::hash([left, right])) {
+                    return Err(unreferenced(depth));
+                }
+                // the bits of the key, from the least significant, select the
+                // child at each depth
+                let byte = path
+                    .as_ref()
+                    .get(depth / 8)
+                    .ok_or_else(|| format!("node {depth} is deeper than the key"))?;
+                let bit = (byte >> (depth % 8)) & 1;
+                expected = Some(if bit == 1 { right } else { left });
+            }
+            NODE_TYPE_LEAF => {
+                let (leaf, hash) = Leaf::parse(node).ok_or_else(invalid_node)?;
+                if matches!(expected, Some(expected) if expected != hash) {
+                    return Err(unreferenced(depth));
+                }
+                // a leaf of another key in place of the key proves its absence
+                return Ok((leaf.key == key).then_some(leaf.value));
+            }
+            NODE_TYPE_EMPTY => {
+                if matches!(expected, Some(expected) if expected != Fr::zero()) {
+                    return Err(unreferenced(depth));
+                }
+                return Ok(None);
+            }
+            _ => return Err(invalid_node()),
+        }
+    }
+    match expected {
+        None => Err("empty proof".to_string()),
+        Some(expected) if expected == Fr::zero() => Ok(None),
+        Some(_) => Err("proof ends before the key".to_string()),
+    }
+}
+
+/// Key of the leaf of the account at `address`.
+fn account_key(address: &eth_types::Address) -> Fr {
+    let mut key = [0u8; 32];
+    key[..20].copy_from_slice(address.as_bytes());
+    secure_key(&key)
+}
+
+/// Decode the words of an account leaf into the account, without storage,
+/// and its storage root.
+fn decode_account(value: Vec<[u8; 32]>) -> Result<(Account, H256), String> {
+    // [0; 16] || code size || nonce, balance, storage root, keccak code hash,
+    // poseidon code hash
+    let [sizes, balance, storage_root, keccak_code_hash, code_hash]: [[u8; 32]; 5] = value
+        .try_into()
+        .map_err(|value: Vec<_>| format!("account of {} words", value.len()))?;
+    let account = Account {
+        nonce: U256::from_big_endian(&sizes[24..]),
+        balance: U256::from_big_endian(&balance),
+        storage: HashMap::new(),
+        code_hash: H256(code_hash),
+        keccak_code_hash: H256(keccak_code_hash),
+        code_size: U256::from_big_endian(&sizes[16..24]),
+    };
+    Ok((account, H256(storage_root)))
+}
+
+/// Decode the value of a storage leaf, or zero for an absent slot.
+fn decode_storage_value(value: Option<Vec<[u8; 32]>>) -> Result<U256, String> {
+    match value.as_deref() {
+        None => Ok(U256::zero()),
+        Some([value]) => Ok(U256::from_big_endian(value)),
+        Some(value) => Err(format!("value of {} words", value.len())),
+    }
+}
+
+/// Check the account proof of an `eth_getProof` response against the zkTrie
+/// `state_root`: the proof must lead to an account with the nonce, balance,
+/// storage root, code hashes and code size of the response, or, for an
+/// absent account, the response must describe an empty account.
+pub fn verify_zktrie_account_proof(
+    state_root: H256,
+    proof: &EIP1186ProofResponse,
+) -> Result<(), Error> {
+    let invalid = |reason: String| {
+        log::error!(
+            "invalid zktrie account proof of {:?} for state root {:?}: {}",
+            proof.address,
+            state_root,
+            reason
+        );
+        Error::InvalidAccountProof(proof.address)
+    };
+
+    match verify_proof(
+        Some(state_root),
+        account_key(&proof.address),
+        &proof.account_proof,
+    )
+    .map_err(invalid)?
+    {
+        None => {
+            if !proof.nonce.is_zero() || !proof.balance.is_zero() {
+                return Err(invalid("account is absent from the trie".to_string()));
+            }
+        }
+        Some(value) => {
+            let (account, storage_root) = decode_account(value).map_err(invalid)?;
+            // nodes return zero code hashes for accounts without code
+            let response_code_hashes = if proof.keccak_code_hash.is_zero() {
+                (*KECCAK_CODE_HASH_ZERO, *POSEIDON_CODE_HASH_ZERO)
+            } else {
+                (proof.keccak_code_hash, proof.code_hash)
+            };
+            if (
+                account.nonce,
+                account.balance,
+                storage_root,
+                account.code_size,
+            ) != (
+                proof.nonce,
+                proof.balance,
+                proof.storage_hash,
+                proof.code_size,
+            ) || (account.keccak_code_hash, account.code_hash) != response_code_hashes
+            {
+                return Err(invalid(format!(
+                    "account (nonce {}, balance {}, storage root {:?}, code hashes {:?} {:?}, code size {}) differs from the response",
+                    account.nonce,
+                    account.balance,
+                    storage_root,
+                    account.keccak_code_hash,
+                    account.code_hash,
+                    account.code_size
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check the storage proofs of an `eth_getProof` response against its zkTrie
+/// storage root: each proof must lead to the value of the response, or, for
+/// an absent slot, the value must be zero. The storage root itself is bound
+/// to the state root by [`verify_zktrie_account_proof`].
+pub fn verify_zktrie_storage_proofs(proof: &EIP1186ProofResponse) -> Result<(), Error> {
+    for storage_proof in &proof.storage_proof {
+        let invalid = |reason: String| {
+            log::error!(
+                "invalid zktrie storage proof of {:?} slot {:?} for storage root {:?}: {}",
+                proof.address,
+                storage_proof.key,
+                proof.storage_hash,
+                reason
+            );
+            Error::InvalidStorageProof(proof.address, storage_proof.key)
+        };
+
+        let key = secure_key(&storage_proof.key.to_be_bytes());
+        let value = verify_proof(Some(proof.storage_hash), key, &storage_proof.proof)
+            .and_then(decode_storage_value)
+            .map_err(invalid)?;
+        if value != storage_proof.value {
+            return Err(invalid(format!("value {value} differs from the response")));
+        }
+    }
+    Ok(())
+}
+
+/// Decode the account of a `scroll_getProof` response, and its storage slots,
+/// from the leaves of its zkTrie proofs rather than from the fields of the
+/// response. The proofs are not checked against a state root, see
+/// [`verify_zktrie_account_proof`], but the storage proofs are checked
+/// against the decoded storage root. An absent account is decoded as
+/// [`Account::zero`].
+pub fn decode_zktrie_proof(proof: &EIP1186ProofResponse) -> Result<Account, Error> {
+    let invalid_account = |reason: String| {
+        log::error!(
+            "invalid zktrie account proof of {:?}: {}",
+            proof.address,
+            reason
+        );
+        Error::InvalidAccountProof(proof.address)
+    };
+    let (mut account, storage_root) =
+        match verify_proof(None, account_key(&proof.address), &proof.account_proof)
+            .map_err(invalid_account)?
+        {
+            // the storage of an absent account is empty
+            None => (Account::zero(), H256::zero()),
+            Some(value) => decode_account(value).map_err(invalid_account)?,
+        };
+
+    for storage_proof in &proof.storage_proof {
+        let key = secure_key(&storage_proof.key.to_be_bytes());
+        let value = verify_proof(Some(storage_root), key, &storage_proof.proof)
+            .and_then(decode_storage_value)
+            .map_err(|reason| {
+                log::error!(
+                    "invalid zktrie storage proof of {:?} slot {:?} for storage root {:?}: {}",
+                    proof.address,
+                    storage_proof.key,
+                    storage_root,
+                    reason
+                );
+                Error::InvalidStorageProof(proof.address, storage_proof.key)
+            })?;
+        account.storage.insert(storage_proof.key, value);
+    }
+    Ok(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{address, Address, Bytes, StorageProof};
+
+    fn hash_to_bytes(hash: Fr) -> [u8; 32] {
+        let mut bytes: [u8; 32] = hash.to_repr().as_ref().try_into().unwrap();
+        bytes.reverse();
+        bytes
+    }
+
+    /// A leaf node of `key` holding `value`, with the given flags, and its
+    /// hash.
+    fn leaf(key: Fr, flags: u32, value: &[[u8; 32]]) -> (Bytes, Fr) {
+        let mut node = vec![NODE_TYPE_LEAF];
+        node.extend_from_slice(&hash_to_bytes(key));
+        node.extend_from_slice(&((flags << 8) | value.len() as u32).to_le_bytes());
+        value.iter().for_each(|word| node.extend_from_slice(word));
+        let (_, hash) = Leaf::parse(&node[1..]).unwrap();
+        (Bytes::from(node), hash)
+    }
+
+    fn response(
+        address: Address,
+        nonce: u64,
+        balance: u64,
+        nodes: Vec<Bytes>,
+    ) -> EIP1186ProofResponse {
+        EIP1186ProofResponse {
+            address,
+            balance: balance.into(),
+            nonce: nonce.into(),
+            keccak_code_hash: *KECCAK_CODE_HASH_ZERO,
+            code_hash: *POSEIDON_CODE_HASH_ZERO,
+            account_proof: nodes,
+            ..Default::default()
+        }
+    }
+
+    /// A trie holding a single account at the left of the root, and the
+    /// proof of its leaf.
+    fn account_trie(address: Address, nonce: u64, balance: u64) -> (H256, Vec<Bytes>) {
+        let mut sizes = [0u8; 32];
+        sizes[24..].copy_from_slice(&nonce.to_be_bytes());
+        let (leaf, leaf_hash) = leaf(
+            account_key(&address),
+            8,
+            &[
+                sizes,
+                U256::from(balance).to_be_bytes(),
+                [0; 32],
+                KECCAK_CODE_HASH_ZERO.to_fixed_bytes(),
+                POSEIDON_CODE_HASH_ZERO.to_fixed_bytes(),
+            ],
+        );
+        let (left, right) = if account_key(&address).to_repr().as_ref()[0] & 1 == 0 {
+            (leaf_hash, Fr::zero())
+        } else {
+            (Fr::zero(), leaf_hash)
+        };
+        let mut middle = vec![NODE_TYPE_MIDDLE];
+        middle.extend_from_slice(&hash_to_bytes(left));
+        middle.extend_from_slice(&hash_to_bytes(right));
+        let root = H256(hash_to_bytes(Fr::hash([left, right])));
+        (
+            root,
+            vec![Bytes::from(middle), leaf, Bytes::from(PROOF_MAGIC.to_vec())],
+        )
+    }
+
+    #[test]
+    fn hash_elems_of_account() {
+        let elems = [1, 2, 3, 4, 5].map(Fr::from);
+        assert_eq!(
+            hash_elems(&elems),
+            Fr::hash([
+                Fr::hash([
+                    Fr::hash([elems[0], elems[1]]),
+                    Fr::hash([elems[2], elems[3]])
+                ]),
+                elems[4]
+            ])
+        );
+    }
+
+    #[test]
+    fn zktrie_account_proof() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let (root, nodes) = account_trie(address, 1, 100);
+
+        assert!(
+            verify_zktrie_account_proof(root, &response(address, 1, 100, nodes.clone())).is_ok()
+        );
+        // wrong balance
+        assert!(matches!(
+            verify_zktrie_account_proof(root, &response(address, 1, 101, nodes.clone())),
+            Err(Error::InvalidAccountProof(a)) if a == address
+        ));
+        // wrong state root
+        assert!(verify_zktrie_account_proof(
+            H256::zero(),
+            &response(address, 1, 100, nodes.clone())
+        )
+        .is_err());
+        // absent account, either in the empty sibling or behind the leaf of
+        // another account
+        let other = address!("0x00000000000000000000000000000000000000bb");
+        let mut empty_sibling = nodes[..1].to_vec();
+        empty_sibling.push(Bytes::from(vec![NODE_TYPE_EMPTY]));
+        let other_nodes = if account_key(&other).to_repr().as_ref()[0] & 1
+            == account_key(&address).to_repr().as_ref()[0] & 1
+        {
+            nodes
+        } else {
+            empty_sibling
+        };
+        assert!(
+            verify_zktrie_account_proof(root, &response(other, 0, 0, other_nodes.clone())).is_ok()
+        );
+        assert!(verify_zktrie_account_proof(root, &response(other, 0, 1, other_nodes)).is_err());
+    }
+
+    #[test]
+    fn zktrie_storage_proof() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let slot = U256::from(7);
+        // a storage trie holding a single slot, i.e. a root leaf
+        let (node, hash) = leaf(
+            secure_key(&slot.to_be_bytes()),
+            1,
+            &[U256::from(42).to_be_bytes()],
+        );
+
+        let response = |key: U256, value: u64| EIP1186ProofResponse {
+            address,
+            storage_hash: H256(hash_to_bytes(hash)),
+            storage_proof: vec![StorageProof {
+                key,
+                value: value.into(),
+                proof: vec![node.clone(), Bytes::from(PROOF_MAGIC.to_vec())],
+            }],
+            ..Default::default()
+        };
+        assert!(verify_zktrie_storage_proofs(&response(slot, 42)).is_ok());
+        // wrong value
+        assert!(matches!(
+            verify_zktrie_storage_proofs(&response(slot, 43)),
+            Err(Error::InvalidStorageProof(a, k)) if a == address && k == slot
+        ));
+        // absent slot
+        assert!(verify_zktrie_storage_proofs(&response(U256::from(8), 0)).is_ok());
+        assert!(verify_zktrie_storage_proofs(&response(U256::from(8), 1)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "scroll")]
+    fn default_format_verifies_zktrie_proofs() {
+        use crate::state_db::{verify_proofs, ProofFormat};
+
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let (root, nodes) = account_trie(address, 1, 100);
+        let proofs = [response(address, 1, 100, nodes)];
+        // the builder client verifies the proofs in the default format
+        assert!(ProofFormat::default().verify_proofs(root, &proofs).is_ok());
+        assert!(verify_proofs(root, &proofs).is_ok());
+        assert!(ProofFormat::Mpt.verify_proofs(root, &proofs).is_err());
+    }
+
+    #[test]
+    fn zktrie_proof_too_deep() {
+        // middle nodes referencing the next one on both sides, whatever the
+        // path of the key, one more than the bits of the key
+        let mut hash = Fr::zero();
+        let mut nodes = vec![];
+        for _ in 0..=256 {
+            let mut middle = vec![NODE_TYPE_MIDDLE];
+            middle.extend_from_slice(&hash_to_bytes(hash));
+            middle.extend_from_slice(&hash_to_bytes(hash));
+            hash = Fr::hash([hash, hash]);
+            nodes.push(middle);
+        }
+        nodes.reverse();
+        let root = H256(hash_to_bytes(hash));
+        assert_eq!(
+            verify_proof(Some(root), Fr::one(), &nodes),
+            Err("node 256 is deeper than the key".to_string())
+        );
+    }
+
+    #[test]
+    fn zktrie_proof_decoding() {
+        let address = address!("0x00000000000000000000000000000000000000aa");
+        let (_, nodes) = account_trie(address, 1, 100);
+        let slot = U256::from(7);
+        let mut proof = response(address, 0, 0, nodes.clone());
+        // the storage trie of the account is empty
+        proof.storage_proof = vec![StorageProof {
+            key: slot,
+            value: U256::zero(),
+            proof: vec![
+                Bytes::from(vec![NODE_TYPE_EMPTY]),
+                Bytes::from(PROOF_MAGIC.to_vec()),
+            ],
+        }];
+
+        // the account comes from the leaf, not from the response
+        let account = decode_zktrie_proof(&proof).unwrap();
+        assert_eq!(account.nonce, U256::from(1));
+        assert_eq!(account.balance, U256::from(100));
+        assert_eq!(account.keccak_code_hash, *KECCAK_CODE_HASH_ZERO);
+        assert_eq!(account.code_hash, *POSEIDON_CODE_HASH_ZERO);
+        assert_eq!(account.storage.get(&slot), Some(&U256::zero()));
+
+        // a leaf which isn't the child of the middle node
+        let mut leaf = nodes[1].to_vec();
+        *leaf.last_mut().unwrap() ^= 1;
+        proof.account_proof = vec![nodes[0].clone(), Bytes::from(leaf)];
+        assert!(matches!(
+            decode_zktrie_proof(&proof),
+            Err(Error::InvalidAccountProof(a)) if a == address
+        ));
+    }
+}
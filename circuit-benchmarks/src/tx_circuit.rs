@@ -84,7 +84,8 @@ mod tests {
         let max_txs: usize = 2_usize.pow(degree) / ROWS_PER_TX;
 
         let txs = vec![mock::CORRECT_MOCK_TXS[0].clone().into()];
-        let circuit = TxCircuit::<Fr>::new(max_txs, MAX_CALLDATA, *mock::MOCK_CHAIN_ID, txs);
+        let circuit =
+            TxCircuit::<Fr>::new(max_txs, MAX_CALLDATA, *mock::MOCK_CHAIN_ID, 0, vec![], txs);
         (degree as usize, circuit)
     }
 
@@ -118,6 +119,7 @@ mod tests {
         // Initialize the proving key
         let vk = keygen_vk(&general_params, &circuit).expect("keygen_vk should not fail");
         let pk = keygen_pk(&general_params, vk, &circuit).expect("keygen_pk should not fail");
+        let instance = circuit.instance();
         // Create a proof
         let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
 
@@ -135,7 +137,7 @@ mod tests {
             &general_params,
             &pk,
             &[circuit],
-            &[&[&[]]],
+            &[&[&instance[0]]],
             rng,
             &mut transcript,
         )
@@ -158,7 +160,7 @@ mod tests {
             &verifier_params,
             pk.get_vk(),
             strategy,
-            &[&[&[]]],
+            &[&[&instance[0]]],
             &mut verifier_transcript,
         )
         .expect("failed to verify bench circuit");
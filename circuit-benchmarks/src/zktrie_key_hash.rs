@@ -0,0 +1,51 @@
+//! Zktrie key hashing benchmarks
+
+#[cfg(test)]
+mod tests {
+    use ark_std::{end_timer, start_timer};
+    use mpt_zktrie::state::key_hash::{hash_zktrie_key, KeyHashCache};
+    use rand::{Rng, SeedableRng};
+    use rand_xorshift::XorShiftRng;
+    use std::env::var;
+
+    #[cfg_attr(not(feature = "benches"), ignore)]
+    #[test]
+    fn bench_zktrie_key_hash() {
+        // Unique string used by bench results module for parsing the result
+        const BENCHMARK_ID: &str = "Zktrie key hash";
+
+        // Number of distinct storage keys, each of them is hashed `REPEAT` times
+        // as when loading the state, checking the proofs and tracing updates.
+        let keys: usize = var("KEYS")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .expect("Cannot parse KEYS env var as usize");
+        const REPEAT: usize = 3;
+
+        let mut rng = XorShiftRng::from_seed([
+            0x59, 0x62, 0xbe, 0x5d, 0x76, 0x3d, 0x31, 0x8d, 0x17, 0xdb, 0x37, 0x32, 0x54, 0x06,
+            0xbc, 0xe5,
+        ]);
+        let keys: Vec<[u8; 32]> = (0..keys).map(|_| rng.gen()).collect();
+
+        let message = format!("{BENCHMARK_ID} without cache, {} keys", keys.len());
+        let start1 = start_timer!(|| message);
+        for _ in 0..REPEAT {
+            for key in &keys {
+                hash_zktrie_key(key);
+            }
+        }
+        end_timer!(start1);
+
+        let cache = KeyHashCache::new();
+        let message = format!("{BENCHMARK_ID} with cache, {} keys", keys.len());
+        let start2 = start_timer!(|| message);
+        for _ in 0..REPEAT {
+            for key in &keys {
+                cache.hash(key);
+            }
+        }
+        end_timer!(start2);
+        assert_eq!(cache.len(), keys.len());
+    }
+}
@@ -0,0 +1,34 @@
+//! Aggregation circuit parameters.
+
+use crate::keccak::{DIGEST_LEN, RATE};
+
+// TODO(ZZ): update to the right degree
+/// Degree of the aggregation circuits.
+pub const LOG_DEGREE: u32 = 19;
+
+/// Max number of snarks to be aggregated in a chunk.
+/// If the input size is less than this, dummy snarks
+/// will be padded.
+// TODO: update me(?)
+pub const MAX_AGG_SNARKS: usize = 10;
+
+/// The number of keccak rounds is the sum of
+/// - batch public input hash: 2 rounds
+/// - chunk's public input hash: 2 * MAX_AGG_SNARKS
+/// - batch data hash: (32 * MAX_AGG_SNARKS)/136 = 3
+pub const MAX_KECCAK_ROUNDS: usize = 2 * MAX_AGG_SNARKS + 5;
+
+/// An decomposed accumulator consists of 12 field elements
+pub const ACC_LEN: usize = 12;
+
+/// number of limbs when decomposing a field element in the ECC chip
+pub const LIMBS: usize = 3;
+/// number of bits in each limb in the ECC chip
+pub const BITS: usize = 88;
+
+// The batch data hash rounds above assume it fits in 3 keccak rounds.
+const _: () = assert!(DIGEST_LEN * MAX_AGG_SNARKS / RATE + 1 <= 3);
+// An accumulator is two G1 points, each coordinate split into LIMBS limbs.
+const _: () = assert!(ACC_LEN == 4 * LIMBS);
+// The limbs must cover a 254 bit base field element.
+const _: () = assert!(LIMBS * BITS >= 254);
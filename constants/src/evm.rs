@@ -0,0 +1,4 @@
+//! EVM circuit parameters.
+
+/// Maximum number of rows taken by a step of the EVM circuit.
+pub const MAX_STEP_HEIGHT: usize = 21;
@@ -0,0 +1,18 @@
+//! Keccak circuit parameters.
+
+/// Number of rounds of the keccak-f permutation.
+pub const NUM_ROUNDS: usize = 24;
+/// Default number of rows per keccak round, overridable with `KECCAK_ROWS`.
+pub const DEFAULT_KECCAK_ROWS: usize = 12;
+/// Number of rows taken by one keccak-f permutation with the default
+/// number of rows per round.
+pub const ROWS_PER_ROUND: usize = (NUM_ROUNDS + 1) * DEFAULT_KECCAK_ROWS;
+/// Number of bytes absorbed per keccak-f permutation.
+pub const RATE: usize = 136;
+/// Length of a keccak digest in bytes.
+pub const DIGEST_LEN: usize = 32;
+
+// The aggregator is hard coded for 300 rows per round.
+const _: () = assert!(ROWS_PER_ROUND == 300);
+// 17 words of 8 bytes are absorbed per permutation.
+const _: () = assert!(RATE == 17 * 8);
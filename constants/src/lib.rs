@@ -0,0 +1,14 @@
+//! # ZKEVM-Constants
+//!
+//! Layout constants shared by bus-mapping, zkevm-circuits and the aggregator.
+//! Values that have to agree across crates are defined here once, and the
+//! relations between them are checked at compile time.
+
+#![deny(missing_docs)]
+#![deny(unsafe_code)]
+
+pub mod aggregation;
+pub mod evm;
+pub mod keccak;
+pub mod pi;
+pub mod predeployed;
@@ -0,0 +1,45 @@
+//! Byte layouts of the public input hash preimages.
+//!
+//! A chunk's public input hash is the keccak of
+//! - chain_id:          8 bytes
+//! - prev_state_root    32 bytes
+//! - post_state_root    32 bytes
+//! - withdraw_root      32 bytes
+//! - chunk_data_hash    32 bytes
+//! - prev_withdraw_root 32 bytes
+//!
+//! and the batch public input hash uses the same layout with the batch data
+//! hash in place of the chunk data hash. The prev withdraw root comes last so
+//! that the offsets of the other fields are the ones of the original layout.
+
+use crate::keccak::{DIGEST_LEN, RATE};
+
+/// A chain_id is u64 and uses 8 bytes
+pub const CHAIN_ID_LEN: usize = 8;
+
+/// Offset of the previous state root in the preimage.
+pub const PREV_STATE_ROOT_INDEX: usize = CHAIN_ID_LEN;
+/// Offset of the post state root in the preimage.
+pub const POST_STATE_ROOT_INDEX: usize = PREV_STATE_ROOT_INDEX + DIGEST_LEN;
+/// Offset of the withdraw root in the preimage.
+pub const WITHDRAW_ROOT_INDEX: usize = POST_STATE_ROOT_INDEX + DIGEST_LEN;
+/// Offset of the data hash in the preimage.
+pub const CHUNK_DATA_HASH_INDEX: usize = WITHDRAW_ROOT_INDEX + DIGEST_LEN;
+/// Offset of the withdraw root before the chunk in the preimage.
+pub const PREV_WITHDRAW_ROOT_INDEX: usize = CHUNK_DATA_HASH_INDEX + DIGEST_LEN;
+/// Length of the public input hash preimage.
+pub const PI_HASH_PREIMAGE_LEN: usize = PREV_WITHDRAW_ROOT_INDEX + DIGEST_LEN;
+
+/// Number of bytes of a block header in the data hash preimage:
+/// number (8), timestamp (8), base_fee (32), gas_limit (8), num_txs (2).
+pub const BLOCK_HEADER_BYTES_NUM: usize = 8 + 8 + 32 + 8 + 2;
+
+// The indices are baked into the aggregation circuit.
+const _: () = assert!(PREV_STATE_ROOT_INDEX == 8);
+const _: () = assert!(POST_STATE_ROOT_INDEX == 40);
+const _: () = assert!(WITHDRAW_ROOT_INDEX == 72);
+const _: () = assert!(CHUNK_DATA_HASH_INDEX == 104);
+const _: () = assert!(PREV_WITHDRAW_ROOT_INDEX == 136);
+// The preimage is absorbed in exactly two keccak rounds, the padding included.
+const _: () = assert!(PI_HASH_PREIMAGE_LEN >= RATE && PI_HASH_PREIMAGE_LEN < 2 * RATE);
+const _: () = assert!(BLOCK_HEADER_BYTES_NUM == 58);
@@ -0,0 +1,45 @@
+//! Addresses and storage slots of the L2 predeployed contracts.
+
+const fn predeployed(index: u8) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address[0] = 0x53;
+    address[19] = index;
+    address
+}
+
+/// Address of the L2MessageQueue predeploy
+pub const L2_MESSAGE_QUEUE: [u8; 20] = predeployed(0x00);
+/// The slot of withdraw root in L2MessageQueue
+pub const WITHDRAW_TRIE_ROOT_SLOT: u64 = 0;
+/// Height of the withdraw trie
+pub const WITHDRAW_TRIE_HEIGHT: usize = 40;
+/// The slot of the index of the next message appended to the withdraw trie
+pub const WITHDRAW_TRIE_NEXT_INDEX_SLOT: u64 = 1;
+/// The first slot of the branches of the withdraw trie, one slot per height,
+/// after the zero hashes of the empty subtrees
+pub const WITHDRAW_TRIE_BRANCHES_SLOT: u64 = 2 + WITHDRAW_TRIE_HEIGHT as u64;
+
+/// Address of the L2ScrollMessenger of Scroll mainnet, which emits the
+/// `SentMessage` logs of the messages appended to the withdraw trie
+pub const L2_SCROLL_MESSENGER: [u8; 20] = [
+    0x78, 0x1e, 0x90, 0xf1, 0xc8, 0xfc, 0x46, 0x11, 0xc9, 0xb7, 0x49, 0x7c, 0x3b, 0x47, 0xf9, 0x9e,
+    0xf6, 0x96, 0x9c, 0xbc,
+];
+
+/// Address of the L1GasPriceOracle predeploy
+pub const L1_GAS_PRICE_ORACLE: [u8; 20] = predeployed(0x02);
+/// L1 base fee slot in L1GasPriceOracle
+pub const L1_BASE_FEE_SLOT: u64 = 1;
+/// L1 overhead slot in L1GasPriceOracle
+pub const L1_OVERHEAD_SLOT: u64 = 2;
+/// L1 scalar slot in L1GasPriceOracle
+pub const L1_SCALAR_SLOT: u64 = 3;
+/// L1 blob base fee slot in L1GasPriceOracle, since Curie
+pub const L1_BLOB_BASE_FEE_SLOT: u64 = 5;
+/// L1 commit scalar slot in L1GasPriceOracle, since Curie
+pub const L1_COMMIT_SCALAR_SLOT: u64 = 6;
+/// L1 blob scalar slot in L1GasPriceOracle, since Curie
+pub const L1_BLOB_SCALAR_SLOT: u64 = 7;
+
+/// Default coinbase, the L2 fee vault
+pub const DEFAULT_COINBASE: [u8; 20] = predeployed(0x05);
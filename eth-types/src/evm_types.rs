@@ -4,12 +4,14 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub mod block_utils;
+pub mod gas_schedule;
 pub mod gas_utils;
 pub mod memory;
 pub mod opcode_ids;
 pub mod stack;
 pub mod storage;
 
+pub use gas_schedule::{GasSchedule, GasSchedules};
 pub use memory::{Memory, MemoryAddress, MemoryRef};
 pub use opcode_ids::OpcodeId;
 pub use stack::{Stack, StackAddress};
@@ -201,6 +203,8 @@ impl GasCost {
     pub const PRECOMPILE_MODEXP: Self = Self(0);
     /// Base gas price for precompile call: BLAKE2F
     pub const PRECOMPILE_BLAKE2F: Self = Self(0);
+    /// Gas price for precompile call: KZG point evaluation (EIP-4844)
+    pub const PRECOMPILE_POINT_EVALUATION: Self = Self(50000);
 }
 
 impl GasCost {
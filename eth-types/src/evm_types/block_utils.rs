@@ -2,6 +2,8 @@
 
 use crate::{U256, U64};
 use ethers_core::utils::keccak256;
+use num::{One, Zero};
+use num_bigint::BigUint;
 
 /// Maximum range of previous blocks allowed inside BLOCKHASH opcode
 pub const NUM_PREV_BLOCK_ALLOWED: u64 = 256;
@@ -27,3 +29,61 @@ pub fn is_valid_block_number(block_number: U256, current_block_number: U256) ->
                 .checked_sub(NUM_PREV_BLOCK_ALLOWED.into())
                 .unwrap_or_default()
 }
+
+/// Minimum base fee per blob gas of EIP-4844
+pub const MIN_BASE_FEE_PER_BLOB_GAS: u64 = 1;
+
+/// Update fraction of the base fee per blob gas of EIP-4844
+pub const BLOB_BASE_FEE_UPDATE_FRACTION: u64 = 3338477;
+
+/// Calculate the base fee per blob gas of a block from its excess blob gas,
+/// which BLOBBASEFEE returns, as `fake_exponential` of EIP-4844 does.
+///
+/// Like geth, the sum is computed in a big integer, as its terms overflow
+/// U256 for a large excess blob gas. The fee saturates at `U256::MAX`, which
+/// real blocks are far from.
+pub fn calculate_blob_base_fee(excess_blob_gas: u64) -> U256 {
+    let factor = BigUint::from(MIN_BASE_FEE_PER_BLOB_GAS);
+    let numerator = BigUint::from(excess_blob_gas);
+    let denominator = BigUint::from(BLOB_BASE_FEE_UPDATE_FRACTION);
+    // beyond this, the fee saturates and the sum can stop
+    let max_output = (BigUint::one() << 256usize) * &denominator;
+
+    let mut output = BigUint::zero();
+    let mut numerator_accum = factor * &denominator;
+    let mut i = 1u64;
+    while !numerator_accum.is_zero() && output < max_output {
+        output += &numerator_accum;
+        numerator_accum = numerator_accum * &numerator / (&denominator * i);
+        i += 1;
+    }
+
+    if output < max_output {
+        U256::from_big_endian(&(output / denominator).to_bytes_be())
+    } else {
+        U256::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_base_fee() {
+        assert_eq!(calculate_blob_base_fee(0), U256::one());
+        assert_eq!(calculate_blob_base_fee(2314057), U256::one());
+        assert_eq!(calculate_blob_base_fee(2314058), U256::from(2));
+        assert_eq!(calculate_blob_base_fee(10 * 1024 * 1024), U256::from(23));
+        // the terms of the sum overflow U256
+        assert_eq!(
+            calculate_blob_base_fee(500_000_000),
+            U256::from_str_radix(
+                "10ce22ab45809aee5874013c7c5cc21a469b331be0702f60719a490",
+                16
+            )
+            .unwrap()
+        );
+        assert_eq!(calculate_blob_base_fee(u64::MAX), U256::MAX);
+    }
+}
@@ -0,0 +1,198 @@
+//! Costs of the storage opcodes, which the hardforks of a chain may reprice,
+//! resolved by block number.
+
+use super::GasCost;
+use crate::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Costs of the storage and transient storage opcodes in a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSchedule {
+    /// Cost of a warm SLOAD, and of an SSTORE which doesn't set the slot from
+    /// its committed value
+    pub warm_access: u64,
+    /// Cost of a cold SLOAD, surcharged to a cold SSTORE
+    pub cold_sload: u64,
+    /// Cost of an SSTORE setting a zero committed slot
+    pub sstore_set: u64,
+    /// Cost of an SSTORE resetting a non-zero committed slot
+    pub sstore_reset: u64,
+    /// Refund of an SSTORE clearing a non-zero committed slot
+    pub sstore_clears_schedule: u64,
+    /// Cost of a TLOAD
+    pub tload: u64,
+    /// Cost of a TSTORE
+    pub tstore: u64,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self {
+            warm_access: GasCost::WARM_ACCESS.0,
+            cold_sload: GasCost::COLD_SLOAD.0,
+            sstore_set: GasCost::SSTORE_SET.0,
+            sstore_reset: GasCost::SSTORE_RESET.0,
+            sstore_clears_schedule: GasCost::SSTORE_CLEARS_SCHEDULE.0,
+            tload: GasCost::WARM_ACCESS.0,
+            tstore: GasCost::WARM_ACCESS.0,
+        }
+    }
+}
+
+impl GasSchedule {
+    /// Cost of an SLOAD
+    pub fn sload(&self, is_warm: bool) -> u64 {
+        if is_warm {
+            self.warm_access
+        } else {
+            self.cold_sload
+        }
+    }
+
+    /// Cost of an SSTORE of `value` into a slot holding `value_prev`, whose
+    /// committed value is `original_value`.
+    pub fn sstore(
+        &self,
+        value: Word,
+        value_prev: Word,
+        original_value: Word,
+        is_warm: bool,
+    ) -> u64 {
+        let warm_case_gas = if value_prev == value || original_value != value_prev {
+            self.warm_access
+        } else if original_value.is_zero() {
+            self.sstore_set
+        } else {
+            self.sstore_reset
+        };
+        if is_warm {
+            warm_case_gas
+        } else {
+            warm_case_gas + self.cold_sload
+        }
+    }
+
+    /// Tx refund after an SSTORE of `value` into a slot holding `value_prev`,
+    /// whose committed value is `original_value`, following
+    /// [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27).
+    pub fn sstore_tx_refund(
+        &self,
+        tx_refund_old: u64,
+        value: Word,
+        value_prev: Word,
+        original_value: Word,
+    ) -> u64 {
+        let mut tx_refund_new = tx_refund_old;
+
+        // The "clearing slot refund" and "resetting value refund" are ADDED
+        // together, they are NOT MUTUALLY EXCLUSIVE: with (original_value,
+        // value_prev, value) = (v, 0, v) where v != 0 both apply.
+        // Search "Apply both of the following clauses" in EIP-2200.
+        if value_prev != value {
+            // refund related to clearing slot
+            // "delete slot (2.1.2b)" can be safely merged in "delete slot (2.2.1.2)"
+            if !original_value.is_zero() {
+                if value_prev.is_zero() {
+                    // recreate slot (2.2.1.1)
+                    tx_refund_new -= self.sstore_clears_schedule;
+                }
+                if value.is_zero() {
+                    // delete slot (2.2.1.2)
+                    tx_refund_new += self.sstore_clears_schedule;
+                }
+            }
+
+            // refund related to resetting value
+            if original_value == value {
+                if original_value.is_zero() {
+                    // reset to original inexistent slot (2.2.2.1)
+                    tx_refund_new += self.sstore_set - self.warm_access;
+                } else {
+                    // reset to original existing slot (2.2.2.2)
+                    tx_refund_new += self.sstore_reset - self.warm_access;
+                }
+            }
+        }
+
+        tx_refund_new
+    }
+}
+
+/// The gas schedules of a chain, by the first block they apply to. The blocks
+/// before the first scheduled one use the default schedule.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSchedules(BTreeMap<u64, GasSchedule>);
+
+impl GasSchedules {
+    /// Apply `schedule` from the block `first_block` on, as a hardfork
+    /// repricing the storage opcodes does.
+    pub fn with_schedule(mut self, first_block: u64, schedule: GasSchedule) -> Self {
+        self.0.insert(first_block, schedule);
+        self
+    }
+
+    /// The schedule of the block `block_num`
+    pub fn schedule(&self, block_num: u64) -> GasSchedule {
+        self.0
+            .range(..=block_num)
+            .next_back()
+            .map_or_else(GasSchedule::default, |(_, schedule)| *schedule)
+    }
+
+    /// Every schedule with the blocks it applies to, from its first block to
+    /// the first block of the next schedule, `u64::MAX` for the last one. The
+    /// ranges cover all the blocks from 0.
+    pub fn ranges(&self) -> Vec<(u64, u64, GasSchedule)> {
+        let starts: Vec<_> = (!self.0.contains_key(&0))
+            .then(|| (0, GasSchedule::default()))
+            .into_iter()
+            .chain(
+                self.0
+                    .iter()
+                    .map(|(first_block, schedule)| (*first_block, *schedule)),
+            )
+            .collect();
+        starts
+            .iter()
+            .enumerate()
+            .map(|(i, (first_block, schedule))| {
+                let next_first_block = starts.get(i + 1).map_or(u64::MAX, |(next, _)| *next);
+                (*first_block, next_first_block, *schedule)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schedule_by_block_number() {
+        let curie = GasSchedule {
+            sstore_set: 22100,
+            ..Default::default()
+        };
+        let schedules = GasSchedules::default().with_schedule(100, curie);
+
+        assert_eq!(schedules.schedule(0), GasSchedule::default());
+        assert_eq!(schedules.schedule(99), GasSchedule::default());
+        assert_eq!(schedules.schedule(100), curie);
+        assert_eq!(schedules.schedule(u64::MAX), curie);
+        assert_eq!(
+            schedules.ranges(),
+            vec![(0, 100, GasSchedule::default()), (100, u64::MAX, curie)]
+        );
+        assert_eq!(
+            GasSchedules::default().with_schedule(0, curie).ranges(),
+            vec![(0, u64::MAX, curie)]
+        );
+
+        let (zero, one) = (Word::zero(), Word::one());
+        assert_eq!(curie.sstore(one, zero, zero, true), 22100);
+        assert_eq!(curie.sstore(one, zero, zero, false), 22100 + 2100);
+        assert_eq!(curie.sstore(zero, one, zero, true), 100);
+        assert_eq!(curie.sstore_tx_refund(0, zero, one, zero), 22100 - 100);
+    }
+}
@@ -35,9 +35,15 @@ pub fn memory_copier_gas_cost(
         }
 }
 
+/// All but one 64th of `gas_available`, the most gas that a call or create
+/// can pass to its callee per EIP 150.
+pub fn all_but_one_64th_gas(gas_available: u64) -> u64 {
+    gas_available - gas_available / 64
+}
+
 /// Calculate EIP 150 gas passed to callee.
 pub fn eip150_gas(gas_left: u64, gas_specified: Word) -> u64 {
-    let capped_gas = gas_left - gas_left / 64;
+    let capped_gas = all_but_one_64th_gas(gas_left);
 
     if gas_specified.bits() <= 64 {
         let gas_specified = gas_specified.low_u64();
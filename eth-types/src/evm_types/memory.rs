@@ -5,11 +5,11 @@ use core::{
     str::FromStr,
 };
 use itertools::Itertools;
-use serde::{Serialize, Serializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::{cmp, cmp::max, fmt};
 
 /// Represents a `MemoryAddress` of the EVM.
-#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct MemoryAddress(pub usize);
 
 impl fmt::Debug for MemoryAddress {
@@ -261,6 +261,16 @@ impl Serialize for Memory {
     }
 }
 
+impl<'de> Deserialize<'de> for Memory {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let encoded = String::deserialize(deserializer)?;
+        hex::decode(encoded).map(Memory).map_err(de::Error::custom)
+    }
+}
+
 define_range_index_variants!(
     IN_RANGE = usize,
     OUT_RANGE = MemoryAddress,
@@ -83,6 +83,8 @@ pub enum OpcodeId {
     MSTORE,
     /// `MSTORE8`
     MSTORE8,
+    /// `MCOPY`
+    MCOPY,
     /// `JUMP`
     JUMP,
     /// `JUMPI`
@@ -281,10 +283,18 @@ pub enum OpcodeId {
     SELFBALANCE,
     /// `BASEFEE`
     BASEFEE,
+    /// `BLOBHASH`
+    BLOBHASH,
+    /// `BLOBBASEFEE`
+    BLOBBASEFEE,
     /// `SLOAD`
     SLOAD,
     /// `SSTORE`
     SSTORE,
+    /// `TLOAD`
+    TLOAD,
+    /// `TSTORE`
+    TSTORE,
     /// `GAS`
     GAS,
 
@@ -420,6 +430,7 @@ impl OpcodeId {
             OpcodeId::MLOAD => 0x51u8,
             OpcodeId::MSTORE => 0x52u8,
             OpcodeId::MSTORE8 => 0x53u8,
+            OpcodeId::MCOPY => 0x5eu8,
             OpcodeId::JUMP => 0x56u8,
             OpcodeId::JUMPI => 0x57u8,
             OpcodeId::PC => 0x58u8,
@@ -514,8 +525,12 @@ impl OpcodeId {
             OpcodeId::CHAINID => 0x46u8,
             OpcodeId::SELFBALANCE => 0x47u8,
             OpcodeId::BASEFEE => 0x48u8,
+            OpcodeId::BLOBHASH => 0x49u8,
+            OpcodeId::BLOBBASEFEE => 0x4au8,
             OpcodeId::SLOAD => 0x54u8,
             OpcodeId::SSTORE => 0x55u8,
+            OpcodeId::TLOAD => 0x5cu8,
+            OpcodeId::TSTORE => 0x5du8,
             OpcodeId::GAS => 0x5au8,
             OpcodeId::LOG0 => 0xa0u8,
             OpcodeId::LOG1 => 0xa1u8,
@@ -592,12 +607,17 @@ impl OpcodeId {
             OpcodeId::CHAINID => GasCost::QUICK,
             OpcodeId::SELFBALANCE => GasCost::FAST,
             OpcodeId::BASEFEE => GasCost::QUICK,
+            OpcodeId::BLOBHASH => GasCost::FASTEST,
+            OpcodeId::BLOBBASEFEE => GasCost::QUICK,
             OpcodeId::POP => GasCost::QUICK,
             OpcodeId::MLOAD => GasCost::FASTEST,
             OpcodeId::MSTORE => GasCost::FASTEST,
             OpcodeId::MSTORE8 => GasCost::FASTEST,
+            OpcodeId::MCOPY => GasCost::FASTEST,
             OpcodeId::SLOAD => GasCost::ZERO,
             OpcodeId::SSTORE => GasCost::ZERO,
+            OpcodeId::TLOAD => GasCost::WARM_ACCESS,
+            OpcodeId::TSTORE => GasCost::WARM_ACCESS,
             OpcodeId::JUMP => GasCost::MID,
             OpcodeId::JUMPI => GasCost::SLOW,
             OpcodeId::PC => GasCost::QUICK,
@@ -747,12 +767,17 @@ impl OpcodeId {
             OpcodeId::CHAINID => (1, 1024),
             OpcodeId::SELFBALANCE => (1, 1024),
             OpcodeId::BASEFEE => (1, 1024),
+            OpcodeId::BLOBHASH => (0, 1023),
+            OpcodeId::BLOBBASEFEE => (1, 1024),
             OpcodeId::POP => (0, 1023),
             OpcodeId::MLOAD => (0, 1023),
             OpcodeId::MSTORE => (0, 1022),
             OpcodeId::MSTORE8 => (0, 1022),
+            OpcodeId::MCOPY => (0, 1021),
             OpcodeId::SLOAD => (0, 1023),
             OpcodeId::SSTORE => (0, 1022),
+            OpcodeId::TLOAD => (0, 1023),
+            OpcodeId::TSTORE => (0, 1022),
             OpcodeId::JUMP => (0, 1023),
             OpcodeId::JUMPI => (0, 1022),
             OpcodeId::PC => (1, 1024),
@@ -857,6 +882,7 @@ impl OpcodeId {
             OpcodeId::MLOAD
                 | OpcodeId::MSTORE
                 | OpcodeId::MSTORE8
+                | OpcodeId::MCOPY
                 | OpcodeId::CALLDATACOPY
                 | OpcodeId::RETURNDATACOPY
                 | OpcodeId::CODECOPY
@@ -964,6 +990,12 @@ impl From<u8> for OpcodeId {
             0x58u8 => OpcodeId::PC,
             0x59u8 => OpcodeId::MSIZE,
             0x5bu8 => OpcodeId::JUMPDEST,
+            #[cfg(feature = "cancun")]
+            0x5cu8 => OpcodeId::TLOAD,
+            #[cfg(feature = "cancun")]
+            0x5du8 => OpcodeId::TSTORE,
+            #[cfg(feature = "cancun")]
+            0x5eu8 => OpcodeId::MCOPY,
             #[cfg(feature = "shanghai")]
             0x5fu8 => OpcodeId::PUSH0,
             0x60u8 => OpcodeId::PUSH1,
@@ -1054,6 +1086,10 @@ impl From<u8> for OpcodeId {
             0x46u8 => OpcodeId::CHAINID,
             0x47u8 => OpcodeId::SELFBALANCE,
             0x48u8 => OpcodeId::BASEFEE,
+            #[cfg(feature = "cancun")]
+            0x49u8 => OpcodeId::BLOBHASH,
+            #[cfg(feature = "cancun")]
+            0x4au8 => OpcodeId::BLOBBASEFEE,
             0x54u8 => OpcodeId::SLOAD,
             0x55u8 => OpcodeId::SSTORE,
             0x5au8 => OpcodeId::GAS,
@@ -1115,6 +1151,8 @@ impl FromStr for OpcodeId {
             "MLOAD" => OpcodeId::MLOAD,
             "MSTORE" => OpcodeId::MSTORE,
             "MSTORE8" => OpcodeId::MSTORE8,
+            #[cfg(feature = "cancun")]
+            "MCOPY" => OpcodeId::MCOPY,
             "JUMP" => OpcodeId::JUMP,
             "JUMPI" => OpcodeId::JUMPI,
             "PC" => OpcodeId::PC,
@@ -1230,7 +1268,21 @@ impl FromStr for OpcodeId {
             "SELFDESTRUCT" => OpcodeId::SELFDESTRUCT,
             "CHAINID" => OpcodeId::CHAINID,
             "BASEFEE" => OpcodeId::BASEFEE,
+            #[cfg(feature = "cancun")]
+            "BLOBHASH" => OpcodeId::BLOBHASH,
+            #[cfg(feature = "cancun")]
+            "BLOBBASEFEE" => OpcodeId::BLOBBASEFEE,
+            #[cfg(not(feature = "cancun"))]
+            "BLOBHASH" => OpcodeId::INVALID(0x49),
+            #[cfg(not(feature = "cancun"))]
+            "BLOBBASEFEE" => OpcodeId::INVALID(0x4a),
+            #[cfg(feature = "cancun")]
+            "TLOAD" => OpcodeId::TLOAD,
+            #[cfg(feature = "cancun")]
+            "TSTORE" => OpcodeId::TSTORE,
+            #[cfg(not(feature = "cancun"))]
             "TLOAD" => OpcodeId::INVALID(0xb3),
+            #[cfg(not(feature = "cancun"))]
             "TSTORE" => OpcodeId::INVALID(0xb4),
             _ => {
                 // Parse an invalid opcode value as reported by geth
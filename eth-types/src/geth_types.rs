@@ -5,21 +5,26 @@ use crate::{
     AccessList, Address, Block, Bytes, Error, GethExecTrace, Hash, ToBigEndian, ToLittleEndian,
     Word, U64,
 };
-use ethers_core::types::{
-    Eip1559TransactionRequest, Eip2930TransactionRequest, NameOrAddress, TransactionRequest, H256,
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
+        Eip2930TransactionRequest, NameOrAddress, OtherFields, TransactionRequest, H256,
+    },
+    utils::rlp::RlpStream,
 };
 use ethers_signers::{LocalWallet, Signer};
 use halo2_proofs::halo2curves::{group::ff::PrimeField, secp256k1};
 use num::Integer;
 use num_bigint::BigUint;
-use serde::{Serialize, Serializer};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize, Serializer};
 use serde_with::serde_as;
 use sha3::{Digest, Keccak256};
-use std::collections::HashMap;
+use std::{borrow::Borrow, collections::HashMap};
 use strum_macros::EnumIter;
 
 /// Tx type
-#[derive(Default, Debug, Copy, Clone, EnumIter, Serialize, PartialEq, Eq)]
+#[derive(Default, Debug, Copy, Clone, EnumIter, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TxType {
     /// EIP 155 tx
     #[default]
@@ -32,6 +37,12 @@ pub enum TxType {
     Eip2930,
     /// L1 Message tx
     L1Msg,
+    /// EIP 4844 tx
+    Eip4844,
+    /// Pseudo-tx of a system call made by the chain itself, of EIP-2718 type
+    /// 0x7d. It is encoded like an L1 msg but doesn't consume the L1 message
+    /// queue, and isn't part of the data hash of the chunk.
+    SystemCall,
 }
 
 impl From<TxType> for usize {
@@ -52,12 +63,27 @@ impl TxType {
         matches!(*self, TxType::L1Msg)
     }
 
-    /// Get the type of transaction
+    /// If this type is SystemCall or not
+    pub fn is_system_call(&self) -> bool {
+        matches!(*self, TxType::SystemCall)
+    }
+
+    /// If this type carries no signature and pays no fee: the L1 msgs and
+    /// the system calls.
+    pub fn is_unsigned(&self) -> bool {
+        self.is_l1_msg() || self.is_system_call()
+    }
+
+    /// Get the type of transaction from its EIP-2718 type: 1 for EIP-2930, 2
+    /// for EIP-1559, 3 for EIP-4844, 0x7e for L1 msgs and 0x7d for the
+    /// pseudo-txs of system calls, the legacy txs being told apart by their v.
     pub fn get_tx_type(tx: &crate::Transaction) -> Self {
         match tx.transaction_type {
             Some(x) if x == U64::from(1) => Self::Eip2930,
-            Some(x) if x == U64::from(2) => Self::Eip2930,
+            Some(x) if x == U64::from(2) => Self::Eip1559,
+            Some(x) if x == U64::from(3) => Self::Eip4844,
             Some(x) if x == U64::from(0x7e) => Self::L1Msg,
+            Some(x) if x == U64::from(0x7d) => Self::SystemCall,
             _ => match tx.v.as_u64() {
                 0 | 1 | 27 | 28 => Self::PreEip155,
                 _ => Self::Eip155,
@@ -65,28 +91,22 @@ impl TxType {
         }
     }
 
-    /// Return the recovery id of signature for recovering the signing pk
-    pub fn get_recovery_id(&self, v: u64) -> u8 {
+    /// Return the recovery id of signature for recovering the signing pk, or
+    /// an error if `v` isn't one of a signature of the tx type: `{27, 28}`
+    /// for pre-EIP-155 txs, at least 35 for EIP-155 txs and `{0, 1}` for typed
+    /// txs.
+    pub fn get_recovery_id(&self, v: u64) -> Result<u8, Error> {
         let recovery_id = match *self {
-            TxType::Eip155 => (v + 1) % 2,
-            TxType::PreEip155 => {
-                assert!(v == 0x1b || v == 0x1c, "v: {v}");
-                v - 27
-            }
-            TxType::Eip1559 => {
-                assert!(v <= 1);
-                v
-            }
-            TxType::Eip2930 => {
-                assert!(v <= 1);
-                v
-            }
-            TxType::L1Msg => {
+            TxType::Eip155 if v >= 35 => (v + 1) % 2,
+            TxType::PreEip155 if v == 0x1b || v == 0x1c => v - 27,
+            TxType::Eip1559 | TxType::Eip2930 | TxType::Eip4844 if v <= 1 => v,
+            TxType::L1Msg | TxType::SystemCall => {
                 unreachable!("L1 msg does not have signature")
             }
+            _ => return Err(Error::Signature(libsecp256k1::Error::InvalidRecoveryId)),
         };
 
-        recovery_id as u8
+        Ok(recovery_id as u8)
     }
 }
 
@@ -101,21 +121,108 @@ pub fn get_rlp_unsigned(tx: &crate::Transaction) -> Vec<u8> {
             let tx: TransactionRequest = tx.into();
             tx.rlp_unsigned().to_vec()
         }
+        // the typed txs sign their EIP-2718 type along with their fields
         TxType::Eip1559 => {
             let tx: Eip1559TransactionRequest = tx.into();
-            tx.rlp().to_vec()
+            TypedTransaction::from(tx).rlp().to_vec()
         }
         TxType::Eip2930 => {
             let tx: Eip2930TransactionRequest = tx.into();
-            tx.rlp().to_vec()
+            TypedTransaction::from(tx).rlp().to_vec()
         }
-        TxType::L1Msg => {
+        TxType::L1Msg | TxType::SystemCall => {
             // L1 msg does not have signature
             vec![]
         }
+        TxType::Eip4844 => get_rlp_eip4844(tx, false),
     }
 }
 
+/// Get the RLP bytes of the signed tx
+pub fn get_rlp_signed(tx: &crate::Transaction) -> Vec<u8> {
+    match TxType::get_tx_type(tx) {
+        // ethers does not know the type-3 payload
+        TxType::Eip4844 => get_rlp_eip4844(tx, true),
+        // nor the pseudo-tx of a system call, which has the fields of an L1
+        // msg under its own type
+        TxType::SystemCall => {
+            let l1_msg = crate::Transaction {
+                transaction_type: Some(U64::from(0x7e)),
+                ..tx.clone()
+            };
+            let mut rlp = l1_msg.rlp().to_vec();
+            rlp[0] = 0x7d;
+            rlp
+        }
+        _ => tx.rlp().to_vec(),
+    }
+}
+
+const MAX_FEE_PER_BLOB_GAS: &str = "maxFeePerBlobGas";
+const BLOB_VERSIONED_HASHES: &str = "blobVersionedHashes";
+
+/// Get the `maxFeePerBlobGas` of an EIP-4844 tx, which ethers keeps in the
+/// `other` fields of the tx.
+pub fn get_max_fee_per_blob_gas(tx: &crate::Transaction) -> Word {
+    tx.other
+        .get_deserialized(MAX_FEE_PER_BLOB_GAS)
+        .and_then(Result::ok)
+        .unwrap_or_default()
+}
+
+/// Get the `blobVersionedHashes` of an EIP-4844 tx, which ethers keeps in the
+/// `other` fields of the tx.
+pub fn get_blob_versioned_hashes(tx: &crate::Transaction) -> Vec<H256> {
+    tx.other
+        .get_deserialized(BLOB_VERSIONED_HASHES)
+        .and_then(Result::ok)
+        .unwrap_or_default()
+}
+
+const EXCESS_BLOB_GAS: &str = "excessBlobGas";
+
+/// Get the `excessBlobGas` of a post EIP-4844 block, which ethers keeps in the
+/// `other` fields of the block. Zero for the blocks before EIP-4844.
+pub fn get_excess_blob_gas(block: &Block<crate::Transaction>) -> u64 {
+    block
+        .other
+        .get_deserialized::<U64>(EXCESS_BLOB_GAS)
+        .and_then(Result::ok)
+        .unwrap_or_default()
+        .as_u64()
+}
+
+/// 0x03 || rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas,
+/// gas_limit, to, value, data, access_list, max_fee_per_blob_gas,
+/// blob_versioned_hashes]) for signing, with (y_parity, r, s) appended to
+/// the list when `signed`.
+fn get_rlp_eip4844(tx: &crate::Transaction, signed: bool) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(if signed { 14 } else { 11 });
+    stream.append(&tx.chain_id.unwrap_or_default());
+    stream.append(&tx.nonce);
+    stream.append(&tx.max_priority_fee_per_gas.unwrap_or_default());
+    stream.append(&tx.max_fee_per_gas.unwrap_or_default());
+    stream.append(&tx.gas);
+    match tx.to {
+        Some(to) => stream.append(&to),
+        None => stream.append_empty_data(),
+    };
+    stream.append(&tx.value);
+    stream.append(&tx.input);
+    stream.append(&tx.access_list.clone().unwrap_or_default());
+    stream.append(&get_max_fee_per_blob_gas(tx));
+    stream.append_list::<H256, _>(&get_blob_versioned_hashes(tx));
+    if signed {
+        stream.append(&tx.v);
+        stream.append(&tx.r);
+        stream.append(&tx.s);
+    }
+
+    let mut rlp = vec![0x03];
+    rlp.extend_from_slice(&stream.out());
+    rlp
+}
+
 /// Definition of all of the data related to an account.
 #[serde_as]
 #[derive(PartialEq, Eq, Debug, Default, Clone, Serialize)]
@@ -235,6 +342,10 @@ pub struct Transaction {
     pub call_data: Bytes,
     /// Access list
     pub access_list: Option<AccessList>,
+    /// Max fee per blob gas (EIP-4844)
+    pub max_fee_per_blob_gas: Word,
+    /// Blob versioned hashes (EIP-4844)
+    pub blob_versioned_hashes: Vec<H256>,
 
     /// "v" value of the transaction signature
     pub v: u64,
@@ -254,6 +365,15 @@ pub struct Transaction {
 
 impl From<&Transaction> for crate::Transaction {
     fn from(tx: &Transaction) -> crate::Transaction {
+        let other = if tx.tx_type == TxType::Eip4844 {
+            serde_json::from_value(serde_json::json!({
+                MAX_FEE_PER_BLOB_GAS: tx.max_fee_per_blob_gas,
+                BLOB_VERSIONED_HASHES: tx.blob_versioned_hashes,
+            }))
+            .expect("blob fields are valid other fields")
+        } else {
+            OtherFields::default()
+        };
         crate::Transaction {
             from: tx.from,
             to: tx.to,
@@ -269,6 +389,8 @@ impl From<&Transaction> for crate::Transaction {
             r: tx.r,
             s: tx.s,
             hash: tx.hash,
+            transaction_type: (tx.tx_type == TxType::Eip4844).then(|| U64::from(3)),
+            other,
             ..Default::default()
         }
     }
@@ -288,10 +410,12 @@ impl From<&crate::Transaction> for Transaction {
             gas_tip_cap: tx.max_fee_per_gas.unwrap_or_default(),
             call_data: tx.input.clone(),
             access_list: tx.access_list.clone(),
+            max_fee_per_blob_gas: get_max_fee_per_blob_gas(tx),
+            blob_versioned_hashes: get_blob_versioned_hashes(tx),
             v: tx.v.as_u64(),
             r: tx.r,
             s: tx.s,
-            rlp_bytes: tx.rlp().to_vec(),
+            rlp_bytes: get_rlp_signed(tx),
             rlp_unsigned_bytes: get_rlp_unsigned(tx),
             hash: tx.hash,
         }
@@ -332,7 +456,7 @@ impl Transaction {
             .to_vec()
             .try_into()
             .expect("hash length isn't 32 bytes");
-        let v = self.tx_type.get_recovery_id(self.v);
+        let v = self.tx_type.get_recovery_id(self.v)?;
         let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
         // msg_hash = msg_hash % q
         let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
@@ -351,6 +475,54 @@ impl Transaction {
     }
 }
 
+impl SignData {
+    /// Return the SignData of all `txs`, computed in parallel. L1 msgs aren't
+    /// signed and get the default SignData. The pubkey recovery of all the
+    /// txs shares the secp256k1 context of libsecp256k1, whose generator
+    /// tables are precomputed at build time.
+    ///
+    /// On failure, returns the index and error of every tx whose signature
+    /// is invalid.
+    pub fn batch_from_txs<T: Borrow<Transaction> + Sync>(
+        txs: &[T],
+    ) -> Result<Vec<Self>, Vec<(usize, Error)>> {
+        Self::batch(txs, |tx| {
+            let tx = tx.borrow();
+            if tx.tx_type.is_unsigned() {
+                Ok(Self::default())
+            } else {
+                tx.sign_data()
+            }
+        })
+    }
+
+    /// Return the SignData of all `txs` by `sign_data`, computed in parallel,
+    /// for any tx type.
+    ///
+    /// On failure, returns the index and error of every tx whose signature
+    /// is invalid.
+    pub fn batch<T: Sync>(
+        txs: &[T],
+        sign_data: impl Fn(&T) -> Result<Self, Error> + Sync + Send,
+    ) -> Result<Vec<Self>, Vec<(usize, Error)>> {
+        let results: Vec<Result<Self, Error>> = txs.par_iter().map(sign_data).collect();
+
+        let mut sign_datas = Vec::with_capacity(results.len());
+        let mut errors = Vec::new();
+        for (i, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(sign_data) => sign_datas.push(sign_data),
+                Err(err) => errors.push((i, err)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(sign_datas)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 /// GethData is a type that contains all the information of a Ethereum block
 #[derive(Debug, Clone)]
 pub struct GethData {
@@ -385,3 +557,141 @@ impl GethData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::k256::ecdsa::SigningKey;
+
+    fn signed_tx(nonce: u64) -> Transaction {
+        let chain_id = 1;
+        let wallet =
+            LocalWallet::from(SigningKey::from_bytes(&[1u8; 32]).unwrap()).with_chain_id(chain_id);
+        let req = TransactionRequest::new()
+            .chain_id(chain_id)
+            .from(wallet.address())
+            .to(Address::zero())
+            .nonce(nonce)
+            .gas(21000)
+            .gas_price(1);
+        let typed: TypedTransaction = req.clone().into();
+        let sig = wallet.sign_transaction_sync(&typed);
+        Transaction {
+            tx_type: TxType::Eip155,
+            from: wallet.address(),
+            nonce: nonce.into(),
+            v: sig.v,
+            r: sig.r,
+            s: sig.s,
+            rlp_unsigned_bytes: req.rlp().to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sign_data_batch_from_txs() {
+        let l1_msg = Transaction {
+            tx_type: TxType::L1Msg,
+            ..Default::default()
+        };
+        let txs = vec![signed_tx(0), l1_msg, signed_tx(1)];
+        let sign_datas = SignData::batch_from_txs(&txs).unwrap();
+        assert_eq!(sign_datas.len(), 3);
+        assert_eq!(sign_datas[0].get_addr(), txs[0].from);
+        assert_eq!(sign_datas[2].get_addr(), txs[2].from);
+
+        let mut invalid = signed_tx(2);
+        invalid.s = Word::MAX;
+        let errors = SignData::batch_from_txs(&[&txs[0], &invalid, &invalid]).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn eip4844_rlp() {
+        let blob_versioned_hashes = vec![H256::repeat_byte(1), H256::repeat_byte(2)];
+        let tx = Transaction {
+            tx_type: TxType::Eip4844,
+            to: Some(Address::repeat_byte(0xaa)),
+            nonce: 7.into(),
+            gas_limit: 21000.into(),
+            max_fee_per_blob_gas: 3.into(),
+            blob_versioned_hashes: blob_versioned_hashes.clone(),
+            v: 1,
+            r: Word::one(),
+            s: Word::one(),
+            ..Default::default()
+        };
+        let eth_tx = crate::Transaction::from(&tx);
+        assert_eq!(TxType::get_tx_type(&eth_tx), TxType::Eip4844);
+
+        let tx = Transaction::from(&eth_tx);
+        assert_eq!(tx.max_fee_per_blob_gas, 3.into());
+        assert_eq!(tx.blob_versioned_hashes, blob_versioned_hashes);
+
+        for (rlp_bytes, num_items) in [(&tx.rlp_unsigned_bytes, 11), (&tx.rlp_bytes, 14)] {
+            assert_eq!(rlp_bytes[0], 0x03);
+            let rlp = ethers_core::utils::rlp::Rlp::new(&rlp_bytes[1..]);
+            assert_eq!(rlp.item_count().unwrap(), num_items);
+            assert_eq!(rlp.val_at::<Word>(9).unwrap(), 3.into());
+            assert_eq!(rlp.list_at::<H256>(10).unwrap(), blob_versioned_hashes);
+        }
+    }
+
+    #[test]
+    fn typed_rlp_unsigned() {
+        for (tx_type, prefix) in [(TxType::Eip2930, 1u8), (TxType::Eip1559, 2u8)] {
+            let eth_tx = crate::Transaction {
+                transaction_type: Some(U64::from(prefix)),
+                to: Some(Address::repeat_byte(0xaa)),
+                gas: 21000.into(),
+                ..Default::default()
+            };
+            assert_eq!(TxType::get_tx_type(&eth_tx), tx_type);
+
+            let rlp_unsigned = get_rlp_unsigned(&eth_tx);
+            assert_eq!(rlp_unsigned[0], prefix);
+            assert!(ethers_core::utils::rlp::Rlp::new(&rlp_unsigned[1..]).is_list());
+        }
+    }
+
+    #[test]
+    fn system_call_rlp() {
+        let system_call = crate::Transaction {
+            transaction_type: Some(U64::from(0x7d)),
+            to: Some(Address::repeat_byte(0xaa)),
+            gas: 21000.into(),
+            ..Default::default()
+        };
+        assert_eq!(TxType::get_tx_type(&system_call), TxType::SystemCall);
+        let l1_msg = crate::Transaction {
+            transaction_type: Some(U64::from(0x7e)),
+            ..system_call.clone()
+        };
+        assert_eq!(TxType::get_tx_type(&l1_msg), TxType::L1Msg);
+
+        // the fields of an L1 msg under the type of a system call
+        let rlp_signed = get_rlp_signed(&system_call);
+        assert_eq!(rlp_signed[0], 0x7d);
+        assert_eq!(rlp_signed[1..], get_rlp_signed(&l1_msg)[1..]);
+        assert!(get_rlp_unsigned(&system_call).is_empty());
+    }
+
+    #[test]
+    fn recovery_id() {
+        assert_eq!(TxType::PreEip155.get_recovery_id(28).unwrap(), 1);
+        assert_eq!(TxType::Eip155.get_recovery_id(2 * 1338 + 35).unwrap(), 0);
+        assert_eq!(TxType::Eip1559.get_recovery_id(1).unwrap(), 1);
+        for (tx_type, v) in [
+            (TxType::PreEip155, 1),
+            (TxType::Eip155, 34),
+            (TxType::Eip1559, 2),
+            (TxType::Eip2930, 27),
+            (TxType::Eip4844, 2),
+        ] {
+            assert!(tx_type.get_recovery_id(v).is_err());
+        }
+    }
+}
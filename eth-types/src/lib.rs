@@ -265,7 +265,7 @@ impl<F: Field> ToScalar<F> for usize {
 }
 
 /// Struct used to define the storage proof
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StorageProof {
     /// Storage key
     pub key: U256,
@@ -276,7 +276,7 @@ pub struct StorageProof {
 }
 
 /// Struct used to define the result of `eth_getProof` call
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EIP1186ProofResponse {
     /// Account address
@@ -320,11 +320,46 @@ struct GethExecStepInternal {
     // storage is hex -> hex
     #[serde(default)]
     storage: HashMap<DebugU256, DebugU256>,
+    // only present in traces of layout version 2
+    #[serde(default, rename = "extraData")]
+    extra_data: Option<GethExecStepExtraData>,
+}
+
+#[derive(Deserialize)]
+#[doc(hidden)]
+struct GethExecStepExtraData {
+    #[serde(default, rename = "proofList")]
+    proof_list: Vec<StorageProof>,
+}
+
+/// A [`GethExecStep`] in the layout of [`GethExecStepInternal`], so that the
+/// serialized steps are read back as they are.
+#[derive(Serialize)]
+struct GethExecStepExternal<'a> {
+    pc: ProgramCounter,
+    op: String,
+    gas: Gas,
+    refund: Gas,
+    #[serde(rename = "gasCost")]
+    gas_cost: GasCost,
+    depth: u16,
+    error: &'a Option<String>,
+    stack: Vec<String>,
+    memory: Vec<String>,
+    storage: &'a Storage,
+    #[serde(rename = "extraData", skip_serializing_if = "Option::is_none")]
+    extra_data: Option<GethExecStepExtraDataExternal<'a>>,
+}
+
+#[derive(Serialize)]
+struct GethExecStepExtraDataExternal<'a> {
+    #[serde(rename = "proofList")]
+    proof_list: &'a [StorageProof],
 }
 
 /// The execution step type returned by geth RPC debug_trace* methods.
 /// Corresponds to `StructLogRes` in `go-ethereum/internal/ethapi/api.go`.
-#[derive(Clone, Eq, PartialEq, Serialize)]
+#[derive(Clone, Eq, PartialEq)]
 #[doc(hidden)]
 pub struct GethExecStep {
     pub pc: ProgramCounter,
@@ -340,6 +375,8 @@ pub struct GethExecStep {
     pub memory: Memory,
     // storage is hex -> hex
     pub storage: Storage,
+    // storage proofs attached to the step, only in traces of layout version 2
+    pub storage_proofs: Vec<StorageProof>,
 }
 
 // Wrapper over u8 that provides formats the byte in hex for [`fmt::Debug`].
@@ -404,10 +441,50 @@ impl<'de> Deserialize<'de> for GethExecStep {
                     .map(|(k, v)| (k.to_word(), v.to_word()))
                     .collect(),
             ),
+            storage_proofs: s
+                .extra_data
+                .map(|extra_data| extra_data.proof_list)
+                .unwrap_or_default(),
         })
     }
 }
 
+impl Serialize for GethExecStep {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let op = match self.op {
+            // as reported by geth, see `OpcodeId::from_str`
+            OpcodeId::INVALID(byte) => format!("opcode {byte:#04x} not defined"),
+            op => op.to_string(),
+        };
+        GethExecStepExternal {
+            pc: self.pc,
+            op,
+            gas: self.gas,
+            refund: self.refund,
+            gas_cost: self.gas_cost,
+            depth: self.depth,
+            error: &self.error,
+            stack: self
+                .stack
+                .0
+                .iter()
+                .map(|word| format!("0x{word:x}"))
+                .collect(),
+            memory: self.memory.0.chunks(32).map(hex::encode).collect(),
+            storage: &self.storage,
+            extra_data: (!self.storage_proofs.is_empty()).then_some(
+                GethExecStepExtraDataExternal {
+                    proof_list: &self.storage_proofs,
+                },
+            ),
+        }
+        .serialize(serializer)
+    }
+}
+
 /// Helper type built to deal with the weird `result` field added between
 /// `GethExecutionTrace`s in `debug_traceBlockByHash` and
 /// `debug_traceBlockByNumber` Geth JSON-RPC calls.
@@ -424,28 +501,142 @@ pub struct ResultGethExecTrace {
     pub result: GethExecTrace,
 }
 
+/// Layout of a [`GethExecTrace`] as emitted by l2geth, selected by the
+/// `version` tag embedded in the trace. Traces without a tag are in the
+/// legacy layout, so that blocks traced by nodes before and after an upgrade
+/// can be proven together.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GethExecTraceVersion {
+    /// Legacy layout: L1 fee in `l1_fee`, no per-step proofs.
+    #[default]
+    V1,
+    /// L1 fee in `l1DataFee`, storage proofs attached to the steps in
+    /// `extraData.proofList`.
+    V2,
+}
+
+impl TryFrom<u64> for GethExecTraceVersion {
+    type Error = String;
+
+    fn try_from(version: u64) -> Result<Self, Self::Error> {
+        match version {
+            1 => Ok(Self::V1),
+            2 => Ok(Self::V2),
+            _ => Err(format!("unsupported trace version {version}")),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[doc(hidden)]
+struct GethExecTraceInternal {
+    #[serde(default)]
+    version: Option<u64>,
+    // version 1
+    #[serde(default)]
+    l1_fee: Option<u64>,
+    // version 2
+    #[serde(default, rename = "l1DataFee")]
+    l1_data_fee: Option<u64>,
+    gas: Gas,
+    failed: bool,
+    #[serde(rename = "returnValue")]
+    return_value: String,
+    #[serde(rename = "structLogs")]
+    struct_logs: Vec<GethExecStep>,
+}
+
 /// The execution trace type returned by geth RPC debug_trace* methods.
 /// Corresponds to `ExecutionResult` in `go-ethereum/internal/ethapi/api.go`.
 /// The deserialization truncates the memory of each step in `struct_logs` to
 /// the memory size before the expansion, so that it corresponds to the memory
 /// before the step is executed.
-#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+/// All the layouts of [`GethExecTraceVersion`] are accepted; fields of a
+/// layout other than the tagged one are rejected.  A trace is serialized in
+/// the layout of version 2, so that it reads back as it is.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct GethExecTrace {
     /// L1 fee
-    #[serde(default)]
     pub l1_fee: u64,
     /// Used gas
     pub gas: Gas,
     /// True when the transaction has failed.
     pub failed: bool,
     /// Return value of execution which is a hex encoded byte array
-    #[serde(rename = "returnValue")]
     pub return_value: String,
     /// Vector of geth execution steps of the trace.
-    #[serde(rename = "structLogs")]
     pub struct_logs: Vec<GethExecStep>,
 }
 
+#[derive(Serialize)]
+struct GethExecTraceExternal<'a> {
+    version: u64,
+    #[serde(rename = "l1DataFee")]
+    l1_data_fee: u64,
+    gas: Gas,
+    failed: bool,
+    #[serde(rename = "returnValue")]
+    return_value: &'a str,
+    #[serde(rename = "structLogs")]
+    struct_logs: &'a [GethExecStep],
+}
+
+impl Serialize for GethExecTrace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        GethExecTraceExternal {
+            version: 2,
+            l1_data_fee: self.l1_fee,
+            gas: self.gas,
+            failed: self.failed,
+            return_value: &self.return_value,
+            struct_logs: &self.struct_logs,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GethExecTrace {
+    fn deserialize<D>(deserializer: D) -> Result<GethExecTrace, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let t = GethExecTraceInternal::deserialize(deserializer)?;
+        let version = t
+            .version
+            .map(GethExecTraceVersion::try_from)
+            .transpose()
+            .map_err(de::Error::custom)?
+            .unwrap_or_default();
+        let l1_fee = match version {
+            GethExecTraceVersion::V1 => {
+                if t.l1_data_fee.is_some() {
+                    return Err(de::Error::custom("l1DataFee in trace of version 1"));
+                }
+                if t.struct_logs.iter().any(|s| !s.storage_proofs.is_empty()) {
+                    return Err(de::Error::custom("step proofs in trace of version 1"));
+                }
+                t.l1_fee
+            }
+            GethExecTraceVersion::V2 => {
+                if t.l1_fee.is_some() {
+                    return Err(de::Error::custom("l1_fee in trace of version 2"));
+                }
+                t.l1_data_fee
+            }
+        };
+        Ok(Self {
+            l1_fee: l1_fee.unwrap_or_default(),
+            gas: t.gas,
+            failed: t.failed,
+            return_value: t.return_value,
+            struct_logs: t.struct_logs,
+        })
+    }
+}
+
 #[macro_export]
 /// Create an [`Address`] from a hex string.  Panics on invalid input.
 macro_rules! address {
@@ -567,6 +758,7 @@ mod tests {
                         stack: Stack::new(),
                         storage: Storage(word_map!()),
                         memory: Memory::new(),
+                        storage_proofs: vec![],
                     },
                     GethExecStep {
                         pc: ProgramCounter(163),
@@ -579,6 +771,7 @@ mod tests {
                         stack: Stack(vec![word!("0x1003e2d2"), word!("0x2a"), word!("0x0")]),
                         storage: Storage(word_map!("0x0" => "0x6f")),
                         memory: Memory::from(vec![word!("0x0"), word!("0x0"), word!("0x080")]),
+                        storage_proofs: vec![],
                     },
                     GethExecStep {
                         pc: ProgramCounter(189),
@@ -614,11 +807,98 @@ mod tests {
                                 "00000000000000000000000000000000000000000000003635c9adc5dea00000"
                             ),
                         ]),
+                        storage_proofs: vec![],
                     }
                 ],
             }
         );
     }
+
+    #[test]
+    fn deserialize_geth_exec_trace_versions() {
+        let step = r#"{"pc": 0, "op": "SLOAD", "gas": 22705, "gasCost": 2100, "depth": 1, "stack": ["0x0"]}"#;
+        let step_with_proof = r#"{
+            "pc": 0, "op": "SLOAD", "gas": 22705, "gasCost": 2100, "depth": 1, "stack": ["0x0"],
+            "extraData": {"proofList": [{"key": "0x0", "value": "0x6f", "proof": ["0x01"]}]}
+        }"#;
+
+        let v1 = format!(
+            r#"{{"l1_fee": 7, "gas": 1, "failed": false, "returnValue": "", "structLogs": [{step}]}}"#
+        );
+        let trace: GethExecTrace = serde_json::from_str(&v1).unwrap();
+        assert_eq!(trace.l1_fee, 7);
+        let tagged_v1 = format!(
+            r#"{{"version": 1, "l1_fee": 7, "gas": 1, "failed": false, "returnValue": "", "structLogs": [{step}]}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<GethExecTrace>(&tagged_v1).unwrap(),
+            trace
+        );
+
+        let v2 = format!(
+            r#"{{"version": 2, "l1DataFee": 7, "gas": 1, "failed": false, "returnValue": "", "structLogs": [{step_with_proof}]}}"#
+        );
+        let trace: GethExecTrace = serde_json::from_str(&v2).unwrap();
+        assert_eq!(trace.l1_fee, 7);
+        assert_eq!(
+            trace.struct_logs[0].storage_proofs,
+            vec![StorageProof {
+                key: U256::zero(),
+                value: U256::from(0x6f),
+                proof: vec![Bytes::from(vec![1u8])],
+            }]
+        );
+
+        // fields of another layout are rejected
+        let mixed = format!(
+            r#"{{"l1DataFee": 7, "gas": 1, "failed": false, "returnValue": "", "structLogs": [{step}]}}"#
+        );
+        assert!(serde_json::from_str::<GethExecTrace>(&mixed).is_err());
+        let mixed = format!(
+            r#"{{"gas": 1, "failed": false, "returnValue": "", "structLogs": [{step_with_proof}]}}"#
+        );
+        assert!(serde_json::from_str::<GethExecTrace>(&mixed).is_err());
+        let unknown = format!(
+            r#"{{"version": 3, "gas": 1, "failed": false, "returnValue": "", "structLogs": [{step}]}}"#
+        );
+        assert!(serde_json::from_str::<GethExecTrace>(&unknown).is_err());
+    }
+
+    #[test]
+    fn serialize_geth_exec_trace_roundtrip() {
+        let trace_json = r#"{
+            "l1_fee": 7, "gas": 1, "failed": true, "returnValue": "2a",
+            "structLogs": [
+                {
+                    "pc": 3, "op": "MSTORE", "gas": 22705, "gasCost": 6, "refund": 4, "depth": 1,
+                    "stack": ["0x2a", "0x0"],
+                    "memory": ["0000000000000000000000000000000000000000000000000000000000000001"],
+                    "storage": {"01": "02"}
+                },
+                {
+                    "pc": 4, "op": "opcode 0xef not defined", "gas": 22699, "gasCost": 0,
+                    "depth": 1, "error": "invalid opcode: opcode 0xef not defined", "stack": []
+                }
+            ]
+        }"#;
+        let trace: GethExecTrace = serde_json::from_str(trace_json).unwrap();
+        assert_eq!(trace.struct_logs[1].op, OpcodeId::INVALID(0xef));
+        let roundtrip: GethExecTrace =
+            serde_json::from_str(&serde_json::to_string(&trace).unwrap()).unwrap();
+        assert_eq!(roundtrip, trace);
+
+        let step_with_proof = r#"{
+            "pc": 0, "op": "SLOAD", "gas": 22705, "gasCost": 2100, "depth": 1, "stack": ["0x0"],
+            "extraData": {"proofList": [{"key": "0x0", "value": "0x6f", "proof": ["0x01"]}]}
+        }"#;
+        let trace: GethExecTrace = serde_json::from_str(&format!(
+            r#"{{"version": 2, "l1DataFee": 7, "gas": 1, "failed": false, "returnValue": "", "structLogs": [{step_with_proof}]}}"#
+        ))
+        .unwrap();
+        let roundtrip: GethExecTrace =
+            serde_json::from_str(&serde_json::to_string(&trace).unwrap()).unwrap();
+        assert_eq!(roundtrip, trace);
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 //! secp256k1 signature types and helper functions.
 
-use crate::{ToBigEndian, Word};
+use crate::{ToBigEndian, ToLittleEndian, Word};
 use ethers_core::{
     types::{Address, Bytes},
     utils::keccak256,
@@ -18,6 +18,7 @@ use halo2_proofs::{
 };
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 use subtle::CtOption;
 
@@ -48,7 +49,8 @@ pub fn sign(
 
 /// Signature data required by the SignVerify Chip as input to verify a
 /// signature.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "SignDataRepr", try_from = "SignDataRepr")]
 pub struct SignData {
     /// Secp256k1 signature point (r, s, v)
     /// v must be 0 or 1
@@ -73,6 +75,66 @@ impl SignData {
     }
 }
 
+/// Serde representation of a [`SignData`], with the secp256k1 scalars as words
+/// and the public key as its coordinates in little endian bytes.
+#[derive(Serialize, Deserialize)]
+struct SignDataRepr {
+    sig_r: Word,
+    sig_s: Word,
+    sig_v: u8,
+    pk: Bytes,
+    msg: Bytes,
+    msg_hash: Word,
+}
+
+impl From<SignData> for SignDataRepr {
+    fn from(sign_data: SignData) -> Self {
+        let word = |fq: secp256k1::Fq| Word::from_little_endian(&fq.to_bytes());
+        let (sig_r, sig_s, sig_v) = sign_data.signature;
+        Self {
+            sig_r: word(sig_r),
+            sig_s: word(sig_s),
+            sig_v,
+            pk: pk_bytes_le(&sign_data.pk).to_vec().into(),
+            msg: sign_data.msg,
+            msg_hash: word(sign_data.msg_hash),
+        }
+    }
+}
+
+impl TryFrom<SignDataRepr> for SignData {
+    type Error = String;
+
+    fn try_from(repr: SignDataRepr) -> Result<Self, Self::Error> {
+        let fq = |word: Word| {
+            ct_option_ok_or(
+                secp256k1::Fq::from_bytes(&word.to_le_bytes()),
+                format!("{word:#x} is not a secp256k1 scalar"),
+            )
+        };
+        let invalid_pk = || format!("invalid public key 0x{}", hex::encode(&repr.pk));
+        if repr.pk.len() != 64 {
+            return Err(invalid_pk());
+        }
+        let fp = |bytes: &[u8]| {
+            ct_option_ok_or(
+                secp256k1::Fp::from_bytes(bytes.try_into().unwrap()),
+                invalid_pk(),
+            )
+        };
+        let pk = ct_option_ok_or(
+            Secp256k1Affine::from_xy(fp(&repr.pk[..32])?, fp(&repr.pk[32..])?),
+            invalid_pk(),
+        )?;
+        Ok(Self {
+            signature: (fq(repr.sig_r)?, fq(repr.sig_s)?, repr.sig_v),
+            pk,
+            msg: repr.msg,
+            msg_hash: fq(repr.msg_hash)?,
+        })
+    }
+}
+
 lazy_static! {
     // FIXME: use Transaction::dummy().sign_data() instead when we merged the develop branch
     static ref SIGN_DATA_DEFAULT: SignData = {
@@ -21,6 +21,7 @@ pub mod less_than;
 pub mod monotone;
 pub mod mul_add;
 pub mod util;
+pub mod word_cmp;
 
 use eth_types::Field;
 use halo2_proofs::{
@@ -0,0 +1,737 @@
+//! Comparison chips over 256-bit words, each word being given as its `(lo, hi)`
+//! 128-bit halves.
+//!
+//! - [`IsEqualWordChip`] checks `lhs == rhs`.
+//! - [`LtWordChip`] checks `lhs < rhs`.
+//! - [`MinMaxWordChip`] selects `min(lhs, rhs)` and `max(lhs, rhs)`.
+//!
+//! The lt of each pair of halves is witnessed by `diff = lhs - rhs + lt *
+//! 2^128` in the 16 diff bytes of a [`LtChip`]. [`LtWordChip`] decomposes the
+//! halves into bytes and looks up these bytes and the diff bytes in a u8 range
+//! table given by the caller, so that each half and each diff is in
+//! `[0, 2^128)` and the lt outcome can't be forged. [`IsEqualWordChip`] only
+//! compares the halves and needs no range check.
+//!
+//! The expression and witness helpers ([`lt_word_expr`], [`word_lo_hi`]) are
+//! shared with the word comparison gadgets of the EVM circuit.
+
+use eth_types::{Field, Word};
+use halo2_proofs::{
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+
+use crate::{
+    is_equal::{IsEqualChip, IsEqualConfig, IsEqualInstruction},
+    less_than::{LtChip, LtConfig, LtInstruction},
+    util::{expr_from_bytes, select, split_u256, Expr},
+};
+
+/// Returns the `(lo, hi)` 128-bit halves of a word as field elements.
+pub fn word_lo_hi<F: Field>(word: Word) -> (F, F) {
+    let (lo, hi) = split_u256(&word);
+    (F::from_u128(lo.as_u128()), F::from_u128(hi.as_u128()))
+}
+
+/// Returns `lhs < rhs` for two words, given `hi_lt = lhs_hi < rhs_hi`,
+/// `hi_eq = lhs_hi == rhs_hi` and `lo_lt = lhs_lo < rhs_lo`.
+pub fn lt_word_expr<F: Field>(
+    hi_lt: Expression<F>,
+    hi_eq: Expression<F>,
+    lo_lt: Expression<F>,
+) -> Expression<F> {
+    hi_lt + hi_eq * lo_lt
+}
+
+/// Instruction that the IsEqualWord chip needs to implement.
+pub trait IsEqualWordInstruction<F: Field> {
+    /// Assign the lhs and rhs words to the IsEqualWord chip's region.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error>;
+}
+
+/// Config for the IsEqualWord chip.
+#[derive(Clone, Debug)]
+pub struct IsEqualWordConfig<F> {
+    /// Compares the lo halves.
+    lo_eq: IsEqualConfig<F>,
+    /// Compares the hi halves.
+    hi_eq: IsEqualConfig<F>,
+    /// Expression that denotes whether the words are equal or not.
+    pub is_equal_word_expression: Expression<F>,
+}
+
+impl<F: Field> IsEqualWordConfig<F> {
+    /// Returns the is_equal expression.
+    pub fn expr(&self) -> Expression<F> {
+        self.is_equal_word_expression.expr()
+    }
+}
+
+/// Chip that compares equality between two words.
+#[derive(Clone, Debug)]
+pub struct IsEqualWordChip<F> {
+    config: IsEqualWordConfig<F>,
+}
+
+impl<F: Field> IsEqualWordChip<F> {
+    /// Configure the IsEqualWord chip. `lhs` and `rhs` return the `(lo, hi)`
+    /// halves of the words.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+        rhs: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+    ) -> IsEqualWordConfig<F> {
+        let lo_eq = IsEqualChip::configure(
+            meta,
+            |meta| q_enable(meta),
+            |meta| lhs(meta).0,
+            |meta| rhs(meta).0,
+        );
+        let hi_eq = IsEqualChip::configure(
+            meta,
+            |meta| q_enable(meta),
+            |meta| lhs(meta).1,
+            |meta| rhs(meta).1,
+        );
+        let is_equal_word_expression = lo_eq.expr() * hi_eq.expr();
+
+        IsEqualWordConfig {
+            lo_eq,
+            hi_eq,
+            is_equal_word_expression,
+        }
+    }
+
+    /// Construct an IsEqualWord chip given a config.
+    pub fn construct(config: IsEqualWordConfig<F>) -> Self {
+        Self { config }
+    }
+}
+
+impl<F: Field> IsEqualWordInstruction<F> for IsEqualWordChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error> {
+        let (lhs_lo, lhs_hi) = word_lo_hi::<F>(lhs);
+        let (rhs_lo, rhs_hi) = word_lo_hi::<F>(rhs);
+        IsEqualChip::construct(self.config.lo_eq.clone()).assign(
+            region,
+            offset,
+            Value::known(lhs_lo),
+            Value::known(rhs_lo),
+        )?;
+        IsEqualChip::construct(self.config.hi_eq.clone()).assign(
+            region,
+            offset,
+            Value::known(lhs_hi),
+            Value::known(rhs_hi),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<F: Field> Chip<F> for IsEqualWordChip<F> {
+    type Config = IsEqualWordConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// Instruction that the LtWord chip needs to implement.
+pub trait LtWordInstruction<F: Field> {
+    /// Assign the lhs and rhs words to the LtWord chip's region.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error>;
+}
+
+/// Config for the LtWord chip.
+#[derive(Clone, Debug)]
+pub struct LtWordConfig<F> {
+    /// Compares equality of the hi halves.
+    hi_eq: IsEqualConfig<F>,
+    /// Compares the hi halves.
+    hi_lt: LtConfig<F, 16>,
+    /// Compares the lo halves.
+    lo_lt: LtConfig<F, 16>,
+    /// Bytes of the lhs lo, lhs hi, rhs lo and rhs hi halves, in little
+    /// endian.
+    halves: [[Column<Advice>; 16]; 4],
+    /// Denotes the lt outcome. If lhs < rhs then lt == 1, otherwise lt == 0.
+    pub lt: Column<Advice>,
+    /// Expression that denotes whether lhs < rhs, or not.
+    pub is_lt_expression: Expression<F>,
+}
+
+impl<F: Field> LtWordConfig<F> {
+    /// Returns the is_lt expression.
+    pub fn expr(&self) -> Expression<F> {
+        self.is_lt_expression.expr()
+    }
+
+    /// Returns an expression that denotes whether lhs < rhs, or not.
+    pub fn is_lt(&self, meta: &mut VirtualCells<F>, rotation: Option<Rotation>) -> Expression<F> {
+        meta.query_advice(self.lt, rotation.unwrap_or_else(Rotation::cur))
+    }
+}
+
+/// Chip that compares lhs < rhs for two words.
+#[derive(Clone, Debug)]
+pub struct LtWordChip<F> {
+    config: LtWordConfig<F>,
+}
+
+impl<F: Field> LtWordChip<F> {
+    /// Configure the LtWord chip. `lhs` and `rhs` return the `(lo, hi)` halves
+    /// of the words, `u8_table` contains the values in `[0, 256)`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+        rhs: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+        u8_table: TableColumn,
+    ) -> LtWordConfig<F> {
+        let lt = meta.advice_column();
+        let halves = [(); 4].map(|_| [(); 16].map(|_| meta.advice_column()));
+        let hi_eq = IsEqualChip::configure(
+            meta,
+            |meta| q_enable(meta),
+            |meta| lhs(meta).1,
+            |meta| rhs(meta).1,
+        );
+        let hi_lt = LtChip::configure(
+            meta,
+            |meta| q_enable(meta),
+            |meta| lhs(meta).1,
+            |meta| rhs(meta).1,
+        );
+        let lo_lt = LtChip::configure(
+            meta,
+            |meta| q_enable(meta),
+            |meta| lhs(meta).0,
+            |meta| rhs(meta).0,
+        );
+
+        // dummy initialization
+        let mut is_lt_expression = 0.expr();
+
+        meta.create_gate("lt word gate", |meta| {
+            let q_enable = q_enable(meta);
+            is_lt_expression = meta.query_advice(lt, Rotation::cur());
+
+            let expected = lt_word_expr(
+                hi_lt.is_lt(meta, None),
+                hi_eq.expr(),
+                lo_lt.is_lt(meta, None),
+            );
+
+            [q_enable * (is_lt_expression.clone() - expected)]
+        });
+
+        meta.create_gate("lt word halves", |meta| {
+            let q_enable = q_enable(meta);
+            let (lhs, rhs) = (lhs(meta), rhs(meta));
+
+            [lhs.0, lhs.1, rhs.0, rhs.1]
+                .into_iter()
+                .zip(halves)
+                .map(|(half, bytes)| {
+                    let bytes = bytes.map(|c| meta.query_advice(c, Rotation::cur()));
+                    q_enable.clone() * (half - expr_from_bytes(&bytes))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for column in halves
+            .into_iter()
+            .flatten()
+            .chain(hi_lt.diff)
+            .chain(lo_lt.diff)
+        {
+            meta.lookup("lt word byte range", |meta| {
+                let q_enable = q_enable(meta);
+                let byte = meta.query_advice(column, Rotation::cur());
+                vec![(q_enable * byte, u8_table)]
+            });
+        }
+
+        LtWordConfig {
+            hi_eq,
+            hi_lt,
+            lo_lt,
+            halves,
+            lt,
+            is_lt_expression,
+        }
+    }
+
+    /// Construct a LtWord chip given a config.
+    pub fn construct(config: LtWordConfig<F>) -> Self {
+        Self { config }
+    }
+}
+
+impl<F: Field> LtWordInstruction<F> for LtWordChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        let (lhs_lo, lhs_hi) = word_lo_hi::<F>(lhs);
+        let (rhs_lo, rhs_hi) = word_lo_hi::<F>(rhs);
+
+        IsEqualChip::construct(config.hi_eq.clone()).assign(
+            region,
+            offset,
+            Value::known(lhs_hi),
+            Value::known(rhs_hi),
+        )?;
+        LtChip::construct(config.hi_lt).assign(region, offset, lhs_hi, rhs_hi)?;
+        LtChip::construct(config.lo_lt).assign(region, offset, lhs_lo, rhs_lo)?;
+        for (bytes, half) in config.halves.iter().zip([lhs_lo, lhs_hi, rhs_lo, rhs_hi]) {
+            for (idx, (column, byte)) in bytes.iter().zip(half.to_repr()).enumerate() {
+                region.assign_advice(
+                    || format!("lt word chip: half byte {idx}"),
+                    *column,
+                    offset,
+                    || Value::known(F::from(byte as u64)),
+                )?;
+            }
+        }
+        region.assign_advice(
+            || "lt word chip: lt",
+            config.lt,
+            offset,
+            || Value::known(F::from((lhs < rhs) as u64)),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<F: Field> Chip<F> for LtWordChip<F> {
+    type Config = LtWordConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// Instruction that the MinMaxWord chip needs to implement.
+pub trait MinMaxWordInstruction<F: Field> {
+    /// Assign the lhs and rhs words to the MinMaxWord chip's region, returning
+    /// `(min, max)`.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(Word, Word), Error>;
+}
+
+/// Config for the MinMaxWord chip.
+#[derive(Clone, Debug)]
+pub struct MinMaxWordConfig<F> {
+    /// Compares lhs < rhs.
+    pub lt: LtWordConfig<F>,
+}
+
+impl<F: Field> MinMaxWordConfig<F> {
+    /// Returns the `(lo, hi)` halves of `min(lhs, rhs)`, for the same `lhs`
+    /// and `rhs` the chip was configured with.
+    pub fn min(
+        &self,
+        lhs: (Expression<F>, Expression<F>),
+        rhs: (Expression<F>, Expression<F>),
+    ) -> (Expression<F>, Expression<F>) {
+        let lt = self.lt.expr();
+        (
+            select::expr(lt.clone(), lhs.0, rhs.0),
+            select::expr(lt, lhs.1, rhs.1),
+        )
+    }
+
+    /// Returns the `(lo, hi)` halves of `max(lhs, rhs)`, for the same `lhs`
+    /// and `rhs` the chip was configured with.
+    pub fn max(
+        &self,
+        lhs: (Expression<F>, Expression<F>),
+        rhs: (Expression<F>, Expression<F>),
+    ) -> (Expression<F>, Expression<F>) {
+        self.min(rhs, lhs)
+    }
+}
+
+/// Chip that selects the min and max of two words.
+#[derive(Clone, Debug)]
+pub struct MinMaxWordChip<F> {
+    config: MinMaxWordConfig<F>,
+}
+
+impl<F: Field> MinMaxWordChip<F> {
+    /// Configure the MinMaxWord chip. `lhs` and `rhs` return the `(lo, hi)`
+    /// halves of the words, `u8_table` contains the values in `[0, 256)`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+        rhs: impl Fn(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>),
+        u8_table: TableColumn,
+    ) -> MinMaxWordConfig<F> {
+        MinMaxWordConfig {
+            lt: LtWordChip::configure(meta, q_enable, lhs, rhs, u8_table),
+        }
+    }
+
+    /// Construct a MinMaxWord chip given a config.
+    pub fn construct(config: MinMaxWordConfig<F>) -> Self {
+        Self { config }
+    }
+}
+
+impl<F: Field> MinMaxWordInstruction<F> for MinMaxWordChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Word,
+        rhs: Word,
+    ) -> Result<(Word, Word), Error> {
+        LtWordChip::construct(self.config.lt.clone()).assign(region, offset, lhs, rhs)?;
+        Ok(if lhs < rhs { (lhs, rhs) } else { (rhs, lhs) })
+    }
+}
+
+impl<F: Field> Chip<F> for MinMaxWordChip<F> {
+    type Config = MinMaxWordConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr as Fp,
+        plonk::{Circuit, Selector},
+    };
+    use std::marker::PhantomData;
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        // lo, hi
+        lhs: [Column<Advice>; 2],
+        rhs: [Column<Advice>; 2],
+        // eq, lt, min lo, min hi, max lo, max hi
+        checks: [Column<Advice>; 6],
+        u8_table: TableColumn,
+        is_equal: IsEqualWordConfig<F>,
+        lt: LtWordConfig<F>,
+        min_max: MinMaxWordConfig<F>,
+    }
+
+    /// How the witness of the LtWord chip is tampered for the first pair.
+    #[derive(Clone, Copy, Debug)]
+    enum Tamper {
+        /// Witness the wrong outcome in the `lt` cell of the chip.
+        Lt,
+        /// Claim `lhs_lo < rhs_lo` for `lhs_lo >= rhs_lo` with the diff
+        /// `lhs_lo - rhs_lo + 2^128`, whose top diff byte is then 256.
+        LoDiff,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F> {
+        pairs: Vec<(Word, Word)>,
+        tamper: Option<Tamper>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let lhs = [(); 2].map(|_| meta.advice_column());
+            let rhs = [(); 2].map(|_| meta.advice_column());
+            let checks = [(); 6].map(|_| meta.advice_column());
+            let u8_table = meta.lookup_table_column();
+
+            let word = |meta: &mut VirtualCells<'_, F>, word: [Column<Advice>; 2]| {
+                (
+                    meta.query_advice(word[0], Rotation::cur()),
+                    meta.query_advice(word[1], Rotation::cur()),
+                )
+            };
+            let q = |meta: &mut VirtualCells<'_, F>| meta.query_selector(q_enable);
+            let is_equal = IsEqualWordChip::configure(meta, q, |m| word(m, lhs), |m| word(m, rhs));
+            let lt = LtWordChip::configure(meta, q, |m| word(m, lhs), |m| word(m, rhs), u8_table);
+            let min_max =
+                MinMaxWordChip::configure(meta, q, |m| word(m, lhs), |m| word(m, rhs), u8_table);
+
+            let config = Self::Config {
+                q_enable,
+                lhs,
+                rhs,
+                checks,
+                u8_table,
+                is_equal,
+                lt,
+                min_max,
+            };
+
+            meta.create_gate("check word comparisons", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let checks = checks.map(|c| meta.query_advice(c, Rotation::cur()));
+                let (lhs, rhs) = (word(meta, lhs), word(meta, rhs));
+                let min = config.min_max.min(lhs.clone(), rhs.clone());
+                let max = config.min_max.max(lhs, rhs);
+
+                [
+                    config.is_equal.expr(),
+                    config.lt.expr(),
+                    min.0,
+                    min.1,
+                    max.0,
+                    max.1,
+                ]
+                .into_iter()
+                .zip(checks)
+                .map(|(value, check)| q_enable.clone() * (value - check))
+                .collect::<Vec<_>>()
+            });
+
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let is_equal = IsEqualWordChip::construct(config.is_equal.clone());
+            let lt = LtWordChip::construct(config.lt.clone());
+            let min_max = MinMaxWordChip::construct(config.min_max.clone());
+
+            layouter.assign_table(
+                || "u8 table",
+                |mut table| {
+                    for value in 0..256 {
+                        table.assign_cell(
+                            || "u8",
+                            config.u8_table,
+                            value,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (offset, &(lhs, rhs)) in self.pairs.iter().enumerate() {
+                        config.q_enable.enable(&mut region, offset)?;
+                        for (columns, word) in [(config.lhs, lhs), (config.rhs, rhs)] {
+                            let (lo, hi) = word_lo_hi::<F>(word);
+                            for (column, value) in columns.into_iter().zip([lo, hi]) {
+                                region.assign_advice(
+                                    || "word",
+                                    column,
+                                    offset,
+                                    || Value::known(value),
+                                )?;
+                            }
+                        }
+
+                        is_equal.assign(&mut region, offset, lhs, rhs)?;
+                        lt.assign(&mut region, offset, lhs, rhs)?;
+                        let (min, max) = min_max.assign(&mut region, offset, lhs, rhs)?;
+
+                        let mut is_lt = lhs < rhs;
+                        match self.tamper.filter(|_| offset == 0) {
+                            Some(Tamper::Lt) => is_lt = !is_lt,
+                            Some(Tamper::LoDiff) => {
+                                let (lhs_lo, _) = word_lo_hi::<F>(lhs);
+                                let (rhs_lo, _) = word_lo_hi::<F>(rhs);
+                                let mut diff = (lhs_lo - rhs_lo)
+                                    .to_repr()
+                                    .as_ref()
+                                    .iter()
+                                    .map(|&byte| F::from(byte as u64))
+                                    .collect::<Vec<_>>();
+                                diff[15] += F::from(256);
+                                for (column, byte) in config.lt.lo_lt.diff.iter().zip(diff) {
+                                    region.assign_advice(
+                                        || "forged diff",
+                                        *column,
+                                        offset,
+                                        || Value::known(byte),
+                                    )?;
+                                }
+                                region.assign_advice(
+                                    || "forged lo lt",
+                                    config.lt.lo_lt.lt,
+                                    offset,
+                                    || Value::known(F::one()),
+                                )?;
+                                is_lt = true;
+                            }
+                            None => {}
+                        }
+                        region.assign_advice(
+                            || "lt",
+                            config.lt.lt,
+                            offset,
+                            || Value::known(F::from(is_lt as u64)),
+                        )?;
+
+                        let (min_lo, min_hi) = word_lo_hi::<F>(min);
+                        let (max_lo, max_hi) = word_lo_hi::<F>(max);
+                        let checks = [
+                            F::from((lhs == rhs) as u64),
+                            F::from(is_lt as u64),
+                            min_lo,
+                            min_hi,
+                            max_lo,
+                            max_hi,
+                        ];
+                        for (column, value) in config.checks.into_iter().zip(checks) {
+                            region.assign_advice(
+                                || "check",
+                                column,
+                                offset,
+                                || Value::known(value),
+                            )?;
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn boundary_words<F: Field>() -> Vec<Word> {
+        let p_minus_one = Word::from_little_endian(&(-F::one()).to_repr());
+        vec![
+            Word::zero(),
+            Word::one(),
+            Word::from(u128::MAX),
+            Word::one() << 128,
+            p_minus_one,
+            Word::MAX,
+        ]
+    }
+
+    fn verify(pairs: Vec<(Word, Word)>, tamper: Option<Tamper>) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = TestCircuit::<Fp> {
+            pairs,
+            tamper,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::<Fp>::run(9, &circuit, vec![]).unwrap();
+        prover.verify()
+    }
+
+    #[test]
+    fn word_cmp_boundaries() {
+        let words = boundary_words::<Fp>();
+        let pairs = words
+            .iter()
+            .flat_map(|&lhs| words.iter().map(move |&rhs| (lhs, rhs)))
+            .collect::<Vec<_>>();
+        assert_eq!(verify(pairs, None), Ok(()));
+    }
+
+    #[test]
+    fn word_cmp_wrong_lt() {
+        let words = boundary_words::<Fp>();
+        for (lhs, rhs) in [
+            (words[0], words[1]),
+            (words[1], words[0]),
+            (words[2], words[3]),
+            (words[3], words[4]),
+            (words[4], words[4]),
+        ] {
+            let errors = verify(vec![(lhs, rhs)], Some(Tamper::Lt)).unwrap_err();
+            assert!(
+                errors.iter().all(|error| matches!(
+                    error,
+                    VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                        if format!("{constraint}").contains("lt word gate")
+                )),
+                "{errors:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn word_cmp_forged_diff() {
+        let words = boundary_words::<Fp>();
+        // Same hi halves and lhs_lo >= rhs_lo: the forged diff satisfies every
+        // gate and is only caught by the byte range lookup.
+        for (lhs, rhs) in [
+            (words[1], words[0]),
+            (words[2], words[0]),
+            (words[5], words[5]),
+        ] {
+            let errors = verify(vec![(lhs, rhs)], Some(Tamper::LoDiff)).unwrap_err();
+            assert!(
+                errors.iter().all(|error| matches!(
+                    error,
+                    VerifyFailure::Lookup { name, .. } if *name == "lt word byte range"
+                )),
+                "{errors:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Append the failed txs of the blocks `START_BLOCK..=END_BLOCK` of the node
+//! at `GETH0_URL` to the error corpus, see [`integration_tests::error_corpus`].
+
+use integration_tests::{
+    error_corpus::{collect_error_txs, ErrorCategory, ErrorCorpus, ERROR_CORPUS_PATH},
+    get_client, log_init, END_BLOCK, START_BLOCK,
+};
+use log::{error, info};
+
+/// Max number of txs of a category in the corpus
+const MAX_TXS_PER_CATEGORY: usize = 20;
+
+#[tokio::main]
+async fn main() {
+    log_init();
+    let cli = get_client();
+    let mut corpus = ErrorCorpus::load(ERROR_CORPUS_PATH);
+
+    for block_num in *START_BLOCK as u64..=*END_BLOCK as u64 {
+        if ErrorCategory::ALL
+            .iter()
+            .all(|category| corpus.count(*category) >= MAX_TXS_PER_CATEGORY)
+        {
+            info!("corpus is full");
+            break;
+        }
+        let entries = match collect_error_txs(&cli, block_num, &ErrorCategory::ALL).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!(
+                    "cannot collect the failed txs of block {}: {:?}",
+                    block_num, e
+                );
+                continue;
+            }
+        };
+        for entry in entries {
+            let (category, tx) = (entry.category, entry.tx.clone());
+            if corpus.add(entry, MAX_TXS_PER_CATEGORY) {
+                info!("block {}: {:?} tx {:?}", block_num, category, tx);
+            }
+        }
+        // keep what was found so far if the scan is interrupted
+        corpus.store(ERROR_CORPUS_PATH);
+    }
+
+    for category in ErrorCategory::ALL {
+        info!("{:?}: {} txs", category, corpus.count(category));
+    }
+}
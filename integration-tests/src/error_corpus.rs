@@ -0,0 +1,313 @@
+//! Regression corpus of failed txs, to test the error paths of the circuits
+//! against real traces.
+//!
+//! `gen_error_corpus` scans a range of blocks of a node for failed txs,
+//! classifies them by the error which made them fail and appends them to the
+//! corpus file, up to a number of txs per category. The `mainnet` tests
+//! replay the corpus through the witness generation and the MockProver. The
+//! checked-in corpus refers to the failed txs of the chain generated by
+//! `gen_blockchain_data`, by the name of their block.
+
+use crate::GenDataOutput;
+use bus_mapping::{
+    geth_errors::{
+        GETH_ERR_GAS_UINT_OVERFLOW, GETH_ERR_INVALID_JUMP, GETH_ERR_OUT_OF_GAS,
+        GETH_ERR_STACK_OVERFLOW,
+    },
+    rpc::GethClient,
+    Error,
+};
+use eth_types::{evm_types::OpcodeId, GethExecTrace, H256};
+use ethers::providers::JsonRpcClient;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, path::Path};
+
+/// Path to the corpus file written by `gen_error_corpus`
+pub const ERROR_CORPUS_PATH: &str = "error_corpus.json";
+
+/// The error a failed tx is classified by: the first error of its trace,
+/// whichever call it happened in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ErrorCategory {
+    /// A REVERT
+    Revert,
+    /// Out of gas, including the gas overflows
+    OutOfGas,
+    /// A JUMP or JUMPI to an invalid destination
+    InvalidJump,
+    /// More than 1024 stack items
+    StackOverflow,
+}
+
+impl ErrorCategory {
+    /// All the categories.
+    pub const ALL: [Self; 4] = [
+        Self::Revert,
+        Self::OutOfGas,
+        Self::InvalidJump,
+        Self::StackOverflow,
+    ];
+
+    /// Category of a tx from its trace, or `None` if the tx hasn't failed or
+    /// failed with an error of no category.
+    pub fn of(trace: &GethExecTrace) -> Option<Self> {
+        if !trace.failed {
+            return None;
+        }
+        trace
+            .struct_logs
+            .iter()
+            .find_map(|step| match &step.error {
+                Some(error)
+                    if error == GETH_ERR_OUT_OF_GAS || error == GETH_ERR_GAS_UINT_OVERFLOW =>
+                {
+                    Some(Some(Self::OutOfGas))
+                }
+                Some(error) if error.starts_with(GETH_ERR_INVALID_JUMP) => {
+                    Some(Some(Self::InvalidJump))
+                }
+                Some(error) if error.starts_with(GETH_ERR_STACK_OVERFLOW) => {
+                    Some(Some(Self::StackOverflow))
+                }
+                Some(_) => Some(None),
+                None if step.op == OpcodeId::REVERT => Some(Some(Self::Revert)),
+                None => None,
+            })?
+    }
+}
+
+/// Where the failed txs of an entry of the corpus are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ErrorCorpusTx {
+    /// A single tx, by its hash
+    Hash {
+        /// Hash of the tx
+        tx_hash: H256,
+        /// Block of the tx
+        block_number: u64,
+    },
+    /// The failed txs of the category of the entry in a block of the chain
+    /// generated by `gen_blockchain_data`
+    GenData {
+        /// Name of the block in [`GenDataOutput::blocks`]
+        block: String,
+    },
+}
+
+/// Failed txs of the corpus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCorpusEntry {
+    /// Error the txs failed with
+    pub category: ErrorCategory,
+    /// The txs
+    #[serde(flatten)]
+    pub tx: ErrorCorpusTx,
+}
+
+impl ErrorCorpusEntry {
+    /// Hashes of the txs of the entry on the node of `cli`.
+    pub async fn tx_hashes<P: JsonRpcClient>(
+        &self,
+        cli: &GethClient<P>,
+    ) -> Result<Vec<H256>, Error> {
+        match &self.tx {
+            ErrorCorpusTx::Hash { tx_hash, .. } => Ok(vec![*tx_hash]),
+            ErrorCorpusTx::GenData { block } => {
+                let block_num = *GenDataOutput::load()
+                    .blocks
+                    .get(block)
+                    .expect("cannot find block in gen data");
+                Ok(collect_error_txs(cli, block_num, &[self.category])
+                    .await?
+                    .into_iter()
+                    .filter_map(|entry| match entry.tx {
+                        ErrorCorpusTx::Hash { tx_hash, .. } => Some(tx_hash),
+                        ErrorCorpusTx::GenData { .. } => None,
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// The failed txs of the regression corpus.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorCorpus {
+    /// Txs of the corpus, in the order they were added
+    pub entries: Vec<ErrorCorpusEntry>,
+}
+
+impl ErrorCorpus {
+    /// Load the corpus from `path`, or an empty one if there is no file.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        match File::open(path) {
+            Ok(file) => serde_json::from_reader(file).expect("cannot deserialize json from file"),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Store the corpus as JSON at `path`.
+    pub fn store(&self, path: impl AsRef<Path>) {
+        serde_json::to_writer_pretty(&File::create(path).expect("cannot create file"), self)
+            .expect("cannot serialize json into file");
+    }
+
+    /// Number of txs of `category` in the corpus.
+    pub fn count(&self, category: ErrorCategory) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.category == category)
+            .count()
+    }
+
+    /// Add `entry` unless its txs are already in the corpus or its category
+    /// already has `max_per_category` entries. Returns whether it was added.
+    pub fn add(&mut self, entry: ErrorCorpusEntry, max_per_category: usize) -> bool {
+        if self.count(entry.category) >= max_per_category
+            || self.entries.iter().any(|e| e.tx == entry.tx)
+        {
+            return false;
+        }
+        self.entries.push(entry);
+        true
+    }
+}
+
+/// The failed txs of `block_num` which fall in one of `categories`.
+pub async fn collect_error_txs<P: JsonRpcClient>(
+    cli: &GethClient<P>,
+    block_num: u64,
+    categories: &[ErrorCategory],
+) -> Result<Vec<ErrorCorpusEntry>, Error> {
+    let block = cli.get_block_by_number(block_num.into()).await?;
+    let traces = cli.trace_block_by_number(block_num.into()).await?;
+    Ok(block
+        .transactions
+        .iter()
+        .zip(traces.iter())
+        .filter_map(|(tx, trace)| {
+            let category = ErrorCategory::of(trace)?;
+            categories.contains(&category).then_some(ErrorCorpusEntry {
+                category,
+                tx: ErrorCorpusTx::Hash {
+                    tx_hash: tx.hash,
+                    block_number: block_num,
+                },
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eth_types::{
+        evm_types::{Gas, GasCost, Memory, ProgramCounter, Stack, Storage},
+        GethExecStep,
+    };
+
+    fn trace(steps: &[(OpcodeId, Option<&str>)]) -> GethExecTrace {
+        GethExecTrace {
+            l1_fee: 0,
+            gas: Gas(0),
+            failed: true,
+            return_value: String::new(),
+            struct_logs: steps
+                .iter()
+                .map(|(op, error)| GethExecStep {
+                    pc: ProgramCounter(0),
+                    op: *op,
+                    gas: Gas(0),
+                    gas_cost: GasCost(0),
+                    refund: Gas(0),
+                    depth: 1,
+                    error: error.map(str::to_string),
+                    stack: Stack(vec![]),
+                    memory: Memory::default(),
+                    storage: Storage::default(),
+                    storage_proofs: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn error_category_of_trace() {
+        use OpcodeId::*;
+
+        assert_eq!(
+            ErrorCategory::of(&trace(&[(PUSH1, None), (REVERT, None)])),
+            Some(ErrorCategory::Revert)
+        );
+        assert_eq!(
+            ErrorCategory::of(&trace(&[(SLOAD, Some(GETH_ERR_OUT_OF_GAS))])),
+            Some(ErrorCategory::OutOfGas)
+        );
+        assert_eq!(
+            ErrorCategory::of(&trace(&[(JUMP, Some("invalid jump destination"))])),
+            Some(ErrorCategory::InvalidJump)
+        );
+        assert_eq!(
+            ErrorCategory::of(&trace(&[(PUSH1, Some("stack limit reached 1024 (1023)"))])),
+            Some(ErrorCategory::StackOverflow)
+        );
+        // the first error of the trace is the one the tx is classified by
+        assert_eq!(
+            ErrorCategory::of(&trace(&[
+                (MLOAD, Some(GETH_ERR_OUT_OF_GAS)),
+                (REVERT, None)
+            ])),
+            Some(ErrorCategory::OutOfGas)
+        );
+        assert_eq!(
+            ErrorCategory::of(&trace(&[(ADD, Some("stack underflow (0 <=> 2)"))])),
+            None
+        );
+        let mut succeeded = trace(&[(REVERT, None)]);
+        succeeded.failed = false;
+        assert_eq!(ErrorCategory::of(&succeeded), None);
+    }
+
+    #[test]
+    fn error_corpus_add() {
+        let entry = |category, byte| ErrorCorpusEntry {
+            category,
+            tx: ErrorCorpusTx::Hash {
+                tx_hash: H256::repeat_byte(byte),
+                block_number: 1,
+            },
+        };
+        let mut corpus = ErrorCorpus::default();
+        assert!(corpus.add(entry(ErrorCategory::Revert, 1), 2));
+        assert!(!corpus.add(entry(ErrorCategory::Revert, 1), 2));
+        assert!(corpus.add(entry(ErrorCategory::Revert, 2), 2));
+        assert!(!corpus.add(entry(ErrorCategory::Revert, 3), 2));
+        assert!(corpus.add(entry(ErrorCategory::OutOfGas, 3), 2));
+        assert_eq!(corpus.count(ErrorCategory::Revert), 2);
+        assert_eq!(corpus.count(ErrorCategory::OutOfGas), 1);
+    }
+
+    #[test]
+    fn checked_in_error_corpus() {
+        let corpus = ErrorCorpus::load(ERROR_CORPUS_PATH);
+        assert!(corpus.count(ErrorCategory::Revert) > 0);
+        assert!(corpus
+            .entries
+            .iter()
+            .any(|entry| matches!(entry.tx, ErrorCorpusTx::GenData { .. })));
+
+        // the txs found by `gen_error_corpus` keep their format
+        let entry: ErrorCorpusEntry = serde_json::from_str(
+            r#"{"category":"OutOfGas","tx_hash":"0x0101010101010101010101010101010101010101010101010101010101010101","block_number":1}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            entry.tx,
+            ErrorCorpusTx::Hash {
+                tx_hash: H256::repeat_byte(1),
+                block_number: 1,
+            }
+        );
+    }
+}
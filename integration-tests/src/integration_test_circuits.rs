@@ -5,7 +5,7 @@ use bus_mapping::{
     },
     mock::BlockData,
 };
-use eth_types::geth_types::GethData;
+use eth_types::{evm_types::MAX_CODE_SIZE, geth_types::GethData};
 use halo2_proofs::{
     dev::{CellValue, MockProver},
     halo2curves::bn256::{Bn256, Fr, G1Affine},
@@ -39,7 +39,7 @@ use zkevm_circuits::{
     super_circuit::SuperCircuit,
     tx_circuit::TestTxCircuit,
     util::SubCircuit,
-    witness::{block_convert, Block},
+    witness::{block_convert, block_convert_with_mock_challenges, Block},
 };
 
 /// TEST_MOCK_RANDOMNESS
@@ -71,6 +71,8 @@ const MAX_EC_ADD: usize = 10;
 const MAX_EC_MUL: usize = 10;
 /// Max number of EcPairing ops.
 const MAX_EC_PAIRING: usize = 4;
+/// MAX_MODEXP_ROWS
+const MAX_MODEXP_ROWS: usize = 32768;
 
 const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_rws: MAX_RWS,
@@ -79,7 +81,9 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_mpt_rows: MAX_CALLDATA,
     max_inner_blocks: 64,
     max_bytecode: MAX_BYTECODE,
+    max_code_size: MAX_CODE_SIZE as usize,
     max_copy_rows: MAX_COPY_ROWS,
+    copy_tight_padding: false,
     max_evm_rows: MAX_EVM_ROWS,
     max_exp_steps: MAX_EXP_STEPS,
     max_keccak_rows: MAX_KECCAK_ROWS,
@@ -89,6 +93,7 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_mul: MAX_EC_MUL,
         ec_pairing: MAX_EC_PAIRING,
     },
+    max_modexp_rows: MAX_MODEXP_ROWS,
 };
 
 const EVM_CIRCUIT_DEGREE: u32 = 18;
@@ -307,8 +312,12 @@ impl<C: SubCircuit<Fr> + Circuit<Fr>> IntegrationTest<C> {
             block_num,
             block_tag
         );
-        let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
-        block.randomness = Fr::from(TEST_MOCK_RANDOMNESS);
+        let block = block_convert_with_mock_challenges(
+            &builder.block,
+            &builder.code_db,
+            TEST_MOCK_RANDOMNESS,
+        )
+        .unwrap();
         let circuit = C::new_from_block(&block);
         let instance = circuit.instance();
 
@@ -164,5 +164,7 @@ pub struct CompiledContract {
     pub bin_runtime: Bytes,
 }
 
+/// Regression corpus of failed txs.
+pub mod error_corpus;
 /// Common code for integration tests of circuits.
 pub mod integration_test_circuits;
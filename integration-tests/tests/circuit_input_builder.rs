@@ -34,7 +34,7 @@ async fn test_circuit_input_builder_block(block_num: u64) {
     .unwrap();
 
     // 1. Query geth for Block, Txs and TxExecTraces
-    let (eth_block, geth_trace, history_hashes, prev_state_root) =
+    let (eth_block, geth_trace, history_hashes, prev_state_root, _receipts) =
         cli.get_block(block_num).await.unwrap();
 
     // 2. Get State Accesses from TxExecTraces
@@ -2,13 +2,16 @@ use bus_mapping::{
     circuit_input_builder::{keccak_inputs, BuilderClient, CircuitsParams, PrecompileEcParams},
     Error::JSONRpcError,
 };
+use eth_types::evm_types::MAX_CODE_SIZE;
 use halo2_proofs::{
-    circuit::Value,
     dev::{MockProver, VerifyFailure},
     halo2curves::bn256::Fr,
     plonk::Circuit,
 };
-use integration_tests::{get_client, log_init, CIRCUIT, END_BLOCK, START_BLOCK, TX_ID};
+use integration_tests::{
+    error_corpus::{ErrorCorpus, ERROR_CORPUS_PATH},
+    get_client, log_init, CIRCUIT, END_BLOCK, START_BLOCK, TX_ID,
+};
 use zkevm_circuits::{
     evm_circuit::{witness::block_convert, EvmCircuit},
     keccak_circuit::keccak_packed_multi::multi_keccak,
@@ -16,7 +19,7 @@ use zkevm_circuits::{
     state_circuit::StateCircuit,
     super_circuit::SuperCircuit,
     tx_circuit::TestTxCircuit as TxCircuit,
-    util::{Challenges, SubCircuit},
+    util::SubCircuit,
     witness,
     witness::Transaction,
 };
@@ -24,10 +27,12 @@ use zkevm_circuits::{
 const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
     max_rws: 30000,
     max_copy_rows: 30000,
+    copy_tight_padding: false,
     max_txs: 20,
     max_calldata: 30000,
     max_inner_blocks: 64,
     max_bytecode: 30000,
+    max_code_size: MAX_CODE_SIZE as usize,
     max_mpt_rows: 30000,
     max_keccak_rows: 0,
     max_exp_steps: 1000,
@@ -38,18 +43,12 @@ const CIRCUITS_PARAMS: CircuitsParams = CircuitsParams {
         ec_mul: 10,
         ec_pairing: 4,
     },
+    max_modexp_rows: 32768,
 };
 
-#[tokio::test]
-async fn test_mock_prove_tx() {
-    log_init();
-    let tx_id: &str = &TX_ID;
-    log::info!("test {} circuit, tx: {}", *CIRCUIT, tx_id);
-    if tx_id.is_empty() {
-        return;
-    }
-    let cli = get_client();
-    let params = CircuitsParams {
+/// Params of the witness of a single tx
+fn tx_circuits_params() -> CircuitsParams {
+    CircuitsParams {
         max_rws: 100000,
         max_copy_rows: 100000,
         max_txs: 10,
@@ -62,9 +61,19 @@ async fn test_mock_prove_tx() {
         max_evm_rows: 0,
         max_rlp_rows: 42000,
         ..Default::default()
-    };
+    }
+}
 
-    let cli = BuilderClient::new(cli, params).await.unwrap();
+#[tokio::test]
+async fn test_mock_prove_tx() {
+    log_init();
+    let tx_id: &str = &TX_ID;
+    log::info!("test {} circuit, tx: {}", *CIRCUIT, tx_id);
+    if tx_id.is_empty() {
+        return;
+    }
+    let cli = get_client();
+    let cli = BuilderClient::new(cli, tx_circuits_params()).await.unwrap();
     let builder = cli.gen_inputs_tx(tx_id).await.unwrap();
 
     if builder.block.txs.is_empty() {
@@ -82,6 +91,45 @@ async fn test_mock_prove_tx() {
     log::info!("prove done");
 }
 
+/// Replay the failed txs of the error corpus, see `gen_error_corpus`.
+#[tokio::test]
+async fn test_mock_prove_error_corpus() {
+    log_init();
+    let corpus = ErrorCorpus::load(ERROR_CORPUS_PATH);
+    let mut tx_hashes = Vec::new();
+    for entry in &corpus.entries {
+        let hashes = entry.tx_hashes(&get_client()).await.unwrap();
+        assert!(!hashes.is_empty(), "no failed tx for {entry:?}");
+        tx_hashes.extend(hashes.into_iter().map(|hash| (entry.category, hash)));
+    }
+    let cli = BuilderClient::new(get_client(), tx_circuits_params())
+        .await
+        .unwrap();
+
+    let mut failed = Vec::new();
+    for (category, tx_hash) in tx_hashes {
+        let tx_hash = format!("{tx_hash:?}");
+        log::info!("test {} circuit, {:?} tx: {}", *CIRCUIT, category, tx_hash);
+        let errs = match cli.gen_inputs_tx(&tx_hash).await {
+            Ok(builder) => match block_convert::<Fr>(&builder.block, &builder.code_db) {
+                Ok(block) => test_witness_block(&block)
+                    .iter()
+                    .map(|err| err.to_string())
+                    .collect(),
+                Err(e) => vec![format!("{e:?}")],
+            },
+            Err(e) => vec![format!("{e:?}")],
+        };
+        for err in &errs {
+            log::error!("{:?} tx {}: {}", category, tx_hash, err);
+        }
+        if !errs.is_empty() {
+            failed.push((category, tx_hash));
+        }
+    }
+    assert!(failed.is_empty(), "failed txs of the corpus: {failed:?}");
+}
+
 fn test_with<C: SubCircuit<Fr> + Circuit<Fr>>(block: &witness::Block<Fr>) -> MockProver<Fr> {
     let k = block.get_test_degree();
     log::debug!("{} circuit needs k = {}", *CIRCUIT, k);
@@ -181,12 +229,7 @@ async fn test_print_circuits_size() {
         let evm_rows = EvmCircuit::get_num_rows_required(&block);
         let keccak_inputs = keccak_inputs(&builder.block, &builder.code_db).unwrap();
 
-        let challenges = Challenges::mock(
-            Value::known(block.randomness),
-            Value::known(block.randomness),
-            Value::known(block.randomness),
-        );
-        let keccak_rows = multi_keccak(&keccak_inputs, challenges, None)
+        let keccak_rows = multi_keccak(&keccak_inputs, block.mock_challenges(), None)
             .unwrap()
             .len();
         log::info!(
@@ -1,6 +1,8 @@
 #![cfg(feature = "rpc")]
 
+use bus_mapping::state_db::ProofFormat;
 use eth_types::{StorageProof, Word};
+use ethers::types::TransactionRequest;
 use integration_tests::{get_client, CompiledContract, GenDataOutput, CHAIN_ID, CONTRACTS_PATH};
 use lazy_static::lazy_static;
 use pretty_assertions::assert_eq;
@@ -51,6 +53,19 @@ async fn test_trace_block_by_number_by_hash() {
     assert!(!trace_by_number[0].struct_logs.is_empty())
 }
 
+#[tokio::test]
+async fn test_trace_call() {
+    let (block_num, address) = GEN_DATA.deployments.get("Greeter").unwrap();
+
+    let cli = get_client();
+    let tx = TransactionRequest::new()
+        .from(GEN_DATA.wallets[0])
+        .to(*address);
+    let trace = cli.trace_call(tx, (*block_num).into()).await.unwrap();
+    // The Greeter code runs its dispatcher on the call.
+    assert!(!trace.struct_logs.is_empty())
+}
+
 #[tokio::test]
 async fn test_get_contract_code() {
     let contract_name = "Greeter";
@@ -87,3 +102,32 @@ async fn test_get_proof() {
         .unwrap();
     assert_eq!(expected_storage_proof, proof.storage_proof[0]);
 }
+
+#[tokio::test]
+async fn test_get_proofs_and_codes() {
+    let (block_num, address) = GEN_DATA.deployments.get("Greeter").unwrap();
+
+    let cli = get_client();
+    let accounts = vec![
+        (*address, vec![Word::from(0)]),
+        (GEN_DATA.wallets[0], vec![]),
+    ];
+    let proofs = cli
+        .get_proofs(accounts.clone(), (*block_num).into(), ProofFormat::Mpt, 2)
+        .await
+        .unwrap();
+    for ((account, keys), proof) in accounts.into_iter().zip(proofs) {
+        let expected = cli
+            .get_proof(account, keys, (*block_num).into())
+            .await
+            .unwrap();
+        assert_eq!(expected, proof);
+    }
+
+    let codes = cli
+        .get_codes(vec![*address], (*block_num).into(), 2)
+        .await
+        .unwrap();
+    let expected = cli.get_code(*address, (*block_num).into()).await.unwrap();
+    assert_eq!(vec![(*address, expected)], codes);
+}
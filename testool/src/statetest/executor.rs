@@ -4,7 +4,10 @@ use bus_mapping::{
     circuit_input_builder::{CircuitInputBuilder, CircuitsParams, PrecompileEcParams},
     mock::BlockData,
 };
-use eth_types::{geth_types, geth_types::TxType, Address, Bytes, GethExecTrace, U256, U64};
+use eth_types::{
+    evm_types::MAX_CODE_SIZE, geth_types, geth_types::TxType, Address, Bytes, GethExecTrace, U256,
+    U64,
+};
 use ethers_core::{
     k256::ecdsa::SigningKey,
     types::{transaction::eip2718::TypedTransaction, TransactionRequest},
@@ -97,7 +100,7 @@ fn check_post(
             let actual_code = if actual.code_hash.is_zero() {
                 std::borrow::Cow::Owned(Vec::new())
             } else {
-                std::borrow::Cow::Borrowed(&builder.code_db.0[&actual.code_hash])
+                std::borrow::Cow::Borrowed(builder.code_db.get(&actual.code_hash).unwrap())
             };
             if &actual_code as &[u8] != expected_code.0 {
                 return Err(StateTestError::CodeMismatch {
@@ -189,6 +192,8 @@ fn into_traceconfig(st: StateTest) -> (String, TraceConfig, StateTestResult) {
                 gas_tip_cap: U256::zero(),
                 call_data: st.data,
                 access_list: None,
+                max_fee_per_blob_gas: U256::zero(),
+                blob_versioned_hashes: vec![],
                 v: sig.v,
                 r: sig.r,
                 s: sig.s,
@@ -325,8 +330,10 @@ pub fn run_test(
             max_rws: 0,
             max_calldata: 5000,
             max_bytecode: 5000,
+            max_code_size: MAX_CODE_SIZE as usize,
             max_mpt_rows: 5000,
             max_copy_rows: 55000,
+            copy_tight_padding: false,
             max_evm_rows: 0,
             max_exp_steps: 5000,
             max_keccak_rows: 0,
@@ -337,6 +344,7 @@ pub fn run_test(
                 ec_mul: 50,
                 ec_pairing: 2,
             },
+            max_modexp_rows: 32768,
         };
         let block_data = BlockData::new_from_geth_data_with_params(geth_data, circuits_params);
 
@@ -358,9 +366,11 @@ pub fn run_test(
             max_calldata: MAX_CALLDATA,
             max_rws: 256,
             max_copy_rows: 256,
+            copy_tight_padding: false,
             max_mpt_rows: 256,
             max_exp_steps: 256,
             max_bytecode: 512,
+            max_code_size: MAX_CODE_SIZE as usize,
             max_evm_rows: 0,
             max_keccak_rows: 0,
             max_inner_blocks: 64,
@@ -370,6 +380,7 @@ pub fn run_test(
                 ec_mul: 50,
                 ec_pairing: 2,
             },
+            max_modexp_rows: 32768,
         };
         let (k, circuit, instance, _builder) =
             SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, 64, 0x100>::build(geth_data, circuits_params)
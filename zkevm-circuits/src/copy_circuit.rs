@@ -17,11 +17,12 @@ use eth_types::{Field, Word};
 use gadgets::{
     binary_number::BinaryNumberChip,
     is_equal::{IsEqualChip, IsEqualConfig, IsEqualInstruction},
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
     util::{not, Expr},
 };
 use halo2_proofs::{
     circuit::{Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed},
     poly::Rotation,
 };
 use itertools::Itertools;
@@ -45,9 +46,10 @@ use crate::{
 
 use self::copy_gadgets::{
     constrain_address, constrain_bytes_left, constrain_event_rlc_acc, constrain_first_last,
-    constrain_forward_parameters, constrain_is_pad, constrain_mask, constrain_masked_value,
-    constrain_must_terminate, constrain_non_pad_non_mask, constrain_rw_counter, constrain_tag,
-    constrain_value_rlc, constrain_word_index, constrain_word_rlc,
+    constrain_forward_parameters, constrain_is_active, constrain_is_memory_copy, constrain_is_pad,
+    constrain_mask, constrain_masked_value, constrain_must_terminate, constrain_non_pad_non_mask,
+    constrain_rw_counter, constrain_tag, constrain_value_rlc, constrain_word_index,
+    constrain_word_rlc,
 };
 
 /// The current row.
@@ -62,12 +64,17 @@ const UNUSED_ROWS: usize = 2;
 // Rows to disable, so they do not query into Halo2 reserved rows.
 const DISABLED_ROWS: usize = 2;
 
+/// Rows used by a copy event, a reader and a writer row per copied byte.
+pub fn copy_event_rows(copy_event: &CopyEvent) -> usize {
+    copy_event.full_length() as usize * 2
+}
+
 /// The rw table shared between evm circuit and state circuit
 #[derive(Clone, Debug)]
 pub struct CopyCircuitConfig<F> {
     /// Whether this row denotes a step. A read row is a step and a write row is
     /// not.
-    pub q_step: Selector,
+    pub q_step: Column<Fixed>,
     /// Whether the row is the last read-write pair for a copy event.
     pub is_last: Column<Advice>,
     /// The value copied in this copy step.
@@ -105,6 +112,9 @@ pub struct CopyCircuitConfig<F> {
     pub is_tx_log: Column<Advice>,
     /// Whether the row is enabled or not.
     pub q_enable: Column<Fixed>,
+    /// Whether the enabled row is an event or padding row, as opposed to the
+    /// trailing rows left unassigned by tight padding. Once it is 0, it stays 0.
+    pub is_active: Column<Advice>,
     /// The Copy Table contains the columns that are exposed via the lookup
     /// expressions
     pub copy_table: CopyTable,
@@ -112,6 +122,11 @@ pub struct CopyCircuitConfig<F> {
     pub is_src_end: IsEqualConfig<F>,
     /// Whether this is the end of a word (last byte).
     pub is_word_end: IsEqualConfig<F>,
+    /// Whether the reader and the writer of a step have the same id.
+    pub is_id_unchanged: IsZeroConfig<F>,
+    /// Whether the event copies within the memory of a single call, see
+    /// [`CopyEvent::is_memory_copy`].
+    pub is_memory_copy: Column<Advice>,
     /// non pad and non mask witness to reduce the degree of lookups.
     pub non_pad_non_mask: Column<Advice>,
     // External tables
@@ -155,7 +170,8 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             challenges,
         }: Self::ConfigArgs,
     ) -> Self {
-        let q_step = meta.complex_selector();
+        let q_step = meta.fixed_column();
+        let is_active = meta.advice_column();
         let is_last = meta.advice_column();
         let value = meta.advice_column();
         let value_prev = meta.advice_column();
@@ -196,18 +212,26 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
 
         let is_src_end = IsEqualChip::configure(
             meta,
-            |meta| meta.query_selector(q_step),
+            |meta| meta.query_fixed(q_step, CURRENT),
             |meta| meta.query_advice(addr, CURRENT),
             |meta| meta.query_advice(src_addr_end, CURRENT),
         );
 
         let is_word_end = IsEqualChip::configure(
             meta,
-            |meta| meta.query_fixed(q_enable, CURRENT),
+            |meta| meta.query_advice(is_active, CURRENT),
             |meta| meta.query_advice(word_index, CURRENT),
             |_meta| 31.expr(),
         );
 
+        let is_id_unchanged = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_fixed(q_step, CURRENT),
+            |meta| meta.query_advice(id, CURRENT) - meta.query_advice(id, NEXT_ROW),
+            meta.advice_column_in(SecondPhase),
+        );
+        let is_memory_copy = meta.advice_column();
+
         let non_pad_non_mask = meta.advice_column();
 
         constrain_tag(
@@ -221,10 +245,12 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             is_tx_log,
         );
 
+        constrain_is_active(meta, q_enable, q_step, is_active, is_first);
+
         meta.create_gate("verify copy events", |meta| {
             let cb = &mut BaseConstraintBuilder::default();
 
-            let is_reader = meta.query_selector(q_step);
+            let is_reader = meta.query_fixed(q_step, CURRENT);
             // Detect the first row of an event. When true, both reader and writer are initialized.
             let is_first = meta.query_advice(is_first, CURRENT);
             // Detect the last step of an event. This works on both reader and writer rows.
@@ -243,7 +269,17 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
 
             constrain_first_last(cb, is_reader.expr(), is_first.expr(), is_last.expr());
 
-            constrain_must_terminate(cb, meta, q_enable, &tag);
+            let is_memory_copy = constrain_is_memory_copy(
+                cb,
+                meta,
+                is_first.expr(),
+                is_last.expr(),
+                is_memory,
+                is_id_unchanged.expr(),
+                is_memory_copy,
+            );
+
+            constrain_must_terminate(cb, meta, q_enable, is_active, &tag);
 
             constrain_forward_parameters(cb, meta, is_continue.expr(), id, tag, src_addr_end);
 
@@ -347,8 +383,10 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 constrain_rw_counter(
                     cb,
                     meta,
+                    is_first.expr(),
                     is_last.expr(),
                     is_last_step.expr(),
+                    is_memory_copy.expr(),
                     is_rw_type.expr(),
                     is_word_end.expr(),
                     rw_counter,
@@ -356,7 +394,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
                 );
             }
 
-            cb.gate(meta.query_fixed(q_enable, CURRENT))
+            cb.gate(meta.query_advice(is_active, CURRENT))
         });
 
         // memory word lookup
@@ -370,7 +408,7 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             vec![
                 1.expr(),
                 meta.query_advice(rw_counter, CURRENT),
-                not::expr(meta.query_selector(q_step)),
+                not::expr(meta.query_fixed(q_step, CURRENT)),
                 RwTableTag::Memory.expr(),
                 meta.query_advice(id, CURRENT), // call_id
                 addr_slot,
@@ -470,8 +508,11 @@ impl<F: Field> SubCircuitConfig<F> for CopyCircuitConfig<F> {
             is_memory,
             is_tx_log,
             q_enable,
+            is_active,
             is_src_end,
             is_word_end,
+            is_id_unchanged,
+            is_memory_copy,
             non_pad_non_mask,
             copy_table,
             tx_table,
@@ -491,14 +532,12 @@ impl<F: Field> CopyCircuitConfig<F> {
         tag_chip: &BinaryNumberChip<F, CopyDataType, 4>,
         is_src_end_chip: &IsEqualChip<F>,
         lt_word_end_chip: &IsEqualChip<F>,
+        is_id_unchanged_chip: &IsZeroChip<F>,
         challenges: Challenges<Value<F>>,
         copy_event: &CopyEvent,
     ) -> Result<(), Error> {
-        for (step_idx, (tag, table_row, circuit_row)) in
-            CopyTable::assignments(copy_event, challenges)
-                .iter()
-                .enumerate()
-        {
+        let assignments = CopyTable::assignments(copy_event, challenges);
+        for (step_idx, (tag, table_row, circuit_row)) in assignments.iter().enumerate() {
             let is_read = step_idx % 2 == 0;
 
             // Copy table assignments
@@ -517,7 +556,12 @@ impl<F: Field> CopyCircuitConfig<F> {
 
             // q_step
             if is_read {
-                self.q_step.enable(region, *offset)?;
+                region.assign_fixed(
+                    || "q_step",
+                    self.q_step,
+                    *offset,
+                    || Value::known(F::one()),
+                )?;
             }
             // q_enable
             region.assign_fixed(
@@ -526,6 +570,13 @@ impl<F: Field> CopyCircuitConfig<F> {
                 *offset,
                 || Value::known(F::one()),
             )?;
+            // is_active
+            region.assign_advice(
+                || format!("assign is_active {}", *offset),
+                self.is_active,
+                *offset,
+                || Value::known(F::one()),
+            )?;
 
             // is_last, value, is_pad, is_code
             for (column, &(value, label)) in [
@@ -564,6 +615,9 @@ impl<F: Field> CopyCircuitConfig<F> {
                     addr,
                     Value::known(F::from(copy_event.src_addr_end)),
                 )?;
+
+                let (reader_id, writer_id) = (table_row[1].0, assignments[step_idx + 1].1[1].0);
+                is_id_unchanged_chip.assign(region, *offset, reader_id - writer_id)?;
             }
 
             lt_word_end_chip.assign(
@@ -615,6 +669,12 @@ impl<F: Field> CopyCircuitConfig<F> {
                 *offset,
                 || Value::known(F::from(tag.eq(&CopyDataType::TxLog))),
             )?;
+            region.assign_advice(
+                || format!("is_memory_copy at row: {}", *offset),
+                self.is_memory_copy,
+                *offset,
+                || Value::known(F::from(copy_event.is_memory_copy())),
+            )?;
 
             *offset += 1;
         }
@@ -622,27 +682,34 @@ impl<F: Field> CopyCircuitConfig<F> {
         Ok(())
     }
 
-    /// Assign vec of copy events
+    /// Assign vec of copy events. With `tight_padding`, only the rows that
+    /// the last event can query are padded after the events, and the rows up
+    /// to `max_copy_rows` are left inactive with only their fixed columns
+    /// assigned.
     pub fn assign_copy_events(
         &self,
         layouter: &mut impl Layouter<F>,
         copy_events: &[CopyEvent],
         max_copy_rows: usize,
+        tight_padding: bool,
         challenges: Challenges<Value<F>>,
     ) -> Result<(), Error> {
-        let copy_rows_needed = copy_events
-            .iter()
-            .map(|c| c.full_length() as usize * 2)
-            .sum::<usize>();
+        let copy_rows_needed = copy_events.iter().map(copy_event_rows).sum::<usize>();
         assert!(
             copy_rows_needed + DISABLED_ROWS + UNUSED_ROWS <= max_copy_rows,
             "copy rows not enough {copy_rows_needed} vs {max_copy_rows}"
         );
-        let filler_rows = max_copy_rows - copy_rows_needed - DISABLED_ROWS;
+        let enabled_rows = max_copy_rows - DISABLED_ROWS;
+        let filler_rows = if tight_padding {
+            UNUSED_ROWS
+        } else {
+            enabled_rows - copy_rows_needed
+        };
 
         let tag_chip = BinaryNumberChip::construct(self.copy_table.tag);
         let is_src_end_chip = IsEqualChip::construct(self.is_src_end.clone());
         let lt_word_end_chip = IsEqualChip::construct(self.is_word_end.clone());
+        let is_id_unchanged_chip = IsZeroChip::construct(self.is_id_unchanged.clone());
 
         layouter.assign_region(
             || "assign copy table",
@@ -658,6 +725,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                 region.name_column(|| "is_code", self.is_code);
                 region.name_column(|| "is_pad", self.is_pad);
                 region.name_column(|| "non_pad_non_mask", self.non_pad_non_mask);
+                region.name_column(|| "is_active", self.is_active);
 
                 let mut offset = 0;
                 for (ev_idx, copy_event) in copy_events.iter().enumerate() {
@@ -678,6 +746,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &tag_chip,
                         &is_src_end_chip,
                         &lt_word_end_chip,
+                        &is_id_unchanged_chip,
                         challenges,
                         copy_event,
                     )?;
@@ -692,8 +761,12 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &tag_chip,
                         &is_src_end_chip,
                         &lt_word_end_chip,
+                        &is_id_unchanged_chip,
                     )?;
                 }
+                while offset < enabled_rows {
+                    self.assign_inactive_row(&mut region, &mut offset)?;
+                }
                 assert_eq!(offset % 2, 0, "enabled rows must come in pairs");
 
                 for _ in 0..DISABLED_ROWS {
@@ -704,6 +777,7 @@ impl<F: Field> CopyCircuitConfig<F> {
                         &tag_chip,
                         &is_src_end_chip,
                         &lt_word_end_chip,
+                        &is_id_unchanged_chip,
                     )?;
                 }
 
@@ -721,6 +795,7 @@ impl<F: Field> CopyCircuitConfig<F> {
         tag_chip: &BinaryNumberChip<F, CopyDataType, 4>,
         is_src_end_chip: &IsEqualChip<F>,
         lt_word_end_chip: &IsEqualChip<F>,
+        is_id_unchanged_chip: &IsZeroChip<F>,
     ) -> Result<(), Error> {
         // q_enable
         region.assign_fixed(
@@ -731,8 +806,15 @@ impl<F: Field> CopyCircuitConfig<F> {
         )?;
         // q_step
         if enabled && *offset % 2 == 0 {
-            self.q_step.enable(region, *offset)?;
+            region.assign_fixed(|| "q_step", self.q_step, *offset, || Value::known(F::one()))?;
         }
+        // is_active
+        region.assign_advice(
+            || format!("assign is_active {}", *offset),
+            self.is_active,
+            *offset,
+            || Value::known(F::from(enabled)),
+        )?;
 
         // is_first
         region.assign_advice(
@@ -884,6 +966,7 @@ impl<F: Field> CopyCircuitConfig<F> {
             Value::known(F::zero()),
             Value::known(F::from(31u64)),
         )?;
+        is_id_unchanged_chip.assign(region, *offset, Value::known(F::zero()))?;
         region.assign_advice(
             || format!("non_pad_non_mask at row: {offset}"),
             self.non_pad_non_mask,
@@ -897,6 +980,7 @@ impl<F: Field> CopyCircuitConfig<F> {
             self.is_bytecode,
             self.is_memory,
             self.is_tx_log,
+            self.is_memory_copy,
         ] {
             region.assign_advice(
                 || format!("assigning padding row: {}", *offset),
@@ -910,6 +994,27 @@ impl<F: Field> CopyCircuitConfig<F> {
 
         Ok(())
     }
+
+    /// Assign an enabled row after the padding of tight padding. Only the fixed
+    /// columns are assigned, the same as on a padding row, and the advice cells
+    /// are left to zero with `is_active` = 0.
+    fn assign_inactive_row(&self, region: &mut Region<F>, offset: &mut usize) -> Result<(), Error> {
+        // q_enable
+        region.assign_fixed(
+            || "q_enable",
+            self.q_enable,
+            *offset,
+            || Value::known(F::one()),
+        )?;
+        // q_step
+        if *offset % 2 == 0 {
+            region.assign_fixed(|| "q_step", self.q_step, *offset, || Value::known(F::one()))?;
+        }
+
+        *offset += 1;
+
+        Ok(())
+    }
 }
 
 /// Struct for external data, specifies values for related lookup tables
@@ -936,6 +1041,10 @@ pub struct CopyCircuit<F: Field> {
     pub copy_events: Vec<CopyEvent>,
     /// Max number of rows in copy circuit
     pub max_copy_rows: usize,
+    /// Leave the rows after the copy events inactive instead of padding up to
+    /// `max_copy_rows`. Taken from the `copy_tight_padding` circuit parameter
+    /// of the block.
+    tight_padding: bool,
     _marker: PhantomData<F>,
     /// Data for external lookup tables
     pub external_data: ExternalData,
@@ -947,6 +1056,7 @@ impl<F: Field> CopyCircuit<F> {
         Self {
             copy_events,
             max_copy_rows,
+            tight_padding: false,
             _marker: PhantomData::default(),
             external_data: ExternalData::default(),
         }
@@ -961,20 +1071,29 @@ impl<F: Field> CopyCircuit<F> {
         Self {
             copy_events,
             max_copy_rows,
+            tight_padding: false,
             _marker: PhantomData::default(),
             external_data,
         }
     }
 
+    /// Rows used by each copy event, in assignment order.
+    pub fn rows_per_event(&self) -> Vec<usize> {
+        self.copy_events.iter().map(copy_event_rows).collect()
+    }
+
     /// Return a new CopyCircuit from a block without the external data required
     /// to assign lookup tables.  This constructor is only suitable to be
     /// used by the SuperCircuit, which already assigns the external lookup
     /// tables.
     pub fn new_from_block_no_external(block: &witness::Block<F>) -> Self {
-        Self::new(
-            block.copy_events.clone(),
-            block.circuits_params.max_copy_rows,
-        )
+        Self {
+            tight_padding: block.circuits_params.copy_tight_padding,
+            ..Self::new(
+                block.copy_events.clone(),
+                block.circuits_params.max_copy_rows,
+            )
+        }
     }
 }
 
@@ -988,28 +1107,27 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
     }
 
     fn new_from_block(block: &witness::Block<F>) -> Self {
-        Self::new_with_external_data(
-            block.copy_events.clone(),
-            block.circuits_params.max_copy_rows,
-            ExternalData {
-                max_txs: block.circuits_params.max_txs,
-                max_calldata: block.circuits_params.max_calldata,
-                txs: block.txs.clone(),
-                max_rws: block.circuits_params.max_rws,
-                rws: block.rws.clone(),
-                bytecodes: block.bytecodes.clone(),
-            },
-        )
+        Self {
+            tight_padding: block.circuits_params.copy_tight_padding,
+            ..Self::new_with_external_data(
+                block.copy_events.clone(),
+                block.circuits_params.max_copy_rows,
+                ExternalData {
+                    max_txs: block.circuits_params.max_txs,
+                    max_calldata: block.circuits_params.max_calldata,
+                    txs: block.txs.clone(),
+                    max_rws: block.circuits_params.max_rws,
+                    rws: block.rws.clone(),
+                    bytecodes: block.bytecodes.clone(),
+                },
+            )
+        }
     }
 
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
         (
-            block
-                .copy_events
-                .iter()
-                .map(|c| c.full_length() as usize * 2)
-                .sum::<usize>()
+            block.copy_events.iter().map(copy_event_rows).sum::<usize>()
                 + UNUSED_ROWS
                 + DISABLED_ROWS,
             block.circuits_params.max_copy_rows,
@@ -1023,7 +1141,13 @@ impl<F: Field> SubCircuit<F> for CopyCircuit<F> {
         challenges: &Challenges<Value<F>>,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
-        config.assign_copy_events(layouter, &self.copy_events, self.max_copy_rows, *challenges)
+        config.assign_copy_events(
+            layouter,
+            &self.copy_events,
+            self.max_copy_rows,
+            self.tight_padding,
+            *challenges,
+        )
     }
 }
 
@@ -47,6 +47,10 @@ pub fn constrain_tag<F: Field>(
                 CURRENT,
             )(meta),
             tag.value_equals(CopyDataType::Precompile(PrecompileCalls::Blake2F), CURRENT)(meta),
+            tag.value_equals(
+                CopyDataType::Precompile(PrecompileCalls::PointEvaluation),
+                CURRENT,
+            )(meta),
         ]);
         vec![
             // Match boolean indicators to their respective tag values.
@@ -61,6 +65,42 @@ pub fn constrain_tag<F: Field>(
     });
 }
 
+/// Verify that is_active is 1 on a prefix of the enabled rows, in whole steps. The inactive rows
+/// cannot start an event, so they are never looked up.
+pub fn constrain_is_active<F: Field>(
+    meta: &mut ConstraintSystem<F>,
+    q_enable: Column<Fixed>,
+    q_step: Column<Fixed>,
+    is_active: Column<Advice>,
+    is_first: Column<Advice>,
+) {
+    meta.create_gate("is_active", |meta| {
+        let cb = &mut BaseConstraintBuilder::default();
+
+        let [is_active, is_active_next] =
+            [CURRENT, NEXT_ROW].map(|at| meta.query_advice(is_active, at));
+
+        cb.require_boolean("is_active is boolean", is_active.expr());
+        cb.require_zero(
+            "is_active does not go from 0 to 1",
+            and::expr([not::expr(is_active.expr()), is_active_next.expr()]),
+        );
+        cb.condition(meta.query_fixed(q_step, CURRENT), |cb| {
+            cb.require_equal(
+                "the reader and the writer are both active or inactive",
+                is_active.expr(),
+                is_active_next,
+            );
+        });
+        cb.require_zero(
+            "is_first == 0 when is_active == 0",
+            and::expr([not::expr(is_active), meta.query_advice(is_first, CURRENT)]),
+        );
+
+        cb.gate(meta.query_fixed(q_enable, CURRENT))
+    });
+}
+
 /// Verify that is_first is on a reader row and is_last is on a write row.
 pub fn constrain_first_last<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
@@ -85,14 +125,15 @@ pub fn constrain_must_terminate<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
     meta: &mut VirtualCells<'_, F>,
     q_enable: Column<Fixed>,
+    is_active: Column<Advice>,
     tag: &BinaryNumberConfig<CopyDataType, 4>,
 ) {
-    // If an event has started (tag != Padding on reader and writer rows), require q_enable=1 at the
-    // next step. This prevents querying rows where constraints are disabled.
+    // If an event has started (tag != Padding on reader and writer rows), require q_enable=1 and
+    // is_active=1 at the next step. This prevents querying rows where constraints are disabled.
     //
     // The tag is then copied to the next step by constrain_forward_parameters. Eventually,
-    // q_enable=0. By that point the tag must have switched to Padding, which is only possible with
-    // is_last=1. This guarantees that all the final conditions are checked.
+    // q_enable=0 or is_active=0. By that point the tag must have switched to Padding, which is only
+    // possible with is_last=1. This guarantees that all the final conditions are checked.
     let is_event = tag.value(CURRENT)(meta) - tag.constant_expr::<F>(CopyDataType::Padding);
     cb.condition(is_event, |cb| {
         cb.require_equal(
@@ -100,6 +141,11 @@ pub fn constrain_must_terminate<F: Field>(
             meta.query_fixed(q_enable, NEXT_STEP),
             1.expr(),
         );
+        cb.require_equal(
+            "the next step is active",
+            meta.query_advice(is_active, NEXT_STEP),
+            1.expr(),
+        );
     });
 }
 
@@ -132,6 +178,43 @@ pub fn constrain_forward_parameters<F: Field>(
     });
 }
 
+/// Detect the events which copy within the memory of a single call, as MCOPY does, from the first
+/// step, and keep the flag through all rows of the event. Return is_memory_copy.
+pub fn constrain_is_memory_copy<F: Field>(
+    cb: &mut BaseConstraintBuilder<F>,
+    meta: &mut VirtualCells<'_, F>,
+    is_first: Expression<F>,
+    is_last: Expression<F>,
+    is_memory: Column<Advice>,
+    is_id_unchanged: Expression<F>,
+    is_memory_copy: Column<Advice>,
+) -> Expression<F> {
+    let [is_memory_copy, is_memory_copy_next] =
+        [CURRENT, NEXT_ROW].map(|at| meta.query_advice(is_memory_copy, at));
+
+    cb.condition(is_first, |cb| {
+        cb.require_equal(
+            "is_memory_copy when the reader and the writer are the same memory",
+            is_memory_copy.expr(),
+            and::expr([
+                meta.query_advice(is_memory, CURRENT),
+                meta.query_advice(is_memory, NEXT_ROW),
+                is_id_unchanged,
+            ]),
+        );
+    });
+
+    cb.condition(not::expr(is_last), |cb| {
+        cb.require_equal(
+            "is_memory_copy does not change",
+            is_memory_copy.expr(),
+            is_memory_copy_next,
+        );
+    });
+
+    is_memory_copy
+}
+
 /// Verify that when and after the address reaches the limit src_addr_end, zero-padding is enabled.
 /// Return (is_pad, is_pad at NEXT_STEP).
 #[allow(clippy::too_many_arguments)]
@@ -516,11 +599,14 @@ pub fn constrain_address<F: Field>(
 
 /// Update the RW counter and verify that all RWs requested by the event are consumed.
 #[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments)]
 pub fn constrain_rw_counter<F: Field>(
     cb: &mut BaseConstraintBuilder<F>,
     meta: &mut VirtualCells<'_, F>,
-    is_last: Expression<F>,      // The last row.
-    is_last_step: Expression<F>, // Both the last reader and writer rows.
+    is_first: Expression<F>,       // The first row.
+    is_last: Expression<F>,        // The last row.
+    is_last_step: Expression<F>,   // Both the last reader and writer rows.
+    is_memory_copy: Expression<F>, // All the reads come before the writes.
     is_rw_type: Expression<F>,
     is_word_end: Expression<F>,
     rw_counter: Column<Advice>,
@@ -535,11 +621,38 @@ pub fn constrain_rw_counter<F: Field>(
         meta.query_advice(rwc_inc_left, NEXT_ROW),
         0.expr(),
     );
-    cb.require_equal(
-        "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff, or 0 at the end",
-        new_value,
-        update_or_finish,
-    );
+    cb.condition(not::expr(is_memory_copy.expr()), |cb| {
+        cb.require_equal(
+            "rwc_inc_left[2] == rwc_inc_left[0] - rwc_diff, or 0 at the end",
+            new_value.expr(),
+            update_or_finish,
+        );
+    });
+
+    // A copy within a single memory reads all its words before it writes any, so that the writes
+    // cannot change the words still to be read. The reader and the writer then decrement their own
+    // rwc_inc_left, and the writer starts with the half of the RW operations that it performs.
+    cb.condition(is_memory_copy.expr(), |cb| {
+        cb.condition(is_first.expr(), |cb| {
+            cb.require_equal(
+                "rwc_inc_left[0] == 2 * rwc_inc_left[1] at the start",
+                meta.query_advice(rwc_inc_left, CURRENT),
+                2.expr() * meta.query_advice(rwc_inc_left, NEXT_ROW),
+            );
+        });
+        let update_or_finish = select::expr(
+            is_last.expr(),
+            0.expr(),
+            meta.query_advice(rwc_inc_left, NEXT_STEP),
+        );
+        cb.condition(not::expr(is_last_step.expr()) + is_last.expr(), |cb| {
+            cb.require_equal(
+                "rwc_inc_left at NEXT_STEP == rwc_inc_left[0] - rwc_diff, or 0 at the end",
+                new_value.expr(),
+                update_or_finish,
+            );
+        });
+    });
 
     // Maintain rw_counter based on rwc_inc_left. Their sum remains constant in all cases.
     cb.condition(not::expr(is_last.expr()), |cb| {
@@ -301,6 +301,29 @@ fn gen_return_data() -> CircuitInputBuilder {
     builder
 }
 
+#[cfg(feature = "cancun")]
+fn gen_mcopy_data() -> CircuitInputBuilder {
+    // the destination overlaps the source at a higher address, so the second
+    // source word is written before it would be read in a memory word order
+    let code = bytecode! {
+        .op_mstore(0x00, Word::from_big_endian(&rand_bytes(32)))
+        .op_mstore(0x20, Word::from_big_endian(&rand_bytes(32)))
+        PUSH1(0x3d)
+        PUSH1(0x03)
+        PUSH1(0x25)
+        MCOPY
+        STOP
+    };
+
+    let test_ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap();
+    let block: GethData = test_ctx.into();
+    let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+    builder
+        .handle_block(&block.eth_block, &block.geth_traces)
+        .unwrap();
+    builder
+}
+
 #[test]
 fn copy_circuit_valid_calldatacopy() {
     let builder = gen_calldatacopy_data();
@@ -357,6 +380,15 @@ fn copy_circuit_valid_return() {
     assert_eq!(test_copy_circuit_from_block(block), Ok(()));
 }
 
+#[cfg(feature = "cancun")]
+#[test]
+fn copy_circuit_valid_mcopy() {
+    let builder = gen_mcopy_data();
+    let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    assert!(block.copy_events[0].is_memory_copy());
+    assert_eq!(test_copy_circuit_from_block(block), Ok(()));
+}
+
 #[test]
 fn copy_circuit_invalid_calldatacopy() {
     let mut builder = gen_calldatacopy_data();
@@ -485,6 +517,30 @@ fn copy_circuit_precompile_call() {
     assert_eq!(test_copy_circuit_from_block(block), Ok(()));
 }
 
+#[test]
+fn copy_circuit_tight_padding() {
+    let builder = gen_calldatacopy_data();
+    let mut block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+    block.circuits_params.copy_tight_padding = true;
+    let circuit = CopyCircuit::<Fr>::new_from_block(&block);
+
+    let rows_per_event = circuit.rows_per_event();
+    assert_eq!(rows_per_event.len(), block.copy_events.len());
+    assert_eq!(
+        rows_per_event.iter().sum::<usize>() + UNUSED_ROWS + DISABLED_ROWS,
+        CopyCircuit::<Fr>::min_num_rows_block(&block).0
+    );
+
+    let prover = MockProver::<Fr>::run(K, &circuit, vec![]).unwrap();
+    assert_eq!(prover.verify_par(), Ok(()));
+
+    // the fixed columns are those of the full padding
+    block.circuits_params.copy_tight_padding = false;
+    let full_circuit = CopyCircuit::<Fr>::new_from_block(&block);
+    let full_prover = MockProver::<Fr>::run(K, &full_circuit, vec![]).unwrap();
+    assert_eq!(prover.fixed(), full_prover.fixed());
+}
+
 #[test]
 fn variadic_size_check() {
     let builder = gen_tx_log_data();
@@ -743,7 +743,10 @@ impl<F: Field, const XI_0: i64> SubCircuit<F> for EccCircuit<F, XI_0> {
         Ok(())
     }
 
+    // Like the sig circuit, the halo2-lib chips are configured for a degree
+    // of LOG_TOTAL_NUM_ROWS, whose usable rows they take.
     fn min_num_rows_block(_block: &Block<F>) -> (usize, usize) {
-        unimplemented!()
+        let row_num = (1 << LOG_TOTAL_NUM_ROWS) - Self::unusable_rows();
+        (row_num, row_num)
     }
 }
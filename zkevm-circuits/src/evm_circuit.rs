@@ -8,10 +8,14 @@ use halo2_proofs::{
 
 mod execution;
 pub mod param;
+#[cfg(feature = "step-profile")]
+pub mod profile;
 pub(crate) mod step;
 pub mod table;
 pub(crate) mod util;
 
+#[cfg(any(feature = "test", test))]
+pub(crate) use execution::assigned_rw_lookups;
 #[cfg(any(feature = "test", test))]
 pub(crate) mod test;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
@@ -26,8 +30,14 @@ use crate::{
     },
     util::{SubCircuit, SubCircuitConfig},
 };
-use bus_mapping::evm::OpcodeId;
-use eth_types::Field;
+use bus_mapping::{
+    circuit_input_builder::{ChainSpec, L1FeeConfig},
+    evm::OpcodeId,
+};
+use eth_types::{
+    evm_types::{GasSchedules, MAX_CODE_SIZE, MAX_INIT_CODE_SIZE},
+    Field,
+};
 use execution::ExecutionConfig;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
@@ -84,6 +94,8 @@ pub struct EvmCircuitConfigArgs<F: Field> {
 pub struct EvmCircuitExports<V> {
     /// withdraw root
     pub withdraw_root: (Cell, Value<V>),
+    /// withdraw root before the block
+    pub prev_withdraw_root: (Cell, Value<V>),
 }
 
 impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
@@ -159,18 +171,69 @@ impl<F: Field> SubCircuitConfig<F> for EvmCircuitConfig<F> {
     }
 }
 
+/// Parameters of the chain which the fixed table of the EVM circuit is built
+/// from. They're parameters of the circuit rather than witness, so that the
+/// verifying key commits to them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvmCircuitParams {
+    /// Gas schedules of the storage opcodes, for [`FixedTableTag::GasSchedule`]
+    pub gas_schedules: GasSchedules,
+    /// First block of the Shanghai hardfork, or `None` if the chain doesn't
+    /// activate it, for [`FixedTableTag::ShanghaiBlock`]
+    pub shanghai_block: Option<u64>,
+    /// First block of the Curie hardfork, or `None` if the chain doesn't
+    /// activate it, for [`FixedTableTag::CurieBlock`]
+    pub curie_block: Option<u64>,
+    /// Maximum size of the code of a contract of the chain (EIP-170), for
+    /// [`FixedTableTag::MaxCodeSize`]
+    pub max_code_size: u64,
+    /// Maximum size of init code of the chain (EIP-3860), for
+    /// [`FixedTableTag::MaxInitCodeSize`]
+    pub max_init_code_size: u64,
+}
+
+impl Default for EvmCircuitParams {
+    fn default() -> Self {
+        Self {
+            gas_schedules: GasSchedules::default(),
+            shanghai_block: ChainSpec::default().shanghai_block,
+            curie_block: L1FeeConfig::default().curie_block,
+            max_code_size: MAX_CODE_SIZE,
+            max_init_code_size: MAX_INIT_CODE_SIZE,
+        }
+    }
+}
+
+impl EvmCircuitParams {
+    /// The parameters `block` was built with
+    pub fn from_block<F: Field>(block: &Block<F>) -> Self {
+        Self {
+            gas_schedules: block.gas_schedules.clone(),
+            shanghai_block: block.chain_spec.shanghai_block,
+            curie_block: block.l1_fee_config.curie_block,
+            max_code_size: block.circuits_params.max_code_size as u64,
+            max_init_code_size: block.circuits_params.max_init_code_size(),
+        }
+    }
+}
+
 impl<F: Field> EvmCircuitConfig<F> {
-    /// Load fixed table
+    /// Load fixed table, with the rows of the gas schedules, the Shanghai and
+    /// Curie blocks and the code size limits of `params` for
+    /// [`FixedTableTag::GasSchedule`], [`FixedTableTag::ShanghaiBlock`],
+    /// [`FixedTableTag::CurieBlock`], [`FixedTableTag::MaxCodeSize`] and
+    /// [`FixedTableTag::MaxInitCodeSize`]
     pub fn load_fixed_table(
         &self,
         layouter: &mut impl Layouter<F>,
         fixed_table_tags: Vec<FixedTableTag>,
+        params: &EvmCircuitParams,
     ) -> Result<(), Error> {
         layouter.assign_region(
             || "fixed table",
             |mut region| {
                 for (offset, row) in std::iter::once([F::zero(); 4])
-                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build()))
+                    .chain(fixed_table_tags.iter().flat_map(|tag| tag.build(params)))
                     .enumerate()
                 {
                     for (column, value) in self.fixed_table.iter().zip_eq(row) {
@@ -209,21 +272,32 @@ pub struct EvmCircuit<F: Field> {
     /// Block
     pub block: Option<Block<F>>,
     fixed_table_tags: Vec<FixedTableTag>,
+    /// Parameters of the chain, which the block must have been built with
+    pub params: EvmCircuitParams,
     pub(crate) exports: std::cell::RefCell<Option<EvmCircuitExports<Assigned<F>>>>,
 }
 
 impl<F: Field> EvmCircuit<F> {
-    /// Return a new EvmCircuit
+    /// Return a new EvmCircuit, with the parameters `block` was built with
     pub fn new(block: Block<F>) -> Self {
+        let params = EvmCircuitParams::from_block(&block);
+        Self::new_with_params(block, params)
+    }
+
+    /// Return a new EvmCircuit for a chain with the parameters `params`, which
+    /// fails to synthesize if `block` was built with other parameters
+    pub fn new_with_params(block: Block<F>, params: EvmCircuitParams) -> Self {
         Self {
             block: Some(block),
             fixed_table_tags: FixedTableTag::iter().collect(),
+            params,
             ..Default::default()
         }
     }
 
     pub fn new_dev(block: Block<F>, fixed_table_tags: Vec<FixedTableTag>) -> Self {
         Self {
+            params: EvmCircuitParams::from_block(&block),
             block: Some(block),
             fixed_table_tags,
             ..Default::default()
@@ -294,7 +368,7 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
             Self::get_num_rows_required_no_padding(block);
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(block)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(&EvmCircuitParams::from_block(block)).count())
             .sum();
         (
             num_rows_required_for_execution_steps,
@@ -316,8 +390,16 @@ impl<F: Field> SubCircuit<F> for EvmCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         let block = self.block.as_ref().unwrap();
+        if EvmCircuitParams::from_block(block) != self.params {
+            log::error!(
+                "block built with the parameters {:?}, but the circuit has {:?}",
+                EvmCircuitParams::from_block(block),
+                self.params
+            );
+            return Err(Error::Synthesis);
+        }
 
-        config.load_fixed_table(layouter, self.fixed_table_tags.clone())?;
+        config.load_fixed_table(layouter, self.fixed_table_tags.clone(), &self.params)?;
         config.load_byte_table(layouter)?;
         let export = config.execution.assign_block(layouter, block, challenges)?;
         self.exports.borrow_mut().replace(export);
@@ -417,7 +499,10 @@ impl<F: Field> Circuit<F> for EvmCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            params: self.params.clone(),
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -521,7 +606,7 @@ mod evm_circuit_stats {
                 N_PHASE2_COPY_COLUMNS,
             },
             step::ExecutionState,
-            EvmCircuit,
+            EvmCircuit, EvmCircuitParams,
         },
         stats::print_circuit_stats_by_states,
         test_util::CircuitTestBuilder,
@@ -530,7 +615,12 @@ mod evm_circuit_stats {
     };
     use bus_mapping::{circuit_input_builder::CircuitsParams, mock::BlockData};
     use cli_table::{print_stdout, Cell, Style, Table};
-    use eth_types::{bytecode, evm_types::OpcodeId, geth_types::GethData, ToWord};
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, GasSchedule, GasSchedules, OpcodeId},
+        geth_types::GethData,
+        ToWord,
+    };
     use halo2_proofs::{
         dev::MockProver,
         halo2curves::bn256::Fr,
@@ -698,6 +788,119 @@ mod evm_circuit_stats {
         );
     }
 
+    /// This function prints to stdout the matrix of the lookups each
+    /// ExecutionState does into each table, as CSV, followed by the lookups
+    /// sharing a name within a state, which are candidates for being merged.
+    ///
+    /// Run with:
+    /// `cargo test -p zkevm-circuits --release get_exec_steps_lookups
+    /// --features test -- --nocapture --ignored`
+    #[ignore]
+    #[test]
+    fn get_exec_steps_lookups() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let circuit = EvmCircuit::configure(&mut meta);
+        let instrument = circuit.0.execution.instrument();
+
+        println!("{}", instrument.lookup_matrix_csv());
+
+        let redundant = instrument
+            .analyze_lookups()
+            .iter()
+            .flat_map(|report| {
+                report
+                    .redundant_lookups()
+                    .into_iter()
+                    .map(|(table, name, count)| {
+                        vec![
+                            format!("{:?}", report.state),
+                            format!("{table:?}"),
+                            name,
+                            format!("{count}"),
+                        ]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<Vec<String>>>();
+        let table = redundant.table().title(vec![
+            "state".cell().bold(true),
+            "table".cell().bold(true),
+            "lookup".cell().bold(true),
+            "count".cell().bold(true),
+        ]);
+        print_stdout(table).unwrap();
+    }
+
+    #[test]
+    fn exec_steps_lookups_fit_lookup_config() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let circuit = EvmCircuit::configure(&mut meta);
+
+        for report in circuit.0.execution.instrument().analyze_lookups() {
+            for (table, columns) in LOOKUP_CONFIG {
+                assert!(
+                    report.required_columns(*table) <= *columns,
+                    "{:?} needs {} columns of {:?} lookups",
+                    report.state,
+                    report.required_columns(*table),
+                    table
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn evm_circuit_params_mismatch() {
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(bytecode! { STOP }),
+            tx_from_1_to_0,
+            |b, _| b,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        let k = block.get_test_degree();
+
+        // a chain which reprices SSTORE, activates Shanghai or Curie at
+        // another block, or limits the code size lower, than the one the
+        // block was built for
+        let repriced = EvmCircuitParams {
+            gas_schedules: GasSchedules::default().with_schedule(
+                0,
+                GasSchedule {
+                    sstore_set: GasCost::SSTORE_SET.0 + 1,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        };
+        let shanghai_later = EvmCircuitParams {
+            shanghai_block: Some(block.chain_spec.shanghai_block.unwrap_or(0) + 1),
+            ..Default::default()
+        };
+        let curie_later = EvmCircuitParams {
+            curie_block: Some(block.l1_fee_config.curie_block.unwrap_or(0) + 1),
+            ..Default::default()
+        };
+        let lower_code_size = EvmCircuitParams {
+            max_code_size: block.circuits_params.max_code_size as u64 - 1,
+            ..Default::default()
+        };
+        for params in [repriced, shanghai_later, curie_later, lower_code_size] {
+            let circuit = EvmCircuit {
+                params: params.clone(),
+                ..EvmCircuit::<Fr>::get_test_cicuit_from_block(block.clone())
+            };
+            assert_eq!(circuit.without_witnesses().params, params);
+            assert!(MockProver::<Fr>::run(k, &circuit, vec![]).is_err());
+        }
+    }
+
     #[ignore = "need to make table dev_load padding to fix this"]
     #[test]
     fn variadic_size_check() {
@@ -56,12 +56,47 @@ use once_cell::sync::Lazy;
 pub(crate) static CHECK_RW_LOOKUP: Lazy<bool> =
     Lazy::new(|| read_env_var("CHECK_RW_LOOKUP", false));
 
+/// Rw lookups assigned to a step by the EVM circuit, with the challenges they
+/// are computed with.
+#[cfg(any(feature = "test", test))]
+#[derive(Clone, Debug)]
+pub(crate) struct AssignedRwLookups<F> {
+    pub(crate) evm_randomness: F,
+    pub(crate) lookup_randomness: F,
+    /// Values of the rw lookups, in the order of the gadget
+    pub(crate) values: Vec<F>,
+}
+
+#[cfg(any(feature = "test", test))]
+thread_local! {
+    /// Rw lookups of the steps assigned by the last EVM circuit assignment of
+    /// the thread, by rw counter and execution state of the step.
+    static ASSIGNED_RW_LOOKUPS: std::cell::RefCell<
+        HashMap<(usize, ExecutionState), Box<dyn std::any::Any>>,
+    > = Default::default();
+}
+
+/// Rw lookups assigned to `step` by the last EVM circuit assignment of the
+/// thread, if the challenges were known.
+#[cfg(any(feature = "test", test))]
+pub(crate) fn assigned_rw_lookups<F: Field>(step: &ExecStep) -> Option<AssignedRwLookups<F>> {
+    ASSIGNED_RW_LOOKUPS.with(|lookups| {
+        lookups
+            .borrow()
+            .get(&(step.rw_counter, step.execution_state))
+            .and_then(|lookups| lookups.downcast_ref::<AssignedRwLookups<F>>())
+            .cloned()
+    })
+}
+
 mod add_sub;
 mod addmod;
 mod address;
 mod balance;
 mod begin_tx;
 mod bitwise;
+mod blobbasefee;
+mod blobhash;
 mod block_ctx;
 mod blockhash;
 mod byte;
@@ -76,6 +111,7 @@ mod codecopy;
 mod codesize;
 mod comparator;
 mod create;
+mod destruct_account;
 #[cfg(not(feature = "scroll"))]
 mod dummy;
 mod dup;
@@ -90,13 +126,12 @@ mod error_oog_account_access;
 mod error_oog_call;
 mod error_oog_constant;
 mod error_oog_create;
-mod error_oog_dynamic_memory;
 mod error_oog_exp;
 mod error_oog_log;
 mod error_oog_memory_copy;
+mod error_oog_memory_expansion;
 mod error_oog_sha3;
 mod error_oog_sload_sstore;
-mod error_oog_static_memory;
 mod error_precompile_failed;
 mod error_return_data_oo_bound;
 mod error_stack;
@@ -112,6 +147,7 @@ mod jump;
 mod jumpdest;
 mod jumpi;
 mod logs;
+mod mcopy;
 mod memory;
 mod msize;
 mod mul_div_mod;
@@ -129,6 +165,8 @@ mod returndatasize;
 mod sar;
 mod sdiv_smod;
 mod selfbalance;
+#[cfg(not(feature = "scroll"))]
+mod selfdestruct;
 mod sha3;
 mod shl_shr;
 mod signed_comparator;
@@ -145,6 +183,8 @@ use address::AddressGadget;
 use balance::BalanceGadget;
 use begin_tx::BeginTxGadget;
 use bitwise::BitwiseGadget;
+use blobbasefee::BlobBaseFeeGadget;
+use blobhash::BlobHashGadget;
 use block_ctx::{BlockCtxU160Gadget, BlockCtxU256Gadget, BlockCtxU64Gadget};
 use blockhash::BlockHashGadget;
 use byte::ByteGadget;
@@ -159,6 +199,7 @@ use codecopy::CodeCopyGadget;
 use codesize::CodesizeGadget;
 use comparator::ComparatorGadget;
 use create::CreateGadget;
+use destruct_account::DestructAccountGadget;
 #[cfg(not(feature = "scroll"))]
 use dummy::DummyGadget;
 use dup::DupGadget;
@@ -173,13 +214,12 @@ use error_oog_account_access::ErrorOOGAccountAccessGadget;
 use error_oog_call::ErrorOOGCallGadget;
 use error_oog_constant::ErrorOOGConstantGadget;
 use error_oog_create::ErrorOOGCreateGadget;
-use error_oog_dynamic_memory::ErrorOOGDynamicMemoryGadget;
 use error_oog_exp::ErrorOOGExpGadget;
 use error_oog_log::ErrorOOGLogGadget;
 use error_oog_memory_copy::ErrorOOGMemoryCopyGadget;
+use error_oog_memory_expansion::ErrorOOGMemoryExpansionGadget;
 use error_oog_sha3::ErrorOOGSha3Gadget;
 use error_oog_sload_sstore::ErrorOOGSloadSstoreGadget;
-use error_oog_static_memory::ErrorOOGStaticMemoryGadget;
 use error_precompile_failed::ErrorPrecompileFailedGadget;
 use error_return_data_oo_bound::ErrorReturnDataOutOfBoundGadget;
 use error_stack::ErrorStackGadget;
@@ -195,6 +235,7 @@ use jump::JumpGadget;
 use jumpdest::JumpdestGadget;
 use jumpi::JumpiGadget;
 
+use mcopy::MCopyGadget;
 use memory::MemoryGadget;
 use msize::MsizeGadget;
 use mul_div_mod::MulDivModGadget;
@@ -211,6 +252,8 @@ use returndatasize::ReturnDataSizeGadget;
 use sar::SarGadget;
 use sdiv_smod::SignedDivModGadget;
 use selfbalance::SelfbalanceGadget;
+#[cfg(not(feature = "scroll"))]
+use selfdestruct::SelfDestructGadget;
 use shl_shr::ShlShrGadget;
 use signed_comparator::SignedComparatorGadget;
 use signextend::SignextendGadget;
@@ -261,6 +304,7 @@ pub(crate) struct ExecutionConfig<F> {
     instrument: Instrument,
     // internal state gadgets
     begin_tx_gadget: Box<BeginTxGadget<F>>,
+    destruct_account_gadget: Box<DestructAccountGadget<F>>,
     end_block_gadget: Box<EndBlockGadget<F>>,
     end_inner_block_gadget: Box<EndInnerBlockGadget<F>>,
     end_tx_gadget: Box<EndTxGadget<F>>,
@@ -270,6 +314,8 @@ pub(crate) struct ExecutionConfig<F> {
     address_gadget: Box<AddressGadget<F>>,
     balance_gadget: Box<BalanceGadget<F>>,
     bitwise_gadget: Box<BitwiseGadget<F>>,
+    blobbasefee_gadget: Box<BlobBaseFeeGadget<F>>,
+    blobhash_gadget: Box<BlobHashGadget<F>>,
     byte_gadget: Box<ByteGadget<F>>,
     call_op_gadget: Box<CallOpGadget<F>>,
     call_value_gadget: Box<CallValueGadget<F>>,
@@ -293,6 +339,7 @@ pub(crate) struct ExecutionConfig<F> {
     jumpdest_gadget: Box<JumpdestGadget<F>>,
     jumpi_gadget: Box<JumpiGadget<F>>,
     log_gadget: Box<LogGadget<F>>,
+    mcopy_gadget: Box<MCopyGadget<F>>,
     memory_gadget: Box<MemoryGadget<F>>,
     msize_gadget: Box<MsizeGadget<F>>,
     mul_div_mod_gadget: Box<MulDivModGadget<F>>,
@@ -313,7 +360,7 @@ pub(crate) struct ExecutionConfig<F> {
     create_gadget: Box<CreateGadget<F, false, { ExecutionState::CREATE }>>,
     create2_gadget: Box<CreateGadget<F, true, { ExecutionState::CREATE2 }>>,
     #[cfg(not(feature = "scroll"))]
-    selfdestruct_gadget: Box<DummyGadget<F, 1, 0, { ExecutionState::SELFDESTRUCT }>>,
+    selfdestruct_gadget: Box<SelfDestructGadget<F>>,
     signed_comparator_gadget: Box<SignedComparatorGadget<F>>,
     signextend_gadget: Box<SignextendGadget<F>>,
     sload_gadget: Box<SloadGadget<F>>,
@@ -330,10 +377,9 @@ pub(crate) struct ExecutionConfig<F> {
     error_oog_exp: Box<ErrorOOGExpGadget<F>>,
     error_oog_memory_copy: Box<ErrorOOGMemoryCopyGadget<F>>,
     error_oog_sload_sstore: Box<ErrorOOGSloadSstoreGadget<F>>,
-    error_oog_static_memory_gadget: Box<ErrorOOGStaticMemoryGadget<F>>,
+    error_oog_memory_expansion: Box<ErrorOOGMemoryExpansionGadget<F>>,
     error_stack: Box<ErrorStackGadget<F>>,
     error_write_protection: Box<ErrorWriteProtectionGadget<F>>,
-    error_oog_dynamic_memory_gadget: Box<ErrorOOGDynamicMemoryGadget<F>>,
     error_oog_log: Box<ErrorOOGLogGadget<F>>,
     error_oog_account_access: Box<ErrorOOGAccountAccessGadget<F>>,
     error_oog_sha3: Box<ErrorOOGSha3Gadget<F>>,
@@ -357,6 +403,8 @@ pub(crate) struct ExecutionConfig<F> {
     precompile_bn128mul_gadget: Box<EcMulGadget<F>>,
     precompile_bn128pairing_gadget: Box<EcPairingGadget<F>>,
     precompile_blake2f_gadget: Box<BasePrecompileGadget<F, { ExecutionState::PrecompileBlake2f }>>,
+    precompile_point_evaluation_gadget:
+        Box<BasePrecompileGadget<F, { ExecutionState::PrecompilePointEvaluation }>>,
 }
 
 impl<F: Field> ExecutionConfig<F> {
@@ -537,6 +585,7 @@ impl<F: Field> ExecutionConfig<F> {
             advices,
             // internal states
             begin_tx_gadget: configure_gadget!(),
+            destruct_account_gadget: configure_gadget!(),
             end_block_gadget: configure_gadget!(),
             end_inner_block_gadget: configure_gadget!(),
             end_tx_gadget: configure_gadget!(),
@@ -544,6 +593,8 @@ impl<F: Field> ExecutionConfig<F> {
             add_sub_gadget: configure_gadget!(),
             addmod_gadget: configure_gadget!(),
             bitwise_gadget: configure_gadget!(),
+            blobbasefee_gadget: configure_gadget!(),
+            blobhash_gadget: configure_gadget!(),
             byte_gadget: configure_gadget!(),
             call_op_gadget: configure_gadget!(),
             call_value_gadget: configure_gadget!(),
@@ -565,6 +616,7 @@ impl<F: Field> ExecutionConfig<F> {
             jumpdest_gadget: configure_gadget!(),
             jumpi_gadget: configure_gadget!(),
             log_gadget: configure_gadget!(),
+            mcopy_gadget: configure_gadget!(),
             memory_gadget: configure_gadget!(),
             msize_gadget: configure_gadget!(),
             mul_div_mod_gadget: configure_gadget!(),
@@ -602,9 +654,8 @@ impl<F: Field> ExecutionConfig<F> {
             block_ctx_u256_gadget: configure_gadget!(),
             // error gadgets
             error_oog_constant: configure_gadget!(),
-            error_oog_static_memory_gadget: configure_gadget!(),
+            error_oog_memory_expansion: configure_gadget!(),
             error_stack: configure_gadget!(),
-            error_oog_dynamic_memory_gadget: configure_gadget!(),
             error_oog_log: configure_gadget!(),
             error_oog_sload_sstore: configure_gadget!(),
             error_oog_call: configure_gadget!(),
@@ -632,6 +683,7 @@ impl<F: Field> ExecutionConfig<F> {
             precompile_bn128mul_gadget: configure_gadget!(),
             precompile_bn128pairing_gadget: configure_gadget!(),
             precompile_blake2f_gadget: configure_gadget!(),
+            precompile_point_evaluation_gadget: configure_gadget!(),
             // step and presets
             step: step_curr,
             height_map,
@@ -806,6 +858,11 @@ impl<F: Field> ExecutionConfig<F> {
             iter::empty()
                 .chain(
                     IntoIterator::into_iter([
+                        (
+                            "DestructAccount can only transit to DestructAccount or EndTx",
+                            ExecutionState::DestructAccount,
+                            vec![ExecutionState::DestructAccount, ExecutionState::EndTx],
+                        ),
                         (
                             "EndTx can only transit to BeginTx or EndInnerBlock",
                             ExecutionState::EndTx,
@@ -833,13 +890,23 @@ impl<F: Field> ExecutionConfig<F> {
                             vec![ExecutionState::EndTx, ExecutionState::EndInnerBlock],
                         ),
                         (
-                            "Only ExecutionState which halts or BeginTx can transit to EndTx",
+                            "Only ExecutionState which halts, BeginTx or DestructAccount can transit to EndTx",
                             ExecutionState::EndTx,
                             ExecutionState::iter()
                                 .filter(ExecutionState::halts)
-                                .chain(iter::once(ExecutionState::BeginTx))
+                                .chain([ExecutionState::BeginTx, ExecutionState::DestructAccount])
                                 .collect(),
                         ),
+                        (
+                            "Only STOP, RETURN, SELFDESTRUCT or DestructAccount can transit to DestructAccount",
+                            ExecutionState::DestructAccount,
+                            vec![
+                                ExecutionState::STOP,
+                                ExecutionState::RETURN_REVERT,
+                                ExecutionState::SELFDESTRUCT,
+                                ExecutionState::DestructAccount,
+                            ],
+                        ),
                         (
                             "Only EndInnerBlock or EndBlock can transit to EndBlock",
                             ExecutionState::EndBlock,
@@ -1007,12 +1074,16 @@ impl<F: Field> ExecutionConfig<F> {
     /// Assign block
     /// When exact is enabled, assign exact steps in block without padding for
     /// unit test purpose
+    /// The steps are streamed into the region: each one is assigned as it is
+    /// iterated, and only the rows of the current and next steps are cached.
     pub fn assign_block(
         &self,
         layouter: &mut impl Layouter<F>,
         block: &Block<F>,
         challenges: &Challenges<Value<F>>,
     ) -> Result<EvmCircuitExports<Assigned<F>>, Error> {
+        #[cfg(any(feature = "test", test))]
+        ASSIGNED_RW_LOOKUPS.with(|lookups| lookups.borrow_mut().clear());
         let mut is_first_time = true;
 
         layouter.assign_region(
@@ -1035,12 +1106,19 @@ impl<F: Field> ExecutionConfig<F> {
 
                 self.q_step_first.enable(&mut region, offset)?;
 
+                // The steps are assigned as they are iterated, through one
+                // cached region moved from step to step.
+                let region = &mut CachedRegion::<'_, '_, F>::new(
+                    &mut region,
+                    challenges,
+                    self.advices.to_vec(),
+                    MAX_STEP_HEIGHT * 3,
+                    offset,
+                );
+
                 let dummy_tx = Transaction::default();
-                let last_call = block
-                    .txs
-                    .last()
-                    .map(|tx| tx.calls[0].clone())
-                    .unwrap_or_else(Call::default);
+                let dummy_call = Call::default();
+                let last_call = block.txs.last().map_or(&dummy_call, |tx| &tx.calls[0]);
                 let end_block_not_last = &block.end_block_not_last;
                 let end_block_last = &block.end_block_last;
                 // Collect all steps
@@ -1052,7 +1130,7 @@ impl<F: Field> ExecutionConfig<F> {
                             .iter()
                             .map(move |step| (tx, &tx.calls[step.call_index], step))
                     })
-                    .chain(std::iter::once((&dummy_tx, &last_call, end_block_not_last)))
+                    .chain(std::iter::once((&dummy_tx, last_call, end_block_not_last)))
                     .peekable();
 
                 let evm_rows = block.circuits_params.max_evm_rows;
@@ -1069,12 +1147,8 @@ impl<F: Field> ExecutionConfig<F> {
 
                     // Assign the step witness
                     if step.execution_state == ExecutionState::EndTx {
-                        let mut tx = transaction.clone();
-                        tx.call_data.clear();
-                        tx.calls.clear();
-                        tx.steps.clear();
-                        tx.rlp_signed.clear();
-                        tx.rlp_unsigned.clear();
+                        // the tx is not cloned, its steps and call data can be large
+                        let tx = transaction;
                         let total_gas = {
                             let gas_used = tx.gas - step.gas_left;
                             let current_cumulative_gas_used: u64 = if tx.id == 1 {
@@ -1098,11 +1172,11 @@ impl<F: Field> ExecutionConfig<F> {
                             tx.id,
                             total_gas,
                             step,
-                            tx
+                            tx.hash
                         );
                     }
                     self.assign_exec_step(
-                        &mut region,
+                        region,
                         offset,
                         block,
                         transaction,
@@ -1110,11 +1184,10 @@ impl<F: Field> ExecutionConfig<F> {
                         step,
                         height,
                         next.copied(),
-                        challenges,
                     )?;
 
                     // q_step logic
-                    self.assign_q_step(&mut region, offset, height)?;
+                    self.assign_q_step(region.region_mut(), offset, height)?;
 
                     offset += height;
                 }
@@ -1139,19 +1212,18 @@ impl<F: Field> ExecutionConfig<F> {
                         return Err(Error::Synthesis);
                     }
                     self.assign_same_exec_step_in_range(
-                        &mut region,
+                        region,
                         offset,
                         last_row,
                         block,
                         &dummy_tx,
-                        &last_call,
+                        last_call,
                         end_block_not_last,
                         height,
-                        challenges,
                     )?;
 
                     for row_idx in offset..last_row {
-                        self.assign_q_step(&mut region, row_idx, height)?;
+                        self.assign_q_step(region.region_mut(), row_idx, height)?;
                     }
                     offset = last_row;
                 }
@@ -1161,19 +1233,19 @@ impl<F: Field> ExecutionConfig<F> {
                 debug_assert_eq!(height, 1);
                 log::trace!("assign last EndBlock at offset {}", offset);
                 self.assign_exec_step(
-                    &mut region,
+                    region,
                     offset,
                     block,
                     &dummy_tx,
-                    &last_call,
+                    last_call,
                     end_block_last,
                     height,
                     None,
-                    challenges,
                 )?;
-                self.assign_q_step(&mut region, offset, height)?;
+                let region = region.region_mut();
+                self.assign_q_step(region, offset, height)?;
                 // enable q_step_last
-                self.q_step_last.enable(&mut region, offset)?;
+                self.q_step_last.enable(region, offset)?;
                 offset += height;
 
                 // part4:
@@ -1203,6 +1275,11 @@ impl<F: Field> ExecutionConfig<F> {
             .withdraw_root_assigned
             .borrow()
             .expect("withdraw_root cell should has been assigned");
+        let final_withdraw_root_prev_cell = self
+            .end_block_gadget
+            .withdraw_root_prev_assigned
+            .borrow()
+            .expect("withdraw_root_prev cell should has been assigned");
 
         // sanity check
         let evm_rows = block.circuits_params.max_evm_rows;
@@ -1213,9 +1290,13 @@ impl<F: Field> ExecutionConfig<F> {
         let withdraw_root_rlc = challenges
             .evm_word()
             .map(|r| rlc::value(&block.withdraw_root.to_le_bytes(), r));
+        let withdraw_root_prev_rlc = challenges
+            .evm_word()
+            .map(|r| rlc::value(&block.prev_withdraw_root.to_le_bytes(), r));
 
         Ok(EvmCircuitExports {
             withdraw_root: (final_withdraw_root_cell, withdraw_root_rlc.into()),
+            prev_withdraw_root: (final_withdraw_root_prev_cell, withdraw_root_prev_rlc.into()),
         })
     }
 
@@ -1258,7 +1339,7 @@ impl<F: Field> ExecutionConfig<F> {
     #[allow(clippy::too_many_arguments)]
     fn assign_same_exec_step_in_range(
         &self,
-        region: &mut Region<'_, F>,
+        region: &mut CachedRegion<'_, '_, F>,
         offset_begin: usize,
         offset_end: usize,
         block: &Block<F>,
@@ -1266,7 +1347,6 @@ impl<F: Field> ExecutionConfig<F> {
         call: &Call,
         step: &ExecStep,
         height: usize,
-        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         if offset_end <= offset_begin {
             return Ok(());
@@ -1276,13 +1356,7 @@ impl<F: Field> ExecutionConfig<F> {
         assert!(matches!(step.execution_state, ExecutionState::EndBlock));
 
         // Disable access to next step deliberately for "repeatable" step
-        let region = &mut CachedRegion::<'_, '_, F>::new(
-            region,
-            challenges,
-            self.advices.to_vec(),
-            1,
-            offset_begin,
-        );
+        region.reset(offset_begin, 1);
         self.assign_exec_step_int(region, offset_begin, block, transaction, call, step, false)?;
 
         region.replicate_assignment_for_range(
@@ -1297,7 +1371,7 @@ impl<F: Field> ExecutionConfig<F> {
     #[allow(clippy::too_many_arguments)]
     fn assign_exec_step(
         &self,
-        region: &mut Region<'_, F>,
+        region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         block: &Block<F>,
         transaction: &Transaction,
@@ -1305,18 +1379,11 @@ impl<F: Field> ExecutionConfig<F> {
         step: &ExecStep,
         height: usize,
         next: Option<(&Transaction, &Call, &ExecStep)>,
-        challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         // Make the region large enough for the current step and the next step.
         // The next step's next step may also be accessed, so make the region large
         // enough for 3 steps.
-        let region = &mut CachedRegion::<'_, '_, F>::new(
-            region,
-            challenges,
-            self.advices.to_vec(),
-            MAX_STEP_HEIGHT * 3,
-            offset,
-        );
+        region.reset(offset, MAX_STEP_HEIGHT * 3);
 
         // Also set the witness of the next step.
         // These may be used in stored expressions and
@@ -1373,6 +1440,7 @@ impl<F: Field> ExecutionConfig<F> {
         match step.execution_state {
             // internal states
             ExecutionState::BeginTx => assign_exec_step!(self.begin_tx_gadget),
+            ExecutionState::DestructAccount => assign_exec_step!(self.destruct_account_gadget),
             ExecutionState::EndTx => assign_exec_step!(self.end_tx_gadget),
             ExecutionState::EndInnerBlock => assign_exec_step!(self.end_inner_block_gadget),
             ExecutionState::EndBlock => assign_exec_step!(self.end_block_gadget),
@@ -1382,6 +1450,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ADDRESS => assign_exec_step!(self.address_gadget),
             ExecutionState::BALANCE => assign_exec_step!(self.balance_gadget),
             ExecutionState::BITWISE => assign_exec_step!(self.bitwise_gadget),
+            ExecutionState::BLOBBASEFEE => assign_exec_step!(self.blobbasefee_gadget),
+            ExecutionState::BLOBHASH => assign_exec_step!(self.blobhash_gadget),
             ExecutionState::BYTE => assign_exec_step!(self.byte_gadget),
             ExecutionState::CALL_OP => assign_exec_step!(self.call_op_gadget),
             ExecutionState::CALLDATACOPY => assign_exec_step!(self.calldatacopy_gadget),
@@ -1404,6 +1474,7 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::JUMPDEST => assign_exec_step!(self.jumpdest_gadget),
             ExecutionState::JUMPI => assign_exec_step!(self.jumpi_gadget),
             ExecutionState::LOG => assign_exec_step!(self.log_gadget),
+            ExecutionState::MCOPY => assign_exec_step!(self.mcopy_gadget),
             ExecutionState::MEMORY => assign_exec_step!(self.memory_gadget),
             ExecutionState::MSIZE => assign_exec_step!(self.msize_gadget),
             ExecutionState::MUL_DIV_MOD => assign_exec_step!(self.mul_div_mod_gadget),
@@ -1426,12 +1497,12 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::SELFBALANCE => assign_exec_step!(self.selfbalance_gadget),
             ExecutionState::CREATE => assign_exec_step!(self.create_gadget),
             ExecutionState::CREATE2 => assign_exec_step!(self.create2_gadget),
-            // dummy gadgets
-            ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
             ExecutionState::SELFDESTRUCT => {
                 #[cfg(not(feature = "scroll"))]
                 assign_exec_step!(self.selfdestruct_gadget)
             }
+            // dummy gadgets
+            ExecutionState::EXTCODECOPY => assign_exec_step!(self.extcodecopy_gadget),
             // end of dummy gadgets
             ExecutionState::SHA3 => assign_exec_step!(self.sha3_gadget),
             ExecutionState::SHL_SHR => assign_exec_step!(self.shl_shr_gadget),
@@ -1441,8 +1512,8 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::STOP => assign_exec_step!(self.stop_gadget),
             ExecutionState::SWAP => assign_exec_step!(self.swap_gadget),
             // dummy errors
-            ExecutionState::ErrorOutOfGasStaticMemoryExpansion => {
-                assign_exec_step!(self.error_oog_static_memory_gadget)
+            ExecutionState::ErrorOutOfGasMemoryExpansion => {
+                assign_exec_step!(self.error_oog_memory_expansion)
             }
             ExecutionState::ErrorOutOfGasConstant => {
                 assign_exec_step!(self.error_oog_constant)
@@ -1450,9 +1521,6 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::ErrorOutOfGasCall => {
                 assign_exec_step!(self.error_oog_call)
             }
-            ExecutionState::ErrorOutOfGasDynamicMemoryExpansion => {
-                assign_exec_step!(self.error_oog_dynamic_memory_gadget)
-            }
             ExecutionState::ErrorOutOfGasLOG => {
                 assign_exec_step!(self.error_oog_log)
             }
@@ -1529,11 +1597,19 @@ impl<F: Field> ExecutionConfig<F> {
             ExecutionState::PrecompileBlake2f => {
                 assign_exec_step!(self.precompile_blake2f_gadget)
             }
+            ExecutionState::PrecompilePointEvaluation => {
+                assign_exec_step!(self.precompile_point_evaluation_gadget)
+            }
         }
 
         // Fill in the witness values for stored expressions
         let assigned_stored_expressions = self.assign_stored_expressions(region, offset, step)?;
 
+        #[cfg(any(feature = "test", test))]
+        if verbose {
+            Self::record_rw_lookups(&assigned_stored_expressions, step, region.challenges());
+        }
+
         // enable with `CHECK_RW_LOOKUP=true`
         if *CHECK_RW_LOOKUP && verbose {
             let is_padding_step = matches!(step.execution_state, ExecutionState::EndBlock)
@@ -1575,6 +1651,51 @@ impl<F: Field> ExecutionConfig<F> {
         Ok(assigned_stored_expressions)
     }
 
+    /// Non-zero rw lookups of the assigned stored expressions, without the
+    /// repeated ones.
+    fn rw_lookup_values(assigned_stored_expressions: &[(String, F)]) -> Vec<(String, F)> {
+        let mut assigned_rw_values = Vec::new();
+        for (name, v) in assigned_stored_expressions {
+            if name.starts_with("rw lookup ")
+                && !v.is_zero_vartime()
+                && !assigned_rw_values.contains(&(name.clone(), *v))
+            {
+                assigned_rw_values.push((name.clone(), *v));
+            }
+        }
+        assigned_rw_values
+    }
+
+    /// Record the rw lookups of `step` for [`assigned_rw_lookups`].
+    #[cfg(any(feature = "test", test))]
+    fn record_rw_lookups(
+        assigned_stored_expressions: &[(String, F)],
+        step: &ExecStep,
+        challenges: &Challenges<Value<F>>,
+    ) {
+        let mut evm_randomness = F::zero();
+        challenges.evm_word().map(|v| evm_randomness = v);
+        let mut lookup_randomness = F::zero();
+        challenges.lookup_input().map(|v| lookup_randomness = v);
+        if evm_randomness.is_zero_vartime() || lookup_randomness.is_zero_vartime() {
+            // challenges not ready
+            return;
+        }
+        let lookups = AssignedRwLookups {
+            evm_randomness,
+            lookup_randomness,
+            values: Self::rw_lookup_values(assigned_stored_expressions)
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect(),
+        };
+        ASSIGNED_RW_LOOKUPS.with(|rw_lookups| {
+            rw_lookups
+                .borrow_mut()
+                .insert((step.rw_counter, step.execution_state), Box::new(lookups))
+        });
+    }
+
     fn check_rw_lookup(
         assigned_stored_expressions: &[(String, F)],
         offset: usize,
@@ -1592,15 +1713,7 @@ impl<F: Field> ExecutionConfig<F> {
             // challenges not ready
             return;
         }
-        let mut assigned_rw_values = Vec::new();
-        for (name, v) in assigned_stored_expressions {
-            if name.starts_with("rw lookup ")
-                && !v.is_zero_vartime()
-                && !assigned_rw_values.contains(&(name.clone(), *v))
-            {
-                assigned_rw_values.push((name.clone(), *v));
-            }
-        }
+        let assigned_rw_values = Self::rw_lookup_values(assigned_stored_expressions);
 
         let rlc_assignments: BTreeSet<_> = step
             .rw_indices
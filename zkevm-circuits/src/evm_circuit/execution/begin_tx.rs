@@ -5,7 +5,7 @@ use crate::{
         step::ExecutionState,
         util::{
             and,
-            common_gadget::{TransferWithGasFeeGadget, TxL1FeeGadget},
+            common_gadget::{IsCurieGadget, TransferWithGasFeeGadget, TxL1FeeGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
@@ -23,7 +23,10 @@ use crate::{
         AccountFieldTag, BlockContextFieldTag, CallContextFieldTag, TxFieldTag as TxContextFieldTag,
     },
 };
-use bus_mapping::circuit_input_builder::CopyDataType;
+use bus_mapping::{
+    circuit_input_builder::{CopyDataType, L1FeeHardfork},
+    precompile::PRECOMPILE_COUNT,
+};
 use eth_types::{Address, Field, ToLittleEndian, ToScalar, U256};
 use ethers_core::utils::{get_contract_address, keccak256, rlp::RlpStream};
 use gadgets::util::{expr_from_bytes, not, or, Expr};
@@ -35,14 +38,13 @@ const SHANGHAI_RW_DELTA: u8 = 1;
 #[cfg(not(feature = "shanghai"))]
 const SHANGHAI_RW_DELTA: u8 = 0;
 
-const PRECOMPILE_COUNT: usize = 9;
-
 use gadgets::util::select;
 
 #[derive(Clone, Debug)]
 pub(crate) struct BeginTxGadget<F> {
     tx_id: Cell<F>,
     tx_nonce: Cell<F>,
+    tx_nonce_in_range: LtGadget<F, N_BYTES_U64>,
     tx_gas: Cell<F>,
     tx_gas_price: Word<F>,
     mul_gas_fee_by_gas: MulWordByU64Gadget<F>,
@@ -85,6 +87,9 @@ pub(crate) struct BeginTxGadget<F> {
     // coinbase, and may be duplicate.
     // <https://github.com/ethereum/go-ethereum/blob/604e215d1bb070dff98fb76aa965064c74e3633f/core/state/statedb.go#LL1119C9-L1119C9>
     is_coinbase_warm: Cell<F>,
+    // The length of the rlp-encoded bytes of signed tx
+    tx_len: Cell<F>,
+    is_curie: IsCurieGadget<F>,
     tx_l1_fee: TxL1FeeGadget<F>,
 }
 
@@ -112,7 +117,15 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             ]
             .map(|field_tag| cb.tx_context(tx_id.expr(), field_tag, None));
 
-        let tx_l1_fee = TxL1FeeGadget::construct(cb, tx_id.expr(), tx_data_gas_cost.expr());
+        let tx_len = cb.tx_context(tx_id.expr(), TxContextFieldTag::TxHashLength, None);
+        let is_curie = IsCurieGadget::construct(cb, tx_id.expr());
+        let tx_l1_fee = TxL1FeeGadget::construct(
+            cb,
+            tx_id.expr(),
+            is_curie.expr(),
+            tx_data_gas_cost.expr(),
+            tx_len.expr(),
+        );
 
         cb.call_context_lookup(
             1.expr(),
@@ -170,6 +183,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             None,
         ); // rwc_delta += 1
 
+        // The nonce can't overflow, since a tx with nonce 2^64 - 1 is invalid
+        // (EIP-2681).
+        let tx_nonce_in_range = LtGadget::construct(cb, tx_nonce.expr(), u64::MAX.expr());
+        cb.require_equal("tx_nonce < 2^64 - 1", tx_nonce_in_range.expr(), 1.expr());
+
         // TODO: Implement EIP 1559 (currently it only supports legacy
         // transaction format)
         // Calculate transaction gas fee
@@ -184,8 +202,9 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             from_bytes::expr(&tx_fee.cells[..16]),
         );
 
-        // a valid precompile address is: 1 <= addr <= 9 (addr != 0 && addr < 0xA)
-        let is_precompile_lt = LtGadget::construct(cb, tx_callee_address.expr(), 0xA.expr());
+        // a valid precompile address is: 1 <= addr <= PRECOMPILE_COUNT
+        let is_precompile_lt =
+            LtGadget::construct(cb, tx_callee_address.expr(), (PRECOMPILE_COUNT + 1).expr());
         let is_precompile = and::expr([
             not::expr(tx_callee_address_is_zero.expr()),
             is_precompile_lt.expr(),
@@ -381,6 +400,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 0.expr(),
                 Some(&mut reversion_info),
             );
+            cb.account_created_write(
+                tx_id.expr(),
+                call_callee_address.expr(),
+                0.expr(),
+                Some(&mut reversion_info),
+            );
             for (field_tag, value) in [
                 (CallContextFieldTag::Depth, 1.expr()),
                 (CallContextFieldTag::CallerAddress, tx_caller_address.expr()),
@@ -409,7 +434,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             }
 
             cb.require_step_state_transition(StepStateTransition {
-                // 21 + a reads and writes:
+                // 22 + a reads and writes:
                 //   - a TxL1FeeGadget
                 //   - Write CallContext TxId
                 //   - Write CallContext RwCounterEndOfReversion
@@ -423,6 +448,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Read Account CodeHash
                 //   - a TransferWithGasFeeGadget
                 //   - Write Account (Callee) Nonce (Reversible)
+                //   - Write TxCreatedAccount (Callee) (Reversible)
                 //   - Write CallContext Depth
                 //   - Write CallContext CallerAddress
                 //   - Write CallContext CalleeAddress
@@ -437,7 +463,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 //   - Write CallContext IsCreate
                 //   - Write CallContext CodeHash
                 rw_counter: Delta(
-                    22.expr()
+                    23.expr()
                         + tx_l1_fee.rw_delta()
                         + transfer_with_gas_fee.rw_delta()
                         + SHANGHAI_RW_DELTA.expr()
@@ -448,10 +474,11 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
                 is_create: To(tx_is_create.expr()),
                 code_hash: To(cb.curr.state.code_hash.expr()),
                 gas_left: To(gas_left.clone()),
-                // There are a + 1 reversible writes:
+                // There are a + 2 reversible writes:
                 //  - a TransferWithGasFeeGadget
                 //  - Callee Account Nonce
-                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 1.expr()),
+                //  - Callee TxCreatedAccount
+                reversible_write_counter: To(transfer_with_gas_fee.reversible_w_delta() + 2.expr()),
                 log_id: To(0.expr()),
                 ..StepStateTransition::new_context()
             });
@@ -639,6 +666,7 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         Self {
             tx_id,
             tx_nonce,
+            tx_nonce_in_range,
             tx_gas,
             tx_gas_price,
             mul_gas_fee_by_gas,
@@ -674,6 +702,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             is_caller_callee_equal,
             coinbase,
             is_coinbase_warm,
+            tx_len,
+            is_curie,
             tx_l1_fee,
         }
     }
@@ -690,7 +720,13 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         let zero = eth_types::Word::zero();
 
         let mut rws = StepRws::new(block, step);
-        rws.offset_add(10 + PRECOMPILE_COUNT);
+        // the params of the L1 fee formula since Curie are 3 more reads
+        let curie_l1_fee_reads = if tx.l1_fee.hardfork == L1FeeHardfork::Curie {
+            3
+        } else {
+            0
+        };
+        rws.offset_add(10 + PRECOMPILE_COUNT as usize + curie_l1_fee_reads);
 
         #[cfg(feature = "shanghai")]
         let is_coinbase_warm = rws.next().tx_access_list_value_pair().1;
@@ -729,6 +765,8 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
         self.tx_nonce
             .assign(region, offset, Value::known(F::from(tx.nonce)))?;
+        self.tx_nonce_in_range
+            .assign(region, offset, F::from(tx.nonce), F::from(u64::MAX))?;
         self.tx_gas
             .assign(region, offset, Value::known(F::from(tx.gas)))?;
         self.tx_gas_price
@@ -759,8 +797,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             .assign(region, offset, Value::known(callee_address))?;
         self.tx_callee_address_is_zero
             .assign(region, offset, callee_address)?;
-        self.is_precompile_lt
-            .assign(region, offset, callee_address, F::from(0xA))?;
+        self.is_precompile_lt.assign(
+            region,
+            offset,
+            callee_address,
+            F::from(PRECOMPILE_COUNT + 1),
+        )?;
         self.call_callee_address.assign(
             region,
             offset,
@@ -917,7 +959,12 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
         self.is_coinbase_warm
             .assign(region, offset, Value::known(F::from(is_coinbase_warm)))?;
 
-        let tx_l1_fee = tx.l1_fee.tx_l1_fee(tx.tx_data_gas_cost).0;
+        let tx_l1_fee = tx
+            .l1_fee
+            .hardfork
+            .oracle()
+            .tx_l1_fee(&tx.l1_fee, &tx.rlp_signed)
+            .0;
         let tx_l2_fee = tx.gas_price * tx.gas;
         if tx_fee != tx_l2_fee + tx_l1_fee {
             log::error!(
@@ -928,12 +975,19 @@ impl<F: Field> ExecutionGadget<F> for BeginTxGadget<F> {
             );
         }
 
+        self.tx_len.assign(
+            region,
+            offset,
+            Value::known(F::from(tx.rlp_signed.len() as u64)),
+        )?;
+        self.is_curie
+            .assign(region, offset, tx.block_number, &block.l1_fee_config)?;
         self.tx_l1_fee.assign(
             region,
             offset,
             tx.l1_fee,
             tx.l1_fee_committed,
-            tx.tx_data_gas_cost,
+            &tx.rlp_signed,
         )
     }
 }
@@ -1269,4 +1323,104 @@ mod test {
 
         CircuitTestBuilder::new_from_test_ctx(ctx).run();
     }
+
+    #[test]
+    fn begin_tx_curie() {
+        use crate::{table::RwTableTag, witness::block_convert};
+        use bus_mapping::{
+            circuit_input_builder::{L1FeeConfig, L1FeeHardfork},
+            mock::BlockData,
+        };
+        use eth_types::geth_types::GethData;
+        use halo2_proofs::halo2curves::bn256::Fr;
+        use mock::test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0};
+
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code_with_return()),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.l1_fee_config = L1FeeConfig {
+            curie_block: Some(0xcafe),
+        };
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+        // the params of the formula since Curie are read after the ones before
+        let tx = &block.txs[0];
+        assert_eq!(tx.l1_fee.hardfork, L1FeeHardfork::Curie);
+        let storage_reads = tx.steps[0]
+            .rw_indices
+            .iter()
+            .filter(|(tag, _)| *tag == RwTableTag::AccountStorage)
+            .count();
+        assert_eq!(storage_reads, 6);
+
+        CircuitTestBuilder::new_from_block(block).run();
+    }
+
+    #[test]
+    fn begin_tx_system_call() {
+        use crate::witness::block_convert;
+        use bus_mapping::{
+            circuit_input_builder::{ChainSpec, SystemCall},
+            mock::BlockData,
+        };
+        use eth_types::{
+            geth_types::{GethData, TxType},
+            U64,
+        };
+        use halo2_proofs::halo2curves::bn256::Fr;
+        use mock::MOCK_WALLETS;
+
+        let mut block: GethData = TestContext::<3, 2>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).code(code_with_return());
+                accs[1].address(MOCK_ACCOUNTS[4]);
+                accs[2].address(MOCK_WALLETS[0].address()).balance(eth(10));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[1].address)
+                    .to(accs[0].address)
+                    .gas(Word::from(100_000))
+                    .gas_price(Word::zero());
+                txs[1].from(MOCK_WALLETS[0].clone()).to(accs[0].address);
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+
+        // the first tx is the pseudo-tx of a pre-block system call
+        let system_call = SystemCall {
+            from: MOCK_ACCOUNTS[4],
+            to: MOCK_ACCOUNTS[0],
+            input: Bytes::default(),
+            gas: 100_000,
+        };
+        block.eth_block.transactions[0] = eth_types::Transaction {
+            transaction_index: Some(U64::zero()),
+            ..system_call.to_eth_tx(&block.eth_block, Word::zero())
+        };
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            pre_block_calls: vec![system_call],
+            ..Default::default()
+        };
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+        assert_eq!(block.txs[0].tx_type, TxType::SystemCall);
+
+        CircuitTestBuilder::new_from_block(block).run();
+    }
 }
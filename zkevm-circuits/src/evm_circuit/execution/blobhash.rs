@@ -0,0 +1,178 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_U64,
+        step::ExecutionState,
+        util::{
+            common_gadget::{SameContextGadget, WordByteCapGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::Delta,
+            },
+            not, CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{CallContextFieldTag, TxContextFieldTag},
+    util::Expr,
+};
+use bus_mapping::evm::OpcodeId;
+use eth_types::Field;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for BLOBHASH, which pushes the versioned hash at `index` of the
+/// blobs of the tx from the tx table, or zero if `index` is out of range.
+#[derive(Clone, Debug)]
+pub(crate) struct BlobHashGadget<F> {
+    same_context: SameContextGadget<F>,
+    index: WordByteCapGadget<F, N_BYTES_U64>,
+    tx_id: Cell<F>,
+    blob_hashes_len: Cell<F>,
+    blob_hash: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for BlobHashGadget<F> {
+    const NAME: &'static str = "BLOBHASH";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::BLOBHASH;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let tx_id = cb.query_cell();
+        let blob_hashes_len = cb.tx_context(
+            tx_id.expr(),
+            TxContextFieldTag::BlobVersionedHashesLen,
+            None,
+        );
+
+        // index < len, where an index which overflows u64 is out of range
+        let index = WordByteCapGadget::construct(cb, blob_hashes_len.expr());
+        cb.stack_pop(index.original_word());
+
+        // Lookup in call_ctx the TxId of the blobs
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::TxId, tx_id.expr());
+
+        let blob_hash = cb.query_cell_phase2();
+        cb.condition(index.lt_cap(), |cb| {
+            cb.tx_context_lookup(
+                tx_id.expr(),
+                TxContextFieldTag::BlobVersionedHash,
+                Some(index.valid_value()),
+                blob_hash.expr(),
+            );
+        });
+        cb.condition(not::expr(index.lt_cap()), |cb| {
+            cb.require_zero(
+                "blob hash is zero if index is out of range",
+                blob_hash.expr(),
+            );
+        });
+        cb.stack_push(blob_hash.expr());
+
+        // State transition
+        let opcode = cb.query_cell();
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(3.expr()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(0.expr()),
+            gas_left: Delta(-OpcodeId::BLOBHASH.constant_gas_cost().expr()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            index,
+            tx_id,
+            blob_hashes_len,
+            blob_hash,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let blob_hashes_len = F::from(tx.blob_versioned_hashes.len() as u64);
+        let index = block.rws[step.rw_indices[0]].stack_value();
+        let blob_hash = block.rws[step.rw_indices[2]].stack_value();
+        self.index.assign(region, offset, index, blob_hashes_len)?;
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.blob_hashes_len
+            .assign(region, offset, Value::known(blob_hashes_len))?;
+        self.blob_hash
+            .assign(region, offset, region.word_rlc(blob_hash))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod test {
+    use crate::{
+        state_circuit::RwTableView,
+        table::{CallContextFieldTag, RwTableTag},
+        test_util::CircuitTestBuilder,
+    };
+    use bus_mapping::evm::OpcodeId;
+    use eth_types::{bytecode, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::test_ctx::TestContext;
+
+    fn test_ok(index: Word) {
+        let bytecode = bytecode! {
+            PUSH32(index)
+            BLOBHASH
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .block_checks(Box::new(move |block| {
+            let step = block.txs[0]
+                .steps
+                .iter()
+                .find(|step| step.opcode == Some(OpcodeId::BLOBHASH))
+                .unwrap();
+            let rws = RwTableView::from_step(block, step);
+            assert_eq!(
+                rws.tags(),
+                [
+                    RwTableTag::Stack,
+                    RwTableTag::CallContext,
+                    RwTableTag::Stack
+                ]
+            );
+            assert_eq!(
+                rws.call_context().collect::<Vec<_>>(),
+                [(CallContextFieldTag::TxId, Word::one())]
+            );
+            // the mock tx carries no blobs
+            assert_eq!(
+                rws.stack().collect::<Vec<_>>(),
+                [(false, 1023, index), (true, 1023, Word::zero())]
+            );
+            rws.assert_lookups_of::<Fr>(step);
+        }))
+        .run();
+    }
+
+    #[test]
+    fn blobhash_gadget_simple() {
+        test_ok(Word::zero());
+        test_ok(Word::from(5));
+    }
+
+    #[test]
+    fn blobhash_gadget_overflow() {
+        test_ok(Word::MAX);
+    }
+}
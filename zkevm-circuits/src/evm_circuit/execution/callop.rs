@@ -1,18 +1,16 @@
 use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_U64},
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_U64},
         step::ExecutionState,
         util::{
             and,
-            common_gadget::{CommonCallGadget, TransferGadget},
+            common_gadget::{CallOpcodeGadget, CommonCallGadget, Eip150GasGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
-            math_gadget::{
-                ConstantDivisionGadget, IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget,
-            },
+            math_gadget::{IsZeroGadget, LtGadget, LtWordGadget, MinMaxGadget},
             memory_gadget::{CommonMemoryAddressGadget, MemoryAddressGadget},
             not, or,
             precompile_gadget::PrecompileGadget,
@@ -26,7 +24,7 @@ use crate::{
 use bus_mapping::{
     circuit_input_builder::CopyDataType,
     evm::OpcodeId,
-    precompile::{is_precompiled, PrecompileCalls},
+    precompile::{is_precompiled, PrecompileCalls, PRECOMPILE_COUNT},
 };
 use eth_types::{
     evm_types::{memory::MemoryWordRange, GAS_STIPEND_CALL_WITH_VALUE},
@@ -42,11 +40,7 @@ use std::cmp::min;
 #[derive(Clone, Debug)]
 
 pub(crate) struct CallOpGadget<F> {
-    opcode: Cell<F>,
-    is_call: IsZeroGadget<F>,
-    is_callcode: IsZeroGadget<F>,
-    is_delegatecall: IsZeroGadget<F>,
-    is_staticcall: IsZeroGadget<F>,
+    opcode: CallOpcodeGadget<F>,
     tx_id: Cell<F>,
     reversion_info: ReversionInfo<F>,
     current_callee_address: Cell<F>,
@@ -64,8 +58,7 @@ pub(crate) struct CallOpGadget<F> {
     // check if insufficient balance case
     is_insufficient_balance: LtWordGadget<F>,
     is_depth_ok: LtGadget<F, N_BYTES_U64>,
-    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
-    capped_callee_gas_left: MinMaxGadget<F, N_BYTES_GAS>,
+    eip150_gas: Eip150GasGadget<F>,
     // FIXME: free cells, only used in empty codehash (empty account and precompiles)
     step_gas_cost: Cell<F>,
     // to handle precompile calls
@@ -90,15 +83,9 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::CALL_OP;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
-        let opcode = cb.query_cell();
-        cb.opcode_lookup(opcode.expr(), 1.expr());
-        let is_call = IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::CALL.expr());
-        let is_callcode =
-            IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::CALLCODE.expr());
-        let is_delegatecall =
-            IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::DELEGATECALL.expr());
-        let is_staticcall =
-            IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::STATICCALL.expr());
+        let opcode = CallOpcodeGadget::construct(cb);
+        cb.opcode_lookup(opcode.opcode.expr(), 1.expr());
+        let [is_call, is_callcode, is_delegatecall, is_staticcall] = opcode.flags();
 
         // Use rw_counter of the step which triggers next call as its call_id.
         let callee_call_id = cb.curr.state.rw_counter.clone();
@@ -120,13 +107,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
 
         let call_gadget: CommonCallGadget<F, MemoryAddressGadget<F>, true> =
-            CommonCallGadget::construct(
-                cb,
-                is_call.expr(),
-                is_callcode.expr(),
-                is_delegatecall.expr(),
-                is_staticcall.expr(),
-            );
+            CommonCallGadget::construct(cb, &opcode);
         cb.condition(not::expr(is_call.expr() + is_callcode.expr()), |cb| {
             cb.require_zero(
                 "for non call/call code, value is zero",
@@ -204,11 +185,14 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         });
 
         // whether the call is to a precompiled contract.
-        // precompile contracts are stored from address 0x01 to 0x09.
+        // precompile contracts are stored from address 0x01 to PRECOMPILE_COUNT.
         let is_code_address_zero =
             IsZeroGadget::construct(cb, "", call_gadget.callee_address_expr());
-        let is_precompile_lt =
-            LtGadget::construct(cb, call_gadget.callee_address_expr(), 0x0A.expr());
+        let is_precompile_lt = LtGadget::construct(
+            cb,
+            call_gadget.callee_address_expr(),
+            (PRECOMPILE_COUNT + 1).expr(),
+        );
         let is_precompile = and::expr([
             not::expr(is_code_address_zero.expr()),
             is_precompile_lt.expr(),
@@ -267,15 +251,13 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let gas_cost = call_gadget.gas_cost_expr(is_warm_prev.expr(), is_call.expr());
         // Apply EIP 150
         let gas_available = cb.curr.state.gas_left.expr() - gas_cost.clone();
-        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
-        let all_but_one_64th_gas = gas_available - one_64th_gas.quotient();
-        let capped_callee_gas_left =
-            MinMaxGadget::construct(cb, call_gadget.gas_expr(), all_but_one_64th_gas.clone());
-        let callee_gas_left = select::expr(
+        let eip150_gas = Eip150GasGadget::construct_capped(
+            cb,
+            gas_available,
+            call_gadget.gas_expr(),
             call_gadget.gas_is_u64.expr(),
-            capped_callee_gas_left.min(),
-            all_but_one_64th_gas,
         );
+        let callee_gas_left = eip150_gas.callee_gas_left();
 
         let stack_pointer_delta =
             select::expr(is_call.expr() + is_callcode.expr(), 6.expr(), 5.expr());
@@ -691,10 +673,6 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
 
         Self {
             opcode,
-            is_call,
-            is_callcode,
-            is_delegatecall,
-            is_staticcall,
             tx_id,
             reversion_info,
             current_callee_address,
@@ -710,8 +688,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
             caller_balance_word,
             is_insufficient_balance,
             is_depth_ok,
-            one_64th_gas,
-            capped_callee_gas_left,
+            eip150_gas,
             step_gas_cost,
             // precompile related fields.
             is_code_address_zero,
@@ -833,28 +810,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
                 ((U256::zero(), U256::zero()), (U256::zero(), U256::zero()))
             };
 
-        self.opcode
-            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
-        self.is_call.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
-        )?;
-        self.is_callcode.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALLCODE.as_u64()),
-        )?;
-        self.is_delegatecall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::DELEGATECALL.as_u64()),
-        )?;
-        self.is_staticcall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::STATICCALL.as_u64()),
-        )?;
+        self.opcode.assign(region, offset, opcode)?;
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
         self.reversion_info.assign(
@@ -934,14 +890,7 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let gas_available = step.gas_left - gas_cost;
         self.step_gas_cost
             .assign(region, offset, Value::known(F::from(step.gas_cost)))?;
-        self.one_64th_gas
-            .assign(region, offset, gas_available.into())?;
-        self.capped_callee_gas_left.assign(
-            region,
-            offset,
-            F::from(gas.low_u64()),
-            F::from(gas_available - gas_available / 64),
-        )?;
+        self.eip150_gas.assign(region, offset, gas_available, gas)?;
 
         // precompile related assignment.
         let (is_precompile_call, precompile_addr) = {
@@ -952,8 +901,12 @@ impl<F: Field> ExecutionGadget<F> for CallOpGadget<F> {
         let code_address: F = callee_address.to_address().to_scalar().unwrap();
         self.is_code_address_zero
             .assign(region, offset, code_address)?;
-        self.is_precompile_lt
-            .assign(region, offset, code_address, 0x0Au64.into())?;
+        self.is_precompile_lt.assign(
+            region,
+            offset,
+            code_address,
+            (PRECOMPILE_COUNT + 1).into(),
+        )?;
         let precompile_return_length = if is_precompile_call {
             let value_rw = block.rws[step.rw_indices[32 + rw_offset]];
             assert_eq!(
@@ -2,18 +2,19 @@ use crate::{
     evm_circuit::{
         execution::ExecutionGadget,
         param::{
-            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE,
-            N_BYTES_U64, N_BYTES_WORD,
+            N_BYTES_ACCOUNT_ADDRESS, N_BYTES_MEMORY_ADDRESS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64,
+            N_BYTES_WORD,
         },
         step::ExecutionState,
         util::{
-            common_gadget::{get_copy_bytes, TransferGadget},
+            common_gadget::{get_copy_bytes, Eip150GasGadget, TransferGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::{Delta, To},
             },
             math_gadget::{
-                ConstantDivisionGadget, ContractCreateGadget, IsZeroGadget, LtGadget, LtWordGadget,
+                ConstantDivisionGadget, ContractCreateGadget, IsEqualGadget, IsZeroGadget,
+                LtGadget, LtWordGadget,
             },
             memory_gadget::{
                 CommonMemoryAddressGadget, MemoryAddressGadget, MemoryExpansionGadget,
@@ -27,8 +28,8 @@ use crate::{
 };
 use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId, state_db::CodeDB};
 use eth_types::{
-    evm_types::{GasCost, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD, MAX_INIT_CODE_SIZE},
-    Field, ToBigEndian, ToLittleEndian, ToScalar, U256,
+    evm_types::{GasCost, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD},
+    Field, ToBigEndian, ToLittleEndian, ToScalar, ToWord, U256,
 };
 use ethers_core::utils::keccak256;
 use gadgets::util::{and, expr_from_bytes};
@@ -51,13 +52,14 @@ pub(crate) struct CreateGadget<F, const IS_CREATE2: bool, const S: ExecutionStat
     transfer: TransferGadget<F>,
     init_code: MemoryAddressGadget<F>,
     init_code_word_size: ConstantDivisionGadget<F, N_BYTES_MEMORY_ADDRESS>,
-    // Init code size must be less than or equal to 49152
-    // (maximum init code size) if Shanghai, otherwise should be less than or
-    // equal to 0x1FFFFFFFE0 (maximum value of offset + size).
+    // Init code size must be less than or equal to the maximum init code size
+    // of the chain if Shanghai, otherwise should be less than or equal to
+    // 0x1FFFFFFFE0 (maximum value of offset + size).
+    max_init_code_size: Cell<F>,
     init_code_size_not_overflow: LtGadget<F, { N_BYTES_MEMORY_ADDRESS }>,
     init_code_rlc: Cell<F>,
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
-    gas_left: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    eip150_gas: Eip150GasGadget<F>,
     create: ContractCreateGadget<F, IS_CREATE2>,
     caller_balance: Word<F>,
     is_depth_in_range: LtGadget<F, N_BYTES_U64>,
@@ -65,10 +67,16 @@ pub(crate) struct CreateGadget<F, const IS_CREATE2: bool, const S: ExecutionStat
     is_nonce_in_range: LtGadget<F, N_BYTES_U64>,
     keccak_code_hash: Cell<F>,
     keccak_output: Word<F>,
-    // prevous code hash befor creating
+    // previous code hash of the new address, zero if the account doesn't exist
     code_hash_previous: Cell<F>,
-    // if code_hash_previous is zero, then no collision
-    not_address_collision: IsZeroGadget<F>,
+    callee_not_exists: IsZeroGadget<F>,
+    callee_is_empty_code_hash: IsEqualGadget<F>,
+    // previous nonce of the new address, read only if the account exists
+    callee_nonce: Cell<F>,
+    callee_nonce_is_zero: IsZeroGadget<F>,
+    // whether the new address was already created in the tx, which doesn't
+    // happen as a self destructed account is only deleted at the end of the tx
+    callee_was_created: Cell<F>,
     copy_rwc_inc: Cell<F>,
 }
 
@@ -109,8 +117,10 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
         let init_code_length = cb.query_word_rlc();
         let init_code =
             MemoryAddressGadget::construct(cb, init_code_memory_offset, init_code_length);
+        let max_init_code_size = cb.query_cell();
+        cb.max_init_code_size_lookup(max_init_code_size.expr());
         let init_code_size_not_overflow =
-            LtGadget::construct(cb, init_code.length(), MAX_INIT_CODE_SIZE.expr() + 1.expr());
+            LtGadget::construct(cb, init_code.length(), max_init_code_size.expr() + 1.expr());
 
         // Init code size overflow is checked before ErrDepth, ErrInsufficientBalance,
         // ErrNonceUintOverflow and ErrContractAddressCollision.
@@ -282,32 +292,45 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             code_hash_previous.expr(),
         );
 
-        let not_address_collision = IsZeroGadget::construct(cb, "", code_hash_previous.expr());
-        /*
-        // CREATE2 may cause address collision error. And for a tricky
-        // case of CREATE, it could also cause this error. e.g. the `to`
-        // field of transaction is set to the calculated contract
-        // address (reference testool case
-        // `RevertDepthCreateAddressCollision_d0_g0_v0` for details).
-        cb.condition(not::expr(not_address_collision.expr()), |cb| {
-            cb.require_equal(
-                "op code is create2 for address collision",
-                opcode.expr(),
-                OpcodeId::CREATE2.expr(),
+        let callee_not_exists = IsZeroGadget::construct(cb, "", code_hash_previous.expr());
+        let callee_is_empty_code_hash =
+            IsEqualGadget::construct(cb, code_hash_previous.expr(), cb.empty_code_hash_rlc());
+        let callee_nonce = cb.query_cell();
+        cb.condition(not::expr(callee_not_exists.expr()), |cb| {
+            cb.account_read(
+                new_address.clone(),
+                AccountFieldTag::Nonce,
+                callee_nonce.expr(),
             );
         });
-        */
-
-        // conditional transfer for address collision case
+        cb.condition(callee_not_exists.expr(), |cb| {
+            cb.require_zero(
+                "callee nonce is zero if callee doesn't exist",
+                callee_nonce.expr(),
+            );
+        });
+        let callee_nonce_is_zero = IsZeroGadget::construct(cb, "", callee_nonce.expr());
+
+        // ErrContractAddressCollision occurs if the account at the new address
+        // has a non-zero nonce or non-empty code (EIP-684). CREATE2 may cause
+        // it, and for a tricky case CREATE could too, e.g. the `to` field of
+        // transaction is set to the calculated contract address (reference
+        // testool case `RevertDepthCreateAddressCollision_d0_g0_v0`).
+        let not_address_collision = callee_nonce_is_zero.expr()
+            * (callee_not_exists.expr() + callee_is_empty_code_hash.expr());
+
+        // conditional transfer for address collision case. An existing account
+        // without nonce and code (e.g. pre-funded) is not created again.
+        let callee_was_created = cb.query_bool();
         let transfer = cb.condition(
-            and::expr([is_precheck_ok.expr(), not_address_collision.expr()]),
+            and::expr([is_precheck_ok.expr(), not_address_collision.clone()]),
             |cb| {
                 let tansfer_gadget = TransferGadget::construct(
                     cb,
                     create.caller_address(),
                     new_address.clone(),
-                    0.expr(),
-                    1.expr(),
+                    not::expr(callee_not_exists.expr()),
+                    callee_not_exists.expr(),
                     value.clone(),
                     &mut callee_reversion_info,
                 );
@@ -318,6 +341,12 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     0.expr(),
                     Some(&mut callee_reversion_info),
                 );
+                cb.account_created_write(
+                    tx_id.expr(),
+                    new_address.clone(),
+                    callee_was_created.expr(),
+                    Some(&mut callee_reversion_info),
+                );
 
                 tansfer_gadget
             },
@@ -340,8 +369,8 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
 
         let gas_cost = GasCost::CREATE.expr() + memory_expansion.gas_cost() + keccak_gas_cost;
         let gas_remaining = cb.curr.state.gas_left.expr() - gas_cost.clone();
-        let gas_left = ConstantDivisionGadget::construct(cb, gas_remaining.clone(), 64);
-        let callee_gas_left = gas_remaining - gas_left.quotient();
+        let eip150_gas = Eip150GasGadget::construct(cb, gas_remaining);
+        let callee_gas_left = eip150_gas.callee_gas_left();
         for (field_tag, value) in [
             (
                 CallContextFieldTag::ProgramCounter,
@@ -351,7 +380,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                 CallContextFieldTag::StackPointer,
                 cb.curr.state.stack_pointer.expr() + 2.expr() + IS_CREATE2.expr(),
             ),
-            (CallContextFieldTag::GasLeft, gas_left.quotient()),
+            (CallContextFieldTag::GasLeft, eip150_gas.one_64th_gas()),
             (
                 CallContextFieldTag::MemorySize,
                 memory_expansion.next_memory_word_size(),
@@ -423,7 +452,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
 
             // handle state transition if non-empty init code and no collision.
             cb.condition(
-                init_code.has_length() * not_address_collision.expr(),
+                init_code.has_length() * not_address_collision.clone(),
                 |cb| {
                     cb.require_step_state_transition(StepStateTransition {
                         rw_counter: Delta(cb.rw_counter_offset()),
@@ -432,7 +461,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         is_create: To(true.expr()),
                         code_hash: To(create.code_hash_word_rlc()),
                         gas_left: To(callee_gas_left),
-                        reversible_write_counter: To(1.expr() + transfer.reversible_w_delta()),
+                        reversible_write_counter: To(2.expr() + transfer.reversible_w_delta()),
                         ..StepStateTransition::new_context()
                     })
                 },
@@ -440,7 +469,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
 
             // handle state transition if empty init code and no collision.
             cb.condition(
-                not::expr(init_code.has_length()) * not_address_collision.expr(),
+                not::expr(init_code.has_length()) * not_address_collision.clone(),
                 |cb| {
                     for field_tag in [
                         CallContextFieldTag::LastCalleeId,
@@ -454,14 +483,14 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                         program_counter: Delta(1.expr()),
                         stack_pointer: Delta(2.expr() + IS_CREATE2.expr()),
                         gas_left: Delta(-gas_cost.expr()),
-                        reversible_write_counter: Delta(3.expr() + transfer.reversible_w_delta()),
+                        reversible_write_counter: Delta(4.expr() + transfer.reversible_w_delta()),
                         ..Default::default()
                     })
                 },
             );
 
             // handle address collision.
-            cb.condition(not::expr(not_address_collision.expr()), |cb| {
+            cb.condition(not::expr(not_address_collision.clone()), |cb| {
                 for field_tag in [
                     CallContextFieldTag::LastCalleeId,
                     CallContextFieldTag::LastCalleeReturnDataOffset,
@@ -474,7 +503,7 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                     rw_counter: Delta(cb.rw_counter_offset()),
                     program_counter: Delta(1.expr()),
                     stack_pointer: Delta(2.expr() + IS_CREATE2.expr()),
-                    gas_left: To(gas_left.quotient()),
+                    gas_left: To(eip150_gas.one_64th_gas()),
                     reversible_write_counter: Delta(2.expr()),
                     ..Default::default()
                 })
@@ -493,9 +522,10 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             init_code,
             init_code_rlc,
             memory_expansion,
-            gas_left,
+            eip150_gas,
             callee_is_success,
             init_code_word_size,
+            max_init_code_size,
             init_code_size_not_overflow,
             create,
             caller_balance,
@@ -505,7 +535,11 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             keccak_code_hash,
             keccak_output,
             code_hash_previous,
-            not_address_collision,
+            callee_not_exists,
+            callee_is_empty_code_hash,
+            callee_nonce,
+            callee_nonce_is_zero,
+            callee_was_created,
             copy_rwc_inc,
         }
     }
@@ -563,11 +597,17 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             offset,
             region.keccak_rlc(&values.iter().rev().cloned().collect::<Vec<u8>>()),
         )?;
+        let max_init_code_size = block.circuits_params.max_init_code_size();
+        self.max_init_code_size.assign(
+            region,
+            offset,
+            Value::known(F::from(max_init_code_size)),
+        )?;
         self.init_code_size_not_overflow.assign(
             region,
             offset,
             F::from(init_code_length.as_u64()),
-            F::from(MAX_INIT_CODE_SIZE + 1),
+            F::from(max_init_code_size + 1),
         )?;
 
         self.tx_id
@@ -634,16 +674,40 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
         let code_hash_previous_rlc = region.code_hash(code_hash_previous.0);
         self.code_hash_previous
             .assign(region, offset, code_hash_previous_rlc)?;
-        self.not_address_collision
+        self.callee_not_exists
             .assign_value(region, offset, code_hash_previous_rlc)?;
-        let is_address_collision = !code_hash_previous.0.is_zero();
+        self.callee_is_empty_code_hash.assign_value(
+            region,
+            offset,
+            code_hash_previous_rlc,
+            region.empty_code_hash_rlc(),
+        )?;
+        let callee_exists = !code_hash_previous.0.is_zero();
+        let callee_nonce = if callee_exists {
+            rw_offset += 1;
+            block.rws[step.rw_indices[14 + rw_offset]]
+                .account_nonce_pair()
+                .1
+                .low_u64()
+        } else {
+            0
+        };
+        self.callee_nonce
+            .assign(region, offset, Value::known(F::from(callee_nonce)))?;
+        self.callee_nonce_is_zero
+            .assign(region, offset, F::from(callee_nonce))?;
+        let is_address_collision = callee_nonce != 0
+            || (callee_exists && code_hash_previous.0 != CodeDB::empty_code_hash().to_word());
 
         if is_precheck_ok == 1 && !is_address_collision {
             /*
             rws:
                 ...
                 read code_hash // 14 + rw_offset
-                if creation needed:
+                if callee exists:
+                    read nonce // 15 + rw_offset
+                    rw_offset += 1
+                if creation needed (callee doesn't exist):
                     code_hash read // 15 + rw_offset
                     code_hash write // 16 + rw_offset
                     rw_offset += 2
@@ -654,10 +718,12 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                 caller balance // 15 + rw_offset
                 callee balance // 16 + rw_offset
              */
-            rw_offset += 2;
-            #[cfg(feature = "scroll")]
-            {
-                rw_offset += 2; // Read Write empty Keccak code hash.
+            if !callee_exists {
+                rw_offset += 2;
+                #[cfg(feature = "scroll")]
+                {
+                    rw_offset += 2; // Read Write empty Keccak code hash.
+                }
             }
             let [caller_balance_pair, callee_balance_pair] = if !value.is_zero() {
                 let account_balance_pair = [15, 16]
@@ -675,6 +741,17 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
                 callee_balance_pair,
                 value,
             )?;
+
+            // callee nonce write // 15 + rw_offset
+            // callee created write // 16 + rw_offset
+            let (_, callee_was_created) =
+                block.rws[step.rw_indices[16 + rw_offset]].tx_created_account_value_pair();
+            self.callee_was_created.assign(
+                region,
+                offset,
+                Value::known(F::from(callee_was_created)),
+            )?;
+            rw_offset += 1;
         }
 
         let (_next_memory_word_size, memory_expansion_gas_cost) = self.memory_expansion.assign(
@@ -696,9 +773,10 @@ impl<F: Field, const IS_CREATE2: bool, const S: ExecutionState> ExecutionGadget<
             } else {
                 CREATE_GAS_PER_CODE_WORD
             };
-        let gas_left =
+        let gas_available =
             step.gas_left - GasCost::CREATE.as_u64() - memory_expansion_gas_cost - keccak_gas_cost;
-        self.gas_left.assign(region, offset, gas_left.into())?;
+        self.eip150_gas
+            .assign(region, offset, gas_available, U256::zero())?;
 
         self.callee_is_success.assign(
             region,
@@ -777,8 +855,10 @@ mod test {
     use crate::test_util::CircuitTestBuilder;
     use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::{
-        address, bytecode, evm_types::OpcodeId, geth_types::Account, word, Address, Bytecode, Word,
+        address, bytecode, evm_types::OpcodeId, geth_types::Account, word, Address, Bytecode,
+        Bytes, ToBigEndian, Word,
     };
+    use ethers_core::utils::{get_contract_address, get_create2_address};
     use itertools::Itertools;
     use lazy_static::lazy_static;
     use mock::{eth, TestContext};
@@ -995,6 +1075,70 @@ mod test {
         run_test_circuits(test_context(caller));
     }
 
+    // An account at the new address with nonce or code collides, while an
+    // account with only balance doesn't.
+    #[test]
+    fn test_create_to_existing_address() {
+        for (nonce, code, is_create2) in [
+            (Word::zero(), Bytes::default(), false),
+            (Word::zero(), Bytes::default(), true),
+            (Word::one(), Bytes::default(), false),
+            (Word::zero(), Bytes::from(vec![0x00]), true),
+        ] {
+            let caller = Account {
+                address: *CALLER_ADDRESS,
+                code: creater_bytecode(
+                    initialization_bytecode(true),
+                    23414.into(),
+                    is_create2,
+                    true,
+                )
+                .into(),
+                nonce: Word::one(),
+                balance: eth(10),
+                ..Default::default()
+            };
+            let new_address = if is_create2 {
+                get_create2_address(
+                    *CALLER_ADDRESS,
+                    Word::from(45).to_be_bytes(),
+                    initialization_bytecode(true).code(),
+                )
+            } else {
+                get_contract_address(*CALLER_ADDRESS, Word::one())
+            };
+            let ctx = TestContext::<3, 1>::new(
+                None,
+                |accs| {
+                    accs[0]
+                        .address(address!("0x000000000000000000000000000000000000cafe"))
+                        .balance(eth(10));
+                    accs[1].account(&caller);
+                    accs[2]
+                        .address(new_address)
+                        .balance(eth(1))
+                        .nonce(nonce)
+                        .code(code.clone());
+                },
+                |mut txs, accs| {
+                    txs[0]
+                        .from(accs[0].address)
+                        .to(accs[1].address)
+                        .gas(word!("0x2386F26FC10000"));
+                },
+                |block, _| block,
+            )
+            .unwrap();
+            CircuitTestBuilder::new_from_test_ctx(ctx)
+                .params(CircuitsParams {
+                    max_rws: 70_000,
+                    max_copy_rows: 140_000,
+                    ..Default::default()
+                })
+                .run();
+        }
+    }
+
     // Ignore this test case. It could run successfully but slow for CI.
     #[ignore]
     #[test]
@@ -0,0 +1,167 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        util::{
+            constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{Field, ToScalar};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget deleting an account self destructed in the tx, between the step
+/// halting the tx and EndTx, like geth deletes it once the tx is done: the
+/// account keeps its code until then. Its code hash, nonce and balance, which
+/// it may have received after its SELFDESTRUCT, are zeroed.
+///
+/// The destructed flag written by SELFDESTRUCT is cleared, and the state
+/// circuit requires every flag to be cleared by the end of the tx, so each
+/// destructed account is deleted once. Unlike geth, the deletion comes before
+/// the fee paid to the coinbase by EndTx.
+#[derive(Clone, Debug)]
+pub(crate) struct DestructAccountGadget<F> {
+    tx_id: Cell<F>,
+    address: Cell<F>,
+    prev_code_hash: Cell<F>,
+    prev_nonce: Cell<F>,
+    prev_balance: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for DestructAccountGadget<F> {
+    const NAME: &'static str = "DestructAccount";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::DestructAccount;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let address = cb.query_cell();
+        cb.account_destructed_clear(tx_id.expr(), address.expr());
+
+        let prev_code_hash = cb.query_cell_phase2();
+        let prev_nonce = cb.query_cell();
+        let prev_balance = cb.query_cell_phase2();
+        for (field_tag, value_prev) in [
+            (AccountFieldTag::CodeHash, prev_code_hash.expr()),
+            (AccountFieldTag::Nonce, prev_nonce.expr()),
+            (AccountFieldTag::Balance, prev_balance.expr()),
+        ] {
+            cb.account_write(address.expr(), field_tag, 0.expr(), value_prev, None);
+        }
+
+        // EndTx reads the state left by the step halting the tx.
+        cb.require_step_state_transition(StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            ..Default::default()
+        });
+
+        Self {
+            tx_id,
+            address,
+            prev_code_hash,
+            prev_nonce,
+            prev_balance,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        _: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        let address = block.rws[step.rw_indices[1]].address().unwrap();
+        self.address
+            .assign(region, offset, Value::known(address.to_scalar().unwrap()))?;
+
+        let [code_hash, nonce, balance] =
+            [2, 3, 4].map(|i| block.rws[step.rw_indices[i]].account_value_pair().1);
+        self.prev_code_hash
+            .assign(region, offset, region.code_hash(code_hash))?;
+        self.prev_nonce
+            .assign(region, offset, Value::known(nonce.to_scalar().unwrap()))?;
+        self.prev_balance
+            .assign(region, offset, region.word_rlc(balance))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, ToWord};
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    // The contract is called again after its SELFDESTRUCT and still runs its
+    // code. Before EIP-6780 it's deleted by a DestructAccount step, together
+    // with the balance sent to it by the second call.
+    #[test]
+    fn destruct_account_after_second_call() {
+        let contract = address!("0x0000000000000000000000000000000000000020");
+        let beneficiary = address!("0x0000000000000000000000000000000000000030");
+        let caller_code = bytecode! {
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH20(contract.to_word())
+            GAS
+            CALL
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(1)
+            PUSH20(contract.to_word())
+            GAS
+            CALL
+            STOP
+        };
+        // The value of the second call is kept in the account.
+        let contract_code = bytecode! {
+            CALLVALUE
+            PUSH1(0x1a)
+            JUMPI
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+            JUMPDEST
+            STOP
+        };
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1]
+                    .address(MOCK_ACCOUNTS[1])
+                    .balance(eth(1))
+                    .code(caller_code);
+                accs[2]
+                    .address(contract)
+                    .balance(eth(2))
+                    .code(contract_code);
+                accs[3].address(beneficiary).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(200000u64.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
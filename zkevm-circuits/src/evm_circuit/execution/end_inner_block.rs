@@ -3,7 +3,9 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition, Transition::Same,
+            },
             math_gadget::IsZeroGadget,
             CachedRegion, Cell,
         },
@@ -83,6 +85,23 @@ impl<F: Field> ExecutionGadget<F> for EndInnerBlockGadget<F> {
                 cb.curr.state.block_number.expr(),
             );
         });
+
+        // The boundary step does no rw operation, so the rw counter carries
+        // over to the next EndInnerBlock or EndBlock step, along with the
+        // call_id by which EndBlock gets the last tx_id.
+        cb.condition(
+            cb.next.execution_state_selector([
+                ExecutionState::EndInnerBlock,
+                ExecutionState::EndBlock,
+            ]),
+            |cb| {
+                cb.require_step_state_transition(StepStateTransition {
+                    rw_counter: Same,
+                    call_id: Same,
+                    ..StepStateTransition::any()
+                });
+            },
+        );
         cb.condition(not::expr(next_step_end_block), |cb| {
             cb.require_equal(
                 "block number increments if there are more inner blocks",
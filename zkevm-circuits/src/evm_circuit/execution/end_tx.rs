@@ -231,12 +231,13 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
             }
             .expr();
         cb.condition(
-            cb.next.execution_state_selector([ExecutionState::EndBlock]),
+            cb.next
+                .execution_state_selector([ExecutionState::EndInnerBlock]),
             |cb| {
                 cb.require_step_state_transition(StepStateTransition {
                     rw_counter: Delta(rw_counter_delta),
-                    // We propagate call_id so that EndBlock can get the last tx_id
-                    // in order to count processed txs.
+                    // We propagate call_id through EndInnerBlock so that EndBlock can
+                    // get the last tx_id in order to count processed txs.
                     call_id: Same,
                     ..StepStateTransition::any()
                 });
@@ -408,9 +409,10 @@ impl<F: Field> ExecutionGadget<F> for EndTxGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::test_util::CircuitTestBuilder;
+    use crate::{evm_circuit::step::ExecutionState, test_util::CircuitTestBuilder};
     use bus_mapping::circuit_input_builder::CircuitsParams;
     use eth_types::{self, bytecode};
+    use halo2_proofs::dev::VerifyFailure;
 
     use mock::{eth, test_ctx::helpers::account_0_code_account_1_no_code, TestContext};
 
@@ -490,4 +492,29 @@ mod test {
             .unwrap(),
         );
     }
+
+    #[test]
+    fn end_tx_gadget_wrong_rw_counter_into_end_inner_block() {
+        let ctx = TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode! { STOP }).unwrap();
+
+        CircuitTestBuilder::<2, 1>::new_from_test_ctx(ctx)
+            .block_modifier(Box::new(|block| {
+                // BeginTx, Stop, EndTx, EndInnerBlock
+                let step = block.txs[0].steps.last_mut().unwrap();
+                assert_eq!(step.execution_state, ExecutionState::EndInnerBlock);
+                step.rw_counter += 1;
+            }))
+            .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+                let errors = prover
+                    .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                    .unwrap_err();
+                assert!(errors.iter().any(|error| matches!(
+                    error,
+                    VerifyFailure::ConstraintNotSatisfied { constraint, .. }
+                        if format!("{constraint}").contains("EndTx")
+                            && format!("{constraint}").contains("rw_counter")
+                )));
+            }))
+            .run();
+    }
 }
@@ -19,8 +19,6 @@ use eth_types::{evm_types::GasCost, Field};
 
 use halo2_proofs::{circuit::Value, plonk::Error};
 
-const MAXCODESIZE: u64 = 0x6000u64;
-
 /// Gadget for code store oog and max code size exceed
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorCodeStoreGadget<F> {
@@ -29,6 +27,7 @@ pub(crate) struct ErrorCodeStoreGadget<F> {
     // check for CodeStoreOutOfGas error
     code_store_gas_insufficient: LtGadget<F, N_BYTES_GAS>,
     // check for MaxCodeSizeExceeded error
+    max_code_size: Cell<F>,
     max_code_size_exceed: LtGadget<F, N_BYTES_U64>,
     common_error_gadget: CommonErrorGadget<F>,
 }
@@ -58,9 +57,11 @@ impl<F: Field> ExecutionGadget<F> for ErrorCodeStoreGadget<F> {
             GasCost::CODE_DEPOSIT_BYTE_COST.expr() * memory_address.length(),
         );
 
-        // constrain code size > MAXCODESIZE
+        // constrain code size > max code size of the chain
+        let max_code_size = cb.query_cell();
+        cb.max_code_size_lookup(max_code_size.expr());
         let max_code_size_exceed =
-            LtGadget::construct(cb, MAXCODESIZE.expr(), memory_address.length());
+            LtGadget::construct(cb, max_code_size.expr(), memory_address.length());
 
         // check must be one of CodeStoreOutOfGas or MaxCodeSizeExceeded
         cb.require_in_set(
@@ -81,6 +82,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorCodeStoreGadget<F> {
             opcode,
             memory_address,
             code_store_gas_insufficient,
+            max_code_size,
             max_code_size_exceed,
             common_error_gadget,
         }
@@ -110,10 +112,13 @@ impl<F: Field> ExecutionGadget<F> for ErrorCodeStoreGadget<F> {
             F::from(GasCost::CODE_DEPOSIT_BYTE_COST.as_u64() * length.as_u64()),
         )?;
 
+        let max_code_size = block.circuits_params.max_code_size as u64;
+        self.max_code_size
+            .assign(region, offset, Value::known(F::from(max_code_size)))?;
         self.max_code_size_exceed.assign(
             region,
             offset,
-            F::from(MAXCODESIZE),
+            F::from(max_code_size),
             F::from(length.as_u64()),
         )?;
 
@@ -129,7 +134,7 @@ mod test {
     use eth_types::{
         address,
         bytecode,
-        evm_types::OpcodeId,
+        evm_types::{OpcodeId, MAX_CODE_SIZE},
         geth_types::Account,
         Address,
         Bytecode,
@@ -147,12 +152,15 @@ mod test {
         static ref CALLER_ADDRESS: Address = address!("0x00bbccddee000000000000000000000000002400");
     }
 
-    const MAXCODESIZE: u64 = 0x6000u64;
-
     fn run_test_circuits(ctx: TestContext<2, 1>) {
+        run_test_circuits_with_max_code_size(ctx, MAX_CODE_SIZE as usize);
+    }
+
+    fn run_test_circuits_with_max_code_size(ctx: TestContext<2, 1>, max_code_size: usize) {
         CircuitTestBuilder::new_from_test_ctx(ctx)
             .params(CircuitsParams {
                 max_rws: 4500,
+                max_code_size,
                 ..Default::default()
             })
             .run();
@@ -161,7 +169,7 @@ mod test {
     fn initialization_bytecode(is_oog: bool) -> Bytecode {
         let memory_bytes = [0x60; 10];
         let memory_value = Word::from_big_endian(&memory_bytes);
-        let code_len = if is_oog { 0 } else { MAXCODESIZE + 1 };
+        let code_len = if is_oog { 0 } else { MAX_CODE_SIZE + 1 };
 
         let mut code = bytecode! {
             PUSH10(memory_value)
@@ -272,6 +280,25 @@ mod test {
         }
     }
 
+    // the code exceeds a chain limit lower than the one of Ethereum, which
+    // the circuit reads from its fixed table
+    #[test]
+    fn test_create_max_code_size_exceed_lower_chain_limit() {
+        let initialization_code = initialization_bytecode(false);
+        let root_code = creator_bytecode(initialization_code, false);
+        let caller = Account {
+            address: *CALLER_ADDRESS,
+            code: root_code.into(),
+            nonce: Word::one(),
+            balance: eth(10),
+            ..Default::default()
+        };
+        run_test_circuits_with_max_code_size(
+            test_context(caller, false),
+            MAX_CODE_SIZE as usize / 2,
+        );
+    }
+
     #[test]
     fn tx_deploy_code_store_oog() {
         let code = initialization_bytecode(true);
@@ -1,22 +1,30 @@
-use crate::evm_circuit::{
-    execution::ExecutionGadget,
-    step::ExecutionState,
-    table::{FixedTableTag, Lookup},
-    util::{
-        common_gadget::CommonErrorGadget, constraint_builder::EVMConstraintBuilder, CachedRegion,
-        Cell,
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        step::ExecutionState,
+        table::{FixedTableTag, Lookup},
+        util::{
+            common_gadget::{CommonErrorGadget, IsShanghaiGadget},
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
+            math_gadget::IsZeroGadget,
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
     },
-    witness::{Block, Call, ExecStep, Transaction},
+    table::CallContextFieldTag,
 };
-use eth_types::Field;
+use eth_types::{evm_types::OpcodeId, Field};
 use gadgets::util::Expr;
 use halo2_proofs::{circuit::Value, plonk::Error};
 
 /// Gadget for invalid opcodes. It verifies by a fixed lookup for
-/// ResponsibleOpcode.
+/// ResponsibleOpcode, and for `PUSH0` that Shanghai isn't active in the block.
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorInvalidOpcodeGadget<F> {
     opcode: Cell<F>,
+    is_push0: IsZeroGadget<F>,
+    tx_id: Cell<F>,
+    is_shanghai: IsShanghaiGadget<F>,
     common_error_gadget: CommonErrorGadget<F>,
 }
 
@@ -39,10 +47,22 @@ impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
             },
         );
 
-        let common_error_gadget = CommonErrorGadget::construct(cb, opcode.expr(), 2.expr());
+        let is_push0 = IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::PUSH0.expr());
+        let (tx_id, is_shanghai) = cb.condition(is_push0.expr(), |cb| {
+            let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+            let is_shanghai = IsShanghaiGadget::construct(cb, tx_id.expr());
+            cb.require_zero("PUSH0 is invalid before Shanghai", is_shanghai.expr());
+            (tx_id, is_shanghai)
+        });
+
+        let common_error_gadget =
+            CommonErrorGadget::construct(cb, opcode.expr(), 2.expr() + is_push0.expr());
 
         Self {
             opcode,
+            is_push0,
+            tx_id,
+            is_shanghai,
             common_error_gadget,
         }
     }
@@ -52,7 +72,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         block: &Block<F>,
-        _: &Transaction,
+        tx: &Transaction,
         call: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
@@ -62,18 +82,45 @@ impl<F: Field> ExecutionGadget<F> for ErrorInvalidOpcodeGadget<F> {
 
         log::debug!("ErrorInvalidOpcode - opcode = {}", opcode);
 
-        self.common_error_gadget
-            .assign(region, offset, block, call, step, 2)?;
+        let is_push0 = opcode == OpcodeId::PUSH0.as_u64();
+        self.is_push0.assign(
+            region,
+            offset,
+            F::from(opcode) - F::from(OpcodeId::PUSH0.as_u64()),
+        )?;
+        if is_push0 {
+            self.tx_id
+                .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+            self.is_shanghai
+                .assign(region, offset, tx.block_number, &block.chain_spec)?;
+        }
+
+        self.common_error_gadget.assign(
+            region,
+            offset,
+            block,
+            call,
+            step,
+            2 + is_push0 as usize,
+        )?;
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
-    use eth_types::{bytecode::Bytecode, Word};
+    use crate::{
+        evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder, witness::block_convert,
+    };
+    use bus_mapping::{circuit_input_builder::ChainSpec, mock::BlockData};
+    use eth_types::{bytecode, bytecode::Bytecode, geth_types::GethData, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
     use lazy_static::lazy_static;
-    use mock::{generate_mock_call_bytecode, MockCallBytecodeParams, TestContext};
+    use mock::{
+        generate_mock_call_bytecode,
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        MockCallBytecodeParams, TestContext,
+    };
 
     lazy_static! {
         static ref TESTING_INVALID_CODES: [Vec<u8>; 6] = [
@@ -120,6 +167,40 @@ mod test {
         test_internal_ok(0x20, 0x00, &[push0]);
     }
 
+    #[test]
+    fn invalid_opcode_push0_before_shanghai_block() {
+        let code = bytecode! {
+            PUSH0
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        // the trace of a chain which activates Shanghai in the next block,
+        // which halts at PUSH0, whether the tracer is Shanghai or not
+        let trace = &mut block.geth_traces[0];
+        trace.struct_logs.truncate(1);
+        trace.struct_logs[0].error = Some("invalid opcode: PUSH0".to_string());
+        trace.failed = true;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.chain_spec = ChainSpec {
+            shanghai_block: Some(0xcaff),
+            ..Default::default()
+        };
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+        CircuitTestBuilder::new_from_block(block).run();
+    }
+
     fn test_root_ok(invalid_code: &[u8]) {
         let mut code = Bytecode::default();
         invalid_code.iter().for_each(|b| {
@@ -4,9 +4,9 @@ use crate::{
         param::N_BYTES_GAS,
         step::ExecutionState,
         util::{
-            common_gadget::{CommonCallGadget, CommonErrorGadget},
+            common_gadget::{CallOpcodeGadget, CommonCallGadget, CommonErrorGadget},
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
-            math_gadget::{IsZeroGadget, LtGadget},
+            math_gadget::LtGadget,
             memory_gadget::MemoryExpandedAddressGadget,
             or, CachedRegion, Cell,
         },
@@ -24,11 +24,7 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 /// [`OpcodeId::STATICCALL`].
 #[derive(Clone, Debug)]
 pub(crate) struct ErrorOOGCallGadget<F> {
-    opcode: Cell<F>,
-    is_call: IsZeroGadget<F>,
-    is_callcode: IsZeroGadget<F>,
-    is_delegatecall: IsZeroGadget<F>,
-    is_staticcall: IsZeroGadget<F>,
+    opcode: CallOpcodeGadget<F>,
     tx_id: Cell<F>,
     is_static: Cell<F>,
     is_warm: Cell<F>,
@@ -43,27 +39,14 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
     const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasCall;
 
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
-        let opcode = cb.query_cell();
-
-        let is_call = IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::CALL.expr());
-        let is_callcode =
-            IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::CALLCODE.expr());
-        let is_delegatecall =
-            IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::DELEGATECALL.expr());
-        let is_staticcall =
-            IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::STATICCALL.expr());
+        let opcode = CallOpcodeGadget::construct(cb);
+        let [is_call, is_callcode, _, _] = opcode.flags();
 
         let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
         let is_static = cb.call_context(None, CallContextFieldTag::IsStatic);
 
         let call_gadget: CommonCallGadget<F, MemoryExpandedAddressGadget<F>, false> =
-            CommonCallGadget::construct(
-                cb,
-                is_call.expr(),
-                is_callcode.expr(),
-                is_delegatecall.expr(),
-                is_staticcall.expr(),
-            );
+            CommonCallGadget::construct(cb, &opcode);
 
         // Add callee to access list
         let is_warm = cb.query_bool();
@@ -100,16 +83,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
         // DELEGATECALL and STATICCALL.
         let common_error_gadget = CommonErrorGadget::construct(
             cb,
-            opcode.expr(),
+            opcode.opcode.expr(),
             13.expr() + is_call.expr() + is_callcode.expr(),
         );
 
         Self {
             opcode,
-            is_call,
-            is_callcode,
-            is_delegatecall,
-            is_staticcall,
             tx_id,
             is_static,
             is_warm,
@@ -175,29 +154,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCallGadget<F> {
             region.code_hash(callee_code_hash),
         )?;
 
-        self.opcode
-            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
-
-        self.is_call.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALL.as_u64()),
-        )?;
-        self.is_callcode.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::CALLCODE.as_u64()),
-        )?;
-        self.is_delegatecall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::DELEGATECALL.as_u64()),
-        )?;
-        self.is_staticcall.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()) - F::from(OpcodeId::STATICCALL.as_u64()),
-        )?;
+        self.opcode.assign(region, offset, opcode)?;
 
         self.tx_id
             .assign(region, offset, Value::known(F::from(tx_id.low_u64())))?;
@@ -17,9 +17,7 @@ use crate::{
     witness::{Block, Call, ExecStep, Transaction},
 };
 use eth_types::{
-    evm_types::{
-        GasCost, OpcodeId, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD, MAX_INIT_CODE_SIZE,
-    },
+    evm_types::{GasCost, OpcodeId, CREATE2_GAS_PER_CODE_WORD, CREATE_GAS_PER_CODE_WORD},
     Field, ToLittleEndian, U256,
 };
 use gadgets::util::Expr;
@@ -34,10 +32,11 @@ pub(crate) struct ErrorOOGCreateGadget<F> {
     minimum_word_size: MemoryWordSizeGadget<F>,
     memory_address: MemoryExpandedAddressGadget<F>,
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
-    // Init code size is overflow when it is greater than 49152
-    // (maximum init code size) if Shanghai, otherwise when it is greater than
+    // Init code size is overflow when it is greater than the maximum init code
+    // size of the chain if Shanghai, otherwise when it is greater than
     // 0x1FFFFFFFE0 (maximum value of offset + size).
     // Uint64 overflow is checked in `memory_address` (offset + length).
+    max_init_code_size: Cell<F>,
     init_code_size_overflow: LtGadget<F, { N_BYTES_MEMORY_ADDRESS }>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
     common_error_gadget: CommonErrorGadget<F>,
@@ -68,8 +67,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
         cb.stack_pop(memory_address.length_rlc());
         cb.condition(is_create2.expr().0, |cb| cb.stack_pop(salt.expr()));
 
+        let max_init_code_size = cb.query_cell();
+        cb.max_init_code_size_lookup(max_init_code_size.expr());
         let init_code_size_overflow =
-            LtGadget::construct(cb, MAX_INIT_CODE_SIZE.expr(), memory_address.length());
+            LtGadget::construct(cb, max_init_code_size.expr(), memory_address.length());
 
         let minimum_word_size = MemoryWordSizeGadget::construct(cb, memory_address.length());
         let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
@@ -107,6 +108,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
             minimum_word_size,
             memory_address,
             memory_expansion,
+            max_init_code_size,
             init_code_size_overflow,
             insufficient_gas,
             common_error_gadget,
@@ -166,10 +168,16 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
             .assign(region, offset, step.memory_word_size(), [memory_address])?
             .1;
 
+        let max_init_code_size = block.circuits_params.max_init_code_size();
+        self.max_init_code_size.assign(
+            region,
+            offset,
+            Value::known(F::from(max_init_code_size)),
+        )?;
         self.init_code_size_overflow.assign(
             region,
             offset,
-            F::from(MAX_INIT_CODE_SIZE),
+            F::from(max_init_code_size),
             F::from(init_code_size),
         )?;
 
@@ -203,7 +211,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGCreateGadget<F> {
 mod tests {
     use super::*;
     use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, word, Bytecode, ToWord};
+    use eth_types::{bytecode, evm_types::MAX_INIT_CODE_SIZE, word, Bytecode, ToWord};
     use mock::{
         eth,
         test_ctx::{helpers::account_0_code_account_1_no_code, LoggerConfig},
@@ -4,83 +4,94 @@ use crate::{
         param::{N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
         step::ExecutionState,
         util::{
-            common_gadget::RestoreContextGadget,
-            constraint_builder::{
-                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
-                Transition::{Delta, Same},
-            },
+            common_gadget::CommonErrorGadget,
+            constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{IsEqualGadget, LtGadget},
             memory_gadget::{
                 CommonMemoryAddressGadget, MemoryExpandedAddressGadget, MemoryExpansionGadget,
             },
-            not, or, select, CachedRegion, Cell,
+            not, or, select, sum, CachedRegion, Cell,
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
-    table::CallContextFieldTag,
     util::Expr,
 };
 use eth_types::{evm_types::OpcodeId, Field};
 use halo2_proofs::{circuit::Value, plonk::Error};
 
+/// Out of gas of the opcodes whose dynamic gas cost is only the memory
+/// expansion: MLOAD, MSTORE and MSTORE8, which access a fixed length at the
+/// offset popped from the stack, and RETURN and REVERT, which also pop the
+/// length.
 #[derive(Clone, Debug)]
-pub(crate) struct ErrorOOGStaticMemoryGadget<F> {
+pub(crate) struct ErrorOOGMemoryExpansionGadget<F> {
     opcode: Cell<F>,
+    is_mload: IsEqualGadget<F>,
+    is_mstore8: IsEqualGadget<F>,
+    is_return: IsEqualGadget<F>,
+    is_revert: IsEqualGadget<F>,
     memory_address: MemoryExpandedAddressGadget<F>,
     memory_expansion: MemoryExpansionGadget<F, 1, N_BYTES_MEMORY_WORD_SIZE>,
     insufficient_gas: LtGadget<F, N_BYTES_GAS>,
-    is_mload: IsEqualGadget<F>,
-    is_mstore8: IsEqualGadget<F>,
-    rw_counter_end_of_reversion: Cell<F>,
-    restore_context: RestoreContextGadget<F>,
+    common_error_gadget: CommonErrorGadget<F>,
 }
 
-impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
-    const NAME: &'static str = "ErrorOutOfGasStaticMemoryExpansion";
+impl<F: Field> ExecutionGadget<F> for ErrorOOGMemoryExpansionGadget<F> {
+    const NAME: &'static str = "ErrorOutOfGasMemoryExpansion";
 
-    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasStaticMemoryExpansion;
+    const EXECUTION_STATE: ExecutionState = ExecutionState::ErrorOutOfGasMemoryExpansion;
 
-    // Support other OOG due to pure memory including MSTORE, MSTORE8 and MLOAD
     fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
         let opcode = cb.query_cell();
-        cb.opcode_lookup(opcode.expr(), 1.expr());
-
-        let memory_address = MemoryExpandedAddressGadget::construct_self(cb);
-        cb.stack_pop(memory_address.offset_rlc());
 
-        // Check if this is an MSTORE8
         let is_mload = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MLOAD.expr());
         let is_mstore8 = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::MSTORE8.expr());
+        let is_return = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::RETURN.expr());
+        let is_revert = IsEqualGadget::construct(cb, opcode.expr(), OpcodeId::REVERT.expr());
+        let is_mstore = not::expr(sum::expr([
+            is_mload.expr(),
+            is_mstore8.expr(),
+            is_return.expr(),
+            is_revert.expr(),
+        ]));
+        // RETURN and REVERT pop the length of the memory access
+        let pops_length = is_return.expr() + is_revert.expr();
 
         cb.require_equal(
-            "ErrorOutOfGasStaticMemoryExpansion opcode must be MLOAD or MSTORE or MSTORE8",
+            "ErrorOutOfGasMemoryExpansion opcode must be MLOAD, MSTORE, MSTORE8, RETURN or REVERT",
             opcode.expr(),
-            select::expr(
-                is_mload.expr(),
-                OpcodeId::MLOAD.expr(),
-                select::expr(
-                    is_mstore8.expr(),
-                    OpcodeId::MSTORE8.expr(),
-                    OpcodeId::MSTORE.expr(),
-                ),
-            ),
+            sum::expr([
+                is_mload.expr() * OpcodeId::MLOAD.expr(),
+                is_mstore.expr() * OpcodeId::MSTORE.expr(),
+                is_mstore8.expr() * OpcodeId::MSTORE8.expr(),
+                is_return.expr() * OpcodeId::RETURN.expr(),
+                is_revert.expr() * OpcodeId::REVERT.expr(),
+            ]),
         );
 
-        cb.require_equal(
-            "Memory length must be 32 for MLOAD and MSTORE, and 1 for MSTORE8",
-            memory_address.length_rlc(),
-            select::expr(is_mstore8.expr(), 1.expr(), 32.expr()),
-        );
+        let memory_address = MemoryExpandedAddressGadget::construct_self(cb);
+        cb.stack_pop(memory_address.offset_rlc());
+        cb.condition(pops_length.expr(), |cb| {
+            cb.stack_pop(memory_address.length_rlc());
+        });
+        cb.condition(not::expr(pops_length.expr()), |cb| {
+            cb.require_equal(
+                "Memory length must be 32 for MLOAD and MSTORE, and 1 for MSTORE8",
+                memory_address.length_rlc(),
+                select::expr(is_mstore8.expr(), 1.expr(), 32.expr()),
+            );
+        });
 
-        // Get the next memory size and the gas cost for this memory access
         let memory_expansion = MemoryExpansionGadget::construct(cb, [memory_address.address()]);
 
-        // Check if the amount of gas available is less than the amount of gas
-        // required
         let insufficient_gas = LtGadget::construct(
             cb,
             cb.curr.state.gas_left.expr(),
-            OpcodeId::MLOAD.constant_gas_cost().expr() + memory_expansion.gas_cost(),
+            select::expr(
+                pops_length.expr(),
+                OpcodeId::RETURN.constant_gas_cost().expr(),
+                OpcodeId::MLOAD.constant_gas_cost().expr(),
+            ) + memory_expansion.gas_cost(),
         );
 
         cb.require_equal(
@@ -89,46 +100,19 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
             1.expr(),
         );
 
-        // Current call must fail.
-        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 0.expr());
-
-        let rw_counter_end_of_reversion = cb.query_cell();
-        cb.call_context_lookup(
-            false.expr(),
-            None,
-            CallContextFieldTag::RwCounterEndOfReversion,
-            rw_counter_end_of_reversion.expr(),
-        );
-
-        cb.condition(cb.curr.state.is_root.expr(), |cb| {
-            cb.require_step_state_transition(StepStateTransition {
-                call_id: Same,
-                rw_counter: Delta(3.expr() + cb.curr.state.reversible_write_counter.expr()),
-                ..StepStateTransition::any()
-            });
-        });
-
-        let restore_context = cb.condition(not::expr(cb.curr.state.is_root.expr()), |cb| {
-            RestoreContextGadget::construct(
-                cb,
-                0.expr(),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-                0.expr(),
-            )
-        });
+        let common_error_gadget =
+            CommonErrorGadget::construct(cb, opcode.expr(), 3.expr() + pops_length);
 
         Self {
             opcode,
+            is_mload,
+            is_mstore8,
+            is_return,
+            is_revert,
             memory_address,
             memory_expansion,
             insufficient_gas,
-            is_mload,
-            is_mstore8,
-            rw_counter_end_of_reversion,
-            restore_context,
+            common_error_gadget,
         }
     }
 
@@ -143,58 +127,49 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGStaticMemoryGadget<F> {
     ) -> Result<(), Error> {
         let opcode = step.opcode.unwrap();
 
-        // Inputs/Outputs
-        let memory_offset = block.rws[step.rw_indices[0]].stack_value();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        for (gadget, op) in [
+            (&self.is_mload, OpcodeId::MLOAD),
+            (&self.is_mstore8, OpcodeId::MSTORE8),
+            (&self.is_return, OpcodeId::RETURN),
+            (&self.is_revert, OpcodeId::REVERT),
+        ] {
+            gadget.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()),
+                F::from(op.as_u64()),
+            )?;
+        }
 
+        let memory_offset = block.rws[step.rw_indices[0]].stack_value();
         // Memory lengths are set to default values for MLOAD, MSTORE and
         // MSTORE8 in go-ethereum.
         // <https://github.com/ethereum/go-ethereum/blob/4a9fa31450d3cdcea84735b68cd5a0a8450473f8/core/vm/memory_table.go#L39>
-        let memory_length = match opcode {
-            OpcodeId::MLOAD | OpcodeId::MSTORE => 32,
-            OpcodeId::MSTORE8 => 1,
+        let (memory_length, num_stack_inputs) = match opcode {
+            OpcodeId::MLOAD | OpcodeId::MSTORE => (32.into(), 1),
+            OpcodeId::MSTORE8 => (1.into(), 1),
+            OpcodeId::RETURN | OpcodeId::REVERT => (block.rws[step.rw_indices[1]].stack_value(), 2),
             _ => unreachable!(),
         };
-
-        self.opcode
-            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
-        self.is_mload.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()),
-            F::from(OpcodeId::MLOAD.as_u64()),
-        )?;
-        self.is_mstore8.assign(
-            region,
-            offset,
-            F::from(opcode.as_u64()),
-            F::from(OpcodeId::MSTORE8.as_u64()),
-        )?;
-
         let memory_address =
             self.memory_address
-                .assign(region, offset, memory_offset, memory_length.into())?;
+                .assign(region, offset, memory_offset, memory_length)?;
 
-        let memory_expansion_cost = self
+        let memory_expansion_gas = self
             .memory_expansion
             .assign(region, offset, step.memory_word_size(), [memory_address])?
             .1;
-
-        // Gas insufficient check
-        // Get `gas_available` variable here once it's available
         self.insufficient_gas.assign(
             region,
             offset,
             F::from(step.gas_left),
-            F::from(OpcodeId::MLOAD.constant_gas_cost().0 + memory_expansion_cost),
+            F::from(opcode.constant_gas_cost().0 + memory_expansion_gas),
         )?;
 
-        self.rw_counter_end_of_reversion.assign(
-            region,
-            offset,
-            Value::known(F::from(call.rw_counter_end_of_reversion as u64)),
-        )?;
-        self.restore_context
-            .assign(region, offset, block, call, step, 3)?;
+        self.common_error_gadget
+            .assign(region, offset, block, call, step, num_stack_inputs + 2)?;
 
         Ok(())
     }
@@ -209,39 +184,47 @@ mod tests {
     };
 
     #[test]
-    fn test_oog_static_memory_simple() {
-        for code in testing_bytecodes(0xffffffff_u64.into()).iter() {
+    fn test_oog_memory_expansion_simple() {
+        for code in testing_bytecodes(0xffffffff_u64.into(), 0xff.into()).iter() {
             test_root(code);
             test_internal(code);
         }
     }
 
     #[test]
-    fn test_oog_static_memory_max_expanded_address() {
+    fn test_oog_memory_expansion_max_expanded_address() {
+        // 0xffffffff1 + 0xffffffff0 = 0x1fffffffe1
         // > MAX_EXPANDED_MEMORY_ADDRESS (0x1fffffffe0)
-        for code in testing_bytecodes(0x1fffffffe1_u64.into()).iter() {
+        for code in testing_bytecodes(0xffffffff1_u64.into(), 0xffffffff0_u64.into()).iter() {
+            test_root(code);
+            test_internal(code);
+        }
+        // > MAX_EXPANDED_MEMORY_ADDRESS (0x1fffffffe0)
+        for code in testing_bytecodes(0x1fffffffe1_u64.into(), 0xff.into())[..3].iter() {
             test_root(code);
             test_internal(code);
         }
     }
 
     #[test]
-    fn test_oog_static_memory_max_u64_address() {
-        for code in testing_bytecodes(u64::MAX.into()).iter() {
+    fn test_oog_memory_expansion_max_u64_address() {
+        for code in testing_bytecodes(u64::MAX.into(), u64::MAX.into()).iter() {
             test_root(code);
             test_internal(code);
         }
     }
 
     #[test]
-    fn test_oog_static_memory_max_word_address() {
-        for code in testing_bytecodes(U256::MAX).iter() {
+    fn test_oog_memory_expansion_max_word_address() {
+        for code in testing_bytecodes(U256::MAX, U256::MAX).iter() {
             test_root(code);
             test_internal(code);
         }
     }
 
-    fn testing_bytecodes(offset: U256) -> Vec<Bytecode> {
+    /// MSTORE, MSTORE8 and MLOAD at `offset`, then RETURN and REVERT of `size`
+    /// bytes at `offset`.
+    fn testing_bytecodes(offset: U256, size: U256) -> Vec<Bytecode> {
         vec![
             bytecode! {
                 PUSH8(0xff)
@@ -260,8 +243,19 @@ mod tests {
                 MLOAD
                 STOP
             },
+            bytecode! {
+                PUSH32(size)
+                PUSH32(offset)
+                RETURN
+            },
+            bytecode! {
+                PUSH32(size)
+                PUSH32(offset)
+                REVERT
+            },
         ]
     }
+
     fn test_root(code: &Bytecode) {
         let ctx = TestContext::<2, 1>::new(
             None,
@@ -287,8 +281,7 @@ mod tests {
             PUSH32(0x00) // argsOffset
             PUSH1(0x00) // value
             PUSH32(MOCK_ACCOUNTS[1].to_word()) // addr
-            // Decrease expected gas cost (by 1) to trigger out of gas error.
-            PUSH32(0xF) // gas
+            PUSH32(0xFFFF) // gas
             CALL
             STOP
         };
@@ -304,7 +297,7 @@ mod tests {
                 txs[0]
                     .from(accs[2].address)
                     .to(accs[0].address)
-                    .gas(word!("0xFFFF"));
+                    .gas(word!("0xFFFFF"));
             },
             |block, _tx| block,
         )
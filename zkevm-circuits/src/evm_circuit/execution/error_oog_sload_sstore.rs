@@ -6,8 +6,7 @@ use crate::{
         util::{
             and,
             common_gadget::{
-                cal_sload_gas_cost_for_assignment, cal_sstore_gas_cost_for_assignment,
-                CommonErrorGadget, SloadGasGadget, SstoreGasGadget,
+                CommonErrorGadget, GasScheduleGadget, SloadGasGadget, SstoreGasGadget,
             },
             constraint_builder::{ConstrainBuilderCommon, EVMConstraintBuilder},
             math_gadget::{LtGadget, PairSelectGadget},
@@ -38,6 +37,7 @@ pub(crate) struct ErrorOOGSloadSstoreGadget<F> {
     phase2_original_value: Cell<F>,
     is_warm: Cell<F>,
     is_sstore: PairSelectGadget<F>,
+    gas_schedule: GasScheduleGadget<F>,
     sstore_gas_cost: SstoreGasGadget<F>,
     insufficient_gas_cost: LtGadget<F, N_BYTES_GAS>,
     // Constrain for SSTORE reentrancy sentry.
@@ -81,7 +81,8 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F> {
             is_warm.expr(),
         );
 
-        let sload_gas_cost = SloadGasGadget::construct(cb, is_warm.expr());
+        let gas_schedule = GasScheduleGadget::construct(cb, tx_id.expr());
+        let sload_gas_cost = SloadGasGadget::construct(cb, is_warm.expr(), &gas_schedule);
         let sstore_gas_cost = cb.condition(is_sstore.expr().0, |cb| {
             cb.stack_pop(phase2_value.expr());
 
@@ -99,6 +100,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F> {
                 phase2_value_prev.clone(),
                 phase2_original_value.clone(),
                 is_warm.clone(),
+                &gas_schedule,
             )
         });
 
@@ -143,6 +145,7 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F> {
             phase2_original_value,
             is_warm,
             is_sstore,
+            gas_schedule,
             sstore_gas_cost,
             insufficient_gas_cost,
             insufficient_gas_sentry,
@@ -163,16 +166,18 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F> {
         let is_sstore = opcode == OpcodeId::SSTORE;
         let key = block.rws[step.rw_indices[3]].stack_value();
         let (is_warm, _) = block.rws[step.rw_indices[4]].tx_access_list_value_pair();
+        let gas_schedule =
+            self.gas_schedule
+                .assign(region, offset, tx.block_number, &block.gas_schedules)?;
 
         let (value, value_prev, original_value, gas_cost) = if is_sstore {
             let value = block.rws[step.rw_indices[5]].stack_value();
             let (_, value_prev, _, original_value) =
                 block.rws[step.rw_indices[6]].storage_value_aux();
-            let gas_cost =
-                cal_sstore_gas_cost_for_assignment(value, value_prev, original_value, is_warm);
+            let gas_cost = gas_schedule.sstore(value, value_prev, original_value, is_warm);
             (value, value_prev, original_value, gas_cost)
         } else {
-            let gas_cost = cal_sload_gas_cost_for_assignment(is_warm);
+            let gas_cost = gas_schedule.sload(is_warm);
             (U256::zero(), U256::zero(), U256::zero(), gas_cost)
         };
 
@@ -249,13 +254,10 @@ impl<F: Field> ExecutionGadget<F> for ErrorOOGSloadSstoreGadget<F> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{
-        evm_circuit::{test::rand_bytes, util::common_gadget::cal_sstore_gas_cost_for_assignment},
-        test_util::CircuitTestBuilder,
-    };
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
     use eth_types::{
         bytecode,
-        evm_types::{GasCost, OpcodeId},
+        evm_types::{GasCost, GasSchedule, OpcodeId},
         Bytecode, U256,
     };
     use mock::{
@@ -390,14 +392,14 @@ mod test {
                 SLOAD
             };
             let mut gas_cost =
-                OpcodeId::PUSH32.constant_gas_cost().0 + cal_sload_gas_cost_for_assignment(false);
+                OpcodeId::PUSH32.constant_gas_cost().0 + GasSchedule::default().sload(false);
             if is_warm {
                 bytecode.append(&bytecode! {
                     PUSH32(key)
                     SLOAD
                 });
-                gas_cost += OpcodeId::PUSH32.constant_gas_cost().0
-                    + cal_sload_gas_cost_for_assignment(true);
+                gas_cost +=
+                    OpcodeId::PUSH32.constant_gas_cost().0 + GasSchedule::default().sload(true);
             }
 
             Self {
@@ -419,12 +421,8 @@ mod test {
                 PUSH32(key)
                 SSTORE
             };
-            let sstore_gas_cost = cal_sstore_gas_cost_for_assignment(
-                value_prev,
-                original_value,
-                original_value,
-                false,
-            );
+            let sstore_gas_cost =
+                GasSchedule::default().sstore(value_prev, original_value, original_value, false);
             let mut gas_cost = 2 * OpcodeId::PUSH32.constant_gas_cost().0
                 + max(
                     sstore_gas_cost,
@@ -436,12 +434,8 @@ mod test {
                     PUSH32(key)
                     SSTORE
                 });
-                let sstore_gas_cost = cal_sstore_gas_cost_for_assignment(
-                    value_prev,
-                    original_value,
-                    original_value,
-                    true,
-                );
+                let sstore_gas_cost =
+                    GasSchedule::default().sstore(value_prev, original_value, original_value, true);
                 gas_cost += 2 * OpcodeId::PUSH32.constant_gas_cost().0
                     + max(
                         sstore_gas_cost,
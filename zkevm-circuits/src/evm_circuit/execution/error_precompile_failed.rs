@@ -175,7 +175,8 @@ mod test {
 
     lazy_static::lazy_static! {
         static ref TEST_VECTOR: Vec<PrecompileCallArgs> = {
-            vec![
+            #[allow(unused_mut)]
+            let mut test_vector = vec![
                 // OOG error for Precompile identity
                 PrecompileCallArgs {
                     name: "insufficient gas (precompile call should fail)",
@@ -196,7 +197,18 @@ mod test {
                 },
 
                 // TODO: add more failed (including OOG) cases for Precompile.
-            ]
+            ];
+            #[cfg(feature = "cancun")]
+            test_vector.push(PrecompileCallArgs {
+                name: "point evaluation with invalid input (precompile call should fail)",
+                call_data_offset: 0x00.into(),
+                call_data_length: 0x20.into(),
+                ret_offset: 0x00.into(),
+                ret_size: 0x40.into(),
+                address: PrecompileCalls::PointEvaluation.address().to_word(),
+                ..Default::default()
+            });
+            test_vector
         };
     }
 
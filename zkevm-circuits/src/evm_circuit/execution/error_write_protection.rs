@@ -44,11 +44,12 @@ impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
         // max_degree. otherwise need to do fixed lookup for these opcodes
         // checking.
         cb.require_in_set(
-            "ErrorWriteProtection only happens in [CALL, SSTORE, CREATE, CREATE2, SELFDESTRUCT, LOG0..4 ]",
+            "ErrorWriteProtection only happens in [CALL, SSTORE, TSTORE, CREATE, CREATE2, SELFDESTRUCT, LOG0..4 ]",
             opcode.expr(),
             vec![
                 OpcodeId::CALL.expr(),
                 OpcodeId::SSTORE.expr(),
+                OpcodeId::TSTORE.expr(),
                 OpcodeId::CREATE.expr(),
                 OpcodeId::CREATE2.expr(),
                 OpcodeId::SELFDESTRUCT.expr(),
@@ -66,8 +67,8 @@ impl<F: Field> ExecutionGadget<F> for ErrorWriteProtectionGadget<F> {
             cb.stack_pop(gas_word.expr());
             cb.stack_pop(code_address_word.expr());
             cb.stack_pop(value.expr());
-            //cb.require_zero("value of call is not zero",
-            // is_value_zero.expr());
+            // a CALL without value doesn't break write protection.
+            cb.require_zero("value of call is not zero", is_value_zero.expr());
         });
 
         // current call context is readonly
@@ -218,15 +219,47 @@ mod test {
     }
 
     #[test]
-    fn test_write_protection() {
-        // test sstore with write protection error
-        test_internal_write_protection(false);
-        // test call with write protection error
-        test_internal_write_protection(true);
+    fn test_write_protection_sstore() {
+        test_internal_write_protection(OpcodeId::SSTORE);
+    }
+
+    #[cfg(feature = "cancun")]
+    #[test]
+    fn test_write_protection_tstore() {
+        test_internal_write_protection(OpcodeId::TSTORE);
+    }
+
+    #[test]
+    fn test_write_protection_call() {
+        test_internal_write_protection(OpcodeId::CALL);
+    }
+
+    #[test]
+    fn test_write_protection_create() {
+        test_internal_write_protection(OpcodeId::CREATE);
+        test_internal_write_protection(OpcodeId::CREATE2);
+    }
+
+    #[test]
+    fn test_write_protection_selfdestruct() {
+        test_internal_write_protection(OpcodeId::SELFDESTRUCT);
+    }
+
+    #[test]
+    fn test_write_protection_log() {
+        for opcode in [
+            OpcodeId::LOG0,
+            OpcodeId::LOG1,
+            OpcodeId::LOG2,
+            OpcodeId::LOG3,
+            OpcodeId::LOG4,
+        ] {
+            test_internal_write_protection(opcode);
+        }
     }
 
     // ErrorWriteProtection error happen in internal call
-    fn test_internal_write_protection(is_call: bool) {
+    fn test_internal_write_protection(opcode: OpcodeId) {
         let mut caller_bytecode = bytecode! {
             PUSH1(0)
             PUSH1(0)
@@ -242,31 +275,52 @@ mod test {
             STOP
         });
 
-        let mut callee_bytecode = bytecode! {
-            PUSH1(42)
-            PUSH1(0x02)
-        };
-
-        if is_call {
-            callee_bytecode.append(&bytecode! {
+        // push the operands of the opcode, which then got error: ErrorWriteProtection
+        let mut callee_bytecode = match opcode {
+            OpcodeId::SSTORE | OpcodeId::TSTORE => bytecode! {
+                PUSH1(42)
+                PUSH1(0x02)
+            },
+            OpcodeId::CALL => bytecode! {
                 PUSH1(0)
                 PUSH1(0)
                 PUSH1(10)
+                PUSH1(10)
                 PUSH1(200)  // non zero value
                 PUSH20(Address::repeat_byte(0xff).to_word())
                 PUSH2(10000)  // gas
-                //this call got error: ErrorWriteProtection
-                CALL
-                RETURN
-                STOP
-            });
-        } else {
-            callee_bytecode.append(&bytecode! {
-                // this SSTORE got error: ErrorWriteProtection
-                SSTORE
-                STOP
-            });
-        }
+            },
+            OpcodeId::CREATE => bytecode! {
+                PUSH1(0) // size
+                PUSH1(0) // offset
+                PUSH1(0) // value
+            },
+            OpcodeId::CREATE2 => bytecode! {
+                PUSH1(0) // salt
+                PUSH1(0) // size
+                PUSH1(0) // offset
+                PUSH1(0) // value
+            },
+            OpcodeId::SELFDESTRUCT => bytecode! {
+                PUSH20(Address::repeat_byte(0xfe).to_word()) // beneficiary
+            },
+            _ => {
+                assert!(opcode.is_log());
+                let mut code = Bytecode::default();
+                for topic in 0..opcode.postfix().unwrap() {
+                    code.push(1, Word::from(topic));
+                }
+                code.append(&bytecode! {
+                    PUSH1(32) // size
+                    PUSH1(0) // offset
+                });
+                code
+            }
+        };
+        callee_bytecode.write_op(opcode);
+        callee_bytecode.append(&bytecode! {
+            STOP
+        });
 
         test_ok(
             Account {
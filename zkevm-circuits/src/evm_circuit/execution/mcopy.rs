@@ -0,0 +1,221 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_MEMORY_WORD_SIZE,
+        step::ExecutionState,
+        util::{
+            common_gadget::SameContextGadget,
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
+                Transition::{Delta, To},
+            },
+            memory_gadget::{
+                CommonMemoryAddressGadget, MemoryAddressGadget, MemoryCopierGasGadget,
+                MemoryExpansionGadget,
+            },
+            CachedRegion, Cell,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    util::Expr,
+};
+use bus_mapping::{circuit_input_builder::CopyDataType, evm::OpcodeId};
+use eth_types::{evm_types::GasCost, Field, ToScalar};
+use gadgets::util::not;
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+#[derive(Clone, Debug)]
+pub(crate) struct MCopyGadget<F> {
+    same_context: SameContextGadget<F>,
+    /// The memory range that is read from.
+    src_memory_addr: MemoryAddressGadget<F>,
+    /// The memory range that is written to. It shares its length with
+    /// `src_memory_addr`.
+    dst_memory_addr: MemoryAddressGadget<F>,
+    /// Opcode MCOPY has a dynamic gas cost:
+    /// gas_code = static_gas * minimum_word_size + memory_expansion_cost,
+    /// where the memory expands to cover both the source and the destination.
+    memory_expansion: MemoryExpansionGadget<F, 2, N_BYTES_MEMORY_WORD_SIZE>,
+    memory_copier_gas: MemoryCopierGasGadget<F, { GasCost::COPY }>,
+    /// RW inverse counter from the copy table at the start of related copy
+    /// steps.
+    copy_rwc_inc: Cell<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for MCopyGadget<F> {
+    const NAME: &'static str = "MCOPY";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::MCOPY;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+
+        let dst_offset = cb.query_cell_phase2();
+        let src_offset = cb.query_cell_phase2();
+        let size = cb.query_word_rlc();
+
+        // 1. Pop dst_offset, src_offset, length from stack
+        cb.stack_pop(dst_offset.expr());
+        cb.stack_pop(src_offset.expr());
+        cb.stack_pop(size.expr());
+
+        // 2. Both ranges have the same length, and expand the memory.
+        let src_memory_addr = MemoryAddressGadget::construct(cb, src_offset, size.clone());
+        let dst_memory_addr = MemoryAddressGadget::construct(cb, dst_offset, size);
+        let memory_expansion = MemoryExpansionGadget::construct(
+            cb,
+            [src_memory_addr.address(), dst_memory_addr.address()],
+        );
+        let memory_copier_gas = MemoryCopierGasGadget::construct(
+            cb,
+            dst_memory_addr.length(),
+            memory_expansion.gas_cost(),
+        );
+
+        // 3. Copy within the memory of the current call.
+        let copy_rwc_inc = cb.query_cell();
+        cb.condition(dst_memory_addr.has_length(), |cb| {
+            cb.copy_table_lookup(
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                cb.curr.state.call_id.expr(),
+                CopyDataType::Memory.expr(),
+                src_memory_addr.offset(),
+                src_memory_addr.address(),
+                dst_memory_addr.offset(),
+                dst_memory_addr.length(),
+                0.expr(), // for MCOPY rlc_acc is 0
+                copy_rwc_inc.expr(),
+            );
+        });
+        cb.condition(not::expr(dst_memory_addr.has_length()), |cb| {
+            cb.require_zero(
+                "if no bytes to copy, copy table rwc inc == 0",
+                copy_rwc_inc.expr(),
+            );
+        });
+
+        // State transition
+        let step_state_transition = StepStateTransition {
+            rw_counter: Delta(cb.rw_counter_offset()),
+            program_counter: Delta(1.expr()),
+            stack_pointer: Delta(3.expr()),
+            gas_left: Delta(
+                -(OpcodeId::MCOPY.constant_gas_cost().expr() + memory_copier_gas.gas_cost()),
+            ),
+            memory_word_size: To(memory_expansion.next_memory_word_size()),
+            ..Default::default()
+        };
+        let same_context = SameContextGadget::construct(cb, opcode, step_state_transition);
+
+        Self {
+            same_context,
+            src_memory_addr,
+            dst_memory_addr,
+            memory_expansion,
+            memory_copier_gas,
+            copy_rwc_inc,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        _tx: &Transaction,
+        _call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        self.same_context.assign_exec_step(region, offset, step)?;
+
+        let [dst_offset, src_offset, size] =
+            [0, 1, 2].map(|i| block.rws[step.rw_indices[i]].stack_value());
+
+        let src_address = self
+            .src_memory_addr
+            .assign(region, offset, src_offset, size)?;
+        let dst_address = self
+            .dst_memory_addr
+            .assign(region, offset, dst_offset, size)?;
+
+        let (_, memory_expansion_cost) = self.memory_expansion.assign(
+            region,
+            offset,
+            step.memory_word_size(),
+            [src_address, dst_address],
+        )?;
+        self.memory_copier_gas
+            .assign(region, offset, size.as_u64(), memory_expansion_cost)?;
+
+        self.copy_rwc_inc.assign(
+            region,
+            offset,
+            Value::known(
+                step.copy_rw_counter_delta
+                    .to_scalar()
+                    .expect("unexpected U256 -> Scalar conversion failure"),
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod test {
+    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
+    use eth_types::{bytecode, Word};
+    use mock::TestContext;
+
+    fn test_ok(dst_offset: Word, src_offset: Word, size: usize) {
+        let code = bytecode! {
+            .op_mstore(0x00, Word::from_big_endian(&rand_bytes(32)))
+            .op_mstore(0x20, Word::from_big_endian(&rand_bytes(32)))
+            PUSH32(size)
+            PUSH32(src_offset)
+            PUSH32(dst_offset)
+            MCOPY
+            STOP
+        };
+
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(code).unwrap(),
+        )
+        .run();
+    }
+
+    #[test]
+    fn mcopy_gadget_simple() {
+        test_ok(0x40.into(), 0x00.into(), 0x20);
+    }
+
+    #[test]
+    fn mcopy_gadget_unaligned() {
+        test_ok(0x45.into(), 0x03.into(), 0x31);
+    }
+
+    #[test]
+    fn mcopy_gadget_overlapping_backward() {
+        test_ok(0x00.into(), 0x08.into(), 0x30);
+    }
+
+    #[test]
+    fn mcopy_gadget_overlapping_forward() {
+        test_ok(0x08.into(), 0x00.into(), 0x18);
+        // the first destination word is the second source word
+        test_ok(0x20.into(), 0x00.into(), 0x40);
+        test_ok(0x25.into(), 0x03.into(), 0x3d);
+    }
+
+    #[test]
+    fn mcopy_gadget_expand_from_source() {
+        // the source lies beyond the current memory and reads zeros
+        test_ok(0x00.into(), 0x100.into(), 0x20);
+    }
+
+    #[test]
+    fn mcopy_gadget_zero_length() {
+        test_ok(Word::MAX, Word::MAX, 0);
+    }
+}
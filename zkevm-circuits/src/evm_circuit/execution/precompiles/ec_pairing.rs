@@ -39,8 +39,6 @@ pub struct EcPairingGadget<F> {
     /// EVM, we need 3 binary bits for a max value of [1, 0, 0].
     n_pairs: Cell<F>,
     n_pairs_cmp: BinaryNumberGadget<F, 3>,
-    /// keccak_rand ^ 64.
-    rand_pow_64: Cell<F>,
 
     evm_input_g1_rlc: [Cell<F>; N_PAIRING_PER_OP],
     evm_input_g2_rlc: [Cell<F>; N_PAIRING_PER_OP],
@@ -66,7 +64,7 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
         let (evm_input_rlc, output, n_pairs) =
             (cb.query_cell_phase2(), cb.query_bool(), cb.query_cell());
         let n_pairs_cmp = BinaryNumberGadget::construct(cb, n_pairs.expr());
-        let rand_pow_64 = cb.query_cell_phase2();
+        let [_, rand_pow_64] = cb.keccak_word_powers_of_randomness();
         let (rand_pow_128, rand_pow_192, rand_pow_384, rand_pow_576) = {
             let rand_pow_128 = rand_pow_64.expr() * rand_pow_64.expr();
             let rand_pow_192 = rand_pow_128.expr() * rand_pow_64.expr();
@@ -74,7 +72,6 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             let rand_pow_576 = rand_pow_384.expr() * rand_pow_192.expr();
             (rand_pow_128, rand_pow_192, rand_pow_384, rand_pow_576)
         };
-        cb.pow_of_rand_lookup(64.expr(), rand_pow_64.expr());
 
         let [is_success, callee_address, caller_id, call_data_offset, call_data_length, return_data_offset, return_data_length] =
             [
@@ -224,7 +221,6 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             output,
             n_pairs,
             n_pairs_cmp,
-            rand_pow_64,
 
             evm_input_g1_rlc,
             evm_input_g2_rlc,
@@ -287,9 +283,6 @@ impl<F: Field> ExecutionGadget<F> for EcPairingGadget<F> {
             self.n_pairs
                 .assign(region, offset, Value::known(F::from(n_pairs as u64)))?;
             self.n_pairs_cmp.assign(region, offset, n_pairs)?;
-            // keccak_rand ^ 64.
-            self.rand_pow_64
-                .assign(region, offset, keccak_rand.map(|r| r.pow(&[64, 0, 0, 0])))?;
             // G1, G2 points from EVM.
             for i in 0..N_PAIRING_PER_OP {
                 let g1_bytes = aux_data.0.pairs[i].g1_bytes_be();
@@ -3,7 +3,7 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::SameContextGadget,
+            common_gadget::{IsShanghaiGadget, SameContextGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, StepStateTransition,
                 Transition::Delta,
@@ -13,6 +13,7 @@ use crate::{
         },
         witness::{Block, Call, ExecStep, Transaction},
     },
+    table::CallContextFieldTag,
     util::Expr,
 };
 use array_init::array_init;
@@ -23,6 +24,8 @@ use halo2_proofs::{circuit::Value, plonk::Error};
 pub(crate) struct PushGadget<F> {
     same_context: SameContextGadget<F>,
     is_push0: IsZeroGadget<F>,
+    tx_id: Cell<F>,
+    is_shanghai: IsShanghaiGadget<F>,
     value: Word<F>,
     selectors: [Cell<F>; 32],
 }
@@ -37,6 +40,14 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
 
         let is_push0 = IsZeroGadget::construct(cb, "", opcode.expr() - OpcodeId::PUSH0.expr());
 
+        // `PUSH0` is only defined from Shanghai on
+        let (tx_id, is_shanghai) = cb.condition(is_push0.expr(), |cb| {
+            let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+            let is_shanghai = IsShanghaiGadget::construct(cb, tx_id.expr());
+            cb.require_equal("PUSH0 needs Shanghai", is_shanghai.expr(), 1.expr());
+            (tx_id, is_shanghai)
+        });
+
         let value = cb.query_word_rlc();
         cb.condition(not::expr(is_push0.expr()), |cb| cb.stack_push(value.expr()));
 
@@ -107,7 +118,7 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
 
         // State transition
         let step_state_transition = StepStateTransition {
-            rw_counter: Delta(1.expr()),
+            rw_counter: Delta(1.expr() + is_push0.expr()),
             program_counter: Delta(opcode.expr() - (OpcodeId::PUSH0.as_u64() - 1).expr()),
             stack_pointer: Delta((-1).expr()),
             gas_left: Delta(select::expr(
@@ -122,6 +133,8 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         Self {
             same_context,
             is_push0,
+            tx_id,
+            is_shanghai,
             value,
             selectors,
         }
@@ -132,7 +145,7 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         block: &Block<F>,
-        _: &Transaction,
+        tx: &Transaction,
         _: &Call,
         step: &ExecStep,
     ) -> Result<(), Error> {
@@ -145,6 +158,13 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
             F::from(opcode.as_u64()) - F::from(OpcodeId::PUSH0.as_u64()),
         )?;
 
+        if opcode == OpcodeId::PUSH0 {
+            self.tx_id
+                .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+            self.is_shanghai
+                .assign(region, offset, tx.block_number, &block.chain_spec)?;
+        }
+
         let value = if opcode.is_push_with_data() {
             block.rws[step.rw_indices[0]].stack_value()
         } else {
@@ -168,8 +188,14 @@ impl<F: Field> ExecutionGadget<F> for PushGadget<F> {
 
 #[cfg(test)]
 mod test {
-    use crate::{evm_circuit::test::rand_bytes, test_util::CircuitTestBuilder};
-    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode};
+    use crate::{
+        evm_circuit::test::rand_bytes,
+        state_circuit::RwTableView,
+        table::{CallContextFieldTag, RwTableTag},
+        test_util::CircuitTestBuilder,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, Bytecode, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
     use mock::TestContext;
 
     fn test_ok(opcode: OpcodeId, bytes: &[u8]) {
@@ -183,9 +209,29 @@ mod test {
         }
         bytecode.op_stop();
 
+        let value = Word::from_big_endian(bytes);
         CircuitTestBuilder::new_from_test_ctx(
             TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
         )
+        .block_checks(Box::new(move |block| {
+            let step = block.txs[0]
+                .steps
+                .iter()
+                .find(|step| step.opcode == Some(opcode))
+                .unwrap();
+            let rws = RwTableView::from_step(block, step);
+            if opcode == OpcodeId::PUSH0 {
+                assert_eq!(rws.tags(), [RwTableTag::CallContext, RwTableTag::Stack]);
+                assert_eq!(
+                    rws.call_context().collect::<Vec<_>>(),
+                    [(CallContextFieldTag::TxId, Word::one())]
+                );
+            } else {
+                assert_eq!(rws.tags(), [RwTableTag::Stack]);
+            }
+            assert_eq!(rws.stack().collect::<Vec<_>>(), [(true, 1023, value)]);
+            rws.assert_lookups_of::<Fr>(step);
+        }))
         .run();
     }
 
@@ -211,6 +257,27 @@ mod test {
         );
     }
 
+    #[cfg(feature = "shanghai")]
+    #[test]
+    fn push0_before_shanghai() {
+        let bytecode = bytecode! {
+            PUSH0
+            STOP
+        };
+        CircuitTestBuilder::new_from_test_ctx(
+            TestContext::<2, 1>::simple_ctx_with_bytecode(bytecode).unwrap(),
+        )
+        .block_modifier(Box::new(|block| {
+            block.chain_spec.shanghai_block = Some(u64::MAX);
+        }))
+        .evm_checks(Box::new(|prover, gate_rows, lookup_rows| {
+            assert!(prover
+                .verify_at_rows_par(gate_rows.iter().cloned(), lookup_rows.iter().cloned())
+                .is_err())
+        }))
+        .run();
+    }
+
     #[ignore]
     #[test]
     fn push_gadget_out_of_range() {
@@ -211,7 +211,11 @@ impl<F: Field> ExecutionGadget<F> for ReturnRevertGadget<F> {
 
         // Case B in the specs.
         cb.condition(is_root.expr(), |cb| {
-            cb.require_next_state(ExecutionState::EndTx);
+            // The accounts destructed in the tx are deleted before EndTx.
+            let is_to_end_tx = cb
+                .next
+                .execution_state_selector([ExecutionState::DestructAccount, ExecutionState::EndTx]);
+            cb.require_true("Go to DestructAccount or EndTx", is_to_end_tx);
             cb.call_context_lookup(
                 false.expr(),
                 None,
@@ -0,0 +1,478 @@
+use crate::{
+    evm_circuit::{
+        execution::ExecutionGadget,
+        param::N_BYTES_ACCOUNT_ADDRESS,
+        step::ExecutionState,
+        util::{
+            common_gadget::{RestoreContextGadget, TransferGadget},
+            constraint_builder::{
+                ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
+                Transition::{Delta, Same},
+            },
+            from_bytes,
+            math_gadget::{IsEqualGadget, IsZeroGadget},
+            not, CachedRegion, Cell, Word,
+        },
+        witness::{Block, Call, ExecStep, Transaction},
+    },
+    table::{AccountFieldTag, CallContextFieldTag},
+    util::Expr,
+};
+use eth_types::{evm_types::GasCost, Field, ToAddress, ToLittleEndian, ToScalar, U256};
+use halo2_proofs::{circuit::Value, plonk::Error};
+
+/// Gadget for SELFDESTRUCT. The balance of the current account is sent to the
+/// beneficiary, then the account is flagged as destructed, burning the balance
+/// if it was sent to itself. The account keeps its code until the end of the
+/// tx, where a `DestructAccount` step deletes it.
+///
+/// Before EIP-6780 the account is always destructed. With the `cancun` feature
+/// it is only destructed if it was created in the same tx, which is read from
+/// the created flag written by the `BeginTx` or `CREATE*` step which created
+/// it.
+#[derive(Clone, Debug)]
+pub(crate) struct SelfDestructGadget<F> {
+    opcode: Cell<F>,
+    beneficiary: Word<F>,
+    tx_id: Cell<F>,
+    callee_address: Cell<F>,
+    reversion_info: ReversionInfo<F>,
+    is_warm: Cell<F>,
+    beneficiary_code_hash: Cell<F>,
+    beneficiary_not_exists: IsZeroGadget<F>,
+    value: Word<F>,
+    value_is_zero: IsZeroGadget<F>,
+    is_self: IsEqualGadget<F>,
+    transfer: TransferGadget<F>,
+    is_created: Cell<F>,
+    is_destructed_prev: Cell<F>,
+    restore_context: RestoreContextGadget<F>,
+}
+
+impl<F: Field> ExecutionGadget<F> for SelfDestructGadget<F> {
+    const NAME: &'static str = "SELFDESTRUCT";
+
+    const EXECUTION_STATE: ExecutionState = ExecutionState::SELFDESTRUCT;
+
+    fn configure(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        cb.opcode_lookup(opcode.expr(), 1.expr());
+
+        let beneficiary = cb.query_word_rlc();
+        cb.stack_pop(beneficiary.expr());
+        let beneficiary_address = from_bytes::expr(&beneficiary.cells[..N_BYTES_ACCOUNT_ADDRESS]);
+
+        let tx_id = cb.call_context(None, CallContextFieldTag::TxId);
+        let callee_address = cb.call_context(None, CallContextFieldTag::CalleeAddress);
+        let mut reversion_info = cb.reversion_info_read(None);
+
+        let is_warm = cb.query_bool();
+        cb.account_access_list_write(
+            tx_id.expr(),
+            beneficiary_address.expr(),
+            1.expr(),
+            is_warm.expr(),
+            Some(&mut reversion_info),
+        );
+        let beneficiary_code_hash = cb.query_cell_phase2();
+        // For non-existing accounts the code_hash must be 0 in the rw_table.
+        cb.account_read(
+            beneficiary_address.expr(),
+            AccountFieldTag::CodeHash,
+            beneficiary_code_hash.expr(),
+        );
+        let beneficiary_not_exists = IsZeroGadget::construct(cb, "", beneficiary_code_hash.expr());
+
+        // The whole balance is sent.
+        let value = cb.query_word_rlc();
+        cb.account_read(
+            callee_address.expr(),
+            AccountFieldTag::Balance,
+            value.expr(),
+        );
+        let value_is_zero = IsZeroGadget::construct(cb, "", value.expr());
+
+        // Sending the balance to the account itself is a no-op.
+        let is_self =
+            IsEqualGadget::construct(cb, beneficiary_address.expr(), callee_address.expr());
+        let transfer = cb.condition(not::expr(is_self.expr()), |cb| {
+            TransferGadget::construct(
+                cb,
+                callee_address.expr(),
+                beneficiary_address.expr(),
+                not::expr(beneficiary_not_exists.expr()),
+                0.expr(),
+                value.clone(),
+                &mut reversion_info,
+            )
+        });
+
+        let is_created = cb.query_bool();
+        let is_destructed = if cfg!(feature = "cancun") {
+            cb.account_created_read(tx_id.expr(), callee_address.expr(), is_created.expr());
+            is_created.expr()
+        } else {
+            1.expr()
+        };
+        let is_burnt = is_self.expr() * not::expr(value_is_zero.expr());
+        let is_destructed_prev = cb.condition(is_destructed.expr(), |cb| {
+            let is_destructed_prev = cb.query_bool();
+            cb.account_destructed_write(
+                tx_id.expr(),
+                callee_address.expr(),
+                is_destructed_prev.expr(),
+                Some(&mut reversion_info),
+            );
+            cb.condition(is_burnt.expr(), |cb| {
+                cb.account_write(
+                    callee_address.expr(),
+                    AccountFieldTag::Balance,
+                    0.expr(),
+                    value.expr(),
+                    Some(&mut reversion_info),
+                );
+            });
+            is_destructed_prev
+        });
+
+        // Call ends with SELFDESTRUCT must be successful
+        cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
+
+        // No refund since EIP-3529.
+        let gas_cost = GasCost::SELFDESTRUCT.expr()
+            + not::expr(is_warm.expr()) * GasCost::COLD_ACCOUNT_ACCESS.expr()
+            + beneficiary_not_exists.expr()
+                * not::expr(value_is_zero.expr())
+                * GasCost::NEW_ACCOUNT.expr();
+        // +1 Write TxAccessListAccount (beneficiary)
+        // + Writes of the transfer when the balance is sent to another account
+        // +1 Write TxDestructedAccount (callee) when destructed
+        // +1 Write Account (callee) Balance when burnt
+        let reversible_write_counter_increase = 1.expr()
+            + not::expr(is_self.expr()) * transfer.reversible_w_delta()
+            + is_destructed.expr() * (1.expr() + is_burnt);
+
+        // The accounts destructed in the tx are deleted before EndTx.
+        let is_to_end_tx = cb
+            .next
+            .execution_state_selector([ExecutionState::DestructAccount, ExecutionState::EndTx]);
+        cb.require_equal(
+            "Go to DestructAccount or EndTx only when is_root",
+            cb.curr.state.is_root.expr(),
+            is_to_end_tx,
+        );
+
+        // When it's a root call
+        cb.condition(cb.curr.state.is_root.expr(), |cb| {
+            cb.require_step_state_transition(StepStateTransition {
+                call_id: Same,
+                rw_counter: Delta(cb.rw_counter_offset()),
+                gas_left: Delta(-gas_cost.expr()),
+                reversible_write_counter: Delta(reversible_write_counter_increase.expr()),
+                ..StepStateTransition::any()
+            });
+        });
+
+        // When it's an internal call. The gas cost of the step is not
+        // refunded to the caller, like a memory expansion cost.
+        let restore_context = cb.condition(1.expr() - cb.curr.state.is_root.expr(), |cb| {
+            RestoreContextGadget::construct(
+                cb,
+                true.expr(),
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                gas_cost.expr(),
+                reversible_write_counter_increase.expr(),
+            )
+        });
+
+        Self {
+            opcode,
+            beneficiary,
+            tx_id,
+            callee_address,
+            reversion_info,
+            is_warm,
+            beneficiary_code_hash,
+            beneficiary_not_exists,
+            value,
+            value_is_zero,
+            is_self,
+            transfer,
+            is_created,
+            is_destructed_prev,
+            restore_context,
+        }
+    }
+
+    fn assign_exec_step(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block: &Block<F>,
+        tx: &Transaction,
+        call: &Call,
+        step: &ExecStep,
+    ) -> Result<(), Error> {
+        let opcode = step.opcode.unwrap();
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+
+        let beneficiary = block.rws[step.rw_indices[0]].stack_value();
+        self.beneficiary
+            .assign(region, offset, Some(beneficiary.to_le_bytes()))?;
+        self.tx_id
+            .assign(region, offset, Value::known(F::from(tx.id as u64)))?;
+        self.callee_address.assign(
+            region,
+            offset,
+            Value::known(call.callee_address.to_scalar().unwrap()),
+        )?;
+        self.reversion_info.assign(
+            region,
+            offset,
+            call.rw_counter_end_of_reversion,
+            call.is_persistent,
+        )?;
+
+        let (_, is_warm) = block.rws[step.rw_indices[5]].tx_access_list_value_pair();
+        self.is_warm
+            .assign(region, offset, Value::known(F::from(is_warm)))?;
+        let beneficiary_code_hash = block.rws[step.rw_indices[6]].account_value_pair().0;
+        self.beneficiary_code_hash.assign(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+        self.beneficiary_not_exists.assign_value(
+            region,
+            offset,
+            region.code_hash(beneficiary_code_hash),
+        )?;
+
+        let value = block.rws[step.rw_indices[7]].account_value_pair().0;
+        self.value
+            .assign(region, offset, Some(value.to_le_bytes()))?;
+        self.value_is_zero
+            .assign_value(region, offset, region.word_rlc(value))?;
+
+        let beneficiary_address = beneficiary.to_address().to_scalar().unwrap();
+        let callee_address = call.callee_address.to_scalar().unwrap();
+        self.is_self
+            .assign(region, offset, beneficiary_address, callee_address)?;
+        let is_self = beneficiary_address == callee_address;
+
+        let mut rw_offset = 8;
+        let (sender_balances, receiver_balances) = if !is_self && !value.is_zero() {
+            if beneficiary_code_hash.is_zero() {
+                // account creation by code hash update
+                rw_offset += if cfg!(feature = "scroll") { 4 } else { 2 };
+            }
+            let pairs = [rw_offset, rw_offset + 1]
+                .map(|i| block.rws[step.rw_indices[i]].account_balance_pair());
+            rw_offset += 2;
+            (pairs[0], pairs[1])
+        } else {
+            ((U256::zero(), value), (value, U256::zero()))
+        };
+        self.transfer
+            .assign(region, offset, sender_balances, receiver_balances, value)?;
+
+        let is_destructed = if cfg!(feature = "cancun") {
+            let is_created = block.rws[step.rw_indices[rw_offset]]
+                .tx_created_account_value_pair()
+                .0;
+            self.is_created
+                .assign(region, offset, Value::known(F::from(is_created)))?;
+            rw_offset += 1;
+            is_created
+        } else {
+            true
+        };
+        let is_destructed_prev = if is_destructed {
+            let is_destructed_prev = block.rws[step.rw_indices[rw_offset]]
+                .tx_destructed_account_value_pair()
+                .1;
+            rw_offset += 1;
+            if is_self && !value.is_zero() {
+                rw_offset += 1;
+            }
+            is_destructed_prev
+        } else {
+            false
+        };
+        self.is_destructed_prev.assign(
+            region,
+            offset,
+            Value::known(F::from(is_destructed_prev)),
+        )?;
+
+        // IsSuccess
+        rw_offset += 1;
+        if !call.is_root {
+            self.restore_context
+                .assign(region, offset, block, call, step, rw_offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::CircuitTestBuilder;
+    use eth_types::{address, bytecode, Address, Bytecode, ToWord, Word};
+    use lazy_static::lazy_static;
+    use mock::{eth, TestContext, MOCK_ACCOUNTS};
+
+    lazy_static! {
+        static ref CALLER: Address = address!("0x0000000000000000000000000000000000000010");
+        static ref CONTRACT: Address = address!("0x0000000000000000000000000000000000000020");
+        static ref BENEFICIARY: Address = address!("0x0000000000000000000000000000000000000030");
+    }
+
+    fn selfdestruct_code(beneficiary: Address) -> Bytecode {
+        bytecode! {
+            PUSH20(beneficiary.to_word())
+            SELFDESTRUCT
+        }
+    }
+
+    fn test_ok(beneficiary: Address, balance: Word, is_root: bool) {
+        let ctx = TestContext::<4, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(*CALLER).code(bytecode! {
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH1(0)
+                    PUSH20(CONTRACT.to_word())
+                    GAS
+                    CALL
+                    STOP
+                });
+                accs[2]
+                    .address(*CONTRACT)
+                    .balance(balance)
+                    .code(selfdestruct_code(beneficiary));
+                accs[3].address(*BENEFICIARY).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(if is_root { *CONTRACT } else { *CALLER })
+                    .gas(100000u64.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_existing_beneficiary() {
+        for is_root in [true, false] {
+            test_ok(*BENEFICIARY, eth(2), is_root);
+            test_ok(*BENEFICIARY, Word::zero(), is_root);
+        }
+    }
+
+    #[test]
+    fn selfdestruct_gadget_new_beneficiary() {
+        let beneficiary = Address::repeat_byte(0xff);
+        for is_root in [true, false] {
+            test_ok(beneficiary, eth(2), is_root);
+            test_ok(beneficiary, Word::zero(), is_root);
+        }
+    }
+
+    #[test]
+    fn selfdestruct_gadget_to_itself() {
+        for is_root in [true, false] {
+            test_ok(*CONTRACT, eth(2), is_root);
+        }
+    }
+
+    // An account created by CREATE which self destructs in a later call of the
+    // same tx, outside its creation call
+    #[test]
+    fn selfdestruct_gadget_created_by_another_call() {
+        let runtime_code = selfdestruct_code(*BENEFICIARY).code();
+        let runtime_len = runtime_code.len();
+        let mut init_code = Bytecode::default();
+        init_code
+            .push(runtime_len as u8, Word::from_big_endian(&runtime_code))
+            .append(&bytecode! {
+                PUSH1(0)
+                MSTORE
+                PUSH1(runtime_len)
+                PUSH1(32 - runtime_len)
+                RETURN
+            });
+        let init_code = init_code.code();
+        let creator_code = bytecode! {
+            PUSH32(Word::from_big_endian(&init_code))
+            PUSH1(0)
+            MSTORE
+            PUSH1(init_code.len())
+            PUSH1(32 - init_code.len())
+            PUSH1(0)
+            CREATE
+            // call the created contract
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            PUSH1(0)
+            DUP6
+            GAS
+            CALL
+            STOP
+        };
+
+        let ctx = TestContext::<3, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(*CALLER).code(creator_code);
+                accs[2].address(*BENEFICIARY).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .to(accs[1].address)
+                    .gas(200000u64.into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+
+    #[test]
+    fn selfdestruct_gadget_in_constructor() {
+        let ctx = TestContext::<2, 1>::new(
+            None,
+            |accs| {
+                accs[0].address(MOCK_ACCOUNTS[0]).balance(eth(10));
+                accs[1].address(*BENEFICIARY).balance(eth(1));
+            },
+            |mut txs, accs| {
+                txs[0]
+                    .from(accs[0].address)
+                    .value(eth(2))
+                    .input(selfdestruct_code(*BENEFICIARY).code().into());
+            },
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap();
+
+        CircuitTestBuilder::new_from_test_ctx(ctx).run();
+    }
+}
@@ -5,8 +5,7 @@ use crate::{
         util::{
             common_gadget::SameContextGadget,
             constraint_builder::{EVMConstraintBuilder, StepStateTransition, Transition::Delta},
-            from_bytes,
-            math_gadget::{ComparisonGadget, IsEqualGadget, LtGadget},
+            math_gadget::{IsEqualGadget, LtGadget, LtWordGadget},
             select, CachedRegion, Cell, Word,
         },
         witness::{Block, Call, ExecStep, Transaction},
@@ -27,8 +26,7 @@ pub(crate) struct SignedComparatorGadget<F> {
 
     sign_check_a: LtGadget<F, 1>,
     sign_check_b: LtGadget<F, 1>,
-    lt_lo: LtGadget<F, 16>,
-    comparison_hi: ComparisonGadget<F, 16>,
+    lt_word: LtWordGadget<F>,
     a_lt_b: Cell<F>,
 
     is_sgt: IsEqualGadget<F>,
@@ -65,38 +63,22 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
         let b_pos = sign_check_b.expr();
 
         // We require the comparison check only for the cases where:
-        // (a < 0 && b < 0) || (a >= 0 && b >= 0).
-        // We ignore the equality expression since we only care about the LT
-        // check.
-        // By `lo` we mean the low bytes, and `hi` stands for the more
-        // significant bytes. While only considering the absolute
-        // values, we have: a < b == 1 iff ((a_hi < b_hi) || ((a_hi ==
-        // b_hi) && (a_lo < b_lo)))
-        let lt_lo = LtGadget::construct(
-            cb,
-            from_bytes::expr(&a.cells[0..16]),
-            from_bytes::expr(&b.cells[0..16]),
-        );
-        let comparison_hi = ComparisonGadget::construct(
-            cb,
-            from_bytes::expr(&a.cells[16..32]),
-            from_bytes::expr(&b.cells[16..32]),
-        );
-        let a_lt_b_lo = lt_lo.expr();
-        let (a_lt_b_hi, a_eq_b_hi) = comparison_hi.expr();
+        // (a < 0 && b < 0) || (a >= 0 && b >= 0), where the signed order is
+        // the unsigned order of the words.
+        let lt_word = LtWordGadget::construct(cb, &a, &b);
 
         // Add selector only for the cases where both a and b are positive or
         // negative. This selector will be used after handling the cases
         // where either only a or only b are negative.
         //
         // if (a > 0 && b > 0) || (a < 0 && b < 0):
-        //      a < b -> (a_hi < b_hi) ? 1 : (a_hi == b_hi) * (a_lo < b_lo)
+        //      a < b -> unsigned a < b
         //
         // for e.g.: consider 8-bit signed integers -1 (0xff) and -2 (0xfe):
         //     -2 < -1 and 0xfe < 0xff
         //
         // Use copy to avoid degree too high for stack_push below.
-        let a_lt_b = cb.copy(select::expr(a_lt_b_hi, 1.expr(), a_eq_b_hi * a_lt_b_lo));
+        let a_lt_b = cb.copy(lt_word.expr());
 
         // Add a trivial selector: if only a or only b is negative we have the
         // result.
@@ -135,8 +117,7 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
             b,
             sign_check_a,
             sign_check_b,
-            lt_lo,
-            comparison_hi,
+            lt_word,
             a_lt_b,
             is_sgt,
         }
@@ -186,21 +167,8 @@ impl<F: Field> ExecutionGadget<F> for SignedComparatorGadget<F> {
             F::from(128u64),
         )?;
 
-        // Assign to the comparison gadgets. The first 16 bytes are assigned to
-        // the `lo` less-than gadget while the last 16 bytes are assigned to
-        // the `hi` comparison.
-        self.lt_lo.assign(
-            region,
-            offset,
-            from_bytes::value(&a_le_bytes[0..16]),
-            from_bytes::value(&b_le_bytes[0..16]),
-        )?;
-        self.comparison_hi.assign(
-            region,
-            offset,
-            from_bytes::value(&a_le_bytes[16..32]),
-            from_bytes::value(&b_le_bytes[16..32]),
-        )?;
+        // Assign to the unsigned comparison of the words.
+        self.lt_word.assign(region, offset, a, b)?;
 
         // Assign to intermediate witness a_lt_b.
         self.a_lt_b.assign(
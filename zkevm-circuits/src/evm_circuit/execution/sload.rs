@@ -3,7 +3,7 @@ use crate::{
         execution::ExecutionGadget,
         step::ExecutionState,
         util::{
-            common_gadget::{SameContextGadget, SloadGasGadget},
+            common_gadget::{GasScheduleGadget, SameContextGadget, SloadGasGadget},
             constraint_builder::{
                 EVMConstraintBuilder, ReversionInfo, StepStateTransition, Transition::Delta,
             },
@@ -27,6 +27,7 @@ pub(crate) struct SloadGadget<F> {
     phase2_value: Cell<F>,
     phase2_committed_value: Cell<F>,
     is_warm: Cell<F>,
+    gas_schedule: GasScheduleGadget<F>,
 }
 
 impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
@@ -73,7 +74,8 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
             Some(&mut reversion_info),
         );
 
-        let gas_cost = SloadGasGadget::construct(cb, is_warm.expr()).expr();
+        let gas_schedule = GasScheduleGadget::construct(cb, tx_id.expr());
+        let gas_cost = SloadGasGadget::construct(cb, is_warm.expr(), &gas_schedule).expr();
         let step_state_transition = StepStateTransition {
             rw_counter: Delta(9.expr()),
             program_counter: Delta(1.expr()),
@@ -92,6 +94,7 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
             phase2_value,
             phase2_committed_value,
             is_warm,
+            gas_schedule,
         }
     }
 
@@ -138,6 +141,8 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
         let (_, is_warm) = block.rws[step.rw_indices[7]].tx_access_list_value_pair();
         self.is_warm
             .assign(region, offset, Value::known(F::from(is_warm as u64)))?;
+        self.gas_schedule
+            .assign(region, offset, tx.block_number, &block.gas_schedules)?;
 
         Ok(())
     }
@@ -146,8 +151,14 @@ impl<F: Field> ExecutionGadget<F> for SloadGadget<F> {
 #[cfg(test)]
 mod test {
 
-    use crate::{evm_circuit::test::rand_word, test_util::CircuitTestBuilder};
-    use eth_types::{bytecode, Word};
+    use crate::{
+        evm_circuit::test::rand_word,
+        state_circuit::RwTableView,
+        table::{CallContextFieldTag, RwTableTag},
+        test_util::CircuitTestBuilder,
+    };
+    use eth_types::{bytecode, evm_types::OpcodeId, Word};
+    use halo2_proofs::halo2curves::bn256::Fr;
     use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
 
     fn test_ok(key: Word, value: Word) {
@@ -170,7 +181,7 @@ mod test {
             PUSH32(0)
             REVERT
         };
-        for bytecode in [bytecode_success, bytecode_failure] {
+        for (bytecode, is_persistent) in [(bytecode_success, true), (bytecode_failure, false)] {
             let ctx = TestContext::<2, 1>::new(
                 None,
                 |accs| {
@@ -188,7 +199,42 @@ mod test {
             )
             .unwrap();
 
-            CircuitTestBuilder::new_from_test_ctx(ctx).run();
+            CircuitTestBuilder::new_from_test_ctx(ctx)
+                .block_checks(Box::new(move |block| {
+                    // the first SLOAD, of a cold slot
+                    let step = block.txs[0]
+                        .steps
+                        .iter()
+                        .find(|step| step.opcode == Some(OpcodeId::SLOAD))
+                        .unwrap();
+                    let rws = RwTableView::from_step(block, step);
+                    rws.assert_lookups_of::<Fr>(step);
+                    assert_eq!(
+                        rws.call_context()
+                            .map(|(field_tag, _)| field_tag)
+                            .collect::<Vec<_>>(),
+                        [
+                            CallContextFieldTag::TxId,
+                            CallContextFieldTag::RwCounterEndOfReversion,
+                            CallContextFieldTag::IsPersistent,
+                            CallContextFieldTag::CalleeAddress,
+                        ]
+                    );
+                    assert_eq!(
+                        rws.stack().collect::<Vec<_>>(),
+                        [(false, 1023, key), (true, 1023, value)]
+                    );
+
+                    let rws = rws.address(MOCK_ACCOUNTS[0]);
+                    assert_eq!(rws.storage().collect::<Vec<_>>(), [(key, value, value)]);
+                    // the slot is warmed, and cooled again when the call reverts
+                    let access_list_writes = if is_persistent { 1 } else { 2 };
+                    assert_eq!(
+                        rws.is_write(true).tags(),
+                        vec![RwTableTag::TxAccessListAccountStorage; access_list_writes]
+                    );
+                }))
+                .run();
         }
     }
 
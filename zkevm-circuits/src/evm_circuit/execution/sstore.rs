@@ -4,9 +4,7 @@ use crate::{
         param::N_BYTES_GAS,
         step::ExecutionState,
         util::{
-            common_gadget::{
-                cal_sstore_gas_cost_for_assignment, SameContextGadget, SstoreGasGadget,
-            },
+            common_gadget::{GasScheduleGadget, SameContextGadget, SstoreGasGadget},
             constraint_builder::{
                 ConstrainBuilderCommon, EVMConstraintBuilder, ReversionInfo, StepStateTransition,
                 Transition::Delta,
@@ -20,7 +18,10 @@ use crate::{
     util::Expr,
 };
 
-use eth_types::{evm_types::GasCost, Field, ToScalar};
+use eth_types::{
+    evm_types::{GasCost, GasSchedule},
+    Field, ToScalar,
+};
 use halo2_proofs::{
     circuit::Value,
     plonk::{Error, Expression},
@@ -41,6 +42,7 @@ pub(crate) struct SstoreGadget<F> {
     tx_refund_prev: Cell<F>,
     // Constrain for SSTORE reentrancy sentry.
     sufficient_gas_sentry: LtGadget<F, N_BYTES_GAS>,
+    gas_schedule: GasScheduleGadget<F>,
     gas_cost: SstoreGasGadget<F>,
     tx_refund: SstoreTxRefundGadget<F>,
 }
@@ -110,12 +112,14 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             1.expr(),
         );
 
+        let gas_schedule = GasScheduleGadget::construct(cb, tx_id.expr());
         let gas_cost = SstoreGasGadget::construct(
             cb,
             phase2_value.clone(),
             phase2_value_prev.clone(),
             phase2_original_value.clone(),
             is_warm.clone(),
+            &gas_schedule,
         );
 
         let tx_refund_prev = cb.query_cell();
@@ -125,6 +129,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             phase2_value.clone(),
             phase2_value_prev.clone(),
             phase2_original_value.clone(),
+            &gas_schedule,
         );
         cb.tx_refund_write(
             tx_id.expr(),
@@ -156,6 +161,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             is_warm,
             tx_refund_prev,
             sufficient_gas_sentry,
+            gas_schedule,
             gas_cost,
             tx_refund,
         }
@@ -220,10 +226,13 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             Value::known(F::from(step.gas_left)),
         )?;
 
+        let gas_schedule =
+            self.gas_schedule
+                .assign(region, offset, tx.block_number, &block.gas_schedules)?;
         self.gas_cost
             .assign(region, offset, value, value_prev, original_value, is_warm)?;
         debug_assert_eq!(
-            cal_sstore_gas_cost_for_assignment(value, value_prev, original_value, is_warm),
+            gas_schedule.sstore(value, value_prev, original_value, is_warm),
             step.gas_cost,
             "invalid gas cost in sstore value {:?} value_prev {:?} original_value {:?} is_warm {:?} contract addr {:?} storage key {:?}",
             value, value_prev, original_value, is_warm, call.callee_address, key
@@ -237,6 +246,7 @@ impl<F: Field> ExecutionGadget<F> for SstoreGadget<F> {
             value,
             value_prev,
             original_value,
+            &gas_schedule,
         )?;
         Ok(())
     }
@@ -264,6 +274,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value: Cell<F>,
         value_prev: Cell<F>,
         original_value: Cell<F>,
+        schedule: &GasScheduleGadget<F>,
     ) -> Self {
         let value_prev_is_zero_gadget = IsZeroGadget::construct(cb, "", value_prev.expr());
         let value_is_zero_gadget = IsZeroGadget::construct(cb, "", value.expr());
@@ -302,10 +313,10 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             not::expr(prev_eq_value) * not::expr(original_eq_prev) * (value_prev_is_zero);
 
         let tx_refund_new = tx_refund_old.expr()
-            + delete_slot * GasCost::SSTORE_CLEARS_SCHEDULE.expr()
-            + reset_existing * (GasCost::SSTORE_RESET.expr() - GasCost::WARM_ACCESS.expr())
-            + reset_inexistent * (GasCost::SSTORE_SET.expr() - GasCost::WARM_ACCESS.expr())
-            - recreate_slot * (GasCost::SSTORE_CLEARS_SCHEDULE.expr());
+            + delete_slot * schedule.sstore_clears_schedule()
+            + reset_existing * (schedule.sstore_reset() - schedule.warm_access())
+            + reset_inexistent * (schedule.sstore_set() - schedule.warm_access())
+            - recreate_slot * schedule.sstore_clears_schedule();
 
         Self {
             value,
@@ -337,6 +348,7 @@ impl<F: Field> SstoreTxRefundGadget<F> {
         value: eth_types::Word,
         value_prev: eth_types::Word,
         original_value: eth_types::Word,
+        schedule: &GasSchedule,
     ) -> Result<(), Error> {
         self.tx_refund_old
             .assign(region, offset, Value::known(F::from(tx_refund_old)))?;
@@ -373,73 +385,29 @@ impl<F: Field> SstoreTxRefundGadget<F> {
             region.word_rlc(value_prev),
         )?;
         debug_assert_eq!(
-            calc_expected_tx_refund(tx_refund_old, value, value_prev, original_value),
+            schedule.sstore_tx_refund(tx_refund_old, value, value_prev, original_value),
             tx_refund
         );
         Ok(())
     }
 }
 
-fn calc_expected_tx_refund(
-    tx_refund_old: u64,
-    value: eth_types::Word,
-    value_prev: eth_types::Word,
-    original_value: eth_types::Word,
-) -> u64 {
-    // Same clause tags(like "delete slot (2.1.2b)") used as [`makeGasSStoreFunc` in go-ethereum](https://github.com/ethereum/go-ethereum/blob/9fd8825d5a196edde6d8ef81382979875145b346/core/vm/operations_acl.go#L27)
-    // Control flow of this function try to follow `makeGasSStoreFunc` for better
-    // understanding and comparison.
-
-    let mut tx_refund_new = tx_refund_old;
-
-    // The "clearing slot refund" and "resetting value refund" are ADDED together,
-    // they are NOT MUTUALLY EXCLUSIVE.
-    // Search "Apply both of the following clauses" in EIP-2200 for more details.
-    // There can be five total kinds of refund:
-    // 1. -SSTORE_CLEARS_SCHEDULE
-    // 2. SSTORE_CLEARS_SCHEDULE
-    // 3. SSTORE_SET - WARM_ACCESS
-    // 4. SSTORE_RESET - WARM_ACCESS
-    // 5. -SSTORE_CLEARS_SCHEDULE + SSTORE_RESET - WARM_ACCESS
-    // The last case can happen if (original_value, prev_value, value) be (v,0,v)
-    // where v != 0,
-    // then both "clearing slot refund" and "resetting value refund" are non zero.
-
-    if value_prev != value {
-        // refund related to clearing slot
-        // "delete slot (2.1.2b)" can be safely merged in "delete slot (2.2.1.2)"
-        if !original_value.is_zero() {
-            if value_prev.is_zero() {
-                // recreate slot (2.2.1.1)
-                tx_refund_new -= GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
-            }
-            if value.is_zero() {
-                // delete slot (2.2.1.2)
-                tx_refund_new += GasCost::SSTORE_CLEARS_SCHEDULE.as_u64()
-            }
-        }
-
-        // refund related to resetting value
-        if original_value == value {
-            if original_value.is_zero() {
-                // reset to original inexistent slot (2.2.2.1)
-                tx_refund_new += GasCost::SSTORE_SET.as_u64() - GasCost::WARM_ACCESS.as_u64();
-            } else {
-                // reset to original existing slot (2.2.2.2)
-                tx_refund_new += GasCost::SSTORE_RESET.as_u64() - GasCost::WARM_ACCESS.as_u64();
-            }
-        }
-    }
-
-    tx_refund_new
-}
-
 #[cfg(test)]
 mod test {
 
-    use crate::test_util::CircuitTestBuilder;
-    use eth_types::{bytecode, Word};
-    use mock::{test_ctx::helpers::tx_from_1_to_0, TestContext, MOCK_ACCOUNTS};
+    use crate::{test_util::CircuitTestBuilder, witness::block_convert};
+    use bus_mapping::mock::BlockData;
+    use eth_types::{
+        bytecode,
+        evm_types::{GasCost, GasSchedule, GasSchedules, OpcodeId},
+        geth_types::GethData,
+        Word,
+    };
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::{
+        test_ctx::helpers::{account_0_code_account_1_no_code, tx_from_1_to_0},
+        TestContext, MOCK_ACCOUNTS,
+    };
 
     #[test]
     fn sstore_gadget_no_refund() {
@@ -502,6 +470,55 @@ mod test {
         );
     }
 
+    #[test]
+    fn sstore_gadget_repriced() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            SSTORE
+            STOP
+        };
+        let mut block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block.number(0xcafeu64),
+        )
+        .unwrap()
+        .into();
+        // repriced in the block of the tx only, which is both the first and
+        // the last block of its schedule
+        let repriced = GasSchedule {
+            sstore_set: GasCost::SSTORE_SET.0 + 1,
+            ..Default::default()
+        };
+        let gas_schedules = GasSchedules::default()
+            .with_schedule(0xcafe, repriced)
+            .with_schedule(0xcaff, GasSchedule::default());
+
+        // charge the repriced SSTORE in the trace
+        let trace = &mut block.geth_traces[0];
+        let index = trace
+            .struct_logs
+            .iter()
+            .position(|step| step.op == OpcodeId::SSTORE)
+            .unwrap();
+        trace.struct_logs[index].gas_cost.0 += 1;
+        for step in &mut trace.struct_logs[index + 1..] {
+            step.gas.0 -= 1;
+        }
+        trace.gas.0 += 1;
+
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder.block.gas_schedules = gas_schedules;
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+        let block = block_convert::<Fr>(&builder.block, &builder.code_db).unwrap();
+
+        CircuitTestBuilder::new_from_block(block).run();
+    }
+
     fn test_ok(key: Word, value: Word, value_prev: Word, original_value: Word) {
         // Here we use two bytecodes to test both is_persistent(STOP) or not(REVERT)
         // Besides, in bytecode we use two SSTOREs,
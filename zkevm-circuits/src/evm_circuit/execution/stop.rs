@@ -55,9 +55,12 @@ impl<F: Field> ExecutionGadget<F> for StopGadget<F> {
         // Call ends with STOP must be successful
         cb.call_context_lookup(false.expr(), None, CallContextFieldTag::IsSuccess, 1.expr());
 
-        let is_to_end_tx = cb.next.execution_state_selector([ExecutionState::EndTx]);
+        // The accounts destructed in the tx are deleted before EndTx.
+        let is_to_end_tx = cb
+            .next
+            .execution_state_selector([ExecutionState::DestructAccount, ExecutionState::EndTx]);
         cb.require_equal(
-            "Go to EndTx only when is_root",
+            "Go to DestructAccount or EndTx only when is_root",
             cb.curr.state.is_root.expr(),
             is_to_end_tx,
         );
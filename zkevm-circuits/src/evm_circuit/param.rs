@@ -9,7 +9,7 @@ use std::collections::HashMap;
 // Step dimension
 pub(crate) const STEP_WIDTH: usize = 140;
 /// Step height
-pub const MAX_STEP_HEIGHT: usize = 21;
+pub use zkevm_constants::evm::MAX_STEP_HEIGHT;
 /// The height of the state of a step, used by gates that connect two
 /// consecutive steps. We target 1, which is also convenient for padding with
 /// EndBlock steps.
@@ -25,7 +25,7 @@ pub(crate) const N_PHASE1_COLUMNS: usize =
 // Number of copy columns
 pub(crate) const N_COPY_COLUMNS: usize = 2;
 // Number of copy columns for phase2
-pub(crate) const N_PHASE2_COPY_COLUMNS: usize = 1;
+pub(crate) const N_PHASE2_COPY_COLUMNS: usize = 2;
 
 pub(crate) const N_BYTE_LOOKUPS: usize = 26;
 
@@ -0,0 +1,104 @@
+//! Per-step profiling data of a block, for explorers and debuggers.
+//!
+//! Each step of the EVM circuit witness is annotated with the number of rows
+//! it occupies in the EVM circuit and the wall-clock time bus-mapping spent
+//! generating it.
+
+use eth_types::Field;
+use serde::Serialize;
+
+use super::witness::Block;
+
+/// Profile of an execution step.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StepProfile {
+    /// Id of the transaction, starting at 1
+    pub tx_id: usize,
+    /// Index of the step within the transaction
+    pub step_index: usize,
+    /// Execution state of the step
+    pub execution_state: String,
+    /// Opcode of the step, if any
+    pub opcode: Option<String>,
+    /// Program counter
+    pub program_counter: u64,
+    /// Gas cost of the step
+    pub gas_cost: u64,
+    /// Number of rows of the step in the EVM circuit
+    pub rows: usize,
+    /// Wall-clock witness generation time in nanoseconds
+    pub elapsed_ns: u64,
+}
+
+/// Profiles of all the steps of the block's transactions.
+pub fn block_step_profiles<F: Field>(block: &Block<F>) -> Vec<StepProfile> {
+    block
+        .txs
+        .iter()
+        .flat_map(|tx| {
+            tx.steps
+                .iter()
+                .enumerate()
+                .map(|(step_index, step)| StepProfile {
+                    tx_id: tx.id,
+                    step_index,
+                    execution_state: format!("{:?}", step.execution_state),
+                    opcode: step.opcode.map(|op| format!("{op:?}")),
+                    program_counter: step.program_counter,
+                    gas_cost: step.gas_cost,
+                    rows: step.execution_state.get_step_height(),
+                    elapsed_ns: step.elapsed.as_nanos() as u64,
+                })
+        })
+        .collect()
+}
+
+/// Export the step profiles of the block as JSON.
+pub fn block_step_profiles_json<F: Field>(block: &Block<F>) -> serde_json::Value {
+    serde_json::to_value(block_step_profiles(block)).expect("step profiles are serializable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evm_circuit::step::ExecutionState,
+        witness::{ExecStep, Transaction},
+    };
+    use bus_mapping::evm::OpcodeId;
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use std::time::Duration;
+
+    #[test]
+    fn step_profiles_json() {
+        let block = Block::<Fr> {
+            txs: vec![Transaction {
+                id: 1,
+                steps: vec![
+                    ExecStep {
+                        execution_state: ExecutionState::BeginTx,
+                        elapsed: Duration::from_nanos(10),
+                        ..Default::default()
+                    },
+                    ExecStep {
+                        execution_state: ExecutionState::STOP,
+                        opcode: Some(OpcodeId::STOP),
+                        elapsed: Duration::from_nanos(3),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let json = block_step_profiles_json(&block);
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[0]["execution_state"], "BeginTx");
+        assert_eq!(
+            json[0]["rows"],
+            ExecutionState::BeginTx.get_step_height() as u64
+        );
+        assert_eq!(json[1]["opcode"], "STOP");
+        assert_eq!(json[1]["elapsed_ns"], 3);
+    }
+}
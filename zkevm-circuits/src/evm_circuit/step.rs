@@ -31,6 +31,7 @@ impl From<PrecompileCalls> for ExecutionState {
             PrecompileCalls::Bn128Mul => ExecutionState::PrecompileBn256ScalarMul,
             PrecompileCalls::Bn128Pairing => ExecutionState::PrecompileBn256Pairing,
             PrecompileCalls::Blake2F => ExecutionState::PrecompileBlake2f,
+            PrecompileCalls::PointEvaluation => ExecutionState::PrecompilePointEvaluation,
         }
     }
 }
@@ -40,6 +41,7 @@ impl From<PrecompileCalls> for ExecutionState {
 pub enum ExecutionState {
     // Internal state
     BeginTx,
+    DestructAccount,
     EndTx,
     EndInnerBlock,
     EndBlock,
@@ -82,9 +84,12 @@ pub enum ExecutionState {
     BLOCKCTXU160, // COINBASE
     BLOCKCTXU256, // DIFFICULTY, BASEFEE
     CHAINID,
+    BLOBHASH,
+    BLOBBASEFEE,
     SELFBALANCE,
     POP,
     MEMORY, // MLOAD, MSTORE, MSTORE8
+    MCOPY,
     SLOAD,
     SSTORE,
     JUMP,
@@ -111,8 +116,7 @@ pub enum ExecutionState {
     ErrorReturnDataOutOfBound,
     ErrorPrecompileFailed,
     ErrorOutOfGasConstant,
-    ErrorOutOfGasStaticMemoryExpansion,
-    ErrorOutOfGasDynamicMemoryExpansion,
+    ErrorOutOfGasMemoryExpansion,
     ErrorOutOfGasMemoryCopy,
     ErrorOutOfGasAccountAccess,
     // error for CodeStoreOOG and MaxCodeSizeExceeded
@@ -134,6 +138,7 @@ pub enum ExecutionState {
     PrecompileBn256ScalarMul,
     PrecompileBn256Pairing,
     PrecompileBlake2f,
+    PrecompilePointEvaluation,
 }
 
 impl Default for ExecutionState {
@@ -169,6 +174,7 @@ impl ExecutionState {
                 | Self::PrecompileBn256ScalarMul
                 | Self::PrecompileBn256Pairing
                 | Self::PrecompileBlake2f
+                | Self::PrecompilePointEvaluation
         )
     }
 
@@ -183,6 +189,7 @@ impl ExecutionState {
             Self::PrecompileBn256ScalarMul => PrecompileCalls::Bn128Mul,
             Self::PrecompileBn256Pairing => PrecompileCalls::Bn128Pairing,
             Self::PrecompileBlake2f => PrecompileCalls::Blake2F,
+            Self::PrecompilePointEvaluation => PrecompileCalls::PointEvaluation,
             _ => return GasCost(0),
         })
         .base_gas_cost()
@@ -198,8 +205,7 @@ impl ExecutionState {
                 | Self::ErrorInvalidJump
                 | Self::ErrorReturnDataOutOfBound
                 | Self::ErrorOutOfGasConstant
-                | Self::ErrorOutOfGasStaticMemoryExpansion
-                | Self::ErrorOutOfGasDynamicMemoryExpansion
+                | Self::ErrorOutOfGasMemoryExpansion
                 | Self::ErrorOutOfGasMemoryCopy
                 | Self::ErrorOutOfGasAccountAccess
                 | Self::ErrorCodeStore
@@ -269,11 +275,14 @@ impl ExecutionState {
             Self::BLOCKCTXU160 => vec![OpcodeId::COINBASE],
             Self::BLOCKCTXU256 => vec![OpcodeId::DIFFICULTY, OpcodeId::BASEFEE],
             Self::CHAINID => vec![OpcodeId::CHAINID],
+            Self::BLOBHASH => vec![OpcodeId::BLOBHASH],
+            Self::BLOBBASEFEE => vec![OpcodeId::BLOBBASEFEE],
             Self::SELFBALANCE => vec![OpcodeId::SELFBALANCE],
             Self::POP => vec![OpcodeId::POP],
             Self::MEMORY => {
                 vec![OpcodeId::MLOAD, OpcodeId::MSTORE, OpcodeId::MSTORE8]
             }
+            Self::MCOPY => vec![OpcodeId::MCOPY],
             Self::SLOAD => vec![OpcodeId::SLOAD],
             Self::SSTORE => vec![OpcodeId::SSTORE],
             Self::JUMP => vec![OpcodeId::JUMP],
@@ -370,7 +379,15 @@ impl ExecutionState {
             ],
             Self::RETURN_REVERT => vec![OpcodeId::RETURN, OpcodeId::REVERT],
             Self::SELFDESTRUCT => vec![OpcodeId::SELFDESTRUCT],
-            Self::ErrorInvalidOpcode => OpcodeId::invalid_opcodes(),
+            Self::ErrorInvalidOpcode => {
+                // `PUSH0` is also invalid before Shanghai, which the gadget
+                // checks by the block number
+                let mut opcodes = OpcodeId::invalid_opcodes();
+                if cfg!(feature = "shanghai") {
+                    opcodes.push(OpcodeId::PUSH0);
+                }
+                opcodes
+            }
             _ => vec![],
         }
         .into_iter()
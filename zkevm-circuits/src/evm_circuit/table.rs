@@ -1,10 +1,13 @@
 pub use crate::table::TxContextFieldTag;
 use crate::{
-    evm_circuit::step::{ExecutionState, ResponsibleOp},
+    evm_circuit::{
+        step::{ExecutionState, ResponsibleOp},
+        EvmCircuitParams,
+    },
     impl_expr,
 };
 use bus_mapping::{evm::OpcodeId, precompile::PrecompileCalls};
-use eth_types::Field;
+use eth_types::{evm_types::GasSchedule, Field};
 use gadgets::util::Expr;
 use halo2_proofs::plonk::Expression;
 use strum::IntoEnumIterator;
@@ -29,11 +32,55 @@ pub enum FixedTableTag {
     Pow2,
     ConstantGasCost,
     PrecompileInfo,
+    GasSchedule,
+    /// Single row `[tag, shanghai_block, 0, 0]`, with the first block of the
+    /// Shanghai hardfork, or `u64::MAX` if the chain doesn't activate it
+    ShanghaiBlock,
+    /// Single row `[tag, curie_block, 0, 0]`, with the first block of the
+    /// Curie hardfork, or `u64::MAX` if the chain doesn't activate it
+    CurieBlock,
+    /// Single row `[tag, max_code_size, 0, 0]`, with the maximum size of the
+    /// code of a contract of the chain
+    MaxCodeSize,
+    /// Single row `[tag, max_init_code_size, 0, 0]`, with the maximum size of
+    /// init code of the chain
+    MaxInitCodeSize,
 }
 impl_expr!(FixedTableTag);
 
+/// Field of a row of the [`FixedTableTag::GasSchedule`] table, whose rows are
+/// `[tag, first_block, field, value]` for each gas schedule of the chain.
+#[derive(Clone, Copy, Debug, EnumIter)]
+pub enum GasScheduleField {
+    /// First block of the next schedule
+    NextFirstBlock = 0,
+    WarmAccess,
+    ColdSload,
+    SstoreSet,
+    SstoreReset,
+    SstoreClearsSchedule,
+    Tload,
+    Tstore,
+}
+impl_expr!(GasScheduleField);
+
+impl GasScheduleField {
+    fn value(&self, next_first_block: u64, schedule: &GasSchedule) -> u64 {
+        match self {
+            Self::NextFirstBlock => next_first_block,
+            Self::WarmAccess => schedule.warm_access,
+            Self::ColdSload => schedule.cold_sload,
+            Self::SstoreSet => schedule.sstore_set,
+            Self::SstoreReset => schedule.sstore_reset,
+            Self::SstoreClearsSchedule => schedule.sstore_clears_schedule,
+            Self::Tload => schedule.tload,
+            Self::Tstore => schedule.tstore,
+        }
+    }
+}
+
 impl FixedTableTag {
-    pub fn build<F: Field>(&self) -> Box<dyn Iterator<Item = [F; 4]>> {
+    pub fn build<F: Field>(&self, params: &EvmCircuitParams) -> Box<dyn Iterator<Item = [F; 4]>> {
         let tag = F::from(*self as u64);
         match self {
             Self::Zero => Box::new((0..1).map(move |_| [tag, F::zero(), F::zero(), F::zero()])),
@@ -129,6 +176,48 @@ impl FixedTableTag {
                     F::from(precompile.base_gas_cost().0),
                 ]
             })),
+            Self::GasSchedule => Box::new(params.gas_schedules.ranges().into_iter().flat_map(
+                move |(first_block, next_first_block, schedule)| {
+                    GasScheduleField::iter().map(move |field| {
+                        [
+                            tag,
+                            F::from(first_block),
+                            F::from(field as u64),
+                            F::from(field.value(next_first_block, &schedule)),
+                        ]
+                    })
+                },
+            )),
+            Self::ShanghaiBlock => {
+                let shanghai_block = params.shanghai_block.unwrap_or(u64::MAX);
+                Box::new(std::iter::once([
+                    tag,
+                    F::from(shanghai_block),
+                    F::zero(),
+                    F::zero(),
+                ]))
+            }
+            Self::CurieBlock => {
+                let curie_block = params.curie_block.unwrap_or(u64::MAX);
+                Box::new(std::iter::once([
+                    tag,
+                    F::from(curie_block),
+                    F::zero(),
+                    F::zero(),
+                ]))
+            }
+            Self::MaxCodeSize => Box::new(std::iter::once([
+                tag,
+                F::from(params.max_code_size),
+                F::zero(),
+                F::zero(),
+            ])),
+            Self::MaxInitCodeSize => Box::new(std::iter::once([
+                tag,
+                F::from(params.max_init_code_size),
+                F::zero(),
+                F::zero(),
+            ])),
         }
     }
 }
@@ -117,6 +117,22 @@ impl<'r, 'b, F: FieldExt> CachedRegion<'r, 'b, F> {
         }
     }
 
+    /// Move the region to `height_start`, with `height` cleared rows. The
+    /// buffer is reused, so the steps of a block are assigned one after the
+    /// other without allocating rows for each of them.
+    pub(crate) fn reset(&mut self, height_start: usize, height: usize) {
+        for values in self.advice.iter_mut() {
+            values.clear();
+            values.resize(height, F::zero());
+        }
+        self.height_start = height_start;
+    }
+
+    /// The underlying region, for the cells outside of the advice columns.
+    pub(crate) fn region_mut(&mut self) -> &mut Region<'b, F> {
+        self.region
+    }
+
     /// This method replicates the assignment of 1 row at height_start (which
     /// must be already assigned via the CachedRegion) into a range of rows
     /// indicated by offset_begin, offset_end. It can be used as a "quick"
@@ -702,7 +718,7 @@ pub(crate) fn transpose_val_ret<F, E>(value: Value<Result<F, E>>) -> Result<Valu
 }
 
 pub(crate) fn is_precompiled(address: &Address) -> bool {
-    address.0[0..19] == [0u8; 19] && (1..=9).contains(&address.0[19])
+    bus_mapping::precompile::is_precompiled(address)
 }
 
 /// Helper struct to read rw operations from a step sequentially.
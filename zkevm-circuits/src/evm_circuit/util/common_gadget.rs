@@ -1,15 +1,15 @@
 use super::{
     constraint_builder::ConstrainBuilderCommon,
     from_bytes,
-    math_gadget::{IsEqualGadget, IsZeroGadget, LtGadget},
+    math_gadget::{ConstantDivisionGadget, IsEqualGadget, IsZeroGadget, LtGadget, MinMaxGadget},
     memory_gadget::{CommonMemoryAddressGadget, MemoryExpansionGadget},
     CachedRegion,
 };
 use crate::{
     evm_circuit::{
-        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE},
+        param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_GAS, N_BYTES_MEMORY_WORD_SIZE, N_BYTES_U64},
         step::ExecutionState,
-        table::{FixedTableTag, Lookup},
+        table::{FixedTableTag, GasScheduleField, Lookup},
         util::{
             constraint_builder::{
                 EVMConstraintBuilder, ReversionInfo, StepStateTransition,
@@ -19,11 +19,21 @@ use crate::{
             not, or, Cell, CellType, Word,
         },
     },
-    table::{AccountFieldTag, CallContextFieldTag},
+    table::{AccountFieldTag, CallContextFieldTag, TxContextFieldTag},
     util::Expr,
     witness::{Block, Call, ExecStep},
 };
-use eth_types::{evm_types::GasCost, Field, ToLittleEndian, ToScalar, U256};
+use bus_mapping::{
+    circuit_input_builder::{ChainSpec, L1FeeConfig, L1FeeHardfork},
+    evm::OpcodeId,
+};
+use eth_types::{
+    evm_types::{
+        gas_utils::{all_but_one_64th_gas, eip150_gas},
+        GasCost, GasSchedule, GasSchedules,
+    },
+    Field, ToLittleEndian, ToScalar, U256,
+};
 use gadgets::util::{select, sum};
 use halo2_proofs::{
     circuit::Value,
@@ -679,6 +689,72 @@ impl<F: Field> TransferGadget<F> {
     }
 }
 
+/// The opcode of CALL, CALLCODE, DELEGATECALL and STATICCALL, decoded into
+/// one flag per opcode. Shared by all the gadgets handling these opcodes so
+/// that they select their behavior in the same way.
+#[derive(Clone, Debug)]
+pub(crate) struct CallOpcodeGadget<F> {
+    pub opcode: Cell<F>,
+    is_call: IsZeroGadget<F>,
+    is_callcode: IsZeroGadget<F>,
+    is_delegatecall: IsZeroGadget<F>,
+    is_staticcall: IsZeroGadget<F>,
+}
+
+impl<F: Field> CallOpcodeGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>) -> Self {
+        let opcode = cb.query_cell();
+        let [is_call, is_callcode, is_delegatecall, is_staticcall] = [
+            OpcodeId::CALL,
+            OpcodeId::CALLCODE,
+            OpcodeId::DELEGATECALL,
+            OpcodeId::STATICCALL,
+        ]
+        .map(|op| IsZeroGadget::construct(cb, "", opcode.expr() - op.expr()));
+
+        Self {
+            opcode,
+            is_call,
+            is_callcode,
+            is_delegatecall,
+            is_staticcall,
+        }
+    }
+
+    /// Returns `[is_call, is_callcode, is_delegatecall, is_staticcall]`.
+    pub(crate) fn flags(&self) -> [Expression<F>; 4] {
+        [
+            self.is_call.expr(),
+            self.is_callcode.expr(),
+            self.is_delegatecall.expr(),
+            self.is_staticcall.expr(),
+        ]
+    }
+
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        opcode: OpcodeId,
+    ) -> Result<(), Error> {
+        self.opcode
+            .assign(region, offset, Value::known(F::from(opcode.as_u64())))?;
+        for (gadget, op) in [
+            (&self.is_call, OpcodeId::CALL),
+            (&self.is_callcode, OpcodeId::CALLCODE),
+            (&self.is_delegatecall, OpcodeId::DELEGATECALL),
+            (&self.is_staticcall, OpcodeId::STATICCALL),
+        ] {
+            gadget.assign(
+                region,
+                offset,
+                F::from(opcode.as_u64()) - F::from(op.as_u64()),
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct CommonCallGadget<F, MemAddrGadget, const IS_SUCCESS_CALL: bool> {
     pub is_success: Cell<F>,
@@ -704,11 +780,10 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
 {
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
-        is_call: Expression<F>,
-        is_callcode: Expression<F>,
-        is_delegatecall: Expression<F>,
-        is_staticcall: Expression<F>,
+        opcode: &CallOpcodeGadget<F>,
     ) -> Self {
+        let [is_call, is_callcode, is_delegatecall, is_staticcall] = opcode.flags();
+
         // Constrain opcode must be one of CALL, CALLCODE, DELEGATECALL or STATICCALL.
         cb.require_equal(
             "Opcode should be CALL, CALLCODE, DELEGATECALL or STATICCALL",
@@ -897,6 +972,333 @@ impl<F: Field, MemAddrGadget: CommonMemoryAddressGadget<F>, const IS_SUCCESS_CAL
     }
 }
 
+/// Applies EIP 150 to the gas passed to a callee: all but one 64th of the gas
+/// available after the cost of the step, capped for the CALL opcodes by the
+/// gas specified on the stack when it fits in `N_BYTES_GAS` bytes.
+#[derive(Clone, Debug)]
+pub(crate) struct Eip150GasGadget<F> {
+    one_64th_gas: ConstantDivisionGadget<F, N_BYTES_GAS>,
+    capped_callee_gas_left: Option<MinMaxGadget<F, N_BYTES_GAS>>,
+    callee_gas_left: Expression<F>,
+}
+
+impl<F: Field> Eip150GasGadget<F> {
+    /// Gas passed to the callee of CREATE and CREATE2.
+    pub(crate) fn construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        gas_available: Expression<F>,
+    ) -> Self {
+        let one_64th_gas = ConstantDivisionGadget::construct(cb, gas_available.clone(), 64);
+        let callee_gas_left = gas_available - one_64th_gas.quotient();
+
+        Self {
+            one_64th_gas,
+            capped_callee_gas_left: None,
+            callee_gas_left,
+        }
+    }
+
+    /// Gas passed to the callee of CALL, CALLCODE, DELEGATECALL and
+    /// STATICCALL, including precompile calls.
+    pub(crate) fn construct_capped(
+        cb: &mut EVMConstraintBuilder<F>,
+        gas_available: Expression<F>,
+        gas_specified: Expression<F>,
+        gas_is_u64: Expression<F>,
+    ) -> Self {
+        let Self {
+            one_64th_gas,
+            callee_gas_left: all_but_one_64th_gas,
+            ..
+        } = Self::construct(cb, gas_available);
+        let capped_callee_gas_left =
+            MinMaxGadget::construct(cb, gas_specified, all_but_one_64th_gas.clone());
+        let callee_gas_left = select::expr(
+            gas_is_u64,
+            capped_callee_gas_left.min(),
+            all_but_one_64th_gas,
+        );
+
+        Self {
+            one_64th_gas,
+            capped_callee_gas_left: Some(capped_callee_gas_left),
+            callee_gas_left,
+        }
+    }
+
+    pub(crate) fn callee_gas_left(&self) -> Expression<F> {
+        self.callee_gas_left.clone()
+    }
+
+    /// The one 64th of the gas available that is kept by the caller of
+    /// CREATE and CREATE2.
+    pub(crate) fn one_64th_gas(&self) -> Expression<F> {
+        self.one_64th_gas.quotient()
+    }
+
+    /// Returns the gas passed to the callee. `gas_specified` is ignored for
+    /// CREATE and CREATE2.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        gas_available: u64,
+        gas_specified: U256,
+    ) -> Result<u64, Error> {
+        self.one_64th_gas
+            .assign(region, offset, gas_available.into())?;
+        let all_but_one_64th_gas = all_but_one_64th_gas(gas_available);
+        Ok(match &self.capped_callee_gas_left {
+            Some(capped_callee_gas_left) => {
+                capped_callee_gas_left.assign(
+                    region,
+                    offset,
+                    F::from(gas_specified.low_u64()),
+                    F::from(all_but_one_64th_gas),
+                )?;
+                eip150_gas(gas_available, gas_specified)
+            }
+            None => all_but_one_64th_gas,
+        })
+    }
+}
+
+/// Costs of the storage opcodes in the gas schedule of the block of a tx. The
+/// costs are looked up in the fixed table by the first block of the schedule,
+/// whose range of blocks must contain the block number.
+#[derive(Clone, Debug)]
+pub(crate) struct GasScheduleGadget<F> {
+    block_number: Cell<F>,
+    first_block: Cell<F>,
+    next_first_block: Cell<F>,
+    before_first_block: LtGadget<F, N_BYTES_U64>,
+    before_next_first_block: LtGadget<F, N_BYTES_U64>,
+    warm_access: Cell<F>,
+    cold_sload: Cell<F>,
+    sstore_set: Cell<F>,
+    sstore_reset: Cell<F>,
+    sstore_clears_schedule: Cell<F>,
+}
+
+impl<F: Field> GasScheduleGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, tx_id: Expression<F>) -> Self {
+        let block_number = cb.tx_context(tx_id, TxContextFieldTag::BlockNumber, None);
+        let first_block = cb.query_cell();
+        let next_first_block = cb.query_cell();
+        let [warm_access, cold_sload, sstore_set, sstore_reset, sstore_clears_schedule] =
+            [(); 5].map(|_| cb.query_cell());
+        for (field, value) in [
+            (GasScheduleField::NextFirstBlock, &next_first_block),
+            (GasScheduleField::WarmAccess, &warm_access),
+            (GasScheduleField::ColdSload, &cold_sload),
+            (GasScheduleField::SstoreSet, &sstore_set),
+            (GasScheduleField::SstoreReset, &sstore_reset),
+            (
+                GasScheduleField::SstoreClearsSchedule,
+                &sstore_clears_schedule,
+            ),
+        ] {
+            cb.gas_schedule_lookup(first_block.expr(), field, value.expr());
+        }
+
+        let before_first_block = LtGadget::construct(cb, block_number.expr(), first_block.expr());
+        let before_next_first_block =
+            LtGadget::construct(cb, block_number.expr(), next_first_block.expr());
+        cb.require_zero("block_number >= first_block", before_first_block.expr());
+        cb.require_equal(
+            "block_number < next_first_block",
+            before_next_first_block.expr(),
+            1.expr(),
+        );
+
+        Self {
+            block_number,
+            first_block,
+            next_first_block,
+            before_first_block,
+            before_next_first_block,
+            warm_access,
+            cold_sload,
+            sstore_set,
+            sstore_reset,
+            sstore_clears_schedule,
+        }
+    }
+
+    pub(crate) fn warm_access(&self) -> Expression<F> {
+        self.warm_access.expr()
+    }
+
+    pub(crate) fn cold_sload(&self) -> Expression<F> {
+        self.cold_sload.expr()
+    }
+
+    pub(crate) fn sstore_set(&self) -> Expression<F> {
+        self.sstore_set.expr()
+    }
+
+    pub(crate) fn sstore_reset(&self) -> Expression<F> {
+        self.sstore_reset.expr()
+    }
+
+    pub(crate) fn sstore_clears_schedule(&self) -> Expression<F> {
+        self.sstore_clears_schedule.expr()
+    }
+
+    /// Assign the schedule of the block `block_number` and return it.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block_number: u64,
+        gas_schedules: &GasSchedules,
+    ) -> Result<GasSchedule, Error> {
+        let (first_block, next_first_block, schedule) = gas_schedules
+            .ranges()
+            .into_iter()
+            .find(|(first_block, next_first_block, _)| {
+                (*first_block..*next_first_block).contains(&block_number)
+            })
+            .ok_or(Error::Synthesis)?;
+
+        for (cell, value) in [
+            (&self.block_number, block_number),
+            (&self.first_block, first_block),
+            (&self.next_first_block, next_first_block),
+            (&self.warm_access, schedule.warm_access),
+            (&self.cold_sload, schedule.cold_sload),
+            (&self.sstore_set, schedule.sstore_set),
+            (&self.sstore_reset, schedule.sstore_reset),
+            (
+                &self.sstore_clears_schedule,
+                schedule.sstore_clears_schedule,
+            ),
+        ] {
+            cell.assign(region, offset, Value::known(F::from(value)))?;
+        }
+        self.before_first_block.assign(
+            region,
+            offset,
+            F::from(block_number),
+            F::from(first_block),
+        )?;
+        self.before_next_first_block.assign(
+            region,
+            offset,
+            F::from(block_number),
+            F::from(next_first_block),
+        )?;
+
+        Ok(schedule)
+    }
+}
+
+/// Whether the Shanghai hardfork is active in the block of a tx, by comparing
+/// the block number with the first block of the hardfork in the fixed table.
+#[derive(Clone, Debug)]
+pub(crate) struct IsShanghaiGadget<F> {
+    block_number: Cell<F>,
+    shanghai_block: Cell<F>,
+    before_shanghai_block: LtGadget<F, N_BYTES_U64>,
+}
+
+impl<F: Field> IsShanghaiGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, tx_id: Expression<F>) -> Self {
+        let block_number = cb.tx_context(tx_id, TxContextFieldTag::BlockNumber, None);
+        let shanghai_block = cb.query_cell();
+        cb.shanghai_block_lookup(shanghai_block.expr());
+        let before_shanghai_block =
+            LtGadget::construct(cb, block_number.expr(), shanghai_block.expr());
+
+        Self {
+            block_number,
+            shanghai_block,
+            before_shanghai_block,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        not::expr(self.before_shanghai_block.expr())
+    }
+
+    /// Assign the block number `block_number` and return whether Shanghai is
+    /// active in it.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block_number: u64,
+        chain_spec: &ChainSpec,
+    ) -> Result<bool, Error> {
+        let shanghai_block = chain_spec.shanghai_block.unwrap_or(u64::MAX);
+        self.block_number
+            .assign(region, offset, Value::known(F::from(block_number)))?;
+        self.shanghai_block
+            .assign(region, offset, Value::known(F::from(shanghai_block)))?;
+        self.before_shanghai_block.assign(
+            region,
+            offset,
+            F::from(block_number),
+            F::from(shanghai_block),
+        )?;
+
+        Ok(chain_spec.is_shanghai(block_number))
+    }
+}
+
+/// Whether the Curie hardfork is active in the block of a tx, by comparing
+/// the block number with the first block of the hardfork in the fixed table,
+/// so that the L1 fee is charged with the formula of the hardfork.
+#[derive(Clone, Debug)]
+pub(crate) struct IsCurieGadget<F> {
+    block_number: Cell<F>,
+    curie_block: Cell<F>,
+    before_curie_block: LtGadget<F, N_BYTES_U64>,
+}
+
+impl<F: Field> IsCurieGadget<F> {
+    pub(crate) fn construct(cb: &mut EVMConstraintBuilder<F>, tx_id: Expression<F>) -> Self {
+        let block_number = cb.tx_context(tx_id, TxContextFieldTag::BlockNumber, None);
+        let curie_block = cb.query_cell();
+        cb.curie_block_lookup(curie_block.expr());
+        let before_curie_block = LtGadget::construct(cb, block_number.expr(), curie_block.expr());
+
+        Self {
+            block_number,
+            curie_block,
+            before_curie_block,
+        }
+    }
+
+    pub(crate) fn expr(&self) -> Expression<F> {
+        not::expr(self.before_curie_block.expr())
+    }
+
+    /// Assign the block number `block_number` and return whether Curie is
+    /// active in it.
+    pub(crate) fn assign(
+        &self,
+        region: &mut CachedRegion<'_, '_, F>,
+        offset: usize,
+        block_number: u64,
+        l1_fee_config: &L1FeeConfig,
+    ) -> Result<bool, Error> {
+        let curie_block = l1_fee_config.curie_block.unwrap_or(u64::MAX);
+        self.block_number
+            .assign(region, offset, Value::known(F::from(block_number)))?;
+        self.curie_block
+            .assign(region, offset, Value::known(F::from(curie_block)))?;
+        self.before_curie_block.assign(
+            region,
+            offset,
+            F::from(block_number),
+            F::from(curie_block),
+        )?;
+
+        Ok(l1_fee_config.hardfork(block_number) == L1FeeHardfork::Curie)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct SloadGasGadget<F> {
     is_warm: Expression<F>,
@@ -904,11 +1306,15 @@ pub(crate) struct SloadGasGadget<F> {
 }
 
 impl<F: Field> SloadGasGadget<F> {
-    pub(crate) fn construct(_cb: &mut EVMConstraintBuilder<F>, is_warm: Expression<F>) -> Self {
+    pub(crate) fn construct(
+        _cb: &mut EVMConstraintBuilder<F>,
+        is_warm: Expression<F>,
+        schedule: &GasScheduleGadget<F>,
+    ) -> Self {
         let gas_cost = select::expr(
             is_warm.expr(),
-            GasCost::WARM_ACCESS.expr(),
-            GasCost::COLD_SLOAD.expr(),
+            schedule.warm_access(),
+            schedule.cold_sload(),
         );
 
         Self { is_warm, gas_cost }
@@ -939,6 +1345,7 @@ impl<F: Field> SstoreGasGadget<F> {
         value_prev: Cell<F>,
         original_value: Cell<F>,
         is_warm: Cell<F>,
+        schedule: &GasScheduleGadget<F>,
     ) -> Self {
         let value_eq_prev = IsEqualGadget::construct(cb, value.expr(), value_prev.expr());
         let original_eq_prev =
@@ -946,21 +1353,21 @@ impl<F: Field> SstoreGasGadget<F> {
         let original_is_zero = IsZeroGadget::construct(cb, "", original_value.expr());
         let warm_case_gas = select::expr(
             value_eq_prev.expr(),
-            GasCost::WARM_ACCESS.expr(),
+            schedule.warm_access(),
             select::expr(
                 original_eq_prev.expr(),
                 select::expr(
                     original_is_zero.expr(),
-                    GasCost::SSTORE_SET.expr(),
-                    GasCost::SSTORE_RESET.expr(),
+                    schedule.sstore_set(),
+                    schedule.sstore_reset(),
                 ),
-                GasCost::WARM_ACCESS.expr(),
+                schedule.warm_access(),
             ),
         );
         let gas_cost = select::expr(
             is_warm.expr(),
             warm_case_gas.expr(),
-            warm_case_gas + GasCost::COLD_SLOAD.expr(),
+            warm_case_gas + schedule.cold_sload(),
         );
 
         Self {
@@ -1013,40 +1420,6 @@ impl<F: Field> SstoreGasGadget<F> {
     }
 }
 
-pub(crate) fn cal_sload_gas_cost_for_assignment(is_warm: bool) -> u64 {
-    let gas_cost = if is_warm {
-        GasCost::WARM_ACCESS
-    } else {
-        GasCost::COLD_SLOAD
-    };
-
-    gas_cost.0
-}
-
-pub(crate) fn cal_sstore_gas_cost_for_assignment(
-    value: U256,
-    value_prev: U256,
-    original_value: U256,
-    is_warm: bool,
-) -> u64 {
-    let warm_case_gas = if value_prev == value {
-        GasCost::WARM_ACCESS
-    } else if original_value == value_prev {
-        if original_value.is_zero() {
-            GasCost::SSTORE_SET
-        } else {
-            GasCost::SSTORE_RESET
-        }
-    } else {
-        GasCost::WARM_ACCESS
-    };
-    if is_warm {
-        warm_case_gas.0
-    } else {
-        warm_case_gas.0 + GasCost::COLD_SLOAD.0
-    }
-}
-
 #[derive(Clone, Debug)]
 pub(crate) struct CommonErrorGadget<F> {
     rw_counter_end_of_reversion: Cell<F>,
@@ -14,11 +14,16 @@ use bus_mapping::{
     l2_predeployed::l1_gas_price_oracle,
 };
 use eth_types::{Field, ToLittleEndian, ToScalar};
+use gadgets::util::select;
 use halo2_proofs::plonk::{Error, Expression};
 
-/// Transaction L1 fee gadget for L1GasPriceOracle contract
+/// Transaction L1 fee gadget for L1GasPriceOracle contract, with the formula
+/// before Curie or the one since Curie, see
+/// [`L1FeeHardfork`](bus_mapping::circuit_input_builder::L1FeeHardfork).
 #[derive(Clone, Debug)]
 pub(crate) struct TxL1FeeGadget<F> {
+    /// Whether the fee is charged with the formula since Curie
+    is_curie: Expression<F>,
     /// Calculated L1 fee of transaction
     tx_l1_fee_word: U64Word<F>,
     /// Remainder when calculating L1 fee
@@ -29,97 +34,146 @@ pub(crate) struct TxL1FeeGadget<F> {
     fee_overhead_word: U64Word<F>,
     /// Current value of L1 fee scalar
     fee_scalar_word: U64Word<F>,
+    /// Current value of L1 blob base fee, zero before Curie
+    blob_base_fee_word: U64Word<F>,
+    /// Current value of L1 commit scalar, zero before Curie
+    commit_scalar_word: U64Word<F>,
+    /// Current value of L1 blob scalar, zero before Curie
+    blob_scalar_word: U64Word<F>,
     /// Committed value of L1 base fee
     base_fee_committed: Cell<F>,
     /// Committed value of L1 fee overhead
     fee_overhead_committed: Cell<F>,
     /// Committed value of L1 fee scalar
     fee_scalar_committed: Cell<F>,
+    /// Committed value of L1 blob base fee
+    blob_base_fee_committed: Cell<F>,
+    /// Committed value of L1 commit scalar
+    commit_scalar_committed: Cell<F>,
+    /// Committed value of L1 blob scalar
+    blob_scalar_committed: Cell<F>,
 }
 
 impl<F: Field> TxL1FeeGadget<F> {
+    /// Construct the gadget of the L1 fee of the tx `tx_id`, whose signed rlp
+    /// has the data gas cost `tx_data_gas_cost` and the length `tx_len`. The
+    /// params of the formula since Curie are only read when `is_curie`.
     pub(crate) fn construct(
         cb: &mut EVMConstraintBuilder<F>,
         tx_id: Expression<F>,
+        is_curie: Expression<F>,
         tx_data_gas_cost: Expression<F>,
+        tx_len: Expression<F>,
     ) -> Self {
-        let this = Self::raw_construct(cb, tx_data_gas_cost);
+        let this = Self::raw_construct(cb, is_curie, tx_data_gas_cost, tx_len);
 
         let l1_fee_address = Expression::Constant(l1_gas_price_oracle::ADDRESS.to_scalar().expect(
             "Unexpected address of l2 gasprice oracle contract -> Scalar conversion failure",
         ));
 
-        let [base_fee_slot, overhead_slot, scalar_slot] = [
-            &l1_gas_price_oracle::BASE_FEE_SLOT,
-            &l1_gas_price_oracle::OVERHEAD_SLOT,
-            &l1_gas_price_oracle::SCALAR_SLOT,
-        ]
-        .map(|slot| cb.word_rlc(slot.to_le_bytes().map(|b| b.expr())));
-
-        // Read L1 base fee
-        cb.account_storage_read(
-            l1_fee_address.expr(),
-            base_fee_slot,
-            this.base_fee_word.expr(),
-            tx_id.expr(),
-            this.base_fee_committed.expr(),
-        );
-
-        // Read L1 fee overhead
-        cb.account_storage_read(
-            l1_fee_address.expr(),
-            overhead_slot,
-            this.fee_overhead_word.expr(),
-            tx_id.expr(),
-            this.fee_overhead_committed.expr(),
-        );
+        // Read L1 base fee, L1 fee overhead and L1 fee scalar
+        for (slot, value, committed_value) in [
+            (
+                &l1_gas_price_oracle::BASE_FEE_SLOT,
+                &this.base_fee_word,
+                &this.base_fee_committed,
+            ),
+            (
+                &l1_gas_price_oracle::OVERHEAD_SLOT,
+                &this.fee_overhead_word,
+                &this.fee_overhead_committed,
+            ),
+            (
+                &l1_gas_price_oracle::SCALAR_SLOT,
+                &this.fee_scalar_word,
+                &this.fee_scalar_committed,
+            ),
+        ] {
+            let slot = cb.word_rlc(slot.to_le_bytes().map(|b| b.expr()));
+            cb.account_storage_read(
+                l1_fee_address.expr(),
+                slot,
+                value.expr(),
+                tx_id.expr(),
+                committed_value.expr(),
+            );
+        }
 
-        // Read L1 fee scalar
-        cb.account_storage_read(
-            l1_fee_address,
-            scalar_slot,
-            this.fee_scalar_word.expr(),
-            tx_id,
-            this.fee_scalar_committed.expr(),
-        );
+        // Read L1 blob base fee, L1 commit scalar and L1 blob scalar since
+        // Curie
+        cb.condition(this.is_curie.expr(), |cb| {
+            for (slot, value, committed_value) in [
+                (
+                    &l1_gas_price_oracle::BLOB_BASE_FEE_SLOT,
+                    &this.blob_base_fee_word,
+                    &this.blob_base_fee_committed,
+                ),
+                (
+                    &l1_gas_price_oracle::COMMIT_SCALAR_SLOT,
+                    &this.commit_scalar_word,
+                    &this.commit_scalar_committed,
+                ),
+                (
+                    &l1_gas_price_oracle::BLOB_SCALAR_SLOT,
+                    &this.blob_scalar_word,
+                    &this.blob_scalar_committed,
+                ),
+            ] {
+                let slot = cb.word_rlc(slot.to_le_bytes().map(|b| b.expr()));
+                cb.account_storage_read(
+                    l1_fee_address.expr(),
+                    slot,
+                    value.expr(),
+                    tx_id.expr(),
+                    committed_value.expr(),
+                );
+            }
+        });
 
         this
     }
 
+    /// Assign the L1 fee of the tx whose signed rlp is `rlp_signed`, charged
+    /// with the formula of `l1_fee.hardfork`.
     pub(crate) fn assign(
         &self,
         region: &mut CachedRegion<'_, '_, F>,
         offset: usize,
         l1_fee: TxL1Fee,
         l1_fee_committed: TxL1Fee,
-        tx_data_gas_cost: u64,
+        rlp_signed: &[u8],
     ) -> Result<(), Error> {
-        let (tx_l1_fee, remainder) = l1_fee.tx_l1_fee(tx_data_gas_cost);
+        let (tx_l1_fee, remainder) = l1_fee.hardfork.oracle().tx_l1_fee(&l1_fee, rlp_signed);
         self.tx_l1_fee_word
             .assign(region, offset, Some(tx_l1_fee.to_le_bytes()))?;
         self.remainder_word
             .assign(region, offset, Some(remainder.to_le_bytes()))?;
-        self.base_fee_word
-            .assign(region, offset, Some(l1_fee.base_fee.to_le_bytes()))?;
-        self.fee_overhead_word
-            .assign(region, offset, Some(l1_fee.fee_overhead.to_le_bytes()))?;
-        self.fee_scalar_word
-            .assign(region, offset, Some(l1_fee.fee_scalar.to_le_bytes()))?;
-        self.base_fee_committed.assign(
-            region,
-            offset,
-            region.word_rlc(l1_fee_committed.base_fee.into()),
-        )?;
-        self.fee_overhead_committed.assign(
-            region,
-            offset,
-            region.word_rlc(l1_fee_committed.fee_overhead.into()),
-        )?;
-        self.fee_scalar_committed.assign(
-            region,
-            offset,
-            region.word_rlc(l1_fee_committed.fee_scalar.into()),
-        )?;
+        for (word, value) in [
+            (&self.base_fee_word, l1_fee.base_fee),
+            (&self.fee_overhead_word, l1_fee.fee_overhead),
+            (&self.fee_scalar_word, l1_fee.fee_scalar),
+            (&self.blob_base_fee_word, l1_fee.blob_base_fee),
+            (&self.commit_scalar_word, l1_fee.commit_scalar),
+            (&self.blob_scalar_word, l1_fee.blob_scalar),
+        ] {
+            word.assign(region, offset, Some(value.to_le_bytes()))?;
+        }
+        for (cell, value) in [
+            (&self.base_fee_committed, l1_fee_committed.base_fee),
+            (&self.fee_overhead_committed, l1_fee_committed.fee_overhead),
+            (&self.fee_scalar_committed, l1_fee_committed.fee_scalar),
+            (
+                &self.blob_base_fee_committed,
+                l1_fee_committed.blob_base_fee,
+            ),
+            (
+                &self.commit_scalar_committed,
+                l1_fee_committed.commit_scalar,
+            ),
+            (&self.blob_scalar_committed, l1_fee_committed.blob_scalar),
+        ] {
+            cell.assign(region, offset, region.word_rlc(value.into()))?;
+        }
 
         Ok(())
     }
@@ -128,51 +182,77 @@ impl<F: Field> TxL1FeeGadget<F> {
         // L1 base fee Read
         // L1 fee overhead Read
         // L1 fee scalar Read
-        3.expr()
+        // L1 blob base fee, L1 commit scalar and L1 blob scalar Reads since Curie
+        3.expr() + 3.expr() * self.is_curie.expr()
     }
 
     pub(crate) fn tx_l1_fee(&self) -> &U64Word<F> {
         &self.tx_l1_fee_word
     }
 
-    fn raw_construct(cb: &mut EVMConstraintBuilder<F>, tx_data_gas_cost: Expression<F>) -> Self {
+    fn raw_construct(
+        cb: &mut EVMConstraintBuilder<F>,
+        is_curie: Expression<F>,
+        tx_data_gas_cost: Expression<F>,
+        tx_len: Expression<F>,
+    ) -> Self {
         let tx_l1_fee_word = cb.query_word_rlc();
         let remainder_word = cb.query_word_rlc();
 
         let base_fee_word = cb.query_word_rlc();
         let fee_overhead_word = cb.query_word_rlc();
         let fee_scalar_word = cb.query_word_rlc();
+        let blob_base_fee_word = cb.query_word_rlc();
+        let commit_scalar_word = cb.query_word_rlc();
+        let blob_scalar_word = cb.query_word_rlc();
 
-        let [tx_l1_fee, remainder, base_fee, fee_overhead, fee_scalar] = [
-            &tx_l1_fee_word,
-            &remainder_word,
-            &base_fee_word,
-            &fee_overhead_word,
-            &fee_scalar_word,
-        ]
-        .map(|word| from_bytes::expr(&word.cells[..N_BYTES_U64]));
+        let [tx_l1_fee, remainder, base_fee, fee_overhead, fee_scalar, blob_base_fee, commit_scalar, blob_scalar] =
+            [
+                &tx_l1_fee_word,
+                &remainder_word,
+                &base_fee_word,
+                &fee_overhead_word,
+                &fee_scalar_word,
+                &blob_base_fee_word,
+                &commit_scalar_word,
+                &blob_scalar_word,
+            ]
+            .map(|word| from_bytes::expr(&word.cells[..N_BYTES_U64]));
 
         // <https://github.com/scroll-tech/go-ethereum/blob/49192260a177f1b63fc5ea3b872fb904f396260c/rollup/fees/rollup_fee.go#L118>
         let tx_l1_gas = tx_data_gas_cost + TX_L1_COMMIT_EXTRA_COST.expr() + fee_overhead;
+        let bernoulli_fee = fee_scalar * base_fee.clone() * tx_l1_gas;
+        // <https://github.com/scroll-tech/go-ethereum/blob/develop/rollup/fees/rollup_fee.go>
+        let curie_fee = commit_scalar * base_fee + blob_scalar * blob_base_fee * tx_len;
         cb.require_equal(
-            "fee_scalar * base_fee * tx_l1_gas == tx_l1_fee * 10e9 + remainder",
-            fee_scalar * base_fee * tx_l1_gas,
+            "l1 fee of the formula of the hardfork == tx_l1_fee * 10e9 + remainder",
+            select::expr(is_curie.expr(), curie_fee, bernoulli_fee),
             tx_l1_fee * TX_L1_FEE_PRECISION.expr() + remainder,
         );
 
         let base_fee_committed = cb.query_cell_phase2();
         let fee_overhead_committed = cb.query_cell_phase2();
         let fee_scalar_committed = cb.query_cell_phase2();
+        let blob_base_fee_committed = cb.query_cell_phase2();
+        let commit_scalar_committed = cb.query_cell_phase2();
+        let blob_scalar_committed = cb.query_cell_phase2();
 
         Self {
+            is_curie,
             tx_l1_fee_word,
             remainder_word,
             base_fee_word,
             fee_overhead_word,
             fee_scalar_word,
+            blob_base_fee_word,
+            commit_scalar_word,
+            blob_scalar_word,
             base_fee_committed,
             fee_overhead_committed,
             fee_scalar_committed,
+            blob_base_fee_committed,
+            commit_scalar_committed,
+            blob_scalar_committed,
         }
     }
 }
@@ -184,57 +264,119 @@ mod tests {
         constraint_builder::ConstrainBuilderCommon,
         math_gadget::test_util::{test_math_gadget_container, try_test, MathGadgetContainer},
     };
-    use eth_types::{ToScalar, U256};
+    use bus_mapping::circuit_input_builder::L1FeeHardfork;
+    use eth_types::{evm_types::gas_utils::tx_data_gas_cost, ToScalar, U256};
     use halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
 
     // <https://github.com/scroll-tech/go-ethereum/blob/develop/rollup/fees/rollup_fee_test.go>
     const TEST_BASE_FEE: u64 = 15_000_000;
     const TEST_FEE_OVERHEAD: u64 = 100;
     const TEST_FEE_SCALAR: u64 = 10;
-    const TEST_TX_DATA_GAS_COST: u64 = 40; // 2 (zeros) * 4 + 2 (non-zeros) * 16
+    const TEST_TX_DATA: [u8; 4] = [0, 0, 1, 1]; // 2 (zeros) * 4 + 2 (non-zeros) * 16
     const TEST_TX_L1_FEE: u128 = 30;
 
-    #[test]
-    fn test_tx_l1_fee_with_right_values() {
-        let witnesses = [
+    const TEST_CURIE_BASE_FEE: u64 = 1_500_000_000;
+    const TEST_CURIE_BLOB_BASE_FEE: u64 = 150_000_000;
+    const TEST_CURIE_COMMIT_SCALAR: u64 = 10;
+    const TEST_CURIE_BLOB_SCALAR: u64 = 10;
+    const TEST_CURIE_TX_DATA: [u8; 4] = [0, 10, 1, 0];
+    const TEST_CURIE_TX_L1_FEE: u128 = 21;
+
+    fn bernoulli_witnesses(tx_l1_fee: u128) -> Vec<U256> {
+        [
+            0,
             TEST_BASE_FEE.into(),
             TEST_FEE_OVERHEAD.into(),
             TEST_FEE_SCALAR.into(),
-            TEST_TX_DATA_GAS_COST.into(),
-            TEST_TX_L1_FEE,
+            0,
+            0,
+            0,
+            tx_l1_fee,
+        ]
+        .into_iter()
+        .map(U256::from)
+        .chain(TEST_TX_DATA.map(U256::from))
+        .collect()
+    }
+
+    fn curie_witnesses(tx_l1_fee: u128) -> Vec<U256> {
+        [
+            1,
+            TEST_CURIE_BASE_FEE.into(),
+            0,
+            0,
+            TEST_CURIE_BLOB_BASE_FEE.into(),
+            TEST_CURIE_COMMIT_SCALAR.into(),
+            TEST_CURIE_BLOB_SCALAR.into(),
+            tx_l1_fee,
         ]
-        .map(U256::from);
+        .into_iter()
+        .map(U256::from)
+        .chain(TEST_CURIE_TX_DATA.map(U256::from))
+        .collect()
+    }
 
-        try_test!(TxL1FeeGadgetTestContainer<Fr>, witnesses, true);
+    #[test]
+    fn test_tx_l1_fee_with_right_values() {
+        try_test!(
+            TxL1FeeGadgetTestContainer<Fr>,
+            bernoulli_witnesses(TEST_TX_L1_FEE),
+            true
+        );
     }
 
     #[test]
     fn test_tx_l1_fee_with_wrong_values() {
-        let witnesses = [
-            TEST_BASE_FEE.into(),
-            TEST_FEE_OVERHEAD.into(),
-            TEST_FEE_SCALAR.into(),
-            TEST_TX_DATA_GAS_COST.into(),
-            TEST_TX_L1_FEE + 1,
-        ]
-        .map(U256::from);
+        try_test!(
+            TxL1FeeGadgetTestContainer<Fr>,
+            bernoulli_witnesses(TEST_TX_L1_FEE + 1),
+            false
+        );
+    }
 
-        try_test!(TxL1FeeGadgetTestContainer<Fr>, witnesses, false);
+    #[test]
+    fn test_tx_l1_fee_curie_with_right_values() {
+        try_test!(
+            TxL1FeeGadgetTestContainer<Fr>,
+            curie_witnesses(TEST_CURIE_TX_L1_FEE),
+            true
+        );
+    }
+
+    #[test]
+    fn test_tx_l1_fee_curie_with_wrong_values() {
+        try_test!(
+            TxL1FeeGadgetTestContainer<Fr>,
+            curie_witnesses(TEST_CURIE_TX_L1_FEE + 1),
+            false
+        );
     }
 
+    /// The witnesses are `[is_curie, base_fee, fee_overhead, fee_scalar,
+    /// blob_base_fee, commit_scalar, blob_scalar, expected_tx_l1_fee,
+    /// ..rlp_signed]`.
     #[derive(Clone)]
     struct TxL1FeeGadgetTestContainer<F> {
         gadget: TxL1FeeGadget<F>,
+        is_curie: Cell<F>,
         tx_data_gas_cost: Cell<F>,
+        tx_len: Cell<F>,
         expected_tx_l1_fee: Cell<F>,
     }
 
     impl<F: Field> MathGadgetContainer<F> for TxL1FeeGadgetTestContainer<F> {
         fn configure_gadget_container(cb: &mut EVMConstraintBuilder<F>) -> Self {
+            let is_curie = cb.query_bool();
             let tx_data_gas_cost = cb.query_cell();
+            let tx_len = cb.query_cell();
             let expected_tx_l1_fee = cb.query_cell();
 
-            let gadget = TxL1FeeGadget::<F>::raw_construct(cb, tx_data_gas_cost.expr());
+            let gadget = TxL1FeeGadget::<F>::raw_construct(
+                cb,
+                is_curie.expr(),
+                tx_data_gas_cost.expr(),
+                tx_len.expr(),
+            );
 
             cb.require_equal(
                 "tx_l1_fee must be correct",
@@ -244,7 +386,9 @@ mod tests {
 
             TxL1FeeGadgetTestContainer {
                 gadget,
+                is_curie,
                 tx_data_gas_cost,
+                tx_len,
                 expected_tx_l1_fee,
             }
         }
@@ -254,29 +398,41 @@ mod tests {
             witnesses: &[U256],
             region: &mut CachedRegion<'_, '_, F>,
         ) -> Result<(), Error> {
-            let [base_fee, fee_overhead, fee_scalar] = [0, 1, 2].map(|i| witnesses[i].as_u64());
+            let is_curie = !witnesses[0].is_zero();
+            let [base_fee, fee_overhead, fee_scalar, blob_base_fee, commit_scalar, blob_scalar] =
+                [1, 2, 3, 4, 5, 6].map(|i| witnesses[i].as_u64());
             let l1_fee = TxL1Fee {
+                hardfork: if is_curie {
+                    L1FeeHardfork::Curie
+                } else {
+                    L1FeeHardfork::Bernoulli
+                },
                 base_fee,
                 fee_overhead,
                 fee_scalar,
+                blob_base_fee,
+                commit_scalar,
+                blob_scalar,
             };
-            let tx_data_gas_cost = witnesses[3];
-            self.gadget.assign(
-                region,
-                0,
-                l1_fee,
-                TxL1Fee::default(),
-                tx_data_gas_cost.as_u64(),
-            )?;
+            let rlp_signed = witnesses[8..]
+                .iter()
+                .map(|byte| byte.as_u32() as u8)
+                .collect::<Vec<_>>();
+            self.gadget
+                .assign(region, 0, l1_fee, TxL1Fee::default(), &rlp_signed)?;
+            self.is_curie
+                .assign(region, 0, Value::known(F::from(is_curie as u64)))?;
             self.tx_data_gas_cost.assign(
                 region,
                 0,
-                Value::known(tx_data_gas_cost.to_scalar().unwrap()),
+                Value::known(F::from(tx_data_gas_cost(&rlp_signed))),
             )?;
+            self.tx_len
+                .assign(region, 0, Value::known(F::from(rlp_signed.len() as u64)))?;
             self.expected_tx_l1_fee.assign(
                 region,
                 0,
-                Value::known(witnesses[4].to_scalar().unwrap()),
+                Value::known(witnesses[7].to_scalar().unwrap()),
             )?;
 
             Ok(())
@@ -2,7 +2,7 @@ use crate::{
     evm_circuit::{
         param::STACK_CAPACITY,
         step::{ExecutionState, Step},
-        table::{FixedTableTag, Lookup, RwValues},
+        table::{FixedTableTag, GasScheduleField, Lookup, RwValues, Table},
         util::{Cell, RandomLinearCombination, Word},
     },
     table::{
@@ -494,6 +494,22 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         rlc::expr(&bytes, self.challenges.keccak_input())
     }
 
+    /// Powers of the keccak randomness by 32 bytes words, `r^32, r^64, ..`,
+    /// which the gadgets packing words into an RLC share. They are
+    /// expressions of the challenge, so they take no phase-2 cell.
+    pub(crate) fn keccak_word_powers_of_randomness<const N: usize>(&self) -> [Expression<F>; N] {
+        let r_pow_32 = self.challenges.keccak_powers_of_randomness::<16>()[15]
+            .clone()
+            .square();
+        std::iter::successors(Some(r_pow_32.clone()), |pow| {
+            Some(pow.clone() * r_pow_32.clone())
+        })
+        .take(N)
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+    }
+
     pub(crate) fn empty_keccak_hash_rlc(&self) -> Expression<F> {
         let bytes = KECCAK_CODE_HASH_ZERO.to_word().to_le_bytes();
         self.word_rlc(bytes.map(|byte| byte.expr()))
@@ -615,6 +631,66 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    // field of the gas schedule applying from `first_block`
+    pub(crate) fn gas_schedule_lookup(
+        &mut self,
+        first_block: Expression<F>,
+        field: GasScheduleField,
+        value: Expression<F>,
+    ) {
+        self.add_lookup(
+            "gas schedule",
+            Lookup::Fixed {
+                tag: FixedTableTag::GasSchedule.expr(),
+                values: [first_block, field.expr(), value],
+            },
+        );
+    }
+
+    // first block of the Shanghai hardfork
+    pub(crate) fn shanghai_block_lookup(&mut self, shanghai_block: Expression<F>) {
+        self.add_lookup(
+            "shanghai block",
+            Lookup::Fixed {
+                tag: FixedTableTag::ShanghaiBlock.expr(),
+                values: [shanghai_block, 0.expr(), 0.expr()],
+            },
+        );
+    }
+
+    // first block of the Curie hardfork
+    pub(crate) fn curie_block_lookup(&mut self, curie_block: Expression<F>) {
+        self.add_lookup(
+            "curie block",
+            Lookup::Fixed {
+                tag: FixedTableTag::CurieBlock.expr(),
+                values: [curie_block, 0.expr(), 0.expr()],
+            },
+        );
+    }
+
+    // maximum size of the code of a contract
+    pub(crate) fn max_code_size_lookup(&mut self, max_code_size: Expression<F>) {
+        self.add_lookup(
+            "max code size",
+            Lookup::Fixed {
+                tag: FixedTableTag::MaxCodeSize.expr(),
+                values: [max_code_size, 0.expr(), 0.expr()],
+            },
+        );
+    }
+
+    // maximum size of init code
+    pub(crate) fn max_init_code_size_lookup(&mut self, max_init_code_size: Expression<F>) {
+        self.add_lookup(
+            "max init code size",
+            Lookup::Fixed {
+                tag: FixedTableTag::MaxInitCodeSize.expr(),
+                values: [max_init_code_size, 0.expr(), 0.expr()],
+            },
+        );
+    }
+
     // Opcode
 
     pub(crate) fn opcode_lookup(&mut self, opcode: Expression<F>, is_code: Expression<F>) {
@@ -878,6 +954,101 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         );
     }
 
+    pub(crate) fn account_created_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TxCreatedAccount write",
+            RwTableTag::TxCreatedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                1.expr(),
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
+    pub(crate) fn account_created_read(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "account created read",
+            false.expr(),
+            RwTableTag::TxCreatedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                value.clone(),
+                value,
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
+    pub(crate) fn account_destructed_write(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+        value_prev: Expression<F>,
+        reversion_info: Option<&mut ReversionInfo<F>>,
+    ) {
+        self.reversible_write(
+            "TxDestructedAccount write",
+            RwTableTag::TxDestructedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                1.expr(),
+                value_prev,
+                0.expr(),
+                0.expr(),
+            ),
+            reversion_info,
+        );
+    }
+
+    /// Clear the destructed flag of an account once it's deleted at the end of
+    /// the tx.
+    pub(crate) fn account_destructed_clear(
+        &mut self,
+        tx_id: Expression<F>,
+        account_address: Expression<F>,
+    ) {
+        self.rw_lookup(
+            "TxDestructedAccount clear",
+            true.expr(),
+            RwTableTag::TxDestructedAccount,
+            RwValues::new(
+                tx_id,
+                account_address,
+                0.expr(),
+                0.expr(),
+                0.expr(),
+                1.expr(),
+                0.expr(),
+                0.expr(),
+            ),
+        );
+    }
+
     pub(crate) fn account_storage_access_list_write(
         &mut self,
         tx_id: Expression<F>,
@@ -1645,6 +1816,20 @@ impl<'a, F: Field> EVMConstraintBuilder<'a, F> {
         }
     }
 
+    /// Table and name of the lookups added so far.
+    pub(crate) fn lookups(&self) -> Vec<(Table, String)> {
+        self.stored_expressions
+            .iter()
+            .filter_map(|e| match e.cell_type {
+                CellType::Lookup(table) => Some((
+                    table,
+                    e.name.trim_end_matches(" (stored expression)").to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub(crate) fn find_stored_expression(
         &self,
         expr: &Expression<F>,
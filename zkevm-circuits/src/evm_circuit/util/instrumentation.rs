@@ -5,6 +5,8 @@ use crate::evm_circuit::{
 };
 use halo2_proofs::arithmetic::FieldExt;
 use itertools::Itertools;
+use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
 
 type StepSize = Vec<(CellType, ColumnSize)>;
 /// Contains (width, height, num_cells)
@@ -15,6 +17,8 @@ type ColumnSize = (usize, usize, usize);
 pub(crate) struct Instrument {
     // States -> Cell Types -> (width, height, num_cells)
     states: Vec<(ExecutionState, StepSize)>,
+    // States -> (Table, lookup name)
+    lookups: Vec<(ExecutionState, Vec<(Table, String)>)>,
 }
 
 impl Instrument {
@@ -34,6 +38,49 @@ impl Instrument {
             .collect::<Vec<_>>();
 
         self.states.push((execution_state, sizes));
+        self.lookups.push((execution_state, cb.lookups()));
+    }
+
+    /// Returns a `LookupReport` for each EVM `ExecutionState`, with the
+    /// lookups its gadget performs into each table.
+    pub(crate) fn analyze_lookups(&self) -> Vec<LookupReport> {
+        self.states
+            .iter()
+            .zip(self.lookups.iter())
+            .map(|((state, sizes), (_, lookups))| {
+                let mut report = LookupReport {
+                    state: *state,
+                    height: sizes.iter().map(|(_, (_, h, _))| *h).max().unwrap_or(0),
+                    lookups: BTreeMap::new(),
+                };
+                for (table, name) in lookups {
+                    report
+                        .lookups
+                        .entry(*table)
+                        .or_insert_with(Vec::new)
+                        .push(name.clone());
+                }
+                report
+            })
+            .collect()
+    }
+
+    /// Emits the matrix of the number of lookups of each `ExecutionState`
+    /// (rows) into each table (columns) as CSV.
+    pub(crate) fn lookup_matrix_csv(&self) -> String {
+        let mut csv = std::iter::once("state,height".to_string())
+            .chain(Table::iter().map(|table| format!("{table:?}")))
+            .join(",");
+        csv.push('\n');
+        for report in self.analyze_lookups() {
+            let row = [format!("{:?}", report.state), report.height.to_string()]
+                .into_iter()
+                .chain(Table::iter().map(|table| report.num_lookups(table).to_string()))
+                .join(",");
+            csv.push_str(&row);
+            csv.push('\n');
+        }
+        csv
     }
 
     /// Dissasembles the instrumentation data and returns a collection of
@@ -178,3 +225,48 @@ pub(crate) struct StateReportRow {
     // The percentage of cells used in computation in the x * y region.
     pub(crate) utilization: f64,
 }
+
+/// Lookups performed by the gadget of a particular EVM `ExecutionState`.
+#[derive(Clone, Debug)]
+pub(crate) struct LookupReport {
+    pub(crate) state: ExecutionState,
+    // Height of the step.
+    pub(crate) height: usize,
+    // Names of the lookups into each table.
+    pub(crate) lookups: BTreeMap<Table, Vec<String>>,
+}
+
+impl LookupReport {
+    /// Number of lookups into `table`.
+    pub(crate) fn num_lookups(&self, table: Table) -> usize {
+        self.lookups.get(&table).map_or(0, |names| names.len())
+    }
+
+    /// Number of lookup columns of `table` needed to fit the lookups in the
+    /// height of the step. The EVM circuit needs the max of it over all the
+    /// states, so this tells which states would grow with fewer columns.
+    pub(crate) fn required_columns(&self, table: Table) -> usize {
+        if self.height == 0 {
+            0
+        } else {
+            (self.num_lookups(table) + self.height - 1) / self.height
+        }
+    }
+
+    /// Lookups sharing their name with another lookup into the same table,
+    /// which are candidates for being merged.
+    pub(crate) fn redundant_lookups(&self) -> Vec<(Table, String, usize)> {
+        self.lookups
+            .iter()
+            .flat_map(|(table, names)| {
+                names
+                    .iter()
+                    .counts()
+                    .into_iter()
+                    .filter(|(_, count)| *count > 1)
+                    .map(|(name, count)| (*table, name.clone(), count))
+                    .sorted()
+            })
+            .collect()
+    }
+}
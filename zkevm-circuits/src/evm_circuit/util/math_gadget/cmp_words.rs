@@ -1,11 +1,8 @@
-use crate::{
-    evm_circuit::util::{
-        self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, select,
-        CachedRegion,
-    },
-    util::Expr,
+use crate::evm_circuit::util::{
+    self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, CachedRegion,
 };
-use eth_types::{Field, ToLittleEndian, Word};
+use eth_types::{Field, Word};
+use gadgets::word_cmp::{lt_word_expr, word_lo_hi};
 use halo2_proofs::plonk::{Error, Expression};
 
 #[derive(Clone, Debug)]
@@ -43,7 +40,7 @@ impl<F: Field> CmpWordsGadget<F> {
         // `a < b` when:
         // - `a[16..32] < b[16..32]` OR
         // - `a[16..32] == b[16..32]` AND `a[0..16] < b[0..16]`
-        let lt = select::expr(lt_hi, 1.expr(), eq_hi.clone() * lt_lo);
+        let lt = lt_word_expr(lt_hi, eq_hi.clone(), lt_lo);
 
         // `a == b` when both parts are equal
         let eq = eq_hi * eq_lo;
@@ -63,21 +60,14 @@ impl<F: Field> CmpWordsGadget<F> {
         a: Word,
         b: Word,
     ) -> Result<(), Error> {
-        // `a[0..1] <= b[0..16]`
-        self.comparison_lo.assign(
-            region,
-            offset,
-            from_bytes::value(&a.to_le_bytes()[0..16]),
-            from_bytes::value(&b.to_le_bytes()[0..16]),
-        )?;
+        let (a_lo, a_hi) = word_lo_hi(a);
+        let (b_lo, b_hi) = word_lo_hi(b);
+
+        // `a[0..16] <= b[0..16]`
+        self.comparison_lo.assign(region, offset, a_lo, b_lo)?;
 
         // `a[16..32] <= b[16..32]`
-        self.comparison_hi.assign(
-            region,
-            offset,
-            from_bytes::value(&a.to_le_bytes()[16..32]),
-            from_bytes::value(&b.to_le_bytes()[16..32]),
-        )?;
+        self.comparison_hi.assign(region, offset, a_hi, b_hi)?;
 
         Ok(())
     }
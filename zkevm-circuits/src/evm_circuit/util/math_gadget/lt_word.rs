@@ -1,8 +1,8 @@
 use crate::evm_circuit::util::{
-    self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, split_u256,
-    CachedRegion,
+    self, constraint_builder::EVMConstraintBuilder, from_bytes, math_gadget::*, CachedRegion,
 };
 use eth_types::{Field, Word};
+use gadgets::word_cmp::{lt_word_expr, word_lo_hi};
 use halo2_proofs::plonk::{Error, Expression};
 
 /// Returns `1` when `lhs < rhs`, and returns `0` otherwise.
@@ -37,7 +37,7 @@ impl<F: Field> LtWordGadget<F> {
 
     pub(crate) fn expr(&self) -> Expression<F> {
         let (hi_lt, hi_eq) = self.comparison_hi.expr();
-        hi_lt + hi_eq * self.lt_lo.expr()
+        lt_word_expr(hi_lt, hi_eq, self.lt_lo.expr())
     }
 
     pub(crate) fn assign(
@@ -47,20 +47,10 @@ impl<F: Field> LtWordGadget<F> {
         lhs: Word,
         rhs: Word,
     ) -> Result<(), Error> {
-        let (lhs_lo, lhs_hi) = split_u256(&lhs);
-        let (rhs_lo, rhs_hi) = split_u256(&rhs);
-        self.comparison_hi.assign(
-            region,
-            offset,
-            F::from_u128(lhs_hi.as_u128()),
-            F::from_u128(rhs_hi.as_u128()),
-        )?;
-        self.lt_lo.assign(
-            region,
-            offset,
-            F::from_u128(lhs_lo.as_u128()),
-            F::from_u128(rhs_lo.as_u128()),
-        )?;
+        let (lhs_lo, lhs_hi) = word_lo_hi(lhs);
+        let (rhs_lo, rhs_hi) = word_lo_hi(rhs);
+        self.comparison_hi.assign(region, offset, lhs_hi, rhs_hi)?;
+        self.lt_lo.assign(region, offset, lhs_lo, rhs_lo)?;
         Ok(())
     }
 }
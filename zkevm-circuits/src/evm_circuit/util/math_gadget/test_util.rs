@@ -11,7 +11,7 @@ use crate::{
             constraint_builder::EVMConstraintBuilder, rlc, CachedRegion, CellType, Expr,
             StoredExpression, LOOKUP_CONFIG,
         },
-        Advice, Column, Fixed,
+        Advice, Column, EvmCircuitParams, Fixed,
     },
     table::LookupTable,
 };
@@ -243,7 +243,7 @@ impl<F: Field, G: MathGadgetContainer<F>> Circuit<F> for UnitTestMathGadgetBaseC
                                         | FixedTableTag::Range1024
                                 )
                             })
-                            .flat_map(|tag| tag.build()),
+                            .flat_map(|tag| tag.build(&EvmCircuitParams::default())),
                     )
                     .enumerate()
                 {
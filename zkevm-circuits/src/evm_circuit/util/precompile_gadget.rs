@@ -88,6 +88,7 @@ impl<F: Field> PrecompileGadget<F> {
             address.value_equals(PrecompileCalls::Bn128Mul),
             address.value_equals(PrecompileCalls::Bn128Pairing),
             address.value_equals(PrecompileCalls::Blake2F),
+            address.value_equals(PrecompileCalls::PointEvaluation),
         ];
         let next_states = vec![
             ExecutionState::PrecompileEcrecover,
@@ -99,6 +100,7 @@ impl<F: Field> PrecompileGadget<F> {
             ExecutionState::PrecompileBn256ScalarMul,
             ExecutionState::PrecompileBn256Pairing,
             ExecutionState::PrecompileBlake2f,
+            ExecutionState::PrecompilePointEvaluation,
         ];
         let constraints: Vec<BoxedClosure<F>> = vec![
             Box::new(|cb| {
@@ -110,14 +112,7 @@ impl<F: Field> PrecompileGadget<F> {
                     cb.query_cell_phase2(),
                     cb.query_keccak_rlc::<N_BYTES_ACCOUNT_ADDRESS>(),
                 );
-                let (r_pow_32, r_pow_64, r_pow_96) = {
-                    let challenges = cb.challenges().keccak_powers_of_randomness::<16>();
-                    let r_pow_16 = challenges[15].clone();
-                    let r_pow_32 = r_pow_16.square();
-                    let r_pow_64 = r_pow_32.expr().square();
-                    let r_pow_96 = r_pow_64.expr() * r_pow_32.expr();
-                    (r_pow_32, r_pow_64, r_pow_96)
-                };
+                let [r_pow_32, r_pow_64, r_pow_96] = cb.keccak_word_powers_of_randomness();
                 cb.require_equal(
                     "input bytes (RLC) = [msg_hash | sig_v_rlc | sig_r | sig_s]",
                     padding_gadget.padded_rlc(),
@@ -163,14 +158,7 @@ impl<F: Field> PrecompileGadget<F> {
                     cb.query_cell_phase2(),
                     cb.query_cell_phase2(),
                 );
-                let (r_pow_32, r_pow_64, r_pow_96) = {
-                    let challenges = cb.challenges().keccak_powers_of_randomness::<16>();
-                    let r_pow_16 = challenges[15].clone();
-                    let r_pow_32 = r_pow_16.square();
-                    let r_pow_64 = r_pow_32.expr().square();
-                    let r_pow_96 = r_pow_64.expr() * r_pow_32.expr();
-                    (r_pow_32, r_pow_64, r_pow_96)
-                };
+                let [r_pow_32, r_pow_64, r_pow_96] = cb.keccak_word_powers_of_randomness();
                 cb.require_equal(
                     "input bytes (RLC) = [ p_x | p_y | q_x | q_y ]",
                     padding_gadget.padded_rlc(),
@@ -194,13 +182,7 @@ impl<F: Field> PrecompileGadget<F> {
                     cb.query_cell_phase2(),
                     cb.query_cell_phase2(),
                 );
-                let (r_pow_32, r_pow_64) = {
-                    let challenges = cb.challenges().keccak_powers_of_randomness::<16>();
-                    let r_pow_16 = challenges[15].clone();
-                    let r_pow_32 = r_pow_16.square();
-                    let r_pow_64 = r_pow_32.expr().square();
-                    (r_pow_32, r_pow_64)
-                };
+                let [r_pow_32, r_pow_64] = cb.keccak_word_powers_of_randomness();
                 cb.require_equal(
                     "input bytes (RLC) = [ p_x | p_y | s ]",
                     padding_gadget.padded_rlc(),
@@ -230,6 +212,11 @@ impl<F: Field> PrecompileGadget<F> {
                 );
             }),
             Box::new(|_cb| { /* Blake2F */ }),
+            Box::new(|cb| {
+                // No KZG sub-circuit checks the point evaluation yet, so only
+                // failed calls to 0x0a are proven, by ErrorPrecompileFailed.
+                cb.require_zero("point evaluation precompile is unsupported", 1.expr());
+            }),
         ];
         cb.constrain_mutually_exclusive_next_step(conditions, next_states, constraints);
 
@@ -21,7 +21,10 @@ pub use KeccakCircuitConfig as KeccakConfig;
 
 use self::{
     cell_manager::*,
-    keccak_packed_multi::{keccak_unusable_rows, multi_keccak, KeccakRow},
+    keccak_packed_multi::{
+        keccak_rows, keccak_unusable_rows, multi_keccak, multi_keccak_num_rows,
+        multi_keccak_streaming, KeccakRow,
+    },
     param::*,
     table::*,
     util::*,
@@ -34,7 +37,7 @@ use crate::{
         split, split_uniform, transform, transform_to, Part,
     },
     table::{KeccakTable, LookupTable},
-    util::{Challenges, SubCircuit, SubCircuitConfig},
+    util::{Challenges, SubCircuit, SubCircuitConfig, STREAMING_ASSIGNMENT},
     witness,
 };
 use eth_types::Field;
@@ -895,6 +898,40 @@ impl<F: Field> KeccakCircuitConfig<F> {
         )
     }
 
+    /// Hash `inputs` and assign the rows as they are generated, see
+    /// [`multi_keccak_streaming`].
+    pub(crate) fn assign_streaming(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        inputs: &[Vec<u8>],
+        challenges: Challenges<Value<F>>,
+        capacity: Option<usize>,
+    ) -> Result<(), Error> {
+        let num_rows = multi_keccak_num_rows(inputs, capacity)?;
+        let batch_size = rayon::current_num_threads();
+        let mut is_first_time = true;
+        layouter.assign_region(
+            || "assign keccak rows",
+            |mut region| {
+                if is_first_time {
+                    is_first_time = false;
+                    // every input ends with a row of the same shape
+                    let last_row = keccak_rows(&[], challenges)
+                        .pop()
+                        .expect("keccak rows are not empty");
+                    self.set_row(&mut region, num_rows - 1, &last_row)?;
+                    return Ok(());
+                }
+                multi_keccak_streaming(inputs, challenges, capacity, batch_size, |offset, row| {
+                    self.set_row(&mut region, offset, row).map(|_| ())
+                })?;
+                self.keccak_table.annotate_columns_in_region(&mut region);
+                self.annotate_circuit(&mut region);
+                Ok(())
+            },
+        )
+    }
+
     /// Set the cells for a keccak row; return the cells that are assigned.
     pub fn set_row(
         &self,
@@ -1048,6 +1085,14 @@ impl<F: Field> SubCircuit<F> for KeccakCircuit<F> {
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         config.load_aux_tables(layouter)?;
+        if *STREAMING_ASSIGNMENT {
+            return config.assign_streaming(
+                layouter,
+                self.inputs.as_slice(),
+                *challenges,
+                self.capacity(),
+            );
+        }
         let witness = self.generate_witness(*challenges);
         config.assign(layouter, witness.as_slice())
     }
@@ -841,6 +841,98 @@ pub(crate) fn keccak<F: Field>(
     }
 }
 
+/// Dummy first rows so that the initial data is absorbed.
+/// The initial data doesn't really matter, `is_final` just needs to be disabled.
+fn first_dummy_rows<F: Field>() -> impl Iterator<Item = KeccakRow<F>> {
+    (0..get_num_rows_per_round()).map(|idx| KeccakRow {
+        q_enable: idx == 0,
+        q_round: false,
+        q_absorb: idx == 0,
+        q_round_last: false,
+        q_padding: false,
+        q_padding_last: false,
+        round_cst: F::zero(),
+        is_final: false,
+        length: 0usize,
+        data_rlc: Value::known(F::zero()),
+        hash_rlc: Value::known(F::zero()),
+        cell_values: Vec::new(),
+    })
+}
+
+/// Number of rows used to hash an input of `len` bytes.
+pub(crate) fn keccak_num_rows(len: usize) -> usize {
+    (len / RATE + 1) * (NUM_ROUNDS + 1) * get_num_rows_per_round()
+}
+
+/// Number of rows assigned by [`multi_keccak`] for `bytes`, or an error if
+/// they do not fit in `capacity`.
+pub fn multi_keccak_num_rows(bytes: &[Vec<u8>], capacity: Option<usize>) -> Result<usize, Error> {
+    let num_rows = get_num_rows_per_round()
+        + bytes
+            .iter()
+            .map(|bytes| keccak_num_rows(bytes.len()))
+            .sum::<usize>();
+    match capacity {
+        Some(capacity) => {
+            let capacity_rows = (1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round();
+            if num_rows > capacity_rows {
+                return Err(Error::BoundsFailure);
+            }
+            Ok(capacity_rows)
+        }
+        None => Ok(num_rows),
+    }
+}
+
+/// Same rows as [`multi_keccak`], passed in order to `assign_row` together
+/// with their offset. Only the rows of `batch_size` inputs, which are hashed
+/// in parallel, are held in memory at a time. Returns the number of rows.
+pub fn multi_keccak_streaming<F: Field>(
+    bytes: &[Vec<u8>],
+    challenges: Challenges<Value<F>>,
+    capacity: Option<usize>,
+    batch_size: usize,
+    mut assign_row: impl FnMut(usize, &KeccakRow<F>) -> Result<(), Error>,
+) -> Result<usize, Error> {
+    log::info!(
+        "multi_keccak streaming assign with capacity: {:?}, batch size: {}",
+        capacity,
+        batch_size
+    );
+    let num_rows = multi_keccak_num_rows(bytes, capacity)?;
+
+    let mut offset = 0;
+    for row in first_dummy_rows() {
+        assign_row(offset, &row)?;
+        offset += 1;
+    }
+    for batch in bytes.chunks(batch_size.max(1)) {
+        let batch_rows: Vec<_> = batch
+            .par_iter()
+            .map(|bytes| keccak_rows(bytes, challenges))
+            .collect();
+        for row in batch_rows.iter().flatten() {
+            assign_row(offset, row)?;
+            offset += 1;
+        }
+    }
+    debug!("keccak rows len without padding: {}", offset);
+    if offset < num_rows {
+        // Pad with no data hashes to the expected capacity
+        let padding_rows = keccak_rows(&[], challenges);
+        while offset < num_rows {
+            for row in padding_rows.iter() {
+                assign_row(offset, row)?;
+                offset += 1;
+            }
+        }
+    }
+    debug_assert_eq!(offset, num_rows);
+    debug!("keccak streaming witgen done");
+    Ok(offset)
+}
+
 /// ...
 pub fn multi_keccak<F: Field>(
     bytes: &[Vec<u8>],
@@ -852,24 +944,7 @@ pub fn multi_keccak<F: Field>(
     if let Some(capacity) = capacity {
         rows.reserve((1 + capacity * (NUM_ROUNDS + 1)) * get_num_rows_per_round());
     }
-    // Dummy first row so that the initial data is absorbed
-    // The initial data doesn't really matter, `is_final` just needs to be disabled.
-    for idx in 0..get_num_rows_per_round() {
-        rows.push(KeccakRow {
-            q_enable: idx == 0,
-            q_round: false,
-            q_absorb: idx == 0,
-            q_round_last: false,
-            q_padding: false,
-            q_padding_last: false,
-            round_cst: F::zero(),
-            is_final: false,
-            length: 0usize,
-            data_rlc: Value::known(F::zero()),
-            hash_rlc: Value::known(F::zero()),
-            cell_values: Vec::new(),
-        });
-    }
+    rows.extend(first_dummy_rows());
 
     // TODO: optimize the `extend` using Iter?
     let real_rows: Vec<_> = bytes
@@ -1,5 +1,6 @@
+pub(crate) use zkevm_constants::keccak::{DEFAULT_KECCAK_ROWS, NUM_ROUNDS};
+
 pub(crate) const MAX_DEGREE: usize = 9;
-pub(crate) const DEFAULT_KECCAK_ROWS: usize = 12;
 pub(crate) const ABSORB_LOOKUP_RANGE: usize = 3;
 pub(crate) const THETA_C_LOOKUP_RANGE: usize = 6;
 pub(crate) const RHO_PI_LOOKUP_RANGE: usize = 4;
@@ -10,13 +11,14 @@ pub(crate) const NUM_BYTES_PER_WORD: usize = 8;
 pub(crate) const NUM_BITS_PER_WORD: usize = NUM_BYTES_PER_WORD * NUM_BITS_PER_BYTE;
 pub(crate) const KECCAK_WIDTH: usize = 5 * 5;
 pub(crate) const KECCAK_WIDTH_IN_BITS: usize = KECCAK_WIDTH * NUM_BITS_PER_WORD;
-pub(crate) const NUM_ROUNDS: usize = 24;
 pub(crate) const NUM_WORDS_TO_ABSORB: usize = 17;
 pub(crate) const NUM_WORDS_TO_SQUEEZE: usize = 4;
 pub(crate) const ABSORB_WIDTH_PER_ROW: usize = NUM_BITS_PER_WORD;
 pub(crate) const ABSORB_WIDTH_PER_ROW_BYTES: usize = ABSORB_WIDTH_PER_ROW / NUM_BITS_PER_BYTE;
 pub(crate) const RATE: usize = NUM_WORDS_TO_ABSORB * NUM_BYTES_PER_WORD;
 pub(crate) const RATE_IN_BITS: usize = RATE * NUM_BITS_PER_BYTE;
+// The aggregator lays out its hash preimages by the shared rate.
+const _: () = assert!(RATE == zkevm_constants::keccak::RATE);
 pub(crate) const THETA_C_WIDTH: usize = 5 * NUM_BITS_PER_WORD;
 pub(crate) const RHO_MATRIX: [[usize; 5]; 5] = [
     [0, 36, 3, 41, 18],
@@ -56,6 +56,78 @@ fn packed_multi_keccak_simple() {
     verify::<Fr>(k, inputs, true);
 }
 
+/// Keccak circuit assigned with [`KeccakCircuitConfig::assign_streaming`].
+struct StreamingKeccakCircuit(KeccakCircuit<Fr>);
+
+impl Circuit<Fr> for StreamingKeccakCircuit {
+    type Config = <KeccakCircuit<Fr> as Circuit<Fr>>::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self(KeccakCircuit::default())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        <KeccakCircuit<Fr> as Circuit<Fr>>::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        (config, challenges): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let challenges = challenges.values(&layouter);
+        config.load_aux_tables(&mut layouter)?;
+        config.assign_streaming(&mut layouter, &self.0.inputs, challenges, self.0.capacity())
+    }
+}
+
+#[test]
+fn streaming_assignment() {
+    let k = 19;
+    let inputs = vec![
+        vec![],
+        (0u8..1).collect::<Vec<_>>(),
+        (0u8..135).collect::<Vec<_>>(),
+        (0u8..136).collect::<Vec<_>>(),
+        (0u8..200).collect::<Vec<_>>(),
+    ];
+
+    // the streamed rows are the rows of the whole witness, in order
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(1)),
+        Value::known(Fr::from(2)),
+        Value::known(Fr::from(3)),
+    );
+    let capacity = KeccakCircuit::<Fr>::new(2usize.pow(k), vec![]).capacity();
+    for capacity in [None, capacity] {
+        let rows = multi_keccak(&inputs, challenges, capacity).unwrap();
+        for batch_size in [1, 2, inputs.len()] {
+            let mut streamed_rows = Vec::new();
+            let num_rows =
+                multi_keccak_streaming(&inputs, challenges, capacity, batch_size, |offset, row| {
+                    assert_eq!(offset, streamed_rows.len());
+                    streamed_rows.push(format!("{row:?}"));
+                    Ok(())
+                })
+                .unwrap();
+            assert_eq!(num_rows, rows.len());
+            assert_eq!(
+                multi_keccak_num_rows(&inputs, capacity).unwrap(),
+                rows.len()
+            );
+            assert!(streamed_rows
+                .iter()
+                .zip(rows.iter())
+                .all(|(streamed, row)| *streamed == format!("{row:?}")));
+        }
+    }
+
+    let circuit = StreamingKeccakCircuit(KeccakCircuit::new(2usize.pow(k), inputs));
+    let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+    prover.assert_satisfied_par();
+}
+
 #[test]
 fn variadic_size_check() {
     let k = 19;
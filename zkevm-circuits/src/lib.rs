@@ -35,6 +35,7 @@ pub mod poseidon_circuit;
 pub mod rlp_circuit_fsm;
 pub mod sig_circuit;
 // we don't use this for aggregation
+// Requires the "prover" feature when re-enabled.
 //pub mod root_circuit;
 pub mod state_circuit;
 pub mod super_circuit;
@@ -7,11 +7,14 @@ mod param;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod test;
 
-use std::{iter, marker::PhantomData, str::FromStr};
+use std::{iter, marker::PhantomData};
 
 use crate::{evm_circuit::util::constraint_builder::ConstrainBuilderCommon, table::KeccakTable};
 use bus_mapping::circuit_input_builder::get_dummy_tx_hash;
-use eth_types::{Address, Field, Hash, ToBigEndian, Word, H256};
+use eth_types::{
+    evm_types::block_utils::MIN_BASE_FEE_PER_BLOB_GAS, geth_types::TxType, Address, Field, Hash,
+    ToBigEndian, Word, H256,
+};
 use ethers_core::utils::keccak256;
 use halo2_proofs::plonk::{Assigned, Expression, Fixed, Instance};
 
@@ -28,15 +31,21 @@ use crate::{
     evm_circuit::{util::constraint_builder::BaseConstraintBuilder, EvmCircuitExports},
     pi_circuit::param::{
         BASE_FEE_OFFSET, BLOCK_HEADER_BYTES_NUM, BLOCK_LEN, BLOCK_NUM_OFFSET, BYTE_POW_BASE,
-        CHAIN_ID_OFFSET, GAS_LIMIT_OFFSET, KECCAK_DIGEST_SIZE, NUM_TXS_OFFSET, RPI_CELL_IDX,
-        RPI_LENGTH_ACC_CELL_IDX, RPI_RLC_ACC_CELL_IDX, TIMESTAMP_OFFSET,
+        CHAIN_ID_OFFSET, GAS_LIMIT_OFFSET, KECCAK_DIGEST_SIZE, NUM_TXS_OFFSET,
+        PI_HASH_PREIMAGE_LEN, RPI_CELL_IDX, RPI_LENGTH_ACC_CELL_IDX, RPI_RLC_ACC_CELL_IDX,
+        TIMESTAMP_OFFSET,
     },
     state_circuit::StateCircuitExports,
-    tx_circuit::{CHAIN_ID_OFFSET as CHAIN_ID_OFFSET_IN_TX, TX_HASH_OFFSET, TX_LEN},
+    tx_circuit::{
+        CHAIN_ID_OFFSET as CHAIN_ID_OFFSET_IN_TX, TX_HASH_OFFSET, TX_LEN, TX_TYPE_OFFSET,
+    },
     witness::{self, Block, BlockContext, BlockContexts, Transaction},
 };
 use bus_mapping::util::read_env_var;
-use gadgets::util::{and, not, select, Expr};
+use gadgets::{
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstruction},
+    util::{and, not, select, Expr},
+};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{Advice, Column, ConstraintSystem, Error, Selector},
@@ -47,10 +56,11 @@ use once_cell::sync::Lazy;
 use crate::{
     evm_circuit::param::{N_BYTES_ACCOUNT_ADDRESS, N_BYTES_U64, N_BYTES_WORD},
     pi_circuit::param::{COINBASE_OFFSET, DIFFICULTY_OFFSET},
-    table::BlockContextFieldTag::{
-        BaseFee, ChainId, Coinbase, CumNumTxs, Difficulty, GasLimit, NumTxs, Number, Timestamp,
+    table::BlockContextFieldTag::{self, BlobBaseFee, CumNumTxs, NumTxs, Number},
+    util::{
+        digest::{digest_halves, digest_hi_lo, DigestByteOrder},
+        rlc_be_bytes,
     },
-    util::rlc_be_bytes,
 };
 use halo2_proofs::circuit::{Cell, RegionIndex};
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
@@ -60,7 +70,7 @@ use itertools::Itertools;
 pub(crate) static COINBASE: Lazy<Address> = Lazy::new(|| {
     read_env_var(
         "COINBASE",
-        Address::from_str("0x5300000000000000000000000000000000000005").unwrap(),
+        Address::from(zkevm_constants::predeployed::DEFAULT_COINBASE),
     )
 });
 pub(crate) static DIFFICULTY: Lazy<Word> = Lazy::new(|| read_env_var("DIFFICULTY", Word::zero()));
@@ -78,6 +88,8 @@ pub struct PublicData {
     pub prev_state_root: Hash,
     /// Withdraw Trie Root
     pub withdraw_trie_root: Hash,
+    /// Withdraw Trie Root before this chunk
+    pub prev_withdraw_trie_root: Hash,
 }
 
 impl Default for PublicData {
@@ -87,19 +99,27 @@ impl Default for PublicData {
             transactions: vec![],
             prev_state_root: H256::zero(),
             withdraw_trie_root: H256::zero(),
+            prev_withdraw_trie_root: H256::zero(),
             block_ctxs: Default::default(),
         }
     }
 }
 
 impl PublicData {
+    /// The txs in the data bytes: all of them but the pseudo-txs of the
+    /// system calls.
+    fn data_txs(&self) -> impl Iterator<Item = &Transaction> {
+        self.transactions
+            .iter()
+            .filter(|tx| !tx.tx_type.is_system_call())
+    }
+
     /// Compute the bytes for dataHash from the verifier's perspective.
     fn data_bytes(&self) -> Vec<u8> {
         let result = iter::empty()
             .chain(self.block_ctxs.ctxs.iter().flat_map(|(block_num, block)| {
                 let num_txs = self
-                    .transactions
-                    .iter()
+                    .data_txs()
                     .filter(|tx| tx.block_number == *block_num)
                     .count() as u16;
 
@@ -112,17 +132,13 @@ impl PublicData {
                     .chain(num_txs.to_be_bytes())
             }))
             // Tx Hashes
-            .chain(
-                self.transactions
-                    .iter()
-                    .flat_map(|tx| tx.hash.to_fixed_bytes()),
-            )
+            .chain(self.data_txs().flat_map(|tx| tx.hash.to_fixed_bytes()))
             .collect::<Vec<u8>>();
 
         assert_eq!(
             result.len(),
             BLOCK_HEADER_BYTES_NUM * self.block_ctxs.ctxs.len()
-                + KECCAK_DIGEST_SIZE * self.transactions.len()
+                + KECCAK_DIGEST_SIZE * self.data_txs().count()
         );
         result
     }
@@ -140,7 +156,7 @@ impl PublicData {
             .map(|(_, blk)| blk.eth_block.state_root)
             .unwrap_or(self.prev_state_root);
 
-        iter::empty()
+        let result = iter::empty()
             .chain(self.chain_id.to_be_bytes())
             // state roots
             .chain(self.prev_state_root.to_fixed_bytes())
@@ -148,7 +164,12 @@ impl PublicData {
             .chain(withdraw_trie_root.to_fixed_bytes())
             // data hash
             .chain(data_hash.to_fixed_bytes())
-            .collect::<Vec<u8>>()
+            // withdraw root before this chunk
+            .chain(self.prev_withdraw_trie_root.to_fixed_bytes())
+            .collect::<Vec<u8>>();
+
+        assert_eq!(result.len(), PI_HASH_PREIMAGE_LEN);
+        result
     }
 
     fn get_pi(&self) -> H256 {
@@ -170,6 +191,7 @@ impl BlockContext {
             number: Default::default(),
             timestamp: Default::default(),
             base_fee: Default::default(),
+            blob_base_fee: MIN_BASE_FEE_PER_BLOB_GAS.into(),
             history_hashes: vec![],
             eth_block: Default::default(),
         }
@@ -212,6 +234,20 @@ pub struct PiCircuitConfig<F: Field> {
     is_block_num_txs: Column<Fixed>,
     q_block_tag: Column<Fixed>,
 
+    // columns for excluding the pseudo-txs of system calls from data bytes
+    tx_type: Column<Advice>,
+    is_system_call: IsZeroConfig<F>,
+    // number of system calls among the txs before the current tx hash
+    num_system_calls_acc: Column<Advice>,
+    q_tx_slot: Column<Fixed>,
+    tx_slot_index: Column<Fixed>,
+    // the num_txs of a block in data bytes, from the one in block table
+    q_num_txs: Selector,
+    num_txs: Column<Advice>,
+    first_tx_slot: Column<Advice>,
+    num_system_calls_before: Column<Advice>,
+    num_system_calls_after: Column<Advice>,
+
     q_field_step: Selector,
     is_field_rlc: Column<Fixed>,
 
@@ -317,6 +353,27 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         let cum_num_txs = meta.advice_column();
         let is_block_num_txs = meta.fixed_column();
 
+        let tx_type = meta.advice_column();
+        let num_system_calls_acc = meta.advice_column();
+        let q_tx_slot = meta.fixed_column();
+        let tx_slot_index = meta.fixed_column();
+        let q_num_txs = meta.complex_selector();
+        let num_txs = meta.advice_column();
+        let first_tx_slot = meta.advice_column();
+        let num_system_calls_before = meta.advice_column();
+        let num_system_calls_after = meta.advice_column();
+
+        // whether the tx of a tx hash is the pseudo-tx of a system call
+        let tx_type_inv = meta.advice_column();
+        let is_system_call = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_fixed(q_tx_hashes, Rotation::cur()),
+            |meta| {
+                meta.query_advice(tx_type, Rotation::cur()) - usize::from(TxType::SystemCall).expr()
+            },
+            tx_type_inv,
+        );
+
         meta.enable_constant(constant);
         meta.enable_equality(rpi_bytes);
         meta.enable_equality(rpi_bytes_acc);
@@ -328,6 +385,10 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         meta.enable_equality(block_table.index);
         meta.enable_equality(tx_table.value); // copy tx hashes to rpi
         meta.enable_equality(cum_num_txs);
+        meta.enable_equality(tx_type); // copy tx types to rpi
+        meta.enable_equality(num_system_calls_acc);
+        meta.enable_equality(num_txs);
+        meta.enable_equality(first_tx_slot);
         meta.enable_equality(pi);
 
         // 1. constrain rpi_bytes, rpi_bytes_acc, and rpi for each field
@@ -354,8 +415,16 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
                 //   q_not_end * (row_next.rpi_rlc_acc - row.rpi_rlc_acc * keccak_rand -
                 // row_next.rpi_bytes) == 0 else,
                 //   q_not_end * (row_next.rpi_rlc_acc - row.rpi_rlc_acc) == 0
+                // The tx hashes of the system calls are skipped as the padding ones.
                 let mut cb = BaseConstraintBuilder::default();
-                let is_rpi_padding = meta.query_advice(is_rpi_padding, Rotation::next());
+                let tx_type_next = meta.query_advice(tx_type, Rotation::next());
+                let is_system_call_next = is_system_call.expr_at(
+                    meta,
+                    Rotation::next(),
+                    tx_type_next - usize::from(TxType::SystemCall).expr(),
+                );
+                let is_skipped = meta.query_advice(is_rpi_padding, Rotation::next())
+                    + meta.query_fixed(q_tx_hashes, Rotation::next()) * is_system_call_next;
                 let rpi_rlc_acc_cur = meta.query_advice(rpi_rlc_acc, Rotation::cur());
                 let rpi_bytes_next = meta.query_advice(rpi_bytes, Rotation::next());
                 let keccak_rand = challenges.keccak_input();
@@ -364,20 +433,20 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
                 let r = select::expr(is_rlc_keccak, keccak_rand, evm_rand);
 
                 cb.require_equal(
-                    "rpi_rlc_acc' = is_rpi_padding ? rpi_rlc_acc : rpi_rlc_acc * r + rpi_bytes'",
+                    "rpi_rlc_acc' = is_skipped ? rpi_rlc_acc : rpi_rlc_acc * r + rpi_bytes'",
                     meta.query_advice(rpi_rlc_acc, Rotation::next()),
                     select::expr(
-                        is_rpi_padding.expr(),
+                        is_skipped.expr(),
                         rpi_rlc_acc_cur.expr(),
                         rpi_rlc_acc_cur * r + rpi_bytes_next,
                     ),
                 );
 
                 cb.require_equal(
-                    "rpi_length_acc' = rpi_length_acc + (is_rpi_padding ? 0 : 1)",
+                    "rpi_length_acc' = rpi_length_acc + (is_skipped ? 0 : 1)",
                     meta.query_advice(rpi_length_acc, Rotation::next()),
                     meta.query_advice(rpi_length_acc, Rotation::cur())
-                        + select::expr(is_rpi_padding, 0.expr(), 1.expr()),
+                        + select::expr(is_skipped, 0.expr(), 1.expr()),
                 );
 
                 cb.gate(meta.query_selector(q_not_end))
@@ -462,9 +531,79 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
                 );
             });
 
+            // the padding txs are not system calls
+            cb.require_zero(
+                "is_rpi_padding * is_system_call == 0",
+                meta.query_advice(is_rpi_padding, Rotation::cur()) * is_system_call.expr(),
+            );
+
             cb.gate(meta.query_fixed(q_tx_hashes, Rotation::cur()))
         });
 
+        // num_system_calls_acc counts the system calls among the txs before the
+        // current tx hash, and is looked up at the first row of each tx hash:
+        // |  rpi  | tx_type | q_tx_slot | tx_slot_index | num_system_calls_acc |
+        // | hash0 |   ty0   |     1     |       0       |          0           |
+        // | hash0 |   ty0   |     0     |       0       |          0           |
+        // |  ...  |   ...   |    ...    |      ...      |         ...          |
+        // | hash1 |   ty1   |     1     |       1       |  (ty0 == SystemCall) |
+        // |  ...  |   ...   |    ...    |      ...      |         ...          |
+        // | dbs   |    0    |     1     |    max_txs    |  num of system calls |
+        meta.create_gate("num_system_calls_acc", |meta| {
+            // the last row of a tx hash is the only one without q_field_step
+            let is_last_row = not::expr(meta.query_selector(q_field_step));
+
+            vec![
+                meta.query_fixed(q_tx_hashes, Rotation::cur())
+                    * (meta.query_advice(num_system_calls_acc, Rotation::next())
+                        - meta.query_advice(num_system_calls_acc, Rotation::cur())
+                        - is_last_row * is_system_call.expr()),
+            ]
+        });
+
+        // The block table counts all the txs of a block, while the num_txs in
+        // the data bytes doesn't count its system calls: the txs of the block
+        // are those from the tx slot cum_num_txs (of the previous blocks), and
+        // the system calls among them are the difference between the
+        // num_system_calls_acc before them and after them.
+        for (name, is_after, num_system_calls) in [
+            ("num_system_calls_before", false, num_system_calls_before),
+            ("num_system_calls_after", true, num_system_calls_after),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let q_num_txs = meta.query_selector(q_num_txs);
+                let q_tx_slot = meta.query_fixed(q_tx_slot, Rotation::cur());
+                let tx_slot = meta.query_advice(first_tx_slot, Rotation::cur())
+                    + if is_after {
+                        meta.query_advice(num_txs, Rotation::cur())
+                    } else {
+                        0.expr()
+                    };
+
+                vec![
+                    (q_num_txs.expr(), q_tx_slot.expr()),
+                    (
+                        q_num_txs.expr() * tx_slot,
+                        q_tx_slot.expr() * meta.query_fixed(tx_slot_index, Rotation::cur()),
+                    ),
+                    (
+                        q_num_txs * meta.query_advice(num_system_calls, Rotation::cur()),
+                        q_tx_slot * meta.query_advice(num_system_calls_acc, Rotation::cur()),
+                    ),
+                ]
+            });
+        }
+        meta.create_gate("num_txs in data bytes", |meta| {
+            let num_system_calls = meta.query_advice(num_system_calls_after, Rotation::cur())
+                - meta.query_advice(num_system_calls_before, Rotation::cur());
+
+            vec![
+                meta.query_selector(q_num_txs)
+                    * (meta.query_advice(real_rpi, Rotation::cur())
+                        - (meta.query_advice(num_txs, Rotation::cur()) - num_system_calls)),
+            ]
+        });
+
         // We reuse the layout for rpi to compute the keccak output.
         // The 32 bytes of keccak output are combined into (hi, lo)
         //  where r = challenges.evm_word().
@@ -495,8 +634,9 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
         // prev_state_root  |   ..    |     ..    |      ...      |     ...     |      ...       |
         // after_state_root |   ..    |     ..    |      ...      |     ...     |      ...       |
         // withdraw_root    |   ..    |     ..    |      ...      |     ...     |      ...       |
-        // data hash        |  dh_rlc |     ..    |      ...      |  pi_bs_rlc  |      136       |
-        // q_keccak = 1     |pi_bs_rlc|     ..    |      ...      | pi_hash_rlc |      136       |
+        // data hash        |  dh_rlc |     ..    |      ...      |     ...     |      ...       |
+        // prev_wd_root     |   ..    |     ..    |      ...      |  pi_bs_rlc  |      168       |
+        // q_keccak = 1     |pi_bs_rlc|     ..    |      ...      | pi_hash_rlc |      168       |
         //   pi hash        |   hi    |     ..    |      ...      |     ...     |       16       |
         //                  |   lo    |     ..    |      ...      | pi_hash_rlc |       32       |
         meta.lookup_any("keccak(rpi)", |meta| {
@@ -569,6 +709,16 @@ impl<F: Field> SubCircuitConfig<F> for PiCircuitConfig<F> {
             cum_num_txs,
             q_block_tag,
             is_block_num_txs,
+            tx_type,
+            is_system_call,
+            num_system_calls_acc,
+            q_tx_slot,
+            tx_slot_index,
+            q_num_txs,
+            num_txs,
+            first_tx_slot,
+            num_system_calls_before,
+            num_system_calls_after,
             pi,
             _marker: PhantomData,
             q_block_context,
@@ -584,6 +734,7 @@ struct Connections<F: Field> {
     start_state_root: AssignedCell<F, F>,
     end_state_root: AssignedCell<F, F>,
     withdraw_root: AssignedCell<F, F>,
+    prev_withdraw_root: AssignedCell<F, F>,
 }
 
 impl<F: Field> PiCircuitConfig<F> {
@@ -593,6 +744,7 @@ impl<F: Field> PiCircuitConfig<F> {
         region: &mut Region<'_, F>,
         public_data: &PublicData,
         block_value_cells: &[AssignedCell<F, F>],
+        cum_num_txs_cells: &[AssignedCell<F, F>],
         challenges: &Challenges<Value<F>>,
     ) -> Result<(PiHashExport<F>, Connections<F>), Error> {
         let block_values = &public_data.block_ctxs;
@@ -600,11 +752,18 @@ impl<F: Field> PiCircuitConfig<F> {
             .ctxs
             .first_key_value()
             .map_or(0, |(_, context)| context.chain_id);
-        let tx_hashes = public_data
+        let txs = public_data
             .transactions
             .iter()
-            .map(|tx| tx.hash)
-            .collect::<Vec<H256>>();
+            .map(|tx| (tx.hash, tx.tx_type))
+            .collect::<Vec<(H256, TxType)>>();
+        // the number of system calls among the first i txs
+        let system_calls_acc = iter::once(0)
+            .chain(txs.iter().scan(0, |acc, (_, tx_type)| {
+                *acc += tx_type.is_system_call() as u64;
+                Some(*acc)
+            }))
+            .collect::<Vec<u64>>();
 
         let mut offset = 0;
         let mut block_copy_cells = vec![];
@@ -624,6 +783,7 @@ impl<F: Field> PiCircuitConfig<F> {
             self.max_inner_blocks * BLOCK_HEADER_BYTES_NUM + self.max_txs * KECCAK_DIGEST_SIZE;
         self.assign_rlc_start(region, &mut offset, &mut rpi_rlc_acc, &mut rpi_length_acc)?;
         // assign block contexts
+        let mut first_tx_slot = 0;
         for (i, block) in block_values
             .ctxs
             .values()
@@ -636,10 +796,14 @@ impl<F: Field> PiCircuitConfig<F> {
             .enumerate()
         {
             let is_rpi_padding = i >= block_values.ctxs.len();
-            let num_txs = public_data
+            let num_all_txs = public_data
                 .transactions
                 .iter()
                 .filter(|tx| tx.block_number == block.number.as_u64())
+                .count();
+            let num_txs = public_data
+                .data_txs()
+                .filter(|tx| tx.block_number == block.number.as_u64())
                 .count() as u16;
 
             // Assign fields in pi columns and connect them to block table
@@ -668,12 +832,41 @@ impl<F: Field> PiCircuitConfig<F> {
                     false,
                     challenges,
                 )?;
-                block_copy_cells.push((
-                    cells[RPI_CELL_IDX].clone(),
-                    block_table_offset + block_offset,
-                ));
+                // the block table counts the system calls, which are not in
+                // the num_txs of the data bytes
+                let block_cell = if block_offset == NUM_TXS_OFFSET {
+                    let row = offset - 1;
+                    self.q_num_txs.enable(region, row)?;
+                    cum_num_txs_cells[i].copy_advice(
+                        || "first_tx_slot",
+                        region,
+                        self.first_tx_slot,
+                        row,
+                    )?;
+                    for (column, tx_slot) in [
+                        (self.num_system_calls_before, first_tx_slot),
+                        (self.num_system_calls_after, first_tx_slot + num_all_txs),
+                    ] {
+                        region.assign_advice(
+                            || "num_system_calls",
+                            column,
+                            row,
+                            || Value::known(F::from(system_calls_acc[tx_slot])),
+                        )?;
+                    }
+                    region.assign_advice(
+                        || "num_txs",
+                        self.num_txs,
+                        row,
+                        || Value::known(F::from(num_all_txs as u64)),
+                    )?
+                } else {
+                    cells[RPI_CELL_IDX].clone()
+                };
+                block_copy_cells.push((block_cell, block_table_offset + block_offset));
             }
 
+            first_tx_slot += num_all_txs;
             block_table_offset += BLOCK_LEN;
         }
 
@@ -694,21 +887,25 @@ impl<F: Field> PiCircuitConfig<F> {
         // assign tx hashes
         let q_tx_hashes_start_row = offset;
         let q_tx_hashes_end_row = q_tx_hashes_start_row + KECCAK_DIGEST_SIZE * self.max_txs;
-        let num_txs = tx_hashes.len();
+        let num_txs = txs.len();
+        let padding_tx_type = Transaction::dummy(chain_id).tx_type;
+        let is_system_call_chip = IsZeroChip::construct(self.is_system_call.clone());
         let mut data_bytes_rlc = None;
         let mut data_bytes_length = None;
-        for (i, tx_hash) in tx_hashes
+        let mut tx_type_cells = vec![];
+        for (i, (tx_hash, tx_type)) in txs
             .into_iter()
             .chain(
                 (0..self.max_txs - num_txs)
                     .into_iter()
-                    .map(|_| dummy_tx_hash),
+                    .map(|_| (dummy_tx_hash, padding_tx_type)),
             )
             .enumerate()
         {
             let is_rpi_padding = i >= num_txs;
+            let tx_slot_start_row = offset;
 
-            let cells = self.assign_field_in_pi(
+            let cells = self.assign_field_in_pi_ext(
                 region,
                 &mut offset,
                 &tx_hash.to_fixed_bytes(),
@@ -717,10 +914,57 @@ impl<F: Field> PiCircuitConfig<F> {
                 false,
                 is_rpi_padding,
                 false,
+                false,
+                tx_type.is_system_call(),
                 challenges,
             )?;
             tx_copy_cells.push(cells[RPI_CELL_IDX].clone());
 
+            region.assign_fixed(
+                || "q_tx_slot",
+                self.q_tx_slot,
+                tx_slot_start_row,
+                || Value::known(F::one()),
+            )?;
+            region.assign_fixed(
+                || "tx_slot_index",
+                self.tx_slot_index,
+                tx_slot_start_row,
+                || Value::known(F::from(i as u64)),
+            )?;
+            let tx_type = F::from(tx_type as u64);
+            for row in tx_slot_start_row..offset {
+                tx_type_cells.push((
+                    i,
+                    region.assign_advice(
+                        || "tx_type",
+                        self.tx_type,
+                        row,
+                        || Value::known(tx_type),
+                    )?,
+                ));
+                is_system_call_chip.assign(
+                    region,
+                    row,
+                    Value::known(tx_type - F::from(TxType::SystemCall as u64)),
+                )?;
+                if row == q_tx_hashes_start_row {
+                    region.assign_advice_from_constant(
+                        || "num_system_calls_acc",
+                        self.num_system_calls_acc,
+                        row,
+                        F::zero(),
+                    )?;
+                } else {
+                    region.assign_advice(
+                        || "num_system_calls_acc",
+                        self.num_system_calls_acc,
+                        row,
+                        || Value::known(F::from(system_calls_acc[i.min(num_txs)])),
+                    )?;
+                }
+            }
+
             if i == self.max_txs - 1 {
                 data_bytes_rlc = Some(cells[RPI_RLC_ACC_CELL_IDX].clone());
                 data_bytes_length = Some(cells[RPI_LENGTH_ACC_CELL_IDX].clone());
@@ -761,6 +1005,17 @@ impl<F: Field> PiCircuitConfig<F> {
                 },
             )?;
         }
+        // copy tx_types from tx table
+        for (i, tx_type_cell) in tx_type_cells.into_iter() {
+            region.constrain_equal(
+                tx_type_cell.cell(),
+                Cell {
+                    region_index: RegionIndex(1), // FIXME: this is not safe
+                    row_offset: i * TX_LEN + TX_TYPE_OFFSET,
+                    column: self.tx_table.value.into(),
+                },
+            )?;
+        }
 
         // assign keccak row for computing data_hash = keccak256(data bytes)
         let data_hash_row = offset;
@@ -785,13 +1040,32 @@ impl<F: Field> PiCircuitConfig<F> {
             || data_hash_rlc,
         )?;
         self.q_keccak.enable(region, data_hash_row)?;
+        // the number of system calls among all the txs
+        region.assign_advice(
+            || "num_system_calls_acc",
+            self.num_system_calls_acc,
+            data_hash_row,
+            || Value::known(F::from(system_calls_acc[num_txs])),
+        )?;
+        region.assign_fixed(
+            || "q_tx_slot",
+            self.q_tx_slot,
+            data_hash_row,
+            || Value::known(F::one()),
+        )?;
+        region.assign_fixed(
+            || "tx_slot_index",
+            self.tx_slot_index,
+            data_hash_row,
+            || Value::known(F::from(self.max_txs as u64)),
+        )?;
         offset += 1;
 
         /////////////////////////////////
         ///////// assign pi bytes ///////
         /////////////////////////////////
         let pi_bytes_start_row = offset;
-        let pi_bytes_end_row = pi_bytes_start_row + N_BYTES_U64 + N_BYTES_WORD * 4;
+        let pi_bytes_end_row = pi_bytes_start_row + N_BYTES_U64 + N_BYTES_WORD * 5;
         self.assign_rlc_start(region, &mut offset, &mut rpi_rlc_acc, &mut rpi_length_acc)?;
         // assign chain_id
         let cells = self.assign_field_in_pi(
@@ -858,12 +1132,6 @@ impl<F: Field> PiCircuitConfig<F> {
                 Ok(cells[RPI_CELL_IDX].clone())
             })
             .collect::<Result<Vec<_>, Error>>()?;
-        let connections = Connections {
-            start_state_root: root_cells[0].clone(),
-            end_state_root: root_cells[1].clone(),
-            withdraw_root: root_cells[2].clone(),
-        };
-
         // assign data_hash
         let cells = self.assign_field_in_pi(
             region,
@@ -877,12 +1145,31 @@ impl<F: Field> PiCircuitConfig<F> {
             challenges,
         )?;
         let data_hash_cell = cells[RPI_CELL_IDX].clone();
-        let pi_bytes_rlc = cells[RPI_RLC_ACC_CELL_IDX].clone();
-        let pi_bytes_length = cells[RPI_LENGTH_ACC_CELL_IDX].clone();
 
         // copy data_hash down here
         region.constrain_equal(data_hash_rlc_cell.cell(), data_hash_cell.cell())?;
 
+        // assign prev_withdraw_trie_root
+        let cells = self.assign_field_in_pi(
+            region,
+            &mut offset,
+            &public_data.prev_withdraw_trie_root.to_fixed_bytes(),
+            &mut rpi_rlc_acc,
+            &mut rpi_length_acc,
+            false,
+            false,
+            false,
+            challenges,
+        )?;
+        let pi_bytes_rlc = cells[RPI_RLC_ACC_CELL_IDX].clone();
+        let pi_bytes_length = cells[RPI_LENGTH_ACC_CELL_IDX].clone();
+        let connections = Connections {
+            start_state_root: root_cells[0].clone(),
+            end_state_root: root_cells[1].clone(),
+            withdraw_root: root_cells[2].clone(),
+            prev_withdraw_root: cells[RPI_CELL_IDX].clone(),
+        };
+
         for i in pi_bytes_start_row..pi_bytes_end_row {
             self.q_not_end.enable(region, i)?;
         }
@@ -926,11 +1213,16 @@ impl<F: Field> PiCircuitConfig<F> {
             self.q_not_end.enable(region, i)?;
         }
 
+        let pi_hash_bytes = pi_hash.to_fixed_bytes();
+        let (pi_hash_hi_bytes, pi_hash_lo_bytes) = digest_halves(&pi_hash_bytes);
+        let (pi_hash_hi, pi_hash_lo) =
+            digest_hi_lo::<F>(&pi_hash_bytes, DigestByteOrder::BigEndian);
+
         // the high 16 bytes of keccak output
         let cells = self.assign_field_in_pi(
             region,
             &mut offset,
-            &pi_hash.to_fixed_bytes()[..16],
+            pi_hash_hi_bytes,
             &mut rpi_rlc_acc,
             &mut rpi_length_acc,
             false,
@@ -939,13 +1231,15 @@ impl<F: Field> PiCircuitConfig<F> {
             challenges,
         )?;
         let pi_hash_hi_byte_cells = cells[3..].to_vec();
-        // let pi_hash_hi_cell = cells[RPI_CELL_IDX].clone();
+        cells[RPI_CELL_IDX]
+            .value()
+            .assert_if_known(|hi| **hi == pi_hash_hi);
 
         // the low 16 bytes of keccak output
         let cells = self.assign_field_in_pi(
             region,
             &mut offset,
-            &pi_hash.to_fixed_bytes()[16..],
+            pi_hash_lo_bytes,
             &mut rpi_rlc_acc,
             &mut rpi_length_acc,
             false,
@@ -954,7 +1248,9 @@ impl<F: Field> PiCircuitConfig<F> {
             challenges,
         )?;
         let pi_hash_lo_byte_cells = cells[3..].to_vec();
-        // let pi_hash_lo_cell = cells[RPI_CELL_IDX].clone();
+        cells[RPI_CELL_IDX]
+            .value()
+            .assert_if_known(|lo| **lo == pi_hash_lo);
 
         // +1 for the rlc_start row
         assert_eq!(offset, pi_hash_bytes_end_row + 1);
@@ -981,6 +1277,7 @@ impl<F: Field> PiCircuitConfig<F> {
             false,
             true,
             true,
+            false,
             challenges,
         )?;
         let coinbase_cell = cells[RPI_CELL_IDX].clone();
@@ -995,6 +1292,7 @@ impl<F: Field> PiCircuitConfig<F> {
             false,
             true,
             true,
+            false,
             challenges,
         )?;
         let difficulty_cell = cells[RPI_CELL_IDX].clone();
@@ -1023,7 +1321,7 @@ impl<F: Field> PiCircuitConfig<F> {
                 + 1 // for data hash row
                 + 1 // for pi bytes start row
                 + N_BYTES_U64
-                + 4 * KECCAK_DIGEST_SIZE
+                + 5 * KECCAK_DIGEST_SIZE
                 + 1 // for pi hash row
                 + 1 // for pi hash bytes start row
                 + KECCAK_DIGEST_SIZE
@@ -1087,6 +1385,7 @@ impl<F: Field> PiCircuitConfig<F> {
             is_rpi_padding,
             keccak_hi_lo,
             false,
+            false,
             challenges,
         )
     }
@@ -1103,9 +1402,12 @@ impl<F: Field> PiCircuitConfig<F> {
         is_rpi_padding: bool,   // if this field is not included in the data bytes
         keccak_hi_lo: bool,     // if this field is related to keccak decomposition
         is_constant: bool,
+        is_system_call: bool, // if this field is the tx hash of a system call
         challenges: &Challenges<Value<F>>,
     ) -> Result<Vec<AssignedCell<F, F>>, Error> {
         let len = value_be_bytes.len();
+        // the tx hash of a system call is not included in the data bytes either
+        let is_skipped = is_rpi_padding || is_system_call;
 
         let (is_field_rlc, t) = if len * 8 > F::CAPACITY as usize {
             (F::one(), challenges.evm_word())
@@ -1144,8 +1446,8 @@ impl<F: Field> PiCircuitConfig<F> {
                 .zip(t)
                 .and_then(|(acc, t)| Value::known(acc * t + F::from(*byte as u64)));
 
-            // this field is not padding then we absorb the byte into rpi_rlc_acc
-            if !is_rpi_padding {
+            // this field is not skipped then we absorb the byte into rpi_rlc_acc
+            if !is_skipped {
                 *rpi_rlc_acc = rpi_rlc_acc
                     .zip(r)
                     .and_then(|(acc, rand)| Value::known(acc * rand + F::from(*byte as u64)));
@@ -1255,13 +1557,17 @@ impl<F: Field> PiCircuitConfig<F> {
         Ok(cells.into_iter().map(|cell| cell.unwrap()).collect())
     }
 
+    /// Assign the block table, and return its value cells along with the
+    /// cum_num_txs cells of the NumTxs rows, the number of txs before each
+    /// block.
+    #[allow(clippy::type_complexity)]
     fn assign_block_table(
         &self,
         region: &mut Region<'_, F>,
         public_data: &PublicData,
         max_inner_blocks: usize,
         challenges: &Challenges<Value<F>>,
-    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+    ) -> Result<(Vec<AssignedCell<F, F>>, Vec<AssignedCell<F, F>>), Error> {
         let mut offset = 0;
 
         let block_table_columns = <BlockTable as LookupTable<F>>::advice_columns(&self.block_table);
@@ -1289,6 +1595,7 @@ impl<F: Field> PiCircuitConfig<F> {
 
         let mut cum_num_txs = 0usize;
         let mut block_value_cells = vec![];
+        let mut cum_num_txs_cells = vec![];
         let block_ctxs = &public_data.block_ctxs;
         for block_ctx in block_ctxs.ctxs.values().cloned().chain(
             (block_ctxs.ctxs.len()..max_inner_blocks)
@@ -1300,10 +1607,7 @@ impl<F: Field> PiCircuitConfig<F> {
                 .iter()
                 .filter(|tx| tx.block_number == block_ctx.number.as_u64())
                 .count();
-            let tag = [
-                Coinbase, Timestamp, Number, Difficulty, GasLimit, BaseFee, ChainId, NumTxs,
-                CumNumTxs,
-            ];
+            let tag = BlockContextFieldTag::BLOCK_TABLE_FIELDS;
             let mut cum_num_txs_field = F::from(cum_num_txs as u64);
             cum_num_txs += num_txs;
             for (row, tag) in block_ctx
@@ -1333,6 +1637,13 @@ impl<F: Field> PiCircuitConfig<F> {
                     if *column == self.block_table.index {
                         index_cells.push(cell.clone());
                     }
+                    if *tag == BlobBaseFee && *column == self.block_table.value {
+                        // The blob base fee isn't in the public input hash. The L2
+                        // blocks carry no blobs, so it's fixed to the minimum, whose
+                        // RLC is itself as it fits in a byte.
+                        region
+                            .constrain_constant(cell.cell(), F::from(MIN_BASE_FEE_PER_BLOB_GAS))?;
+                    }
                     if *column == self.block_table.value {
                         block_value_cells.push(cell);
                     }
@@ -1365,27 +1676,30 @@ impl<F: Field> PiCircuitConfig<F> {
                 if *tag == CumNumTxs {
                     cum_num_txs_field = F::from(cum_num_txs as u64);
                 }
-                if offset == 1 {
+                let cum_num_txs_cell = if offset == 1 {
                     assert_eq!(cum_num_txs_field, F::zero());
                     region.assign_advice_from_constant(
                         || "cum_num_txs",
                         self.cum_num_txs,
                         offset,
                         cum_num_txs_field,
-                    )?;
+                    )?
                 } else {
                     region.assign_advice(
                         || "cum_num_txs",
                         self.cum_num_txs,
                         offset,
                         || Value::known(cum_num_txs_field),
-                    )?;
+                    )?
+                };
+                if *tag == NumTxs {
+                    cum_num_txs_cells.push(cum_num_txs_cell);
                 }
                 offset += 1;
             }
         }
 
-        Ok(block_value_cells)
+        Ok((block_value_cells, cum_num_txs_cells))
     }
 }
 
@@ -1418,6 +1732,7 @@ impl<F: Field> PiCircuit<F> {
             block_ctxs: block.context.clone(),
             prev_state_root: H256(block.mpt_updates.old_root().to_be_bytes()),
             withdraw_trie_root: H256(block.withdraw_root.to_be_bytes()),
+            prev_withdraw_trie_root: H256(block.prev_withdraw_root.to_be_bytes()),
         };
         Self {
             public_data,
@@ -1475,13 +1790,20 @@ impl<F: Field> PiCircuit<F> {
                 if let Some(withdraw_roots) = withdraw_roots {
                     log::debug!(
                         "constrain_equal of withdraw root: {:?} <-> {:?}",
-                        &local_conn.withdraw_root,
-                        &withdraw_roots.withdraw_root
+                        (&local_conn.prev_withdraw_root, &local_conn.withdraw_root),
+                        (
+                            &withdraw_roots.prev_withdraw_root,
+                            &withdraw_roots.withdraw_root
+                        )
                     );
                     region.constrain_equal(
                         local_conn.withdraw_root.cell(),
                         withdraw_roots.withdraw_root.0,
                     )?;
+                    region.constrain_equal(
+                        local_conn.prev_withdraw_root.cell(),
+                        withdraw_roots.prev_withdraw_root.0,
+                    )?;
                 } else {
                     log::warn!("withdraw roots are not set, skip connection with evm circuit");
                 }
@@ -1507,7 +1829,11 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
         let row_num = |inner_block_num, tx_num| -> usize {
-            BLOCK_HEADER_BYTES_NUM * inner_block_num + KECCAK_DIGEST_SIZE * tx_num + 33
+            // the prev withdraw root adds a digest to the pi bytes
+            BLOCK_HEADER_BYTES_NUM * inner_block_num
+                + KECCAK_DIGEST_SIZE * tx_num
+                + KECCAK_DIGEST_SIZE
+                + 33
         };
         (
             row_num(block.context.ctxs.len(), block.txs.len()),
@@ -1549,7 +1875,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                 config.block_table.annotate_columns_in_region(&mut region);
 
                 // assign block table
-                let block_value_cells = config.assign_block_table(
+                let (block_value_cells, cum_num_txs_cells) = config.assign_block_table(
                     &mut region,
                     &self.public_data,
                     self.max_inner_blocks,
@@ -1560,6 +1886,7 @@ impl<F: Field> SubCircuit<F> for PiCircuit<F> {
                     &mut region,
                     &self.public_data,
                     &block_value_cells,
+                    &cum_num_txs_cells,
                     challenges,
                 )?;
 
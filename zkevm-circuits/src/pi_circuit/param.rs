@@ -1,8 +1,12 @@
+use crate::table::BlockContextFieldTag;
+
 /// Fixed by the spec
-pub(super) const BLOCK_LEN: usize = 9;
+pub(super) const BLOCK_LEN: usize = BlockContextFieldTag::BLOCK_TABLE_FIELDS.len();
 pub(super) const BYTE_POW_BASE: u64 = 256;
-pub(super) const BLOCK_HEADER_BYTES_NUM: usize = 58;
-pub(super) const KECCAK_DIGEST_SIZE: usize = 32;
+pub(super) use zkevm_constants::{
+    keccak::DIGEST_LEN as KECCAK_DIGEST_SIZE,
+    pi::{BLOCK_HEADER_BYTES_NUM, PI_HASH_PREIMAGE_LEN},
+};
 
 pub(super) const RPI_CELL_IDX: usize = 0;
 pub(super) const RPI_RLC_ACC_CELL_IDX: usize = 1;
@@ -12,12 +16,12 @@ pub(super) const ZERO_BYTE_GAS_COST: u64 = 4;
 pub(super) const NONZERO_BYTE_GAS_COST: u64 = 16;
 
 /// Block table related offset constants
-pub(super) const COINBASE_OFFSET: usize = 0;
-pub(super) const TIMESTAMP_OFFSET: usize = 1;
-pub(super) const BLOCK_NUM_OFFSET: usize = 2;
-pub(super) const DIFFICULTY_OFFSET: usize = 3;
-pub(super) const GAS_LIMIT_OFFSET: usize = 4;
-pub(super) const BASE_FEE_OFFSET: usize = 5;
-pub(super) const CHAIN_ID_OFFSET: usize = 6;
-pub(super) const NUM_TXS_OFFSET: usize = 7;
-pub(super) const CUM_NUM_TXS_OFFSET: usize = 8;
+pub(super) const COINBASE_OFFSET: usize = BlockContextFieldTag::Coinbase.block_table_offset();
+pub(super) const TIMESTAMP_OFFSET: usize = BlockContextFieldTag::Timestamp.block_table_offset();
+pub(super) const BLOCK_NUM_OFFSET: usize = BlockContextFieldTag::Number.block_table_offset();
+pub(super) const DIFFICULTY_OFFSET: usize = BlockContextFieldTag::Difficulty.block_table_offset();
+pub(super) const GAS_LIMIT_OFFSET: usize = BlockContextFieldTag::GasLimit.block_table_offset();
+pub(super) const BASE_FEE_OFFSET: usize = BlockContextFieldTag::BaseFee.block_table_offset();
+pub(super) const CHAIN_ID_OFFSET: usize = BlockContextFieldTag::ChainId.block_table_offset();
+pub(super) const NUM_TXS_OFFSET: usize = BlockContextFieldTag::NumTxs.block_table_offset();
+pub(super) const CUM_NUM_TXS_OFFSET: usize = BlockContextFieldTag::CumNumTxs.block_table_offset();
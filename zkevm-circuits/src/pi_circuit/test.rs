@@ -97,6 +97,102 @@ fn serial_test_simple_pi() {
     );
 }
 
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_pi_blob_base_fee() {
+    const MAX_TXS: usize = 4;
+    const MAX_CALLDATA: usize = 20;
+    const MAX_INNER_BLOCKS: usize = 4;
+
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+
+    // the blob base fee isn't in the PI, so any other value is rejected
+    let mut block = block_1tx();
+    for ctx in block.context.ctxs.values_mut() {
+        ctx.blob_base_fee = Word::from(2);
+    }
+
+    let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(PiCircuit::new(
+        MAX_TXS,
+        MAX_CALLDATA,
+        MAX_INNER_BLOCKS,
+        &block,
+    ));
+    let public_inputs = circuit.0.instance();
+    let prover = MockProver::run(16, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_pi_prev_withdraw_root() {
+    const MAX_TXS: usize = 4;
+    const MAX_CALLDATA: usize = 20;
+    const MAX_INNER_BLOCKS: usize = 4;
+
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+
+    let mut block = block_1tx();
+    block.prev_withdraw_root = Word::from(0xabcd);
+    assert_eq!(
+        run::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(16, block.clone()),
+        Ok(())
+    );
+
+    // the pi hash commits to the prev withdraw root
+    let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(PiCircuit::new(
+        MAX_TXS,
+        MAX_CALLDATA,
+        MAX_INNER_BLOCKS,
+        &block,
+    ));
+    block.prev_withdraw_root = Word::from(0xabce);
+    let public_inputs = PiCircuit::<Fr>::new_from_block(&block).instance();
+    let prover = MockProver::run(16, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_pi_system_call() {
+    use eth_types::geth_types::TxType;
+
+    const MAX_TXS: usize = 4;
+    const MAX_CALLDATA: usize = 20;
+    const MAX_INNER_BLOCKS: usize = 4;
+
+    let mut difficulty_be_bytes = [0u8; 32];
+    MOCK_DIFFICULTY.to_big_endian(&mut difficulty_be_bytes);
+    set_var("DIFFICULTY", hex::encode(difficulty_be_bytes));
+    set_var("COINBASE", "0x0000000000000000000000000000000000000000");
+
+    let mut block = block_2txs();
+    let tx_type = block.txs[0].tx_type;
+    block.txs[0].tx_type = TxType::SystemCall;
+    assert_eq!(
+        run::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(16, block.clone()),
+        Ok(())
+    );
+
+    // the pi hash doesn't commit to the tx hash of the system call
+    let circuit = PiTestCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>(PiCircuit::new(
+        MAX_TXS,
+        MAX_CALLDATA,
+        MAX_INNER_BLOCKS,
+        &block,
+    ));
+    block.txs[0].tx_type = tx_type;
+    let public_inputs = PiCircuit::<Fr>::new_from_block(&block).instance();
+    let prover = MockProver::run(16, &circuit, public_inputs).unwrap();
+    assert!(prover.verify().is_err());
+}
+
 fn run_size_check<
     F: Field,
     const MAX_TXS: usize,
@@ -140,3 +236,41 @@ fn variadic_size_check() {
     run_size_check::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>([block_1, block_2.clone()]);
     run_size_check::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS>([block_0, block_2]);
 }
+
+#[test]
+fn block_table_follows_schema() {
+    use super::param::*;
+    use crate::{table::BlockContextFieldTag, util::Challenges};
+    use halo2_proofs::circuit::Value;
+
+    let block = block_1tx();
+    let challenges = Challenges::mock(
+        Value::known(Fr::from(0x100)),
+        Value::known(Fr::from(0x100)),
+        Value::known(Fr::from(0x100)),
+    );
+    let block_ctx = block.context.ctxs.values().next().unwrap();
+    let rows = block_ctx.table_assignments(1, 1, &challenges);
+
+    assert_eq!(BLOCK_LEN, BlockContextFieldTag::BLOCK_TABLE_FIELDS.len());
+    for (offset, tag) in [
+        (COINBASE_OFFSET, BlockContextFieldTag::Coinbase),
+        (TIMESTAMP_OFFSET, BlockContextFieldTag::Timestamp),
+        (BLOCK_NUM_OFFSET, BlockContextFieldTag::Number),
+        (DIFFICULTY_OFFSET, BlockContextFieldTag::Difficulty),
+        (GAS_LIMIT_OFFSET, BlockContextFieldTag::GasLimit),
+        (BASE_FEE_OFFSET, BlockContextFieldTag::BaseFee),
+        (CHAIN_ID_OFFSET, BlockContextFieldTag::ChainId),
+        (NUM_TXS_OFFSET, BlockContextFieldTag::NumTxs),
+        (CUM_NUM_TXS_OFFSET, BlockContextFieldTag::CumNumTxs),
+        (
+            BlockContextFieldTag::BlobBaseFee.block_table_offset(),
+            BlockContextFieldTag::BlobBaseFee,
+        ),
+    ] {
+        rows[offset][0].assert_if_known(|value| *value == Fr::from(tag as u64));
+        rows[offset][2]
+            .zip(block_ctx.field_value(tag, 1, 1, challenges.evm_word()))
+            .assert_if_known(|(lhs, rhs)| lhs == rhs);
+    }
+}
@@ -1757,17 +1757,10 @@ impl<F: Field> RlpCircuitConfig<F> {
             .iter()
             .flat_map(|input| input.gen_data_table(challenges))
             .collect::<Vec<_>>();
-        let sm_rows = inputs
-            .iter()
-            .flat_map(|input| input.gen_sm_witness(challenges))
-            .collect::<Vec<_>>();
-
-        debug_assert!(sm_rows.len() <= last_row);
 
         self.range256_table.load(layouter)?;
         self.rom_table.load(layouter)?;
 
-        log::debug!("num_sm_rows: {}", sm_rows.len());
         log::debug!("num_dt_rows: {}", dt_rows.len());
 
         layouter.assign_region(
@@ -1788,15 +1781,20 @@ impl<F: Field> RlpCircuitConfig<F> {
         layouter.assign_region(
             || "RLP sm region",
             |mut region| {
-                for (i, sm_row) in sm_rows.iter().enumerate() {
-                    let sm_row_next = if i == sm_rows.len() - 1 {
-                        None
-                    } else {
-                        Some(&sm_rows[i + 1])
-                    };
-                    self.assign_sm_row(&mut region, i, sm_row, sm_row_next)?;
+                // the rows are streamed from the RLP bytes of the inputs.
+                let mut sm_rows = inputs
+                    .iter()
+                    .flat_map(|input| input.sm_witness(challenges))
+                    .peekable();
+                let mut num_sm_rows = 0;
+                while let Some(sm_row) = sm_rows.next() {
+                    self.assign_sm_row(&mut region, num_sm_rows, &sm_row, sm_rows.peek())?;
+                    num_sm_rows += 1;
                 }
-                for i in sm_rows.len()..last_row {
+                log::debug!("num_sm_rows: {}", num_sm_rows);
+                debug_assert!(num_sm_rows <= last_row);
+
+                for i in num_sm_rows..last_row {
                     self.assign_sm_end_row(&mut region, i)?;
                 }
 
@@ -1899,7 +1897,7 @@ impl<F: Field> SubCircuit<F> for RlpCircuit<F, Transaction> {
         let sm_rows: usize = block
             .txs
             .iter()
-            .map(|tx| tx.gen_sm_witness(&challenges).len())
+            .map(|tx| tx.sm_witness(&challenges).count())
             .sum();
         let max_num_rows = block.circuits_params.max_rlp_rows;
 
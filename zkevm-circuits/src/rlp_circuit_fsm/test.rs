@@ -1,10 +1,16 @@
 #![allow(unused_imports)]
 use crate::{rlp_circuit_fsm::RlpCircuit, witness::Transaction};
-use eth_types::{geth_types::TxType, word, Address};
+use eth_types::{
+    geth_types::{get_rlp_unsigned, TxType},
+    word, Address, H256,
+};
 use ethers_core::{
     types::{
-        transaction::eip2718::TypedTransaction, Eip1559TransactionRequest,
-        Transaction as EthTransaction, TransactionRequest,
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessList, AccessListItem},
+        },
+        Eip2930TransactionRequest, Transaction as EthTransaction, TransactionRequest,
     },
     utils::rlp::{Decodable, Rlp},
 };
@@ -115,8 +121,7 @@ fn test_eip1559_tx() {
     let eth_tx = EthTransaction::decode(&Rlp::new(&raw_tx_rlp_bytes))
         .expect("decode tx's rlp bytes shall not fail");
 
-    let eth_tx_req: Eip1559TransactionRequest = (&eth_tx).into();
-    let rlp_unsigned = eth_tx_req.rlp().to_vec();
+    let rlp_unsigned = get_rlp_unsigned(&eth_tx);
 
     let tx = Transaction::new_from_rlp_bytes(TxType::Eip1559, raw_tx_rlp_bytes, rlp_unsigned);
     let rlp_circuit = RlpCircuit::<Fr, Transaction> {
@@ -135,3 +140,39 @@ fn test_eip1559_tx() {
 
     mock_prover.assert_satisfied_par();
 }
+
+#[test]
+fn test_eip2930_tx() {
+    let rng = &mut OsRng;
+    let from = Wallet::new(rng);
+    let tx = TransactionRequest::new()
+        .to(Address::random())
+        .value(eth(10))
+        .gas_price(word!("0x4321"))
+        .gas(word!("0x77320"))
+        .nonce(word!("0x7f"))
+        .chain_id(*MOCK_CHAIN_ID);
+    let access_list = AccessList(vec![AccessListItem {
+        address: Address::random(),
+        storage_keys: vec![H256::random(), H256::random()],
+    }]);
+    let typed_tx: TypedTransaction = Eip2930TransactionRequest::new(tx, access_list).into();
+    let sig = from.sign_transaction_sync(&typed_tx);
+    let tx = Transaction::new_from_rlp_bytes(
+        TxType::Eip2930,
+        typed_tx.rlp_signed(&sig).to_vec(),
+        typed_tx.rlp().to_vec(),
+    );
+    let rlp_circuit = RlpCircuit::<Fr, Transaction> {
+        txs: vec![tx],
+        max_txs: 10,
+        size: 1000,
+        _marker: Default::default(),
+    };
+
+    let mock_prover = MockProver::run(16, &rlp_circuit, vec![]);
+    assert!(mock_prover.is_ok());
+    let mock_prover = mock_prover.unwrap();
+
+    mock_prover.assert_satisfied_par();
+}
@@ -233,9 +233,10 @@ impl<F: Field> SubCircuit<F> for SigCircuit<F> {
     }
 
     // Since sig circuit / halo2-lib use veticle cell assignment,
-    // so the returned pair is consisted of same values
+    // so the returned pair is consisted of same values. The chip is configured
+    // for a degree of LOG_TOTAL_NUM_ROWS, whose usable rows it takes.
     fn min_num_rows_block(block: &crate::witness::Block<F>) -> (usize, usize) {
-        let row_num = Self::min_num_rows(block.circuits_params.max_txs);
+        let row_num = Self::min_num_rows(block.circuits_params.max_txs) - Self::unusable_rows();
         (row_num, row_num)
     }
 }
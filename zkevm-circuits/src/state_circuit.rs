@@ -9,10 +9,14 @@ mod random_linear_combination;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 mod dev;
 #[cfg(any(feature = "test", test))]
+mod rw_table_view;
+#[cfg(any(feature = "test", test))]
 mod test;
 #[cfg(any(feature = "test", test, feature = "test-circuits"))]
 pub use dev::StateCircuit as TestStateCircuit;
 use mpt_zktrie::mpt_circuits::MPTProofType;
+#[cfg(any(feature = "test", test))]
+pub use rw_table_view::RwTableView;
 
 use self::{
     constraint_builder::{MptUpdateTableQueries, RwTableQueries},
@@ -233,14 +237,15 @@ impl<F: Field> StateCircuitConfig<F> {
     ) -> Result<StateCircuitExports<Assigned<F>>, Error> {
         let tag_chip = BinaryNumberChip::construct(self.sort_keys.tag);
 
-        let (rows, padding_length) = RwMap::table_assignments_prepad(rows, n_rows);
+        let (rows, padding_length) = RwMap::table_assignments_prepad_iter(rows, n_rows);
+        // both the padding and the real rows have an exact size
+        let rows_len = rows.size_hint().0;
         log::info!(
             "state circuit assign total rows {}, n_rows {}, padding_length {}",
-            rows.len(),
+            rows_len,
             n_rows,
             padding_length
         );
-        let rows_len = rows.len();
 
         let mut state_root =
             randomness.map(|randomness| rlc::value(&updates.old_root().to_le_bytes(), randomness));
@@ -250,7 +255,9 @@ impl<F: Field> StateCircuitConfig<F> {
         // annotate columns
         self.annotate_circuit_in_region(region);
 
-        for (offset, row) in rows.iter().enumerate() {
+        let mut prev_row: Option<Rw> = None;
+        for (offset, row) in rows.enumerate() {
+            let row = &row;
             if offset == 0 || offset + 1 >= padding_length {
                 log::trace!("state circuit assign offset:{} row:{:?}", offset, row);
             }
@@ -282,8 +289,7 @@ impl<F: Field> StateCircuitConfig<F> {
                     .assign(region, offset, randomness, storage_key)?;
             }
 
-            if offset > 0 {
-                let prev_row = &rows[offset - 1];
+            if let Some(prev_row) = &prev_row {
                 let index = self
                     .lexicographic_ordering
                     .assign(region, offset, row, prev_row)?;
@@ -420,6 +426,7 @@ impl<F: Field> StateCircuitConfig<F> {
                 )?;
                 end_state_root.replace(assigned);
             }
+            prev_row = Some(*row);
         }
 
         let start_state_root = start_state_root.expect("should be assigned");
@@ -504,6 +511,13 @@ impl<F: Field> StateCircuit<F> {
             _marker: PhantomData::default(),
         }
     }
+
+    /// Grand product fingerprint of the rws of the circuit, exported for
+    /// checking the rw consistency between chunks. See
+    /// [`RwMap::fingerprints`].
+    pub fn rw_fingerprint(&self, randomness: F, alpha: F, gamma: F) -> F {
+        RwMap::fingerprint(&self.rows, randomness, alpha, gamma)
+    }
 }
 
 impl<F: Field> SubCircuit<F> for StateCircuit<F> {
@@ -114,6 +114,12 @@ impl<F: Field> ConstraintBuilder<F> {
         self.condition(q.tag_matches(RwTableTag::TxAccessListAccount), |cb| {
             cb.build_tx_access_list_account_constraints(q)
         });
+        self.condition(q.tag_matches(RwTableTag::TxCreatedAccount), |cb| {
+            cb.build_tx_created_account_constraints(q)
+        });
+        self.condition(q.tag_matches(RwTableTag::TxDestructedAccount), |cb| {
+            cb.build_tx_destructed_account_constraints(q)
+        });
         self.condition(
             q.tag_matches(RwTableTag::TxAccessListAccountStorage),
             |cb| cb.build_tx_access_list_account_storage_constraints(q),
@@ -361,6 +367,62 @@ impl<F: Field> ConstraintBuilder<F> {
         });
     }
 
+    fn build_tx_destructed_account_constraints(&mut self, q: &Queries<F>) {
+        self.require_zero("field_tag is 0 for TxDestructedAccount", q.field_tag());
+        self.require_zero(
+            "storage_key is 0 for TxDestructedAccount",
+            q.rw_table.storage_key.clone(),
+        );
+        self.require_boolean("TxDestructedAccount value is boolean", q.value());
+        self.require_zero(
+            "initial TxDestructedAccount value is false",
+            q.initial_value(),
+        );
+        // The DestructAccount step deleting the account clears the flag, so
+        // no self destructed account is left undeleted.
+        self.condition(q.last_access(), |cb| {
+            cb.require_zero("final TxDestructedAccount value is false", q.value())
+        });
+
+        self.require_equal(
+            "state_root is unchanged for TxDestructedAccount",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
+    fn build_tx_created_account_constraints(&mut self, q: &Queries<F>) {
+        self.require_zero("field_tag is 0 for TxCreatedAccount", q.field_tag());
+        self.require_zero(
+            "storage_key is 0 for TxCreatedAccount",
+            q.rw_table.storage_key.clone(),
+        );
+        self.require_boolean("TxCreatedAccount value is boolean", q.value());
+        self.require_zero("initial TxCreatedAccount value is false", q.initial_value());
+
+        self.require_equal(
+            "state_root is unchanged for TxCreatedAccount",
+            q.state_root(),
+            q.state_root_prev(),
+        );
+
+        self.condition(q.not_first_access.clone(), |cb| {
+            cb.require_equal(
+                "value column at Rotation::prev() equals value_prev at Rotation::cur()",
+                q.rw_table.value_prev.clone(),
+                q.value_prev_column(),
+            );
+        });
+    }
+
     fn build_tx_access_list_account_storage_constraints(&mut self, q: &Queries<F>) {
         self.require_zero(
             "field_tag is 0 for TxAccessListAccountStorage",
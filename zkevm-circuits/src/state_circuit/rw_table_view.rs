@@ -0,0 +1,171 @@
+//! A queryable view of the rows of the rw table, built from the witness rws,
+//! for the tests of the circuits looking up the rw table.
+
+use crate::{
+    evm_circuit::assigned_rw_lookups,
+    table::{CallContextFieldTag, RwTableTag},
+    witness::{Block, ExecStep, Rw, RwMap},
+};
+use eth_types::{Address, Field, Word};
+
+/// Rows of the rw table, ordered by rw counter, which can be narrowed down
+/// by tag, address or call id and read back as typed values. Gadget tests use
+/// it to assert the exact lookups of a step.
+#[derive(Clone, Debug, Default)]
+pub struct RwTableView {
+    rows: Vec<Rw>,
+}
+
+impl RwTableView {
+    /// View of all the rws of `rws`.
+    pub fn new(rws: &RwMap) -> Self {
+        let mut rows: Vec<Rw> = rws.0.values().flatten().copied().collect();
+        rows.sort_by_key(Rw::rw_counter);
+        Self { rows }
+    }
+
+    /// View of all the rws of `block`.
+    pub fn from_block<F: Field>(block: &Block<F>) -> Self {
+        Self::new(&block.rws)
+    }
+
+    /// View of the rws looked up by `step` of `block`, in the order of its
+    /// lookups.
+    pub fn from_step<F: Field>(block: &Block<F>, step: &ExecStep) -> Self {
+        let rows = step.rw_indices.iter().map(|idx| block.rws[*idx]).collect();
+        Self { rows }
+    }
+
+    /// Panic unless the rows are exactly the rw lookups assigned to `step` by
+    /// the last EVM circuit assignment of the thread, e.g. in
+    /// [`CircuitTestBuilder::block_checks`](crate::test_util::CircuitTestBuilder::block_checks).
+    /// The order is not checked, as the circuit looks up a reversion right
+    /// after its write.
+    pub fn assert_lookups_of<F: Field>(&self, step: &ExecStep) {
+        let assigned = assigned_rw_lookups::<F>(step).unwrap_or_else(|| {
+            panic!(
+                "no rw lookups assigned to the {:?} step at rw counter {}",
+                step.execution_state, step.rw_counter
+            )
+        });
+        let mut expected: Vec<F> = self
+            .rows
+            .iter()
+            .map(|rw| {
+                rw.table_assignment_aux(assigned.evm_randomness)
+                    .rlc(assigned.lookup_randomness)
+            })
+            .collect();
+        let mut found = assigned.values;
+        expected.sort();
+        found.sort();
+        assert_eq!(
+            found, expected,
+            "rw lookups of the {:?} step",
+            step.execution_state
+        );
+    }
+
+    /// Rows of the view
+    pub fn rows(&self) -> impl Iterator<Item = &Rw> {
+        self.rows.iter()
+    }
+
+    /// Number of rows of the view
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Whether the view has no row
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Tags of the rows, in order
+    pub fn tags(&self) -> Vec<RwTableTag> {
+        self.rows.iter().map(Rw::tag).collect()
+    }
+
+    /// Keep the rows of `tag`.
+    pub fn tag(self, tag: RwTableTag) -> Self {
+        self.filter(|rw| rw.tag() == tag)
+    }
+
+    /// Keep the rows of the account, storage slot, access list entry or
+    /// created or destructed flag of `address`.
+    pub fn address(self, address: Address) -> Self {
+        self.filter(|rw| {
+            matches!(
+                rw,
+                Rw::Account { account_address, .. }
+                    | Rw::AccountStorage { account_address, .. }
+                    | Rw::TxAccessListAccount { account_address, .. }
+                    | Rw::TxAccessListAccountStorage { account_address, .. }
+                    | Rw::TxCreatedAccount { account_address, .. }
+                    | Rw::TxDestructedAccount { account_address, .. }
+                    if *account_address == address
+            )
+        })
+    }
+
+    /// Keep the call context, stack and memory rows of the call `call_id`.
+    pub fn call_id(self, call_id: usize) -> Self {
+        self.filter(|rw| {
+            matches!(
+                rw,
+                Rw::CallContext { call_id: id, .. }
+                    | Rw::Stack { call_id: id, .. }
+                    | Rw::Memory { call_id: id, .. }
+                    if *id == call_id
+            )
+        })
+    }
+
+    /// Keep the reads, or the writes if `is_write`.
+    pub fn is_write(self, is_write: bool) -> Self {
+        self.filter(|rw| rw.is_write() == is_write)
+    }
+
+    /// Keep the rows matching `predicate`.
+    pub fn filter(self, predicate: impl Fn(&Rw) -> bool) -> Self {
+        Self {
+            rows: self.rows.into_iter().filter(|rw| predicate(rw)).collect(),
+        }
+    }
+
+    /// Field tag and value of the call context rows
+    pub fn call_context(&self) -> impl Iterator<Item = (CallContextFieldTag, Word)> + '_ {
+        self.rows.iter().filter_map(|rw| match rw {
+            Rw::CallContext {
+                field_tag, value, ..
+            } => Some((*field_tag, *value)),
+            _ => None,
+        })
+    }
+
+    /// Whether it is a write, stack pointer and value of the stack rows
+    pub fn stack(&self) -> impl Iterator<Item = (bool, usize, Word)> + '_ {
+        self.rows.iter().filter_map(|rw| match rw {
+            Rw::Stack {
+                is_write,
+                stack_pointer,
+                value,
+                ..
+            } => Some((*is_write, *stack_pointer, *value)),
+            _ => None,
+        })
+    }
+
+    /// Key, value and committed value of the storage rows
+    pub fn storage(&self) -> impl Iterator<Item = (Word, Word, Word)> + '_ {
+        self.rows.iter().filter_map(|rw| match rw {
+            Rw::AccountStorage {
+                storage_key,
+                value,
+                committed_value,
+                ..
+            } => Some((*storage_key, *value, *committed_value)),
+            _ => None,
+        })
+    }
+}
@@ -171,6 +171,52 @@ fn state_circuit_simple_2() {
     );
 }
 
+#[test]
+fn rw_fingerprint_of_chunks() {
+    let rw_map = RwMap::from(&OperationContainer {
+        stack: vec![
+            Operation::new(
+                RWCounter::from(17),
+                RW::WRITE,
+                StackOp::new(1, StackAddress::from(1), Word::from(32)),
+            ),
+            Operation::new(
+                RWCounter::from(87),
+                RW::READ,
+                StackOp::new(1, StackAddress::from(1), Word::from(32)),
+            ),
+        ],
+        memory: vec![Operation::new(
+            RWCounter::from(12),
+            RW::WRITE,
+            MemoryOp::new_write(1, MemoryAddress::from(0), 32.into(), 0.into()),
+        )],
+        ..Default::default()
+    });
+    let (randomness, alpha, gamma) = (Fr::from(3), Fr::from(5), Fr::from(7));
+    let rows = rw_map.table_assignments();
+    let fingerprint =
+        StateCircuit::<Fr>::new(rw_map, N_ROWS).rw_fingerprint(randomness, alpha, gamma);
+
+    // rows split into chunks, in execution order
+    let mut rows_by_counter = rows.clone();
+    rows_by_counter.sort_by_key(|rw| rw.rw_counter());
+    let (chunk_0, chunk_1) = rows_by_counter.split_at(1);
+    assert_eq!(
+        RwMap::fingerprint(chunk_0, randomness, alpha, gamma)
+            * RwMap::fingerprint(chunk_1, randomness, alpha, gamma),
+        fingerprint
+    );
+    assert_eq!(
+        RwMap::fingerprints(&rows, randomness, alpha, gamma).len(),
+        rows.len()
+    );
+    assert_ne!(
+        RwMap::fingerprint(chunk_1, randomness, alpha, gamma),
+        fingerprint
+    );
+}
+
 #[test]
 fn state_circuit_simple_6() {
     let memory_op_0 = Operation::new(
@@ -918,6 +964,51 @@ fn bad_initial_tx_access_list_account_value() {
     );
 }
 
+#[test]
+fn bad_initial_tx_created_account_value() {
+    let rows = vec![Rw::TxCreatedAccount {
+        rw_counter: 1,
+        is_write: false,
+        tx_id: 1,
+        account_address: address!("0x0000000000000000000000000000000004356002"),
+        is_created: true,
+        is_created_prev: true,
+    }];
+
+    let overrides = HashMap::from([
+        ((AdviceColumn::InitialValue, 0), Fr::from(1)),
+        ((AdviceColumn::ValuePrev, 0), Fr::from(1)),
+    ]);
+
+    assert_error_matches(
+        verify_with_overrides(rows, overrides),
+        "initial TxCreatedAccount value is false",
+    );
+}
+
+#[test]
+fn tx_destructed_account_cleared() {
+    let address = address!("0x0000000000000000000000000000000004356002");
+    let destructed = |rw_counter, is_destructed, is_destructed_prev| Rw::TxDestructedAccount {
+        rw_counter,
+        is_write: true,
+        tx_id: 1,
+        account_address: address,
+        is_destructed,
+        is_destructed_prev,
+    };
+
+    assert_eq!(
+        verify(vec![destructed(1, true, false), destructed(2, false, true)]),
+        Ok(())
+    );
+    // an account self destructed in the tx must be deleted at its end
+    assert_error_matches(
+        verify(vec![destructed(1, true, false)]),
+        "final TxDestructedAccount value is false",
+    );
+}
+
 #[test]
 fn bad_initial_tx_refund_value() {
     let rows = vec![Rw::TxRefund {
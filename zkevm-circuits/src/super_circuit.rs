@@ -50,9 +50,12 @@
 //!   - [x] Tx Circuit
 //!   - [ ] MPT Circuit
 
+mod manifest;
 #[cfg(any(feature = "test", test))]
 pub(crate) mod test;
 
+pub use manifest::{ColumnCounts, ComponentKind, ComponentLayout, LayoutManifest};
+
 #[cfg(feature = "poseidon-codehash")]
 use crate::bytecode_circuit::circuit::to_poseidon_hash::{
     ToHashBlockBytecodeCircuitConfigArgs, ToHashBlockCircuitConfig, HASHBLOCK_BYTES_IN_FIELD,
@@ -70,7 +73,7 @@ use crate::{
     sig_circuit::{SigCircuit, SigCircuitConfig, SigCircuitConfigArgs},
     tx_circuit::{TxCircuit, TxCircuitConfig, TxCircuitConfigArgs},
     util::{log2_ceil, SubCircuit, SubCircuitConfig},
-    witness::{block_convert, Block},
+    witness::{block_convert_with_mock_challenges, Block},
 };
 
 #[cfg(feature = "zktrie")]
@@ -88,16 +91,17 @@ use crate::{
 
 use crate::util::circuit_stats;
 use bus_mapping::{
-    circuit_input_builder::{CircuitInputBuilder, CircuitsParams},
+    circuit_input_builder::{ChunkLayout, CircuitInputBuilder, CircuitsParams},
     mock::BlockData,
 };
 use eth_types::{geth_types::GethData, Field};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
-    halo2curves::bn256::Fr,
+    halo2curves::bn256::{Bn256, Fr},
     plonk::{Circuit, ConstraintSystem, Error},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
 };
-use itertools::Itertools;
+#[cfg(feature = "prover")]
 use snark_verifier_sdk::CircuitExt;
 
 use crate::{
@@ -106,6 +110,73 @@ use crate::{
     witness::Transaction,
 };
 
+/// Names of the sub-circuits, in the order of
+/// [`SuperCircuit::min_num_rows_block_subcircuits`].
+pub const SUBCIRCUIT_NAMES: &[&str] = &[
+    "evm",
+    "state",
+    "bytecode",
+    "copy",
+    "keccak",
+    "tx",
+    "rlp",
+    "exp",
+    "pi",
+    "poseidon",
+    "sig",
+    "ecc",
+    #[cfg(feature = "zktrie")]
+    "mpt",
+];
+
+/// The minimal SRS degrees able to prove a block, per sub-circuit and for the
+/// super circuit. The degrees follow the padded number of rows of each
+/// sub-circuit, so they are only minimal for the witness when the circuit
+/// params leave the rows to be computed dynamically (`max_evm_rows` and
+/// `max_keccak_rows` set to 0); otherwise they are the ones fixed by the
+/// params.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitDegrees {
+    /// Degree of each sub-circuit, in the order of [`SUBCIRCUIT_NAMES`].
+    pub subcircuits: Vec<(&'static str, u32)>,
+    /// Degree of the super circuit.
+    pub super_circuit: u32,
+}
+
+impl CircuitDegrees {
+    /// Degree of the sub-circuit `name`.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.subcircuits
+            .iter()
+            .find(|(subcircuit, _)| *subcircuit == name)
+            .map(|(_, k)| *k)
+    }
+
+    /// Slice `params` down to the degree of the sub-circuit `name`. Returns
+    /// None for an unknown sub-circuit or if `params` are too small.
+    pub fn params_for(&self, name: &str, params: &ParamsKZG<Bn256>) -> Option<ParamsKZG<Bn256>> {
+        let k = self.get(name)?;
+        Self::downsize(k, params)
+    }
+
+    /// Slice `params` down to the degree of the super circuit. Returns None if
+    /// `params` are too small.
+    pub fn params_for_super_circuit(&self, params: &ParamsKZG<Bn256>) -> Option<ParamsKZG<Bn256>> {
+        Self::downsize(self.super_circuit, params)
+    }
+
+    fn downsize(k: u32, params: &ParamsKZG<Bn256>) -> Option<ParamsKZG<Bn256>> {
+        if params.k() < k {
+            return None;
+        }
+        let mut params = params.clone();
+        if params.k() > k {
+            params.downsize(k);
+        }
+        Some(params)
+    }
+}
+
 /// Configuration of the Super Circuit
 #[derive(Clone)]
 pub struct SuperCircuitConfig<F: Field> {
@@ -114,6 +185,11 @@ pub struct SuperCircuitConfig<F: Field> {
     rlp_table: RlpTable,
     tx_table: TxTable,
     poseidon_table: PoseidonTable,
+    bytecode_table: BytecodeTable,
+    copy_table: CopyTable,
+    exp_table: ExpTable,
+    keccak_table: KeccakTable,
+    sig_table: SigTable,
     evm_circuit: EvmCircuitConfig<F>,
     state_circuit: StateCircuitConfig<F>,
     tx_circuit: TxCircuitConfig<F>,
@@ -152,51 +228,59 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
     type ConfigArgs = SuperCircuitConfigArgs;
 
     /// Configure SuperCircuitConfig
-    fn new(
+    fn new(meta: &mut ConstraintSystem<Fr>, args: Self::ConfigArgs) -> Self {
+        Self::configure_components(meta, args, &mut |meta, tag| {
+            log::debug!("circuit info after {}: {:#?}", tag, circuit_stats(meta));
+        })
+    }
+}
+
+impl SuperCircuitConfig<Fr> {
+    /// Configure the tables and circuits one after the other, calling
+    /// `on_component` with the name of each once it's configured.
+    pub(crate) fn configure_components(
         meta: &mut ConstraintSystem<Fr>,
-        Self::ConfigArgs {
+        SuperCircuitConfigArgs {
             max_txs,
             max_calldata,
             max_inner_blocks,
             mock_randomness: _mock_randomness,
             challenges,
-        }: Self::ConfigArgs,
+        }: SuperCircuitConfigArgs,
+        on_component: &mut dyn FnMut(&ConstraintSystem<Fr>, &'static str),
     ) -> Self {
-        let log_circuit_info = |meta: &ConstraintSystem<Fr>, tag: &str| {
-            log::debug!("circuit info after {}: {:#?}", tag, circuit_stats(meta));
-        };
         let challenges_expr = challenges.exprs(meta);
 
         let tx_table = TxTable::construct(meta);
-        log_circuit_info(meta, "tx table");
+        on_component(meta, "tx table");
         let rw_table = RwTable::construct(meta);
-        log_circuit_info(meta, "rw table");
+        on_component(meta, "rw table");
 
         let mpt_table = MptTable::construct(meta);
-        log_circuit_info(meta, "mpt table");
+        on_component(meta, "mpt table");
         let poseidon_table = PoseidonTable::construct(meta);
-        log_circuit_info(meta, "poseidon table");
+        on_component(meta, "poseidon table");
 
         let bytecode_table = BytecodeTable::construct(meta);
-        log_circuit_info(meta, "bytecode table");
+        on_component(meta, "bytecode table");
         let block_table = BlockTable::construct(meta);
-        log_circuit_info(meta, "block table");
+        on_component(meta, "block table");
         let q_copy_table = meta.fixed_column();
         log::debug!("q_copy_table {:?}", q_copy_table);
         let copy_table = CopyTable::construct(meta, q_copy_table);
-        log_circuit_info(meta, "copy table");
+        on_component(meta, "copy table");
         let exp_table = ExpTable::construct(meta);
-        log_circuit_info(meta, "exp table");
+        on_component(meta, "exp table");
         let rlp_table = RlpTable::construct(meta);
-        log_circuit_info(meta, "rlp table");
+        on_component(meta, "rlp table");
         let keccak_table = KeccakTable::construct(meta);
-        log_circuit_info(meta, "keccak table");
+        on_component(meta, "keccak table");
         let sig_table = SigTable::construct(meta);
-        log_circuit_info(meta, "sig table");
+        on_component(meta, "sig table");
         let ecc_table = EccTable::construct(meta);
-        log_circuit_info(meta, "ecc table");
+        on_component(meta, "ecc table");
         let pow_of_rand_table = PowOfRandTable::construct(meta, &challenges_expr);
-        log_circuit_info(meta, "power of randomness table");
+        on_component(meta, "power of randomness table");
 
         let keccak_circuit = KeccakCircuitConfig::new(
             meta,
@@ -205,11 +289,11 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "keccak circuit");
+        on_component(meta, "keccak circuit");
 
         let poseidon_circuit =
             PoseidonCircuitConfig::new(meta, PoseidonCircuitConfigArgs { poseidon_table });
-        log_circuit_info(meta, "poseidon circuit");
+        on_component(meta, "poseidon circuit");
 
         let rlp_circuit = RlpCircuitConfig::new(
             meta,
@@ -218,7 +302,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "rlp circuit");
+        on_component(meta, "rlp circuit");
 
         let pi_circuit = PiCircuitConfig::new(
             meta,
@@ -232,7 +316,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "pi circuit");
+        on_component(meta, "pi circuit");
 
         let tx_circuit = TxCircuitConfig::new(
             meta,
@@ -245,7 +329,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "tx circuit");
+        on_component(meta, "tx circuit");
 
         #[cfg(not(feature = "poseidon-codehash"))]
         let bytecode_circuit = BytecodeCircuitConfig::new(
@@ -269,7 +353,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             },
         );
 
-        log_circuit_info(meta, "bytecode circuit");
+        on_component(meta, "bytecode circuit");
 
         let copy_circuit = CopyCircuitConfig::new(
             meta,
@@ -282,7 +366,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "copy circuit");
+        on_component(meta, "copy circuit");
 
         #[cfg(feature = "zktrie")]
         let mpt_circuit = MptCircuitConfig::new(
@@ -294,7 +378,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             },
         );
         #[cfg(feature = "zktrie")]
-        log_circuit_info(meta, "zktrie circuit");
+        on_component(meta, "zktrie circuit");
 
         let sig_circuit = SigCircuitConfig::new(
             meta,
@@ -304,7 +388,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "sig circuit");
+        on_component(meta, "sig circuit");
 
         let ecc_circuit = EccCircuitConfig::new(
             meta,
@@ -313,7 +397,7 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "ecc circuit");
+        on_component(meta, "ecc circuit");
 
         let state_circuit = StateCircuitConfig::new(
             meta,
@@ -323,10 +407,10 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr.clone(),
             },
         );
-        log_circuit_info(meta, "state circuit");
+        on_component(meta, "state circuit");
 
         let exp_circuit = ExpCircuitConfig::new(meta, exp_table);
-        log_circuit_info(meta, "exp circuit");
+        on_component(meta, "exp circuit");
 
         let evm_circuit = EvmCircuitConfig::new(
             meta,
@@ -334,17 +418,17 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
                 challenges: challenges_expr,
                 tx_table: tx_table.clone(),
                 rw_table,
-                bytecode_table,
+                bytecode_table: bytecode_table.clone(),
                 block_table: block_table.clone(),
                 copy_table,
-                keccak_table,
+                keccak_table: keccak_table.clone(),
                 exp_table,
                 sig_table,
                 ecc_table,
                 pow_of_rand_table,
             },
         );
-        log_circuit_info(meta, "evm circuit");
+        on_component(meta, "evm circuit");
 
         #[cfg(feature = "onephase")]
         if meta.max_phase() != 0 {
@@ -357,6 +441,11 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
             tx_table,
             rlp_table,
             poseidon_table,
+            bytecode_table,
+            copy_table,
+            exp_table,
+            keccak_table,
+            sig_table,
             evm_circuit,
             state_circuit,
             copy_circuit,
@@ -375,6 +464,25 @@ impl SubCircuitConfig<Fr> for SuperCircuitConfig<Fr> {
     }
 }
 
+/// Sub-circuits whose assignment the [`SuperCircuit`] replaces with a dev load
+/// of their lookup table from the block, see
+/// [`SuperCircuit::with_skipped_assignments`]. The lookups of the other
+/// sub-circuits into the table are still checked, but the table itself isn't
+/// constrained.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SkippedAssignments {
+    /// Keccak Circuit, the keccak table is loaded from the keccak inputs
+    pub keccak: bool,
+    /// Bytecode Circuit
+    pub bytecode: bool,
+    /// Copy Circuit
+    pub copy: bool,
+    /// Exp Circuit
+    pub exp: bool,
+    /// Sig Circuit
+    pub sig: bool,
+}
+
 /// The Super Circuit contains all the zkEVM circuits
 #[derive(Clone, Default, Debug)]
 pub struct SuperCircuit<
@@ -411,6 +519,7 @@ pub struct SuperCircuit<
     /// Mpt Circuit
     #[cfg(feature = "zktrie")]
     pub mpt_circuit: MptCircuit<F>,
+    skipped_assignments: SkippedAssignments,
 }
 
 impl<
@@ -442,7 +551,9 @@ impl<
         let rlp = RlpCircuit::min_num_rows_block(block);
         let exp = ExpCircuit::min_num_rows_block(block);
         let pi = PiCircuit::min_num_rows_block(block);
-        let poseidon = (0, 0); //PoseidonCircuit::min_num_rows_block(block);
+        let poseidon = PoseidonCircuit::min_num_rows_block(block);
+        let sig = SigCircuit::min_num_rows_block(block);
+        let ecc = EccCircuit::<Fr, 9>::min_num_rows_block(block);
         #[cfg(feature = "zktrie")]
         let mpt = MptCircuit::<Fr>::min_num_rows_block(block);
 
@@ -457,6 +568,8 @@ impl<
             exp,
             pi,
             poseidon,
+            sig,
+            ecc,
             #[cfg(feature = "zktrie")]
             mpt,
         ];
@@ -469,6 +582,62 @@ impl<
         log::debug!("subcircuit rows(with    padding): {:?}", rows_with_padding);
         (rows_without_padding, rows_with_padding)
     }
+
+    /// Replace the assignment of the `skipped` sub-circuits with a dev load of
+    /// their table, for tests which focus on the other sub-circuits. The
+    /// circuit is then only meant for the `MockProver`.
+    #[cfg(any(feature = "test", test))]
+    pub fn with_skipped_assignments(mut self, skipped: SkippedAssignments) -> Self {
+        self.skipped_assignments = skipped;
+        self
+    }
+
+    fn block(&self) -> &Block<F> {
+        self.evm_circuit
+            .block
+            .as_ref()
+            .expect("the evm circuit has the witness block")
+    }
+
+    /// Return the minimal SRS degrees able to prove the block, see
+    /// [`CircuitDegrees`].
+    pub fn min_degrees(block: &Block<Fr>) -> CircuitDegrees {
+        let unusable_rows = [
+            EvmCircuit::<Fr>::unusable_rows(),
+            StateCircuit::<Fr>::unusable_rows(),
+            BytecodeCircuit::<Fr>::unusable_rows(),
+            CopyCircuit::<Fr>::unusable_rows(),
+            KeccakCircuit::<Fr>::unusable_rows(),
+            TxCircuit::<Fr>::unusable_rows(),
+            RlpCircuit::<Fr, Transaction>::unusable_rows(),
+            ExpCircuit::<Fr>::unusable_rows(),
+            PiCircuit::<Fr>::unusable_rows(),
+            PoseidonCircuit::<Fr>::unusable_rows(),
+            SigCircuit::<Fr>::unusable_rows(),
+            EccCircuit::<Fr, 9>::unusable_rows(),
+            #[cfg(feature = "zktrie")]
+            MptCircuit::<Fr>::unusable_rows(),
+        ];
+        let (_, rows_with_padding) = Self::min_num_rows_block_subcircuits(block);
+        debug_assert_eq!(rows_with_padding.len(), SUBCIRCUIT_NAMES.len());
+
+        let subcircuits = SUBCIRCUIT_NAMES
+            .iter()
+            .zip(unusable_rows)
+            .zip(rows_with_padding.iter())
+            .map(|((name, unusable_rows), rows)| (*name, log2_ceil(unusable_rows + rows)))
+            .collect();
+        let super_circuit = log2_ceil(
+            SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>::unusable_rows()
+                + itertools::max(rows_with_padding).unwrap(),
+        );
+        let degrees = CircuitDegrees {
+            subcircuits,
+            super_circuit,
+        };
+        log::debug!("circuit degrees: {:?}", degrees);
+        degrees
+    }
 }
 
 // Eventhough the SuperCircuit is not a subcircuit we implement the SubCircuit
@@ -528,6 +697,7 @@ impl<
             ecc_circuit,
             #[cfg(feature = "zktrie")]
             mpt_circuit,
+            skipped_assignments: SkippedAssignments::default(),
         }
     }
 
@@ -562,22 +732,55 @@ impl<
         challenges: &crate::util::Challenges<Value<Fr>>,
         layouter: &mut impl Layouter<Fr>,
     ) -> Result<(), Error> {
-        self.keccak_circuit
-            .synthesize_sub(&config.keccak_circuit, challenges, layouter)?;
+        let skipped = self.skipped_assignments;
+        if skipped.keccak {
+            // the lookup tables of the keccak circuit keep their shape
+            config.keccak_circuit.load_aux_tables(layouter)?;
+            config
+                .keccak_table
+                .dev_load(layouter, &self.block().keccak_inputs, challenges)?;
+        } else {
+            self.keccak_circuit
+                .synthesize_sub(&config.keccak_circuit, challenges, layouter)?;
+        }
         self.poseidon_circuit
             .synthesize_sub(&config.poseidon_circuit, challenges, layouter)?;
-        self.bytecode_circuit
-            .synthesize_sub(&config.bytecode_circuit, challenges, layouter)?;
+        if skipped.bytecode {
+            config.bytecode_table.dev_load(
+                layouter,
+                self.block().bytecodes.values(),
+                challenges,
+            )?;
+        } else {
+            self.bytecode_circuit
+                .synthesize_sub(&config.bytecode_circuit, challenges, layouter)?;
+        }
         self.tx_circuit
             .synthesize_sub(&config.tx_circuit, challenges, layouter)?;
-        self.sig_circuit
-            .synthesize_sub(&config.sig_circuit, challenges, layouter)?;
+        if skipped.sig {
+            config
+                .sig_table
+                .dev_load(layouter, self.block(), challenges)?;
+        } else {
+            self.sig_circuit
+                .synthesize_sub(&config.sig_circuit, challenges, layouter)?;
+        }
         self.state_circuit
             .synthesize_sub(&config.state_circuit, challenges, layouter)?;
-        self.copy_circuit
-            .synthesize_sub(&config.copy_circuit, challenges, layouter)?;
-        self.exp_circuit
-            .synthesize_sub(&config.exp_circuit, challenges, layouter)?;
+        if skipped.copy {
+            config
+                .copy_table
+                .dev_load(layouter, self.block(), challenges)?;
+        } else {
+            self.copy_circuit
+                .synthesize_sub(&config.copy_circuit, challenges, layouter)?;
+        }
+        if skipped.exp {
+            config.exp_table.dev_load(layouter, self.block())?;
+        } else {
+            self.exp_circuit
+                .synthesize_sub(&config.exp_circuit, challenges, layouter)?;
+        }
         self.evm_circuit
             .synthesize_sub(&config.evm_circuit, challenges, layouter)?;
 
@@ -612,7 +815,12 @@ impl<
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // the parameters of the fixed tables must be kept for the keygen
+        Self {
+            evm_circuit: self.evm_circuit.without_witnesses(),
+            skipped_assignments: self.skipped_assignments,
+            ..Self::default()
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
@@ -654,6 +862,7 @@ impl<
     }
 }
 
+#[cfg(feature = "prover")]
 impl<
         const MAX_TXS: usize,
         const MAX_CALLDATA: usize,
@@ -663,7 +872,7 @@ impl<
     for SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
 {
     fn num_instance(&self) -> Vec<usize> {
-        self.instances().iter().map(|l| l.len()).collect_vec()
+        self.instances().iter().map(|l| l.len()).collect()
     }
 
     fn instances(&self) -> Vec<Vec<Fr>> {
@@ -700,6 +909,27 @@ impl<
         Ok((ret.0, ret.1, ret.2, builder))
     }
 
+    /// Same as [`Self::build`], with the sub-circuits left out of `layout`
+    /// zero-sized. Fails if the block needs any of them.
+    #[allow(clippy::type_complexity)]
+    pub fn build_with_layout(
+        geth_data: GethData,
+        circuits_params: CircuitsParams,
+        layout: ChunkLayout,
+    ) -> Result<(u32, Self, Vec<Vec<Fr>>, CircuitInputBuilder), bus_mapping::Error> {
+        let block_data = BlockData::new_from_geth_data_with_params(
+            geth_data.clone(),
+            layout.apply(circuits_params),
+        );
+
+        let mut builder = block_data.new_circuit_input_builder();
+        builder.handle_block(&geth_data.eth_block, &geth_data.geth_traces)?;
+        layout.check(&builder.block)?;
+
+        let ret = Self::build_from_circuit_input_builder(&builder)?;
+        Ok((ret.0, ret.1, ret.2, builder))
+    }
+
     /// From CircuitInputBuilder, generate a SuperCircuit instance with all of
     /// the sub-circuits filled with their corresponding witnesses.
     ///
@@ -708,8 +938,9 @@ impl<
     pub fn build_from_circuit_input_builder(
         builder: &CircuitInputBuilder,
     ) -> Result<(u32, Self, Vec<Vec<Fr>>), bus_mapping::Error> {
-        let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
-        block.randomness = Fr::from(MOCK_RANDOMNESS);
+        let block =
+            block_convert_with_mock_challenges(&builder.block, &builder.code_db, MOCK_RANDOMNESS)
+                .unwrap();
         assert_eq!(block.circuits_params.max_txs, MAX_TXS);
         assert_eq!(block.circuits_params.max_calldata, MAX_CALLDATA);
         Self::build_from_witness_block(block)
@@ -723,8 +954,7 @@ impl<
             block.circuits_params
         );
 
-        let (_, rows_needed) = Self::min_num_rows_block(&block);
-        let k = log2_ceil(Self::unusable_rows() + rows_needed);
+        let k = Self::min_degrees(&block).super_circuit;
         log::debug!("super circuit needs k = {}", k);
 
         let circuit =
@@ -0,0 +1,174 @@
+//! Machine-readable layout of the super circuit: the columns, lookups and
+//! constraints of each table and sub-circuit, the instance columns and the
+//! degree, for audit tooling and documentation to be generated from the code.
+
+use super::{SuperCircuit, SuperCircuitConfig, SuperCircuitConfigArgs};
+use crate::util::{Challenges, SubCircuit};
+use halo2_proofs::{halo2curves::bn256::Fr, plonk::ConstraintSystem};
+use serde::Serialize;
+
+/// Numbers of columns, by type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ColumnCounts {
+    /// Advice columns
+    pub advice: usize,
+    /// Fixed columns, the selectors excluded
+    pub fixed: usize,
+    /// Instance columns
+    pub instance: usize,
+    /// Selectors
+    pub selectors: usize,
+}
+
+impl ColumnCounts {
+    fn of(meta: &ConstraintSystem<Fr>) -> Self {
+        Self {
+            advice: meta.num_advice_columns,
+            fixed: meta.num_fixed_columns,
+            instance: meta.num_instance_columns,
+            selectors: meta.num_selectors,
+        }
+    }
+
+    fn sub(self, prev: Self) -> Self {
+        Self {
+            advice: self.advice - prev.advice,
+            fixed: self.fixed - prev.fixed,
+            instance: self.instance - prev.instance,
+            selectors: self.selectors - prev.selectors,
+        }
+    }
+}
+
+/// Kind of a component of the super circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    /// Lookup table shared by the circuits
+    Table,
+    /// Sub-circuit
+    Circuit,
+}
+
+/// Layout of a table or sub-circuit, which is what it adds to the constraint
+/// system of the super circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ComponentLayout {
+    /// Name, e.g. "rw table" or "evm circuit"
+    pub name: &'static str,
+    /// Table or sub-circuit
+    pub kind: ComponentKind,
+    /// Columns
+    pub columns: ColumnCounts,
+    /// Polynomial constraints of the gates
+    pub constraints: usize,
+    /// Lookup arguments
+    pub lookups: usize,
+    /// Highest degree of the constraints of the gates
+    pub max_gate_degree: usize,
+}
+
+/// Layout of the super circuit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayoutManifest {
+    /// `MAX_TXS` of the super circuit
+    pub max_txs: usize,
+    /// `MAX_CALLDATA` of the super circuit
+    pub max_calldata: usize,
+    /// `MAX_INNER_BLOCKS` of the super circuit
+    pub max_inner_blocks: usize,
+    /// Degree of the constraint system, gates and lookups included
+    pub degree: usize,
+    /// Rows unusable at the end of the circuit
+    pub unusable_rows: usize,
+    /// Challenges
+    pub challenges: usize,
+    /// Highest phase of the advice columns
+    pub max_phase: u8,
+    /// Columns of the super circuit
+    pub columns: ColumnCounts,
+    /// Owner of each instance column, in order
+    pub instance_columns: Vec<&'static str>,
+    /// Tables and sub-circuits, in the order they are configured
+    pub components: Vec<ComponentLayout>,
+}
+
+impl LayoutManifest {
+    /// The manifest as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("manifest serializes to JSON")
+    }
+}
+
+impl<
+        const MAX_TXS: usize,
+        const MAX_CALLDATA: usize,
+        const MAX_INNER_BLOCKS: usize,
+        const MOCK_RANDOMNESS: u64,
+    > SuperCircuit<Fr, MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>
+{
+    /// Configure the super circuit and describe its layout, without any
+    /// witness.
+    pub fn layout_manifest() -> LayoutManifest {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let challenges = Challenges::construct(&mut meta);
+        let mut prev = (
+            ColumnCounts::of(&meta),
+            meta.gates().len(),
+            meta.lookups.len(),
+        );
+        let mut components = Vec::new();
+        let mut instance_columns = Vec::new();
+        SuperCircuitConfig::configure_components(
+            &mut meta,
+            SuperCircuitConfigArgs {
+                max_txs: MAX_TXS,
+                max_calldata: MAX_CALLDATA,
+                max_inner_blocks: MAX_INNER_BLOCKS,
+                mock_randomness: MOCK_RANDOMNESS,
+                challenges,
+            },
+            &mut |meta, name| {
+                let (prev_columns, prev_gates, prev_lookups) = prev;
+                let columns = ColumnCounts::of(meta).sub(prev_columns);
+                let gates = &meta.gates()[prev_gates..];
+                instance_columns.extend(std::iter::repeat(name).take(columns.instance));
+                components.push(ComponentLayout {
+                    name,
+                    kind: if name.ends_with("table") {
+                        ComponentKind::Table
+                    } else {
+                        ComponentKind::Circuit
+                    },
+                    columns,
+                    constraints: gates.iter().map(|gate| gate.polynomials().len()).sum(),
+                    lookups: meta.lookups.len() - prev_lookups,
+                    max_gate_degree: gates
+                        .iter()
+                        .flat_map(|gate| gate.polynomials())
+                        .map(|poly| poly.degree())
+                        .max()
+                        .unwrap_or_default(),
+                });
+                prev = (
+                    ColumnCounts::of(meta),
+                    meta.gates().len(),
+                    meta.lookups.len(),
+                );
+            },
+        );
+
+        LayoutManifest {
+            max_txs: MAX_TXS,
+            max_calldata: MAX_CALLDATA,
+            max_inner_blocks: MAX_INNER_BLOCKS,
+            degree: meta.degree(),
+            unusable_rows: Self::unusable_rows(),
+            challenges: meta.num_challenges(),
+            max_phase: meta.max_phase(),
+            columns: ColumnCounts::of(&meta),
+            instance_columns,
+            components,
+        }
+    }
+}
@@ -20,6 +20,48 @@ fn super_circuit_degree() {
     assert!(cs.degree() <= 9);
 }
 
+#[test]
+fn super_circuit_layout_manifest() {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    SuperCircuit::<Fr, 1, 32, 64, 0x100>::configure(&mut cs);
+    let manifest = SuperCircuit::<Fr, 1, 32, 64, 0x100>::layout_manifest();
+
+    assert_eq!(manifest.degree, cs.degree());
+    assert_eq!(manifest.columns.advice, cs.num_advice_columns);
+    assert_eq!(manifest.columns.fixed, cs.num_fixed_columns);
+    assert_eq!(manifest.instance_columns.len(), cs.num_instance_columns);
+    let mut columns = ColumnCounts::default();
+    for component in &manifest.components {
+        columns.advice += component.columns.advice;
+        columns.fixed += component.columns.fixed;
+        columns.instance += component.columns.instance;
+        columns.selectors += component.columns.selectors;
+        assert!(component.max_gate_degree <= manifest.degree);
+    }
+    assert_eq!(columns, manifest.columns);
+    assert_eq!(
+        manifest.components.iter().map(|c| c.lookups).sum::<usize>(),
+        cs.lookups.len()
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&manifest.to_json()).unwrap();
+    assert_eq!(json["components"][0]["name"], "tx table");
+    assert_eq!(json["components"][0]["kind"], "table");
+}
+
+#[test]
+fn super_circuit_without_witnesses_keeps_params() {
+    let mut circuit = SuperCircuit::<Fr, 1, 32, 64, 0x100>::default();
+    circuit.evm_circuit.params.shanghai_block = Some(1);
+    circuit.evm_circuit.params.curie_block = Some(2);
+    circuit.evm_circuit.params.max_code_size = 1024;
+
+    assert_eq!(
+        circuit.without_witnesses().evm_circuit.params,
+        circuit.evm_circuit.params
+    );
+}
+
 fn test_super_circuit<
     const MAX_TXS: usize,
     const MAX_CALLDATA: usize,
@@ -28,6 +70,23 @@ fn test_super_circuit<
 >(
     geth_data: GethData,
     circuits_params: CircuitsParams,
+) {
+    test_super_circuit_skipping::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, MOCK_RANDOMNESS>(
+        geth_data,
+        circuits_params,
+        SkippedAssignments::default(),
+    )
+}
+
+fn test_super_circuit_skipping<
+    const MAX_TXS: usize,
+    const MAX_CALLDATA: usize,
+    const MAX_INNER_BLOCKS: usize,
+    const MOCK_RANDOMNESS: u64,
+>(
+    geth_data: GethData,
+    circuits_params: CircuitsParams,
+    skipped: SkippedAssignments,
 ) {
     set_var("COINBASE", "0x0000000000000000000000000000000000000000");
     set_var("CHAIN_ID", MOCK_CHAIN_ID.to_string());
@@ -40,8 +99,9 @@ fn test_super_circuit<
     builder
         .handle_block(&block_data.eth_block, &block_data.geth_traces)
         .expect("could not handle block tx");
-    let mut block = block_convert(&builder.block, &builder.code_db).unwrap();
-    block.randomness = Fr::from(MOCK_RANDOMNESS);
+    let mut block =
+        block_convert_with_mock_challenges(&builder.block, &builder.code_db, MOCK_RANDOMNESS)
+            .unwrap();
 
     // Mock fill state roots
     assert!(*HASH_SCHEME_DONE);
@@ -60,6 +120,7 @@ fn test_super_circuit<
         MOCK_RANDOMNESS,
     >::build_from_witness_block(block)
     .unwrap();
+    let circuit = circuit.with_skipped_assignments(skipped);
     let prover = MockProver::run(k, &circuit, instance).unwrap();
     prover.assert_satisfied_par();
     let res = prover.verify_par();
@@ -204,6 +265,50 @@ pub(crate) fn block_2tx() -> GethData {
 
 const TEST_MOCK_RANDOMNESS: u64 = 0x100;
 
+#[cfg(feature = "scroll")]
+#[test]
+fn super_circuit_min_degrees() {
+    let circuits_params = CircuitsParams {
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        ..Default::default()
+    };
+    let block_data = BlockData::new_from_geth_data_with_params(block_1tx(), circuits_params);
+    let mut builder = block_data.new_circuit_input_builder();
+    builder
+        .handle_block(&block_data.eth_block, &block_data.geth_traces)
+        .expect("could not handle block tx");
+    let block =
+        block_convert_with_mock_challenges(&builder.block, &builder.code_db, TEST_MOCK_RANDOMNESS)
+            .unwrap();
+
+    type Circuit = SuperCircuit<Fr, 1, 256, 1, TEST_MOCK_RANDOMNESS>;
+    let degrees = Circuit::min_degrees(&block);
+    assert_eq!(degrees.subcircuits.len(), SUBCIRCUIT_NAMES.len());
+    for &(name, k) in &degrees.subcircuits {
+        assert_eq!(degrees.get(name), Some(k));
+        assert!(k <= degrees.super_circuit, "{name} needs k = {k}");
+    }
+    assert_eq!(degrees.get("unknown"), None);
+
+    let (k, _, _) = Circuit::build_from_witness_block(block).unwrap();
+    assert_eq!(k, degrees.super_circuit);
+}
+
+#[test]
+fn circuit_degrees_params_for() {
+    let degrees = CircuitDegrees {
+        subcircuits: vec![("evm", 6), ("state", 9)],
+        super_circuit: 8,
+    };
+    let params = ParamsKZG::<Bn256>::setup(8, rand::rngs::OsRng);
+
+    assert_eq!(degrees.params_for("evm", &params).unwrap().k(), 6);
+    assert!(degrees.params_for("state", &params).is_none());
+    assert!(degrees.params_for("unknown", &params).is_none());
+    assert_eq!(degrees.params_for_super_circuit(&params).unwrap().k(), 8);
+}
+
 // High memory usage test.  Run in serial with:
 // `cargo test [...] serial_ -- --ignored --test-threads 1`
 #[ignore]
@@ -349,3 +454,37 @@ fn serial_test_super_circuit_2tx_2max_tx() {
         circuits_params,
     );
 }
+
+#[ignore]
+#[cfg(feature = "scroll")]
+#[test]
+fn serial_test_super_circuit_1tx_skipped_assignments() {
+    let block = block_1tx();
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 256;
+    const MAX_INNER_BLOCKS: usize = 1;
+    let circuits_params = CircuitsParams {
+        max_txs: MAX_TXS,
+        max_calldata: MAX_CALLDATA,
+        max_rws: 256,
+        max_copy_rows: 256,
+        max_exp_steps: 256,
+        max_bytecode: 512,
+        max_mpt_rows: 512,
+        max_evm_rows: 0,
+        max_keccak_rows: 0,
+        max_inner_blocks: MAX_INNER_BLOCKS,
+        max_rlp_rows: 500,
+        ..Default::default()
+    };
+    let skipped = SkippedAssignments {
+        keccak: true,
+        sig: true,
+        ..Default::default()
+    };
+    test_super_circuit_skipping::<MAX_TXS, MAX_CALLDATA, MAX_INNER_BLOCKS, TEST_MOCK_RANDOMNESS>(
+        block,
+        circuits_params,
+        skipped,
+    );
+}
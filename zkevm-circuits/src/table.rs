@@ -174,8 +174,18 @@ pub enum TxFieldTag {
     TxHashRLC,
     /// TxHash: Hash of the transaction with the signature
     TxHash,
+    /// Number of the versioned hashes of the blobs of the transaction
+    BlobVersionedHashesLen,
+    /// TxType: the type of the transaction, as a `TxType`
+    TxType,
+    /// Whether the sender of the transaction is provided by the caller instead
+    /// of recovered from its signature
+    UnverifiedSender,
     /// The block number in which this tx is included.
     BlockNumber,
+    /// BlobVersionedHash: the versioned hash of the blob at `index`, zero
+    /// beyond the blobs of the transaction.
+    BlobVersionedHash,
 }
 impl_expr!(TxFieldTag);
 
@@ -398,6 +408,10 @@ pub enum RwTableTag {
     TxLog,
     /// Tx Receipt operation
     TxReceipt,
+    /// Tx Created Account operation
+    TxCreatedAccount,
+    /// Tx Destructed Account operation
+    TxDestructedAccount,
 }
 impl_expr!(RwTableTag);
 
@@ -411,6 +425,8 @@ impl RwTableTag {
                 | RwTableTag::TxRefund
                 | RwTableTag::Account
                 | RwTableTag::AccountStorage
+                | RwTableTag::TxCreatedAccount
+                | RwTableTag::TxDestructedAccount
         )
     }
 }
@@ -658,8 +674,8 @@ impl RwTable {
         n_rows: usize,
         challenges: Value<F>,
     ) -> Result<(), Error> {
-        let (rows, _) = RwMap::table_assignments_prepad(rws, n_rows);
-        for (offset, row) in rows.iter().enumerate() {
+        let (rows, _) = RwMap::table_assignments_prepad_iter(rws, n_rows);
+        for (offset, row) in rows.enumerate() {
             self.assign(region, offset, &row.table_assignment(challenges))?;
         }
         Ok(())
@@ -1185,9 +1201,46 @@ pub enum BlockContextFieldTag {
     /// In a multi-block setup, this variant represents the cumulative number of
     /// txs included up to this block, including the txs in this block.
     CumNumTxs,
+    /// Blob Base Fee field, the base fee per blob gas of EIP-4844. Unlike the
+    /// base fee, it is not in the public input hash, so the PI circuit fixes it
+    /// to the minimum blob base fee.
+    BlobBaseFee,
 }
 impl_expr!(BlockContextFieldTag);
 
+impl BlockContextFieldTag {
+    /// Fields of the rows of each block in the block table, in order, which
+    /// are followed by the history block hashes. The assignments of the block
+    /// table and the row offsets of the fields are derived from it, so adding
+    /// a field to the block table only needs it added here and valued in
+    /// [`BlockContext::field_value`](crate::witness::BlockContext::field_value).
+    pub const BLOCK_TABLE_FIELDS: [Self; 10] = [
+        Self::Coinbase,
+        Self::Timestamp,
+        Self::Number,
+        Self::Difficulty,
+        Self::GasLimit,
+        Self::BaseFee,
+        Self::ChainId,
+        Self::NumTxs,
+        Self::CumNumTxs,
+        Self::BlobBaseFee,
+    ];
+
+    /// Row offset of the field among the rows of its block in the block
+    /// table. Panics if the field is not in [`Self::BLOCK_TABLE_FIELDS`].
+    pub const fn block_table_offset(self) -> usize {
+        let mut offset = 0;
+        while offset < Self::BLOCK_TABLE_FIELDS.len() {
+            if Self::BLOCK_TABLE_FIELDS[offset] as u64 == self as u64 {
+                return offset;
+            }
+            offset += 1;
+        }
+        panic!("not a field of the block table rows of a block")
+    }
+}
+
 impl From<BlockContextFieldTag> for usize {
     fn from(value: BlockContextFieldTag) -> Self {
         value as usize
@@ -1567,6 +1620,10 @@ impl CopyTable {
 
         let mut rw_counter = copy_event.rw_counter_start();
         let mut rwc_inc_left = copy_event.rw_counter_delta();
+        // A copy within a single memory does all its reads before its writes,
+        // so the writer starts after the words of the reader.
+        let mut writer_rw_counter = rw_counter + rwc_inc_left / 2;
+        let mut writer_rwc_inc_left = rwc_inc_left / 2;
 
         let mut reader = CopyThread {
             tag: copy_event.src_type,
@@ -1664,6 +1721,13 @@ impl CopyTable {
 
             let word_index = (step_idx as u64 / 2) % 32;
 
+            let is_memory_copy_writer = copy_event.is_memory_copy() && !is_read_step;
+            let (row_rw_counter, row_rwc_inc_left) = if is_memory_copy_writer {
+                (writer_rw_counter, writer_rwc_inc_left)
+            } else {
+                (rw_counter, rwc_inc_left)
+            };
+
             // is_code
             let is_code = Value::known(copy_step.is_code.map_or(F::zero(), |v| F::from(v)));
 
@@ -1695,8 +1759,8 @@ impl CopyTable {
                         },
                         "rlc_acc",
                     ),
-                    (Value::known(F::from(rw_counter)), "rw_counter"),
-                    (Value::known(F::from(rwc_inc_left)), "rwc_inc_left"),
+                    (Value::known(F::from(row_rw_counter)), "rw_counter"),
+                    (Value::known(F::from(row_rwc_inc_left)), "rwc_inc_left"),
                 ],
                 [
                     (Value::known(F::from(is_last)), "is_last"),
@@ -1724,8 +1788,13 @@ impl CopyTable {
             // Update the RW counter.
             let is_word_end = (step_idx / 2) % 32 == 31;
             if is_word_end && thread.is_rw {
-                rw_counter += 1;
-                rwc_inc_left -= 1;
+                if is_memory_copy_writer {
+                    writer_rw_counter += 1;
+                    writer_rwc_inc_left -= 1;
+                } else {
+                    rw_counter += 1;
+                    rwc_inc_left -= 1;
+                }
             }
         }
         assignments
@@ -2119,8 +2188,8 @@ impl RlpFsmRlpTable {
         challenges: &Challenges<Value<F>>,
     ) -> Result<(), Error> {
         let rows = txs
-            .into_iter()
-            .flat_map(|tx| tx.gen_sm_witness(challenges))
+            .iter()
+            .flat_map(|tx| tx.sm_witness(challenges))
             .filter(|row| row.rlp_table.is_output)
             .map(|row| row.rlp_table)
             .collect::<Vec<_>>();
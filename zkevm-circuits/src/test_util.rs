@@ -83,6 +83,7 @@ pub struct CircuitTestBuilder<const NACC: usize, const NTX: usize> {
     state_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     copy_checks: Box<dyn Fn(MockProver<Fr>, &Vec<usize>, &Vec<usize>)>,
     block_modifiers: Vec<Box<dyn Fn(&mut Block<Fr>)>>,
+    block_checks: Vec<Box<dyn Fn(&Block<Fr>)>>,
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -111,6 +112,7 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
                 ), Ok(()));
             }),
             block_modifiers: vec![],
+            block_checks: vec![],
         }
     }
 
@@ -182,6 +184,15 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
         self.block_modifiers.push(modifier);
         self
     }
+
+    #[allow(clippy::type_complexity)]
+    /// Allows to provide checks of the [`Block`] generated within this
+    /// builder, run after the EVM circuit, e.g. of the exact rw lookups of a
+    /// step with a [`RwTableView`](crate::state_circuit::RwTableView).
+    pub fn block_checks(mut self, check: Box<dyn Fn(&Block<Fr>)>) -> Self {
+        self.block_checks.push(check);
+        self
+    }
 }
 
 impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
@@ -229,6 +240,10 @@ impl<const NACC: usize, const NTX: usize> CircuitTestBuilder<NACC, NTX> {
             self.evm_checks.as_ref()(prover, &active_gate_rows, &active_lookup_rows)
         }
 
+        for check_fn in self.block_checks {
+            check_fn.as_ref()(&block);
+        }
+
         // Run state circuit test
         {
             let rows_needed = StateCircuit::<Fr>::min_num_rows_block(&block).1;
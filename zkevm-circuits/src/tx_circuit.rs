@@ -24,30 +24,30 @@ use crate::{
     witness::{rlp_fsm::Tag, RlpTag, Transaction},
 };
 use bus_mapping::circuit_input_builder::keccak_inputs_sign_verify;
-use eth_types::{sign_types::SignData, Address, Field, ToAddress, ToLittleEndian, ToScalar};
+use eth_types::{sign_types::SignData, Address, Field, ToAddress, ToLittleEndian, ToScalar, H256};
 use gadgets::{
     binary_number::{BinaryNumberChip, BinaryNumberConfig},
     is_equal::{IsEqualChip, IsEqualConfig, IsEqualInstruction},
     util::{and, not, select, sum, Expr},
 };
 use halo2_proofs::{
-    circuit::{Layouter, Region, Value},
-    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Instance, VirtualCells},
     poly::Rotation,
 };
-use log::error;
 use num::Zero;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet},
     iter,
     marker::PhantomData,
 };
 
 use crate::{
     table::TxFieldTag::{
-        BlockNumber, CallData, CallDataGasCost, CallDataLength, CallDataRLC, CalleeAddress,
-        CallerAddress, Gas, GasPrice, IsCreate, Nonce, SigR, SigS, SigV, TxDataGasCost,
-        TxHashLength, TxHashRLC, TxSignHash, TxSignLength, TxSignRLC,
+        BlobVersionedHash, BlobVersionedHashesLen, BlockNumber, CallData, CallDataGasCost,
+        CallDataLength, CallDataRLC, CalleeAddress, CallerAddress, Gas, GasPrice, IsCreate, Nonce,
+        SigR, SigS, SigV, TxDataGasCost, TxHashLength, TxHashRLC, TxSignHash, TxSignLength,
+        TxSignRLC, UnverifiedSender,
     },
     util::is_zero::{IsZeroChip, IsZeroConfig},
 };
@@ -61,25 +61,45 @@ use crate::{
     table::{BlockContextFieldTag::CumNumTxs, TxFieldTag::ChainID},
     util::rlc_be_bytes,
     witness::{
-        Format::{L1MsgHash, TxHashEip155, TxHashPreEip155, TxSignEip155, TxSignPreEip155},
+        Format::{
+            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashEip4844, TxHashPreEip155,
+            TxSignEip155, TxSignEip1559, TxSignEip2930, TxSignEip4844, TxSignPreEip155,
+        },
         RlpTag::{GasCost, Len, Null, RLC},
         Tag::TxType as RLPTxType,
     },
 };
 use eth_types::geth_types::{
     TxType,
-    TxType::{Eip155, L1Msg, PreEip155},
+    TxType::{Eip155, Eip1559, Eip2930, Eip4844, L1Msg, PreEip155, SystemCall},
 };
 use gadgets::comparator::{ComparatorChip, ComparatorConfig, ComparatorInstruction};
 
+/// Max number of blobs of an eip4844 tx, the max blob gas per block over the
+/// gas per blob as of Cancun
+pub const MAX_BLOBS_PER_TX: usize = 6;
 /// Number of rows of one tx occupies in the fixed part of tx table
-pub const TX_LEN: usize = 22;
+pub const TX_LEN: usize = 25 + MAX_BLOBS_PER_TX;
 /// Offset of TxHash tag in the tx table
 pub const TX_HASH_OFFSET: usize = 21;
 /// Offset of ChainID tag in the tx table
 pub const CHAIN_ID_OFFSET: usize = 12;
+/// Offset of TxType tag in the tx table
+pub const TX_TYPE_OFFSET: usize = 23;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Whether the chain id is in the rlp signed by `tx`, which it isn't for the
+/// pre-eip155 txs and the unsigned txs.
+fn is_chain_id_signed(tx: &Transaction) -> bool {
+    !matches!(tx.tx_type, PreEip155 | L1Msg | SystemCall)
+}
+
+/// Whether the gas price is in the rlp signed by `tx`, which it isn't for the
+/// eip1559 and eip4844 txs: they sign their fee caps instead.
+fn is_gas_price_signed(tx: &Transaction) -> bool {
+    !matches!(tx.tx_type, Eip1559 | Eip4844)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum LookupCondition {
     // lookup into tx table
     TxCalldata,
@@ -122,8 +142,12 @@ pub struct TxCircuitConfig<F: Field> {
     is_calldata: Column<Advice>,
     is_caller_address: Column<Advice>,
     is_l1_msg: Column<Advice>,
+    is_system_call: Column<Advice>,
+    is_dynamic_fee: Column<Advice>,
     is_chain_id: Column<Advice>,
-    lookup_conditions: HashMap<LookupCondition, Column<Advice>>,
+    /// Whether a BlobVersionedHash row holds a blob of the tx
+    is_blob: Column<Advice>,
+    lookup_conditions: BTreeMap<LookupCondition, Column<Advice>>,
 
     /// Columns for accumulating call_data_length and call_data_gas_cost
     /// A boolean advice column, which is turned on only for the last byte in
@@ -140,6 +164,14 @@ pub struct TxCircuitConfig<F: Field> {
     /// Cumulative number of txs up to a block
     cum_num_txs: Column<Advice>,
 
+    /// Columns for proving the consumption of the L1 message queue
+    /// Queue index of the next L1 msg at the beginning of the tx.
+    l1_queue_index: Column<Advice>,
+    /// Queue index of the next L1 msg at the end of the tx.
+    l1_queue_index_next: Column<Advice>,
+    /// Queue indices before and after the txs of the chunk.
+    l1_queue_index_pi: Column<Instance>,
+
     /// Address recovered by SignVerifyChip
     sv_address: Column<Advice>,
 
@@ -197,6 +229,11 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         let cum_num_txs = meta.advice_column();
         let is_padding_tx = meta.advice_column();
 
+        // columns for constraining the L1 message queue consumption
+        let l1_queue_index = meta.advice_column();
+        let l1_queue_index_next = meta.advice_column();
+        let l1_queue_index_pi = meta.instance_column();
+
         // columns for accumulating length and gas_cost of call_data
         let is_final = meta.advice_column();
         let calldata_gas_cost_acc = meta.advice_column();
@@ -206,6 +243,9 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
         // booleans to reduce degree
         let is_l1_msg = meta.advice_column();
+        let is_system_call = meta.advice_column();
+        let is_dynamic_fee = meta.advice_column();
+        let is_blob = meta.advice_column();
         let is_calldata = meta.advice_column();
         let is_caller_address = meta.advice_column();
         let is_chain_id = meta.advice_column();
@@ -219,7 +259,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         ]
         .into_iter()
         .map(|condition| (condition, meta.advice_column()))
-        .collect::<HashMap<LookupCondition, Column<Advice>>>();
+        .collect::<BTreeMap<LookupCondition, Column<Advice>>>();
 
         // TODO: add lookup to SignVerify table for sv_address
         let sv_address = meta.advice_column();
@@ -271,8 +311,42 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
         is_tx_tag!(is_hash_rlc, TxHashRLC);
         is_tx_tag!(is_sign_hash, TxSignHash);
         is_tx_tag!(is_hash, TxHash);
+        is_tx_tag!(is_blob_hashes_len, BlobVersionedHashesLen);
+        is_tx_tag!(is_blob_hash, BlobVersionedHash);
+        is_tx_tag!(is_type, TxType);
+        is_tx_tag!(is_unverified_sender, UnverifiedSender);
         is_tx_tag!(is_block_num, BlockNumber);
 
+        // the l1 msgs and the system calls carry no signature and are hashed
+        // as l1 msgs, but only the l1 msgs consume the l1 message queue
+        let is_unsigned = |meta: &mut VirtualCells<F>| {
+            meta.query_advice(is_l1_msg, Rotation::cur())
+                + meta.query_advice(is_system_call, Rotation::cur())
+        };
+
+        // the chain id is signed by all the txs but the pre-eip155 txs and the
+        // unsigned txs
+        let is_chain_id_signed = |meta: &mut VirtualCells<F>| {
+            meta.query_advice(is_chain_id, Rotation::cur())
+                * not::expr(sum::expr([
+                    tx_type_bits.value_equals(PreEip155, Rotation::cur())(meta),
+                    is_unsigned(meta),
+                ]))
+        };
+
+        // the gas price is signed by all the txs but the eip1559 and eip4844 txs,
+        // which sign their fee caps instead
+        // TODO: constrain the gas price of an eip1559 or eip4844 tx by its fee
+        // caps and the base fee
+        let is_gas_price_signed = |meta: &mut VirtualCells<F>| {
+            is_gas_price(meta) * not::expr(meta.query_advice(is_dynamic_fee, Rotation::cur()))
+        };
+
+        // the blob versioned hashes of the tx are signed, zero beyond its blobs
+        let is_blob_hash_signed = |meta: &mut VirtualCells<F>| {
+            is_blob_hash(meta) * meta.query_advice(is_blob, Rotation::cur())
+        };
+
         // testing if value is zero for tags
         let value_is_zero = IsZeroChip::configure(
             meta,
@@ -334,7 +408,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             let rlp_tag_map: Vec<(Expression<F>, RlpTag)> = vec![
                 (is_nonce(meta), Tag::Nonce.into()),
-                (is_gas_price(meta), Tag::GasPrice.into()),
+                (is_gas_price_signed(meta), Tag::GasPrice.into()),
                 (is_gas(meta), Tag::Gas.into()),
                 (is_to(meta), Tag::To.into()),
                 (is_value(meta), Tag::Value.into()),
@@ -348,6 +422,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 (is_hash_rlc(meta), RLC),
                 (is_caller_addr(meta), Tag::Sender.into()),
                 (is_tx_gas_cost(meta), GasCost),
+                (is_blob_hash_signed(meta), Tag::BlobVersionedHash.into()),
                 // tx tags which correspond to Null
                 (is_null(meta), Null),
                 (is_create(meta), Null),
@@ -356,8 +431,14 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 (is_sign_hash(meta), Null),
                 (is_hash(meta), Null),
                 (is_data(meta), Null),
+                (is_blob_hashes_len(meta), Null),
+                (is_type(meta), Null),
+                (is_unverified_sender(meta), Null),
                 (is_block_num(meta), Null),
-                (is_chain_id_expr(meta), Null),
+                (is_chain_id_signed(meta), Tag::ChainId.into()),
+                (is_chain_id_expr(meta) - is_chain_id_signed(meta), Null),
+                (is_gas_price(meta) - is_gas_price_signed(meta), Null),
+                (is_blob_hash(meta) - is_blob_hash_signed(meta), Null),
             ];
 
             cb.require_boolean(
@@ -371,7 +452,11 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 vec![
                     usize::from(PreEip155).expr(),
                     usize::from(Eip155).expr(),
+                    usize::from(Eip1559).expr(),
+                    usize::from(Eip2930).expr(),
+                    usize::from(Eip4844).expr(),
                     usize::from(L1Msg).expr(),
+                    usize::from(SystemCall).expr(),
                 ],
             );
 
@@ -392,6 +477,21 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 );
             });
 
+            cb.condition(is_type(meta), |cb| {
+                cb.require_equal(
+                    "TxType.value == tx_type",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                    meta.query_advice(tx_type, Rotation::cur()),
+                );
+            });
+
+            cb.condition(is_unverified_sender(meta), |cb| {
+                cb.require_boolean(
+                    "UnverifiedSender.value is boolean",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                );
+            });
+
             let is_none_expr = meta.query_advice(is_none, Rotation::cur());
             // is_none == true
             cb.condition(is_none_expr.expr(), |cb| {
@@ -486,6 +586,55 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
+        // the BlobVersionedHash rows follow UnverifiedSender, and their blobs
+        // come first: only the eip4844 txs carry blobs, at least one of them
+        // TODO: the order and the number of the hashes aren't bound to the
+        // rlp, whose table has no index of the items of a vector
+        meta.create_gate("blob versioned hashes", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            // the offset between BlobVersionedHashesLen and the first
+            // BlobVersionedHash
+            let offset = usize::from(UnverifiedSender) - usize::from(BlobVersionedHashesLen) + 1;
+            let is_blobs = (0..MAX_BLOBS_PER_TX)
+                .map(|i| meta.query_advice(is_blob, Rotation((offset + i) as i32)))
+                .collect::<Vec<_>>();
+
+            cb.condition(is_blob_hashes_len(meta), |cb| {
+                cb.require_equal(
+                    "BlobVersionedHashesLen.value == number of blobs",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                    sum::expr(&is_blobs),
+                );
+                cb.require_equal(
+                    "the first BlobVersionedHash holds a blob iff tx_type == Eip4844",
+                    is_blobs[0].expr(),
+                    tx_type_bits.value_equals(Eip4844, Rotation::cur())(meta),
+                );
+                for (i, is_blob) in is_blobs.iter().enumerate() {
+                    let rotation = Rotation((offset + i) as i32);
+                    cb.require_boolean("is_blob is boolean", is_blob.expr());
+                    cb.require_equal(
+                        "BlobVersionedHash.index == i",
+                        meta.query_advice(tx_table.index, rotation),
+                        i.expr(),
+                    );
+                    cb.require_zero(
+                        "BlobVersionedHash.value == 0 beyond the blobs",
+                        not::expr(is_blob.expr()) * meta.query_advice(tx_table.value, rotation),
+                    );
+                    if let Some(is_blob_next) = is_blobs.get(i + 1) {
+                        cb.require_zero(
+                            "the blobs come first",
+                            is_blob_next.expr() * not::expr(is_blob.expr()),
+                        );
+                    }
+                }
+            });
+
+            cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
+        });
+
         meta.create_gate("is_l1_msg", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
@@ -494,6 +643,17 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 meta.query_advice(is_l1_msg, Rotation::cur()),
                 tx_type_bits.value_equals(L1Msg, Rotation::cur())(meta),
             );
+            cb.require_equal(
+                "is_system_call = (tx_type == SystemCall)",
+                meta.query_advice(is_system_call, Rotation::cur()),
+                tx_type_bits.value_equals(SystemCall, Rotation::cur())(meta),
+            );
+            cb.require_equal(
+                "is_dynamic_fee = (tx_type == Eip1559 || tx_type == Eip4844)",
+                meta.query_advice(is_dynamic_fee, Rotation::cur()),
+                tx_type_bits.value_equals(Eip1559, Rotation::cur())(meta)
+                    + tx_type_bits.value_equals(Eip4844, Rotation::cur())(meta),
+            );
 
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
@@ -521,11 +681,13 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             let is_tag_in_tx_sign = sum::expr([
                 is_nonce(meta),
-                is_gas_price(meta),
+                is_gas_price_signed(meta),
                 is_gas(meta),
                 is_to(meta),
                 is_value(meta),
                 is_data_rlc(meta),
+                is_chain_id_signed(meta),
+                is_blob_hash_signed(meta),
                 is_sign_length(meta),
                 is_sign_rlc(meta),
             ]);
@@ -541,7 +703,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enable, Rotation::cur()),
-                not::expr(meta.query_advice(is_l1_msg, Rotation::cur())),
+                not::expr(is_unsigned(meta)),
             ]))
         });
 
@@ -550,7 +712,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             let is_tag_in_tx_hash = sum::expr([
                 is_nonce(meta),
-                is_gas_price(meta),
+                is_gas_price_signed(meta),
                 is_gas(meta),
                 is_to(meta),
                 is_value(meta),
@@ -559,6 +721,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 is_sig_v(meta),
                 is_sig_r(meta),
                 is_sig_s(meta),
+                is_blob_hash_signed(meta),
                 is_hash_length(meta),
                 is_hash_rlc(meta),
             ]);
@@ -574,7 +737,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enable, Rotation::cur()),
-                not::expr(meta.query_advice(is_l1_msg, Rotation::cur())),
+                not::expr(is_unsigned(meta)),
             ]))
         });
 
@@ -602,7 +765,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enable, Rotation::cur()),
-                meta.query_advice(is_l1_msg, Rotation::cur()),
+                is_unsigned(meta),
             ]))
         });
 
@@ -610,10 +773,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             let mut cb = BaseConstraintBuilder::default();
 
             let is_tag_sign_or_hash = sum::expr([
-                and::expr([
-                    is_sign_length(meta),
-                    not::expr(meta.query_advice(is_l1_msg, Rotation::cur())),
-                ]),
+                and::expr([is_sign_length(meta), not::expr(is_unsigned(meta))]),
                 is_hash_length(meta),
             ]);
             cb.require_equal(
@@ -636,6 +796,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             is_final,
             is_chain_id,
             is_l1_msg,
+            is_system_call,
             sv_address,
             calldata_gas_cost_acc,
             tx_table.clone(),
@@ -644,7 +805,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             sig_table,
         );
 
-        meta.create_gate("tx_gas_cost == 0 for L1 msg", |meta| {
+        meta.create_gate("tx_gas_cost == 0 for unsigned tx", |meta| {
             let mut cb = BaseConstraintBuilder::default();
 
             cb.condition(is_tx_gas_cost(meta), |cb| {
@@ -656,7 +817,7 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
 
             cb.gate(and::expr([
                 meta.query_fixed(q_enable, Rotation::cur()),
-                meta.query_advice(is_l1_msg, Rotation::cur()),
+                is_unsigned(meta),
             ]))
         });
 
@@ -667,8 +828,9 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             let is_tag_caller_addr = is_caller_addr(meta);
             let mut cb = BaseConstraintBuilder::default();
 
-            // the offset between CallerAddress and BlockNumber
-            let offset = usize::from(BlockNumber) - usize::from(CallerAddress);
+            // the offset between CallerAddress and BlockNumber, which follows
+            // the BlobVersionedHash rows
+            let offset = usize::from(BlockNumber) - usize::from(CallerAddress) + MAX_BLOBS_PER_TX;
             // if tag == CallerAddress
             cb.condition(is_tag_caller_addr.expr(), |cb| {
                 cb.require_equal(
@@ -722,6 +884,68 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 .collect::<Vec<_>>()
         });
 
+        ////////////////////////////////////////////////////////////////////////
+        ///////////  constraints on L1 message queue consumption  //////////////
+        ////////////////////////////////////////////////////////////////////////
+        meta.enable_equality(l1_queue_index);
+        meta.enable_equality(l1_queue_index_next);
+        meta.enable_equality(l1_queue_index_pi);
+
+        meta.create_gate("l1 msg queue index", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            let is_l1_msg = meta.query_advice(is_l1_msg, Rotation::cur());
+            let queue_index = meta.query_advice(l1_queue_index, Rotation::cur());
+
+            // each L1 msg pops one entry from the queue
+            cb.require_equal(
+                "l1_queue_index_next = l1_queue_index + is_l1_msg",
+                meta.query_advice(l1_queue_index_next, Rotation::cur()),
+                queue_index.expr() + is_l1_msg.expr(),
+            );
+            // the nonce of a L1 msg is its index in the queue
+            cb.condition(and::expr([is_nonce(meta), is_l1_msg]), |cb| {
+                cb.require_equal(
+                    "L1 msg nonce == l1_queue_index",
+                    meta.query_advice(tx_table.value, Rotation::cur()),
+                    queue_index.expr(),
+                );
+            });
+
+            cb.gate(and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                not::expr(meta.query_advice(is_calldata, Rotation::cur())),
+            ]))
+        });
+
+        meta.create_gate("l1 msg queue index transition", |meta| {
+            let mut cb = BaseConstraintBuilder::default();
+
+            // the next tx continues from the queue index left by this one
+            cb.condition(tag_bits.value_equals(Nonce, Rotation::next())(meta), |cb| {
+                cb.require_equal(
+                    "l1_queue_index' = l1_queue_index_next",
+                    meta.query_advice(l1_queue_index, Rotation::next()),
+                    meta.query_advice(l1_queue_index_next, Rotation::cur()),
+                );
+            });
+            cb.condition(
+                not::expr(tag_bits.value_equals(Nonce, Rotation::next())(meta)),
+                |cb| {
+                    cb.require_equal(
+                        "l1_queue_index' = l1_queue_index",
+                        meta.query_advice(l1_queue_index, Rotation::next()),
+                        meta.query_advice(l1_queue_index, Rotation::cur()),
+                    );
+                },
+            );
+
+            cb.gate(and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                not::expr(meta.query_advice(is_calldata, Rotation::next())),
+            ]))
+        });
+
         ////////////////////////////////////////////////////////////////////////
         ///////////  CallData length and gas_cost calculation  /////////////////
         ////////////////////////////////////////////////////////////////////////
@@ -826,11 +1050,12 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 },
             );
 
-            //  3. l1 msg: v == 0
+            //  3. l1 msg and system call: v == 0
             cb.condition(
                 and::expr([
                     is_chain_id.expr(),
-                    tx_type_bits.value_equals(L1Msg, Rotation::cur())(meta),
+                    tx_type_bits.value_equals(L1Msg, Rotation::cur())(meta)
+                        + tx_type_bits.value_equals(SystemCall, Rotation::cur())(meta),
                 ]),
                 |cb| {
                     let v = meta.query_advice(tx_table.value, Rotation::next());
@@ -838,18 +1063,34 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 },
             );
 
-            // TODO:
-            //  4. eip1559 tx: v Є {0, 1}
-            //  5. eip2930 tx: v Є {0, 1}
+            //  4. eip1559, eip2930 and eip4844 tx: v Є {0, 1}, the chain id being
+            //     signed in the rlp instead
+            cb.condition(
+                and::expr([
+                    is_chain_id.expr(),
+                    sum::expr([
+                        tx_type_bits.value_equals(Eip1559, Rotation::cur())(meta),
+                        tx_type_bits.value_equals(Eip2930, Rotation::cur())(meta),
+                        tx_type_bits.value_equals(Eip4844, Rotation::cur())(meta),
+                    ]),
+                ]),
+                |cb| {
+                    let v = meta.query_advice(tx_table.value, Rotation::next());
+                    cb.require_boolean("V Є {0, 1}", v);
+                },
+            );
 
             cb.gate(meta.query_fixed(q_enable, Rotation::cur()))
         });
 
         meta.create_gate(
-            "caller address == sv_address if it's not zero and tx_type != L1Msg",
+            "caller address == sv_address if it's not zero and tx is signed",
             |meta| {
                 let mut cb = BaseConstraintBuilder::default();
 
+                // the offset between CallerAddress and UnverifiedSender
+                let offset = usize::from(UnverifiedSender) - usize::from(CallerAddress);
+
                 cb.condition(not::expr(value_is_zero.expr(Rotation::cur())(meta)), |cb| {
                     cb.require_equal(
                         "caller address == sv_address",
@@ -861,7 +1102,8 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
                 cb.gate(and::expr([
                     meta.query_fixed(q_enable, Rotation::cur()),
                     meta.query_advice(is_caller_address, Rotation::cur()),
-                    not::expr(meta.query_advice(is_l1_msg, Rotation::cur())),
+                    not::expr(is_unsigned(meta)),
+                    not::expr(meta.query_advice(tx_table.value, Rotation(offset as i32))),
                 ]))
             },
         );
@@ -884,9 +1126,15 @@ impl<F: Field> SubCircuitConfig<F> for TxCircuitConfig<F> {
             tx_id_cmp_cum_num_txs,
             cum_num_txs,
             is_padding_tx,
+            l1_queue_index,
+            l1_queue_index_next,
+            l1_queue_index_pi,
             lookup_conditions,
             is_l1_msg,
+            is_system_call,
+            is_dynamic_fee,
             is_chain_id,
+            is_blob,
             is_final,
             calldata_gas_cost_acc,
             sv_address,
@@ -909,10 +1157,11 @@ impl<F: Field> TxCircuitConfig<F> {
         rlp_tag: Column<Advice>,
         tx_type_bits: BinaryNumberConfig<TxType, 3>,
         is_none: Column<Advice>,
-        lookup_conditions: &HashMap<LookupCondition, Column<Advice>>,
+        lookup_conditions: &BTreeMap<LookupCondition, Column<Advice>>,
         is_final: Column<Advice>,
         is_chain_id: Column<Advice>,
         is_l1_msg_col: Column<Advice>,
+        is_system_call_col: Column<Advice>,
         sv_address: Column<Advice>,
         calldata_gas_cost_acc: Column<Advice>,
         tx_table: TxTable,
@@ -1004,12 +1253,20 @@ impl<F: Field> TxCircuitConfig<F> {
         is_tx_type!(is_pre_eip155, PreEip155);
         is_tx_type!(is_eip155, Eip155);
         is_tx_type!(is_l1_msg, L1Msg);
+        is_tx_type!(is_eip1559, Eip1559);
+        is_tx_type!(is_eip2930, Eip2930);
+        is_tx_type!(is_eip4844, Eip4844);
+        is_tx_type!(is_system_call, SystemCall);
 
-        // lookup tx type in RLP table for L1Msg only
+        // lookup tx type in RLP table for the txs hashed as L1Msg only: 0x7e
+        // for the L1 msgs and 0x7d for the pseudo-txs of system calls
         meta.lookup_any("lookup tx type in RLP table", |meta| {
-            let enable = and::expr([meta.query_fixed(q_enable, Rotation::cur()), is_l1_msg(meta)]);
+            let enable = and::expr([
+                meta.query_fixed(q_enable, Rotation::cur()),
+                is_l1_msg(meta) + is_system_call(meta),
+            ]);
             let hash_format = L1MsgHash.expr();
-            let tag_value = 0x7E.expr();
+            let tag_value = is_l1_msg(meta) * 0x7E.expr() + is_system_call(meta) * 0x7D.expr();
 
             let input_exprs = vec![
                 1.expr(), // q_enable = true
@@ -1029,6 +1286,42 @@ impl<F: Field> TxCircuitConfig<F> {
                 .collect()
         });
 
+        // lookup the EIP-2718 type of the typed txs in RLP table, both in the rlp
+        // they sign and in the one they are hashed by
+        for (tx_type, tx_type_byte, formats) in [
+            (Eip2930, 1u64, [TxSignEip2930, TxHashEip2930]),
+            (Eip1559, 2, [TxSignEip1559, TxHashEip1559]),
+            (Eip4844, 3, [TxSignEip4844, TxHashEip4844]),
+        ] {
+            for format in formats {
+                meta.lookup_any("lookup typed tx type in RLP table", |meta| {
+                    // once per tx, on its ChainID row
+                    let enable = and::expr([
+                        meta.query_fixed(q_enable, Rotation::cur()),
+                        meta.query_advice(is_chain_id, Rotation::cur()),
+                        tx_type_bits.value_equals(tx_type, Rotation::cur())(meta),
+                    ]);
+
+                    let input_exprs = vec![
+                        1.expr(), // q_enable = true
+                        meta.query_advice(tx_table.tx_id, Rotation::cur()),
+                        format.expr(),
+                        RLPTxType.expr(),
+                        tx_type_byte.expr(),
+                        1.expr(), // is_output = true
+                        0.expr(), // is_none = false
+                    ];
+                    assert_eq!(input_exprs.len(), rlp_table.table_exprs(meta).len());
+
+                    input_exprs
+                        .into_iter()
+                        .zip(rlp_table.table_exprs(meta).into_iter())
+                        .map(|(input, table)| (enable.expr() * input, table))
+                        .collect()
+                });
+            }
+        }
+
         // lookup tx tag in RLP table for signing.
         meta.lookup_any("lookup tx tag in RLP Table for signing", |meta| {
             let enable = and::expr([
@@ -1041,7 +1334,10 @@ impl<F: Field> TxCircuitConfig<F> {
             let rlp_tag = meta.query_advice(rlp_tag, Rotation::cur());
             let is_none = meta.query_advice(is_none, Rotation::cur());
             let sign_format = is_pre_eip155(meta) * TxSignPreEip155.expr()
-                + is_eip155(meta) * TxSignEip155.expr();
+                + is_eip155(meta) * TxSignEip155.expr()
+                + is_eip2930(meta) * TxSignEip2930.expr()
+                + is_eip1559(meta) * TxSignEip1559.expr()
+                + is_eip4844(meta) * TxSignEip4844.expr();
 
             // q_enable, tx_id, format, rlp_tag, tag_value, is_output, is_none
             vec![
@@ -1078,7 +1374,10 @@ impl<F: Field> TxCircuitConfig<F> {
             let is_none = meta.query_advice(is_none, Rotation::cur());
             let hash_format = is_pre_eip155(meta) * TxHashPreEip155.expr()
                 + is_eip155(meta) * TxHashEip155.expr()
-                + is_l1_msg(meta) * L1MsgHash.expr();
+                + is_eip2930(meta) * TxHashEip2930.expr()
+                + is_eip1559(meta) * TxHashEip1559.expr()
+                + is_eip4844(meta) * TxHashEip4844.expr()
+                + (is_l1_msg(meta) + is_system_call(meta)) * L1MsgHash.expr();
 
             vec![
                 1.expr(), // q_enable = true
@@ -1099,9 +1398,20 @@ impl<F: Field> TxCircuitConfig<F> {
         /////////////////    Sig table lookups     //////////////////////
         ///////////////// //////////////////////////////////////////////////
         meta.lookup_any("Sig table lookup", |meta| {
+            // the offset between ChainID and UnverifiedSender
+            let unverified_sender_offset = usize::from(UnverifiedSender) - usize::from(ChainID);
             let enabled = and::expr([
-                // use is_l1_msg_col instead of is_l1_msg(meta) because it has lower degree
-                not::expr(meta.query_advice(is_l1_msg_col, Rotation::cur())),
+                // use the columns instead of is_l1_msg(meta) and is_system_call(meta)
+                // because they have lower degree
+                not::expr(
+                    meta.query_advice(is_l1_msg_col, Rotation::cur())
+                        + meta.query_advice(is_system_call_col, Rotation::cur()),
+                ),
+                // the sender of a tx marked as unverified is not recovered from
+                // its signature
+                not::expr(
+                    meta.query_advice(tx_table.value, Rotation(unverified_sender_offset as i32)),
+                ),
                 // lookup to sig table on the ChainID row because we have an indicator of degree 1
                 // for ChainID and ChainID is not far from (msg_hash_rlc, sig_v,
                 // ...)
@@ -1116,7 +1426,8 @@ impl<F: Field> TxCircuitConfig<F> {
             let sv_address = meta.query_advice(sv_address, Rotation::cur());
 
             let v = is_eip155(meta) * (sig_v.expr() - 2.expr() * chain_id - 35.expr())
-                + is_pre_eip155(meta) * (sig_v.expr() - 27.expr());
+                + is_pre_eip155(meta) * (sig_v.expr() - 27.expr())
+                + (is_eip1559(meta) + is_eip2930(meta) + is_eip4844(meta)) * sig_v.expr();
 
             let input_exprs = vec![
                 1.expr(),     // q_enable = true
@@ -1239,7 +1550,11 @@ impl<F: Field> TxCircuitConfig<F> {
 
         // assign to lookup condition columns
         let is_l1_msg = tx.map(|tx| tx.tx_type.is_l1_msg()).unwrap_or(false);
-        let mut conditions = HashMap::<LookupCondition, Value<F>>::new();
+        let is_system_call = tx.map(|tx| tx.tx_type.is_system_call()).unwrap_or(false);
+        let is_unsigned = is_l1_msg || is_system_call;
+        let is_dynamic_fee = tx.map_or(false, |tx| matches!(tx.tx_type, Eip1559 | Eip4844));
+        let is_blob = tag == BlobVersionedHash && rlp_tag.is_some();
+        let mut conditions = BTreeMap::<LookupCondition, Value<F>>::new();
         if tag == CallData {
             conditions = vec![
                 (LookupCondition::TxCalldata, Value::known(F::zero())),
@@ -1264,7 +1579,6 @@ impl<F: Field> TxCircuitConfig<F> {
             conditions.insert(LookupCondition::RlpSignTag, {
                 let sign_set = [
                     Nonce,
-                    GasPrice,
                     Gas,
                     CalleeAddress,
                     TxFieldTag::Value,
@@ -1273,13 +1587,17 @@ impl<F: Field> TxCircuitConfig<F> {
                     TxSignRLC,
                 ];
                 let is_tag_in_set = sign_set.into_iter().filter(|_tag| tag == *_tag).count() == 1;
-                Value::known(F::from((is_tag_in_set && !is_l1_msg) as u64))
+                let is_signed_chain_id = tag == ChainID && tx.map_or(false, is_chain_id_signed);
+                let is_signed_gas_price = tag == GasPrice && tx.map_or(true, is_gas_price_signed);
+                Value::known(F::from(
+                    ((is_tag_in_set || is_signed_chain_id || is_signed_gas_price || is_blob)
+                        && !is_unsigned) as u64,
+                ))
             });
             // lookup to RLP table for hashing (non L1 msg)
             conditions.insert(LookupCondition::RlpHashTag, {
                 let hash_set = [
                     Nonce,
-                    GasPrice,
                     Gas,
                     CalleeAddress,
                     TxFieldTag::Value,
@@ -1292,7 +1610,10 @@ impl<F: Field> TxCircuitConfig<F> {
                     TxHashRLC,
                 ];
                 let is_tag_in_set = hash_set.into_iter().filter(|_tag| tag == *_tag).count() == 1;
-                Value::known(F::from((!is_l1_msg && is_tag_in_set) as u64))
+                let is_signed_gas_price = tag == GasPrice && tx.map_or(true, is_gas_price_signed);
+                Value::known(F::from(
+                    (!is_unsigned && (is_tag_in_set || is_signed_gas_price || is_blob)) as u64,
+                ))
             });
             // lookup to RLP table for hashing (L1 msg)
             conditions.insert(LookupCondition::L1MsgHash, {
@@ -1308,11 +1629,11 @@ impl<F: Field> TxCircuitConfig<F> {
                 ];
 
                 let is_tag_in_set = hash_set.into_iter().filter(|_tag| tag == *_tag).count() == 1;
-                Value::known(F::from((is_l1_msg && is_tag_in_set) as u64))
+                Value::known(F::from((is_unsigned && is_tag_in_set) as u64))
             });
             // lookup to Keccak table for tx_sign_hash and tx_hash
             conditions.insert(LookupCondition::Keccak, {
-                let case1 = (tag == TxSignLength) && !is_l1_msg;
+                let case1 = (tag == TxSignLength) && !is_unsigned;
                 let case2 = tag == TxHashLength;
                 Value::known(F::from((case1 || case2) as u64))
             });
@@ -1333,6 +1654,24 @@ impl<F: Field> TxCircuitConfig<F> {
             *offset,
             || Value::known(F::from(is_l1_msg as u64)),
         )?;
+        region.assign_advice(
+            || "is_system_call",
+            self.is_system_call,
+            *offset,
+            || Value::known(F::from(is_system_call as u64)),
+        )?;
+        region.assign_advice(
+            || "is_dynamic_fee",
+            self.is_dynamic_fee,
+            *offset,
+            || Value::known(F::from(is_dynamic_fee as u64)),
+        )?;
+        region.assign_advice(
+            || "is_blob",
+            self.is_blob,
+            *offset,
+            || Value::known(F::from(is_blob as u64)),
+        )?;
         region.assign_advice(
             || "is_tag_block_num",
             self.is_tag_block_num,
@@ -1413,6 +1752,31 @@ impl<F: Field> TxCircuitConfig<F> {
         Ok(())
     }
 
+    /// Assigns the L1 message queue indices of a tx row and returns their
+    /// assigned cells.
+    fn assign_l1_queue_index(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        queue_index: u64,
+        queue_index_next: u64,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        Ok((
+            region.assign_advice(
+                || "l1_queue_index",
+                self.l1_queue_index,
+                offset,
+                || Value::known(F::from(queue_index)),
+            )?,
+            region.assign_advice(
+                || "l1_queue_index_next",
+                self.l1_queue_index_next,
+                offset,
+                || Value::known(F::from(queue_index_next)),
+            )?,
+        ))
+    }
+
     fn assign_calldata_zeros(
         &self,
         region: &mut Region<'_, F>,
@@ -1508,6 +1872,14 @@ pub struct TxCircuit<F: Field> {
     pub txs: Vec<Transaction>,
     /// Chain ID
     pub chain_id: u64,
+    /// Index in the L1 message queue of the first L1 msg that may be
+    /// included by the txs, continued from the previous chunk.
+    pub l1_queue_index: u64,
+    /// Hashes of the entries of the L1 message queue from `l1_queue_index`
+    /// on, as enqueued on L1. The L1 msgs of the txs must match them: their
+    /// hashes are committed to by the data hash, which the rollup contract
+    /// rebuilds from the queue.
+    pub l1_queue_hashes: Vec<H256>,
     /// Size
     pub size: usize,
     _marker: PhantomData<F>,
@@ -1515,12 +1887,20 @@ pub struct TxCircuit<F: Field> {
 
 impl<F: Field> TxCircuit<F> {
     /// Return a new TxCircuit
-    pub fn new(max_txs: usize, max_calldata: usize, chain_id: u64, txs: Vec<Transaction>) -> Self {
+    pub fn new(
+        max_txs: usize,
+        max_calldata: usize,
+        chain_id: u64,
+        l1_queue_index: u64,
+        l1_queue_hashes: Vec<H256>,
+        txs: Vec<Transaction>,
+    ) -> Self {
         log::info!(
-            "TxCircuit::new(max_txs = {}, max_calldata = {}, chain_id = {})",
+            "TxCircuit::new(max_txs = {}, max_calldata = {}, chain_id = {}, l1_queue_index = {})",
             max_txs,
             max_calldata,
-            chain_id
+            chain_id,
+            l1_queue_index
         );
         debug_assert!(txs.len() <= max_txs);
 
@@ -1530,10 +1910,21 @@ impl<F: Field> TxCircuit<F> {
             txs,
             size: Self::min_num_rows(max_txs, max_calldata),
             chain_id,
+            l1_queue_index,
+            l1_queue_hashes,
             _marker: PhantomData::default(),
         }
     }
 
+    /// Return the indices of the L1 message queue before and after the txs:
+    /// the first L1 msg included must be at the former, the L1 msgs must be
+    /// consecutive entries of the queue, and the next chunk continues from
+    /// the latter.
+    pub fn l1_queue_indices(&self) -> (u64, u64) {
+        let num_l1_msgs = self.txs.iter().filter(|tx| tx.tx_type.is_l1_msg()).count() as u64;
+        (self.l1_queue_index, self.l1_queue_index + num_l1_msgs)
+    }
+
     /// Returned data contains both the tx hash and sig hash
     fn keccak_inputs(&self) -> Result<Vec<Vec<u8>>, Error> {
         let mut inputs = Vec::new();
@@ -1551,22 +1942,7 @@ impl<F: Field> TxCircuit<F> {
             .collect::<Vec<Vec<u8>>>();
         inputs.extend_from_slice(&hash_datas);
 
-        let sign_datas: Vec<SignData> = self
-            .txs
-            .iter()
-            .chain(iter::once(&padding_tx))
-            .enumerate()
-            .map(|(_, tx)| {
-                if tx.tx_type.is_l1_msg() {
-                    Ok(SignData::default())
-                } else {
-                    tx.sign_data().map_err(|e| {
-                        error!("keccak_inputs_tx_circuit error: {:?}", e);
-                        Error::Synthesis
-                    })
-                }
-            })
-            .collect::<Result<Vec<SignData>, Error>>()?;
+        let sign_datas = get_sign_data(&self.txs, self.txs.len() + 1, self.chain_id as usize)?;
         // Keccak inputs from SignVerify Chip
         let sign_verify_inputs = keccak_inputs_sign_verify(&sign_datas);
         inputs.extend_from_slice(&sign_verify_inputs);
@@ -1641,7 +2017,7 @@ impl<F: Field> TxCircuit<F> {
         sign_datas: Vec<SignData>,
         padding_txs: &[Transaction],
     ) -> Result<(), Error> {
-        let last_off = layouter.assign_region(
+        let (last_off, l1_queue_index_cells) = layouter.assign_region(
             || "tx table aux",
             |mut region| {
                 let mut offset = 0;
@@ -1668,6 +2044,13 @@ impl<F: Field> TxCircuit<F> {
                     None,
                     None,
                 )?;
+                let mut l1_queue_index = self.l1_queue_index;
+                let mut l1_queue_index_cells = config.assign_l1_queue_index(
+                    &mut region,
+                    offset - 1,
+                    l1_queue_index,
+                    l1_queue_index,
+                )?;
 
                 // Assign all tx fields except for call data
                 for (i, sign_data) in sigs.iter().enumerate() {
@@ -1678,6 +2061,7 @@ impl<F: Field> TxCircuit<F> {
                     };
                     let rlp_unsigned_tx_be_bytes = tx.rlp_unsigned.clone();
                     let rlp_signed_tx_be_bytes = tx.rlp_signed.clone();
+                    let l1_queue_index_next = l1_queue_index + tx.tx_type.is_l1_msg() as u64;
                     if i < self.txs.len() {
                         cum_num_txs = self
                             .txs
@@ -1699,6 +2083,18 @@ impl<F: Field> TxCircuit<F> {
                                 .fold(F::zero(), |acc, byte| acc * rand + F::from(byte as u64))
                         })
                     };
+                    // a row per blob a tx may carry, zero beyond the blobs of the tx
+                    let blob_rows = (0..MAX_BLOBS_PER_TX).map(|index| {
+                        let blob_hash = tx.blob_versioned_hashes.get(index);
+                        (
+                            BlobVersionedHash,
+                            blob_hash.map(|_| Tag::BlobVersionedHash.into()),
+                            blob_hash.map(|_| false),
+                            blob_hash.map_or(Value::known(F::zero()), |hash| {
+                                rlc_be_bytes(hash.as_bytes(), challenges.evm_word())
+                            }),
+                        )
+                    });
                     log::debug!("calldata len: {}", tx.call_data.len());
                     for (tag, rlp_tag, is_none, value) in [
                         // need to be in same order as that tx table load function uses
@@ -1716,8 +2112,8 @@ impl<F: Field> TxCircuit<F> {
                         ),
                         (
                             GasPrice,
-                            Some(Tag::GasPrice.into()),
-                            Some(tx.gas_price.is_zero()),
+                            is_gas_price_signed(tx).then_some(Tag::GasPrice.into()),
+                            is_gas_price_signed(tx).then_some(tx.gas_price.is_zero()),
                             challenges
                                 .evm_word()
                                 .map(|challenge| rlc(tx.gas_price.to_le_bytes(), challenge)),
@@ -1777,7 +2173,12 @@ impl<F: Field> TxCircuit<F> {
                             None,
                             Value::known(F::from(tx.tx_data_gas_cost)),
                         ),
-                        (ChainID, None, None, Value::known(F::from(tx.chain_id))),
+                        (
+                            ChainID,
+                            is_chain_id_signed(tx).then_some(Tag::ChainId.into()),
+                            is_chain_id_signed(tx).then_some(tx.chain_id == 0),
+                            Value::known(F::from(tx.chain_id)),
+                        ),
                         (
                             SigV,
                             Some(Tag::SigV.into()),
@@ -1847,12 +2248,32 @@ impl<F: Field> TxCircuit<F> {
                             }),
                         ),
                         (
-                            BlockNumber,
+                            BlobVersionedHashesLen,
+                            None,
+                            None,
+                            Value::known(F::from(tx.blob_versioned_hashes.len() as u64)),
+                        ),
+                        (
+                            TxFieldTag::TxType,
                             None,
                             None,
-                            Value::known(F::from(tx.block_number)),
+                            Value::known(F::from(tx.tx_type as u64)),
                         ),
-                    ] {
+                        (
+                            UnverifiedSender,
+                            None,
+                            None,
+                            Value::known(F::from(tx.unverified_sender as u64)),
+                        ),
+                    ]
+                    .into_iter()
+                    .chain(blob_rows)
+                    .chain(iter::once((
+                        BlockNumber,
+                        None,
+                        None,
+                        Value::known(F::from(tx.block_number)),
+                    ))) {
                         let tx_id_next = match tag {
                             BlockNumber => {
                                 if i == sigs.len() - 1 {
@@ -1890,7 +2311,15 @@ impl<F: Field> TxCircuit<F> {
                             offset - 1,
                             || Value::known(sv_address),
                         )?;
+                        let (_, next_cell) = config.assign_l1_queue_index(
+                            &mut region,
+                            offset - 1,
+                            l1_queue_index,
+                            l1_queue_index_next,
+                        )?;
+                        l1_queue_index_cells.1 = next_cell;
                     }
+                    l1_queue_index = l1_queue_index_next;
                 }
 
                 log::debug!("assigning calldata, offset {}", offset);
@@ -1942,9 +2371,12 @@ impl<F: Field> TxCircuit<F> {
 
                 debug_assert_eq!(offset, self.max_txs * TX_LEN + 1 + calldata_count);
 
-                Ok(offset)
+                Ok((offset, l1_queue_index_cells))
             },
         )?;
+        // the queue index before the first tx, and after the last one
+        layouter.constrain_instance(l1_queue_index_cells.0.cell(), config.l1_queue_index_pi, 0)?;
+        layouter.constrain_instance(l1_queue_index_cells.1.cell(), config.l1_queue_index_pi, 1)?;
         if last_off + config.minimum_rows > self.size {
             log::error!(
                 "circuit size not enough, last offset {}, minimum_rows {}, self.size {}",
@@ -1993,10 +2425,18 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             block.circuits_params.max_txs,
             block.circuits_params.max_calldata,
             block.chain_id,
+            block.l1_queue_index,
+            block.l1_queue_hashes.clone(),
             block.txs.clone(),
         )
     }
 
+    /// The L1 message queue indices before and after the txs.
+    fn instance(&self) -> Vec<Vec<F>> {
+        let (first, next) = self.l1_queue_indices();
+        vec![vec![F::from(first), F::from(next)]]
+    }
+
     /// Return the minimum number of rows required to prove the block
     fn min_num_rows_block(block: &witness::Block<F>) -> (usize, usize) {
         (
@@ -2028,21 +2468,7 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
                 tx
             })
             .collect::<Vec<Transaction>>();
-        let sign_datas: Vec<SignData> = self
-            .txs
-            .iter()
-            .chain(padding_txs.iter())
-            .map(|tx| {
-                if tx.tx_type.is_l1_msg() {
-                    Ok(SignData::default())
-                } else {
-                    tx.sign_data().map_err(|e| {
-                        error!("tx_to_sign_data error for tx {:?}", e);
-                        Error::Synthesis
-                    })
-                }
-            })
-            .collect::<Result<Vec<SignData>, Error>>()?;
+        let sign_datas = get_sign_data(&self.txs, self.max_txs, self.chain_id as usize)?;
 
         config.load_aux_tables(layouter)?;
 
@@ -2058,11 +2484,49 @@ impl<F: Field> SubCircuit<F> for TxCircuit<F> {
             .map(|(_, input)| input)
             .collect::<Vec<_>>();
 
+        if let Some(tx) = self
+            .txs
+            .iter()
+            .find(|tx| tx.blob_versioned_hashes.len() > MAX_BLOBS_PER_TX)
+        {
+            log::error!(
+                "tx {:?} has {} blobs, more than {}",
+                tx.hash,
+                tx.blob_versioned_hashes.len(),
+                MAX_BLOBS_PER_TX
+            );
+            return Err(Error::Synthesis);
+        }
+
+        let l1_msgs = self.txs.iter().filter(|tx| tx.tx_type.is_l1_msg());
+        for (i, tx) in l1_msgs.enumerate() {
+            let queue_index = self.l1_queue_index + i as u64;
+            if tx.nonce != queue_index {
+                log::error!(
+                    "L1 msg {:?} has queue index {}, expected {}",
+                    tx.hash,
+                    tx.nonce,
+                    queue_index
+                );
+                return Err(Error::Synthesis);
+            }
+            if self.l1_queue_hashes.get(i) != Some(&tx.hash) {
+                log::error!(
+                    "L1 msg {:?} doesn't match the entry {:?} of the L1 message queue at {}",
+                    tx.hash,
+                    self.l1_queue_hashes.get(i),
+                    queue_index
+                );
+                return Err(Error::Synthesis);
+            }
+        }
+
         for (pk, tx) in recovered_pks.into_iter().zip(self.txs.iter()) {
             let pk_hash = keccak(&pk);
             let address = pk_hash.to_address();
-            // L1 Msg does not have signature
-            if !tx.tx_type.is_l1_msg() && address != tx.caller_address {
+            // L1 Msg and system call do not have signature, and the sender of an
+            // unverified tx is not recovered from it
+            if !tx.tx_type.is_unsigned() && !tx.unverified_sender && address != tx.caller_address {
                 log::error!(
                     "pk address from sign data {:?} does not match the one from tx address {:?}",
                     address,
@@ -2090,21 +2554,20 @@ pub(crate) fn get_sign_data(
             tx
         })
         .collect::<Vec<Transaction>>();
-    let signatures: Vec<SignData> = txs
-        .iter()
-        .chain(padding_txs.iter())
-        .map(|tx| {
-            if tx.tx_type.is_l1_msg() {
-                // dummy signature
-                Ok(SignData::default())
-            } else {
-                // TODO: map err or still use bus_mapping::err?
-                tx.sign_data().map_err(|e| {
-                    log::error!("tx_to_sign_data error for tx {:?}", e);
-                    halo2_proofs::plonk::Error::Synthesis
-                })
-            }
-        })
-        .collect::<Result<Vec<SignData>, halo2_proofs::plonk::Error>>()?;
-    Ok(signatures)
+    let txs = txs.iter().chain(padding_txs.iter()).collect::<Vec<_>>();
+    // the pubkey recovery dominates, so the txs are handled in parallel
+    SignData::batch(&txs, |tx| {
+        if tx.tx_type.is_unsigned() || tx.unverified_sender {
+            // dummy signature
+            Ok(SignData::default())
+        } else {
+            tx.sign_data()
+        }
+    })
+    .map_err(|errors| {
+        for (i, e) in errors {
+            log::error!("tx_to_sign_data error for tx {:?}: {:?}", txs[i].hash, e);
+        }
+        halo2_proofs::plonk::Error::Synthesis
+    })
 }
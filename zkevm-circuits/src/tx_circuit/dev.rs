@@ -11,7 +11,7 @@ use crate::{
     util::{Challenges, SubCircuit, SubCircuitConfig},
     witness::Transaction,
 };
-use eth_types::Field;
+use eth_types::{Field, H256};
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner},
     plonk::{Circuit, ConstraintSystem, Error, Expression},
@@ -90,14 +90,30 @@ pub struct TxCircuitTester<F: Field> {
 
 impl<F: Field> TxCircuitTester<F> {
     /// Return a new TxCircuit
-    pub fn new(max_txs: usize, max_calldata: usize, chain_id: u64, txs: Vec<Transaction>) -> Self {
+    pub fn new(
+        max_txs: usize,
+        max_calldata: usize,
+        chain_id: u64,
+        l1_queue_index: u64,
+        l1_queue_hashes: Vec<H256>,
+        txs: Vec<Transaction>,
+    ) -> Self {
+        // the txs with an invalid signature are rejected by the synthesis
+        let signatures = get_sign_data(&txs, max_txs, chain_id as usize).unwrap_or_default();
         TxCircuitTester::<F> {
             sig_circuit: SigCircuit {
                 max_verif: max_txs,
-                signatures: get_sign_data(&txs, max_txs, chain_id as usize).unwrap(),
+                signatures,
                 _marker: PhantomData,
             },
-            tx_circuit: TxCircuit::new(max_txs, max_calldata, chain_id, txs),
+            tx_circuit: TxCircuit::new(
+                max_txs,
+                max_calldata,
+                chain_id,
+                l1_queue_index,
+                l1_queue_hashes,
+                txs,
+            ),
         }
     }
 }
@@ -110,7 +126,18 @@ impl<F: Field> SubCircuit<F> for TxCircuitTester<F> {
         let max_txs = block.circuits_params.max_txs;
         let chain_id = block.chain_id;
         let max_calldata = block.circuits_params.max_calldata;
-        Self::new(max_txs, max_calldata, chain_id, txs)
+        Self::new(
+            max_txs,
+            max_calldata,
+            chain_id,
+            block.l1_queue_index,
+            block.l1_queue_hashes.clone(),
+            txs,
+        )
+    }
+
+    fn instance(&self) -> Vec<Vec<F>> {
+        self.tx_circuit.instance()
     }
 
     fn synthesize_sub(
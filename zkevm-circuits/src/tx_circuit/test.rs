@@ -1,9 +1,14 @@
 #![allow(unused_imports)]
 
 use ethers_core::{
-    types::{NameOrAddress, Signature, Transaction as EthTransaction, TransactionRequest},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip2930::AccessList},
+        Eip1559TransactionRequest, Eip2930TransactionRequest, NameOrAddress, Signature,
+        Transaction as EthTransaction, TransactionRequest,
+    },
     utils::{keccak256, rlp, rlp::Decodable},
 };
+use ethers_signers::Signer;
 use std::cmp::max;
 
 use super::*;
@@ -108,25 +113,177 @@ fn build_l1_msg_tx() -> Transaction {
     tx
 }
 
+/// Build the pseudo-tx of a system call: the L1 msg of `build_l1_msg_tx`
+/// under the EIP-2718 type of a system call.
+#[cfg(test)]
+fn build_system_call_tx() -> Transaction {
+    let mut tx = build_l1_msg_tx();
+    tx.tx_type = SystemCall;
+    tx.rlp_signed[0] = 0x7d;
+    tx.hash = H256(keccak256(&tx.rlp_signed));
+    tx
+}
+
+/// The request of a tx of `tx_type`, either EIP-1559 or EIP-2930, sent by
+/// the first mock wallet for `chain_id`.
+#[cfg(test)]
+fn typed_tx_request(tx_type: TxType, chain_id: u64) -> TypedTransaction {
+    let wallet = &mock::MOCK_WALLETS[0];
+    let to = address!("0x00000000000000000000000000000000000000aa");
+    let gas_price = word!("0x3b9aca00");
+    match tx_type {
+        Eip1559 => Eip1559TransactionRequest::new()
+            .from(wallet.address())
+            .to(to)
+            .nonce(1)
+            .gas(21000)
+            .value(0x100)
+            .max_priority_fee_per_gas(gas_price)
+            .max_fee_per_gas(gas_price)
+            .chain_id(chain_id)
+            .into(),
+        Eip2930 => Eip2930TransactionRequest::new(
+            TransactionRequest::new()
+                .from(wallet.address())
+                .to(to)
+                .nonce(1)
+                .gas(21000)
+                .value(0x100)
+                .gas_price(gas_price)
+                .chain_id(chain_id),
+            AccessList::default(),
+        )
+        .into(),
+        _ => unreachable!("tx type {tx_type:?} is not typed"),
+    }
+}
+
+/// Build a tx of `tx_type`, either EIP-1559 or EIP-2930, sent by the first
+/// mock wallet and signed for `chain_id`.
+#[cfg(test)]
+fn build_typed_tx(tx_type: TxType, chain_id: u64) -> Transaction {
+    let wallet = &mock::MOCK_WALLETS[0];
+    let to = address!("0x00000000000000000000000000000000000000aa");
+    let gas_price = word!("0x3b9aca00");
+    let eth_tx = typed_tx_request(tx_type, chain_id);
+    // the v of the signature encodes the chain id as per EIP-155, the typed
+    // txs only keep its parity
+    let eth_sig = wallet.sign_transaction_sync(&eth_tx);
+    let signed_bytes = eth_tx.rlp_signed(&eth_sig).to_vec();
+    let unsigned_bytes = eth_tx.rlp().to_vec();
+
+    let mut tx = Transaction::new_from_rlp_bytes(tx_type, signed_bytes, unsigned_bytes);
+
+    tx.hash = H256(keccak256(&tx.rlp_signed));
+    tx.block_number = 1;
+    tx.id = 1;
+    tx.nonce = 1;
+    tx.gas_price = gas_price;
+    tx.gas = 21000;
+    tx.callee_address = Some(to);
+    tx.caller_address = wallet.address();
+    tx.value = U256::from(0x100);
+    tx.tx_data_gas_cost = tx_data_gas_cost(&tx.rlp_signed);
+    tx.chain_id = chain_id;
+    tx.v = (eth_sig.v + 1) % 2;
+    tx.r = eth_sig.r;
+    tx.s = eth_sig.s;
+
+    tx
+}
+
+/// Build an EIP-4844 tx carrying `blob_versioned_hashes`, sent by the first
+/// mock wallet and signed for `chain_id`.
+#[cfg(test)]
+fn build_eip4844_tx(chain_id: u64, blob_versioned_hashes: Vec<H256>) -> Transaction {
+    let wallet = &mock::MOCK_WALLETS[0];
+    let to = address!("0x00000000000000000000000000000000000000aa");
+    let gas_price = word!("0x3b9aca00");
+    let mut eth_tx = EthTransaction::from(&eth_types::geth_types::Transaction {
+        tx_type: Eip4844,
+        to: Some(to),
+        nonce: 1.into(),
+        gas_limit: 21000.into(),
+        value: 0x100.into(),
+        gas_fee_cap: gas_price,
+        gas_tip_cap: gas_price,
+        max_fee_per_blob_gas: 1.into(),
+        blob_versioned_hashes: blob_versioned_hashes.clone(),
+        ..Default::default()
+    });
+    eth_tx.chain_id = Some(chain_id.into());
+    let unsigned_bytes = eth_types::geth_types::get_rlp_unsigned(&eth_tx);
+    let eth_sig = wallet.sign_hash(H256(keccak256(&unsigned_bytes)));
+    eth_tx.v = (eth_sig.v - 27).into();
+    eth_tx.r = eth_sig.r;
+    eth_tx.s = eth_sig.s;
+    let signed_bytes = eth_types::geth_types::get_rlp_signed(&eth_tx);
+
+    let mut tx = Transaction::new_from_rlp_bytes(Eip4844, signed_bytes, unsigned_bytes);
+
+    tx.hash = H256(keccak256(&tx.rlp_signed));
+    tx.block_number = 1;
+    tx.id = 1;
+    tx.nonce = 1;
+    tx.gas_price = gas_price;
+    tx.gas = 21000;
+    tx.callee_address = Some(to);
+    tx.caller_address = wallet.address();
+    tx.value = U256::from(0x100);
+    tx.tx_data_gas_cost = tx_data_gas_cost(&tx.rlp_signed);
+    tx.chain_id = chain_id;
+    tx.v = eth_sig.v - 27;
+    tx.r = eth_sig.r;
+    tx.s = eth_sig.s;
+    tx.blob_versioned_hashes = blob_versioned_hashes;
+
+    tx
+}
+
+fn degree<F: Field>(max_txs: usize, max_calldata: usize) -> u32 {
+    max(
+        19,
+        log2_ceil(TxCircuit::<F>::min_num_rows(max_txs, max_calldata)),
+    )
+}
+
 fn run<F: Field>(
     txs: Vec<Transaction>,
     chain_id: u64,
     max_txs: usize,
     max_calldata: usize,
 ) -> Result<(), Vec<VerifyFailure>> {
-    let k = max(
-        19,
-        log2_ceil(TxCircuit::<F>::min_num_rows(max_txs, max_calldata)),
+    run_from_queue_index::<F>(txs, chain_id, 0, max_txs, max_calldata)
+}
+
+// the entries of the L1 message queue popped by the L1 msgs of `txs`
+fn l1_queue_hashes(txs: &[Transaction]) -> Vec<H256> {
+    txs.iter()
+        .filter(|tx| tx.tx_type.is_l1_msg())
+        .map(|tx| tx.hash)
+        .collect()
+}
+
+fn run_from_queue_index<F: Field>(
+    txs: Vec<Transaction>,
+    chain_id: u64,
+    l1_queue_index: u64,
+    max_txs: usize,
+    max_calldata: usize,
+) -> Result<(), Vec<VerifyFailure>> {
+    let circuit = TxCircuitTester::<F>::new(
+        max_txs,
+        max_calldata,
+        chain_id,
+        l1_queue_index,
+        l1_queue_hashes(&txs),
+        txs,
     );
-    let circuit = TxCircuitTester::<F> {
-        sig_circuit: SigCircuit {
-            max_verif: max_txs,
-            signatures: get_sign_data(&txs, max_txs, chain_id as usize).unwrap(),
-            _marker: PhantomData,
-        },
-        tx_circuit: TxCircuit::new(max_txs, max_calldata, chain_id, txs),
-    };
-    let prover = match MockProver::run(k, &circuit, vec![]) {
+    let prover = match MockProver::run(
+        degree::<F>(max_txs, max_calldata),
+        &circuit,
+        circuit.instance(),
+    ) {
         Ok(prover) => prover,
         Err(e) => panic!("{e:#?}"),
     };
@@ -205,11 +362,130 @@ fn tx_circuit_l1_msg_tx() {
     const MAX_CALLDATA: usize = 400;
 
     let tx = build_l1_msg_tx();
+    let l1_queue_index = tx.nonce;
 
     assert_eq!(
-        run::<Fr>(vec![tx], *mock::MOCK_CHAIN_ID, MAX_TXS, MAX_CALLDATA),
+        run_from_queue_index::<Fr>(
+            vec![tx],
+            *mock::MOCK_CHAIN_ID,
+            l1_queue_index,
+            MAX_TXS,
+            MAX_CALLDATA
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn tx_circuit_l1_msg_bad_queue_index() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 400;
+
+    let tx = build_l1_msg_tx();
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    let circuit = TxCircuitTester::<Fr>::new(
+        MAX_TXS,
+        MAX_CALLDATA,
+        chain_id,
+        tx.nonce,
+        vec![tx.hash],
+        vec![tx.clone()],
+    );
+    assert_eq!(
+        circuit.tx_circuit.l1_queue_indices(),
+        (tx.nonce, tx.nonce + 1)
+    );
+
+    // the chunk claims to start after the L1 msg it includes, or before it
+    for l1_queue_index in [tx.nonce + 1, tx.nonce - 1] {
+        let circuit = TxCircuitTester::<Fr>::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            chain_id,
+            l1_queue_index,
+            vec![tx.hash],
+            vec![tx.clone()],
+        );
+        let k = degree::<Fr>(MAX_TXS, MAX_CALLDATA);
+        assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+    }
+}
+
+#[test]
+fn tx_circuit_l1_msg_bad_queue_hash() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 400;
+
+    let tx = build_l1_msg_tx();
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    let k = degree::<Fr>(MAX_TXS, MAX_CALLDATA);
+    // the L1 msg at the right index of the queue, but the entry enqueued
+    // there is another msg, or isn't known
+    for l1_queue_hashes in [vec![H256::repeat_byte(0xaa)], vec![]] {
+        let circuit = TxCircuitTester::<Fr>::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            chain_id,
+            tx.nonce,
+            l1_queue_hashes,
+            vec![tx.clone()],
+        );
+        assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+    }
+}
+
+#[test]
+fn tx_circuit_system_call_tx() {
+    const MAX_TXS: usize = 4;
+    const MAX_CALLDATA: usize = 800;
+
+    // a system call with the fields of the L1 msg which follows it, sharing
+    // its nonce
+    let system_call = build_system_call_tx();
+    let mut l1_msg = build_l1_msg_tx();
+    l1_msg.id = 2;
+
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    let circuit = TxCircuitTester::<Fr>::new(
+        MAX_TXS,
+        MAX_CALLDATA,
+        chain_id,
+        l1_msg.nonce,
+        vec![l1_msg.hash],
+        vec![system_call.clone(), l1_msg.clone()],
+    );
+    // the system call doesn't consume the L1 message queue
+    assert_eq!(
+        circuit.tx_circuit.l1_queue_indices(),
+        (l1_msg.nonce, l1_msg.nonce + 1)
+    );
+    assert_eq!(
+        run_from_queue_index::<Fr>(
+            vec![system_call.clone(), l1_msg.clone()],
+            chain_id,
+            l1_msg.nonce,
+            MAX_TXS,
+            MAX_CALLDATA
+        ),
         Ok(())
     );
+
+    let k = degree::<Fr>(MAX_TXS, MAX_CALLDATA);
+    // the pseudo-tx hashed under the type of an L1 msg
+    let mut typed_as_l1_msg = build_l1_msg_tx();
+    typed_as_l1_msg.tx_type = SystemCall;
+    // an L1 msg would pop the queue index of the L1 msg
+    for first_tx in [typed_as_l1_msg, build_l1_msg_tx()] {
+        let circuit = TxCircuitTester::<Fr>::new(
+            MAX_TXS,
+            MAX_CALLDATA,
+            chain_id,
+            l1_msg.nonce,
+            vec![l1_msg.hash],
+            vec![first_tx, l1_msg.clone()],
+        );
+        assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+    }
 }
 
 #[test]
@@ -237,3 +513,112 @@ fn tx_circuit_to_is_zero() {
         Ok(())
     );
 }
+
+#[test]
+fn tx_circuit_typed_txs() {
+    const MAX_TXS: usize = 1;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    for tx_type in [Eip1559, Eip2930] {
+        let tx = build_typed_tx(tx_type, chain_id);
+        let eth_tx = EthTransaction::decode(&rlp::Rlp::new(&tx.rlp_signed)).unwrap();
+        assert_eq!(TxType::get_tx_type(&eth_tx), tx_type);
+        assert_eq!(tx.sign_data().unwrap().get_addr(), tx.caller_address);
+        assert_eq!(
+            run::<Fr>(vec![tx.clone()], chain_id, MAX_TXS, MAX_CALLDATA),
+            Ok(())
+        );
+
+        // value not signed by the sender
+        let mut tampered_tx = tx;
+        tampered_tx.value += U256::one();
+        assert!(run::<Fr>(vec![tampered_tx], chain_id, MAX_TXS, MAX_CALLDATA).is_err());
+
+        // signed for another chain
+        let mut tx = build_typed_tx(tx_type, chain_id + 1);
+        tx.chain_id = chain_id;
+        assert!(run::<Fr>(vec![tx], chain_id, MAX_TXS, MAX_CALLDATA).is_err());
+    }
+}
+
+#[test]
+fn tx_circuit_eip4844_tx() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    let tx = build_eip4844_tx(chain_id, vec![H256::repeat_byte(1), H256::repeat_byte(2)]);
+    assert_eq!(tx.sign_data().unwrap().get_addr(), tx.caller_address);
+    assert_eq!(
+        run::<Fr>(vec![tx.clone()], chain_id, MAX_TXS, MAX_CALLDATA),
+        Ok(())
+    );
+
+    // blob hashes not signed by the sender
+    let mut tampered_tx = tx;
+    tampered_tx.blob_versioned_hashes[1] = H256::repeat_byte(3);
+    assert!(run::<Fr>(vec![tampered_tx], chain_id, MAX_TXS, MAX_CALLDATA).is_err());
+
+    // more blobs than the tx table holds
+    let tx = build_eip4844_tx(chain_id, vec![H256::repeat_byte(1); MAX_BLOBS_PER_TX + 1]);
+    let k = degree::<Fr>(MAX_TXS, MAX_CALLDATA);
+    let circuit = TxCircuitTester::<Fr>::new(MAX_TXS, MAX_CALLDATA, chain_id, 0, vec![], vec![tx]);
+    assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+}
+
+#[test]
+fn tx_circuit_bad_v() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 320;
+
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    let k = degree::<Fr>(MAX_TXS, MAX_CALLDATA);
+    let mut typed_tx = build_typed_tx(Eip1559, chain_id);
+    typed_tx.v = 2;
+    let mut pre_eip155_tx = build_pre_eip155_tx();
+    pre_eip155_tx.v = 29;
+    for tx in [typed_tx, pre_eip155_tx] {
+        assert!(tx.sign_data().is_err());
+        let circuit =
+            TxCircuitTester::<Fr>::new(MAX_TXS, MAX_CALLDATA, chain_id, 0, vec![], vec![tx]);
+        assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+    }
+}
+
+#[test]
+fn tx_circuit_unverified_sender() {
+    const MAX_TXS: usize = 2;
+    const MAX_CALLDATA: usize = 32;
+
+    let chain_id = *mock::MOCK_CHAIN_ID;
+    let k = degree::<Fr>(MAX_TXS, MAX_CALLDATA);
+    // the tx of build_typed_tx left unsigned, and sent by another address
+    let no_sig = Signature {
+        r: U256::zero(),
+        s: U256::zero(),
+        v: 0,
+    };
+    let mut tx = build_typed_tx(Eip1559, chain_id);
+    tx.rlp_signed = typed_tx_request(Eip1559, chain_id)
+        .rlp_signed(&no_sig)
+        .to_vec();
+    tx.hash = H256(keccak256(&tx.rlp_signed));
+    tx.tx_data_gas_cost = tx_data_gas_cost(&tx.rlp_signed);
+    tx.v = 0;
+    tx.r = U256::zero();
+    tx.s = U256::zero();
+    tx.caller_address = address!("0x00000000000000000000000000000000000000bb");
+    tx.unverified_sender = true;
+
+    assert_eq!(
+        run::<Fr>(vec![tx.clone()], chain_id, MAX_TXS, MAX_CALLDATA),
+        Ok(())
+    );
+
+    // the sender of a tx not flagged in the tx table is recovered from its
+    // signature
+    tx.unverified_sender = false;
+    let circuit = TxCircuitTester::<Fr>::new(MAX_TXS, MAX_CALLDATA, chain_id, 0, vec![], vec![tx]);
+    assert!(MockProver::run(k, &circuit, circuit.instance()).is_err());
+}
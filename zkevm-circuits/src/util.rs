@@ -15,12 +15,29 @@ use halo2_proofs::plonk::FirstPhase as SecondPhase;
 use halo2_proofs::plonk::SecondPhase;
 
 use crate::{evm_circuit::util::rlc, table::TxLogFieldTag, witness};
+use bus_mapping::util::read_env_var;
 use eth_types::{Field, ToAddress, Word};
 pub use ethers_core::types::{Address, U256};
 pub use gadgets::util::Expr;
+use once_cell::sync::Lazy;
 
+/// Assign the rows of the keccak circuit as they are generated, a batch of
+/// inputs at a time, instead of building the whole witness first. This lowers
+/// the peak memory of large chunks at the cost of some witness generation
+/// parallelism; the assignment is the same in both modes.
+pub static STREAMING_ASSIGNMENT: Lazy<bool> =
+    Lazy::new(|| read_env_var("STREAMING_ASSIGNMENT", false));
+
+/// Recomposition of keccak digests into (hi, lo) field elements
+pub mod digest;
 /// A wrapper of is_zero in gadgets which gives is_zero at any rotation
 pub mod is_zero;
+/// Binary keccak trie over the tx hashes of a chunk
+pub mod tx_root;
+
+/// Audit of public cells for private witness data
+#[cfg(any(feature = "test", test))]
+pub mod blinding_audit;
 
 pub(crate) fn query_expression<F: FieldExt, T>(
     meta: &mut ConstraintSystem<F>,
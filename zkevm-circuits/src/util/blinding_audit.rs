@@ -0,0 +1,252 @@
+//! Audit of the public values of a circuit against private witness data.
+//!
+//! The instance columns and the fixed columns (which are committed in the
+//! verifying key) are not blinded, so any value placed there is public. This
+//! module scans the assignments of a [`MockProver`] for public cells holding a
+//! value derived from data the auditor marks as private, e.g. tx signatures or
+//! call data, and reports them.
+
+use std::{collections::HashMap, fmt};
+
+use eth_types::{Address, Field, ToScalar, Word};
+use halo2_proofs::dev::{CellValue, MockProver};
+
+use crate::{evm_circuit::util::rlc, witness::Transaction};
+
+/// Values smaller than 2^16 collide with counters, tags and lengths, so they
+/// are not tracked.
+const MIN_TRACKED_VALUE: u64 = 1 << 16;
+
+/// Private values, indexed by their field representation.
+#[derive(Clone, Debug, Default)]
+pub struct PrivateData {
+    values: HashMap<[u8; 32], Vec<String>>,
+}
+
+impl PrivateData {
+    /// Track a private field element.
+    pub fn add<F: Field>(&mut self, label: &str, value: F) {
+        if value.get_lower_128() < MIN_TRACKED_VALUE as u128
+            && value.to_repr()[16..].iter().all(|b| *b == 0)
+        {
+            return;
+        }
+        self.values
+            .entry(value.to_repr())
+            .or_default()
+            .push(label.to_owned());
+    }
+
+    /// Track a private word, as a field element if it fits and as its 128 bits
+    /// halves.
+    pub fn add_word<F: Field>(&mut self, label: &str, word: Word) {
+        if let Some(value) = ToScalar::<F>::to_scalar(&word) {
+            self.add(label, value);
+        }
+        self.add(&format!("{label}.lo"), F::from_u128(word.low_u128()));
+        self.add(
+            &format!("{label}.hi"),
+            F::from_u128((word >> 128).low_u128()),
+        );
+    }
+
+    /// Track a private address.
+    pub fn add_address<F: Field>(&mut self, label: &str, address: Address) {
+        self.add::<F>(label, address.to_scalar().unwrap());
+    }
+
+    /// Track the random linear combination of private bytes.
+    pub fn add_bytes<F: Field>(&mut self, label: &str, bytes: &[u8], randomness: F) {
+        if !bytes.is_empty() {
+            self.add(label, rlc::value(bytes.iter().rev(), randomness));
+        }
+    }
+
+    /// Track the sender, value, signature and call data of the txs, with the
+    /// call data combined with `randomness`.
+    pub fn from_txs<F: Field>(txs: &[Transaction], randomness: F) -> Self {
+        let mut private = Self::default();
+        for tx in txs {
+            let id = tx.id;
+            private.add_address::<F>(&format!("tx {id} caller_address"), tx.caller_address);
+            private.add_word::<F>(&format!("tx {id} value"), tx.value);
+            private.add_word::<F>(&format!("tx {id} sig_r"), tx.r);
+            private.add_word::<F>(&format!("tx {id} sig_s"), tx.s);
+            private.add_bytes(&format!("tx {id} call_data"), &tx.call_data, randomness);
+        }
+        private
+    }
+
+    /// Labels of the private values equal to `value`.
+    pub fn labels<F: Field>(&self, value: &F) -> Option<&[String]> {
+        self.values.get(&value.to_repr()).map(Vec::as_slice)
+    }
+}
+
+/// Kind of a public cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicColumn {
+    /// Instance column
+    Instance,
+    /// Fixed column, committed in the verifying key
+    Fixed,
+}
+
+/// A public cell holding a private value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Leak {
+    /// Kind of the column
+    pub kind: PublicColumn,
+    /// Index of the column among the columns of its kind
+    pub column: usize,
+    /// Row of the cell
+    pub row: usize,
+    /// Labels of the private values matching the cell
+    pub labels: Vec<String>,
+}
+
+/// Result of [`audit_blinding`].
+#[derive(Clone, Debug, Default)]
+pub struct BlindingAuditReport {
+    /// Public cells holding private values
+    pub leaks: Vec<Leak>,
+}
+
+impl BlindingAuditReport {
+    /// Returns `true` if no private value was found in a public cell.
+    pub fn is_clean(&self) -> bool {
+        self.leaks.is_empty()
+    }
+}
+
+impl fmt::Display for BlindingAuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "no private value found in public cells");
+        }
+        for leak in &self.leaks {
+            writeln!(
+                f,
+                "{:?} column {} row {}: {}",
+                leak.kind,
+                leak.column,
+                leak.row,
+                leak.labels.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Scan the instance and fixed columns of a circuit run by `prover` with
+/// `instance` for the values of `private`.
+pub fn audit_blinding<F: Field>(
+    prover: &MockProver<F>,
+    instance: &[Vec<F>],
+    private: &PrivateData,
+) -> BlindingAuditReport {
+    let mut leaks = vec![];
+    let mut check = |kind, column, row, value: &F| {
+        if let Some(labels) = private.labels(value) {
+            leaks.push(Leak {
+                kind,
+                column,
+                row,
+                labels: labels.to_vec(),
+            });
+        }
+    };
+    for (column, values) in instance.iter().enumerate() {
+        for (row, value) in values.iter().enumerate() {
+            check(PublicColumn::Instance, column, row, value);
+        }
+    }
+    for (column, values) in prover.fixed().iter().enumerate() {
+        for (row, value) in values.iter().enumerate() {
+            if let CellValue::Assigned(value) = value {
+                check(PublicColumn::Fixed, column, row, value);
+            }
+        }
+    }
+    BlindingAuditReport { leaks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance},
+    };
+
+    #[derive(Default)]
+    struct LeakyCircuit {
+        secret: Fr,
+    }
+
+    impl Circuit<Fr> for LeakyCircuit {
+        type Config = (Column<Advice>, Column<Fixed>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (advice, meta.fixed_column(), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (advice, fixed, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "leak",
+                |mut region| {
+                    region.assign_fixed(|| "fixed", fixed, 0, || Value::known(self.secret))?;
+                    region.assign_advice(|| "advice", advice, 0, || Value::known(self.secret))
+                },
+            )?;
+            layouter.constrain_instance(cell.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn blinding_audit() {
+        let secret = Fr::from(0xdeadbeef);
+        let circuit = LeakyCircuit { secret };
+        let instance = vec![vec![secret]];
+        let prover = MockProver::run(4, &circuit, instance.clone()).unwrap();
+
+        let mut private = PrivateData::default();
+        private.add("secret", secret);
+        private.add("small", Fr::from(1));
+        let report = audit_blinding(&prover, &instance, &private);
+        assert_eq!(
+            report.leaks,
+            vec![
+                Leak {
+                    kind: PublicColumn::Instance,
+                    column: 0,
+                    row: 0,
+                    labels: vec!["secret".to_owned()],
+                },
+                Leak {
+                    kind: PublicColumn::Fixed,
+                    column: 0,
+                    row: 0,
+                    labels: vec!["secret".to_owned()],
+                },
+            ]
+        );
+
+        let report = audit_blinding(&prover, &instance, &PrivateData::default());
+        assert!(report.is_clean());
+    }
+}
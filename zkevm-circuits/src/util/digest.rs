@@ -0,0 +1,202 @@
+//! Recomposition of 32-byte keccak digests into field elements.
+//!
+//! The PI circuit exposes its pi hash as a (hi, lo) pair of 128-bit halves,
+//! while the aggregator reads the same digest straight from the keccak
+//! circuit, whose digest cells are laid out as four 8-byte words in reverse
+//! word order. Both go through this module so the byte order is defined once.
+
+use eth_types::Field;
+use halo2_proofs::plonk::Expression;
+
+/// Number of bytes in a keccak digest.
+pub const DIGEST_LEN: usize = 32;
+/// Number of bytes in each of the (hi, lo) halves of a digest.
+pub const DIGEST_HALF_LEN: usize = DIGEST_LEN / 2;
+/// Number of bytes in a word of the keccak circuit digest layout.
+const KECCAK_WORD_LEN: usize = 8;
+/// Number of words in the keccak circuit digest layout.
+const KECCAK_NUM_WORDS: usize = DIGEST_LEN / KECCAK_WORD_LEN;
+
+/// Order in which the 32 bytes of a digest are given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DigestByteOrder {
+    /// Same order as `H256::to_fixed_bytes`.
+    BigEndian,
+    /// Reverse of `BigEndian`.
+    LittleEndian,
+    /// Order of the digest cells assigned by the keccak circuit: the four
+    /// 8-byte words of the digest with the last word first, each word keeping
+    /// its bytes in big-endian order.
+    KeccakWords,
+}
+
+impl DigestByteOrder {
+    /// All supported byte orders.
+    pub const ALL: [Self; 3] = [Self::BigEndian, Self::LittleEndian, Self::KeccakWords];
+
+    /// Position in the big-endian digest of the `i`-th byte given in this
+    /// order.
+    pub fn be_index(&self, i: usize) -> usize {
+        assert!(i < DIGEST_LEN, "digest byte index {i} out of range");
+        match self {
+            Self::BigEndian => i,
+            Self::LittleEndian => DIGEST_LEN - 1 - i,
+            Self::KeccakWords => {
+                (KECCAK_NUM_WORDS - 1 - i / KECCAK_WORD_LEN) * KECCAK_WORD_LEN + i % KECCAK_WORD_LEN
+            }
+        }
+    }
+
+    /// Reorder the 32 digest items given in this order into big-endian order.
+    pub fn to_be<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        assert_eq!(items.len(), DIGEST_LEN);
+        let mut be = items.to_vec();
+        for (i, item) in items.iter().enumerate() {
+            be[self.be_index(i)] = item.clone();
+        }
+        be
+    }
+
+    /// Reorder the 32 big-endian digest items into this order.
+    pub fn from_be<T: Clone>(&self, be: &[T]) -> Vec<T> {
+        assert_eq!(be.len(), DIGEST_LEN);
+        (0..DIGEST_LEN)
+            .map(|i| be[self.be_index(i)].clone())
+            .collect()
+    }
+}
+
+/// Split a big-endian digest into its (hi, lo) halves.
+pub fn digest_halves(be_bytes: &[u8; DIGEST_LEN]) -> (&[u8], &[u8]) {
+    be_bytes.split_at(DIGEST_HALF_LEN)
+}
+
+/// Recompose the digest bytes, given in `order`, into (hi, lo) field elements,
+/// each holding 16 bytes interpreted as a big-endian integer.
+pub fn digest_hi_lo<F: Field>(bytes: &[u8], order: DigestByteOrder) -> (F, F) {
+    let be_bytes = order.to_be(bytes);
+    let (hi, lo) = be_bytes.split_at(DIGEST_HALF_LEN);
+    let compose = |half: &[u8]| {
+        F::from_u128(
+            half.iter()
+                .fold(0u128, |acc, byte| (acc << 8) | *byte as u128),
+        )
+    };
+    (compose(hi), compose(lo))
+}
+
+/// Same as [`digest_hi_lo`] for byte expressions.
+pub fn digest_hi_lo_expr<F: Field>(
+    bytes: &[Expression<F>],
+    order: DigestByteOrder,
+) -> (Expression<F>, Expression<F>) {
+    let be_bytes = order.to_be(bytes);
+    let (hi, lo) = be_bytes.split_at(DIGEST_HALF_LEN);
+    let compose = |half: &[Expression<F>]| {
+        half.iter()
+            .fold(Expression::Constant(F::zero()), |acc, byte| {
+                acc * Expression::Constant(F::from(256)) + byte.clone()
+            })
+    };
+    (compose(hi), compose(lo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        arithmetic::{Field as Halo2Field, FieldExt},
+        halo2curves::bn256::Fr,
+    };
+
+    fn eval(expr: &Expression<Fr>) -> Fr {
+        expr.evaluate(
+            &|c| c,
+            &|_| unreachable!(),
+            &|_| unreachable!(),
+            &|_| unreachable!(),
+            &|_| unreachable!(),
+            &|_| unreachable!(),
+            &|a| -a,
+            &|a, b| a + b,
+            &|a, b| a * b,
+            &|a, s| a * s,
+        )
+    }
+
+    #[test]
+    fn be_index_is_a_permutation() {
+        for order in DigestByteOrder::ALL {
+            let mut seen = [false; DIGEST_LEN];
+            for i in 0..DIGEST_LEN {
+                seen[order.be_index(i)] = true;
+            }
+            assert!(seen.iter().all(|s| *s), "{order:?}");
+        }
+    }
+
+    #[test]
+    fn keccak_words_layout() {
+        // the first cell of the keccak digest is the first byte of the last word
+        let expected = [
+            24, 25, 26, 27, 28, 29, 30, 31, 16, 17, 18, 19, 20, 21, 22, 23,
+        ];
+        for (i, be_index) in expected.iter().enumerate() {
+            assert_eq!(DigestByteOrder::KeccakWords.be_index(i), *be_index);
+        }
+        assert_eq!(DigestByteOrder::KeccakWords.be_index(31), 7);
+    }
+
+    #[test]
+    fn reorder_roundtrip() {
+        let be: Vec<u8> = (0..DIGEST_LEN as u8).collect();
+        for order in DigestByteOrder::ALL {
+            assert_eq!(order.to_be(&order.from_be(&be)), be, "{order:?}");
+            assert_eq!(order.from_be(&order.to_be(&be)), be, "{order:?}");
+        }
+    }
+
+    #[test]
+    fn hi_lo_of_every_byte_position() {
+        for order in DigestByteOrder::ALL {
+            for be_pos in 0..DIGEST_LEN {
+                let mut be = [0u8; DIGEST_LEN];
+                be[be_pos] = 0xab;
+                let bytes = order.from_be(&be);
+
+                let shift = 8 * (DIGEST_HALF_LEN - 1 - be_pos % DIGEST_HALF_LEN);
+                let weight = Fr::from_u128(0xab << shift);
+                let expected = if be_pos < DIGEST_HALF_LEN {
+                    (weight, Fr::zero())
+                } else {
+                    (Fr::zero(), weight)
+                };
+                assert_eq!(digest_hi_lo::<Fr>(&bytes, order), expected, "{order:?}");
+
+                let exprs: Vec<_> = bytes
+                    .iter()
+                    .map(|byte| Expression::Constant(Fr::from(*byte as u64)))
+                    .collect();
+                let (hi, lo) = digest_hi_lo_expr(&exprs, order);
+                assert_eq!((eval(&hi), eval(&lo)), expected, "{order:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn hi_lo_agree_across_orders() {
+        let be: Vec<u8> = (0..DIGEST_LEN).map(|i| (i * 7 + 3) as u8).collect();
+        let expected = digest_hi_lo::<Fr>(&be, DigestByteOrder::BigEndian);
+        assert_eq!(
+            expected.0,
+            Fr::from_u128(u128::from_be_bytes(be[..16].try_into().unwrap()))
+        );
+        assert_eq!(
+            expected.1,
+            Fr::from_u128(u128::from_be_bytes(be[16..].try_into().unwrap()))
+        );
+        for order in DigestByteOrder::ALL {
+            assert_eq!(digest_hi_lo::<Fr>(&order.from_be(&be), order), expected);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Binary keccak trie over the tx hashes of a chunk.
+//!
+//! The leaves are the tx hashes in the order they are committed to by the
+//! chunk's data hash, padded with zero hashes up to a power of two, and each
+//! node is `keccak(left || right)`. The root is `keccak(num_txs || node)` of
+//! the top node, with `num_txs` as 8 big-endian bytes, so that it commits to
+//! the number of txs and not only to the padded leaves.
+//!
+//! The root is computed off-circuit and isn't part of any public input: it's
+//! only as trustworthy as the tx hashes it is computed from, e.g. the ones
+//! of the data hash preimage of a proven chunk.
+
+use eth_types::H256;
+use ethers_core::utils::keccak256;
+
+/// Number of levels above the leaves of the trie over `num_txs` tx hashes.
+pub fn tx_trie_depth(num_txs: usize) -> usize {
+    num_txs.next_power_of_two().trailing_zeros() as usize
+}
+
+fn hash_pair(left: &H256, right: &H256) -> H256 {
+    H256(keccak256([left.as_bytes(), right.as_bytes()].concat()))
+}
+
+/// The levels of the trie, from the padded leaves to the root.
+fn tx_trie_levels(tx_hashes: &[H256]) -> Vec<Vec<H256>> {
+    let mut level = tx_hashes.to_vec();
+    level.resize(tx_hashes.len().next_power_of_two(), H256::zero());
+    let mut levels = vec![level];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Commit the top node of the trie to the number of txs.
+fn commit_num_txs(num_txs: usize, node: &H256) -> H256 {
+    H256(keccak256(
+        [&(num_txs as u64).to_be_bytes()[..], node.as_bytes()].concat(),
+    ))
+}
+
+/// Root of the trie over `tx_hashes`, whose top node is the zero hash for no
+/// tx.
+pub fn tx_hash_root(tx_hashes: &[H256]) -> H256 {
+    let node = if tx_hashes.is_empty() {
+        H256::zero()
+    } else {
+        tx_trie_levels(tx_hashes).pop().unwrap()[0]
+    };
+    commit_num_txs(tx_hashes.len(), &node)
+}
+
+/// The sibling hashes from the leaf at `index` up to the root, or `None` if
+/// `index` is out of range.
+pub fn tx_inclusion_proof(tx_hashes: &[H256], index: usize) -> Option<Vec<H256>> {
+    if index >= tx_hashes.len() {
+        return None;
+    }
+    let levels = tx_trie_levels(tx_hashes);
+    Some(
+        levels[..levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect(),
+    )
+}
+
+/// Check that `tx_hash` is the tx at `index` among the `num_txs` txs of the
+/// trie of `root`, given its [`tx_inclusion_proof`].
+pub fn verify_tx_inclusion(
+    root: H256,
+    num_txs: usize,
+    index: usize,
+    tx_hash: H256,
+    proof: &[H256],
+) -> bool {
+    if index >= num_txs || proof.len() != tx_trie_depth(num_txs) {
+        return false;
+    }
+    let node = proof
+        .iter()
+        .enumerate()
+        .fold(tx_hash, |node, (height, sibling)| {
+            if (index >> height) & 1 == 0 {
+                hash_pair(&node, sibling)
+            } else {
+                hash_pair(sibling, &node)
+            }
+        });
+    commit_num_txs(num_txs, &node) == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx_hashes(num_txs: usize) -> Vec<H256> {
+        (0..num_txs)
+            .map(|i| H256(keccak256(i.to_be_bytes())))
+            .collect()
+    }
+
+    #[test]
+    fn tx_root_small_tries() {
+        assert_eq!(tx_hash_root(&[]), commit_num_txs(0, &H256::zero()));
+
+        let hashes = tx_hashes(3);
+        assert_eq!(tx_hash_root(&hashes[..1]), commit_num_txs(1, &hashes[0]));
+        assert_eq!(
+            tx_hash_root(&hashes),
+            commit_num_txs(
+                3,
+                &hash_pair(
+                    &hash_pair(&hashes[0], &hashes[1]),
+                    &hash_pair(&hashes[2], &H256::zero())
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn tx_root_commits_to_num_txs() {
+        // 3 txs and the same txs with a zero hash padding them to 4 have the
+        // same top node
+        let mut hashes = tx_hashes(3);
+        let root = tx_hash_root(&hashes);
+        hashes.push(H256::zero());
+        assert_ne!(tx_hash_root(&hashes), root);
+
+        let proof = tx_inclusion_proof(&hashes, 3).unwrap();
+        assert!(verify_tx_inclusion(
+            tx_hash_root(&hashes),
+            4,
+            3,
+            H256::zero(),
+            &proof
+        ));
+        // the padding isn't a tx of the trie of 3 txs
+        assert!(!verify_tx_inclusion(root, 4, 3, H256::zero(), &proof));
+        assert!(!verify_tx_inclusion(root, 3, 3, H256::zero(), &proof));
+    }
+
+    #[test]
+    fn tx_inclusion_proofs() {
+        for num_txs in 1..20 {
+            let hashes = tx_hashes(num_txs);
+            let root = tx_hash_root(&hashes);
+            for (index, hash) in hashes.iter().enumerate() {
+                let proof = tx_inclusion_proof(&hashes, index).unwrap();
+                assert_eq!(proof.len(), tx_trie_depth(num_txs));
+                assert!(verify_tx_inclusion(root, num_txs, index, *hash, &proof));
+                // wrong position or padding
+                assert!(!verify_tx_inclusion(
+                    root,
+                    num_txs,
+                    index ^ 1,
+                    *hash,
+                    &proof
+                ));
+                assert!(!verify_tx_inclusion(
+                    root,
+                    num_txs,
+                    index,
+                    H256::zero(),
+                    &proof
+                ));
+            }
+            assert!(tx_inclusion_proof(&hashes, num_txs).is_none());
+        }
+    }
+}
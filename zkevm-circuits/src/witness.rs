@@ -3,7 +3,11 @@
 //! used to generate witnesses for circuits.
 
 mod block;
-pub use block::{block_apply_mpt_state, block_convert, Block, BlockContext, BlockContexts};
+pub use block::{
+    block_apply_mpt_state, block_apply_mpt_state_with_tx_roots, block_convert,
+    block_convert_with_mock_challenges, block_convert_with_randomness, Block, BlockContext,
+    BlockContexts,
+};
 
 mod bytecode;
 pub use bytecode::Bytecode;
@@ -12,15 +16,15 @@ mod call;
 pub use call::Call;
 
 mod mpt;
-pub use mpt::{MptUpdate, MptUpdateRow, MptUpdates, WithdrawProof};
+pub use mpt::{mpt_updates_from_statedb, MptUpdate, MptUpdateRow, MptUpdates, WithdrawProof};
 
 mod receipt;
 pub use receipt::Receipt;
 
 pub(crate) mod rlp_fsm;
 pub use rlp_fsm::{
-    DataTable, Format, RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTable, RlpTag, RomTableRow, State,
-    StateMachine, Tag,
+    DataTable, Format, RlpFsm, RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTable, RlpTag, RomTableRow,
+    State, StateMachine, Tag,
 };
 
 mod rw;
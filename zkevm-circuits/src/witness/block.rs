@@ -2,16 +2,20 @@ use ethers_core::types::Signature;
 use std::collections::BTreeMap;
 
 #[cfg(any(feature = "test", test))]
-use crate::evm_circuit::{detect_fixed_table_tags, EvmCircuit};
+use crate::evm_circuit::{detect_fixed_table_tags, EvmCircuit, EvmCircuitParams};
 
 use crate::{evm_circuit::util::rlc, table::BlockContextFieldTag, util::SubCircuit};
 use bus_mapping::{
     circuit_input_builder::{
-        self, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp, ExpEvent, PrecompileEvents,
+        self, ChainSpec, CircuitsParams, CopyEvent, EcAddOp, EcMulOp, EcPairingOp, ExpEvent,
+        L1FeeConfig, PrecompileEvents,
     },
     Error,
 };
-use eth_types::{sign_types::SignData, Address, Field, ToLittleEndian, ToScalar, Word, U256};
+use eth_types::{
+    evm_types::GasSchedules, sign_types::SignData, Address, Field, ToLittleEndian, ToScalar, Word,
+    H256, U256,
+};
 use halo2_proofs::circuit::Value;
 
 use super::{
@@ -58,14 +62,29 @@ pub struct Block<F> {
     pub withdraw_root: Word,
     /// Withdraw roof of the previous block
     pub prev_withdraw_root: Word,
+    /// Index in the L1 message queue of the first L1 msg the block may
+    /// include: the index after the L1 msgs of the previous chunk
+    pub l1_queue_index: u64,
+    /// Hashes of the entries of the L1 message queue from `l1_queue_index`
+    /// on, as enqueued on L1, which the L1 msgs of the block must match
+    pub l1_queue_hashes: Vec<H256>,
     /// Keccak inputs
     pub keccak_inputs: Vec<Vec<u8>>,
     /// Mpt updates
     pub mpt_updates: MptUpdates,
+    /// State root after each tx, only computed by
+    /// [`block_apply_mpt_state_with_tx_roots`]
+    pub tx_state_roots: Vec<Word>,
     /// Chain ID
     pub chain_id: u64,
     /// IO to/from precompile calls.
     pub precompile_events: PrecompileEvents,
+    /// Costs of the storage opcodes, by the block they apply from
+    pub gas_schedules: GasSchedules,
+    /// Hardforks and system calls of the chain
+    pub chain_spec: ChainSpec,
+    /// Blocks the L1 fee hardforks of the chain activate at
+    pub l1_fee_config: L1FeeConfig,
 }
 
 /// ...
@@ -91,6 +110,16 @@ impl BlockContexts {
 }
 
 impl<F: Field> Block<F> {
+    /// Challenges with every phase set to the witness randomness, matching
+    /// the values seen by the circuits under `MockProver`.
+    pub fn mock_challenges(&self) -> Challenges<Value<F>> {
+        Challenges::mock(
+            Value::known(self.randomness),
+            Value::known(self.randomness),
+            Value::known(self.randomness),
+        )
+    }
+
     /// For each tx, for each step, print the rwc at the beginning of the step,
     /// and all the rw operations of the step.
     pub(crate) fn debug_print_txs_steps_rw_ops(&self) {
@@ -111,7 +140,7 @@ impl<F: Field> Block<F> {
             .txs
             .iter()
             .map(|tx| {
-                if tx.tx_type.is_l1_msg() {
+                if tx.tx_type.is_unsigned() || tx.unverified_sender {
                     // dummy signature
                     Ok(SignData::default())
                 } else {
@@ -164,7 +193,7 @@ impl<F: Field> Block<F> {
         let num_rows_required_for_rw_table: usize = self.circuits_params.max_rws;
         let num_rows_required_for_fixed_table: usize = detect_fixed_table_tags(self)
             .iter()
-            .map(|tag| tag.build::<F>().count())
+            .map(|tag| tag.build::<F>(&EvmCircuitParams::from_block(self)).count())
             .sum();
         let num_rows_required_for_bytecode_table: usize = self
             .bytecodes
@@ -229,6 +258,8 @@ pub struct BlockContext {
     pub difficulty: Word,
     /// The base fee, the minimum amount of gas fee for a transaction
     pub base_fee: Word,
+    /// The base fee per blob gas of EIP-4844
+    pub blob_base_fee: Word,
     /// The hash of previous blocks
     pub history_hashes: Vec<Word>,
     /// The chain id
@@ -247,58 +278,49 @@ impl BlockContext {
     ) -> Vec<[Value<F>; 3]> {
         let current_block_number = self.number.to_scalar().unwrap();
         let randomness = challenges.evm_word();
-        [
-            vec![
-                [
-                    Value::known(F::from(BlockContextFieldTag::Coinbase as u64)),
-                    Value::known(current_block_number),
-                    Value::known(self.coinbase.to_scalar().unwrap()),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::Timestamp as u64)),
-                    Value::known(current_block_number),
-                    Value::known(self.timestamp.to_scalar().unwrap()),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::Number as u64)),
-                    Value::known(current_block_number),
-                    Value::known(current_block_number),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::Difficulty as u64)),
-                    Value::known(current_block_number),
-                    randomness.map(|rand| rlc::value(&self.difficulty.to_le_bytes(), rand)),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::GasLimit as u64)),
-                    Value::known(current_block_number),
-                    Value::known(F::from(self.gas_limit)),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::BaseFee as u64)),
-                    Value::known(current_block_number),
-                    randomness
-                        .map(|randomness| rlc::value(&self.base_fee.to_le_bytes(), randomness)),
-                ],
+        BlockContextFieldTag::BLOCK_TABLE_FIELDS
+            .into_iter()
+            .map(|tag| {
                 [
-                    Value::known(F::from(BlockContextFieldTag::ChainId as u64)),
+                    Value::known(F::from(tag as u64)),
                     Value::known(current_block_number),
-                    Value::known(F::from(self.chain_id)),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::NumTxs as u64)),
-                    Value::known(current_block_number),
-                    Value::known(F::from(num_txs as u64)),
-                ],
-                [
-                    Value::known(F::from(BlockContextFieldTag::CumNumTxs as u64)),
-                    Value::known(current_block_number),
-                    Value::known(F::from(cum_num_txs as u64)),
-                ],
-            ],
-            self.block_hash_assignments(randomness),
-        ]
-        .concat()
+                    self.field_value(tag, num_txs, cum_num_txs, randomness),
+                ]
+            })
+            .chain(self.block_hash_assignments(randomness))
+            .collect()
+    }
+
+    /// Value of the field `tag` of [`BlockContextFieldTag::BLOCK_TABLE_FIELDS`]
+    /// in the block table rows of this block.
+    pub fn field_value<F: Field>(
+        &self,
+        tag: BlockContextFieldTag,
+        num_txs: usize,
+        cum_num_txs: usize,
+        randomness: Value<F>,
+    ) -> Value<F> {
+        match tag {
+            BlockContextFieldTag::Coinbase => Value::known(self.coinbase.to_scalar().unwrap()),
+            BlockContextFieldTag::Timestamp => Value::known(self.timestamp.to_scalar().unwrap()),
+            BlockContextFieldTag::Number => Value::known(self.number.to_scalar().unwrap()),
+            BlockContextFieldTag::Difficulty => {
+                randomness.map(|rand| rlc::value(&self.difficulty.to_le_bytes(), rand))
+            }
+            BlockContextFieldTag::GasLimit => Value::known(F::from(self.gas_limit)),
+            BlockContextFieldTag::BaseFee => {
+                randomness.map(|rand| rlc::value(&self.base_fee.to_le_bytes(), rand))
+            }
+            BlockContextFieldTag::ChainId => Value::known(F::from(self.chain_id)),
+            BlockContextFieldTag::NumTxs => Value::known(F::from(num_txs as u64)),
+            BlockContextFieldTag::CumNumTxs => Value::known(F::from(cum_num_txs as u64)),
+            BlockContextFieldTag::BlobBaseFee => {
+                randomness.map(|rand| rlc::value(&self.blob_base_fee.to_le_bytes(), rand))
+            }
+            BlockContextFieldTag::Null | BlockContextFieldTag::BlockHash => {
+                unreachable!("{tag:?} is not in the block table rows of a block")
+            }
+        }
     }
 
     fn block_hash_assignments<F: Field>(&self, randomness: Value<F>) -> Vec<[Value<F>; 3]> {
@@ -349,6 +371,7 @@ impl From<&circuit_input_builder::Block> for BlockContexts {
                             timestamp: block.timestamp,
                             difficulty: block.difficulty,
                             base_fee: block.base_fee,
+                            blob_base_fee: block.blob_base_fee,
                             history_hashes: block.history_hashes.clone(),
                             chain_id: block.chain_id,
                             eth_block: block.eth_block.clone(),
@@ -364,6 +387,27 @@ impl From<&circuit_input_builder::Block> for BlockContexts {
 pub fn block_convert<F: Field>(
     block: &circuit_input_builder::Block,
     code_db: &bus_mapping::state_db::CodeDB,
+) -> Result<Block<F>, Error> {
+    block_convert_with_randomness(block, code_db, F::from_u128(DEFAULT_RAND))
+}
+
+/// Convert a block struct in bus-mapping to a witness block whose RLC values
+/// are computed with the given `MOCK_RANDOMNESS`, i.e. the same value the
+/// circuits use for all their challenges under `MockProver`.
+pub fn block_convert_with_mock_challenges<F: Field>(
+    block: &circuit_input_builder::Block,
+    code_db: &bus_mapping::state_db::CodeDB,
+    mock_randomness: u64,
+) -> Result<Block<F>, Error> {
+    block_convert_with_randomness(block, code_db, F::from(mock_randomness))
+}
+
+/// Convert a block struct in bus-mapping to a witness block, using
+/// `randomness` for the random linear combinations of the witness.
+pub fn block_convert_with_randomness<F: Field>(
+    block: &circuit_input_builder::Block,
+    code_db: &bus_mapping::state_db::CodeDB,
+    randomness: F,
 ) -> Result<Block<F>, Error> {
     let rws = RwMap::from(&block.container);
     #[cfg(debug_assertions)]
@@ -378,8 +422,8 @@ pub fn block_convert<F: Field>(
         .unwrap_or_default();
     let chain_id = block.chain_id();
     rws.check_rw_counter_sanity();
-    let end_block_not_last = step_convert(&block.block_steps.end_block_not_last, last_block_num);
-    let end_block_last = step_convert(&block.block_steps.end_block_last, last_block_num);
+    let end_block_not_last = step_convert(&block.block_steps.end_block_not_last, last_block_num)?;
+    let end_block_last = step_convert(&block.block_steps.end_block_last, last_block_num)?;
     log::trace!(
         "witness block: end_block_not_last {:?}, end_block_last {:?}",
         end_block_not_last,
@@ -428,28 +472,21 @@ pub fn block_convert<F: Field>(
     }
 
     Ok(Block {
-        randomness: F::from_u128(DEFAULT_RAND),
+        randomness,
         context: block.into(),
         rws,
         txs: block
             .txs()
             .iter()
             .enumerate()
-            .map(|(idx, tx)| {
-                let next_block_num = if idx + 1 < num_txs {
-                    block.txs()[idx + 1].block_num
-                } else {
-                    last_block_num + 1
-                };
-                tx_convert(tx, idx + 1, chain_id, next_block_num)
-            })
-            .collect(),
+            .map(|(idx, tx)| tx_convert(tx, idx + 1, chain_id))
+            .collect::<Result<_, _>>()?,
         sigs: block.txs().iter().map(|tx| tx.signature).collect(),
         end_block_not_last,
         end_block_last,
         bytecodes: code_db
-            .0
             .iter()
+            .filter(|(code_hash, _)| block.bytecode_hashes.contains(code_hash))
             .map(|(code_hash, bytes)| {
                 let hash = Word::from_big_endian(code_hash.as_bytes());
                 (
@@ -472,10 +509,16 @@ pub fn block_convert<F: Field>(
         prev_state_root: block.prev_state_root,
         withdraw_root: block.withdraw_root,
         prev_withdraw_root: block.prev_withdraw_root,
+        l1_queue_index: block.l1_queue_index,
+        l1_queue_hashes: block.l1_queue_hashes.clone(),
         keccak_inputs: circuit_input_builder::keccak_inputs(block, code_db)?,
         mpt_updates,
+        tx_state_roots: Vec::new(),
         chain_id,
         precompile_events: block.precompile_events.clone(),
+        gas_schedules: block.gas_schedules.clone(),
+        chain_spec: block.chain_spec.clone(),
+        l1_fee_config: block.l1_fee_config,
     })
 }
 
@@ -483,3 +526,87 @@ pub fn block_convert<F: Field>(
 pub fn block_apply_mpt_state<F: Field>(block: &mut Block<F>, mpt_state: &MptState) {
     block.mpt_updates.fill_state_roots(mpt_state);
 }
+
+/// Attach witness block with mpt states, and with the state root after each
+/// tx, which locates the tx where a state divergence starts.
+pub fn block_apply_mpt_state_with_tx_roots<F: Field>(block: &mut Block<F>, mpt_state: &MptState) {
+    block_apply_mpt_state(block, mpt_state);
+
+    let tx_rws = block
+        .txs
+        .iter()
+        .map(|tx| {
+            tx.steps
+                .iter()
+                .flat_map(|step| step.rw_indices.iter().map(|idx| block.rws[*idx]))
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    block.tx_state_roots = MptUpdates::tx_state_roots(&tx_rws, mpt_state);
+    if let Some(root) = block.tx_state_roots.last() {
+        if *root != block.mpt_updates.new_root() {
+            log::error!(
+                "state root after the last tx {:#x} differs from the block's {:#x}",
+                root,
+                block.mpt_updates.new_root()
+            );
+        }
+    }
+}
+
+#[cfg(all(test, feature = "cancun"))]
+mod tests {
+    use super::block_convert;
+    use crate::evm_circuit::step::ExecutionState;
+    use bus_mapping::{
+        circuit_input_builder::{ExecState, ExecStep},
+        evm::OpcodeId,
+        mock::BlockData,
+        precompile::PrecompileCalls,
+        Error,
+    };
+    use eth_types::{bytecode, geth_types::GethData};
+    use halo2_proofs::halo2curves::bn256::Fr;
+    use mock::test_ctx::{helpers::*, TestContext};
+
+    #[test]
+    fn block_convert_unsupported_opcode() {
+        let code = bytecode! {
+            PUSH1(0x01)
+            PUSH1(0x00)
+            TSTORE
+            STOP
+        };
+        let block: GethData = TestContext::<2, 1>::new(
+            None,
+            account_0_code_account_1_no_code(code),
+            tx_from_1_to_0,
+            |block, _tx| block,
+        )
+        .unwrap()
+        .into();
+        let mut builder = BlockData::new_from_geth_data(block.clone()).new_circuit_input_builder();
+        builder
+            .handle_block(&block.eth_block, &block.geth_traces)
+            .unwrap();
+
+        assert!(matches!(
+            block_convert::<Fr>(&builder.block, &builder.code_db),
+            Err(Error::UnsupportedOpcode(OpcodeId::TSTORE))
+        ));
+    }
+
+    #[test]
+    fn step_convert_unsupported_precompile() {
+        let step = ExecStep {
+            exec_state: ExecState::Precompile(PrecompileCalls::PointEvaluation),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ExecutionState::try_from(&step),
+            Err(Error::UnsupportedPrecompile(
+                PrecompileCalls::PointEvaluation
+            ))
+        ));
+    }
+}
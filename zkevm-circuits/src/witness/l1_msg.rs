@@ -86,6 +86,7 @@ mod tests {
             base_fee: 0x64,
             fee_overhead: 0x17d4,
             fee_scalar: 0x4a42fc80,
+            ..Default::default()
         };
 
         let expected = [(173usize, 0xfffe8u64), (140, 0xf3f2f)];
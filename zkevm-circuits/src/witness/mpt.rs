@@ -2,7 +2,8 @@ use crate::{
     evm_circuit::{util::rlc, witness::Rw},
     table::AccountFieldTag,
 };
-use eth_types::{Address, Field, ToLittleEndian, ToScalar, Word, U256};
+use bus_mapping::{state_db::StateDB, Error};
+use eth_types::{Address, Field, ToBigEndian, ToLittleEndian, ToScalar, ToWord, Word, H256, U256};
 use halo2_proofs::circuit::Value;
 use itertools::Itertools;
 use mpt_zktrie::{
@@ -11,7 +12,7 @@ use mpt_zktrie::{
     state::witness::WitnessGenerator,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub use state::ZktrieState;
 
@@ -283,11 +284,143 @@ impl MptUpdates {
             .collect()
     }
 
+    /// State roots after each tx, computed by applying the updates of the
+    /// rws of one tx at a time on `init_trie`, where `tx_rws` holds the rws of
+    /// each tx. The updates of a block are ordered by key instead, so they
+    /// only expose the states before and after the whole block.
+    pub(crate) fn tx_state_roots(tx_rws: &[Vec<Rw>], init_trie: &ZktrieState) -> Vec<Word> {
+        let mut root = U256::from_big_endian(init_trie.root());
+        let mut wit_gen = WitnessGenerator::from(init_trie);
+        let mut roots = Vec::with_capacity(tx_rws.len());
+        for rws in tx_rws {
+            let mut rws = rws
+                .iter()
+                .filter(|rw| key(rw).is_some())
+                .copied()
+                .collect_vec();
+            rws.sort_by_key(|rw| (key(rw), rw.rw_counter()));
+            let mut updates = Self::mock_from(&rws);
+            wit_gen = updates.fill_state_roots_from_generator(wit_gen);
+            if updates.len() > 0 {
+                root = updates.new_root;
+            }
+            roots.push(root);
+        }
+        roots
+    }
+
     fn insert(&mut self, update: MptUpdate) {
         self.updates.insert(update.key, update);
     }
 }
 
+/// Build the MPT updates of the transition from the state `pre` to the state
+/// `post`, without replaying the operations in between: each account field
+/// and storage slot whose committed value differs gets an update. `zktrie`
+/// must hold the state `pre`, so that the state roots of the updates can be
+/// computed, and `new_root` is the state root of `post`, e.g. from the block
+/// header. Fails if the computed state root differs from `new_root`.
+pub fn mpt_updates_from_statedb(
+    pre: &StateDB,
+    post: &StateDB,
+    zktrie: &ZktrieState,
+    new_root: Word,
+) -> Result<MptUpdates, Error> {
+    let mut updates = statedb_diff(pre, post);
+    updates.old_root = U256::from_big_endian(zktrie.root());
+    updates.new_root = new_root;
+    updates.fill_state_roots(zktrie);
+    // without updates, the state doesn't change
+    let computed_root = if updates.len() == 0 {
+        updates.old_root
+    } else {
+        updates.new_root
+    };
+    if computed_root != new_root {
+        return Err(Error::StateRootMismatch(
+            H256(new_root.to_be_bytes()),
+            H256(computed_root.to_be_bytes()),
+        ));
+    }
+    Ok(updates)
+}
+
+// The updates of the transition from `pre` to `post`, without state roots.
+fn statedb_diff(pre: &StateDB, post: &StateDB) -> MptUpdates {
+    let addresses: BTreeSet<Address> = pre
+        .accounts()
+        .chain(post.accounts())
+        .map(|(address, _)| *address)
+        .collect();
+
+    let mut updates = MptUpdates::default();
+    for address in addresses {
+        let (old_fields, new_fields) = (
+            account_fields(pre, &address),
+            account_fields(post, &address),
+        );
+        for ((field_tag, old_value), (_, new_value)) in old_fields.into_iter().zip(new_fields) {
+            if old_value != new_value {
+                updates.insert(MptUpdate {
+                    key: Key::Account { address, field_tag },
+                    old_value,
+                    new_value,
+                    old_root: Word::zero(),
+                    new_root: Word::zero(),
+                    original_rws: Vec::new(),
+                });
+            }
+        }
+
+        let storage_keys: BTreeSet<Word> = [pre, post]
+            .iter()
+            .flat_map(|sdb| sdb.get_account(&address).1.storage.keys().copied())
+            .collect();
+        for storage_key in storage_keys {
+            let old_value = *pre.get_committed_storage(&address, &storage_key).1;
+            let new_value = *post.get_committed_storage(&address, &storage_key).1;
+            if old_value != new_value {
+                updates.insert(MptUpdate {
+                    key: Key::AccountStorage {
+                        tx_id: 0,
+                        address,
+                        storage_key,
+                        exists: true,
+                    },
+                    old_value,
+                    new_value,
+                    old_root: Word::zero(),
+                    new_root: Word::zero(),
+                    original_rws: Vec::new(),
+                });
+            }
+        }
+    }
+
+    updates
+}
+
+// The account fields of `address` in the MptTable, which are all zero for a
+// dead account, i.e. absent from the state or empty, as it's encoded as a
+// non-existing account, see `StateDB::is_dead`.
+fn account_fields(sdb: &StateDB, address: &Address) -> [(AccountFieldTag, Word); 5] {
+    let (dead, account) = (sdb.is_dead(address), sdb.get_account(address).1);
+    let value = |value: Word| if dead { Word::zero() } else { value };
+    [
+        (AccountFieldTag::Nonce, value(account.nonce)),
+        (AccountFieldTag::Balance, value(account.balance)),
+        (
+            AccountFieldTag::KeccakCodeHash,
+            value(account.keccak_code_hash.to_word()),
+        ),
+        (
+            AccountFieldTag::CodeHash,
+            value(account.code_hash.to_word()),
+        ),
+        (AccountFieldTag::CodeSize, value(account.code_size)),
+    ]
+}
+
 impl MptUpdate {
     pub(crate) fn values(&self) -> (Word, Word) {
         (self.new_value, self.old_value)
@@ -447,7 +580,7 @@ fn value_prev(row: &Rw) -> Word {
 #[cfg(test)]
 mod test {
     use super::*;
-    use mpt_zktrie::state::builder::HASH_SCHEME_DONE;
+    use mpt_zktrie::state::{builder::HASH_SCHEME_DONE, ZkMemoryDb, ZkTrieHash};
 
     #[test]
     fn invalid_state_from_reading_nonce() {
@@ -1549,4 +1682,108 @@ mod test {
             serde_json::to_string_pretty(&updates.smt_traces.last().unwrap()).unwrap()
         );
     }
+
+    #[test]
+    fn mpt_updates_from_statedb_diff() {
+        assert!(*HASH_SCHEME_DONE,);
+
+        let address = Address::repeat_byte(0xaa);
+        // an empty account deleted at the end of a tx is already encoded as a
+        // non-existing one, so its deletion isn't an update
+        let empty_address = Address::repeat_byte(0xbb);
+        let mut pre = StateDB::new();
+        pre.set_account(&empty_address, bus_mapping::state_db::Account::zero());
+        let mut post = StateDB::new();
+        post.set_account(
+            &address,
+            bus_mapping::state_db::Account {
+                nonce: 1.into(),
+                balance: 100.into(),
+                ..bus_mapping::state_db::Account::zero()
+            },
+        );
+        post.set_storage(&address, &1.into(), &2.into());
+        post.commit_tx();
+
+        // the root of the state `post`, built by inserting its account and
+        // storage in an empty trie
+        let post_root = {
+            let mut db = ZkMemoryDb::default();
+            let mut storage = db.new_trie(&ZkTrieHash::default()).unwrap();
+            storage
+                .update_store(&U256::from(1).to_be_bytes(), &U256::from(2).to_be_bytes())
+                .unwrap();
+            let mut trie = db.new_trie(&ZkTrieHash::default()).unwrap();
+            let mut nonce_code_size = [0u8; 32];
+            nonce_code_size[31] = 1;
+            let account = bus_mapping::state_db::Account::zero();
+            trie.update_account(
+                address.as_bytes(),
+                &[
+                    nonce_code_size,
+                    U256::from(100).to_be_bytes(),
+                    storage.root(),
+                    account.keccak_code_hash.0,
+                    account.code_hash.0,
+                ],
+            )
+            .unwrap();
+            U256::from_big_endian(&trie.root())
+        };
+
+        let zktrie = ZktrieState::default();
+        let updates = mpt_updates_from_statedb(&pre, &post, &zktrie, post_root).unwrap();
+
+        // nonce, balance, keccak code hash, code hash and storage
+        assert_eq!(updates.len(), 5);
+        assert_eq!(updates.old_root(), U256::from_big_endian(zktrie.root()));
+        assert_eq!(updates.new_root(), post_root);
+        assert_ne!(updates.old_root(), updates.new_root());
+
+        // a root which isn't the one of `post`
+        assert!(matches!(
+            mpt_updates_from_statedb(&pre, &post, &zktrie, post_root + 1),
+            Err(Error::StateRootMismatch(expected, computed))
+                if expected == H256((post_root + 1).to_be_bytes())
+                    && computed == H256(post_root.to_be_bytes())
+        ));
+        // the state doesn't change from `pre` to itself
+        assert!(mpt_updates_from_statedb(&pre, &pre, &zktrie, post_root).is_err());
+        assert!(mpt_updates_from_statedb(
+            &pre,
+            &pre,
+            &zktrie,
+            U256::from_big_endian(zktrie.root())
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn tx_state_roots() {
+        assert!(*HASH_SCHEME_DONE,);
+
+        let balance_rw = |rw_counter: usize, value_prev: u64, value: u64| Rw::Account {
+            rw_counter,
+            is_write: true,
+            account_address: Address::repeat_byte(0xaa),
+            field_tag: AccountFieldTag::Balance,
+            value: value.into(),
+            value_prev: value_prev.into(),
+        };
+        let tx_rws = vec![
+            vec![balance_rw(1, 0, 10)],
+            vec![],
+            vec![balance_rw(2, 10, 20), balance_rw(3, 20, 30)],
+        ];
+        let zktrie = ZktrieState::default();
+        let roots = MptUpdates::tx_state_roots(&tx_rws, &zktrie);
+
+        let mut updates = MptUpdates::mock_from(&tx_rws.concat());
+        updates.fill_state_roots(&zktrie);
+
+        assert_eq!(roots.len(), 3);
+        assert_ne!(roots[0], updates.old_root());
+        assert_eq!(roots[0], roots[1]);
+        assert_eq!(roots[2], updates.new_root());
+    }
 }
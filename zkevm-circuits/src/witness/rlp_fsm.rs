@@ -4,6 +4,7 @@ use halo2_proofs::{arithmetic::FieldExt, circuit::Value, plonk::Expression};
 use strum_macros::EnumIter;
 
 use crate::util::Challenges;
+use std::{cmp::Ordering, collections::VecDeque};
 
 /// RLP tags
 #[derive(Default, Clone, Copy, Debug, EnumIter, PartialEq, Eq)]
@@ -67,6 +68,12 @@ pub enum Tag {
     // L1MsgHash
     /// Sender
     Sender,
+
+    // EIP-4844
+    /// Max fee per blob gas
+    MaxFeePerBlobGas,
+    /// Blob versioned hash
+    BlobVersionedHash,
 }
 
 impl From<Tag> for usize {
@@ -140,13 +147,14 @@ use crate::{
     witness::{
         l1_msg,
         Format::{
-            TxHashEip155, TxHashEip1559, TxHashPreEip155, TxSignEip155, TxSignEip1559,
-            TxSignPreEip155,
+            TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashEip4844, TxHashPreEip155,
+            TxSignEip155, TxSignEip1559, TxSignEip2930, TxSignEip4844, TxSignPreEip155,
         },
         Tag::{
-            AccessListAddress, AccessListStorageKey, BeginList, BeginVector, ChainId, Data,
-            EndList, EndVector, Gas, GasPrice, MaxFeePerGas, MaxPriorityFeePerGas, Nonce, SigR,
-            SigS, SigV, To, TxType, Value as TxValue, Zero1, Zero2,
+            AccessListAddress, AccessListStorageKey, BeginList, BeginVector, BlobVersionedHash,
+            ChainId, Data, EndList, EndVector, Gas, GasPrice, MaxFeePerBlobGas, MaxFeePerGas,
+            MaxPriorityFeePerGas, Nonce, SigR, SigS, SigV, To, TxType, Value as TxValue, Zero1,
+            Zero2,
         },
     },
 };
@@ -240,8 +248,84 @@ pub fn pre_eip155_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
         .collect()
 }
 
-pub fn eip1559_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
-    let rows = vec![
+/// The rows shared by the sign and hash formats of EIP-2930 txs, i.e. the tx
+/// type followed by the fields of the tx up to its access_list.
+fn eip2930_tx_common_rom_table_rows() -> Vec<(Tag, Tag, usize, Vec<usize>)> {
+    vec![
+        (TxType, BeginList, 1, vec![1]),
+        (BeginList, ChainId, N_BYTES_LIST, vec![2]),
+        (ChainId, Nonce, N_BYTES_U64, vec![3]),
+        (Nonce, GasPrice, N_BYTES_U64, vec![4]),
+        (GasPrice, Gas, N_BYTES_WORD, vec![5]),
+        (Gas, To, N_BYTES_U64, vec![6]),
+        (To, TxValue, N_BYTES_ACCOUNT_ADDRESS, vec![7]),
+        (TxValue, Data, N_BYTES_WORD, vec![8]),
+        (Data, BeginVector, N_BYTES_CALLDATA, vec![9, 10]),
+        (BeginVector, EndVector, N_BYTES_LIST, vec![20]), // access_list is none
+        (BeginVector, BeginList, N_BYTES_LIST, vec![11]),
+        (BeginList, AccessListAddress, N_BYTES_LIST, vec![12]),
+        (
+            AccessListAddress,
+            BeginVector,
+            N_BYTES_ACCOUNT_ADDRESS,
+            vec![13, 14],
+        ),
+        (BeginVector, EndVector, N_BYTES_LIST, vec![17]), /* access_list.storage_keys
+                                                           * is none */
+        (
+            BeginVector,
+            AccessListStorageKey,
+            N_BYTES_LIST,
+            vec![15, 16],
+        ),
+        (AccessListStorageKey, EndVector, N_BYTES_WORD, vec![17]), // finished parsing storage keys
+        (
+            AccessListStorageKey,
+            AccessListStorageKey,
+            N_BYTES_WORD,
+            vec![15, 16],
+        ), // keep parsing storage_keys
+        (EndVector, EndList, 0, vec![18, 19]),
+        (EndList, EndVector, 0, vec![20]), // finished parsing access_list
+        (EndList, BeginList, 0, vec![11]), // parse another access_list entry
+    ]
+}
+
+pub fn eip2930_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = eip2930_tx_common_rom_table_rows();
+    rows.extend(vec![
+        (EndVector, SigV, 0, vec![21]),
+        (SigV, SigR, N_BYTES_U64, vec![22]),
+        (SigR, SigS, N_BYTES_WORD, vec![23]),
+        (SigS, EndList, N_BYTES_WORD, vec![24]),
+        (EndList, EndList, 0, vec![25]),
+        // used to emit TxGasCostInL1
+        (EndList, BeginList, 0, vec![]),
+    ]);
+
+    rows.into_iter()
+        .map(|row| (row.0, row.1, row.2, TxHashEip2930, row.3).into())
+        .collect()
+}
+
+pub fn eip2930_tx_sign_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = eip2930_tx_common_rom_table_rows();
+    rows.extend(vec![
+        (EndVector, EndList, 0, vec![21]),
+        (EndList, EndList, 0, vec![22]),
+        // used to emit TxGasCostInL1
+        (EndList, BeginList, 0, vec![]),
+    ]);
+
+    rows.into_iter()
+        .map(|row| (row.0, row.1, row.2, TxSignEip2930, row.3).into())
+        .collect()
+}
+
+/// The rows shared by the sign and hash formats of EIP-1559 txs, i.e. the tx
+/// type followed by the fields of the tx up to its access_list.
+fn eip1559_tx_common_rom_table_rows() -> Vec<(Tag, Tag, usize, Vec<usize>)> {
+    vec![
         (TxType, BeginList, 1, vec![1]),
         (BeginList, ChainId, N_BYTES_LIST, vec![2]),
         (ChainId, Nonce, N_BYTES_U64, vec![3]),
@@ -279,6 +363,12 @@ pub fn eip1559_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
         (EndVector, EndList, 0, vec![19, 20]),
         (EndList, EndVector, 0, vec![21]), // finished parsing access_list
         (EndList, BeginList, 0, vec![12]), // parse another access_list entry
+    ]
+}
+
+pub fn eip1559_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = eip1559_tx_common_rom_table_rows();
+    rows.extend(vec![
         (EndVector, SigV, 0, vec![22]),
         (SigV, SigR, N_BYTES_U64, vec![23]),
         (SigR, SigS, N_BYTES_WORD, vec![24]),
@@ -286,7 +376,7 @@ pub fn eip1559_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
         (EndList, EndList, 0, vec![26]),
         // used to exit TxGasCostInL1
         (EndList, BeginList, 0, vec![]),
-    ];
+    ]);
 
     rows.into_iter()
         .map(|row| (row.0, row.1, row.2, TxHashEip1559, row.3).into())
@@ -294,50 +384,102 @@ pub fn eip1559_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
 }
 
 pub fn eip1559_tx_sign_rom_table_rows() -> Vec<RomTableRow> {
-    let rows = vec![
-        (BeginList, ChainId, N_BYTES_LIST, vec![1]),
-        (ChainId, Nonce, N_BYTES_U64, vec![2]),
-        (Nonce, MaxPriorityFeePerGas, N_BYTES_U64, vec![3]),
-        (MaxPriorityFeePerGas, MaxFeePerGas, N_BYTES_WORD, vec![4]),
-        (MaxFeePerGas, Gas, N_BYTES_WORD, vec![5]),
-        (Gas, To, N_BYTES_U64, vec![6]),
-        (To, TxValue, N_BYTES_ACCOUNT_ADDRESS, vec![7]),
-        (TxValue, Data, N_BYTES_WORD, vec![8]),
-        (Data, BeginVector, N_BYTES_CALLDATA, vec![9, 10]),
-        (BeginVector, EndVector, N_BYTES_LIST, vec![20]), // access_list is none
-        (BeginVector, BeginList, N_BYTES_LIST, vec![11]),
-        (BeginList, AccessListAddress, N_BYTES_LIST, vec![12]),
+    let mut rows = eip1559_tx_common_rom_table_rows();
+    rows.extend(vec![
+        (EndVector, EndList, 0, vec![22]),
+        (EndList, EndList, 0, vec![23]),
+        // used to emit TxGasCostInL1
+        (EndList, BeginList, 0, vec![]),
+    ]);
+
+    rows.into_iter()
+        .map(|row| (row.0, row.1, row.2, TxSignEip1559, row.3).into())
+        .collect()
+}
+
+/// The rows shared by the sign and hash formats of EIP-4844 txs, i.e. the
+/// EIP-1559 fields followed by max_fee_per_blob_gas and the non-empty vector
+/// of blob_versioned_hashes.
+fn eip4844_tx_common_rom_table_rows() -> Vec<(Tag, Tag, usize, Vec<usize>)> {
+    vec![
+        (TxType, BeginList, 1, vec![1]),
+        (BeginList, ChainId, N_BYTES_LIST, vec![2]),
+        (ChainId, Nonce, N_BYTES_U64, vec![3]),
+        (Nonce, MaxPriorityFeePerGas, N_BYTES_U64, vec![4]),
+        (MaxPriorityFeePerGas, MaxFeePerGas, N_BYTES_WORD, vec![5]),
+        (MaxFeePerGas, Gas, N_BYTES_WORD, vec![6]),
+        (Gas, To, N_BYTES_U64, vec![7]),
+        (To, TxValue, N_BYTES_ACCOUNT_ADDRESS, vec![8]),
+        (TxValue, Data, N_BYTES_WORD, vec![9]),
+        (Data, BeginVector, N_BYTES_CALLDATA, vec![10, 11]),
+        (BeginVector, EndVector, N_BYTES_LIST, vec![21]), // access_list is none
+        (BeginVector, BeginList, N_BYTES_LIST, vec![12]),
+        (BeginList, AccessListAddress, N_BYTES_LIST, vec![13]),
         (
             AccessListAddress,
             BeginVector,
             N_BYTES_ACCOUNT_ADDRESS,
-            vec![13, 14],
+            vec![14, 15],
         ),
-        (BeginVector, EndVector, N_BYTES_LIST, vec![17]), /* access_list.storage_keys is none */
+        (BeginVector, EndVector, N_BYTES_LIST, vec![18]), /* access_list.storage_keys
+                                                           * is none */
         (
             BeginVector,
             AccessListStorageKey,
             N_BYTES_LIST,
-            vec![15, 16],
+            vec![16, 17],
         ),
-        (AccessListStorageKey, EndVector, N_BYTES_WORD, vec![17]), // finished parsing storage keys
+        (AccessListStorageKey, EndVector, N_BYTES_WORD, vec![18]), // finished parsing storage keys
         (
             AccessListStorageKey,
             AccessListStorageKey,
             N_BYTES_WORD,
-            vec![15, 16],
+            vec![16, 17],
         ), // keep parsing storage_keys
-        (EndVector, EndList, 0, vec![18, 19]),
-        (EndList, EndVector, 0, vec![20]), // finished parsing access_list
-        (EndList, BeginList, 0, vec![11]), // parse another access_list entry
-        (EndVector, EndList, 0, vec![21]),
-        (EndList, EndList, 0, vec![22]),
+        (EndVector, EndList, 0, vec![19, 20]),
+        (EndList, EndVector, 0, vec![21]), // finished parsing access_list
+        (EndList, BeginList, 0, vec![12]), // parse another access_list entry
+        (EndVector, MaxFeePerBlobGas, 0, vec![22]),
+        (MaxFeePerBlobGas, BeginVector, N_BYTES_WORD, vec![23]),
+        (BeginVector, BlobVersionedHash, N_BYTES_LIST, vec![24, 25]),
+        (BlobVersionedHash, EndVector, N_BYTES_WORD, vec![26]), // finished parsing blob hashes
+        (
+            BlobVersionedHash,
+            BlobVersionedHash,
+            N_BYTES_WORD,
+            vec![24, 25],
+        ), // keep parsing blob hashes
+    ]
+}
+
+pub fn eip4844_tx_hash_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = eip4844_tx_common_rom_table_rows();
+    rows.extend(vec![
+        (EndVector, SigV, 0, vec![27]),
+        (SigV, SigR, N_BYTES_U64, vec![28]),
+        (SigR, SigS, N_BYTES_WORD, vec![29]),
+        (SigS, EndList, N_BYTES_WORD, vec![30]),
+        (EndList, EndList, 0, vec![31]),
         // used to emit TxGasCostInL1
         (EndList, BeginList, 0, vec![]),
-    ];
+    ]);
 
     rows.into_iter()
-        .map(|row| (row.0, row.1, row.2, TxSignEip1559, row.3).into())
+        .map(|row| (row.0, row.1, row.2, TxHashEip4844, row.3).into())
+        .collect()
+}
+
+pub fn eip4844_tx_sign_rom_table_rows() -> Vec<RomTableRow> {
+    let mut rows = eip4844_tx_common_rom_table_rows();
+    rows.extend(vec![
+        (EndVector, EndList, 0, vec![27]),
+        (EndList, EndList, 0, vec![28]),
+        // used to emit TxGasCostInL1
+        (EndList, BeginList, 0, vec![]),
+    ]);
+
+    rows.into_iter()
+        .map(|row| (row.0, row.1, row.2, TxSignEip4844, row.3).into())
         .collect()
 }
 
@@ -395,6 +537,14 @@ pub enum Format {
     TxHashEip1559,
     /// L1 Msg
     L1MsgHash,
+    /// Sign for EIP4844 tx
+    TxSignEip4844,
+    /// Hash for EIP4844 tx
+    TxHashEip4844,
+    /// Sign for EIP2930 tx
+    TxSignEip2930,
+    /// Hash for EIP2930 tx
+    TxHashEip2930,
 }
 
 impl From<Format> for usize {
@@ -414,6 +564,10 @@ impl Format {
             TxSignEip1559 => eip1559_tx_sign_rom_table_rows(),
             TxHashEip1559 => eip1559_tx_hash_rom_table_rows(),
             Self::L1MsgHash => l1_msg::rom_table_rows(),
+            TxSignEip4844 => eip4844_tx_sign_rom_table_rows(),
+            TxHashEip4844 => eip4844_tx_hash_rom_table_rows(),
+            TxSignEip2930 => eip2930_tx_sign_rom_table_rows(),
+            TxHashEip2930 => eip2930_tx_hash_rom_table_rows(),
         }
     }
 }
@@ -549,8 +703,16 @@ pub struct RlpFsmWitnessRow<F: FieldExt> {
 /// The RlpFsmWitnessGen trait is implemented by data types who's RLP encoding can
 /// be verified by the RLP-encoding circuit.
 pub trait RlpFsmWitnessGen<F: FieldExt>: Sized {
+    /// Stream the witness to the RLP state machine, row by row.
+    fn sm_witness<'a>(
+        &'a self,
+        challenges: &Challenges<Value<F>>,
+    ) -> Box<dyn Iterator<Item = RlpFsmWitnessRow<F>> + 'a>;
+
     /// Generate witness to the RLP state machine, as a vector of RlpFsmWitnessRow.
-    fn gen_sm_witness(&self, challenges: &Challenges<Value<F>>) -> Vec<RlpFsmWitnessRow<F>>;
+    fn gen_sm_witness(&self, challenges: &Challenges<Value<F>>) -> Vec<RlpFsmWitnessRow<F>> {
+        self.sm_witness(challenges).collect()
+    }
 
     /// Generate witness to the Data table that RLP circuit does lookup into.
     fn gen_data_table(&self, challenges: &Challenges<Value<F>>) -> Vec<DataTable<F>>;
@@ -568,3 +730,397 @@ pub(crate) struct SmState<F: Field> {
     pub(crate) tag_length: usize,
     pub(crate) tag_value_acc: Value<F>,
 }
+
+/// Streams the rows of the RLP state machine decoding the RLP bytes of a tx.
+///
+/// The RLC and the gas cost of the bytes are accumulated as the bytes are
+/// read. A row is yielded as soon as the ROM table row of its tag is known,
+/// which is at the first row of the tag except for the elements of a vector,
+/// whose rows are held back until the end of the element tells whether the
+/// vector continues.
+pub struct RlpFsm<'a, F: Field> {
+    tx_id: u64,
+    format: Format,
+    rlp_bytes: &'a [u8],
+    rom_table: Vec<RomTableRow>,
+    keccak_rand: Value<F>,
+    word_rand: Value<F>,
+    cur: SmState<F>,
+    // The candidate ROM table rows of the current tag.
+    cur_rom_row: Vec<usize>,
+    // The bytes left to decode in each layer.
+    remaining_bytes: Vec<usize>,
+    lb_len: usize,
+    // Number of bytes accumulated into `bytes_rlc` and `gas_cost_acc`.
+    acc_len: usize,
+    bytes_rlc: Value<F>,
+    gas_cost_acc: Value<F>,
+    // Rows of the current tag waiting for its ROM table row.
+    pending: Vec<RlpFsmWitnessRow<F>>,
+    ready: VecDeque<RlpFsmWitnessRow<F>>,
+    done: bool,
+}
+
+impl<'a, F: Field> RlpFsm<'a, F> {
+    /// Start decoding `rlp_bytes` in `format`, for the tx `tx_id`.
+    pub fn new(
+        tx_id: u64,
+        format: Format,
+        rlp_bytes: &'a [u8],
+        challenges: &Challenges<Value<F>>,
+    ) -> Self {
+        let rom_table = format.rom_table_rows();
+        Self {
+            tx_id,
+            format,
+            rlp_bytes,
+            keccak_rand: challenges.keccak_input(),
+            word_rand: challenges.evm_word(),
+            cur: SmState {
+                tag: rom_table[0].tag,
+                state: State::DecodeTagStart,
+                tag_idx: 0,
+                tag_length: 0,
+                tag_value_acc: Value::known(F::zero()),
+                byte_idx: 0,
+                depth: 0,
+            },
+            rom_table,
+            cur_rom_row: vec![0],
+            remaining_bytes: vec![rlp_bytes.len()],
+            lb_len: 0,
+            acc_len: 0,
+            bytes_rlc: Value::known(F::zero()),
+            gas_cost_acc: Value::known(F::zero()),
+            pending: vec![],
+            ready: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// The RLC and the gas cost of the bytes up to `byte_idx`.
+    fn accumulate(&mut self, byte_idx: usize) -> (Value<F>, Value<F>) {
+        while self.acc_len <= byte_idx {
+            let byte = self.rlp_bytes[self.acc_len];
+            let cost = if byte == 0 { 4 } else { 16 };
+            self.bytes_rlc = self.bytes_rlc * self.keccak_rand + Value::known(F::from(byte as u64));
+            self.gas_cost_acc = self.gas_cost_acc + Value::known(F::from(cost));
+            self.acc_len += 1;
+        }
+        (self.bytes_rlc, self.gas_cost_acc)
+    }
+
+    /// Decode the next row. Return it with the ROM table row of its tag if
+    /// the row ends the tag.
+    fn decode_row(&mut self) -> (RlpFsmWitnessRow<F>, Option<usize>) {
+        let rlp_bytes = self.rlp_bytes;
+        let rom_table = &self.rom_table;
+        let remaining_bytes = &mut self.remaining_bytes;
+        let cur = &mut self.cur;
+        let lb_len = &mut self.lb_len;
+
+        // default behavior
+        let mut is_none = false;
+        let mut is_output = false;
+        let mut rlp_tag = RlpTag::Tag(cur.tag);
+
+        let mut next = cur.clone();
+        match cur.state {
+            State::DecodeTagStart => {
+                if cur.tag.is_end() {
+                    // assertions
+                    assert_eq!(
+                        remaining_bytes
+                            .pop()
+                            .expect("remaining_bytes shall not be empty"),
+                        0
+                    );
+                    if cur.depth == 1 {
+                        assert_eq!(remaining_bytes.len(), 1);
+                        assert_eq!(remaining_bytes[0], 0);
+                        assert_eq!(cur.byte_idx, rlp_bytes.len() - 1);
+                        is_output = true;
+                        rlp_tag = RlpTag::RLC;
+                    } else if cur.depth == 0 {
+                        // emit GasCost
+                        is_output = true;
+                        rlp_tag = RlpTag::GasCost;
+                    }
+
+                    // state transitions
+                    // if cur.depth == 0 then we are at the end of decoding
+                    if cur.depth > 0 {
+                        next.depth = cur.depth - 1;
+                    }
+                    next.state = State::DecodeTagStart;
+                } else {
+                    let byte_value = rlp_bytes[cur.byte_idx];
+                    if let Some(rem) = remaining_bytes.last_mut() {
+                        // read one more byte
+                        assert!(*rem >= 1);
+                        *rem -= 1;
+                    }
+                    if byte_value < 0x80 {
+                        // assertions
+                        assert!(!cur.tag.is_list());
+                        is_output = true;
+                        cur.tag_value_acc = Value::known(F::from(byte_value as u64));
+
+                        // state transitions
+                        next.state = State::DecodeTagStart;
+                    } else if byte_value == 0x80 {
+                        // assertions
+                        assert!(!cur.tag.is_list());
+                        is_output = true;
+                        is_none = true;
+                        cur.tag_value_acc = Value::known(F::zero());
+
+                        // state transitions
+                        next.state = State::DecodeTagStart;
+                    } else if byte_value < 0xb8 {
+                        // assertions
+                        assert!(!cur.tag.is_list());
+
+                        // state transitions
+                        next.tag_idx = 1;
+                        next.tag_length = (byte_value - 0x80) as usize;
+                        next.tag_value_acc =
+                            Value::known(F::from(rlp_bytes[cur.byte_idx + 1] as u64));
+                        next.state = State::Bytes;
+                    } else if byte_value < 0xc0 {
+                        // assertions
+                        assert!(!cur.tag.is_list());
+
+                        // state transitions
+                        next.tag_idx = 1;
+                        next.tag_length = (byte_value - 0xb7) as usize;
+                        *lb_len = rlp_bytes[cur.byte_idx + 1] as usize;
+                        next.tag_value_acc = Value::known(F::from(*lb_len as u64));
+                        next.state = State::LongBytes;
+                    } else if byte_value < 0xf8 {
+                        // assertions
+                        assert!(cur.tag.is_begin());
+                        if cur.depth == 0 {
+                            is_output = true;
+                            rlp_tag = RlpTag::Len;
+                        }
+                        cur.tag_value_acc = Value::known(F::from(u64::from(byte_value - 0xc0)));
+
+                        // state transitions
+                        let num_bytes_of_new_list = usize::from(byte_value - 0xc0);
+                        if let Some(rem) = remaining_bytes.last_mut() {
+                            // Since we are going to decode a new list inside current list,
+                            // after that the remaining bytes of
+                            // current list should be subtracted by
+                            // the number of bytes of the new list.
+                            assert!(*rem >= num_bytes_of_new_list);
+                            *rem -= num_bytes_of_new_list;
+                        }
+                        remaining_bytes.push(num_bytes_of_new_list);
+                        next.depth = cur.depth + 1;
+                        next.state = State::DecodeTagStart;
+                    } else {
+                        // assertions
+                        assert!(cur.tag.is_begin());
+                        // TODO: assert first leading byte is non-zero
+
+                        // state transitions
+                        next.tag_idx = 1;
+                        next.tag_length = (byte_value - 0xf7) as usize;
+                        *lb_len = rlp_bytes[cur.byte_idx + 1] as usize;
+                        next.tag_value_acc = Value::known(F::from(*lb_len as u64));
+                        next.state = State::LongList;
+                    }
+                }
+            }
+            State::Bytes => {
+                if let Some(rem) = remaining_bytes.last_mut() {
+                    assert!(*rem >= 1);
+                    *rem -= 1;
+                }
+                if cur.tag_idx < cur.tag_length {
+                    // state transitions
+                    let max_length = rom_table[self.cur_rom_row[0]].max_length;
+                    let b = match max_length.cmp(&32) {
+                        Ordering::Less => Value::known(F::from(256_u64)),
+                        Ordering::Equal => self.word_rand,
+                        Ordering::Greater => self.keccak_rand,
+                    };
+                    next.tag_idx = cur.tag_idx + 1;
+                    next.tag_value_acc = cur.tag_value_acc * b
+                        + Value::known(F::from(rlp_bytes[cur.byte_idx + 1] as u64));
+                } else {
+                    // assertions
+                    is_output = true;
+
+                    // state transitions
+                    next.state = State::DecodeTagStart;
+                }
+            }
+            State::LongBytes => {
+                if let Some(rem) = remaining_bytes.last_mut() {
+                    assert!(*rem >= 1);
+                    *rem -= 1;
+                }
+
+                if cur.tag_idx < cur.tag_length {
+                    // state transitions
+                    next.tag_idx = cur.tag_idx + 1;
+                    *lb_len = *lb_len * 256 + usize::from(rlp_bytes[cur.byte_idx + 1]);
+                    next.tag_value_acc = Value::known(F::from(*lb_len as u64));
+                } else {
+                    // we're dealing with case cur.tag_idx == cur.tag_length
+
+                    // state transitions
+                    next.tag_idx = 1;
+                    next.tag_length = *lb_len;
+                    next.tag_value_acc =
+                        Value::known(F::from(u64::from(rlp_bytes[cur.byte_idx + 1])));
+                    next.state = State::Bytes;
+                }
+            }
+            State::LongList => {
+                if let Some(rem) = remaining_bytes.last_mut() {
+                    // read one more byte
+                    assert!(*rem >= 1);
+                    *rem -= 1;
+                }
+                if cur.tag_idx < cur.tag_length {
+                    // state transitions
+                    next.tag_idx = cur.tag_idx + 1;
+                    *lb_len = *lb_len * 256 + usize::from(rlp_bytes[cur.byte_idx + 1]);
+                    next.tag_value_acc = Value::known(F::from(*lb_len as u64));
+                } else {
+                    // assertions
+                    if cur.depth == 0 {
+                        is_output = true;
+                        rlp_tag = RlpTag::Len;
+                    }
+                    if let Some(rem) = remaining_bytes.last_mut() {
+                        assert!(*rem >= *lb_len);
+                        *rem -= *lb_len;
+                    }
+                    remaining_bytes.push(*lb_len);
+                    next.depth = cur.depth + 1;
+                    next.state = State::DecodeTagStart;
+                }
+            }
+            State::End => {
+                unreachable!()
+            }
+        }
+
+        let mut tag_rom_row = None;
+        if next.state == State::DecodeTagStart {
+            // we finished parsing current tag
+            let row = if self.cur_rom_row.len() == 1 {
+                self.cur_rom_row[0]
+            } else if self.cur_rom_row.len() == 2 {
+                // only cur_rom_row[0].tag_next is EndVector.
+                assert_eq!(rom_table[self.cur_rom_row[0]].tag_next, Tag::EndVector);
+
+                let rem = remaining_bytes.last().expect("");
+                if *rem == 0 {
+                    // we have finished parsing the vector.
+                    self.cur_rom_row[0]
+                } else {
+                    // we have not finished parsing the vector.
+                    self.cur_rom_row[1]
+                }
+            } else {
+                unreachable!()
+            };
+
+            assert_eq!(cur.tag, rom_table[row].tag);
+
+            tag_rom_row = Some(row);
+            next.tag = rom_table[row].tag_next;
+            self.cur_rom_row = rom_table[row].tag_next_idx.clone();
+
+            if next.tag.is_end() {
+                // Since the EndList or EndVector tag does not read any byte from the data
+                // table.
+                next.byte_idx = cur.byte_idx;
+            } else {
+                next.byte_idx = cur.byte_idx + 1;
+            }
+        } else {
+            // next.state is one of { Bytes, LongBytes, LongList }
+            // the sm in these states need to read new byte from data table
+            next.byte_idx = cur.byte_idx + 1;
+        }
+
+        assert!(cur.byte_idx < rlp_bytes.len());
+        let cur = cur.clone();
+        let (bytes_rlc, gas_cost_acc) = self.accumulate(cur.byte_idx);
+
+        let tag_value = match rlp_tag {
+            RlpTag::Len => cur.tag_value_acc + Value::known(F::from((cur.byte_idx + 1) as u64)),
+            RlpTag::RLC => bytes_rlc,
+            RlpTag::GasCost => gas_cost_acc,
+            RlpTag::Tag(_) => cur.tag_value_acc,
+            RlpTag::Null => unreachable!("Null is not used"),
+        };
+
+        let row = RlpFsmWitnessRow {
+            rlp_table: RlpTable {
+                tx_id: self.tx_id,
+                format: self.format,
+                rlp_tag,
+                tag_value,
+                is_output,
+                is_none,
+            },
+            state_machine: StateMachine {
+                state: cur.state,
+                tag: cur.tag,
+                max_length: Default::default(), // filled up with the ROM table row
+                tag_next: Default::default(),   // filled up with the ROM table row
+                byte_idx: cur.byte_idx + 1,
+                byte_rev_idx: rlp_bytes.len() - cur.byte_idx,
+                byte_value: rlp_bytes[cur.byte_idx],
+                tag_idx: cur.tag_idx,
+                tag_length: cur.tag_length,
+                tag_acc_value: cur.tag_value_acc,
+                depth: cur.depth,
+                bytes_rlc,
+                gas_cost_acc,
+            },
+        };
+
+        if cur.tag == Tag::EndList && cur.depth == 0 {
+            self.done = true;
+        }
+        self.cur = next;
+
+        (row, tag_rom_row)
+    }
+}
+
+impl<'a, F: Field> Iterator for RlpFsm<'a, F> {
+    type Item = RlpFsmWitnessRow<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.ready.pop_front() {
+                return Some(row);
+            }
+            if self.done {
+                return None;
+            }
+            // the ROM table row is known before the end of the tag unless
+            // the tag may end a vector.
+            let known_rom_row = (self.cur_rom_row.len() == 1).then(|| self.cur_rom_row[0]);
+            let (row, tag_rom_row) = self.decode_row();
+            self.pending.push(row);
+            if let Some(rom_row) = tag_rom_row.or(known_rom_row) {
+                let rom_row = &self.rom_table[rom_row];
+                for mut row in self.pending.drain(..) {
+                    row.state_machine.tag_next = rom_row.tag_next;
+                    row.state_machine.max_length = rom_row.max_length;
+                    self.ready.push_back(row);
+                }
+            }
+        }
+    }
+}
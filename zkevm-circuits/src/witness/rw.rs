@@ -113,15 +113,24 @@ impl RwMap {
     }
     /// Prepad Rw::Start rows to target length
     pub fn table_assignments_prepad(rows: &[Rw], target_len: usize) -> (Vec<Rw>, usize) {
+        let (rows, padding_length) = Self::table_assignments_prepad_iter(rows, target_len);
+        (rows.collect(), padding_length)
+    }
+    /// Same as [`Self::table_assignments_prepad`], but yields the rows lazily
+    /// so that they are never all held in memory at once.
+    pub fn table_assignments_prepad_iter(
+        rows: &[Rw],
+        target_len: usize,
+    ) -> (impl Iterator<Item = Rw> + '_, usize) {
         // Remove Start rows as we will add them from scratch.
-        let rows: Vec<Rw> = rows
+        let num_start_rows = rows
             .iter()
-            .skip_while(|rw| matches!(rw, Rw::Start { .. }))
-            .cloned()
-            .collect();
+            .take_while(|rw| matches!(rw, Rw::Start { .. }))
+            .count();
+        let rows = &rows[num_start_rows..];
         let padding_length = Self::padding_len(rows.len(), target_len);
         let padding = (1..=padding_length).map(|rw_counter| Rw::Start { rw_counter });
-        (padding.chain(rows.into_iter()).collect(), padding_length)
+        (padding.chain(rows.iter().copied()), padding_length)
     }
     /// Build Rws for assignment
     pub fn table_assignments(&self) -> Vec<Rw> {
@@ -138,6 +147,28 @@ impl RwMap {
         });
         rows
     }
+    /// Running fingerprints of the multiset of `rows`, skipping Start rows:
+    /// the i-th element is `prod_{j <= i} (alpha - rlc(row_j, gamma))`, where
+    /// the row values are assigned with the word `randomness` as in the rw
+    /// table. The last element is independent of the order of the rows, so
+    /// the fingerprints of consecutive chunks can be multiplied and compared
+    /// with the fingerprint of the rws of the whole batch.
+    pub fn fingerprints<F: Field>(rows: &[Rw], randomness: F, alpha: F, gamma: F) -> Vec<F> {
+        rows.iter()
+            .filter(|rw| !matches!(rw, Rw::Start { .. }))
+            .scan(F::one(), |acc, rw| {
+                *acc *= alpha - rw.table_assignment_aux(randomness).rlc(gamma);
+                Some(*acc)
+            })
+            .collect()
+    }
+    /// Fingerprint of the multiset of `rows`, see [`Self::fingerprints`].
+    pub fn fingerprint<F: Field>(rows: &[Rw], randomness: F, alpha: F, gamma: F) -> F {
+        Self::fingerprints(rows, randomness, alpha, gamma)
+            .last()
+            .copied()
+            .unwrap_or_else(F::one)
+    }
 }
 
 /// Read-write records in execution. Rws are used for connecting evm circuit and
@@ -155,6 +186,24 @@ pub enum Rw {
         is_warm: bool,
         is_warm_prev: bool,
     },
+    /// TxCreatedAccount
+    TxCreatedAccount {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        is_created: bool,
+        is_created_prev: bool,
+    },
+    /// TxDestructedAccount
+    TxDestructedAccount {
+        rw_counter: usize,
+        is_write: bool,
+        tx_id: usize,
+        account_address: Address,
+        is_destructed: bool,
+        is_destructed_prev: bool,
+    },
     /// TxAccessListAccountStorage
     TxAccessListAccountStorage {
         rw_counter: usize,
@@ -304,6 +353,28 @@ impl Rw {
         }
     }
 
+    pub fn tx_created_account_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::TxCreatedAccount {
+                is_created,
+                is_created_prev,
+                ..
+            } => (*is_created, *is_created_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
+    pub fn tx_destructed_account_value_pair(&self) -> (bool, bool) {
+        match self {
+            Self::TxDestructedAccount {
+                is_destructed,
+                is_destructed_prev,
+                ..
+            } => (*is_destructed, *is_destructed_prev),
+            _ => unreachable!("{:?}", self),
+        }
+    }
+
     pub fn tx_refund_value_pair(&self) -> (u64, u64) {
         match self {
             Self::TxRefund {
@@ -471,6 +542,8 @@ impl Rw {
             | Self::Stack { rw_counter, .. }
             | Self::AccountStorage { rw_counter, .. }
             | Self::TxAccessListAccount { rw_counter, .. }
+            | Self::TxCreatedAccount { rw_counter, .. }
+            | Self::TxDestructedAccount { rw_counter, .. }
             | Self::TxAccessListAccountStorage { rw_counter, .. }
             | Self::TxRefund { rw_counter, .. }
             | Self::Account { rw_counter, .. }
@@ -487,6 +560,8 @@ impl Rw {
             | Self::Stack { is_write, .. }
             | Self::AccountStorage { is_write, .. }
             | Self::TxAccessListAccount { is_write, .. }
+            | Self::TxCreatedAccount { is_write, .. }
+            | Self::TxDestructedAccount { is_write, .. }
             | Self::TxAccessListAccountStorage { is_write, .. }
             | Self::TxRefund { is_write, .. }
             | Self::Account { is_write, .. }
@@ -503,6 +578,8 @@ impl Rw {
             Self::Stack { .. } => RwTableTag::Stack,
             Self::AccountStorage { .. } => RwTableTag::AccountStorage,
             Self::TxAccessListAccount { .. } => RwTableTag::TxAccessListAccount,
+            Self::TxCreatedAccount { .. } => RwTableTag::TxCreatedAccount,
+            Self::TxDestructedAccount { .. } => RwTableTag::TxDestructedAccount,
             Self::TxAccessListAccountStorage { .. } => RwTableTag::TxAccessListAccountStorage,
             Self::TxRefund { .. } => RwTableTag::TxRefund,
             Self::Account { .. } => RwTableTag::Account,
@@ -516,6 +593,8 @@ impl Rw {
         match self {
             Self::AccountStorage { tx_id, .. }
             | Self::TxAccessListAccount { tx_id, .. }
+            | Self::TxCreatedAccount { tx_id, .. }
+            | Self::TxDestructedAccount { tx_id, .. }
             | Self::TxAccessListAccountStorage { tx_id, .. }
             | Self::TxRefund { tx_id, .. }
             | Self::TxLog { tx_id, .. }
@@ -532,6 +611,12 @@ impl Rw {
             Self::TxAccessListAccount {
                 account_address, ..
             }
+            | Self::TxCreatedAccount {
+                account_address, ..
+            }
+            | Self::TxDestructedAccount {
+                account_address, ..
+            }
             | Self::TxAccessListAccountStorage {
                 account_address, ..
             }
@@ -573,6 +658,8 @@ impl Rw {
             | Self::Memory { .. }
             | Self::Stack { .. }
             | Self::TxAccessListAccount { .. }
+            | Self::TxCreatedAccount { .. }
+            | Self::TxDestructedAccount { .. }
             | Self::TxAccessListAccountStorage { .. }
             | Self::TxRefund { .. }
             | Self::TxLog { .. } => None,
@@ -590,6 +677,8 @@ impl Rw {
             | Self::TxRefund { .. }
             | Self::Account { .. }
             | Self::TxAccessListAccount { .. }
+            | Self::TxCreatedAccount { .. }
+            | Self::TxDestructedAccount { .. }
             | Self::TxLog { .. }
             | Self::TxReceipt { .. } => None,
         }
@@ -646,6 +735,8 @@ impl Rw {
 
             Self::TxAccessListAccount { is_warm, .. }
             | Self::TxAccessListAccountStorage { is_warm, .. } => F::from(*is_warm as u64),
+            Self::TxCreatedAccount { is_created, .. } => F::from(*is_created as u64),
+            Self::TxDestructedAccount { is_destructed, .. } => F::from(*is_destructed as u64),
             Self::Memory { value, .. } => rlc::value(&value.to_le_bytes(), randomness),
             Self::TxRefund { value, .. } | Self::TxReceipt { value, .. } => F::from(*value),
         }
@@ -682,6 +773,12 @@ impl Rw {
             | Self::TxAccessListAccountStorage { is_warm_prev, .. } => {
                 Some(F::from(*is_warm_prev as u64))
             }
+            Self::TxCreatedAccount {
+                is_created_prev, ..
+            } => Some(F::from(*is_created_prev as u64)),
+            Self::TxDestructedAccount {
+                is_destructed_prev, ..
+            } => Some(F::from(*is_destructed_prev as u64)),
             Self::TxRefund { value_prev, .. } => Some(F::from(*value_prev)),
             Self::Start { .. }
             | Self::Stack { .. }
@@ -730,6 +827,36 @@ impl From<&operation::OperationContainer> for RwMap {
                 })
                 .collect(),
         );
+        rws.insert(
+            RwTableTag::TxCreatedAccount,
+            container
+                .tx_created_account
+                .iter()
+                .map(|op| Rw::TxCreatedAccount {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    is_created: op.op().is_created,
+                    is_created_prev: op.op().is_created_prev,
+                })
+                .collect(),
+        );
+        rws.insert(
+            RwTableTag::TxDestructedAccount,
+            container
+                .tx_destructed_account
+                .iter()
+                .map(|op| Rw::TxDestructedAccount {
+                    rw_counter: op.rwc().into(),
+                    is_write: op.rw().is_write(),
+                    tx_id: op.op().tx_id,
+                    account_address: op.op().address,
+                    is_destructed: op.op().is_destructed,
+                    is_destructed_prev: op.op().is_destructed_prev,
+                })
+                .collect(),
+        );
         rws.insert(
             RwTableTag::TxAccessListAccountStorage,
             container
@@ -7,6 +7,7 @@ use bus_mapping::{
     evm::OpcodeId,
     operation,
     precompile::{PrecompileAuxData, PrecompileCalls},
+    Error,
 };
 
 use crate::{
@@ -52,6 +53,9 @@ pub struct ExecStep {
     pub block_num: u64,
     /// Aux data used for precompiles
     pub aux_data: Option<PrecompileAuxData>,
+    /// Wall-clock time spent generating the step in bus-mapping
+    #[cfg(feature = "step-profile")]
+    pub elapsed: std::time::Duration,
 }
 
 impl ExecStep {
@@ -100,12 +104,7 @@ impl From<&ExecError> for ExecutionState {
             ExecError::PrecompileFailed => ExecutionState::ErrorPrecompileFailed,
             ExecError::OutOfGas(oog_error) => match oog_error {
                 OogError::Constant => ExecutionState::ErrorOutOfGasConstant,
-                OogError::StaticMemoryExpansion => {
-                    ExecutionState::ErrorOutOfGasStaticMemoryExpansion
-                }
-                OogError::DynamicMemoryExpansion => {
-                    ExecutionState::ErrorOutOfGasDynamicMemoryExpansion
-                }
+                OogError::MemoryExpansion => ExecutionState::ErrorOutOfGasMemoryExpansion,
                 OogError::MemoryCopy => ExecutionState::ErrorOutOfGasMemoryCopy,
                 OogError::AccountAccess => ExecutionState::ErrorOutOfGasAccountAccess,
                 OogError::CodeStore => ExecutionState::ErrorCodeStore,
@@ -121,32 +120,27 @@ impl From<&ExecError> for ExecutionState {
     }
 }
 
-impl From<&circuit_input_builder::ExecStep> for ExecutionState {
-    fn from(step: &circuit_input_builder::ExecStep) -> Self {
+impl TryFrom<&circuit_input_builder::ExecStep> for ExecutionState {
+    type Error = Error;
+
+    fn try_from(step: &circuit_input_builder::ExecStep) -> Result<Self, Self::Error> {
         if let Some(error) = step.error.as_ref() {
             log::debug!("step err {:?}", error);
-            return error.into();
+            return Ok(error.into());
         }
-        match step.exec_state {
+        Ok(match step.exec_state {
             circuit_input_builder::ExecState::Op(op) => {
                 if op.is_dup() {
-                    return ExecutionState::DUP;
+                    return Ok(ExecutionState::DUP);
                 }
                 if op.is_push() {
-                    return ExecutionState::PUSH;
+                    return Ok(ExecutionState::PUSH);
                 }
                 if op.is_swap() {
-                    return ExecutionState::SWAP;
+                    return Ok(ExecutionState::SWAP);
                 }
                 if op.is_log() {
-                    return ExecutionState::LOG;
-                }
-
-                macro_rules! dummy {
-                    ($name:expr) => {{
-                        log::warn!("{:?} is implemented with DummyGadget", $name);
-                        $name
-                    }};
+                    return Ok(ExecutionState::LOG);
                 }
 
                 match op {
@@ -171,6 +165,7 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::MLOAD => ExecutionState::MEMORY,
                     OpcodeId::MSTORE => ExecutionState::MEMORY,
                     OpcodeId::MSTORE8 => ExecutionState::MEMORY,
+                    OpcodeId::MCOPY => ExecutionState::MCOPY,
                     OpcodeId::JUMPDEST => ExecutionState::JUMPDEST,
                     OpcodeId::JUMP => ExecutionState::JUMP,
                     OpcodeId::JUMPI => ExecutionState::JUMPI,
@@ -197,6 +192,8 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::CALLDATASIZE => ExecutionState::CALLDATASIZE,
                     OpcodeId::CALLDATACOPY => ExecutionState::CALLDATACOPY,
                     OpcodeId::CHAINID => ExecutionState::CHAINID,
+                    OpcodeId::BLOBHASH => ExecutionState::BLOBHASH,
+                    OpcodeId::BLOBBASEFEE => ExecutionState::BLOBBASEFEE,
                     OpcodeId::ISZERO => ExecutionState::ISZERO,
                     OpcodeId::CALL
                     | OpcodeId::CALLCODE
@@ -212,9 +209,8 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                     OpcodeId::RETURNDATACOPY => ExecutionState::RETURNDATACOPY,
                     OpcodeId::CREATE => ExecutionState::CREATE,
                     OpcodeId::CREATE2 => ExecutionState::CREATE2,
-                    // dummy ops
-                    OpcodeId::SELFDESTRUCT => dummy!(ExecutionState::SELFDESTRUCT),
-                    _ => unimplemented!("unimplemented opcode {:?}", op),
+                    OpcodeId::SELFDESTRUCT => ExecutionState::SELFDESTRUCT,
+                    _ => return Err(Error::UnsupportedOpcode(op)),
                 }
             }
             circuit_input_builder::ExecState::Precompile(precompile) => match precompile {
@@ -227,16 +223,29 @@ impl From<&circuit_input_builder::ExecStep> for ExecutionState {
                 PrecompileCalls::Bn128Mul => ExecutionState::PrecompileBn256ScalarMul,
                 PrecompileCalls::Bn128Pairing => ExecutionState::PrecompileBn256Pairing,
                 PrecompileCalls::Blake2F => ExecutionState::PrecompileBlake2f,
+                // no KZG sub-circuit checks a successful point evaluation yet,
+                // failed calls go through ErrorPrecompileFailed
+                PrecompileCalls::PointEvaluation => {
+                    return Err(Error::UnsupportedPrecompile(precompile))
+                }
             },
             circuit_input_builder::ExecState::BeginTx => ExecutionState::BeginTx,
+            circuit_input_builder::ExecState::DestructAccount => ExecutionState::DestructAccount,
             circuit_input_builder::ExecState::EndTx => ExecutionState::EndTx,
+            circuit_input_builder::ExecState::EndInnerBlock => ExecutionState::EndInnerBlock,
             circuit_input_builder::ExecState::EndBlock => ExecutionState::EndBlock,
-        }
+        })
     }
 }
 
-pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u64) -> ExecStep {
-    ExecStep {
+/// Convert a step of the builder, which fails if the EVM circuit can't prove
+/// it.
+pub(super) fn step_convert(
+    step: &circuit_input_builder::ExecStep,
+    block_num: u64,
+) -> Result<ExecStep, Error> {
+    let execution_state = ExecutionState::try_from(step)?;
+    Ok(ExecStep {
         call_index: step.call_index,
         rw_indices: step
             .bus_mapping_instance
@@ -247,6 +256,8 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
                     operation::Target::Stack => RwTableTag::Stack,
                     operation::Target::Storage => RwTableTag::AccountStorage,
                     operation::Target::TxAccessListAccount => RwTableTag::TxAccessListAccount,
+                    operation::Target::TxCreatedAccount => RwTableTag::TxCreatedAccount,
+                    operation::Target::TxDestructedAccount => RwTableTag::TxDestructedAccount,
                     operation::Target::TxAccessListAccountStorage => {
                         RwTableTag::TxAccessListAccountStorage
                     }
@@ -256,12 +267,17 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
                     operation::Target::TxReceipt => RwTableTag::TxReceipt,
                     operation::Target::TxLog => RwTableTag::TxLog,
                     operation::Target::Start => RwTableTag::Start,
+                    // only written by TSTORE, and reverted in the steps
+                    // which revert its call
+                    operation::Target::TransientStorage => {
+                        return Err(Error::UnsupportedOpcode(OpcodeId::TSTORE))
+                    }
                 };
-                (tag, x.as_usize())
+                Ok((tag, x.as_usize()))
             })
-            .collect(),
+            .collect::<Result<_, _>>()?,
         copy_rw_counter_delta: step.copy_rw_counter_delta,
-        execution_state: ExecutionState::from(step),
+        execution_state,
         rw_counter: usize::from(step.rwc),
         program_counter: usize::from(step.pc) as u64,
         stack_pointer: STACK_CAPACITY - step.stack_size,
@@ -277,5 +293,7 @@ pub(super) fn step_convert(step: &circuit_input_builder::ExecStep, block_num: u6
         log_id: step.log_id,
         block_num,
         aux_data: step.aux_data.clone(),
-    }
+        #[cfg(feature = "step-profile")]
+        elapsed: step.elapsed,
+    })
 }
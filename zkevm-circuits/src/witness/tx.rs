@@ -1,18 +1,15 @@
 use crate::{
     evm_circuit::{step::ExecutionState, util::rlc},
     table::TxContextFieldTag,
+    tx_circuit::MAX_BLOBS_PER_TX,
     util::{rlc_be_bytes, Challenges},
     witness::{
-        rlp_fsm::SmState,
         DataTable, Format,
         Format::{
-            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashPreEip155, TxSignEip155, TxSignEip1559,
-            TxSignPreEip155,
+            L1MsgHash, TxHashEip155, TxHashEip1559, TxHashEip2930, TxHashEip4844, TxHashPreEip155,
+            TxSignEip155, TxSignEip1559, TxSignEip2930, TxSignEip4844, TxSignPreEip155,
         },
-        RlpFsmWitnessGen, RlpFsmWitnessRow, RlpTable, RlpTag, State,
-        State::DecodeTagStart,
-        StateMachine,
-        Tag::{EndList, EndVector},
+        RlpFsm, RlpFsmWitnessGen, RlpFsmWitnessRow,
     },
 };
 use bus_mapping::circuit_input_builder::{self, get_dummy_tx, get_dummy_tx_hash, TxL1Fee};
@@ -30,7 +27,6 @@ use halo2_proofs::{
 use mock::MockTransaction;
 use num::Integer;
 use num_bigint::BigUint;
-use std::{cmp::Ordering, collections::BTreeMap};
 
 use super::{step::step_convert, Call, ExecStep};
 
@@ -79,10 +75,17 @@ pub struct Transaction {
     pub r: Word,
     /// "s" value of the transaction signature
     pub s: Word,
+    /// The versioned hashes of the blobs of an EIP-4844 transaction
+    pub blob_versioned_hashes: Vec<H256>,
     /// Current values of L1 fee
     pub l1_fee: TxL1Fee,
     /// Committed values of L1 fee
     pub l1_fee_committed: TxL1Fee,
+    /// Whether the caller address was provided instead of recovered from the
+    /// signature, see [`circuit_input_builder::Transaction::unverified_sender`].
+    /// The tx circuit skips the signature verification of such a tx and flags
+    /// it in the tx table.
+    pub unverified_sender: bool,
     /// The calls made in the transaction
     pub calls: Vec<Call>,
     /// The steps executioned in the transaction
@@ -134,7 +137,7 @@ impl Transaction {
         )?;
         let msg = self.rlp_unsigned.clone().into();
         let msg_hash = keccak256(&self.rlp_unsigned);
-        let v = self.tx_type.get_recovery_id(self.v);
+        let v = self.tx_type.get_recovery_id(self.v)?;
         let pk = recover_pk(v, &self.r, &self.s, &msg_hash)?;
         // msg_hash = msg_hash % q
         let msg_hash = BigUint::from_bytes_be(msg_hash.as_slice());
@@ -163,7 +166,7 @@ impl Transaction {
         let tx_hash_be_bytes = keccak256(&self.rlp_signed);
         let tx_sign_hash_be_bytes = keccak256(&self.rlp_unsigned);
 
-        let ret = vec![
+        let mut ret = vec![
             [
                 Value::known(F::from(self.id as u64)),
                 Value::known(F::from(TxContextFieldTag::Nonce as u64)),
@@ -301,11 +304,42 @@ impl Transaction {
             ],
             [
                 Value::known(F::from(self.id as u64)),
-                Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+                Value::known(F::from(TxContextFieldTag::BlobVersionedHashesLen as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.blob_versioned_hashes.len() as u64)),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::TxType as u64)),
+                Value::known(F::zero()),
+                Value::known(F::from(self.tx_type as u64)),
+            ],
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::UnverifiedSender as u64)),
                 Value::known(F::zero()),
-                Value::known(F::from(self.block_number)),
+                Value::known(F::from(self.unverified_sender as u64)),
             ],
         ];
+        // a row per blob a tx may carry, zero beyond the blobs of the tx
+        ret.extend((0..MAX_BLOBS_PER_TX).map(|index| {
+            [
+                Value::known(F::from(self.id as u64)),
+                Value::known(F::from(TxContextFieldTag::BlobVersionedHash as u64)),
+                Value::known(F::from(index as u64)),
+                self.blob_versioned_hashes
+                    .get(index)
+                    .map_or(Value::known(F::zero()), |hash| {
+                        rlc_be_bytes(hash.as_bytes(), challenges.evm_word())
+                    }),
+            ]
+        }));
+        ret.push([
+            Value::known(F::from(self.id as u64)),
+            Value::known(F::from(TxContextFieldTag::BlockNumber as u64)),
+            Value::known(F::zero()),
+            Value::known(F::from(self.block_number)),
+        ]);
 
         ret
     }
@@ -329,62 +363,368 @@ impl Transaction {
             .collect()
     }
 
-    pub(crate) fn gen_rlp_witness<F: Field>(
+    /// Stream the rows of the RLP state machine decoding the signed
+    /// (`is_hash`) or unsigned RLP bytes of the tx.
+    pub fn rlp_fsm<F: Field>(
         &self,
         is_hash: bool,
         challenges: &Challenges<Value<F>>,
-    ) -> Vec<RlpFsmWitnessRow<F>> {
+    ) -> RlpFsm<'_, F> {
         let (rlp_bytes, format) = if is_hash {
             (
-                self.rlp_signed.clone(),
+                &self.rlp_signed,
                 match self.tx_type {
                     TxType::Eip155 => TxHashEip155,
                     TxType::PreEip155 => TxHashPreEip155,
                     TxType::Eip1559 => TxHashEip1559,
-                    TxType::L1Msg => L1MsgHash,
-                    _ => unreachable!("tx type {:?} not supported", self.tx_type),
+                    TxType::Eip2930 => TxHashEip2930,
+                    TxType::Eip4844 => TxHashEip4844,
+                    // the pseudo-tx of a system call is hashed as an L1 msg
+                    TxType::L1Msg | TxType::SystemCall => L1MsgHash,
                 },
             )
         } else {
             (
-                self.rlp_unsigned.clone(),
+                &self.rlp_unsigned,
                 match self.tx_type {
                     TxType::Eip155 => TxSignEip155,
                     TxType::PreEip155 => TxSignPreEip155,
                     TxType::Eip1559 => TxSignEip1559,
+                    TxType::Eip2930 => TxSignEip2930,
+                    TxType::Eip4844 => TxSignEip4844,
                     _ => unreachable!("tx type {:?} not supported", self.tx_type),
                 },
             )
         };
 
+        RlpFsm::new(self.id as u64, format, rlp_bytes, challenges)
+    }
+
+    pub(crate) fn gen_rlp_witness<F: Field>(
+        &self,
+        is_hash: bool,
+        challenges: &Challenges<Value<F>>,
+    ) -> Vec<RlpFsmWitnessRow<F>> {
+        self.rlp_fsm(is_hash, challenges).collect()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_from_rlp_bytes(
+        tx_type: TxType,
+        signed_bytes: Vec<u8>,
+        unsigned_bytes: Vec<u8>,
+    ) -> Self {
+        Self {
+            id: 1,
+            tx_type,
+            rlp_signed: signed_bytes,
+            rlp_unsigned: unsigned_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_from_rlp_signed_bytes(tx_type: TxType, bytes: Vec<u8>) -> Self {
+        Self {
+            id: 1,
+            tx_type,
+            rlp_signed: bytes,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new_from_rlp_unsigned_bytes(tx_type: TxType, bytes: Vec<u8>) -> Self {
+        Self {
+            id: 1,
+            tx_type,
+            rlp_unsigned: bytes,
+            ..Default::default()
+        }
+    }
+}
+
+impl<F: Field> RlpFsmWitnessGen<F> for Transaction {
+    fn sm_witness<'a>(
+        &'a self,
+        challenges: &Challenges<Value<F>>,
+    ) -> Box<dyn Iterator<Item = RlpFsmWitnessRow<F>> + 'a> {
+        let sign_wit = if self.tx_type.is_unsigned() {
+            None
+        } else {
+            Some(self.rlp_fsm(false, challenges))
+        };
+
+        Box::new(
+            sign_wit
+                .into_iter()
+                .flatten()
+                .chain(self.rlp_fsm(true, challenges)),
+        )
+    }
+
+    fn gen_data_table(&self, challenges: &Challenges<Value<F>>) -> Vec<DataTable<F>> {
         let tx_id = self.id as u64;
+        let r = challenges.keccak_input();
+
+        let (hash_format, sign_format) = match self.tx_type {
+            TxType::Eip155 => (TxHashEip155, Some(TxSignEip155)),
+            TxType::PreEip155 => (TxHashPreEip155, Some(TxSignPreEip155)),
+            TxType::Eip1559 => (TxHashEip1559, Some(TxSignEip1559)),
+            TxType::Eip2930 => (TxHashEip2930, Some(TxSignEip2930)),
+            TxType::Eip4844 => (TxHashEip4844, Some(TxSignEip4844)),
+            TxType::L1Msg | TxType::SystemCall => (L1MsgHash, None),
+        };
+
+        let get_table = |rlp_bytes: &Vec<u8>, format: Format| {
+            let n = rlp_bytes.len();
+            rlp_bytes
+                .iter()
+                .enumerate()
+                .scan(
+                    (Value::known(F::zero()), Value::known(F::zero())),
+                    |(rlc, gas_cost_acc), (i, &byte_value)| {
+                        let byte_cost = if byte_value == 0 { 4 } else { 16 };
+                        *rlc = *rlc * r + Value::known(F::from(byte_value as u64));
+                        *gas_cost_acc = *gas_cost_acc + Value::known(F::from(byte_cost));
+                        Some(DataTable {
+                            tx_id,
+                            format,
+                            byte_idx: i + 1,
+                            byte_rev_idx: n - i,
+                            byte_value,
+                            bytes_rlc: *rlc,
+                            gas_cost_acc: *gas_cost_acc,
+                        })
+                    },
+                )
+                .collect::<Vec<_>>()
+        };
+
+        let hash_table = get_table(&self.rlp_signed, hash_format);
+        if let Some(sign_format) = sign_format {
+            let sign_table = get_table(&self.rlp_unsigned, sign_format);
+            [sign_table, hash_table].concat()
+        } else {
+            hash_table
+        }
+    }
+}
+
+impl From<MockTransaction> for Transaction {
+    fn from(mock_tx: MockTransaction) -> Self {
+        let is_create = mock_tx.to.is_none();
+        let sig = Signature {
+            r: mock_tx.r.expect("tx expected to be signed"),
+            s: mock_tx.s.expect("tx expected to be signed"),
+            v: mock_tx.v.expect("tx expected to be signed").as_u64(),
+        };
+        let (rlp_unsigned, rlp_signed) = {
+            let mut legacy_tx = TransactionRequest::new()
+                .from(mock_tx.from.address())
+                .nonce(mock_tx.nonce)
+                .gas_price(mock_tx.gas_price)
+                .gas(mock_tx.gas)
+                .value(mock_tx.value)
+                .data(mock_tx.input.clone())
+                .chain_id(mock_tx.chain_id);
+            if !is_create {
+                legacy_tx = legacy_tx.to(mock_tx.to.as_ref().map(|to| to.address()).unwrap());
+            }
+
+            let unsigned = legacy_tx.rlp().to_vec();
+            let signed = legacy_tx.rlp_signed(&sig).to_vec();
+
+            (unsigned, signed)
+        };
+        Self {
+            block_number: 1,
+            id: mock_tx.transaction_index.as_usize(),
+            hash: mock_tx.hash.unwrap_or_default(),
+            tx_type: TxType::Eip155,
+            nonce: mock_tx.nonce.as_u64(),
+            gas: mock_tx.gas.as_u64(),
+            gas_price: mock_tx.gas_price,
+            caller_address: mock_tx.from.address(),
+            callee_address: mock_tx.to.as_ref().map(|to| to.address()),
+            is_create,
+            value: mock_tx.value,
+            call_data: mock_tx.input.to_vec(),
+            call_data_length: mock_tx.input.len(),
+            call_data_gas_cost: tx_data_gas_cost(&mock_tx.input),
+            tx_data_gas_cost: tx_data_gas_cost(&rlp_signed),
+            chain_id: mock_tx.chain_id,
+            rlp_unsigned,
+            rlp_signed,
+            v: sig.v,
+            r: sig.r,
+            s: sig.s,
+            blob_versioned_hashes: vec![],
+            l1_fee: Default::default(),
+            l1_fee_committed: Default::default(),
+            unverified_sender: false,
+            calls: vec![],
+            steps: vec![],
+        }
+    }
+}
+
+pub(super) fn tx_convert(
+    tx: &circuit_input_builder::Transaction,
+    id: usize,
+    chain_id: u64,
+) -> Result<Transaction, bus_mapping::Error> {
+    debug_assert_eq!(
+        chain_id, tx.chain_id,
+        "block.chain_id = {}, tx.chain_id = {}",
+        chain_id, tx.chain_id
+    );
+    let callee_address = tx.to;
+    //if tx.is_create() { None } else { Some(tx.to) };
+    let tx_gas_cost = if tx.tx_type.is_unsigned() {
+        0
+    } else {
+        tx_data_gas_cost(&tx.rlp_bytes)
+    };
+
+    // Each EndInnerBlock step closes one block, starting from the tx's own
+    // block and followed by the empty blocks after it, if any.
+    let mut block_num = tx.block_num;
+    let steps = tx
+        .steps()
+        .iter()
+        .map(|step| {
+            let step = step_convert(step, block_num)?;
+            if step.execution_state == ExecutionState::EndInnerBlock {
+                block_num += 1;
+            }
+            Ok(step)
+        })
+        .collect::<Result<_, bus_mapping::Error>>()?;
+
+    Ok(Transaction {
+        block_number: tx.block_num,
+        id,
+        hash: tx.hash,
+        tx_type: tx.tx_type,
+        nonce: tx.nonce,
+        gas: tx.gas,
+        gas_price: tx.gas_price,
+        caller_address: tx.from,
+        callee_address,
+        is_create: tx.is_create(),
+        value: tx.value,
+        call_data: tx.input.clone(),
+        call_data_length: tx.input.len(),
+        call_data_gas_cost: tx_data_gas_cost(&tx.input),
+        tx_data_gas_cost: tx_gas_cost,
+        chain_id,
+        rlp_unsigned: tx.rlp_unsigned_bytes.clone(),
+        rlp_signed: tx.rlp_bytes.clone(),
+        v: tx.signature.v,
+        r: tx.signature.r,
+        s: tx.signature.s,
+        blob_versioned_hashes: tx.blob_versioned_hashes.clone(),
+        l1_fee: tx.l1_fee,
+        l1_fee_committed: tx.l1_fee_committed,
+        unverified_sender: tx.unverified_sender,
+        calls: tx
+            .calls()
+            .iter()
+            .map(|call| Call {
+                id: call.call_id,
+                is_root: call.is_root,
+                is_create: call.is_create(),
+                code_hash: call.code_hash.to_word(),
+                rw_counter_end_of_reversion: call.rw_counter_end_of_reversion,
+                caller_id: call.caller_id,
+                depth: call.depth,
+                caller_address: call.caller_address,
+                callee_address: call.address,
+                code_address: call.code_address(),
+                call_data_offset: call.call_data_offset,
+                call_data_length: call.call_data_length,
+                return_data_offset: call.return_data_offset,
+                return_data_length: call.return_data_length,
+                value: call.value,
+                is_success: call.is_success,
+                is_persistent: call.is_persistent,
+                is_static: call.is_static,
+            })
+            .collect(),
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::witness::{
+        rlp_fsm::SmState,
+        tx::Challenges,
+        Format, RlpFsmWitnessRow, RlpTable, RlpTag, State, StateMachine, Tag,
+        Tag::{EndList, EndVector},
+        Transaction,
+    };
+    use eth_types::{
+        evm_types::gas_utils::tx_data_gas_cost,
+        geth_types::{self, get_rlp_signed, get_rlp_unsigned, TxType},
+        Address, ToBigEndian, ToScalar, Word, H256,
+    };
+    use ethers_core::{
+        types::{Transaction as EthTransaction, TransactionRequest},
+        utils::rlp::{Decodable, Rlp},
+    };
+    use halo2_proofs::{circuit::Value, dev::unwrap_value, halo2curves::bn256::Fr};
+    use std::{cmp::Ordering, collections::BTreeMap};
+
+    // the tx is downloaded from https://etherscan.io/getRawTx?tx=0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060
+    const PRE_EIP155_TX: &str = "f86780862d79883d2000825208945df9b87991262f6ba471f09758cde1c0fc1de734827a69801ca088ff6cf0fefd94db46111149ae4bfc179e9b94721fffd821d38d16464b3f71d0a045e0aff800961cfce805daef7016b9b675c137a6a41a548f7b60a3484c06a33a";
+    const L1_MSG_TX: &str = "7ef901b60b825dc0941a258d17bf244c4df02d40343a7626a9d321e10580b901848ef1332e000000000000000000000000ea08a65b1829af779261e768d609e59279b510f2000000000000000000000000f2ec6b6206f6208e8f9b394efc1a01c1cbde77750000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000000b00000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000a4232e87480000000000000000000000002b5ad5c4795c026514f8317c7a215e218dccd6cf0000000000000000000000002b5ad5c4795c026514f8317c7a215e218dccd6cf0000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000094478cdd110520a8e733e2acf9e543d2c687ea5239";
+    // the tx is downloaded from https://etherscan.io/getRawTx?tx=0x1c5bd618bdbc575f71bfe0a54f09bca2997bbf6d90d4f371a509b05e2b3124e3
+    const EIP1559_TX: &str = "02f901e901833c3139842b27f14d86012309ce540083055ca8945f65f7b609678448494de4c87521cdf6cef1e93280b8e4fa558b7100000000000000000000000095ad61b0a150d79219dcf64e1e6cc01f0b64c4ce000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a0000000000000000000000000000000000000000000000000000000000000000100000000000000000000000016a217dedfacdf9c23edb84b57154f26a15848e60000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000028cad80bb7cf17e27c4c8f893f7945f65f7b609678448494de4c87521cdf6cef1e932e1a0d2dc2a0881b05440a4908cf506b4871b1f7eaa46ea0c5dfdcda5f52bc17164a4f8599495ad61b0a150d79219dcf64e1e6cc01f0b64c4cef842a0ba03decd934aae936605e9d437c401439ec4cefbad5795e0965100f929fe339ca0b36e2afa1a25492257090107ad99d079032e543c8dd1ffcd44cf14a96d3015ac80a0821193127789b107351f670025dd3b862f5836e5155f627a29741a251e8d28e8a07ea1e82b1bf6f29c5d0f1e4024acdb698086ac40c353704d7d5e301fb916f2e3";
+
+    fn rlc(be_bytes: &[u8], rand: Fr) -> Fr {
+        be_bytes
+            .iter()
+            .fold(Fr::zero(), |acc, &byte| acc * rand + Fr::from(byte as u64))
+    }
+
+    /// The witness generation replaced by [`RlpFsm`](crate::witness::RlpFsm),
+    /// which precomputed the RLC and gas cost of every byte and filled the
+    /// ROM table columns once all the rows were collected.
+    fn rlp_witness_reference(
+        tx_id: u64,
+        format: Format,
+        rlp_bytes: &[u8],
+        challenges: &Challenges<Value<Fr>>,
+    ) -> Vec<RlpFsmWitnessRow<Fr>> {
         let mut witness = vec![];
         let rom_table = format.rom_table_rows();
         let keccak_rand = challenges.keccak_input();
         let word_rand = challenges.evm_word();
         let rlp_bytes_rlc = rlp_bytes
             .iter()
-            .scan(Value::known(F::zero()), |rlc, &byte| {
-                *rlc = *rlc * keccak_rand + Value::known(F::from(byte as u64));
+            .scan(Value::known(Fr::zero()), |rlc, &byte| {
+                *rlc = *rlc * keccak_rand + Value::known(Fr::from(byte as u64));
 
                 Some(*rlc)
             })
             .collect::<Vec<_>>();
         let rlp_gas_cost_acc = rlp_bytes
             .iter()
-            .scan(Value::known(F::zero()), |acc, &byte| {
+            .scan(Value::known(Fr::zero()), |acc, &byte| {
                 let cost = if byte == 0 { 4 } else { 16 };
-                *acc = *acc + Value::known(F::from(cost));
+                *acc = *acc + Value::known(Fr::from(cost));
 
                 Some(*acc)
             })
             .collect::<Vec<_>>();
         let mut cur = SmState {
             tag: rom_table[0].tag,
-            state: DecodeTagStart,
+            state: State::DecodeTagStart,
             tag_idx: 0,
             tag_length: 0,
-            tag_value_acc: Value::known(F::zero()),
+            tag_value_acc: Value::known(Fr::zero()),
             byte_idx: 0,
             depth: 0,
         };
@@ -414,7 +754,7 @@ impl Transaction {
 
             let mut next = cur.clone();
             match cur.state {
-                DecodeTagStart => {
+                State::DecodeTagStart => {
                     if cur.tag.is_end() {
                         // assertions
                         assert_eq!(
@@ -440,7 +780,7 @@ impl Transaction {
                         if cur.depth > 0 {
                             next.depth = cur.depth - 1;
                         }
-                        next.state = DecodeTagStart;
+                        next.state = State::DecodeTagStart;
                     } else {
                         let byte_value = rlp_bytes[cur.byte_idx];
                         if let Some(rem) = remaining_bytes.last_mut() {
@@ -452,19 +792,19 @@ impl Transaction {
                             // assertions
                             assert!(!cur.tag.is_list());
                             is_output = true;
-                            cur.tag_value_acc = Value::known(F::from(byte_value as u64));
+                            cur.tag_value_acc = Value::known(Fr::from(byte_value as u64));
 
                             // state transitions
-                            next.state = DecodeTagStart;
+                            next.state = State::DecodeTagStart;
                         } else if byte_value == 0x80 {
                             // assertions
                             assert!(!cur.tag.is_list());
                             is_output = true;
                             is_none = true;
-                            cur.tag_value_acc = Value::known(F::zero());
+                            cur.tag_value_acc = Value::known(Fr::zero());
 
                             // state transitions
-                            next.state = DecodeTagStart;
+                            next.state = State::DecodeTagStart;
                         } else if byte_value < 0xb8 {
                             // assertions
                             assert!(!cur.tag.is_list());
@@ -473,7 +813,7 @@ impl Transaction {
                             next.tag_idx = 1;
                             next.tag_length = (byte_value - 0x80) as usize;
                             next.tag_value_acc =
-                                Value::known(F::from(rlp_bytes[cur.byte_idx + 1] as u64));
+                                Value::known(Fr::from(rlp_bytes[cur.byte_idx + 1] as u64));
                             next.state = State::Bytes;
                         } else if byte_value < 0xc0 {
                             // assertions
@@ -483,7 +823,7 @@ impl Transaction {
                             next.tag_idx = 1;
                             next.tag_length = (byte_value - 0xb7) as usize;
                             lb_len = rlp_bytes[cur.byte_idx + 1] as usize;
-                            next.tag_value_acc = Value::known(F::from(lb_len as u64));
+                            next.tag_value_acc = Value::known(Fr::from(lb_len as u64));
                             next.state = State::LongBytes;
                         } else if byte_value < 0xf8 {
                             // assertions
@@ -492,7 +832,8 @@ impl Transaction {
                                 is_output = true;
                                 rlp_tag = RlpTag::Len;
                             }
-                            cur.tag_value_acc = Value::known(F::from(u64::from(byte_value - 0xc0)));
+                            cur.tag_value_acc =
+                                Value::known(Fr::from(u64::from(byte_value - 0xc0)));
 
                             // state transitions
                             let num_bytes_of_new_list = usize::from(byte_value - 0xc0);
@@ -506,7 +847,7 @@ impl Transaction {
                             }
                             remaining_bytes.push(num_bytes_of_new_list);
                             next.depth = cur.depth + 1;
-                            next.state = DecodeTagStart;
+                            next.state = State::DecodeTagStart;
                         } else {
                             // assertions
                             assert!(cur.tag.is_begin());
@@ -516,7 +857,7 @@ impl Transaction {
                             next.tag_idx = 1;
                             next.tag_length = (byte_value - 0xf7) as usize;
                             lb_len = rlp_bytes[cur.byte_idx + 1] as usize;
-                            next.tag_value_acc = Value::known(F::from(lb_len as u64));
+                            next.tag_value_acc = Value::known(Fr::from(lb_len as u64));
                             next.state = State::LongList;
                         }
                     }
@@ -530,19 +871,19 @@ impl Transaction {
                         // state transitions
                         let max_length = rom_table[cur_rom_row[0]].max_length;
                         let b = match max_length.cmp(&32) {
-                            Ordering::Less => Value::known(F::from(256_u64)),
+                            Ordering::Less => Value::known(Fr::from(256_u64)),
                             Ordering::Equal => word_rand,
                             Ordering::Greater => keccak_rand,
                         };
                         next.tag_idx = cur.tag_idx + 1;
                         next.tag_value_acc = cur.tag_value_acc * b
-                            + Value::known(F::from(rlp_bytes[cur.byte_idx + 1] as u64));
+                            + Value::known(Fr::from(rlp_bytes[cur.byte_idx + 1] as u64));
                     } else {
                         // assertions
                         is_output = true;
 
                         // state transitions
-                        next.state = DecodeTagStart;
+                        next.state = State::DecodeTagStart;
                     }
                 }
                 State::LongBytes => {
@@ -555,7 +896,7 @@ impl Transaction {
                         // state transitions
                         next.tag_idx = cur.tag_idx + 1;
                         lb_len = lb_len * 256 + usize::from(rlp_bytes[cur.byte_idx + 1]);
-                        next.tag_value_acc = Value::known(F::from(lb_len as u64));
+                        next.tag_value_acc = Value::known(Fr::from(lb_len as u64));
                     } else {
                         // we're dealing with case cur.tag_idx == cur.tag_length
 
@@ -563,7 +904,7 @@ impl Transaction {
                         next.tag_idx = 1;
                         next.tag_length = lb_len;
                         next.tag_value_acc =
-                            Value::known(F::from(u64::from(rlp_bytes[cur.byte_idx + 1])));
+                            Value::known(Fr::from(u64::from(rlp_bytes[cur.byte_idx + 1])));
                         next.state = State::Bytes;
                     }
                 }
@@ -577,7 +918,7 @@ impl Transaction {
                         // state transitions
                         next.tag_idx = cur.tag_idx + 1;
                         lb_len = lb_len * 256 + usize::from(rlp_bytes[cur.byte_idx + 1]);
-                        next.tag_value_acc = Value::known(F::from(lb_len as u64));
+                        next.tag_value_acc = Value::known(Fr::from(lb_len as u64));
                     } else {
                         // assertions
                         if cur.depth == 0 {
@@ -590,7 +931,7 @@ impl Transaction {
                         }
                         remaining_bytes.push(lb_len);
                         next.depth = cur.depth + 1;
-                        next.state = DecodeTagStart;
+                        next.state = State::DecodeTagStart;
                     }
                 }
                 State::End => {
@@ -598,7 +939,7 @@ impl Transaction {
                 }
             }
 
-            if next.state == DecodeTagStart {
+            if next.state == State::DecodeTagStart {
                 // we finished parsing current tag
                 let row = if cur_rom_row.len() == 1 {
                     cur_rom_row[0]
@@ -642,7 +983,9 @@ impl Transaction {
             let gas_cost_acc = rlp_gas_cost_acc[cur.byte_idx];
 
             let tag_value = match rlp_tag {
-                RlpTag::Len => cur.tag_value_acc + Value::known(F::from((cur.byte_idx + 1) as u64)),
+                RlpTag::Len => {
+                    cur.tag_value_acc + Value::known(Fr::from((cur.byte_idx + 1) as u64))
+                }
                 RlpTag::RLC => bytes_rlc,
                 RlpTag::GasCost => gas_cost_acc,
                 RlpTag::Tag(_) => cur.tag_value_acc,
@@ -694,281 +1037,9 @@ impl Transaction {
         witness
     }
 
-    #[cfg(test)]
-    pub(crate) fn new_from_rlp_bytes(
-        tx_type: TxType,
-        signed_bytes: Vec<u8>,
-        unsigned_bytes: Vec<u8>,
-    ) -> Self {
-        Self {
-            id: 1,
-            tx_type,
-            rlp_signed: signed_bytes,
-            rlp_unsigned: unsigned_bytes,
-            ..Default::default()
-        }
-    }
-
-    #[cfg(test)]
-    pub(crate) fn new_from_rlp_signed_bytes(tx_type: TxType, bytes: Vec<u8>) -> Self {
-        Self {
-            id: 1,
-            tx_type,
-            rlp_signed: bytes,
-            ..Default::default()
-        }
-    }
-
-    #[cfg(test)]
-    pub(crate) fn new_from_rlp_unsigned_bytes(tx_type: TxType, bytes: Vec<u8>) -> Self {
-        Self {
-            id: 1,
-            tx_type,
-            rlp_unsigned: bytes,
-            ..Default::default()
-        }
-    }
-}
-
-impl<F: Field> RlpFsmWitnessGen<F> for Transaction {
-    fn gen_sm_witness(&self, challenges: &Challenges<Value<F>>) -> Vec<RlpFsmWitnessRow<F>> {
-        let hash_wit = self.gen_rlp_witness(true, challenges);
-        let sign_wit = match self.tx_type {
-            TxType::L1Msg => vec![],
-            _ => self.gen_rlp_witness(false, challenges),
-        };
-
-        log::debug!(
-            "{}th tx sign witness rows len = {}",
-            self.id,
-            sign_wit.len()
-        );
-        log::debug!(
-            "{}th tx hash witness rows len = {}",
-            self.id,
-            hash_wit.len()
-        );
-
-        [sign_wit, hash_wit].concat()
-    }
-
-    fn gen_data_table(&self, challenges: &Challenges<Value<F>>) -> Vec<DataTable<F>> {
-        let tx_id = self.id as u64;
-        let r = challenges.keccak_input();
-
-        let (hash_format, sign_format) = match self.tx_type {
-            TxType::Eip155 => (TxHashEip155, Some(TxSignEip155)),
-            TxType::PreEip155 => (TxHashPreEip155, Some(TxSignPreEip155)),
-            TxType::Eip1559 => (TxHashEip1559, Some(TxSignEip1559)),
-            TxType::Eip2930 => {
-                unimplemented!("eip2930 not supported now")
-            }
-            TxType::L1Msg => (L1MsgHash, None),
-        };
-
-        let get_table = |rlp_bytes: &Vec<u8>, format: Format| {
-            let n = rlp_bytes.len();
-            rlp_bytes
-                .iter()
-                .enumerate()
-                .scan(
-                    (Value::known(F::zero()), Value::known(F::zero())),
-                    |(rlc, gas_cost_acc), (i, &byte_value)| {
-                        let byte_cost = if byte_value == 0 { 4 } else { 16 };
-                        *rlc = *rlc * r + Value::known(F::from(byte_value as u64));
-                        *gas_cost_acc = *gas_cost_acc + Value::known(F::from(byte_cost));
-                        Some(DataTable {
-                            tx_id,
-                            format,
-                            byte_idx: i + 1,
-                            byte_rev_idx: n - i,
-                            byte_value,
-                            bytes_rlc: *rlc,
-                            gas_cost_acc: *gas_cost_acc,
-                        })
-                    },
-                )
-                .collect::<Vec<_>>()
-        };
-
-        let hash_table = get_table(&self.rlp_signed, hash_format);
-        if let Some(sign_format) = sign_format {
-            let sign_table = get_table(&self.rlp_unsigned, sign_format);
-            [sign_table, hash_table].concat()
-        } else {
-            hash_table
-        }
-    }
-}
-
-impl From<MockTransaction> for Transaction {
-    fn from(mock_tx: MockTransaction) -> Self {
-        let is_create = mock_tx.to.is_none();
-        let sig = Signature {
-            r: mock_tx.r.expect("tx expected to be signed"),
-            s: mock_tx.s.expect("tx expected to be signed"),
-            v: mock_tx.v.expect("tx expected to be signed").as_u64(),
-        };
-        let (rlp_unsigned, rlp_signed) = {
-            let mut legacy_tx = TransactionRequest::new()
-                .from(mock_tx.from.address())
-                .nonce(mock_tx.nonce)
-                .gas_price(mock_tx.gas_price)
-                .gas(mock_tx.gas)
-                .value(mock_tx.value)
-                .data(mock_tx.input.clone())
-                .chain_id(mock_tx.chain_id);
-            if !is_create {
-                legacy_tx = legacy_tx.to(mock_tx.to.as_ref().map(|to| to.address()).unwrap());
-            }
-
-            let unsigned = legacy_tx.rlp().to_vec();
-            let signed = legacy_tx.rlp_signed(&sig).to_vec();
-
-            (unsigned, signed)
-        };
-        Self {
-            block_number: 1,
-            id: mock_tx.transaction_index.as_usize(),
-            hash: mock_tx.hash.unwrap_or_default(),
-            tx_type: TxType::Eip155,
-            nonce: mock_tx.nonce.as_u64(),
-            gas: mock_tx.gas.as_u64(),
-            gas_price: mock_tx.gas_price,
-            caller_address: mock_tx.from.address(),
-            callee_address: mock_tx.to.as_ref().map(|to| to.address()),
-            is_create,
-            value: mock_tx.value,
-            call_data: mock_tx.input.to_vec(),
-            call_data_length: mock_tx.input.len(),
-            call_data_gas_cost: tx_data_gas_cost(&mock_tx.input),
-            tx_data_gas_cost: tx_data_gas_cost(&rlp_signed),
-            chain_id: mock_tx.chain_id,
-            rlp_unsigned,
-            rlp_signed,
-            v: sig.v,
-            r: sig.r,
-            s: sig.s,
-            l1_fee: Default::default(),
-            l1_fee_committed: Default::default(),
-            calls: vec![],
-            steps: vec![],
-        }
-    }
-}
-
-pub(super) fn tx_convert(
-    tx: &circuit_input_builder::Transaction,
-    id: usize,
-    chain_id: u64,
-    next_block_num: u64,
-) -> Transaction {
-    debug_assert_eq!(
-        chain_id, tx.chain_id,
-        "block.chain_id = {}, tx.chain_id = {}",
-        chain_id, tx.chain_id
-    );
-    let callee_address = tx.to;
-    //if tx.is_create() { None } else { Some(tx.to) };
-    let tx_gas_cost = if tx.tx_type.is_l1_msg() {
-        0
-    } else {
-        tx_data_gas_cost(&tx.rlp_bytes)
-    };
-
-    Transaction {
-        block_number: tx.block_num,
-        id,
-        hash: tx.hash,
-        tx_type: tx.tx_type,
-        nonce: tx.nonce,
-        gas: tx.gas,
-        gas_price: tx.gas_price,
-        caller_address: tx.from,
-        callee_address,
-        is_create: tx.is_create(),
-        value: tx.value,
-        call_data: tx.input.clone(),
-        call_data_length: tx.input.len(),
-        call_data_gas_cost: tx_data_gas_cost(&tx.input),
-        tx_data_gas_cost: tx_gas_cost,
-        chain_id,
-        rlp_unsigned: tx.rlp_unsigned_bytes.clone(),
-        rlp_signed: tx.rlp_bytes.clone(),
-        v: tx.signature.v,
-        r: tx.signature.r,
-        s: tx.signature.s,
-        l1_fee: tx.l1_fee,
-        l1_fee_committed: tx.l1_fee_committed,
-        calls: tx
-            .calls()
-            .iter()
-            .map(|call| Call {
-                id: call.call_id,
-                is_root: call.is_root,
-                is_create: call.is_create(),
-                code_hash: call.code_hash.to_word(),
-                rw_counter_end_of_reversion: call.rw_counter_end_of_reversion,
-                caller_id: call.caller_id,
-                depth: call.depth,
-                caller_address: call.caller_address,
-                callee_address: call.address,
-                code_address: call.code_address(),
-                call_data_offset: call.call_data_offset,
-                call_data_length: call.call_data_length,
-                return_data_offset: call.return_data_offset,
-                return_data_length: call.return_data_length,
-                value: call.value,
-                is_success: call.is_success,
-                is_persistent: call.is_persistent,
-                is_static: call.is_static,
-            })
-            .collect(),
-        steps: tx
-            .steps()
-            .iter()
-            .map(|step| step_convert(step, tx.block_num))
-            .chain({
-                let rw_counter = tx.steps().last().unwrap().rwc.0 + 9 - (id == 1) as usize;
-                debug_assert!(next_block_num >= tx.block_num);
-                let end_inner_block_steps = (tx.block_num..next_block_num)
-                    .map(|block_num| ExecStep {
-                        rw_counter,
-                        execution_state: ExecutionState::EndInnerBlock,
-                        block_num,
-                        ..Default::default()
-                    })
-                    .collect::<Vec<ExecStep>>();
-                log::trace!("end_inner_block_steps {:?}", end_inner_block_steps);
-                end_inner_block_steps
-            })
-            .collect(),
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::witness::{tx::Challenges, RlpTag, Tag, Transaction};
-    use eth_types::{
-        evm_types::gas_utils::tx_data_gas_cost, geth_types::TxType, Address, ToBigEndian, ToScalar,
-    };
-    use ethers_core::{
-        types::{Transaction as EthTransaction, TransactionRequest},
-        utils::rlp::{Decodable, Rlp},
-    };
-    use halo2_proofs::{circuit::Value, dev::unwrap_value, halo2curves::bn256::Fr};
-
-    fn rlc(be_bytes: &[u8], rand: Fr) -> Fr {
-        be_bytes
-            .iter()
-            .fold(Fr::zero(), |acc, &byte| acc * rand + Fr::from(byte as u64))
-    }
-
     #[test]
     fn test_rlp_pre_eip155() {
-        // the tx is downloaded from https://etherscan.io/getRawTx?tx=0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060
-        let raw_tx_rlp_bytes = hex::decode("f86780862d79883d2000825208945df9b87991262f6ba471f09758cde1c0fc1de734827a69801ca088ff6cf0fefd94db46111149ae4bfc179e9b94721fffd821d38d16464b3f71d0a045e0aff800961cfce805daef7016b9b675c137a6a41a548f7b60a3484c06a33a")
-            .expect("decode tx's hex shall not fail");
+        let raw_tx_rlp_bytes = hex::decode(PRE_EIP155_TX).expect("decode tx's hex shall not fail");
 
         let eth_tx = EthTransaction::decode(&Rlp::new(&raw_tx_rlp_bytes))
             .expect("decode tx's rlp bytes shall not fail");
@@ -1041,8 +1112,7 @@ mod tests {
 
     #[test]
     fn test_rlp_l1_msg() {
-        let raw_tx_rlp_bytes = hex::decode("7ef901b60b825dc0941a258d17bf244c4df02d40343a7626a9d321e10580b901848ef1332e000000000000000000000000ea08a65b1829af779261e768d609e59279b510f2000000000000000000000000f2ec6b6206f6208e8f9b394efc1a01c1cbde77750000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000000b00000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000a4232e87480000000000000000000000002b5ad5c4795c026514f8317c7a215e218dccd6cf0000000000000000000000002b5ad5c4795c026514f8317c7a215e218dccd6cf0000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000008000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000094478cdd110520a8e733e2acf9e543d2c687ea5239")
-            .expect("decode tx's hex shall not fail");
+        let raw_tx_rlp_bytes = hex::decode(L1_MSG_TX).expect("decode tx's hex shall not fail");
 
         let eth_tx = EthTransaction::decode(&Rlp::new(&raw_tx_rlp_bytes))
             .expect("decode tx's rlp bytes shall not fail");
@@ -1108,9 +1178,7 @@ mod tests {
 
     #[test]
     fn test_rlp_eip1559() {
-        // the tx is downloaded from https://etherscan.io/getRawTx?tx=0x1c5bd618bdbc575f71bfe0a54f09bca2997bbf6d90d4f371a509b05e2b3124e3
-        let raw_tx_rlp_bytes = hex::decode("02f901e901833c3139842b27f14d86012309ce540083055ca8945f65f7b609678448494de4c87521cdf6cef1e93280b8e4fa558b7100000000000000000000000095ad61b0a150d79219dcf64e1e6cc01f0b64c4ce000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a0000000000000000000000000000000000000000000000000000000000000000100000000000000000000000016a217dedfacdf9c23edb84b57154f26a15848e60000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000028cad80bb7cf17e27c4c8f893f7945f65f7b609678448494de4c87521cdf6cef1e932e1a0d2dc2a0881b05440a4908cf506b4871b1f7eaa46ea0c5dfdcda5f52bc17164a4f8599495ad61b0a150d79219dcf64e1e6cc01f0b64c4cef842a0ba03decd934aae936605e9d437c401439ec4cefbad5795e0965100f929fe339ca0b36e2afa1a25492257090107ad99d079032e543c8dd1ffcd44cf14a96d3015ac80a0821193127789b107351f670025dd3b862f5836e5155f627a29741a251e8d28e8a07ea1e82b1bf6f29c5d0f1e4024acdb698086ac40c353704d7d5e301fb916f2e3")
-            .expect("decode tx's hex shall not fail");
+        let raw_tx_rlp_bytes = hex::decode(EIP1559_TX).expect("decode tx's hex shall not fail");
 
         let eth_tx = EthTransaction::decode(&Rlp::new(&raw_tx_rlp_bytes))
             .expect("decode tx's rlp bytes shall not fail");
@@ -1189,4 +1257,138 @@ mod tests {
             Fr::from(tx_data_gas_cost(&tx.rlp_signed)),
         );
     }
+
+    fn eip4844_tx() -> (EthTransaction, Transaction) {
+        let mut eth_tx = EthTransaction::from(&geth_types::Transaction {
+            tx_type: TxType::Eip4844,
+            to: Some(Address::repeat_byte(0xaa)),
+            nonce: 7.into(),
+            gas_limit: 21000.into(),
+            value: 1.into(),
+            gas_fee_cap: 2.into(),
+            gas_tip_cap: 10.into(),
+            call_data: vec![1, 2, 3].into(),
+            max_fee_per_blob_gas: 3.into(),
+            blob_versioned_hashes: vec![H256::repeat_byte(1), H256::repeat_byte(2)],
+            v: 1,
+            r: Word::from(0x1234),
+            s: Word::from(0x5678),
+            ..Default::default()
+        });
+        eth_tx.chain_id = Some(1.into());
+
+        let tx = Transaction::new_from_rlp_bytes(
+            TxType::Eip4844,
+            get_rlp_signed(&eth_tx),
+            get_rlp_unsigned(&eth_tx),
+        );
+        (eth_tx, tx)
+    }
+
+    #[test]
+    fn test_rlp_eip4844() {
+        let (eth_tx, tx) = eip4844_tx();
+        let evm_word = Fr::from(0x1ab);
+        let keccak_input = Fr::from(0x10000);
+        let mock_challenges = Challenges::mock(
+            Value::known(evm_word),
+            Value::known(keccak_input),
+            Value::known(Fr::from(0x100)),
+        );
+
+        let mut tx_table = vec![
+            Fr::from(3),
+            Fr::from(eth_tx.chain_id.unwrap().as_u64()),
+            Fr::from(eth_tx.nonce.as_u64()),
+            rlc(
+                &eth_tx.max_priority_fee_per_gas.unwrap().to_be_bytes(),
+                evm_word,
+            ),
+            rlc(&eth_tx.max_fee_per_gas.unwrap().to_be_bytes(), evm_word),
+            Fr::from(eth_tx.gas.as_u64()),
+            eth_tx.to.unwrap().to_scalar().unwrap(),
+            rlc(&eth_tx.value.to_be_bytes(), evm_word),
+            rlc(&eth_tx.input.to_vec(), keccak_input),
+            rlc(&Word::from(3).to_be_bytes(), evm_word),
+            rlc(&[1; 32], evm_word),
+            rlc(&[2; 32], evm_word),
+        ];
+        for (is_hash, rlp_bytes) in [(false, &tx.rlp_unsigned), (true, &tx.rlp_signed)] {
+            if is_hash {
+                tx_table.extend(vec![
+                    Fr::from(eth_tx.v.as_u64()),
+                    rlc(&eth_tx.r.to_be_bytes(), evm_word),
+                    rlc(&eth_tx.s.to_be_bytes(), evm_word),
+                ]);
+            }
+            let rlp_table = tx
+                .gen_rlp_witness(is_hash, &mock_challenges)
+                .iter()
+                .filter(|row| row.rlp_table.is_output)
+                .map(|row| row.rlp_table)
+                .collect::<Vec<_>>();
+
+            assert_eq!(tx_table.len() + 3, rlp_table.len()); // +3 for Len, RLC and GasCost
+            assert_eq!(rlp_table[0].rlp_tag, Tag::TxType.into());
+            assert_eq!(unwrap_value(rlp_table[0].tag_value), tx_table[0]);
+            assert_eq!(rlp_table[1].rlp_tag, RlpTag::Len);
+            assert_eq!(
+                unwrap_value(rlp_table[1].tag_value),
+                Fr::from(rlp_bytes.len() as u64)
+            );
+            for i in 1..tx_table.len() {
+                assert_eq!(unwrap_value(rlp_table[i + 1].tag_value), tx_table[i]);
+            }
+            assert_eq!(rlp_table[10].rlp_tag, Tag::MaxFeePerBlobGas.into());
+            assert_eq!(rlp_table[11].rlp_tag, Tag::BlobVersionedHash.into());
+            assert_eq!(rlp_table[12].rlp_tag, Tag::BlobVersionedHash.into());
+            assert_eq!(
+                unwrap_value(rlp_table[rlp_table.len() - 2].tag_value),
+                rlc(rlp_bytes, keccak_input)
+            );
+            assert_eq!(
+                unwrap_value(rlp_table[rlp_table.len() - 1].tag_value),
+                Fr::from(tx_data_gas_cost(rlp_bytes)),
+            );
+        }
+    }
+
+    #[test]
+    fn test_rlp_fsm_equivalence() {
+        let mock_challenges = Challenges::mock(
+            Value::known(Fr::from(0x1ab)),
+            Value::known(Fr::from(0x10000)),
+            Value::known(Fr::from(0x100)),
+        );
+        let pre_eip155 = Transaction::new_from_rlp_signed_bytes(
+            TxType::PreEip155,
+            hex::decode(PRE_EIP155_TX).unwrap(),
+        );
+        let l1_msg =
+            Transaction::new_from_rlp_signed_bytes(TxType::L1Msg, hex::decode(L1_MSG_TX).unwrap());
+        // with long bytes and an access list
+        let eip1559 = Transaction::new_from_rlp_signed_bytes(
+            TxType::Eip1559,
+            hex::decode(EIP1559_TX).unwrap(),
+        );
+        // with a vector of blob versioned hashes
+        let (_, eip4844) = eip4844_tx();
+
+        for (tx, is_hash, format) in [
+            (&pre_eip155, true, Format::TxHashPreEip155),
+            (&l1_msg, true, Format::L1MsgHash),
+            (&eip1559, true, Format::TxHashEip1559),
+            (&eip4844, false, Format::TxSignEip4844),
+            (&eip4844, true, Format::TxHashEip4844),
+        ] {
+            let rlp_bytes = if is_hash {
+                &tx.rlp_signed
+            } else {
+                &tx.rlp_unsigned
+            };
+            let expected = rlp_witness_reference(tx.id as u64, format, rlp_bytes, &mock_challenges);
+            let rows = tx.rlp_fsm(is_hash, &mock_challenges).collect::<Vec<_>>();
+            assert_eq!(format!("{rows:?}"), format!("{expected:?}"), "{format:?}");
+        }
+    }
 }
@@ -13,7 +13,9 @@ use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use mock::test_ctx::{gen_geth_traces, LoggerConfig};
 use serde_json::{from_value, Value};
 use std::{collections::HashMap, fs::File, io::BufReader};
-use zkevm_circuits::{super_circuit::SuperCircuit, util::SubCircuit, witness::block_convert};
+use zkevm_circuits::{
+    super_circuit::SuperCircuit, util::SubCircuit, witness::block_convert_with_mock_challenges,
+};
 
 #[derive(serde::Deserialize)]
 struct MyAccount {
@@ -99,11 +101,9 @@ fn prover_error() {
     builder
         .handle_block(&geth_data.eth_block, &geth_data.geth_traces)
         .expect("handle_block");
-    let block_witness = {
-        let mut block = block_convert(&builder.block, &builder.code_db).expect("block_convert");
-        block.randomness = Fr::from(MOCK_RANDOMNESS);
-        block
-    };
+    let block_witness =
+        block_convert_with_mock_challenges(&builder.block, &builder.code_db, MOCK_RANDOMNESS)
+            .expect("block_convert");
     let circuit = SuperCircuit::<Fr, MAX_TXS, MAX_CALLDATA, 64, MOCK_RANDOMNESS>::new_from_block(
         &block_witness,
     );
@@ -8,8 +8,11 @@ use std::{collections::HashMap, io::Error};
 pub use zktrie::{Hash as ZkTrieHash, ZkMemoryDb, ZkTrie, ZkTrieNode};
 
 pub mod builder;
+pub mod key_hash;
 pub mod witness;
 
+use key_hash::KeyHashCache;
+
 use std::{cell::RefCell, fmt, rc::Rc};
 
 /// represent a storage state being applied in specified block
@@ -19,6 +22,7 @@ pub struct ZktrieState {
     zk_db: Rc<RefCell<ZkMemoryDb>>,
     trie_root: ZkTrieHash,
     accounts: HashMap<Address, ZkTrieHash>,
+    key_hashes: Rc<KeyHashCache>,
 }
 
 unsafe impl Send for ZktrieState {}
@@ -46,6 +50,11 @@ impl ZktrieState {
         &self.sdb
     }
 
+    /// cache of hashed zktrie keys, shared with the witness generators
+    pub fn key_hashes(&self) -> &Rc<KeyHashCache> {
+        &self.key_hashes
+    }
+
     /// ...
     pub fn set_state(&mut self, statedb: StateDB) {
         self.sdb = statedb
@@ -69,6 +78,7 @@ impl ZktrieState {
             zk_db: Rc::new(RefCell::new(zk_db)),
             trie_root: state_root.0,
             accounts: Default::default(),
+            key_hashes: Default::default(),
         }
     }
 
@@ -105,6 +115,7 @@ impl ZktrieState {
             let acc_proof = builder::verify_proof_leaf(
                 AccountProof::try_from(BytesArray(bytes.into_iter()))?,
                 &builder::extend_address_to_h256(addr),
+                &self.key_hashes,
             );
             let acc_data = acc_proof.data;
             let (exists, acc) = self.sdb.get_account(addr);
@@ -148,8 +159,11 @@ impl ZktrieState {
             let mut key_buf = [0u8; 32];
             key.to_big_endian(key_buf.as_mut_slice());
             let bytes_array = BytesArray(bytes.into_iter());
-            let store_proof =
-                builder::verify_proof_leaf(StorageProof::try_from(bytes_array)?, &key_buf);
+            let store_proof = builder::verify_proof_leaf(
+                StorageProof::try_from(bytes_array)?,
+                &key_buf,
+                &self.key_hashes,
+            );
             if store_proof.key.is_some() {
                 if !store_proof.data.as_ref().is_zero() {
                     log::trace!(
@@ -6,14 +6,13 @@ use std::{
     io::{Error, ErrorKind, Read},
 };
 
-use halo2_proofs::{
-    arithmetic::FieldExt,
-    halo2curves::{bn256::Fr, group::ff::PrimeField},
-};
+use halo2_proofs::halo2curves::{bn256::Fr, group::ff::PrimeField};
 use hash_circuit::hash::Hashable;
 
 use lazy_static::lazy_static;
 
+use super::key_hash::KeyHashCache;
+
 lazy_static! {
     /// Use this boolean to initialize the hash scheme.
     pub static ref HASH_SCHEME_DONE: bool = {
@@ -214,18 +213,16 @@ impl<T: CanRead + Default> TryFrom<&[Bytes]> for TrieProof<T> {
     }
 }
 
-pub(crate) fn verify_proof_leaf<T: Default>(inp: TrieProof<T>, key_buf: &[u8; 32]) -> TrieProof<T> {
-    let first_16bytes: [u8; 16] = key_buf[..16].try_into().expect("expect first 16 bytes");
-    let last_16bytes: [u8; 16] = key_buf[16..].try_into().expect("expect last 16 bytes");
-
-    let bt_high = Fr::from_u128(u128::from_be_bytes(first_16bytes));
-    let bt_low = Fr::from_u128(u128::from_be_bytes(last_16bytes));
-
+pub(crate) fn verify_proof_leaf<T: Default>(
+    inp: TrieProof<T>,
+    key_buf: &[u8; 32],
+    key_hashes: &KeyHashCache,
+) -> TrieProof<T> {
     if let Some(key) = inp.key {
         let rev_key_bytes: Vec<u8> = key.to_fixed_bytes().into_iter().rev().collect();
         let key_fr = Fr::from_bytes(&rev_key_bytes.try_into().unwrap()).unwrap();
 
-        let secure_hash = Fr::hash([bt_high, bt_low]);
+        let secure_hash = key_hashes.hash(key_buf);
 
         if key_fr == secure_hash {
             inp
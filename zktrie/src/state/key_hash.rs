@@ -0,0 +1,86 @@
+//! memoized hashing of zktrie keys
+
+use eth_types::{Word, U256};
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    halo2curves::{bn256::Fr, group::ff::PrimeField},
+};
+use hash_circuit::hash::Hashable;
+use std::{cell::RefCell, collections::HashMap};
+
+/// Cache of the secure keys of zktrie, i.e. the poseidon hash of account
+/// addresses and storage keys.
+///
+/// The same keys are hashed when the state is loaded from proofs, when the
+/// mpt updates are traced and when the proofs are decoded into MPT paths; for
+/// storage heavy blocks this hashing dominates the witness preparation. A
+/// cache is shared by a [`ZktrieState`](super::ZktrieState) and the
+/// [`WitnessGenerator`](super::witness::WitnessGenerator)s built from it.
+#[derive(Debug, Default)]
+pub struct KeyHashCache {
+    hashes: RefCell<HashMap<[u8; 32], Fr>>,
+}
+
+impl KeyHashCache {
+    /// create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// secure key of a 32 bytes big endian key
+    pub fn hash(&self, key_buf: &[u8; 32]) -> Fr {
+        if let Some(hash) = self.hashes.borrow().get(key_buf) {
+            return *hash;
+        }
+        let hash = hash_zktrie_key(key_buf);
+        self.hashes.borrow_mut().insert(*key_buf, hash);
+        hash
+    }
+
+    /// secure key of a 32 bytes big endian key, as word
+    pub fn hash_word(&self, key_buf: &[u8; 32]) -> Word {
+        U256::from_little_endian(self.hash(key_buf).to_repr().as_ref())
+    }
+
+    /// number of cached keys
+    pub fn len(&self) -> usize {
+        self.hashes.borrow().len()
+    }
+
+    /// if no key is cached
+    pub fn is_empty(&self) -> bool {
+        self.hashes.borrow().is_empty()
+    }
+}
+
+/// secure key of a 32 bytes big endian key, without cache
+pub fn hash_zktrie_key(key_buf: &[u8; 32]) -> Fr {
+    let first_16bytes: [u8; 16] = key_buf[..16].try_into().expect("expect first 16 bytes");
+    let last_16bytes: [u8; 16] = key_buf[16..].try_into().expect("expect last 16 bytes");
+
+    let bt_high = Fr::from_u128(u128::from_be_bytes(first_16bytes));
+    let bt_low = Fr::from_u128(u128::from_be_bytes(last_16bytes));
+
+    Fr::hash([bt_high, bt_low])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_hash_cache() {
+        let cache = KeyHashCache::new();
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        assert!(cache.is_empty());
+        let hash = cache.hash(&key);
+        assert_eq!(hash, hash_zktrie_key(&key));
+        assert_eq!(cache.hash(&key), hash);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            cache.hash_word(&key),
+            U256::from_little_endian(hash.to_repr().as_ref())
+        );
+    }
+}
@@ -1,15 +1,15 @@
 //! witness generator
 use super::{
     builder::{extend_address_to_h256, AccountData, BytesArray, CanRead, TrieProof},
+    key_hash::KeyHashCache,
     MPTProofType, ZktrieState,
 };
 use bus_mapping::{state_db::CodeDB, util::KECCAK_CODE_HASH_ZERO};
 use eth_types::{Address, Hash, Word, H256, U256};
-use halo2_proofs::halo2curves::group::ff::PrimeField;
 use mpt_circuits::serde::{
     AccountData as SMTAccount, Hash as SMTHash, HexBytes, SMTNode, SMTPath, SMTTrace, StateData,
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 use zktrie::{Hash as ZkTrieHash, ZkTrie, ZkTrieNode};
 
 use num_bigint::BigUint;
@@ -39,6 +39,7 @@ pub struct WitnessGenerator {
     trie: ZkTrie,
     accounts: HashMap<Address, AccountData>,
     storages: HashMap<Address, ZkTrie>,
+    key_hashes: Rc<KeyHashCache>,
 }
 
 impl From<&ZktrieState> for WitnessGenerator {
@@ -90,6 +91,7 @@ impl From<&ZktrieState> for WitnessGenerator {
             trie,
             accounts,
             storages,
+            key_hashes: state.key_hashes.clone(),
         }
     }
 }
@@ -105,10 +107,10 @@ impl WitnessGenerator {
     }
     /// get storage proof
     pub fn storage_proof(&self, address: Address, key: Word) -> Vec<Vec<u8>> {
-        let (_storage_key, key) = {
+        let key = {
             let mut word_buf = [0u8; 32];
             key.to_big_endian(word_buf.as_mut_slice());
-            (hash_zktrie_key(&word_buf), HexBytes(word_buf))
+            HexBytes(word_buf)
         };
         // TODO: use or_else to optimize
         let default_trie = &ZktrieState::default()
@@ -129,7 +131,7 @@ impl WitnessGenerator {
         let (storage_key, key) = {
             let mut word_buf = [0u8; 32];
             key.to_big_endian(word_buf.as_mut_slice());
-            (hash_zktrie_key(&word_buf), HexBytes(word_buf))
+            (self.key_hashes.hash_word(&word_buf), HexBytes(word_buf))
         };
 
         // Handle corner case where the account doesn't exist at all. In this case we produce an
@@ -243,7 +245,7 @@ impl WitnessGenerator {
         let account_data_before = self.accounts.get(&address).copied();
 
         let proofs = self.trie.prove(address.as_bytes()).unwrap();
-        let address_key = hash_zktrie_key(&extend_address_to_h256(&address));
+        let address_key = self.key_hashes.hash_word(&extend_address_to_h256(&address));
 
         let account_path_before = decode_proof_for_mpt_path(address_key, proofs).unwrap();
 
@@ -426,21 +428,6 @@ fn smt_hash_from_bytes(bt: &[u8]) -> SMTHash {
     HexBytes(out.try_into().expect("extract size has been set"))
 }
 
-fn hash_zktrie_key(key_buf: &[u8; 32]) -> Word {
-    use halo2_proofs::{arithmetic::FieldExt, halo2curves::bn256::Fr};
-    use hash_circuit::hash::Hashable;
-
-    let first_16bytes: [u8; 16] = key_buf[..16].try_into().expect("expect first 16 bytes");
-    let last_16bytes: [u8; 16] = key_buf[16..].try_into().expect("expect last 16 bytes");
-
-    let bt_high = Fr::from_u128(u128::from_be_bytes(first_16bytes));
-    let bt_low = Fr::from_u128(u128::from_be_bytes(last_16bytes));
-
-    let hash = Fr::hash([bt_high, bt_low]);
-
-    U256::from_little_endian(hash.to_repr().as_ref())
-}
-
 #[derive(Debug, Default, Clone)]
 struct LeafNodeHash(H256);
 